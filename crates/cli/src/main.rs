@@ -131,6 +131,11 @@ struct Args {
     /// by having Zed act like netcat communicating over a Unix socket.
     #[arg(long, hide = true)]
     askpass: Option<String>,
+    /// Connect to the MCP server of the running Zed over stdio, for use by external agents.
+    ///
+    /// Requires `mcp_server.enabled` to be set in Zed's settings.
+    #[arg(long)]
+    mcp: bool,
 }
 
 /// Parses a path containing a position (e.g. `path:line:column`)
@@ -382,6 +387,17 @@ fn main() -> Result<()> {
         paths::set_custom_data_dir(dir);
     }
 
+    // `zed --mcp` Makes zed forward stdio to the MCP server of the running Zed
+    if args.mcp {
+        let socket = paths::mcp_socket_file();
+        return nc::main(&socket.to_string_lossy()).with_context(|| {
+            format!(
+                "Failed to connect to Zed's MCP server at {socket:?}. \
+                Make sure that Zed is running and that `mcp_server.enabled` is set in its settings."
+            )
+        });
+    }
+
     #[cfg(target_os = "linux")]
     let args = flatpak::set_bin_if_no_escape(args);
 
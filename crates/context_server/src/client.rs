@@ -21,12 +21,15 @@ use std::{
 use util::{ResultExt, TryFutureExt};
 
 use crate::{
+    IoHandler,
     transport::{StdioTransport, Transport},
     types::{CancelledParams, ClientNotification, Notification as _, notifications::Cancelled},
 };
 
 const JSON_RPC_VERSION: &str = "2.0";
 const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// How often a request checks whether the user is done authorizing the transport.
+const AUTHORIZATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 // Standard JSON-RPC error codes
 pub const PARSE_ERROR: i32 = -32700;
@@ -39,6 +42,14 @@ type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncApp)>;
 type RequestHandler = Box<dyn Send + FnMut(RequestId, &RawValue, AsyncApp)>;
 
+/// Which way a message passed between us and a context server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoKind {
+    Send,
+    Receive,
+    StdErr,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RequestId {
@@ -53,13 +64,14 @@ pub(crate) struct Client {
     name: Arc<str>,
     subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
+    io_handler: Arc<Mutex<Option<IoHandler>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
     io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
     #[allow(dead_code)]
     output_done_rx: Mutex<Option<barrier::Receiver>>,
     executor: BackgroundExecutor,
-    #[allow(dead_code)]
     transport: Arc<dyn Transport>,
     request_timeout: Option<Duration>,
 }
@@ -196,18 +208,23 @@ impl Client {
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
         let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
+        let io_handler = Arc::new(Mutex::new(None::<IoHandler>));
 
         let receive_input_task = cx.spawn({
             let subscription_set = subscription_set.clone();
             let response_handlers = response_handlers.clone();
             let request_handlers = request_handlers.clone();
+            let io_handler = io_handler.clone();
             let transport = transport.clone();
+            let outbound_tx = outbound_tx.clone();
             async move |cx| {
                 Self::handle_input(
                     transport,
                     subscription_set,
                     request_handlers,
                     response_handlers,
+                    io_handler,
+                    outbound_tx,
                     cx,
                 )
                 .log_err()
@@ -216,7 +233,8 @@ impl Client {
         });
         let receive_err_task = cx.spawn({
             let transport = transport.clone();
-            async move |_| Self::handle_err(transport).log_err().await
+            let io_handler = io_handler.clone();
+            async move |_| Self::handle_err(transport, io_handler).log_err().await
         });
         let input_task = cx.spawn(async move |_| {
             let (input, err) = futures::join!(receive_input_task, receive_err_task);
@@ -230,6 +248,7 @@ impl Client {
                 outbound_rx,
                 output_done_tx,
                 response_handlers.clone(),
+                io_handler.clone(),
             )
             .log_err()
         });
@@ -238,6 +257,8 @@ impl Client {
             server_id,
             subscription_set,
             response_handlers,
+            request_handlers,
+            io_handler,
             name: server_name,
             next_id: Default::default(),
             outbound_tx,
@@ -260,12 +281,17 @@ impl Client {
         subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
         request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        io_handler: Arc<Mutex<Option<IoHandler>>>,
+        outbound_tx: channel::Sender<String>,
         cx: &mut AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
 
         while let Some(message) = receiver.next().await {
             log::trace!("recv: {}", &message);
+            if let Some(handler) = io_handler.lock().as_ref() {
+                handler(IoKind::Receive, &message);
+            }
             if let Ok(request) = serde_json::from_str::<AnyRequest>(&message) {
                 let mut request_handlers = request_handlers.lock();
                 if let Some(handler) = request_handlers.get_mut(request.method) {
@@ -274,6 +300,20 @@ impl Client {
                         request.params.unwrap_or(RawValue::NULL),
                         cx.clone(),
                     );
+                } else {
+                    // The server blocks on our answer, so tell it we can't help rather than
+                    // letting the request hang.
+                    let response = Response::<()> {
+                        jsonrpc: JSON_RPC_VERSION,
+                        id: request.id,
+                        value: CspResult::Error(Some(Error {
+                            message: format!("method not found: {}", request.method),
+                            code: METHOD_NOT_FOUND,
+                        })),
+                    };
+                    outbound_tx
+                        .try_send(serde_json::to_string(&response)?)
+                        .log_err();
                 }
             } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut()
@@ -299,9 +339,15 @@ impl Client {
 
     /// Handles the stderr output from the context server.
     /// Continuously reads and logs any error messages from the server.
-    async fn handle_err(transport: Arc<dyn Transport>) -> anyhow::Result<()> {
+    async fn handle_err(
+        transport: Arc<dyn Transport>,
+        io_handler: Arc<Mutex<Option<IoHandler>>>,
+    ) -> anyhow::Result<()> {
         while let Some(err) = transport.receive_err().next().await {
             log::debug!("context server stderr: {}", err.trim());
+            if let Some(handler) = io_handler.lock().as_ref() {
+                handler(IoKind::StdErr, &err);
+            }
         }
 
         Ok(())
@@ -315,6 +361,7 @@ impl Client {
         outbound_rx: channel::Receiver<String>,
         output_done_tx: barrier::Sender,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        io_handler: Arc<Mutex<Option<IoHandler>>>,
     ) -> anyhow::Result<()> {
         let _clear_response_handlers = util::defer({
             let response_handlers = response_handlers.clone();
@@ -324,6 +371,9 @@ impl Client {
         });
         while let Ok(message) = outbound_rx.recv().await {
             log::trace!("outgoing message: {}", message);
+            if let Some(handler) = io_handler.lock().as_ref() {
+                handler(IoKind::Send, &message);
+            }
             transport.send(message).await?;
         }
         drop(output_done_tx);
@@ -387,9 +437,22 @@ impl Client {
         handle_response?;
         send?;
 
+        let transport = self.transport.clone();
         let mut timeout_fut = pin!(
             match timeout {
-                Some(timeout) => future::Either::Left(executor.timer(timeout)),
+                Some(timeout) => future::Either::Left(async move {
+                    loop {
+                        executor.timer(timeout).await;
+                        if !transport.is_authorizing() {
+                            break;
+                        }
+                        // Authorizing in the browser takes as long as the user needs, so the
+                        // request gets its full timeout again once they're done.
+                        while transport.is_authorizing() {
+                            executor.timer(AUTHORIZATION_POLL_INTERVAL).await;
+                        }
+                    }
+                }),
                 None => future::Either::Right(future::pending()),
             }
             .fuse()
@@ -452,6 +515,66 @@ impl Client {
         Ok(())
     }
 
+    /// Registers a handler for requests the server sends to the client, such as
+    /// `elicitation/create`. The handler's result is sent back as the response.
+    pub fn on_request<R: crate::types::Request>(
+        &self,
+        mut f: impl 'static + Send + FnMut(R::Params, AsyncApp) -> Task<Result<R::Response>>,
+    ) {
+        let outbound_tx = self.outbound_tx.clone();
+        self.request_handlers.lock().insert(
+            R::METHOD,
+            Box::new(move |id, params, cx| {
+                let task = match serde_json::from_str::<R::Params>(params.get()) {
+                    Ok(params) => f(params, cx.clone()),
+                    Err(error) => {
+                        let response = Response::<R::Response> {
+                            jsonrpc: JSON_RPC_VERSION,
+                            id,
+                            value: CspResult::Error(Some(Error {
+                                message: error.to_string(),
+                                code: INVALID_PARAMS,
+                            })),
+                        };
+                        outbound_tx
+                            .try_send(serde_json::to_string(&response).unwrap())
+                            .log_err();
+                        return;
+                    }
+                };
+                let outbound_tx = outbound_tx.clone();
+                cx.background_spawn(async move {
+                    let value = match task.await {
+                        Ok(result) => CspResult::Ok(Some(result)),
+                        Err(error) => CspResult::Error(Some(Error {
+                            message: format!("{error:#}"),
+                            code: INTERNAL_ERROR,
+                        })),
+                    };
+                    let response = Response {
+                        jsonrpc: JSON_RPC_VERSION,
+                        id,
+                        value,
+                    };
+                    outbound_tx
+                        .try_send(serde_json::to_string(&response).unwrap())
+                        .log_err();
+                })
+                .detach();
+            }),
+        );
+    }
+
+    /// Observes every message sent to or received from the server.
+    pub fn on_io(&self, handler: IoHandler) {
+        *self.io_handler.lock() = Some(handler);
+    }
+
+    /// Tells the transport which protocol revision was negotiated during initialization.
+    pub fn set_protocol_version(&self, version: &str) {
+        self.transport.set_protocol_version(version);
+    }
+
     #[must_use]
     pub fn on_notification(
         &self,
@@ -1,5 +1,6 @@
 pub mod client;
 pub mod listener;
+pub mod oauth;
 pub mod protocol;
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
@@ -15,12 +16,15 @@ use std::{fmt::Display, path::PathBuf};
 
 use anyhow::Result;
 use client::Client;
-use gpui::AsyncApp;
+use gpui::{AsyncApp, Task};
 use parking_lot::RwLock;
 pub use settings::ContextServerCommand;
 use url::Url;
 
-use crate::transport::HttpTransport;
+use crate::{
+    oauth::{OAuthDelegate, OAuthSession},
+    transport::HttpTransport,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ContextServerId(pub Arc<str>);
@@ -36,11 +40,26 @@ enum ContextServerTransport {
     Custom(Arc<dyn crate::transport::Transport>),
 }
 
+/// Answers `elicitation/create` requests, usually by asking the user to fill in a form.
+pub type ElicitationHandler = Arc<
+    dyn Send
+        + Sync
+        + Fn(
+            types::ElicitationCreateParams,
+            AsyncApp,
+        ) -> Task<Result<types::ElicitationCreateResponse>>,
+>;
+
+/// Observes the raw JSON-RPC traffic of a server, e.g. for an inspector.
+pub type IoHandler = Arc<dyn Send + Sync + Fn(client::IoKind, &str)>;
+
 pub struct ContextServer {
     id: ContextServerId,
     client: RwLock<Option<Arc<crate::protocol::InitializedContextServerProtocol>>>,
     configuration: ContextServerTransport,
     request_timeout: Option<Duration>,
+    elicitation_handler: RwLock<Option<ElicitationHandler>>,
+    io_handler: RwLock<Option<IoHandler>>,
 }
 
 impl ContextServer {
@@ -57,6 +76,8 @@ impl ContextServer {
                 working_directory.map(|directory| directory.to_path_buf()),
             ),
             request_timeout: None,
+            elicitation_handler: RwLock::new(None),
+            io_handler: RwLock::new(None),
         }
     }
 
@@ -67,12 +88,30 @@ impl ContextServer {
         http_client: Arc<dyn HttpClient>,
         executor: gpui::BackgroundExecutor,
         request_timeout: Option<Duration>,
+        oauth_delegate: Option<Arc<dyn OAuthDelegate>>,
     ) -> Result<Self> {
         let transport = match endpoint.scheme() {
             "http" | "https" => {
                 log::info!("Using HTTP transport for {}", endpoint);
-                let transport =
-                    HttpTransport::new(http_client, endpoint.to_string(), headers, executor);
+                // Explicitly configured credentials take precedence over OAuth.
+                let oauth_delegate = oauth_delegate.filter(|_| {
+                    !headers
+                        .keys()
+                        .any(|key| key.eq_ignore_ascii_case("authorization"))
+                });
+                let mut transport = HttpTransport::new(
+                    http_client.clone(),
+                    endpoint.to_string(),
+                    headers,
+                    executor,
+                );
+                if let Some(oauth_delegate) = oauth_delegate {
+                    transport = transport.with_oauth(Arc::new(OAuthSession::new(
+                        endpoint.clone(),
+                        http_client,
+                        oauth_delegate,
+                    )));
+                }
                 Arc::new(transport) as _
             }
             _ => anyhow::bail!("unsupported MCP url scheme {}", endpoint.scheme()),
@@ -94,6 +133,8 @@ impl ContextServer {
             client: RwLock::new(None),
             configuration: ContextServerTransport::Custom(transport),
             request_timeout,
+            elicitation_handler: RwLock::new(None),
+            io_handler: RwLock::new(None),
         }
     }
 
@@ -105,6 +146,17 @@ impl ContextServer {
         self.client.read().clone()
     }
 
+    /// Lets the server request structured input from the user. Must be called before
+    /// [`Self::start`], since the capability is advertised during initialization.
+    pub fn set_elicitation_handler(&self, handler: ElicitationHandler) {
+        *self.elicitation_handler.write() = Some(handler);
+    }
+
+    /// Observes the messages exchanged with the server, starting with the next [`Self::start`].
+    pub fn set_io_handler(&self, handler: IoHandler) {
+        *self.io_handler.write() = Some(handler);
+    }
+
     pub async fn start(&self, cx: &AsyncApp) -> Result<()> {
         self.initialize(self.new_client(cx)?).await
     }
@@ -134,12 +186,28 @@ impl ContextServer {
 
     async fn initialize(&self, client: Client) -> Result<()> {
         log::debug!("starting context server {}", self.id);
+        if let Some(handler) = self.io_handler.read().clone() {
+            client.on_io(handler);
+        }
+        let elicitation_handler = self.elicitation_handler.read().clone();
+        if let Some(handler) = elicitation_handler.clone() {
+            client.on_request::<types::requests::ElicitationCreate>(move |params, cx| {
+                handler(params, cx)
+            });
+        }
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
             name: "Zed".to_string(),
+            title: None,
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
-        let initialized_protocol = protocol.initialize(client_info).await?;
+        let capabilities = types::ClientCapabilities {
+            experimental: None,
+            sampling: None,
+            roots: None,
+            elicitation: elicitation_handler.map(|_| serde_json::json!({})),
+        };
+        let initialized_protocol = protocol.initialize(client_info, capabilities).await?;
 
         log::debug!(
             "context server {} initialized: {:?}",
@@ -19,6 +19,7 @@ use std::{
     path::{Path, PathBuf},
     rc::Rc,
 };
+use tempfile::TempDir;
 use util::ResultExt;
 
 use crate::{
@@ -32,6 +33,8 @@ use crate::{
 
 pub struct McpServer {
     socket_path: PathBuf,
+    /// The socket created by [`McpServer::listen`], which is removed when the server is dropped.
+    socket_file: Option<SocketFile>,
     tools: Rc<RefCell<HashMap<&'static str, RegisteredTool>>>,
     handlers: Rc<RefCell<HashMap<&'static str, RequestHandler>>>,
     _server_task: Task<()>,
@@ -50,6 +53,30 @@ type ToolHandler = Box<
 >;
 type RequestHandler = Box<dyn Fn(RequestId, Option<Box<RawValue>>, &App) -> Task<String>>;
 
+struct SocketFile {
+    path: PathBuf,
+    /// Identifies the file, so that it isn't removed once another instance has replaced it.
+    id: Option<FileId>,
+}
+
+#[cfg(unix)]
+type FileId = (u64, u64);
+#[cfg(not(unix))]
+type FileId = std::time::SystemTime;
+
+fn file_id(path: &Path) -> Option<FileId> {
+    let metadata = std::fs::symlink_metadata(path).ok()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt as _;
+        Some((metadata.dev(), metadata.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.modified().ok()
+    }
+}
+
 impl McpServer {
     pub fn new(cx: &AsyncApp) -> Task<Result<Self>> {
         let task = cx.background_spawn(async move {
@@ -62,27 +89,69 @@ impl McpServer {
 
         cx.spawn(async move |cx| {
             let (temp_dir, socket_path, listener) = task.await?;
-            let tools = Rc::new(RefCell::new(HashMap::default()));
-            let handlers = Rc::new(RefCell::new(HashMap::default()));
-            let server_task = cx.spawn({
-                let tools = tools.clone();
-                let handlers = handlers.clone();
-                async move |cx| {
-                    while let Ok((stream, _)) = listener.accept().await {
-                        Self::serve_connection(stream, tools.clone(), handlers.clone(), cx);
-                    }
-                    drop(temp_dir)
+            Ok(Self::serve(socket_path, listener, Some(temp_dir), None, cx))
+        })
+    }
+
+    /// Listens on a socket at a known path, so that clients started outside of Zed can
+    /// connect to it. A socket left behind at that path by an earlier run is replaced.
+    pub fn listen(socket_path: PathBuf, cx: &AsyncApp) -> Task<Result<Self>> {
+        let task = cx.background_spawn({
+            let socket_path = socket_path.clone();
+            async move {
+                if let Some(parent) = socket_path.parent() {
+                    std::fs::create_dir_all(parent)?;
                 }
-            });
-            Ok(Self {
+                std::fs::remove_file(&socket_path).ok();
+                let listener = UnixListener::bind(&socket_path).context("creating mcp socket")?;
+                anyhow::Ok((listener, file_id(&socket_path)))
+            }
+        });
+
+        cx.spawn(async move |cx| {
+            let (listener, id) = task.await?;
+            let socket_file = SocketFile {
+                path: socket_path.clone(),
+                id,
+            };
+            Ok(Self::serve(
                 socket_path,
-                _server_task: server_task,
-                tools,
-                handlers,
-            })
+                listener,
+                None,
+                Some(socket_file),
+                cx,
+            ))
         })
     }
 
+    fn serve(
+        socket_path: PathBuf,
+        listener: UnixListener,
+        temp_dir: Option<TempDir>,
+        socket_file: Option<SocketFile>,
+        cx: &mut AsyncApp,
+    ) -> Self {
+        let tools = Rc::new(RefCell::new(HashMap::default()));
+        let handlers = Rc::new(RefCell::new(HashMap::default()));
+        let server_task = cx.spawn({
+            let tools = tools.clone();
+            let handlers = handlers.clone();
+            async move |cx| {
+                while let Ok((stream, _)) = listener.accept().await {
+                    Self::serve_connection(stream, tools.clone(), handlers.clone(), cx);
+                }
+                drop(temp_dir)
+            }
+        });
+        Self {
+            socket_path,
+            socket_file,
+            _server_task: server_task,
+            tools,
+            handlers,
+        }
+    }
+
     pub fn add_tool<T: McpServerTool + Clone + 'static>(&mut self, tool: T) {
         let mut settings = schemars::generate::SchemaSettings::draft07();
         settings.inline_subschemas = true;
@@ -102,6 +171,7 @@ impl McpServer {
         let registered_tool = RegisteredTool {
             tool: Tool {
                 name: T::NAME.into(),
+                title: None,
                 description,
                 input_schema: input_schema.into(),
                 output_schema: if TypeId::of::<T::Output>() == TypeId::of::<()>() {
@@ -110,12 +180,15 @@ impl McpServer {
                     Some(generator.root_schema_for::<T::Output>().into())
                 },
                 annotations: Some(tool.annotations()),
+                meta: None,
             },
             handler: Box::new({
                 move |input_value, cx| {
+                    // Clients may omit the arguments of tools that take no required ones.
                     let input = match input_value {
                         Some(input) => serde_json::from_value(input),
-                        None => serde_json::from_value(serde_json::Value::Null),
+                        None => serde_json::from_value(serde_json::Value::Null)
+                            .or_else(|_| serde_json::from_value(json!({}))),
                     };
 
                     let tool = tool.clone();
@@ -397,6 +470,17 @@ impl McpServer {
     }
 }
 
+impl Drop for McpServer {
+    fn drop(&mut self) {
+        if let Some(socket_file) = &self.socket_file
+            && socket_file.id.is_some()
+            && file_id(&socket_file.path) == socket_file.id
+        {
+            std::fs::remove_file(&socket_file.path).log_err();
+        }
+    }
+}
+
 pub trait McpServerTool {
     type Input: DeserializeOwned + JsonSchema;
     type Output: Serialize + JsonSchema;
@@ -434,3 +518,35 @@ struct RawRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     params: Option<Box<serde_json::value::RawValue>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_listen_removes_socket_when_dropped(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("mcp.sock");
+
+        let server = McpServer::listen(socket_path.clone(), &cx.to_async())
+            .await
+            .unwrap();
+        assert!(socket_path.exists());
+        drop(server);
+        assert!(!socket_path.exists());
+
+        // A server whose socket was replaced by another instance leaves that socket alone.
+        let first_server = McpServer::listen(socket_path.clone(), &cx.to_async())
+            .await
+            .unwrap();
+        let second_server = McpServer::listen(socket_path.clone(), &cx.to_async())
+            .await
+            .unwrap();
+        drop(first_server);
+        assert!(socket_path.exists());
+        drop(second_server);
+        assert!(!socket_path.exists());
+    }
+}
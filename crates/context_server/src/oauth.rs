@@ -0,0 +1,1047 @@
+//! OAuth 2.1 authorization for HTTP context servers.
+//!
+//! This follows the MCP authorization spec: when a server rejects a request with `401`, we
+//! discover its authorization server through protected resource metadata (RFC 9728) and
+//! authorization server metadata (RFC 8414), register ourselves as a client (RFC 7591), and run
+//! the authorization code flow with PKCE, receiving the code on a loopback redirect.
+
+use std::{
+    net::Ipv4Addr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use base64::prelude::*;
+use collections::HashMap;
+use credentials_provider::CredentialsProvider;
+use futures::{
+    AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, StreamExt as _,
+    channel::{mpsc, oneshot},
+    future::BoxFuture,
+    lock::Mutex,
+};
+use gpui::App;
+use http_client::{AsyncBody, HttpClient, Request, http::Method};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest as _, Sha256};
+use url::Url;
+use util::ResultExt as _;
+
+/// How long we wait for the user to finish authorizing in their browser.
+const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Tokens this close to expiring are refreshed before they're used.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+const CALLBACK_PATH: &str = "/callback";
+const CLIENT_NAME: &str = "Zed";
+
+/// What we remember about an authorization between sessions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OAuthCredentials {
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    /// Kept so tokens can be refreshed without discovering the authorization server again.
+    pub token_endpoint: Url,
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl OAuthCredentials {
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| unix_time(SystemTime::now() + EXPIRY_MARGIN) >= expires_at)
+    }
+}
+
+/// Persists credentials and involves the user on behalf of an [`OAuthSession`].
+pub trait OAuthDelegate: Send + Sync {
+    /// Sends the user to the authorization server's consent page.
+    fn open_authorization_url(&self, url: &Url);
+    fn load_credentials(&self) -> BoxFuture<'static, Result<Option<OAuthCredentials>>>;
+    fn store_credentials(&self, credentials: &OAuthCredentials) -> BoxFuture<'static, Result<()>>;
+}
+
+/// Obtains and refreshes access tokens for one context server.
+pub struct OAuthSession {
+    server_url: Url,
+    http_client: Arc<dyn HttpClient>,
+    delegate: Arc<dyn OAuthDelegate>,
+    /// `None` until the stored credentials have been loaded. Held across refreshes and
+    /// authorization so concurrent requests don't start several flows.
+    credentials: Mutex<Option<Option<OAuthCredentials>>>,
+    /// Set while the user is authorizing us in their browser.
+    authorizing: AtomicBool,
+}
+
+impl OAuthSession {
+    pub fn new(
+        server_url: Url,
+        http_client: Arc<dyn HttpClient>,
+        delegate: Arc<dyn OAuthDelegate>,
+    ) -> Self {
+        Self {
+            server_url,
+            http_client,
+            delegate,
+            credentials: Mutex::new(None),
+            authorizing: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether we're waiting for the user to authorize us, which can take much longer than
+    /// requests are otherwise allowed to.
+    pub fn is_authorizing(&self) -> bool {
+        self.authorizing.load(Ordering::SeqCst)
+    }
+
+    /// Returns the token to send with the next request, refreshing it first if it has expired.
+    pub async fn access_token(&self) -> Option<String> {
+        let mut slot = self.credentials.lock().await;
+        let credentials = self.current_credentials(&mut slot).await;
+        let current = credentials.as_ref()?;
+        if !current.is_expired() {
+            return Some(current.access_token.clone());
+        }
+        // If refreshing fails, the server will reject the request and we'll reauthorize.
+        let refreshed = self.refresh(current).await.log_err()?;
+        self.store(&refreshed).await;
+        let access_token = refreshed.access_token.clone();
+        *credentials = Some(refreshed);
+        Some(access_token)
+    }
+
+    /// Called when the server rejected `rejected_token` with a `401` carrying `challenge` (the
+    /// `WWW-Authenticate` header). Refreshes the token if possible, and otherwise asks the user to
+    /// authorize us again.
+    pub async fn handle_unauthorized(
+        &self,
+        rejected_token: Option<&str>,
+        challenge: Option<&str>,
+    ) -> Result<String> {
+        let mut slot = self.credentials.lock().await;
+        let credentials = self.current_credentials(&mut slot).await;
+        if let Some(current) = credentials.as_ref() {
+            // Another request may have replaced the token while we waited for the lock.
+            if Some(current.access_token.as_str()) != rejected_token && !current.is_expired() {
+                return Ok(current.access_token.clone());
+            }
+            if current.refresh_token.is_some() {
+                match self.refresh(current).await {
+                    Ok(refreshed) => {
+                        self.store(&refreshed).await;
+                        let access_token = refreshed.access_token.clone();
+                        *credentials = Some(refreshed);
+                        return Ok(access_token);
+                    }
+                    Err(error) => log::info!("reauthorizing after failed refresh: {error:#}"),
+                }
+            }
+        }
+
+        self.authorizing.store(true, Ordering::SeqCst);
+        let _authorizing = util::defer(|| self.authorizing.store(false, Ordering::SeqCst));
+        let authorized = self
+            .authorize(challenge.map(BearerChallenge::parse).unwrap_or_default())
+            .await
+            .with_context(|| format!("authorizing with {}", self.server_url))?;
+        self.store(&authorized).await;
+        let access_token = authorized.access_token.clone();
+        *credentials = Some(authorized);
+        Ok(access_token)
+    }
+
+    async fn current_credentials<'a>(
+        &self,
+        slot: &'a mut Option<Option<OAuthCredentials>>,
+    ) -> &'a mut Option<OAuthCredentials> {
+        if slot.is_none() {
+            *slot = Some(self.delegate.load_credentials().await.log_err().flatten());
+        }
+        slot.get_or_insert(None)
+    }
+
+    async fn store(&self, credentials: &OAuthCredentials) {
+        self.delegate
+            .store_credentials(credentials)
+            .await
+            .context("storing context server credentials")
+            .log_err();
+    }
+
+    async fn authorize(&self, challenge: BearerChallenge) -> Result<OAuthCredentials> {
+        let resource_metadata = self
+            .discover_protected_resource(challenge.resource_metadata.as_ref())
+            .await;
+        // Servers predating protected resource metadata are their own authorization server.
+        let (issuer, scopes_supported) = match resource_metadata {
+            Some(metadata) => (
+                metadata
+                    .authorization_servers
+                    .into_iter()
+                    .next()
+                    .context("the server doesn't list any authorization servers")?,
+                metadata.scopes_supported,
+            ),
+            None => (origin(&self.server_url), None),
+        };
+        let scope = challenge
+            .scope
+            .or_else(|| scopes_supported.map(|scopes| scopes.join(" ")));
+
+        let metadata = self.discover_authorization_server(&issuer).await?;
+        if let Some(methods) = &metadata.code_challenge_methods_supported {
+            anyhow::ensure!(
+                methods.iter().any(|method| method == "S256"),
+                "the authorization server doesn't support PKCE with S256"
+            );
+        }
+
+        let listener = LoopbackListener::bind().await?;
+        let redirect_uri = listener.redirect_uri();
+        let registration = self.register_client(&metadata, &redirect_uri).await?;
+
+        let code_verifier = random_token();
+        let code_challenge = BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(&code_verifier));
+        let state = random_token();
+        let mut authorization_url = metadata.authorization_endpoint.clone();
+        {
+            let mut query = authorization_url.query_pairs_mut();
+            query
+                .append_pair("response_type", "code")
+                .append_pair("client_id", registration.client_id.as_str())
+                .append_pair("redirect_uri", redirect_uri.as_str())
+                .append_pair("code_challenge", &code_challenge)
+                .append_pair("code_challenge_method", "S256")
+                .append_pair("state", &state)
+                .append_pair("resource", self.server_url.as_str());
+            if let Some(scope) = &scope {
+                query.append_pair("scope", scope);
+            }
+        }
+        self.delegate.open_authorization_url(&authorization_url);
+
+        let code = smol::future::or(listener.wait_for_code(&state), async {
+            smol::Timer::after(AUTHORIZATION_TIMEOUT).await;
+            Err(anyhow!(
+                "timed out waiting for authorization in the browser"
+            ))
+        })
+        .await?;
+
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("client_id", registration.client_id.as_str()),
+            ("code_verifier", code_verifier.as_str()),
+            ("resource", self.server_url.as_str()),
+        ];
+        if let Some(client_secret) = &registration.client_secret {
+            params.push(("client_secret", client_secret.as_str()));
+        }
+        let tokens: TokenResponse = self
+            .post_form(&metadata.token_endpoint, &params)
+            .await
+            .context("exchanging the authorization code")?;
+
+        Ok(OAuthCredentials {
+            client_id: registration.client_id,
+            client_secret: registration.client_secret,
+            token_endpoint: metadata.token_endpoint,
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            expires_at: tokens.expires_at(),
+        })
+    }
+
+    async fn refresh(&self, credentials: &OAuthCredentials) -> Result<OAuthCredentials> {
+        let refresh_token = credentials
+            .refresh_token
+            .as_deref()
+            .context("no refresh token")?;
+        let mut params = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", credentials.client_id.as_str()),
+            ("resource", self.server_url.as_str()),
+        ];
+        if let Some(client_secret) = &credentials.client_secret {
+            params.push(("client_secret", client_secret.as_str()));
+        }
+        let tokens: TokenResponse = self
+            .post_form(&credentials.token_endpoint, &params)
+            .await
+            .context("refreshing the access token")?;
+        Ok(OAuthCredentials {
+            expires_at: tokens.expires_at(),
+            access_token: tokens.access_token,
+            // Servers that don't rotate refresh tokens leave them out of the response.
+            refresh_token: tokens
+                .refresh_token
+                .or_else(|| credentials.refresh_token.clone()),
+            ..credentials.clone()
+        })
+    }
+
+    async fn discover_protected_resource(
+        &self,
+        metadata_url: Option<&Url>,
+    ) -> Option<ProtectedResourceMetadata> {
+        let candidates = match metadata_url {
+            Some(url) => vec![url.clone()],
+            None => well_known_urls(&self.server_url, "oauth-protected-resource"),
+        };
+        for url in candidates {
+            match self.get_json(&url).await {
+                Ok(metadata) => return Some(metadata),
+                Err(error) => log::debug!("no protected resource metadata at {url}: {error:#}"),
+            }
+        }
+        None
+    }
+
+    async fn discover_authorization_server(
+        &self,
+        issuer: &Url,
+    ) -> Result<AuthorizationServerMetadata> {
+        let candidates = well_known_urls(issuer, "oauth-authorization-server")
+            .into_iter()
+            .chain(well_known_urls(issuer, "openid-configuration"));
+        for url in candidates {
+            match self.get_json(&url).await {
+                Ok(metadata) => return Ok(metadata),
+                Err(error) => log::debug!("no authorization server metadata at {url}: {error:#}"),
+            }
+        }
+        // The 2025-03-26 revision of the spec falls back to fixed paths on the issuer.
+        let origin = origin(issuer);
+        Ok(AuthorizationServerMetadata {
+            authorization_endpoint: origin.join("authorize")?,
+            token_endpoint: origin.join("token")?,
+            registration_endpoint: Some(origin.join("register")?),
+            code_challenge_methods_supported: None,
+        })
+    }
+
+    async fn register_client(
+        &self,
+        metadata: &AuthorizationServerMetadata,
+        redirect_uri: &str,
+    ) -> Result<ClientRegistration> {
+        let registration_endpoint = metadata
+            .registration_endpoint
+            .as_ref()
+            .context("the authorization server doesn't support dynamic client registration")?;
+        let body = serde_json::json!({
+            "client_name": CLIENT_NAME,
+            "redirect_uris": [redirect_uri],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "response_types": ["code"],
+            "token_endpoint_auth_method": "none",
+        });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(registration_endpoint.as_str())
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .body(AsyncBody::from(body.to_string()))?;
+        self.send(request)
+            .await
+            .context("registering with the authorization server")
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &Url) -> Result<T> {
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(url.as_str())
+            .header("Accept", "application/json")
+            .body(AsyncBody::empty())?;
+        self.send(request).await
+    }
+
+    async fn post_form<T: DeserializeOwned>(
+        &self,
+        url: &Url,
+        params: &[(&str, &str)],
+    ) -> Result<T> {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params)
+            .finish();
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(url.as_str())
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Accept", "application/json")
+            .body(AsyncBody::from(body))?;
+        self.send(request).await
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: Request<AsyncBody>) -> Result<T> {
+        let mut response = self.http_client.send(request).await?;
+        let mut body = Vec::new();
+        response.body_mut().read_to_end(&mut body).await?;
+        anyhow::ensure!(
+            response.status().is_success(),
+            "HTTP {}: {}",
+            response.status(),
+            String::from_utf8_lossy(&body)
+        );
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+/// The parameters of a `WWW-Authenticate: Bearer ...` challenge that we use.
+#[derive(Debug, Default, PartialEq)]
+struct BearerChallenge {
+    resource_metadata: Option<Url>,
+    scope: Option<String>,
+}
+
+impl BearerChallenge {
+    fn parse(header: &str) -> Self {
+        let mut challenge = Self::default();
+        let Some(params) = header
+            .trim()
+            .strip_prefix("Bearer")
+            .or_else(|| header.trim().strip_prefix("bearer"))
+        else {
+            return challenge;
+        };
+
+        let mut rest = params.trim_start();
+        while let Some((name, value)) = rest.split_once('=') {
+            let name = name.trim().trim_start_matches(',').trim();
+            let value = value.trim_start();
+            let (value, remainder) = match value.strip_prefix('"') {
+                Some(quoted) => match quoted.split_once('"') {
+                    Some((value, remainder)) => (value, remainder),
+                    None => (quoted, ""),
+                },
+                None => match value.split_once(',') {
+                    Some((value, remainder)) => (value.trim(), remainder),
+                    None => (value.trim(), ""),
+                },
+            };
+            match name {
+                "resource_metadata" => challenge.resource_metadata = Url::parse(value).ok(),
+                "scope" => challenge.scope = Some(value.to_string()),
+                _ => {}
+            }
+            rest = remainder.trim_start().trim_start_matches(',');
+        }
+        challenge
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    #[serde(default)]
+    authorization_servers: Vec<Url>,
+    #[serde(default)]
+    scopes_supported: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorizationServerMetadata {
+    authorization_endpoint: Url,
+    token_endpoint: Url,
+    #[serde(default)]
+    registration_endpoint: Option<Url>,
+    #[serde(default)]
+    code_challenge_methods_supported: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+impl TokenResponse {
+    fn expires_at(&self) -> Option<u64> {
+        self.expires_in
+            .map(|expires_in| unix_time(SystemTime::now()) + expires_in)
+    }
+}
+
+/// Receives the authorization server's redirect on a local port.
+struct LoopbackListener {
+    listener: smol::net::TcpListener,
+    port: u16,
+}
+
+impl LoopbackListener {
+    async fn bind() -> Result<Self> {
+        let listener = smol::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .context("binding the OAuth redirect listener")?;
+        let port = listener.local_addr()?.port();
+        Ok(Self { listener, port })
+    }
+
+    fn redirect_uri(&self) -> String {
+        format!("http://127.0.0.1:{}{CALLBACK_PATH}", self.port)
+    }
+
+    async fn wait_for_code(self, state: &str) -> Result<String> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+            let mut reader = futures::io::BufReader::new(stream.clone());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).await?;
+            // Drain the headers so the browser sees a clean response.
+            let mut header = String::new();
+            while reader.read_line(&mut header).await? > 2 {
+                header.clear();
+            }
+
+            let query = request_line
+                .split_whitespace()
+                .nth(1)
+                .and_then(|target| target.strip_prefix(CALLBACK_PATH))
+                .map(|rest| rest.strip_prefix('?').unwrap_or(rest));
+            let Some(query) = query else {
+                stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                    .await
+                    .log_err();
+                continue;
+            };
+
+            let params = url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect::<HashMap<String, String>>();
+            // Anything can send a request to the listener, so responses that don't belong to
+            // the flow we started are turned away without giving up on it.
+            if params.get("state").map(String::as_str) != Some(state) {
+                log::warn!("ignoring an authorization response with an unexpected state");
+                respond(
+                    &mut stream,
+                    "400 Bad Request",
+                    "This authorization response doesn't match the one Zed is waiting for.",
+                )
+                .await;
+                continue;
+            }
+
+            let result = if let Some(error) = params.get("error") {
+                match params.get("error_description") {
+                    Some(description) => {
+                        Err(anyhow!("authorization failed: {error}: {description}"))
+                    }
+                    None => Err(anyhow!("authorization failed: {error}")),
+                }
+            } else {
+                params
+                    .get("code")
+                    .cloned()
+                    .context("the authorization response had no code")
+            };
+
+            let (status, message) = match &result {
+                Ok(_) => ("200 OK", "Zed is now authorized. You can close this tab."),
+                Err(_) => (
+                    "400 Bad Request",
+                    "Authorization failed. Check Zed's logs for details.",
+                ),
+            };
+            respond(&mut stream, status, message).await;
+            return result;
+        }
+    }
+}
+
+async fn respond(stream: &mut smol::net::TcpStream, status: &str, message: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n\
+         {message}",
+        message.len()
+    );
+    stream.write_all(response.as_bytes()).await.log_err();
+}
+
+/// An [`OAuthDelegate`] that keeps credentials in the system keychain and opens the system
+/// browser.
+pub struct KeychainOAuthDelegate {
+    requests: mpsc::UnboundedSender<DelegateRequest>,
+}
+
+enum DelegateRequest {
+    OpenUrl(Url),
+    Load(oneshot::Sender<Result<Option<OAuthCredentials>>>),
+    Store(OAuthCredentials, oneshot::Sender<Result<()>>),
+}
+
+impl KeychainOAuthDelegate {
+    pub fn new(server_url: &Url, cx: &App) -> Arc<Self> {
+        let (requests, mut request_rx) = mpsc::unbounded();
+        let credentials_provider = <dyn CredentialsProvider>::global(cx);
+        let credentials_url = server_url.to_string();
+        // The keychain is only accessible from the main thread, so requests from the transport
+        // are forwarded here. The loop ends when the transport is dropped.
+        cx.spawn(async move |cx| {
+            while let Some(request) = request_rx.next().await {
+                match request {
+                    DelegateRequest::OpenUrl(url) => cx.update(|cx| cx.open_url(url.as_str())),
+                    DelegateRequest::Load(response_tx) => {
+                        let credentials = credentials_provider
+                            .read_credentials(&credentials_url, cx)
+                            .await
+                            .and_then(|credentials| {
+                                credentials
+                                    .map(|(_, password)| {
+                                        anyhow::Ok(serde_json::from_slice(&password)?)
+                                    })
+                                    .transpose()
+                            });
+                        response_tx.send(credentials).ok();
+                    }
+                    DelegateRequest::Store(credentials, response_tx) => {
+                        let result = match serde_json::to_vec(&credentials) {
+                            Ok(password) => {
+                                credentials_provider
+                                    .write_credentials(
+                                        &credentials_url,
+                                        &credentials.client_id,
+                                        &password,
+                                        cx,
+                                    )
+                                    .await
+                            }
+                            Err(error) => Err(error.into()),
+                        };
+                        response_tx.send(result).ok();
+                    }
+                }
+            }
+        })
+        .detach();
+        Arc::new(Self { requests })
+    }
+}
+
+impl OAuthDelegate for KeychainOAuthDelegate {
+    fn open_authorization_url(&self, url: &Url) {
+        self.requests
+            .unbounded_send(DelegateRequest::OpenUrl(url.clone()))
+            .log_err();
+    }
+
+    fn load_credentials(&self) -> BoxFuture<'static, Result<Option<OAuthCredentials>>> {
+        let (response_tx, response_rx) = oneshot::channel();
+        let sent = self
+            .requests
+            .unbounded_send(DelegateRequest::Load(response_tx));
+        Box::pin(async move {
+            sent?;
+            response_rx.await?
+        })
+    }
+
+    fn store_credentials(&self, credentials: &OAuthCredentials) -> BoxFuture<'static, Result<()>> {
+        let (response_tx, response_rx) = oneshot::channel();
+        let sent = self
+            .requests
+            .unbounded_send(DelegateRequest::Store(credentials.clone(), response_tx));
+        Box::pin(async move {
+            sent?;
+            response_rx.await?
+        })
+    }
+}
+
+/// The URLs to try for `/.well-known/{name}` metadata about `url`: the path-aware location
+/// first, then the root.
+fn well_known_urls(url: &Url, name: &str) -> Vec<Url> {
+    let mut urls = Vec::new();
+    let path = url.path().trim_end_matches('/');
+    let mut well_known = origin(url);
+    if !path.is_empty() {
+        well_known.set_path(&format!("/.well-known/{name}{path}"));
+        urls.push(well_known.clone());
+    }
+    well_known.set_path(&format!("/.well-known/{name}"));
+    urls.push(well_known);
+    urls
+}
+
+fn origin(url: &Url) -> Url {
+    let mut origin = url.clone();
+    origin.set_path("/");
+    origin.set_query(None);
+    origin.set_fragment(None);
+    origin
+}
+
+fn random_token() -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_client::{FakeHttpClient, Response};
+    use parking_lot::Mutex as SyncMutex;
+    use std::io::{Read as _, Write as _};
+
+    const SERVER_URL: &str = "https://mcp.example.com/mcp";
+    const CHALLENGE: &str = r#"Bearer error="invalid_token", resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource/mcp", scope="tools:read tools:write""#;
+
+    #[test]
+    fn test_authorization_flow() {
+        let server = StandInServer::new();
+        let delegate = FakeDelegate::new(None, Browser::Approve);
+        let session = OAuthSession::new(
+            Url::parse(SERVER_URL).unwrap(),
+            server.http_client(),
+            delegate.clone(),
+        );
+
+        smol::block_on(async {
+            assert_eq!(session.access_token().await, None);
+            let access_token = session
+                .handle_unauthorized(None, Some(CHALLENGE))
+                .await
+                .unwrap();
+            assert_eq!(access_token, "access-1");
+            assert_eq!(session.access_token().await.as_deref(), Some("access-1"));
+        });
+
+        let authorization_url = delegate.opened_urls.lock()[0].clone();
+        let query = authorization_url
+            .query_pairs()
+            .into_owned()
+            .collect::<HashMap<String, String>>();
+        assert_eq!(authorization_url.path(), "/authorize");
+        assert_eq!(query["client_id"], "registered-client");
+        assert_eq!(query["code_challenge_method"], "S256");
+        assert_eq!(query["resource"], SERVER_URL);
+        assert_eq!(query["scope"], "tools:read tools:write");
+        assert!(query["redirect_uri"].starts_with("http://127.0.0.1:"));
+
+        let stored = delegate.credentials.lock().clone().unwrap();
+        assert_eq!(stored.client_id, "registered-client");
+        assert_eq!(stored.access_token, "access-1");
+        assert_eq!(stored.refresh_token.as_deref(), Some("refresh-1"));
+        assert_eq!(
+            stored.token_endpoint.as_str(),
+            "https://auth.example.com/token"
+        );
+        assert!(!stored.is_expired());
+    }
+
+    #[test]
+    fn test_refreshing_expired_tokens() {
+        let server = StandInServer::new();
+        let delegate = FakeDelegate::new(
+            Some(OAuthCredentials {
+                client_id: "registered-client".into(),
+                client_secret: None,
+                token_endpoint: Url::parse("https://auth.example.com/token").unwrap(),
+                access_token: "access-1".into(),
+                refresh_token: Some("refresh-1".into()),
+                expires_at: Some(unix_time(SystemTime::now())),
+            }),
+            Browser::Unreachable,
+        );
+        let session = OAuthSession::new(
+            Url::parse(SERVER_URL).unwrap(),
+            server.http_client(),
+            delegate.clone(),
+        );
+
+        smol::block_on(async {
+            assert_eq!(session.access_token().await.as_deref(), Some("access-2"));
+            // A request that was rejected with the old token reuses the refreshed one.
+            let access_token = session
+                .handle_unauthorized(Some("access-1"), Some(CHALLENGE))
+                .await
+                .unwrap();
+            assert_eq!(access_token, "access-2");
+        });
+
+        let stored = delegate.credentials.lock().clone().unwrap();
+        assert_eq!(stored.access_token, "access-2");
+        assert_eq!(stored.refresh_token.as_deref(), Some("refresh-1"));
+        assert_eq!(*server.refresh_count.lock(), 1);
+    }
+
+    #[test]
+    fn test_ignoring_forged_state() {
+        let server = StandInServer::new();
+        let delegate = FakeDelegate::new(None, Browser::ForgeStateThenApprove);
+        let session = OAuthSession::new(
+            Url::parse(SERVER_URL).unwrap(),
+            server.http_client(),
+            delegate.clone(),
+        );
+
+        let access_token =
+            smol::block_on(session.handle_unauthorized(None, Some(CHALLENGE))).unwrap();
+        assert_eq!(access_token, "access-1");
+        assert!(!session.is_authorizing());
+        // The forged response was answered before the genuine one was sent.
+        let forged_response = delegate.responses.lock()[0].clone();
+        assert!(
+            forged_response.starts_with("HTTP/1.1 400"),
+            "unexpected response: {forged_response}"
+        );
+        assert_eq!(
+            delegate.credentials.lock().as_ref().unwrap().access_token,
+            "access-1"
+        );
+    }
+
+    #[test]
+    fn test_parse_bearer_challenge() {
+        assert_eq!(
+            BearerChallenge::parse(CHALLENGE),
+            BearerChallenge {
+                resource_metadata: Some(
+                    Url::parse("https://mcp.example.com/.well-known/oauth-protected-resource/mcp")
+                        .unwrap()
+                ),
+                scope: Some("tools:read tools:write".into()),
+            }
+        );
+        assert_eq!(
+            BearerChallenge::parse("Bearer realm=example, scope=read"),
+            BearerChallenge {
+                resource_metadata: None,
+                scope: Some("read".into()),
+            }
+        );
+        assert_eq!(
+            BearerChallenge::parse(r#"Basic realm="example""#),
+            BearerChallenge::default()
+        );
+    }
+
+    #[test]
+    fn test_well_known_urls() {
+        assert_eq!(
+            well_known_urls(
+                &Url::parse("https://example.com/tenant/mcp").unwrap(),
+                "oauth-protected-resource"
+            )
+            .iter()
+            .map(Url::as_str)
+            .collect::<Vec<_>>(),
+            [
+                "https://example.com/.well-known/oauth-protected-resource/tenant/mcp",
+                "https://example.com/.well-known/oauth-protected-resource",
+            ]
+        );
+        assert_eq!(
+            well_known_urls(
+                &Url::parse("https://example.com").unwrap(),
+                "oauth-authorization-server"
+            )
+            .iter()
+            .map(Url::as_str)
+            .collect::<Vec<_>>(),
+            ["https://example.com/.well-known/oauth-authorization-server"]
+        );
+    }
+
+    /// What the user does in the browser when asked to authorize.
+    #[derive(Clone, Copy)]
+    enum Browser {
+        Approve,
+        /// Something other than the authorization server calls the redirect URI first.
+        ForgeStateThenApprove,
+        Unreachable,
+    }
+
+    struct FakeDelegate {
+        credentials: SyncMutex<Option<OAuthCredentials>>,
+        opened_urls: SyncMutex<Vec<Url>>,
+        /// The responses the browser got from the redirect URI.
+        responses: Arc<SyncMutex<Vec<String>>>,
+        browser: Browser,
+    }
+
+    impl FakeDelegate {
+        fn new(credentials: Option<OAuthCredentials>, browser: Browser) -> Arc<Self> {
+            Arc::new(Self {
+                credentials: SyncMutex::new(credentials),
+                opened_urls: SyncMutex::default(),
+                responses: Arc::default(),
+                browser,
+            })
+        }
+    }
+
+    impl OAuthDelegate for FakeDelegate {
+        fn open_authorization_url(&self, url: &Url) {
+            self.opened_urls.lock().push(url.clone());
+            let query = url
+                .query_pairs()
+                .into_owned()
+                .collect::<HashMap<String, String>>();
+            let states = match self.browser {
+                Browser::Approve => vec![query["state"].clone()],
+                Browser::ForgeStateThenApprove => vec!["forged".into(), query["state"].clone()],
+                Browser::Unreachable => panic!("unexpected authorization request"),
+            };
+            // The stand-in authorization server redirects back with a code bound to the
+            // challenge, so the token endpoint can verify the PKCE verifier.
+            let code = format!("code-for-{}", query["code_challenge"]);
+            let redirect_uri = Url::parse(&query["redirect_uri"]).unwrap();
+            let responses = self.responses.clone();
+            std::thread::spawn(move || {
+                for state in states {
+                    let mut stream = std::net::TcpStream::connect((
+                        redirect_uri.host_str().unwrap(),
+                        redirect_uri.port().unwrap(),
+                    ))
+                    .unwrap();
+                    write!(
+                        stream,
+                        "GET {}?code={code}&state={state} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n",
+                        redirect_uri.path()
+                    )
+                    .unwrap();
+                    let mut response = String::new();
+                    stream.read_to_string(&mut response).unwrap();
+                    responses.lock().push(response);
+                }
+            });
+        }
+
+        fn load_credentials(&self) -> BoxFuture<'static, Result<Option<OAuthCredentials>>> {
+            let credentials = self.credentials.lock().clone();
+            Box::pin(async move { Ok(credentials) })
+        }
+
+        fn store_credentials(
+            &self,
+            credentials: &OAuthCredentials,
+        ) -> BoxFuture<'static, Result<()>> {
+            *self.credentials.lock() = Some(credentials.clone());
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    /// Plays both the MCP server's metadata endpoint and the authorization server.
+    struct StandInServer {
+        refresh_count: Arc<SyncMutex<usize>>,
+    }
+
+    impl StandInServer {
+        fn new() -> Self {
+            Self {
+                refresh_count: Arc::default(),
+            }
+        }
+
+        fn http_client(&self) -> Arc<dyn HttpClient> {
+            let refresh_count = self.refresh_count.clone();
+            FakeHttpClient::create(move |request| {
+                let refresh_count = refresh_count.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let method = request.method().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    let form = url::form_urlencoded::parse(body.as_bytes())
+                        .into_owned()
+                        .collect::<HashMap<String, String>>();
+
+                    let response = match (method.as_str(), uri.as_str()) {
+                        (
+                            "GET",
+                            "https://mcp.example.com/.well-known/oauth-protected-resource/mcp",
+                        ) => serde_json::json!({
+                            "resource": SERVER_URL,
+                            "authorization_servers": ["https://auth.example.com/"],
+                        }),
+                        (
+                            "GET",
+                            "https://auth.example.com/.well-known/oauth-authorization-server",
+                        ) => serde_json::json!({
+                            "issuer": "https://auth.example.com/",
+                            "authorization_endpoint": "https://auth.example.com/authorize",
+                            "token_endpoint": "https://auth.example.com/token",
+                            "registration_endpoint": "https://auth.example.com/register",
+                            "code_challenge_methods_supported": ["S256"],
+                        }),
+                        ("POST", "https://auth.example.com/register") => {
+                            let registration: serde_json::Value = serde_json::from_str(&body)?;
+                            assert_eq!(registration["token_endpoint_auth_method"], "none");
+                            serde_json::json!({
+                                "client_id": "registered-client",
+                                "redirect_uris": registration["redirect_uris"],
+                            })
+                        }
+                        ("POST", "https://auth.example.com/token") => {
+                            assert_eq!(form["client_id"], "registered-client");
+                            assert_eq!(form["resource"], SERVER_URL);
+                            match form["grant_type"].as_str() {
+                                "authorization_code" => {
+                                    let challenge = BASE64_URL_SAFE_NO_PAD
+                                        .encode(Sha256::digest(&form["code_verifier"]));
+                                    assert_eq!(form["code"], format!("code-for-{challenge}"));
+                                    serde_json::json!({
+                                        "access_token": "access-1",
+                                        "token_type": "Bearer",
+                                        "refresh_token": "refresh-1",
+                                        "expires_in": 3600,
+                                    })
+                                }
+                                "refresh_token" => {
+                                    assert_eq!(form["refresh_token"], "refresh-1");
+                                    *refresh_count.lock() += 1;
+                                    serde_json::json!({
+                                        "access_token": "access-2",
+                                        "token_type": "Bearer",
+                                        "expires_in": 3600,
+                                    })
+                                }
+                                grant_type => panic!("unexpected grant type {grant_type}"),
+                            }
+                        }
+                        _ => {
+                            return Ok(Response::builder()
+                                .status(404)
+                                .body(AsyncBody::empty())
+                                .unwrap());
+                        }
+                    };
+                    Ok(Response::builder()
+                        .status(200)
+                        .header("Content-Type", "application/json")
+                        .body(AsyncBody::from(response.to_string()))
+                        .unwrap())
+                }
+            })
+        }
+    }
+}
@@ -7,7 +7,7 @@
 
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context as _, Result, anyhow};
 use futures::channel::oneshot;
 use gpui::AsyncApp;
 use serde_json::Value;
@@ -24,9 +24,12 @@ impl ModelContextProtocol {
         Self { inner }
     }
 
+    /// Protocol revisions we can speak, newest first. We always offer the latest one, and
+    /// servers that don't support it answer with an older revision from this list.
     fn supported_protocols() -> Vec<types::ProtocolVersion> {
         vec![
             types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
+            types::ProtocolVersion(types::VERSION_2025_03_26.to_string()),
             types::ProtocolVersion(types::VERSION_2024_11_05.to_string()),
         ]
     }
@@ -34,14 +37,11 @@ impl ModelContextProtocol {
     pub async fn initialize(
         self,
         client_info: types::Implementation,
+        capabilities: types::ClientCapabilities,
     ) -> Result<InitializedContextServerProtocol> {
         let params = types::InitializeParams {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
-            capabilities: types::ClientCapabilities {
-                experimental: None,
-                sampling: None,
-                roots: None,
-            },
+            capabilities,
             meta: None,
             client_info,
         };
@@ -58,6 +58,8 @@ impl ModelContextProtocol {
         );
 
         log::trace!("mcp server info {:?}", response.server_info);
+        self.inner
+            .set_protocol_version(&response.protocol_version.0);
 
         let initialized_protocol = InitializedContextServerProtocol {
             inner: self.inner,
@@ -111,6 +113,23 @@ impl InitializedContextServerProtocol {
             .await
     }
 
+    /// Calls `tool`, checking any structured content it returns against the tool's declared
+    /// output schema.
+    pub async fn call_tool(
+        &self,
+        tool: &types::Tool,
+        params: types::CallToolParams,
+    ) -> Result<types::CallToolResponse> {
+        let response = self.request::<types::requests::CallTool>(params).await?;
+        if let Some(output_schema) = &tool.output_schema
+            && response.is_error != Some(true)
+        {
+            validate_structured_content(output_schema, response.structured_content.as_ref())
+                .with_context(|| format!("invalid result from tool {:?}", tool.name))?;
+        }
+        Ok(response)
+    }
+
     pub fn notify<T: Notification>(&self, params: T::Params) -> Result<()> {
         self.inner.notify(T::METHOD, params)
     }
@@ -123,3 +142,312 @@ impl InitializedContextServerProtocol {
         self.inner.on_notification(method, f)
     }
 }
+
+fn validate_structured_content(output_schema: &Value, content: Option<&Value>) -> Result<()> {
+    let content =
+        content.context("the tool declares an output schema but returned no structured content")?;
+    let validator = jsonschema::validator_for(output_schema)
+        .map_err(|error| anyhow!("the tool's output schema is invalid: {error}"))?;
+    let errors = validator
+        .iter_errors(content)
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>();
+    anyhow::ensure!(
+        errors.is_empty(),
+        "structured content does not match the output schema: {}",
+        errors.join("; ")
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering::SeqCst},
+    };
+
+    use collections::HashMap;
+    use gpui::{Task, TestAppContext};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        ContextServer, ContextServerId,
+        test::create_fake_transport,
+        types::{
+            CallToolParams, CallToolResponse, ElicitationAction, ElicitationCreateParams,
+            ElicitationCreateResponse, Implementation, InitializeResponse, ProtocolVersion,
+            ServerCapabilities, Tool, ToolResponseContent, requests,
+        },
+    };
+
+    fn initialize_response(version: &str) -> InitializeResponse {
+        InitializeResponse {
+            protocol_version: ProtocolVersion(version.to_string()),
+            capabilities: ServerCapabilities::default(),
+            server_info: Implementation {
+                name: "fake".to_string(),
+                title: None,
+                version: "1.0.0".to_string(),
+            },
+            meta: None,
+        }
+    }
+
+    fn weather_tool(output_schema: Option<Value>) -> Tool {
+        Tool {
+            name: "weather".to_string(),
+            title: None,
+            description: None,
+            input_schema: json!({"type": "object"}),
+            output_schema,
+            annotations: None,
+            meta: None,
+        }
+    }
+
+    fn call_tool_params() -> CallToolParams {
+        CallToolParams {
+            name: "weather".to_string(),
+            arguments: None,
+            meta: None,
+        }
+    }
+
+    #[gpui::test]
+    async fn test_protocol_version_negotiation(cx: &mut TestAppContext) {
+        let transport = Arc::new(
+            create_fake_transport("old", cx.executor()).on_request::<requests::Initialize, _>(
+                |params| async move {
+                    assert_eq!(params.protocol_version.0, types::LATEST_PROTOCOL_VERSION);
+                    initialize_response(types::VERSION_2025_03_26)
+                },
+            ),
+        );
+        let server = ContextServer::new(ContextServerId("old".into()), transport.clone());
+        server.start(&cx.to_async()).await.unwrap();
+        assert_eq!(
+            transport.protocol_version().as_deref(),
+            Some(types::VERSION_2025_03_26)
+        );
+
+        let transport = Arc::new(
+            create_fake_transport("unknown", cx.executor()).on_request::<requests::Initialize, _>(
+                |_| async move { initialize_response("2023-01-01") },
+            ),
+        );
+        let server = ContextServer::new(ContextServerId("unknown".into()), transport.clone());
+        assert!(server.start(&cx.to_async()).await.is_err());
+        assert_eq!(transport.protocol_version(), None);
+    }
+
+    #[gpui::test]
+    async fn test_structured_output_validation(cx: &mut TestAppContext) {
+        let structured_content = Arc::new(parking_lot::Mutex::new(None::<Value>));
+        let is_error = Arc::new(AtomicBool::new(false));
+        let transport = create_fake_transport("weather", cx.executor())
+            .on_request::<requests::CallTool, _>({
+                let structured_content = structured_content.clone();
+                let is_error = is_error.clone();
+                move |_| {
+                    let structured_content = structured_content.lock().clone();
+                    let is_error = is_error.load(SeqCst);
+                    async move {
+                        CallToolResponse {
+                            content: Vec::new(),
+                            is_error: Some(is_error),
+                            meta: None,
+                            structured_content,
+                        }
+                    }
+                }
+            });
+        let server = ContextServer::new(ContextServerId("weather".into()), Arc::new(transport));
+        server.start(&cx.to_async()).await.unwrap();
+        let client = server.client().unwrap();
+
+        let tool = weather_tool(Some(json!({
+            "type": "object",
+            "properties": {"temperature": {"type": "number"}},
+            "required": ["temperature"],
+        })));
+
+        *structured_content.lock() = Some(json!({"temperature": 21.5}));
+        let response = client.call_tool(&tool, call_tool_params()).await.unwrap();
+        assert_eq!(
+            response.structured_content,
+            Some(json!({"temperature": 21.5}))
+        );
+
+        *structured_content.lock() = Some(json!({"temperature": "warm"}));
+        let error = client
+            .call_tool(&tool, call_tool_params())
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{error:#}").contains("does not match the output schema"),
+            "unexpected error: {error:#}"
+        );
+
+        *structured_content.lock() = None;
+        assert!(client.call_tool(&tool, call_tool_params()).await.is_err());
+
+        // Failed calls aren't expected to produce structured output.
+        is_error.store(true, SeqCst);
+        assert!(client.call_tool(&tool, call_tool_params()).await.is_ok());
+
+        // Tools without an output schema aren't validated.
+        is_error.store(false, SeqCst);
+        *structured_content.lock() = Some(json!("anything"));
+        assert!(
+            client
+                .call_tool(&weather_tool(None), call_tool_params())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_resource_links_and_meta(cx: &mut TestAppContext) {
+        let transport = create_fake_transport("files", cx.executor())
+            .on_request::<requests::ListTools, _>(|_| async move {
+                serde_json::from_value(json!({
+                    "tools": [{
+                        "name": "find",
+                        "title": "Find Files",
+                        "inputSchema": {"type": "object"},
+                        "_meta": {"example.com/version": 2},
+                    }],
+                }))
+                .unwrap()
+            })
+            .on_request::<requests::CallTool, _>(|params| async move {
+                assert_eq!(
+                    params.meta.unwrap()["progressToken"],
+                    json!("call-1"),
+                    "request _meta should reach the server"
+                );
+                serde_json::from_value(json!({
+                    "content": [
+                        {"type": "text", "text": "Found 1 file"},
+                        {
+                            "type": "resource_link",
+                            "uri": "file:///project/src/main.rs",
+                            "name": "main.rs",
+                            "mimeType": "text/x-rust",
+                            "size": 1024,
+                            "_meta": {"example.com/score": 0.9},
+                        },
+                    ],
+                    "_meta": {"example.com/elapsed": 12},
+                }))
+                .unwrap()
+            });
+        let server = ContextServer::new(ContextServerId("files".into()), Arc::new(transport));
+        server.start(&cx.to_async()).await.unwrap();
+        let client = server.client().unwrap();
+
+        let tools = client.request::<requests::ListTools>(()).await.unwrap();
+        let tool = &tools.tools[0];
+        assert_eq!(tool.title.as_deref(), Some("Find Files"));
+        assert_eq!(tool.meta.as_ref().unwrap()["example.com/version"], json!(2));
+        assert_eq!(
+            serde_json::to_value(tool).unwrap()["_meta"],
+            json!({"example.com/version": 2})
+        );
+
+        let response = client
+            .call_tool(
+                tool,
+                CallToolParams {
+                    name: "find".to_string(),
+                    arguments: None,
+                    meta: Some(HashMap::from_iter([(
+                        "progressToken".to_string(),
+                        json!("call-1"),
+                    )])),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.text_contents(), "Found 1 file");
+        assert_eq!(response.meta.unwrap()["example.com/elapsed"], json!(12));
+        let ToolResponseContent::ResourceLink(link) = &response.content[1] else {
+            panic!("expected a resource link, got {:?}", response.content[1]);
+        };
+        assert_eq!(link.uri.as_str(), "file:///project/src/main.rs");
+        assert_eq!(link.name, "main.rs");
+        assert_eq!(link.mime_type.as_deref(), Some("text/x-rust"));
+        assert_eq!(link.size, Some(1024));
+        assert_eq!(link.meta.as_ref().unwrap()["example.com/score"], json!(0.9));
+    }
+
+    #[gpui::test]
+    async fn test_elicitation(cx: &mut TestAppContext) {
+        let advertised_elicitation = Arc::new(AtomicBool::new(false));
+        let transport = Arc::new(
+            create_fake_transport("deploy", cx.executor()).on_request::<requests::Initialize, _>({
+                let advertised_elicitation = advertised_elicitation.clone();
+                move |params| {
+                    advertised_elicitation.store(params.capabilities.elicitation.is_some(), SeqCst);
+                    async move { initialize_response(types::LATEST_PROTOCOL_VERSION) }
+                }
+            }),
+        );
+        let server = ContextServer::new(ContextServerId("deploy".into()), transport.clone());
+        server.set_elicitation_handler(Arc::new(|params: ElicitationCreateParams, _: AsyncApp| {
+            assert_eq!(params.message, "Deploy to which environment?");
+            Task::ready(Ok(ElicitationCreateResponse::accept(HashMap::from_iter([
+                ("environment".to_string(), json!("staging")),
+            ]))))
+        }));
+        server.start(&cx.to_async()).await.unwrap();
+        assert!(advertised_elicitation.load(SeqCst));
+
+        let response = transport
+            .request::<requests::ElicitationCreate>(elicitation_params())
+            .await
+            .unwrap();
+        assert_eq!(response.action, ElicitationAction::Accept);
+        assert_eq!(response.content.unwrap()["environment"], json!("staging"));
+
+        // Clients that can't answer elicitations neither advertise them nor leave them hanging.
+        let transport = Arc::new(
+            create_fake_transport("deploy", cx.executor()).on_request::<requests::Initialize, _>({
+                let advertised_elicitation = advertised_elicitation.clone();
+                move |params| {
+                    advertised_elicitation.store(params.capabilities.elicitation.is_some(), SeqCst);
+                    async move { initialize_response(types::LATEST_PROTOCOL_VERSION) }
+                }
+            }),
+        );
+        let server = ContextServer::new(ContextServerId("deploy".into()), transport.clone());
+        server.start(&cx.to_async()).await.unwrap();
+        assert!(!advertised_elicitation.load(SeqCst));
+        assert!(
+            transport
+                .request::<requests::ElicitationCreate>(elicitation_params())
+                .await
+                .is_err()
+        );
+    }
+
+    fn elicitation_params() -> ElicitationCreateParams {
+        serde_json::from_value(json!({
+            "message": "Deploy to which environment?",
+            "requestedSchema": {
+                "type": "object",
+                "properties": {
+                    "environment": {
+                        "type": "string",
+                        "enum": ["staging", "production"],
+                        "enumNames": ["Staging", "Production"],
+                    },
+                },
+                "required": ["environment"],
+            },
+        }))
+        .unwrap()
+    }
+}
@@ -1,8 +1,16 @@
 use anyhow::Context as _;
 use collections::HashMap;
-use futures::{FutureExt, Stream, StreamExt as _, future::BoxFuture, lock::Mutex};
+use futures::{
+    FutureExt, Stream, StreamExt as _, channel::oneshot, future::BoxFuture, lock::Mutex,
+};
 use gpui::BackgroundExecutor;
-use std::{pin::Pin, sync::Arc};
+use std::{
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
+};
 
 use crate::{
     transport::Transport,
@@ -27,6 +35,7 @@ fn create_initialize_response(server_name: String) -> InitializeResponse {
         protocol_version: ProtocolVersion(crate::types::LATEST_PROTOCOL_VERSION.to_string()),
         server_info: Implementation {
             name: server_name,
+            title: None,
             version: "1.0.0".to_string(),
         },
         capabilities: ServerCapabilities::default(),
@@ -41,6 +50,10 @@ pub struct FakeTransport {
     >,
     tx: futures::channel::mpsc::UnboundedSender<String>,
     rx: Arc<Mutex<futures::channel::mpsc::UnboundedReceiver<String>>>,
+    /// Requests sent by the fake server that the client hasn't answered yet.
+    pending_requests: parking_lot::Mutex<HashMap<String, oneshot::Sender<serde_json::Value>>>,
+    next_request_id: AtomicUsize,
+    protocol_version: parking_lot::Mutex<Option<String>>,
     executor: BackgroundExecutor,
 }
 
@@ -51,6 +64,9 @@ impl FakeTransport {
             request_handlers: Default::default(),
             tx,
             rx: Arc::new(Mutex::new(rx)),
+            pending_requests: Default::default(),
+            next_request_id: AtomicUsize::new(0),
+            protocol_version: Default::default(),
             executor,
         }
     }
@@ -78,6 +94,40 @@ impl FakeTransport {
         );
         self
     }
+
+    /// Sends a request from the fake server to the client and waits for the client's answer.
+    pub fn request<T: crate::types::Request>(
+        &self,
+        params: T::Params,
+    ) -> impl Future<Output = anyhow::Result<T::Response>> + use<T> {
+        let id = format!("fake-{}", self.next_request_id.fetch_add(1, SeqCst));
+        let (response_tx, response_rx) = oneshot::channel();
+        self.pending_requests.lock().insert(id.clone(), response_tx);
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": T::METHOD,
+            "params": params,
+        });
+        let sent = self.tx.unbounded_send(request.to_string());
+        async move {
+            sent.context("sending a request")?;
+            let response = response_rx.await.context("client dropped the request")?;
+            if let Some(error) = response.get("error") {
+                anyhow::bail!("client returned an error: {error}");
+            }
+            let result = response
+                .get("result")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            Ok(serde_json::from_value(result)?)
+        }
+    }
+
+    /// The protocol version the client settled on during initialization.
+    pub fn protocol_version(&self) -> Option<String> {
+        self.protocol_version.lock().clone()
+    }
 }
 
 #[async_trait::async_trait]
@@ -101,6 +151,10 @@ impl Transport for FakeTransport {
                 } else {
                     log::debug!("No handler registered for MCP request '{method}'");
                 }
+            } else if let Some(id) = msg.get("id").and_then(|id| id.as_str()).map(str::to_string)
+                && let Some(response_tx) = self.pending_requests.lock().remove(&id)
+            {
+                response_tx.send(msg).ok();
             }
         }
         Ok(())
@@ -127,4 +181,8 @@ impl Transport for FakeTransport {
     fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
         Box::pin(futures::stream::empty())
     }
+
+    fn set_protocol_version(&self, version: &str) {
+        *self.protocol_version.lock() = Some(version.to_string());
+    }
 }
@@ -14,4 +14,14 @@ pub trait Transport: Send + Sync {
     async fn send(&self, message: String) -> Result<()>;
     fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>>;
     fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>>;
+
+    /// Called once initialization has negotiated a protocol version, for transports that
+    /// need to echo it back to the server.
+    fn set_protocol_version(&self, _version: &str) {}
+
+    /// Whether the transport is waiting for the user to grant it access to the server. Requests
+    /// don't time out while this is the case.
+    fn is_authorizing(&self) -> bool {
+        false
+    }
 }
@@ -8,10 +8,11 @@ use parking_lot::Mutex as SyncMutex;
 use smol::channel;
 use std::{pin::Pin, sync::Arc};
 
-use crate::transport::Transport;
+use crate::{oauth::OAuthSession, transport::Transport};
 
 // Constants from MCP spec
 const HEADER_SESSION_ID: &str = "Mcp-Session-Id";
+const HEADER_PROTOCOL_VERSION: &str = "MCP-Protocol-Version";
 const EVENT_STREAM_MIME_TYPE: &str = "text/event-stream";
 const JSON_MIME_TYPE: &str = "application/json";
 
@@ -20,6 +21,7 @@ pub struct HttpTransport {
     http_client: Arc<dyn HttpClient>,
     endpoint: String,
     session_id: Arc<SyncMutex<Option<String>>>,
+    protocol_version: SyncMutex<Option<String>>,
     executor: BackgroundExecutor,
     response_tx: channel::Sender<String>,
     response_rx: channel::Receiver<String>,
@@ -27,6 +29,7 @@ pub struct HttpTransport {
     error_rx: channel::Receiver<String>,
     // Authentication headers to include in requests
     headers: HashMap<String, String>,
+    oauth: Option<Arc<OAuthSession>>,
 }
 
 impl HttpTransport {
@@ -44,19 +47,27 @@ impl HttpTransport {
             executor,
             endpoint,
             session_id: Arc::new(SyncMutex::new(None)),
+            protocol_version: SyncMutex::new(None),
             response_tx,
             response_rx,
             error_tx,
             error_rx,
             headers,
+            oauth: None,
         }
     }
 
-    /// Send a message and handle the response based on content type
-    async fn send_message(&self, message: String) -> Result<()> {
-        let is_notification =
-            !message.contains("\"id\":") || message.contains("notifications/initialized");
+    /// Authorize requests with OAuth, prompting the user when the server asks for authorization.
+    pub fn with_oauth(mut self, oauth: Arc<OAuthSession>) -> Self {
+        self.oauth = Some(oauth);
+        self
+    }
 
+    fn build_request(
+        &self,
+        message: &str,
+        access_token: Option<&str>,
+    ) -> Result<Request<AsyncBody>> {
         let mut request_builder = Request::builder()
             .method(Method::POST)
             .uri(&self.endpoint)
@@ -69,15 +80,53 @@ impl HttpTransport {
         for (key, value) in &self.headers {
             request_builder = request_builder.header(key.as_str(), value.as_str());
         }
+        if let Some(access_token) = access_token {
+            request_builder =
+                request_builder.header("Authorization", format!("Bearer {access_token}"));
+        }
 
         // Add session ID if we have one (except for initialize)
         if let Some(ref session_id) = *self.session_id.lock() {
             request_builder = request_builder.header(HEADER_SESSION_ID, session_id.as_str());
         }
+        if let Some(ref protocol_version) = *self.protocol_version.lock() {
+            request_builder =
+                request_builder.header(HEADER_PROTOCOL_VERSION, protocol_version.as_str());
+        }
+
+        Ok(request_builder.body(AsyncBody::from(message.to_string()))?)
+    }
+
+    /// Send a message and handle the response based on content type
+    async fn send_message(&self, message: String) -> Result<()> {
+        // Notifications and our responses to server requests expect no reply, so servers may
+        // acknowledge them with a bare 202.
+        let expects_reply = serde_json::from_str::<serde_json::Value>(&message)
+            .ok()
+            .is_some_and(|message| message.get("id").is_some() && message.get("method").is_some());
 
-        let request = request_builder.body(AsyncBody::from(message.into_bytes()))?;
+        let access_token = match &self.oauth {
+            Some(oauth) => oauth.access_token().await,
+            None => None,
+        };
+        let request = self.build_request(&message, access_token.as_deref())?;
         let mut response = self.http_client.send(request).await?;
 
+        if response.status().as_u16() == 401
+            && let Some(oauth) = &self.oauth
+        {
+            let challenge = response
+                .headers()
+                .get("www-authenticate")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            let access_token = oauth
+                .handle_unauthorized(access_token.as_deref(), challenge.as_deref())
+                .await?;
+            let request = self.build_request(&message, Some(&access_token))?;
+            response = self.http_client.send(request).await?;
+        }
+
         // Handle different response types based on status and content-type
         match response.status() {
             status if status.is_success() => {
@@ -117,9 +166,8 @@ impl HttpTransport {
                         self.setup_sse_stream(response).await?;
                     }
                     _ => {
-                        // For notifications, 202 Accepted with no content type is ok
-                        if is_notification && status.as_u16() == 202 {
-                            log::debug!("Notification accepted");
+                        if !expects_reply && status.as_u16() == 202 {
+                            log::debug!("Message accepted");
                         } else {
                             return Err(anyhow!("Unexpected content type: {:?}", content_type));
                         }
@@ -224,6 +272,16 @@ impl Transport for HttpTransport {
     fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
         Box::pin(self.error_rx.clone())
     }
+
+    fn set_protocol_version(&self, version: &str) {
+        *self.protocol_version.lock() = Some(version.to_string());
+    }
+
+    fn is_authorizing(&self) -> bool {
+        self.oauth
+            .as_ref()
+            .is_some_and(|oauth| oauth.is_authorizing())
+    }
 }
 
 impl Drop for HttpTransport {
@@ -232,7 +290,9 @@ impl Drop for HttpTransport {
         let http_client = self.http_client.clone();
         let endpoint = self.endpoint.clone();
         let session_id = self.session_id.lock().clone();
+        let protocol_version = self.protocol_version.lock().clone();
         let headers = self.headers.clone();
+        let oauth = self.oauth.clone();
 
         if let Some(session_id) = session_id {
             self.executor
@@ -241,11 +301,21 @@ impl Drop for HttpTransport {
                         .method(Method::DELETE)
                         .uri(&endpoint)
                         .header(HEADER_SESSION_ID, &session_id);
+                    if let Some(protocol_version) = protocol_version {
+                        request_builder =
+                            request_builder.header(HEADER_PROTOCOL_VERSION, protocol_version);
+                    }
 
                     // Add authentication headers if present
                     for (key, value) in headers {
                         request_builder = request_builder.header(key.as_str(), value.as_str());
                     }
+                    if let Some(oauth) = oauth
+                        && let Some(access_token) = oauth.access_token().await
+                    {
+                        request_builder = request_builder
+                            .header("Authorization", format!("Bearer {access_token}"));
+                    }
 
                     let request = request_builder.body(AsyncBody::empty());
 
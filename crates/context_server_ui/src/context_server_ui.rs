@@ -0,0 +1,42 @@
+//! UI for requests that context servers make of the user.
+
+mod elicitation_modal;
+
+use gpui::App;
+use project::context_server_store;
+use workspace::Workspace;
+
+pub use elicitation_modal::ElicitationModal;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        let context_server_store = workspace.project().read(cx).context_server_store();
+        cx.subscribe_in(
+            &context_server_store,
+            window,
+            |workspace, context_server_store, event, window, cx| {
+                if let context_server_store::Event::ElicitationRequested {
+                    server_id,
+                    elicitation_id,
+                    params,
+                } = event
+                {
+                    ElicitationModal::show(
+                        workspace,
+                        context_server_store.downgrade(),
+                        server_id.clone(),
+                        *elicitation_id,
+                        params.clone(),
+                        window,
+                        cx,
+                    );
+                }
+            },
+        )
+        .detach();
+    })
+    .detach();
+}
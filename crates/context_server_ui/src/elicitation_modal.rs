@@ -0,0 +1,592 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use collections::HashMap;
+use context_server::{
+    ContextServerId,
+    types::{ElicitationCreateParams, ElicitationCreateResponse, PrimitiveSchema, StringSchema},
+};
+use editor::Editor;
+use gpui::{App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, WeakEntity};
+use project::context_server_store::{ContextServerStore, ElicitationId};
+use serde_json::Value;
+use ui::{Checkbox, ToggleState, prelude::*};
+use workspace::{DismissDecision, ModalView, Workspace};
+
+struct Elicitation {
+    server_id: ContextServerId,
+    id: ElicitationId,
+    params: Arc<ElicitationCreateParams>,
+}
+
+/// A form for the input a context server asked for with `elicitation/create`.
+///
+/// Requests that arrive while the form is open are queued behind it. Dismissing the modal
+/// cancels every request it still holds.
+pub struct ElicitationModal {
+    context_server_store: WeakEntity<ContextServerStore>,
+    current: Option<Elicitation>,
+    fields: Vec<Field>,
+    queue: VecDeque<Elicitation>,
+    focus_handle: FocusHandle,
+}
+
+struct Field {
+    name: String,
+    schema: PrimitiveSchema,
+    required: bool,
+    input: FieldInput,
+    error: Option<SharedString>,
+}
+
+enum FieldInput {
+    Text(Entity<Editor>),
+    /// A string restricted to an enum, as `(value, display name)` pairs.
+    Choice {
+        options: Vec<(String, SharedString)>,
+        selected: Option<usize>,
+    },
+    Toggle(bool),
+}
+
+impl ElicitationModal {
+    pub fn show(
+        workspace: &mut Workspace,
+        context_server_store: WeakEntity<ContextServerStore>,
+        server_id: ContextServerId,
+        elicitation_id: ElicitationId,
+        params: Arc<ElicitationCreateParams>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let elicitation = Elicitation {
+            server_id,
+            id: elicitation_id,
+            params,
+        };
+        if let Some(modal) = workspace.active_modal::<Self>(cx) {
+            modal.update(cx, |modal, cx| {
+                modal.queue.push_back(elicitation);
+                cx.notify();
+            });
+            return;
+        }
+        workspace.toggle_modal(window, cx, |window, cx| {
+            let mut modal = Self {
+                context_server_store,
+                current: None,
+                fields: Vec::new(),
+                queue: VecDeque::new(),
+                focus_handle: cx.focus_handle(),
+            };
+            modal.set_current(elicitation, window, cx);
+            modal
+        });
+    }
+
+    fn set_current(
+        &mut self,
+        elicitation: Elicitation,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let schema = &elicitation.params.requested_schema;
+        self.fields = schema
+            .properties
+            .iter()
+            .map(|(name, property)| Field {
+                name: name.clone(),
+                schema: property.clone(),
+                required: schema.required.contains(name),
+                input: FieldInput::new(property, window, cx),
+                error: None,
+            })
+            .collect();
+        self.current = Some(elicitation);
+        cx.notify();
+    }
+
+    fn submit(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let mut content = HashMap::default();
+        let mut valid = true;
+        for field in &mut self.fields {
+            field.error = None;
+            let value = match &field.input {
+                FieldInput::Text(editor) => {
+                    match parse_text(&field.schema, &editor.read(cx).text(cx)) {
+                        Ok(value) => value,
+                        Err(error) => {
+                            field.error = Some(error.into());
+                            valid = false;
+                            continue;
+                        }
+                    }
+                }
+                FieldInput::Choice { options, selected } => {
+                    selected.map(|ix| Value::String(options[ix].0.clone()))
+                }
+                FieldInput::Toggle(value) => Some(Value::Bool(*value)),
+            };
+            match value {
+                Some(value) => {
+                    content.insert(field.name.clone(), value);
+                }
+                None if field.required => {
+                    field.error = Some("Required".into());
+                    valid = false;
+                }
+                None => {}
+            }
+        }
+        cx.notify();
+
+        if valid {
+            self.respond(ElicitationCreateResponse::accept(content), window, cx);
+        }
+    }
+
+    fn decline(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.respond(ElicitationCreateResponse::decline(), window, cx);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        self.respond(ElicitationCreateResponse::cancel(), window, cx);
+    }
+
+    /// Answers the request on screen and moves on to the next queued one.
+    fn respond(
+        &mut self,
+        response: ElicitationCreateResponse,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(elicitation) = self.current.take() {
+            self.context_server_store
+                .update(cx, |store, _| {
+                    store.respond_to_elicitation(elicitation.id, response)
+                })
+                .ok();
+        }
+        match self.queue.pop_front() {
+            Some(next) => {
+                self.set_current(next, window, cx);
+                window.focus(&self.focus_handle(cx), cx);
+            }
+            None => cx.emit(DismissEvent),
+        }
+    }
+
+    fn render_field(&self, ix: usize, field: &Field, cx: &mut Context<Self>) -> impl IntoElement {
+        let label = field.schema.title().unwrap_or(&field.name).to_string();
+        let label = if field.required {
+            format!("{label} *")
+        } else {
+            label
+        };
+        let input = match &field.input {
+            FieldInput::Text(editor) => div()
+                .px_2()
+                .py_1()
+                .rounded_sm()
+                .border_1()
+                .border_color(if field.error.is_some() {
+                    cx.theme().status().error_border
+                } else {
+                    cx.theme().colors().border
+                })
+                .child(editor.clone())
+                .into_any_element(),
+            FieldInput::Choice { options, selected } => h_flex()
+                .flex_wrap()
+                .gap_1()
+                .children(options.iter().enumerate().map(|(option_ix, (_, name))| {
+                    Button::new(("choice", ix * 1000 + option_ix), name.clone())
+                        .style(ButtonStyle::Outlined)
+                        .toggle_state(*selected == Some(option_ix))
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            if let Some(FieldInput::Choice { selected, .. }) =
+                                this.fields.get_mut(ix).map(|field| &mut field.input)
+                            {
+                                *selected = Some(option_ix);
+                                cx.notify();
+                            }
+                        }))
+                }))
+                .into_any_element(),
+            FieldInput::Toggle(value) => Checkbox::new(("toggle", ix), ToggleState::from(*value))
+                .label(label.clone())
+                .on_click(cx.listener(move |this, state: &ToggleState, _, cx| {
+                    if let Some(FieldInput::Toggle(value)) =
+                        this.fields.get_mut(ix).map(|field| &mut field.input)
+                    {
+                        *value = state.selected();
+                        cx.notify();
+                    }
+                }))
+                .into_any_element(),
+        };
+
+        v_flex()
+            .gap_1()
+            .when(!matches!(field.input, FieldInput::Toggle(_)), |this| {
+                this.child(Label::new(label).size(LabelSize::Small))
+            })
+            .when_some(field.schema.description(), |this, description| {
+                this.child(
+                    Label::new(description.to_string())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .child(input)
+            .when_some(field.error.clone(), |this, error| {
+                this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+            })
+    }
+}
+
+impl FieldInput {
+    fn new(
+        schema: &PrimitiveSchema,
+        window: &mut Window,
+        cx: &mut Context<ElicitationModal>,
+    ) -> Self {
+        match schema {
+            PrimitiveSchema::String(StringSchema {
+                enum_values: Some(values),
+                enum_names,
+                default,
+                ..
+            }) => FieldInput::Choice {
+                options: values
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, value)| {
+                        let name = enum_names
+                            .as_ref()
+                            .and_then(|names| names.get(ix))
+                            .unwrap_or(value);
+                        (value.clone(), SharedString::from(name.clone()))
+                    })
+                    .collect(),
+                selected: default
+                    .as_ref()
+                    .and_then(|default| values.iter().position(|value| value == default)),
+            },
+            PrimitiveSchema::Boolean(schema) => FieldInput::Toggle(schema.default.unwrap_or(false)),
+            PrimitiveSchema::String(schema) => Self::text(
+                schema.default.clone(),
+                placeholder(schema.format.as_deref()),
+                window,
+                cx,
+            ),
+            PrimitiveSchema::Number(schema) => Self::text(
+                schema.default.map(|default| default.to_string()),
+                Some("Number"),
+                window,
+                cx,
+            ),
+            PrimitiveSchema::Integer(schema) => Self::text(
+                schema.default.map(|default| default.to_string()),
+                Some("Whole number"),
+                window,
+                cx,
+            ),
+        }
+    }
+
+    fn text(
+        default: Option<String>,
+        placeholder: Option<&str>,
+        window: &mut Window,
+        cx: &mut Context<ElicitationModal>,
+    ) -> Self {
+        FieldInput::Text(cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            if let Some(placeholder) = placeholder {
+                editor.set_placeholder_text(placeholder, window, cx);
+            }
+            if let Some(default) = default {
+                editor.set_text(default, window, cx);
+            }
+            editor
+        }))
+    }
+}
+
+fn placeholder(format: Option<&str>) -> Option<&'static str> {
+    match format? {
+        "email" => Some("name@example.com"),
+        "uri" => Some("https://"),
+        "date" => Some("YYYY-MM-DD"),
+        "date-time" => Some("YYYY-MM-DDThh:mm:ssZ"),
+        _ => None,
+    }
+}
+
+/// Parses what was typed into a text field, returning `None` when the field was left empty.
+fn parse_text(schema: &PrimitiveSchema, text: &str) -> Result<Option<Value>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    match schema {
+        PrimitiveSchema::String(schema) => {
+            let len = text.chars().count();
+            if let Some(min_length) = schema.min_length
+                && len < min_length
+            {
+                return Err(format!("Must be at least {min_length} characters"));
+            }
+            if let Some(max_length) = schema.max_length
+                && len > max_length
+            {
+                return Err(format!("Must be at most {max_length} characters"));
+            }
+            if let Some(format) = schema.format.as_deref() {
+                validate_format(format, text)?;
+            }
+            Ok(Some(Value::String(text.to_string())))
+        }
+        PrimitiveSchema::Number(schema) => {
+            let number = text
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or("Enter a number")?;
+            check_range(number, schema.minimum, schema.maximum)?;
+            Ok(Some(number.into()))
+        }
+        PrimitiveSchema::Integer(schema) => {
+            let number = text
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| "Enter a whole number")?;
+            check_range(number as f64, schema.minimum, schema.maximum)?;
+            Ok(Some(number.into()))
+        }
+        PrimitiveSchema::Boolean(_) => Err("Expected a checkbox".to_string()),
+    }
+}
+
+fn check_range(number: f64, minimum: Option<f64>, maximum: Option<f64>) -> Result<(), String> {
+    if let Some(minimum) = minimum
+        && number < minimum
+    {
+        return Err(format!("Must be at least {minimum}"));
+    }
+    if let Some(maximum) = maximum
+        && number > maximum
+    {
+        return Err(format!("Must be at most {maximum}"));
+    }
+    Ok(())
+}
+
+fn validate_format(format: &str, text: &str) -> Result<(), String> {
+    let (valid, error) = match format {
+        "email" => (
+            !text.contains(char::is_whitespace)
+                && text
+                    .split_once('@')
+                    .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.')),
+            "Enter an email address",
+        ),
+        "uri" => (url::Url::parse(text).is_ok(), "Enter a URL"),
+        "date" => (
+            chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok(),
+            "Enter a date as YYYY-MM-DD",
+        ),
+        "date-time" => (
+            chrono::DateTime::parse_from_rfc3339(text).is_ok(),
+            "Enter a date and time as YYYY-MM-DDThh:mm:ssZ",
+        ),
+        _ => (true, ""),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(error.to_string())
+    }
+}
+
+impl EventEmitter<DismissEvent> for ElicitationModal {}
+
+impl ModalView for ElicitationModal {
+    fn fade_out_background(&self) -> bool {
+        true
+    }
+
+    fn on_before_dismiss(&mut self, _: &mut Window, cx: &mut Context<Self>) -> DismissDecision {
+        let pending = self
+            .current
+            .take()
+            .into_iter()
+            .chain(self.queue.drain(..))
+            .collect::<Vec<_>>();
+        self.context_server_store
+            .update(cx, |store, _| {
+                for elicitation in pending {
+                    store.respond_to_elicitation(
+                        elicitation.id,
+                        ElicitationCreateResponse::cancel(),
+                    );
+                }
+            })
+            .ok();
+        DismissDecision::Dismiss(true)
+    }
+}
+
+impl Focusable for ElicitationModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.fields
+            .iter()
+            .find_map(|field| match &field.input {
+                FieldInput::Text(editor) => Some(editor.focus_handle(cx)),
+                _ => None,
+            })
+            .unwrap_or_else(|| self.focus_handle.clone())
+    }
+}
+
+impl Render for ElicitationModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(current) = self.current.as_ref() else {
+            return div().into_any_element();
+        };
+        let queued = self.queue.len();
+
+        v_flex()
+            .key_context("ElicitationModal")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::submit))
+            .on_action(cx.listener(Self::cancel))
+            .elevation_3(cx)
+            .w(rems(34.))
+            .child(
+                h_flex()
+                    .px_3()
+                    .py_2()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Icon::new(IconName::ToolHammer).color(Color::Muted))
+                            .child(Label::new(format!(
+                                "{} is requesting information",
+                                current.server_id
+                            ))),
+                    )
+                    .when(queued > 0, |this| {
+                        this.child(
+                            Label::new(format!("{queued} more"))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    }),
+            )
+            .child(
+                v_flex()
+                    .p_3()
+                    .gap_3()
+                    .child(Label::new(current.params.message.clone()))
+                    .children(
+                        self.fields
+                            .iter()
+                            .enumerate()
+                            .map(|(ix, field)| self.render_field(ix, field, cx)),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .px_3()
+                    .pb_3()
+                    .gap_1()
+                    .justify_end()
+                    .child(Button::new("cancel", "Cancel").on_click(cx.listener(
+                        |this, _, window, cx| {
+                            this.cancel(&menu::Cancel, window, cx);
+                        },
+                    )))
+                    .child(Button::new("decline", "Decline").on_click(cx.listener(
+                        |this, _, window, cx| {
+                            this.decline(window, cx);
+                        },
+                    )))
+                    .child(
+                        Button::new("submit", "Submit")
+                            .style(ButtonStyle::Filled)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.submit(&menu::Confirm, window, cx);
+                            })),
+                    ),
+            )
+            .into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context_server::types::NumberSchema;
+    use serde_json::json;
+
+    fn string(schema: StringSchema) -> PrimitiveSchema {
+        PrimitiveSchema::String(schema)
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let schema = string(StringSchema {
+            min_length: Some(2),
+            max_length: Some(4),
+            ..Default::default()
+        });
+        assert_eq!(parse_text(&schema, "  "), Ok(None));
+        assert_eq!(parse_text(&schema, "abc"), Ok(Some(json!("abc"))));
+        assert!(parse_text(&schema, "a").is_err());
+        assert!(parse_text(&schema, "abcde").is_err());
+
+        let email = string(StringSchema {
+            format: Some("email".into()),
+            ..Default::default()
+        });
+        assert!(parse_text(&email, "me@example.com").is_ok());
+        assert!(parse_text(&email, "me@localhost").is_err());
+        assert!(parse_text(&email, "not an email").is_err());
+
+        let date = string(StringSchema {
+            format: Some("date".into()),
+            ..Default::default()
+        });
+        assert!(parse_text(&date, "2025-06-18").is_ok());
+        assert!(parse_text(&date, "2025-13-01").is_err());
+
+        let uri = string(StringSchema {
+            format: Some("uri".into()),
+            ..Default::default()
+        });
+        assert!(parse_text(&uri, "https://zed.dev").is_ok());
+        assert!(parse_text(&uri, "zed dot dev").is_err());
+
+        let number = NumberSchema {
+            minimum: Some(0.),
+            maximum: Some(10.),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_text(&PrimitiveSchema::Number(number.clone()), " 2.5 "),
+            Ok(Some(json!(2.5)))
+        );
+        assert!(parse_text(&PrimitiveSchema::Number(number.clone()), "11").is_err());
+        assert!(parse_text(&PrimitiveSchema::Number(number.clone()), "NaN").is_err());
+        assert_eq!(
+            parse_text(&PrimitiveSchema::Integer(number.clone()), "3"),
+            Ok(Some(json!(3)))
+        );
+        assert!(parse_text(&PrimitiveSchema::Integer(number), "2.5").is_err());
+    }
+}
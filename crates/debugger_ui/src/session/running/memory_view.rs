@@ -3,7 +3,7 @@ use std::{
     fmt::Write,
     ops::RangeInclusive,
     rc::Rc,
-    sync::{Arc, LazyLock},
+    sync::Arc,
     time::Duration,
 };
 
@@ -14,6 +14,7 @@ use gpui::{
     Subscription, Task, TextStyle, UniformList, UniformListScrollHandle, WeakEntity, actions,
     anchored, deferred, uniform_list,
 };
+use hex_editor::{hex_byte, printable_char};
 use notifications::status_toast::{StatusToast, ToastIcon};
 use project::debugger::{MemoryCell, dap_command::DataBreakpointContext, session::Session};
 use settings::Settings;
@@ -158,8 +159,6 @@ impl ScrollableHandle for ViewStateHandle {
     }
 }
 
-static HEX_BYTES_MEMOIZED: LazyLock<[SharedString; 256]> =
-    LazyLock::new(|| std::array::from_fn(|byte| SharedString::from(format!("{byte:02X}"))));
 static UNKNOWN_BYTE: SharedString = SharedString::new_static("??");
 
 impl MemoryView {
@@ -758,9 +757,7 @@ fn render_single_memory_view_line(
                         })
                         .child(
                             Label::new(
-                                cell.0
-                                    .map(|val| HEX_BYTES_MEMOIZED[val as usize].clone())
-                                    .unwrap_or_else(|| UNKNOWN_BYTE.clone()),
+                                cell.0.map(hex_byte).unwrap_or_else(|| UNKNOWN_BYTE.clone()),
                             )
                             .buffer_font(cx)
                             .when(cell.0.is_none(), |this| this.color(Color::Muted))
@@ -821,12 +818,7 @@ fn render_single_memory_view_line(
                 .border_x_1()
                 .border_color(Color::Muted.color(cx))
                 .children(memory.iter().enumerate().map(|(ix, cell)| {
-                    let as_character = char::from(cell.0.unwrap_or(0));
-                    let as_visible = if as_character.is_ascii_graphic() {
-                        as_character
-                    } else {
-                        '·'
-                    };
+                    let as_visible = printable_char(cell.0.unwrap_or(0));
                     div()
                         .px_0p5()
                         .when_some(view_state.selection.as_ref(), |this, selection| {
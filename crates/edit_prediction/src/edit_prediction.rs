@@ -22,7 +22,7 @@ use gpui::{
     http_client::{self, AsyncBody, Method},
     prelude::*,
 };
-use language::language_settings::all_language_settings;
+use language::language_settings::{LocalEditPredictionSettings, all_language_settings};
 use language::{Anchor, File, LanguageBuffer, Point, TextBufferSnapshot, ToOffset, ToPoint};
 use language::{BufferSnapshot, OffsetRangeExt};
 use project::{Project, ProjectPath, WorktreeId};
@@ -47,6 +47,7 @@ use util::{RangeExt as _, ResultExt as _};
 pub mod cursor_excerpt;
 pub mod example_spec;
 mod license_detection;
+mod local_model;
 mod prediction;
 
 pub mod udiff;
@@ -59,6 +60,7 @@ mod edit_prediction_tests;
 
 use crate::capture_example::should_sample_edit_prediction_example_capture;
 use crate::license_detection::LicenseDetectionWatcher;
+pub use crate::local_model::LocalPredictionModel;
 pub use crate::prediction::EditPrediction;
 pub use crate::prediction::EditPredictionId;
 use crate::prediction::EditPredictionResult;
@@ -96,6 +98,7 @@ pub struct EditPredictionStore {
     #[cfg(feature = "cli-support")]
     eval_cache: Option<Arc<dyn EvalCache>>,
     edit_prediction_model: Box<dyn EditPredictionModel>,
+    local_model_settings: Option<LocalEditPredictionSettings>,
     data_collection_choice: DataCollectionChoice,
     reject_predictions_tx: mpsc::UnboundedSender<EditPredictionRejection>,
     shown_predictions: VecDeque<EditPrediction>,
@@ -103,17 +106,39 @@ pub struct EditPredictionStore {
 }
 
 pub trait EditPredictionModel {
+    fn request_prediction(
+        self: &Self,
+        store: &EditPredictionStore,
+        inputs: EditPredictionModelInput,
+        cx: &mut App,
+    ) -> Task<Result<Option<EditPredictionResult>>>;
+
     fn edit_prediction_accepted(
         self: &Self,
         store: &EditPredictionStore,
         current_prediction: CurrentEditPrediction,
         cx: &App,
     );
+
+    /// Whether requests stay on the user's network, in which case nothing about
+    /// predictions or the code they were made for is reported to Zed.
+    fn is_local(&self) -> bool {
+        false
+    }
 }
 
 struct DummyPredictionModel {}
 
 impl EditPredictionModel for DummyPredictionModel {
+    fn request_prediction(
+        self: &Self,
+        _: &EditPredictionStore,
+        _: EditPredictionModelInput,
+        _: &mut App,
+    ) -> Task<Result<Option<EditPredictionResult>>> {
+        Task::ready(Ok(None))
+    }
+
     fn edit_prediction_accepted(
         self: &Self,
         _: &EditPredictionStore,
@@ -124,7 +149,11 @@ impl EditPredictionModel for DummyPredictionModel {
 }
 
 pub struct EditPredictionModelInput {
+    buffer: Entity<LanguageBuffer>,
+    snapshot: BufferSnapshot,
+    position: Anchor,
     events: Vec<Arc<zeta_prompt::Event>>,
+    related_files: Arc<[RelatedFile]>,
     pub user_actions: Vec<UserActionRecord>,
 }
 
@@ -555,6 +584,7 @@ impl EditPredictionStore {
             #[cfg(feature = "cli-support")]
             eval_cache: None,
             edit_prediction_model: Box::new(DummyPredictionModel {}),
+            local_model_settings: None,
             data_collection_choice,
             reject_predictions_tx: reject_tx,
             rated_predictions: Default::default(),
@@ -562,6 +592,7 @@ impl EditPredictionStore {
         };
 
         this.configure_context_retrieval(cx);
+        this.configure_edit_prediction_model(cx);
         let weak_this = cx.weak_entity();
         cx.on_flags_ready(move |_, cx| {
             weak_this
@@ -571,6 +602,7 @@ impl EditPredictionStore {
         .detach();
         cx.observe_global::<SettingsStore>(|this, cx| {
             this.configure_context_retrieval(cx);
+            this.configure_edit_prediction_model(cx);
         })
         .detach();
 
@@ -1159,6 +1191,10 @@ impl EditPredictionStore {
         reason: EditPredictionRejectReason,
         was_shown: bool,
     ) {
+        if self.edit_prediction_model.is_local() {
+            return;
+        }
+
         self.reject_predictions_tx
             .unbounded_send(EditPredictionRejection {
                 request_id: prediction_id.to_string(),
@@ -1511,13 +1547,18 @@ impl EditPredictionStore {
         }
 
         let inputs = EditPredictionModelInput {
+            related_files: self.context_for_project(&project, cx),
+            buffer: active_buffer.clone(),
+            snapshot: snapshot.clone(),
+            position,
             events,
             user_actions,
         };
 
-        let can_collect_example = snapshot
-            .file()
-            .is_some_and(|file| self.can_collect_file(&project, file, cx))
+        let can_collect_example = !self.edit_prediction_model.is_local()
+            && snapshot
+                .file()
+                .is_some_and(|file| self.can_collect_file(&project, file, cx))
             && self.can_collect_events(&inputs.events, cx);
 
         if can_collect_example && should_sample_edit_prediction_example_capture(cx) {
@@ -1539,13 +1580,9 @@ impl EditPredictionStore {
                 .detach_and_log_err(cx);
             }
         }
-        // let task = todo!();
 
-        // cx.spawn(async move |this, cx| {
-        //     let prediction = task.await?;
-        //     Ok(prediction)
-        // })
-        Task::ready(Ok(None))
+        self.edit_prediction_model
+            .request_prediction(self, inputs, cx)
     }
 
     async fn next_diagnostic_location(
@@ -1811,6 +1848,24 @@ impl EditPredictionStore {
     fn configure_context_retrieval(&mut self, cx: &mut Context<'_, EditPredictionStore>) {
         self.use_context = all_language_settings(None, cx).edit_predictions.use_context;
     }
+
+    fn configure_edit_prediction_model(&mut self, cx: &mut Context<'_, EditPredictionStore>) {
+        let settings = &all_language_settings(None, cx).edit_predictions;
+        let local_model_settings =
+            (settings.provider == EditPredictionProvider::Local).then(|| settings.local.clone());
+        if local_model_settings == self.local_model_settings {
+            return;
+        }
+
+        self.edit_prediction_model = match &local_model_settings {
+            Some(local_model_settings) => Box::new(LocalPredictionModel::new(
+                local_model_settings.clone(),
+                self.client.http_client(),
+            )),
+            None => Box::new(DummyPredictionModel {}),
+        };
+        self.local_model_settings = local_model_settings;
+    }
 }
 
 #[derive(Error, Debug)]
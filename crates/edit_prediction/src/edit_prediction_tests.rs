@@ -2170,6 +2170,113 @@ async fn test_unauthenticated_without_custom_url_blocks_prediction_impl(cx: &mut
     );
 }
 
+#[gpui::test]
+async fn test_local_model(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |settings| {
+                let all_languages = &mut settings.project.all_languages;
+                all_languages
+                    .features
+                    .get_or_insert_default()
+                    .edit_prediction_provider = Some(EditPredictionProvider::Local);
+                all_languages.edit_predictions.get_or_insert_default().local =
+                    Some(settings::LocalEditPredictionSettingsContent {
+                        api_url: Some("http://localhost:1234/v1/".into()),
+                        model: Some("qwen2.5-coder".into()),
+                        max_tokens: None,
+                    });
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/project",
+        serde_json::json!({
+            "main.rs": "fn main() {\n    let x = 1;\n}\n"
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+
+    let (request_tx, mut requests) = mpsc::unbounded();
+    let http_client = FakeHttpClient::create(move |req| {
+        let request_tx = request_tx.clone();
+        async move {
+            assert_eq!(
+                req.uri().to_string(),
+                "http://localhost:1234/v1/completions"
+            );
+            let mut body = String::new();
+            req.into_body().read_to_string(&mut body).await?;
+            let request: RawCompletionRequest = serde_json::from_str(&body)?;
+
+            let (response_tx, response_rx) = oneshot::channel();
+            request_tx.unbounded_send((request, response_tx)).unwrap();
+            let response: RawCompletionResponse = response_rx.await?;
+            Ok(Response::builder()
+                .body(serde_json::to_string(&response)?.into())
+                .unwrap())
+        }
+    });
+    let client =
+        cx.update(|cx| client::Client::new(Arc::new(FakeSystemClock::new()), http_client, cx));
+    let ep_store = cx.new(|cx| EditPredictionStore::new(client, project.read(cx).user_store(), cx));
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            let path = project
+                .find_project_path(path!("/project/main.rs"), cx)
+                .unwrap();
+            project.open_buffer(path, cx)
+        })
+        .await
+        .unwrap();
+    let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(1, 14)));
+    ep_store.update(cx, |ep_store, cx| {
+        ep_store.register_buffer(&buffer, &project, cx)
+    });
+    cx.background_executor.run_until_parked();
+
+    let prediction_task = ep_store.update(cx, |ep_store, cx| {
+        ep_store.request_prediction(&project, &buffer, cursor, cx)
+    });
+    let (request, response_tx) = requests.next().await.unwrap();
+    assert_eq!(request.model, "qwen2.5-coder");
+    assert_eq!(request.max_tokens, Some(256));
+    let prompt = prompt_from_request(&request);
+    assert!(
+        prompt.contains("    let x = 1;<|user_cursor|>\n}"),
+        "{prompt}"
+    );
+    assert!(prompt.ends_with("<|fim_middle|>updated\n"), "{prompt}");
+
+    response_tx
+        .send(model_response(
+            request,
+            indoc! {r"
+                --- a/main.rs
+                +++ b/main.rs
+                @@ ... @@
+                 fn main() {
+                -    let x = 1;
+                +    let x = 2;
+                 }
+            "},
+        ))
+        .unwrap();
+    let prediction = prediction_task.await.unwrap().unwrap().prediction.unwrap();
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit(prediction.edits.iter().cloned(), None, EditType::Other, cx)
+    });
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "fn main() {\n    let x = 2;\n}\n"
+    );
+}
+
 #[gpui::test]
 fn test_compute_diff_between_snapshots(cx: &mut TestAppContext) {
     let buffer = cx.new(|cx| {
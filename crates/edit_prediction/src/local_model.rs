@@ -0,0 +1,290 @@
+use std::{ops::Range, sync::Arc, time::Instant};
+
+use anyhow::{Context as _, Result};
+use cloud_llm_client::predict_edits_v3::RawCompletionRequest;
+use futures::AsyncReadExt as _;
+use gpui::{
+    App, Task,
+    http_client::{self, AsyncBody, HttpClient, Method},
+};
+use language::{
+    OffsetRangeExt as _, ToOffset as _, ToPoint as _,
+    language_settings::LocalEditPredictionSettings,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use zeta_prompt::{CURSOR_MARKER, ZetaPromptInput};
+
+use crate::{
+    CurrentEditPrediction, EditPredictionModel, EditPredictionModelInput, EditPredictionStore,
+    buffer_path_with_id_fallback,
+    cursor_excerpt::editable_and_context_ranges_for_cursor_position,
+    prediction::{EditPredictionId, EditPredictionResult},
+    udiff::{self, DiffLine},
+};
+
+const DEFAULT_API_URL: &str = "http://localhost:8080/v1";
+const DEFAULT_MAX_TOKENS: u32 = 256;
+const MAX_EDITABLE_TOKENS: usize = 150;
+const MAX_CONTEXT_TOKENS: usize = 350;
+const STOP_SEQUENCES: [&str; 2] = ["<|file_sep|>", "<|fim_middle|>"];
+
+/// Requests predictions from an OpenAI-compatible completion server running on the user's
+/// network, such as llama.cpp, LM Studio or Ollama.
+pub struct LocalPredictionModel {
+    settings: LocalEditPredictionSettings,
+    http_client: Arc<dyn HttpClient>,
+}
+
+#[derive(Deserialize)]
+struct CompletionResponse {
+    choices: Vec<CompletionChoice>,
+}
+
+#[derive(Deserialize)]
+struct CompletionChoice {
+    text: String,
+}
+
+impl LocalPredictionModel {
+    pub fn new(settings: LocalEditPredictionSettings, http_client: Arc<dyn HttpClient>) -> Self {
+        Self {
+            settings,
+            http_client,
+        }
+    }
+
+    fn completions_url(&self) -> String {
+        let api_url = self.settings.api_url.as_deref().unwrap_or(DEFAULT_API_URL);
+        format!("{}/completions", api_url.trim_end_matches('/'))
+    }
+}
+
+impl EditPredictionModel for LocalPredictionModel {
+    fn request_prediction(
+        self: &Self,
+        _store: &EditPredictionStore,
+        inputs: EditPredictionModelInput,
+        cx: &mut App,
+    ) -> Task<Result<Option<EditPredictionResult>>> {
+        let EditPredictionModelInput {
+            buffer,
+            snapshot,
+            position,
+            events,
+            related_files,
+            ..
+        } = inputs;
+        let buffer_snapshotted_at = Instant::now();
+
+        let (editable_range, context_range) = editable_and_context_ranges_for_cursor_position(
+            position.to_point(&snapshot),
+            &snapshot,
+            MAX_EDITABLE_TOKENS,
+            MAX_CONTEXT_TOKENS,
+        );
+        let editable_range = editable_range.to_offset(&snapshot);
+        let context_range = context_range.to_offset(&snapshot);
+        let prompt_input = ZetaPromptInput {
+            cursor_path: buffer_path_with_id_fallback(snapshot.file(), &snapshot, cx),
+            cursor_excerpt: snapshot
+                .text_for_range(context_range.clone())
+                .collect::<String>()
+                .into(),
+            editable_range_in_excerpt: editable_range.start - context_range.start
+                ..editable_range.end - context_range.start,
+            cursor_offset_in_excerpt: position.to_offset(&snapshot) - context_range.start,
+            events,
+            related_files,
+        };
+
+        let request = RawCompletionRequest {
+            model: self.settings.model.clone().unwrap_or_default(),
+            prompt: zeta_prompt::format_zeta_prompt(&prompt_input),
+            max_tokens: Some(self.settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)),
+            temperature: Some(0.),
+            stop: STOP_SEQUENCES.iter().map(|stop| (*stop).into()).collect(),
+        };
+        let url = self.completions_url();
+        let http_client = self.http_client.clone();
+        #[cfg(feature = "cli-support")]
+        let eval_cache = _store.eval_cache.clone();
+
+        cx.spawn(async move |cx| {
+            let request_body = serde_json::to_string(&request)?;
+            #[cfg(feature = "cli-support")]
+            let cache_key = {
+                use std::hash::{DefaultHasher, Hash as _, Hasher as _};
+                let mut hasher = DefaultHasher::new();
+                url.hash(&mut hasher);
+                request_body.hash(&mut hasher);
+                (crate::EvalCacheEntryKind::Prediction, hasher.finish())
+            };
+            #[cfg(feature = "cli-support")]
+            let cached_response = eval_cache.as_ref().and_then(|cache| cache.read(cache_key));
+            #[cfg(not(feature = "cli-support"))]
+            let cached_response = None;
+
+            let response_body = match cached_response {
+                Some(response_body) => response_body,
+                None => {
+                    let response_body =
+                        send_completion_request(http_client.as_ref(), &url, request_body.clone())
+                            .await?;
+                    #[cfg(feature = "cli-support")]
+                    if let Some(eval_cache) = &eval_cache {
+                        eval_cache.write(cache_key, &request_body, &response_body);
+                    }
+                    response_body
+                }
+            };
+            let response: CompletionResponse = serde_json::from_str(&response_body)?;
+            let response_received_at = Instant::now();
+            let completion = response
+                .choices
+                .into_iter()
+                .next()
+                .context("completion response contained no choices")?;
+
+            let editable_text =
+                &prompt_input.cursor_excerpt[prompt_input.editable_range_in_excerpt.clone()];
+            let edits = edits_for_completion(editable_text, &completion.text)?
+                .into_iter()
+                .map(|(range, text)| {
+                    (
+                        snapshot.anchor_after(editable_range.start + range.start)
+                            ..snapshot.anchor_before(editable_range.start + range.end),
+                        text,
+                    )
+                })
+                .collect();
+
+            Ok(Some(
+                EditPredictionResult::new(
+                    EditPredictionId(Uuid::new_v4().to_string().into()),
+                    &buffer,
+                    &snapshot,
+                    edits,
+                    buffer_snapshotted_at,
+                    response_received_at,
+                    prompt_input,
+                    cx,
+                )
+                .await,
+            ))
+        })
+    }
+
+    fn edit_prediction_accepted(
+        self: &Self,
+        _: &EditPredictionStore,
+        _: CurrentEditPrediction,
+        _: &App,
+    ) {
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+async fn send_completion_request(
+    http_client: &dyn HttpClient,
+    url: &str,
+    request_body: String,
+) -> Result<String> {
+    let request = http_client::Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header("Content-Type", "application/json")
+        .body(AsyncBody::from(request_body))?;
+    let mut response = http_client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "Request to {url} failed with status: {:?}\nBody: {body}",
+        response.status(),
+    );
+    Ok(body)
+}
+
+/// Returns the edits a completion makes to the editable region. The prompt asks for the
+/// rewritten region, but models may also answer with a diff against it.
+fn edits_for_completion(
+    editable_text: &str,
+    completion: &str,
+) -> Result<Vec<(Range<usize>, Arc<str>)>> {
+    let completion = completion.replace(CURSOR_MARKER, "");
+    if completion
+        .lines()
+        .any(|line| matches!(DiffLine::parse(line), DiffLine::HunkHeader(_)))
+    {
+        return Ok(udiff::edits_for_diff(editable_text, &completion)?
+            .into_iter()
+            .map(|(range, text)| (range, text.into()))
+            .collect());
+    }
+
+    // Whether the region ends in a newline depends on where it was cut, so ignore it.
+    let old_text = editable_text.strip_suffix('\n').unwrap_or(editable_text);
+    let new_text = completion.strip_suffix('\n').unwrap_or(&completion);
+    Ok(language::text_diff(old_text, new_text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn apply_edits(text: &str, edits: Vec<(Range<usize>, Arc<str>)>) -> String {
+        let mut text = text.to_string();
+        for (range, new_text) in edits.into_iter().rev() {
+            text.replace_range(range, &new_text);
+        }
+        text
+    }
+
+    #[test]
+    fn test_edits_for_rewritten_region() {
+        let editable_text = "fn main() {\n    let x = 1;\n}\n";
+
+        let edits = edits_for_completion(
+            editable_text,
+            "fn main() {\n    let x = 1;\n    println!(\"{x}\");<|user_cursor|>\n}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            apply_edits(editable_text, edits),
+            "fn main() {\n    let x = 1;\n    println!(\"{x}\");\n}\n"
+        );
+
+        // A missing trailing newline isn't a deletion.
+        let edits = edits_for_completion(editable_text, "fn main() {\n    let x = 1;\n}").unwrap();
+        assert_eq!(edits, Vec::new());
+    }
+
+    #[test]
+    fn test_edits_for_diff() {
+        let editable_text = "fn main() {\n    let x = 1;\n}\n";
+        let edits = edits_for_completion(
+            editable_text,
+            indoc! {"
+                --- a/main.rs
+                +++ b/main.rs
+                @@ ... @@
+                 fn main() {
+                -    let x = 1;
+                +    let x = 2;
+                 }
+            "},
+        )
+        .unwrap();
+        assert_eq!(
+            apply_edits(editable_text, edits),
+            "fn main() {\n    let x = 2;\n}\n"
+        );
+    }
+}
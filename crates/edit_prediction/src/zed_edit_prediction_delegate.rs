@@ -114,9 +114,11 @@ impl EditPredictionDelegate for ZedEditPredictionDelegate {
     ) {
         let store = self.store.read(cx);
 
-        if store.user_store.read_with(cx, |user_store, _cx| {
-            user_store.account_too_young() || user_store.has_overdue_invoices()
-        }) {
+        if !store.edit_prediction_model.is_local()
+            && store.user_store.read_with(cx, |user_store, _cx| {
+                user_store.account_too_young() || user_store.has_overdue_invoices()
+            })
+        {
             return;
         }
 
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use edit_prediction::{EvalCache, EvalCacheKey};
+
+/// Caches model responses on disk, so that re-running a set of examples doesn't query the
+/// model again unless its request changed.
+pub struct FsEvalCache {
+    dir: PathBuf,
+}
+
+impl FsEvalCache {
+    pub fn new(dir: PathBuf) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, (kind, hash): EvalCacheKey) -> PathBuf {
+        self.dir.join(format!("{kind}-{hash:x}.json"))
+    }
+}
+
+impl EvalCache for FsEvalCache {
+    fn read(&self, key: EvalCacheKey) -> Option<String> {
+        std::fs::read_to_string(self.path(key)).ok()
+    }
+
+    fn write(&self, key: EvalCacheKey, input: &str, value: &str) {
+        let path = self.path(key);
+        let input_path = path.with_extension("input.json");
+        if let Err(error) =
+            std::fs::write(&input_path, input).and_then(|_| std::fs::write(&path, value))
+        {
+            log::error!("failed to write {}: {error}", path.display());
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use edit_prediction::example_spec::ExampleSpec;
+use futures::AsyncWriteExt as _;
+use smol::process::Stdio;
+use util::command::new_smol_command;
+
+/// Returns the name a repository is mirrored under, e.g. `zed-industries/zed` for
+/// `git@github.com:zed-industries/zed.git`.
+pub fn repository_name(repository_url: &str) -> Result<String> {
+    let mut components = repository_url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':'])
+        .filter(|component| !component.is_empty() && *component != "..");
+    let name = components
+        .next()
+        .with_context(|| format!("invalid repository url {repository_url:?}"))?;
+    // Hosts aren't part of the name, so `https://example.com/zed` is mirrored as `zed`.
+    Ok(match components.next() {
+        Some(owner) if !owner.contains(['.', '@']) => format!("{owner}/{name}"),
+        _ => name.to_string(),
+    })
+}
+
+/// Checks out the example's revision from its local mirror into a worktree, and applies the
+/// changes that weren't committed when it was captured. Returns the path of the worktree.
+pub async fn prepare_worktree(
+    spec: &ExampleSpec,
+    repos_dir: &Path,
+    worktrees_dir: &Path,
+) -> Result<PathBuf> {
+    let repository_name = repository_name(&spec.repository_url)?;
+    let mirror_path = repos_dir.join(&repository_name);
+    anyhow::ensure!(
+        mirror_path.exists(),
+        "no mirror of {} at {}",
+        spec.repository_url,
+        mirror_path.display()
+    );
+
+    let worktree_path = worktrees_dir.join(&repository_name);
+    if worktree_path.exists() {
+        run_git(
+            &worktree_path,
+            &["checkout", "--detach", "--force", &spec.revision],
+        )
+        .await?;
+        run_git(&worktree_path, &["clean", "--force", "-d"]).await?;
+    } else {
+        std::fs::create_dir_all(worktrees_dir)?;
+        let worktree_path = worktree_path
+            .to_str()
+            .context("worktree path is not valid UTF-8")?;
+        run_git(
+            &mirror_path,
+            &["worktree", "add", "--detach", worktree_path, &spec.revision],
+        )
+        .await?;
+    }
+
+    if !spec.uncommitted_diff.is_empty() {
+        let mut child = new_smol_command("git")
+            .current_dir(&worktree_path)
+            .args(["apply", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().context("failed to open stdin")?;
+        stdin.write_all(spec.uncommitted_diff.as_bytes()).await?;
+        drop(stdin);
+        let output = child.output().await?;
+        anyhow::ensure!(
+            output.status.success(),
+            "failed to apply uncommitted diff: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(worktree_path)
+}
+
+async fn run_git(repo_path: &Path, args: &[&str]) -> Result<String> {
+    let output = new_smol_command("git")
+        .current_dir(repo_path)
+        .args(args)
+        .output()
+        .await?;
+    anyhow::ensure!(
+        output.status.success(),
+        "`git {}` in {} failed: {}",
+        args.join(" "),
+        repo_path.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_repository_name() {
+        for (url, name) in [
+            (
+                "https://github.com/zed-industries/zed.git",
+                "zed-industries/zed",
+            ),
+            (
+                "https://github.com/zed-industries/zed",
+                "zed-industries/zed",
+            ),
+            (
+                "git@github.com:zed-industries/zed.git",
+                "zed-industries/zed",
+            ),
+            (
+                "ssh://git@github.com/zed-industries/zed.git",
+                "zed-industries/zed",
+            ),
+            ("https://example.com/zed.git", "zed"),
+            ("/srv/git/zed", "git/zed"),
+        ] {
+            assert_eq!(repository_name(url).unwrap(), name, "{url}");
+        }
+    }
+}
@@ -0,0 +1,276 @@
+mod cache;
+mod git;
+mod predict;
+mod report;
+mod score;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use clap::{Parser, ValueEnum};
+use client::{Client, UserStore};
+use edit_prediction::{
+    EditPredictionModel, EvalCache, LocalPredictionModel, example_spec::ExampleSpec,
+};
+use gpui::{AppContext as _, Application, AsyncApp};
+use language::{LanguageRegistry, language_settings::LocalEditPredictionSettings};
+use node_runtime::NodeRuntime;
+use project::RealFs;
+use reqwest_client::ReqwestClient;
+use settings::SettingsStore;
+
+use crate::{
+    cache::FsEvalCache, predict::AppState, report::ExampleResult, score::score_prediction,
+};
+
+/// Runs edit prediction examples against a model and scores its predictions.
+#[derive(Parser)]
+struct Args {
+    /// Example files, or directories containing them.
+    #[arg(required = true)]
+    examples: Vec<PathBuf>,
+    /// Directory containing a git mirror of each example's repository, at `<owner>/<repo>`
+    /// or `<repo>`.
+    #[arg(long)]
+    repos: PathBuf,
+    /// Directory in which to check out examples.
+    #[arg(long)]
+    worktrees: Option<PathBuf>,
+    /// Where to request predictions from.
+    #[arg(long, value_enum, default_value_t = Provider::Local)]
+    provider: Provider,
+    /// Base URL of the OpenAI-compatible completion server to request predictions from.
+    #[arg(long)]
+    api_url: Option<String>,
+    /// Model to request predictions from.
+    #[arg(long)]
+    model: Option<String>,
+    /// Maximum number of tokens to predict.
+    #[arg(long)]
+    max_tokens: Option<u32>,
+    /// Directory in which to cache model responses, so unchanged requests aren't repeated.
+    #[arg(long)]
+    cache: Option<PathBuf>,
+    /// Where to write the report. An `.html` extension writes HTML, anything else writes
+    /// markdown. Defaults to printing markdown to stdout.
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Provider {
+    /// An OpenAI-compatible completion server, configured with `--api-url`, `--model` and
+    /// `--max-tokens`.
+    Local,
+}
+
+impl Provider {
+    fn prediction_model(
+        self,
+        args: &Args,
+        http_client: Arc<ReqwestClient>,
+    ) -> Arc<dyn Fn() -> Box<dyn EditPredictionModel>> {
+        match self {
+            Provider::Local => {
+                let settings = LocalEditPredictionSettings {
+                    api_url: args.api_url.clone(),
+                    model: args.model.clone(),
+                    max_tokens: args.max_tokens,
+                };
+                Arc::new(move || {
+                    Box::new(LocalPredictionModel::new(
+                        settings.clone(),
+                        http_client.clone(),
+                    )) as Box<dyn EditPredictionModel>
+                })
+            }
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    env_logger::init();
+    let args = Args::parse();
+    let examples = load_examples(&args.examples)?;
+    let eval_cache = args
+        .cache
+        .clone()
+        .map(|dir| anyhow::Ok(Arc::new(FsEvalCache::new(dir)?) as Arc<dyn EvalCache>))
+        .transpose()?;
+    let http_client = Arc::new(ReqwestClient::user_agent("Zed edit prediction CLI")?);
+    let prediction_model = args.provider.prediction_model(&args, http_client.clone());
+
+    Application::headless()
+        .with_http_client(http_client.clone())
+        .run(move |cx| {
+            release_channel::init(semver::Version::new(0, 0, 0), cx);
+            settings::init(cx);
+            // Predictions don't use language servers, and starting them for every example
+            // would be slow.
+            let settings = serde_json::json!({ "enable_language_server": false });
+            SettingsStore::update_global(cx, |store, cx| {
+                store.set_user_settings(&settings.to_string(), cx).unwrap();
+            });
+
+            let client = Client::production(cx);
+            let user_store = cx.new(|cx| UserStore::new(client.clone(), cx));
+            let (_, node_settings_rx) = watch::channel(None);
+            let node_runtime = NodeRuntime::new(http_client, None, node_settings_rx);
+            let languages = Arc::new(LanguageRegistry::new(cx.background_executor().clone()));
+            let fs = Arc::new(RealFs::new(None, cx.background_executor().clone()));
+            languages::init(languages.clone(), fs.clone(), node_runtime.clone(), cx);
+            let app_state = AppState {
+                client,
+                user_store,
+                languages,
+                fs,
+                node_runtime,
+                prediction_model,
+            };
+
+            cx.spawn(async move |cx| {
+                if let Err(error) = run(&args, &examples, &app_state, eval_cache, cx).await {
+                    eprintln!("{error:#}");
+                    // Quitting terminates the process successfully on macOS, so exit directly.
+                    std::process::exit(1);
+                }
+                cx.update(|cx| cx.quit());
+            })
+            .detach();
+        });
+    Ok(())
+}
+
+async fn run(
+    args: &Args,
+    examples: &[ExampleSpec],
+    app_state: &AppState,
+    eval_cache: Option<Arc<dyn EvalCache>>,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let worktrees_dir = args
+        .worktrees
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("ep-worktrees"));
+    let mut results = Vec::new();
+    for example in examples {
+        eprintln!("Running {}", example.name);
+        let result = run_example(
+            example,
+            &args.repos,
+            &worktrees_dir,
+            app_state,
+            eval_cache.clone(),
+            cx,
+        )
+        .await;
+        if let Some(error) = &result.error {
+            eprintln!("{} failed: {error}", example.name);
+        }
+        results.push(result);
+    }
+
+    let report = match &args.report {
+        Some(path)
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "html") =>
+        {
+            Some((path, report::html_report(&results)))
+        }
+        Some(path) => Some((path, report::markdown_report(&results))),
+        None => {
+            println!("{}", report::markdown_report(&results));
+            None
+        }
+    };
+    if let Some((path, report)) = report {
+        std::fs::write(path, report)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        eprintln!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+async fn run_example(
+    example: &ExampleSpec,
+    repos_dir: &Path,
+    worktrees_dir: &Path,
+    app_state: &AppState,
+    eval_cache: Option<Arc<dyn EvalCache>>,
+    cx: &mut AsyncApp,
+) -> ExampleResult {
+    let mut result = ExampleResult {
+        name: example.name.clone(),
+        score: None,
+        actual_patch: None,
+        expected_patch: example
+            .expected_patches
+            .first()
+            .cloned()
+            .unwrap_or_default(),
+        error: None,
+    };
+
+    let outcome = async {
+        let worktree_path = git::prepare_worktree(example, repos_dir, worktrees_dir).await?;
+        let outcome =
+            predict::run_example(example, &worktree_path, app_state, eval_cache, cx).await?;
+        let score = outcome
+            .predicted_text
+            .as_ref()
+            .map(|predicted_text| {
+                score_prediction(
+                    &outcome.original_text,
+                    predicted_text,
+                    &example.expected_patches,
+                )
+            })
+            .transpose()?;
+        anyhow::Ok((outcome, score))
+    }
+    .await;
+
+    match outcome {
+        Ok((outcome, score)) => {
+            if let Some(score) = &score {
+                result.expected_patch = example.expected_patches[score.expected_patch_ix].clone();
+            }
+            result.actual_patch = outcome.predicted_text.map(|predicted_text| {
+                language::unified_diff(&outcome.original_text, &predicted_text)
+            });
+            result.score = score;
+        }
+        Err(error) => result.error = Some(format!("{error:#}")),
+    }
+    result
+}
+
+fn load_examples(paths: &[PathBuf]) -> Result<Vec<ExampleSpec>> {
+    let mut example_paths = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut dir_paths = std::fs::read_dir(path)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>>>()?;
+            dir_paths.retain(|path| path.extension().is_some_and(|extension| extension == "md"));
+            dir_paths.sort();
+            example_paths.extend(dir_paths);
+        } else {
+            example_paths.push(path.clone());
+        }
+    }
+
+    example_paths
+        .iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            ExampleSpec::from_markdown(&content)
+                .with_context(|| format!("failed to parse {}", path.display()))
+        })
+        .collect()
+}
@@ -0,0 +1,148 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use client::{Client, UserStore};
+use cloud_llm_client::EditPredictionRejectReason;
+use collections::HashSet;
+use edit_prediction::{
+    EditPredictionModel, EditPredictionStore, EvalCache,
+    example_spec::ExampleSpec,
+    udiff::{self, DiffLine},
+};
+use fs::Fs;
+use gpui::{AppContext as _, AsyncApp, Entity};
+use language::{LanguageBuffer, LanguageRegistry, OffsetRangeExt as _};
+use node_runtime::NodeRuntime;
+use project::Project;
+
+pub struct AppState {
+    pub client: Arc<Client>,
+    pub user_store: Entity<UserStore>,
+    pub languages: Arc<LanguageRegistry>,
+    pub fs: Arc<dyn Fs>,
+    pub node_runtime: NodeRuntime,
+    /// Creates the model that predictions are requested from, once for each example.
+    pub prediction_model: Arc<dyn Fn() -> Box<dyn EditPredictionModel>>,
+}
+
+/// The cursor's file before and after applying the prediction.
+pub struct PredictionOutcome {
+    pub original_text: String,
+    /// `None` if the model didn't predict any edits.
+    pub predicted_text: Option<String>,
+}
+
+/// Replays an example's edit history in a project opened at `worktree_path`, and requests a
+/// prediction at its cursor position.
+pub async fn run_example(
+    spec: &ExampleSpec,
+    worktree_path: &Path,
+    app_state: &AppState,
+    eval_cache: Option<Arc<dyn EvalCache>>,
+    cx: &mut AsyncApp,
+) -> Result<PredictionOutcome> {
+    let project = cx.update(|cx| {
+        Project::local(
+            app_state.client.clone(),
+            app_state.node_runtime.clone(),
+            app_state.user_store.clone(),
+            app_state.languages.clone(),
+            app_state.fs.clone(),
+            Some(Default::default()),
+            false,
+            cx,
+        )
+    });
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(worktree_path, true, cx)
+        })
+        .await?;
+    let scan_complete = worktree.read_with(cx, |worktree, _| {
+        worktree.as_local().map(|worktree| worktree.scan_complete())
+    });
+    if let Some(scan_complete) = scan_complete {
+        scan_complete.await;
+    }
+
+    let store = cx.new(|cx| {
+        let mut store =
+            EditPredictionStore::new(app_state.client.clone(), app_state.user_store.clone(), cx);
+        store.set_edit_prediction_model((app_state.prediction_model)());
+        if let Some(eval_cache) = eval_cache {
+            store.with_eval_cache(eval_cache);
+        }
+        store.register_project(&project, cx);
+        store
+    });
+
+    // Buffers have to be registered before they're edited, for the store to record the
+    // edits as events.
+    let mut edited_paths = HashSet::default();
+    for line in spec.edit_history.lines() {
+        if let DiffLine::OldPath { path } = DiffLine::parse(line)
+            && path != "/dev/null"
+            && edited_paths.insert(path.to_string())
+        {
+            let buffer = open_buffer(&project, worktree_path, Path::new(path.as_ref()), cx)
+                .await
+                .with_context(|| format!("failed to open {path} from the edit history"))?;
+            store.update(cx, |store, cx| store.register_buffer(&buffer, &project, cx));
+        }
+    }
+    udiff::apply_diff(&spec.edit_history, &project, cx)
+        .await
+        .context("failed to apply the edit history")?;
+
+    let buffer = open_buffer(&project, worktree_path, &spec.cursor_path, cx).await?;
+    store.update(cx, |store, cx| store.register_buffer(&buffer, &project, cx));
+    let original_text = buffer.read_with(cx, |buffer, _| buffer.text());
+    let (excerpt, cursor_offset_in_excerpt) = spec.cursor_excerpt()?;
+    let excerpt_offset = original_text.find(&excerpt).with_context(|| {
+        format!(
+            "cursor position excerpt not found in {}",
+            spec.cursor_path.display()
+        )
+    })?;
+    let position = buffer.read_with(cx, |buffer, _| {
+        buffer.anchor_before(excerpt_offset + cursor_offset_in_excerpt)
+    });
+
+    let result = store
+        .update(cx, |store, cx| {
+            store.request_prediction(&project, &buffer, position, cx)
+        })
+        .await?;
+    let prediction = match result.map(|result| result.prediction) {
+        Some(Ok(prediction)) => prediction,
+        None | Some(Err(EditPredictionRejectReason::Empty)) => {
+            return Ok(PredictionOutcome {
+                original_text,
+                predicted_text: None,
+            });
+        }
+        Some(Err(reason)) => anyhow::bail!("the prediction was rejected: {reason:?}"),
+    };
+
+    let mut predicted_text = original_text.clone();
+    for (range, new_text) in prediction.edits.iter().rev() {
+        predicted_text.replace_range(range.to_offset(&prediction.snapshot), new_text);
+    }
+    Ok(PredictionOutcome {
+        original_text,
+        predicted_text: Some(predicted_text),
+    })
+}
+
+async fn open_buffer(
+    project: &Entity<Project>,
+    worktree_path: &Path,
+    path: &Path,
+    cx: &mut AsyncApp,
+) -> Result<Entity<LanguageBuffer>> {
+    project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(worktree_path.join(path), cx)
+        })
+        .await
+}
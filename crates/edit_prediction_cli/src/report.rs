@@ -0,0 +1,246 @@
+use std::fmt::Write as _;
+
+use crate::score::Score;
+
+/// The outcome of running a single example.
+pub struct ExampleResult {
+    pub name: String,
+    /// `None` if the example failed or the model didn't predict any edits.
+    pub score: Option<Score>,
+    /// The predicted changes to the cursor's file, as a unified diff.
+    pub actual_patch: Option<String>,
+    /// The expected patch the prediction was scored against.
+    pub expected_patch: String,
+    pub error: Option<String>,
+}
+
+struct Summary {
+    examples: usize,
+    exact_matches: usize,
+    mean_line_f1: f64,
+    no_prediction: usize,
+    errors: usize,
+}
+
+impl Summary {
+    fn new(results: &[ExampleResult]) -> Self {
+        let examples = results.len();
+        let exact_matches = results
+            .iter()
+            .filter(|result| result.score.as_ref().is_some_and(|score| score.exact_match))
+            .count();
+        let total_line_f1: f64 = results
+            .iter()
+            .filter_map(|result| Some(result.score.as_ref()?.line_f1))
+            .sum();
+        let errors = results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .count();
+        let no_prediction = results
+            .iter()
+            .filter(|result| result.error.is_none() && result.actual_patch.is_none())
+            .count();
+        Self {
+            examples,
+            exact_matches,
+            // Examples without a prediction count as a score of zero.
+            mean_line_f1: if examples == 0 {
+                0.
+            } else {
+                total_line_f1 / examples as f64
+            },
+            no_prediction,
+            errors,
+        }
+    }
+}
+
+fn status(result: &ExampleResult) -> String {
+    match (&result.error, &result.score) {
+        (Some(_), _) => "error".to_string(),
+        (None, None) => "no prediction".to_string(),
+        (None, Some(score)) if score.exact_match => "exact match".to_string(),
+        (None, Some(score)) => format!("{:.2}", score.line_f1),
+    }
+}
+
+pub fn markdown_report(results: &[ExampleResult]) -> String {
+    let summary = Summary::new(results);
+    let mut output = String::new();
+    writeln!(output, "# Edit Prediction Evaluation\n").ok();
+    writeln!(
+        output,
+        "| Examples | Exact matches | Mean line F1 | No prediction | Errors |"
+    )
+    .ok();
+    writeln!(output, "| --- | --- | --- | --- | --- |").ok();
+    writeln!(
+        output,
+        "| {} | {} | {:.3} | {} | {} |\n",
+        summary.examples,
+        summary.exact_matches,
+        summary.mean_line_f1,
+        summary.no_prediction,
+        summary.errors
+    )
+    .ok();
+
+    writeln!(output, "| Example | Result |").ok();
+    writeln!(output, "| --- | --- |").ok();
+    for result in results {
+        writeln!(
+            output,
+            "| {} | {} |",
+            result.name.replace('|', "\\|"),
+            status(result)
+        )
+        .ok();
+    }
+
+    for result in results {
+        if result.score.as_ref().is_some_and(|score| score.exact_match) {
+            continue;
+        }
+        writeln!(output, "\n## {}\n", result.name).ok();
+        if let Some(error) = &result.error {
+            writeln!(output, "```\n{error}\n```").ok();
+            continue;
+        }
+        writeln!(
+            output,
+            "### Expected\n\n```diff\n{}```\n",
+            result.expected_patch
+        )
+        .ok();
+        match &result.actual_patch {
+            Some(actual_patch) => writeln!(output, "### Actual\n\n```diff\n{actual_patch}```").ok(),
+            None => writeln!(output, "### Actual\n\nNo prediction.").ok(),
+        };
+    }
+    output
+}
+
+pub fn html_report(results: &[ExampleResult]) -> String {
+    let summary = Summary::new(results);
+    let mut output = String::new();
+    output.push_str(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>Edit Prediction Evaluation</title>\n<style>\n",
+        "body { font-family: sans-serif; margin: 2em; }\n",
+        "table { border-collapse: collapse; }\n",
+        "td, th { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }\n",
+        "pre { background: #f6f6f6; padding: 8px; overflow-x: auto; }\n",
+        ".exact { color: #1a7f37; } .error { color: #cf222e; }\n",
+        "</style>\n</head>\n<body>\n<h1>Edit Prediction Evaluation</h1>\n",
+    ));
+    writeln!(
+        output,
+        "<table>\n<tr><th>Examples</th><th>Exact matches</th><th>Mean line F1</th><th>No prediction</th><th>Errors</th></tr>\n<tr><td>{}</td><td>{}</td><td>{:.3}</td><td>{}</td><td>{}</td></tr>\n</table>",
+        summary.examples,
+        summary.exact_matches,
+        summary.mean_line_f1,
+        summary.no_prediction,
+        summary.errors
+    )
+    .ok();
+
+    for result in results {
+        let class = match (&result.error, &result.score) {
+            (Some(_), _) => "error",
+            (None, Some(score)) if score.exact_match => "exact",
+            _ => "",
+        };
+        writeln!(
+            output,
+            "<details>\n<summary>{} &mdash; <span class=\"{class}\">{}</span></summary>",
+            escape_html(&result.name),
+            status(result)
+        )
+        .ok();
+        if let Some(error) = &result.error {
+            writeln!(output, "<pre>{}</pre>", escape_html(error)).ok();
+        } else {
+            writeln!(
+                output,
+                "<h3>Expected</h3>\n<pre>{}</pre>\n<h3>Actual</h3>\n<pre>{}</pre>",
+                escape_html(&result.expected_patch),
+                escape_html(result.actual_patch.as_deref().unwrap_or("No prediction."))
+            )
+            .ok();
+        }
+        output.push_str("</details>\n");
+    }
+    output.push_str("</body>\n</html>\n");
+    output
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn results() -> Vec<ExampleResult> {
+        vec![
+            ExampleResult {
+                name: "exact".into(),
+                score: Some(Score {
+                    exact_match: true,
+                    line_f1: 1.,
+                    expected_patch_ix: 0,
+                }),
+                actual_patch: Some("@@ -1 +1 @@\n-a\n+b\n".into()),
+                expected_patch: "@@ -1 +1 @@\n-a\n+b\n".into(),
+                error: None,
+            },
+            ExampleResult {
+                name: "missing".into(),
+                score: None,
+                actual_patch: None,
+                expected_patch: "@@ -1 +1 @@\n-a\n+<b>\n".into(),
+                error: None,
+            },
+            ExampleResult {
+                name: "failed".into(),
+                score: None,
+                actual_patch: None,
+                expected_patch: String::new(),
+                error: Some("no mirror".into()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_summary() {
+        let markdown = markdown_report(&results());
+        assert!(markdown.contains("| 3 | 1 | 0.333 | 1 | 1 |"), "{markdown}");
+        assert!(markdown.contains("| missing | no prediction |"));
+        assert!(!markdown.contains("## exact"));
+
+        let html = html_report(&results());
+        assert!(html.contains("+&lt;b&gt;"));
+        assert!(!html.contains("+<b>"));
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("if a < b && c > \"d\""),
+            "if a &lt; b &amp;&amp; c &gt; &quot;d&quot;"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use collections::HashMap;
+use edit_prediction::udiff::apply_diff_to_string;
+
+/// How close a prediction came to one of an example's expected patches.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    /// Whether the predicted text matches the text produced by an expected patch.
+    pub exact_match: bool,
+    /// The F1 score of the lines the prediction added and removed, against those of the
+    /// closest expected patch.
+    pub line_f1: f64,
+    /// The index of the expected patch the prediction was compared against.
+    pub expected_patch_ix: usize,
+}
+
+/// Scores the text of the cursor's file after applying a prediction against the patches
+/// an example expects, which are applied to the same original text.
+pub fn score_prediction(
+    original_text: &str,
+    predicted_text: &str,
+    expected_patches: &[String],
+) -> Result<Score> {
+    anyhow::ensure!(
+        !expected_patches.is_empty(),
+        "example has no expected patches"
+    );
+
+    let predicted_changes = changed_lines(original_text, predicted_text);
+    let mut best: Option<Score> = None;
+    for (expected_patch_ix, expected_patch) in expected_patches.iter().enumerate() {
+        let expected_text = apply_diff_to_string(expected_patch, original_text)?;
+        let score = Score {
+            exact_match: expected_text == predicted_text,
+            line_f1: f1(
+                &predicted_changes,
+                &changed_lines(original_text, &expected_text),
+            ),
+            expected_patch_ix,
+        };
+        if best.as_ref().is_none_or(|best| {
+            (score.exact_match, score.line_f1) > (best.exact_match, best.line_f1)
+        }) {
+            best = Some(score);
+        }
+    }
+    Ok(best.unwrap())
+}
+
+/// Returns the added and removed lines of the diff between two texts, prefixed with
+/// `+` or `-`.
+fn changed_lines(old_text: &str, new_text: &str) -> Vec<String> {
+    language::unified_diff(old_text, new_text)
+        .lines()
+        .filter(|line| line.starts_with('+') || line.starts_with('-'))
+        .map(|line| line.trim_end().to_string())
+        .collect()
+}
+
+fn f1(predicted: &[String], expected: &[String]) -> f64 {
+    if predicted.is_empty() && expected.is_empty() {
+        return 1.;
+    }
+
+    let mut expected_counts = HashMap::<&str, usize>::default();
+    for line in expected {
+        *expected_counts.entry(line).or_default() += 1;
+    }
+    let mut true_positives = 0;
+    for line in predicted {
+        if let Some(count) = expected_counts.get_mut(line.as_str())
+            && *count > 0
+        {
+            *count -= 1;
+            true_positives += 1;
+        }
+    }
+    if true_positives == 0 {
+        return 0.;
+    }
+
+    let precision = true_positives as f64 / predicted.len() as f64;
+    let recall = true_positives as f64 / expected.len() as f64;
+    2. * precision * recall / (precision + recall)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const ORIGINAL: &str = indoc! {"
+        fn main() {
+            let x = 1;
+            let y = 2;
+        }
+    "};
+
+    const EXPECTED_PATCH: &str = indoc! {"
+        --- a/src/main.rs
+        +++ b/src/main.rs
+        @@ -1,4 +1,5 @@
+         fn main() {
+             let x = 1;
+             let y = 2;
+        +    println!(\"{x} {y}\");
+         }
+    "};
+
+    #[test]
+    fn test_exact_match() {
+        let predicted = indoc! {"
+            fn main() {
+                let x = 1;
+                let y = 2;
+                println!(\"{x} {y}\");
+            }
+        "};
+        let score = score_prediction(ORIGINAL, predicted, &[EXPECTED_PATCH.to_string()]).unwrap();
+        assert_eq!(
+            score,
+            Score {
+                exact_match: true,
+                line_f1: 1.,
+                expected_patch_ix: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_partial_match() {
+        let predicted = indoc! {"
+            fn main() {
+                let x = 1;
+                let y = 2;
+                println!(\"{x} {y}\");
+                println!(\"done\");
+            }
+        "};
+        let score = score_prediction(ORIGINAL, predicted, &[EXPECTED_PATCH.to_string()]).unwrap();
+        assert!(!score.exact_match);
+        assert!((score.line_f1 - 2. / 3.).abs() < 1e-9, "{}", score.line_f1);
+
+        let score = score_prediction(ORIGINAL, ORIGINAL, &[EXPECTED_PATCH.to_string()]).unwrap();
+        assert!(!score.exact_match);
+        assert_eq!(score.line_f1, 0.);
+    }
+
+    #[test]
+    fn test_best_of_several_patches() {
+        let other_patch = indoc! {"
+            --- a/src/main.rs
+            +++ b/src/main.rs
+            @@ -1,4 +1,4 @@
+             fn main() {
+                 let x = 1;
+            -    let y = 2;
+            +    let y = 3;
+             }
+        "};
+        let predicted = ORIGINAL.replace("2", "3");
+        let score = score_prediction(
+            ORIGINAL,
+            &predicted,
+            &[EXPECTED_PATCH.to_string(), other_patch.to_string()],
+        )
+        .unwrap();
+        assert!(score.exact_match);
+        assert_eq!(score.expected_patch_ix, 1);
+    }
+}
@@ -71,14 +71,18 @@ impl Render for EditPredictionButton {
         let all_language_settings = all_language_settings(None, cx);
 
         match all_language_settings.edit_predictions.provider {
-            EditPredictionProvider::Zed => {
+            provider @ (EditPredictionProvider::Zed | EditPredictionProvider::Local) => {
                 let enabled = self.editor_enabled.unwrap_or(true);
                 let ep_icon = if enabled {
                     IconName::ZedPredict
                 } else {
                     IconName::ZedPredictDisabled
                 };
-                let tooltip_meta = "Powered by Zeta";
+                let tooltip_meta = if provider == EditPredictionProvider::Local {
+                    "Powered by a Local Model"
+                } else {
+                    "Powered by Zeta"
+                };
                 let mut over_limit = false;
 
                 if let Some(usage) = self
@@ -133,11 +137,7 @@ impl Render for EditPredictionButton {
                 let mut popover_menu = PopoverMenu::new("edit-prediction")
                     .menu(move |window, cx| {
                         this.update(cx, |this, cx| {
-                            this.build_edit_prediction_context_menu(
-                                EditPredictionProvider::Zed,
-                                window,
-                                cx,
-                            )
+                            this.build_edit_prediction_context_menu(provider, window, cx)
                         })
                         .ok()
                     })
@@ -205,6 +205,7 @@ impl EditPredictionButton {
     fn get_available_providers(&self) -> Vec<EditPredictionProvider> {
         let mut providers = Vec::new();
         providers.push(EditPredictionProvider::Zed);
+        providers.push(EditPredictionProvider::Local);
         providers
     }
 
@@ -229,6 +230,7 @@ impl EditPredictionButton {
 
                 let name = match provider {
                     EditPredictionProvider::Zed => "Zed AI",
+                    EditPredictionProvider::Local => "Local Model",
                     EditPredictionProvider::None => {
                         continue;
                     }
@@ -801,6 +801,9 @@ actions!(
         Backtab,
         /// Toggles a breakpoint at the current line.
         ToggleBreakpoint,
+        /// Starts recording the operations applied to the current buffer, or stops recording and
+        /// saves them to a file that can be replayed to reproduce the buffer's state.
+        ToggleBufferOperationRecording,
         /// Toggles the case of selected text.
         ToggleCase,
         /// Disables the breakpoint at the current line.
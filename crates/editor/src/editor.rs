@@ -21269,6 +21269,83 @@ impl Editor {
         }
     }
 
+    pub fn toggle_buffer_operation_recording(
+        &mut self,
+        _: &ToggleBufferOperationRecording,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        struct BufferOperationRecording;
+
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let operation_log = buffer.update(cx, |buffer, cx| {
+            if buffer.is_recording_operations() {
+                buffer.stop_recording_operations()
+            } else {
+                buffer.start_recording_operations(cx);
+                None
+            }
+        });
+        let Some(operation_log) = operation_log else {
+            workspace.update(cx, |workspace, cx| {
+                workspace.show_toast(
+                    Toast::new(
+                        NotificationId::unique::<BufferOperationRecording>(),
+                        "Recording buffer operations. Run the action again to save them.",
+                    )
+                    .autohide(),
+                    cx,
+                )
+            });
+            return;
+        };
+
+        let fs = workspace.read(cx).app_state().fs.clone();
+        let buffer = buffer.read(cx);
+        let directory = buffer
+            .file()
+            .and_then(|file| file.as_local())
+            .and_then(|file| file.abs_path(cx).parent().map(Path::to_path_buf))
+            .unwrap_or_else(std::env::temp_dir);
+        let file_name = buffer
+            .file()
+            .map(|file| file.file_name(cx).to_string())
+            .unwrap_or_else(|| "untitled".to_string());
+        let save_path = cx.prompt_for_new_path(&directory, Some(&format!("{file_name}.oplog")));
+        cx.spawn_in(window, async move |_, cx| {
+            let Some(path) = save_path.await?? else {
+                return Ok(());
+            };
+            let bytes = cx
+                .background_spawn(async move { operation_log.to_bytes() })
+                .await;
+            fs.write(&path, &bytes)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            workspace.update(cx, |workspace, cx| {
+                workspace.show_toast(
+                    Toast::new(
+                        NotificationId::unique::<BufferOperationRecording>(),
+                        format!("Saved the buffer operations to {}", path.display()),
+                    )
+                    .autohide(),
+                    cx,
+                )
+            })
+        })
+        .detach_and_prompt_err(
+            "Failed to save the buffer operations",
+            window,
+            cx,
+            |error, _, _| Some(format!("{error:#}")),
+        );
+    }
+
     pub fn open_permalink_to_line(
         &mut self,
         _: &OpenPermalinkToLine,
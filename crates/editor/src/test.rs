@@ -134,6 +134,18 @@ pub(crate) fn build_editor(
     Editor::new(EditorMode::full(), buffer, None, window, cx)
 }
 
+/// Replays a recording saved by [`crate::actions::ToggleBufferOperationRecording`] into a new
+/// editor, so that the state it captured can be reproduced in a test.
+pub fn editor_for_operation_log(
+    bytes: &[u8],
+    window: &mut Window,
+    cx: &mut App,
+) -> anyhow::Result<Entity<Editor>> {
+    let buffer = language::OperationLog::from_bytes(bytes)?.replay(cx)?;
+    let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+    Ok(cx.new(|cx| build_editor(buffer, window, cx)))
+}
+
 pub(crate) fn build_editor_with_project(
     project: Entity<Project>,
     buffer: Entity<MultiBuffer>,
@@ -122,6 +122,11 @@ impl GitRepository for FakeGitRepository {
             .boxed()
     }
 
+    fn load_committed_bytes(&self, path: RepoPath) -> BoxFuture<'_, Option<Vec<u8>>> {
+        let content = self.load_committed_text(path);
+        async move { Some(content.await?.into_bytes()) }.boxed()
+    }
+
     fn load_blob_content(&self, oid: git::Oid) -> BoxFuture<'_, Result<String>> {
         self.with_state_async(false, move |state| {
             state.oids.get(&oid).cloned().context("oid does not exist")
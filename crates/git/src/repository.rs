@@ -432,6 +432,11 @@ pub trait GitRepository: Send + Sync {
     ///
     /// Also returns `None` for symlinks.
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>>;
+
+    /// Returns the raw contents of an entry in the repository's HEAD, for files that may not be text.
+    ///
+    /// Also returns `None` for symlinks.
+    fn load_committed_bytes(&self, path: RepoPath) -> BoxFuture<'_, Option<Vec<u8>>>;
     fn load_blob_content(&self, oid: Oid) -> BoxFuture<'_, Result<String>>;
 
     fn set_index_text(
@@ -1064,6 +1069,11 @@ impl GitRepository for RealGitRepository {
     }
 
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>> {
+        let content = self.load_committed_bytes(path);
+        async move { String::from_utf8(content.await?).ok() }.boxed()
+    }
+
+    fn load_committed_bytes(&self, path: RepoPath) -> BoxFuture<'_, Option<Vec<u8>>> {
         let repo = self.repository.clone();
         self.executor
             .spawn(async move {
@@ -1073,8 +1083,7 @@ impl GitRepository for RealGitRepository {
                 if entry.filemode() == i32::from(git2::FileMode::Link) {
                     return None;
                 }
-                let content = repo.find_blob(entry.id()).log_err()?.content().to_owned();
-                String::from_utf8(content).ok()
+                Some(repo.find_blob(entry.id()).log_err()?.content().to_owned())
             })
             .boxed()
     }
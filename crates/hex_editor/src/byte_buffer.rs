@@ -0,0 +1,159 @@
+use std::{ops::Range, sync::Arc};
+
+/// The bytes of a file open in a hex editor, with the history of edits made to them.
+pub struct ByteBuffer {
+    /// Shared with background tasks, such as searches and saves, and copied if it's edited
+    /// while they run.
+    bytes: Arc<Vec<u8>>,
+    undo_stack: Vec<ByteEdit>,
+    redo_stack: Vec<ByteEdit>,
+    next_edit_id: usize,
+}
+
+/// A replacement of a range of bytes, kept so that it can be undone.
+struct ByteEdit {
+    id: usize,
+    offset: usize,
+    old_bytes: Vec<u8>,
+    new_bytes: Vec<u8>,
+}
+
+impl ByteBuffer {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes: Arc::new(bytes),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            next_edit_id: 1,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn byte(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(offset).copied()
+    }
+
+    pub fn snapshot(&self) -> Arc<Vec<u8>> {
+        self.bytes.clone()
+    }
+
+    /// Identifies the current contents, so that they can be compared with the contents at an
+    /// earlier time, such as when the file was last saved.
+    pub fn version(&self) -> usize {
+        self.undo_stack.last().map_or(0, |edit| edit.id)
+    }
+
+    /// Replaces the contents, such as after the file was reloaded, forgetting the edit history.
+    pub fn set_bytes(&mut self, bytes: Vec<u8>) {
+        self.bytes = Arc::new(bytes);
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.next_edit_id += 1;
+    }
+
+    /// Replaces a range of bytes. When `merge` is true and the range lies within the bytes written
+    /// by the last edit, both are undone together, as when typing the two digits of a byte.
+    pub fn edit(&mut self, range: Range<usize>, new_bytes: &[u8], merge: bool) {
+        let range = range.start.min(self.len())..range.end.min(self.len());
+        let id = self.next_edit_id;
+        self.next_edit_id += 1;
+        self.redo_stack.clear();
+
+        if merge
+            && let Some(last_edit) = self.undo_stack.last_mut()
+            && range.start >= last_edit.offset
+            && range.end <= last_edit.offset + last_edit.new_bytes.len()
+        {
+            let relative_range = range.start - last_edit.offset..range.end - last_edit.offset;
+            last_edit
+                .new_bytes
+                .splice(relative_range, new_bytes.iter().copied());
+            last_edit.id = id;
+        } else {
+            self.undo_stack.push(ByteEdit {
+                id,
+                offset: range.start,
+                old_bytes: self.bytes[range.clone()].to_vec(),
+                new_bytes: new_bytes.to_vec(),
+            });
+        }
+        Arc::make_mut(&mut self.bytes).splice(range, new_bytes.iter().copied());
+    }
+
+    /// Reverts the last edit, returning the range of bytes it restored.
+    pub fn undo(&mut self) -> Option<Range<usize>> {
+        let edit = self.undo_stack.pop()?;
+        let range = edit.offset..edit.offset + edit.new_bytes.len();
+        Arc::make_mut(&mut self.bytes).splice(range, edit.old_bytes.iter().copied());
+        let restored = edit.offset..edit.offset + edit.old_bytes.len();
+        self.redo_stack.push(edit);
+        Some(restored)
+    }
+
+    /// Reapplies the last undone edit, returning the range of bytes it wrote.
+    pub fn redo(&mut self) -> Option<Range<usize>> {
+        let edit = self.redo_stack.pop()?;
+        let range = edit.offset..edit.offset + edit.old_bytes.len();
+        Arc::make_mut(&mut self.bytes).splice(range, edit.new_bytes.iter().copied());
+        let written = edit.offset..edit.offset + edit.new_bytes.len();
+        self.undo_stack.push(edit);
+        Some(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_undo_redo() {
+        let mut buffer = ByteBuffer::new(vec![0, 1, 2, 3]);
+        let original_version = buffer.version();
+
+        buffer.edit(1..2, &[0xAA], false);
+        buffer.edit(4..4, &[0xBB, 0xCC], false);
+        buffer.edit(0..1, &[], false);
+        assert_eq!(buffer.bytes(), &[0xAA, 2, 3, 0xBB, 0xCC]);
+
+        assert_eq!(buffer.undo(), Some(0..1));
+        assert_eq!(buffer.undo(), Some(4..4));
+        assert_eq!(buffer.bytes(), &[0, 0xAA, 2, 3]);
+        assert_eq!(buffer.redo(), Some(4..6));
+        assert_eq!(buffer.bytes(), &[0, 0xAA, 2, 3, 0xBB, 0xCC]);
+
+        // A new edit discards the undone ones.
+        buffer.edit(2..3, &[0xDD], false);
+        assert_eq!(buffer.redo(), None);
+
+        while buffer.undo().is_some() {}
+        assert_eq!(buffer.bytes(), &[0, 1, 2, 3]);
+        assert_eq!(buffer.version(), original_version);
+    }
+
+    #[test]
+    fn test_merged_edits() {
+        let mut buffer = ByteBuffer::new(vec![0x12, 0x34]);
+
+        // Typing "AB" over the first byte, one digit at a time.
+        buffer.edit(0..1, &[0xA2], false);
+        let version = buffer.version();
+        buffer.edit(0..1, &[0xAB], true);
+        assert_ne!(buffer.version(), version);
+        assert_eq!(buffer.bytes(), &[0xAB, 0x34]);
+
+        // Edits outside the last one aren't merged.
+        buffer.edit(1..2, &[0xC4], true);
+        assert_eq!(buffer.bytes(), &[0xAB, 0xC4]);
+
+        assert_eq!(buffer.undo(), Some(1..2));
+        assert_eq!(buffer.undo(), Some(0..1));
+        assert_eq!(buffer.bytes(), &[0x12, 0x34]);
+    }
+}
@@ -0,0 +1,179 @@
+use std::ops::Range;
+
+/// A sequence of bytes to search for, parsed from a search query.
+///
+/// Queries made of hex bytes, such as `DE AD ?? EF`, match those bytes, with `??` matching any
+/// byte. Other queries, or queries in double quotes, match their text as UTF-8.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BytePattern {
+    bytes: Vec<PatternByte>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PatternByte {
+    Any,
+    Exact(u8),
+    /// An ASCII letter, matched in either case.
+    Letter(u8),
+}
+
+impl PatternByte {
+    fn matches(self, byte: u8) -> bool {
+        match self {
+            PatternByte::Any => true,
+            PatternByte::Exact(expected) => byte == expected,
+            PatternByte::Letter(expected) => byte.eq_ignore_ascii_case(&expected),
+        }
+    }
+}
+
+impl BytePattern {
+    pub fn parse(query: &str, case_sensitive: bool) -> Option<Self> {
+        let bytes = if let Some(text) = query
+            .strip_prefix('"')
+            .and_then(|query| query.strip_suffix('"'))
+        {
+            Self::text_bytes(text, case_sensitive)
+        } else if let Some(bytes) = Self::hex_bytes(query) {
+            bytes
+        } else {
+            Self::text_bytes(query, case_sensitive)
+        };
+        (!bytes.is_empty()).then_some(Self { bytes })
+    }
+
+    fn hex_bytes(query: &str) -> Option<Vec<PatternByte>> {
+        let digits = query
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<Vec<_>>();
+        if digits.len() % 2 != 0 {
+            return None;
+        }
+        digits
+            .chunks(2)
+            .map(|pair| match pair {
+                ['?', '?'] => Some(PatternByte::Any),
+                [high, low] => Some(PatternByte::Exact(
+                    (high.to_digit(16)? * 16 + low.to_digit(16)?) as u8,
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn text_bytes(text: &str, case_sensitive: bool) -> Vec<PatternByte> {
+        text.bytes()
+            .map(|byte| {
+                if !case_sensitive && byte.is_ascii_alphabetic() {
+                    PatternByte::Letter(byte)
+                } else {
+                    PatternByte::Exact(byte)
+                }
+            })
+            .collect()
+    }
+
+    /// Finds non-overlapping occurrences of the pattern, stopping after `max_matches`. Returns
+    /// whether the search stopped early.
+    pub fn find(&self, haystack: &[u8], max_matches: usize) -> (Vec<Range<usize>>, bool) {
+        let mut matches = Vec::new();
+        let len = self.bytes.len();
+        let mut start = 0;
+        while start + len <= haystack.len() {
+            let is_match = self
+                .bytes
+                .iter()
+                .zip(&haystack[start..start + len])
+                .all(|(pattern_byte, byte)| pattern_byte.matches(*byte));
+            if is_match {
+                if matches.len() == max_matches {
+                    return (matches, true);
+                }
+                matches.push(start..start + len);
+                start += len;
+            } else {
+                start += 1;
+            }
+        }
+        (matches, false)
+    }
+}
+
+/// Parses bytes written as hex digits, ignoring whitespace, such as `DE AD BE EF`.
+pub fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16))
+        .collect::<Option<Vec<_>>>()?;
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] * 16 + pair[1]) as u8)
+            .collect(),
+    )
+}
+
+/// Parses bytes to write, such as the replacement for search matches, the same way as search
+/// queries are parsed: as hex bytes, or otherwise as text.
+pub fn parse_bytes(text: &str) -> Vec<u8> {
+    if let Some(text) = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+    {
+        return text.as_bytes().to_vec();
+    }
+    parse_hex(text).unwrap_or_else(|| text.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_patterns() {
+        let haystack = [0xDE, 0xAD, 0xBE, 0xEF, 0xDE, 0xAD, 0x00, 0xEF];
+
+        let pattern = BytePattern::parse("de ad ?? ef", true).unwrap();
+        assert_eq!(pattern.find(&haystack, 10), (vec![0..4, 4..8], false));
+        assert_eq!(pattern.find(&haystack, 1), (vec![0..4], true));
+
+        let pattern = BytePattern::parse("ADBE", true).unwrap();
+        assert_eq!(pattern.find(&haystack, 10), (vec![1..3], false));
+    }
+
+    #[test]
+    fn test_text_patterns() {
+        let haystack = b"Hello, hello!";
+
+        // Not hex digits, so matched as text.
+        let pattern = BytePattern::parse("hello", true).unwrap();
+        assert_eq!(pattern.find(haystack, 10), (vec![7..12], false));
+        let pattern = BytePattern::parse("hello", false).unwrap();
+        assert_eq!(pattern.find(haystack, 10), (vec![0..5, 7..12], false));
+
+        // Quotes force text matching for queries that look like hex.
+        let pattern = BytePattern::parse("\"ab\"", true).unwrap();
+        assert_eq!(pattern.find(b"\xab ab", 10), (vec![2..4], false));
+
+        assert_eq!(BytePattern::parse("", true), None);
+        assert_eq!(BytePattern::parse("\"\"", true), None);
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(
+            parse_hex("de ad\nBE EF"),
+            Some(vec![0xDE, 0xAD, 0xBE, 0xEF])
+        );
+        assert_eq!(parse_hex("ABC"), None);
+        assert_eq!(parse_hex(""), None);
+        assert_eq!(parse_bytes("00 ff"), vec![0x00, 0xFF]);
+        assert_eq!(parse_bytes("\"ff\""), b"ff".to_vec());
+        assert_eq!(parse_bytes("zip"), b"zip".to_vec());
+    }
+}
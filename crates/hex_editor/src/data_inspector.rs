@@ -0,0 +1,114 @@
+/// The byte order that multi-byte values are read in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn label(self) -> &'static str {
+        match self {
+            Endianness::Little => "Little Endian",
+            Endianness::Big => "Big Endian",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        }
+    }
+}
+
+/// The ways the bytes at the cursor are interpreted, with their values. A value is `None` when
+/// there aren't enough bytes left in the file for its type.
+pub fn inspect(bytes: &[u8], endianness: Endianness) -> Vec<(&'static str, Option<String>)> {
+    macro_rules! read {
+        ($type:ty) => {
+            bytes
+                .get(..size_of::<$type>())
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(|bytes| match endianness {
+                    Endianness::Little => <$type>::from_le_bytes(bytes),
+                    Endianness::Big => <$type>::from_be_bytes(bytes),
+                })
+        };
+    }
+
+    vec![
+        ("binary", read!(u8).map(|value| format!("{value:08b}"))),
+        ("u8", read!(u8).map(|value| value.to_string())),
+        ("i8", read!(i8).map(|value| value.to_string())),
+        ("u16", read!(u16).map(|value| value.to_string())),
+        ("i16", read!(i16).map(|value| value.to_string())),
+        ("u32", read!(u32).map(|value| value.to_string())),
+        ("i32", read!(i32).map(|value| value.to_string())),
+        ("u64", read!(u64).map(|value| value.to_string())),
+        ("i64", read!(i64).map(|value| value.to_string())),
+        ("f32", read!(f32).map(|value| value.to_string())),
+        ("f64", read!(f64).map(|value| value.to_string())),
+        (
+            "UTF-8",
+            bytes.first().map(|_| {
+                utf8_char(bytes).map_or_else(|| "invalid".to_string(), |c| format!("{c:?}"))
+            }),
+        ),
+    ]
+}
+
+/// The character that the bytes start with, if they start with valid UTF-8.
+fn utf8_char(bytes: &[u8]) -> Option<char> {
+    let len = bytes.len().min(4);
+    let valid_len = match std::str::from_utf8(&bytes[..len]) {
+        Ok(_) => len,
+        Err(error) => error.valid_up_to(),
+    };
+    std::str::from_utf8(&bytes[..valid_len])
+        .ok()?
+        .chars()
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(values: &[(&str, Option<String>)], name: &str) -> Option<String> {
+        values
+            .iter()
+            .find(|(value_name, _)| *value_name == name)
+            .and_then(|(_, value)| value.clone())
+    }
+
+    #[test]
+    fn test_inspect() {
+        let bytes = [0xFF, 0x01, 0x00, 0x00];
+
+        let values = inspect(&bytes, Endianness::Little);
+        assert_eq!(value(&values, "binary").as_deref(), Some("11111111"));
+        assert_eq!(value(&values, "u8").as_deref(), Some("255"));
+        assert_eq!(value(&values, "i8").as_deref(), Some("-1"));
+        assert_eq!(value(&values, "u16").as_deref(), Some("511"));
+        assert_eq!(value(&values, "u32").as_deref(), Some("511"));
+        assert_eq!(value(&values, "u64"), None);
+        assert_eq!(value(&values, "UTF-8").as_deref(), Some("invalid"));
+
+        let values = inspect(&bytes, Endianness::Big);
+        assert_eq!(value(&values, "u16").as_deref(), Some("65281"));
+        assert_eq!(value(&values, "i32").as_deref(), Some("-16711680"));
+
+        let values = inspect(&1.5f32.to_be_bytes(), Endianness::Big);
+        assert_eq!(value(&values, "f32").as_deref(), Some("1.5"));
+        assert_eq!(value(&values, "f64"), None);
+    }
+
+    #[test]
+    fn test_utf8_char() {
+        assert_eq!(utf8_char("é!".as_bytes()), Some('é'));
+        assert_eq!(utf8_char(&[b'a', 0xFF]), Some('a'));
+        assert_eq!(utf8_char(&[0xC3]), None);
+        assert_eq!(utf8_char(&[]), None);
+    }
+}
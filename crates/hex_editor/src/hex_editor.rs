@@ -0,0 +1,1274 @@
+mod byte_buffer;
+mod byte_pattern;
+mod data_inspector;
+mod offset_modal;
+
+use std::{
+    cmp::Reverse,
+    ops::Range,
+    path::PathBuf,
+    sync::{Arc, LazyLock},
+};
+
+use editor::{
+    EditorSettings,
+    actions::{
+        Backspace, Copy, Cut, Delete, MoveDown, MoveLeft, MovePageDown, MovePageUp, MoveRight,
+        MoveToBeginning, MoveToEnd, MoveUp, Paste, Redo, SelectAll, SelectDown, SelectLeft,
+        SelectRight, SelectUp, ToggleGoToLine, Undo,
+    },
+};
+use file_icons::FileIcons;
+use fs::{Fs, MTime};
+use gpui::{
+    AnyElement, App, ClipboardItem, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, KeyDownEvent, MouseButton, MouseDownEvent, MouseMoveEvent,
+    ParentElement, Render, ScrollStrategy, Stateful, Task, UniformListScrollHandle, Window,
+    actions, uniform_list,
+};
+use project::{Project, ProjectPath, search::SearchQuery};
+use settings::Settings;
+use theme::{Theme, ThemeSettings};
+use ui::{Tooltip, prelude::*};
+use util::{paths::PathExt, size::format_file_size};
+use workspace::{
+    Toast, ToolbarItemLocation, Workspace, WorkspaceId,
+    invalid_item_view::InvalidItemView,
+    item::{BreadcrumbText, Item, ItemEvent, SaveOptions, TabContentParams},
+    notifications::{DetachAndPromptErr as _, NotificationId},
+    searchable::{Direction, SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
+};
+use worktree::Worktree;
+use zed_actions::hex_editor::Open;
+
+use crate::{
+    byte_buffer::ByteBuffer,
+    byte_pattern::{BytePattern, parse_bytes, parse_hex},
+    data_inspector::{Endianness, inspect},
+    offset_modal::OffsetModal,
+};
+
+actions!(
+    hex_editor,
+    [
+        /// Moves the cursor between the hex and text columns.
+        SwitchColumn,
+        /// Switches between inserting bytes and overwriting them while typing.
+        ToggleInsertMode,
+        /// Switches the byte order that the data inspector reads values in.
+        ToggleEndianness,
+    ]
+);
+
+const BYTES_PER_ROW: usize = 16;
+
+/// The number of rows shown at once. The list is paged so that its pixel offsets stay within
+/// `f32` precision for large files.
+const PAGE_ROWS: usize = 100_000;
+
+/// The number of rows moved by page up and page down.
+const ROWS_PER_SCREEN: usize = 32;
+
+/// Searching stops after this many matches, since each one is kept in memory.
+const MAX_SEARCH_MATCHES: usize = 10_000;
+
+/// Selections up to this length are suggested as search queries.
+const MAX_QUERY_SUGGESTION_LEN: usize = 64;
+
+static HEX_BYTES: LazyLock<[SharedString; 256]> =
+    LazyLock::new(|| std::array::from_fn(|byte| SharedString::from(format!("{byte:02X}"))));
+
+/// A byte as two uppercase hex digits.
+pub fn hex_byte(byte: u8) -> SharedString {
+    HEX_BYTES[byte as usize].clone()
+}
+
+/// The character that a byte is shown as next to its hex digits: itself if it's visible ASCII,
+/// and a dot otherwise.
+pub fn printable_char(byte: u8) -> char {
+    if byte.is_ascii_graphic() {
+        char::from(byte)
+    } else {
+        '·'
+    }
+}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(HexEditor::open);
+    })
+    .detach();
+}
+
+/// The column of a hex editor that the cursor is in, which decides how typing is interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Text,
+}
+
+pub enum HexEditorEvent {
+    Edited,
+    Saved,
+    Reloaded,
+    /// The file changed on disk while it had unsaved edits, so it wasn't reloaded.
+    FileChanged,
+}
+
+/// An editor for the raw bytes of a local file, shown as rows of hex digits next to their text.
+///
+/// The whole file is loaded into memory, while only the visible rows are rendered. Edits are kept
+/// in memory with their undo history until the file is saved.
+pub struct HexEditor {
+    project: Entity<Project>,
+    fs: Arc<dyn Fs>,
+    abs_path: PathBuf,
+    project_path: Option<ProjectPath>,
+    worktree: Option<Entity<Worktree>>,
+    buffer: ByteBuffer,
+    saved_version: usize,
+    /// The modification time of the file when it was last loaded or saved.
+    saved_mtime: Option<MTime>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    /// The first row of the page being shown.
+    page_start: usize,
+    /// The offset of the cursor, which is before the byte it's on. It can be at the end of the
+    /// file, for appending bytes.
+    cursor: usize,
+    /// The other end of the selection, which is empty when this is the cursor.
+    selection_anchor: usize,
+    /// Whether the mouse is being dragged to select bytes.
+    selecting: bool,
+    column: Column,
+    insert_mode: bool,
+    /// Whether the first hex digit of the byte at the cursor has been typed, so that the next one
+    /// completes it.
+    typed_high_digit: bool,
+    endianness: Endianness,
+    search_matches: Vec<Range<usize>>,
+    active_match: Option<usize>,
+    search_truncated: bool,
+}
+
+impl HexEditor {
+    pub fn new(
+        project: Entity<Project>,
+        fs: Arc<dyn Fs>,
+        abs_path: PathBuf,
+        bytes: Vec<u8>,
+        mtime: Option<MTime>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let project_path = project.read(cx).find_project_path(&abs_path, cx);
+        let worktree = project_path.as_ref().and_then(|project_path| {
+            project
+                .read(cx)
+                .worktree_for_id(project_path.worktree_id, cx)
+        });
+        if let Some(worktree) = &worktree {
+            cx.subscribe(worktree, Self::on_worktree_event).detach();
+        }
+        let buffer = ByteBuffer::new(bytes);
+        Self {
+            project,
+            fs,
+            abs_path,
+            project_path,
+            worktree,
+            saved_version: buffer.version(),
+            saved_mtime: mtime,
+            buffer,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            page_start: 0,
+            cursor: 0,
+            selection_anchor: 0,
+            selecting: false,
+            column: Column::Hex,
+            insert_mode: false,
+            typed_high_digit: false,
+            endianness: Endianness::default(),
+            search_matches: Vec::new(),
+            active_match: None,
+            search_truncated: false,
+        }
+    }
+
+    /// Opens the file shown in the active item in a hex editor, replacing the item if it's shown
+    /// because the file couldn't be opened otherwise.
+    fn open(workspace: &mut Workspace, _: &Open, window: &mut Window, cx: &mut Context<Workspace>) {
+        let Some(active_item) = workspace.active_item(cx) else {
+            return;
+        };
+        let project = workspace.project().clone();
+        let invalid_item = active_item.downcast::<InvalidItemView>();
+        let abs_path = match &invalid_item {
+            Some(invalid_item) => Some(invalid_item.read(cx).abs_path.to_path_buf()),
+            None => active_item
+                .project_path(cx)
+                .and_then(|project_path| project.read(cx).absolute_path(&project_path, cx)),
+        };
+        let Some(abs_path) = abs_path else {
+            return;
+        };
+        if !project.read(cx).is_local() {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<Open>(),
+                    "Only local files can be opened in the hex editor",
+                ),
+                cx,
+            );
+            return;
+        }
+
+        let pane = workspace.active_pane().clone();
+        let existing_editor = pane
+            .read(cx)
+            .items_of_type::<HexEditor>()
+            .find(|hex_editor| hex_editor.read(cx).abs_path == abs_path);
+        if let Some(existing_editor) = existing_editor {
+            pane.update(cx, |pane, cx| {
+                if let Some(ix) = pane.index_for_item(&existing_editor) {
+                    pane.activate_item(ix, true, true, window, cx);
+                }
+            });
+            return;
+        }
+
+        let fs = workspace.app_state().fs.clone();
+        let replaced_item_id = invalid_item.map(|invalid_item| invalid_item.entity_id());
+        cx.spawn_in(window, async move |_, cx| {
+            let mtime = fs.metadata(&abs_path).await?.map(|metadata| metadata.mtime);
+            let bytes = fs.load_bytes(&abs_path).await?;
+            pane.update_in(cx, |pane, window, cx| {
+                let hex_editor =
+                    cx.new(|cx| HexEditor::new(project, fs, abs_path, bytes, mtime, cx));
+                pane.add_item(Box::new(hex_editor), true, true, None, window, cx);
+                if let Some(item_id) = replaced_item_id {
+                    pane.remove_item(item_id, false, false, window, cx);
+                }
+            })
+        })
+        .detach_and_prompt_err("Failed to open the hex editor", window, cx, |_, _, _| None);
+    }
+
+    fn on_worktree_event(
+        &mut self,
+        _: Entity<Worktree>,
+        event: &worktree::Event,
+        cx: &mut Context<Self>,
+    ) {
+        if let worktree::Event::UpdatedEntries(changes) = event
+            && let Some(project_path) = &self.project_path
+            && changes
+                .iter()
+                .any(|(path, _, _)| path == &project_path.path)
+        {
+            // Unsaved edits are kept, and saving them asks whether to overwrite the file.
+            if self.is_dirty() {
+                cx.emit(HexEditorEvent::FileChanged);
+                cx.notify();
+            } else {
+                self.reload_from_disk(false, cx).detach_and_log_err(cx);
+            }
+        }
+    }
+
+    /// The modification time of the file, as last seen by the worktree.
+    fn disk_mtime(&self, cx: &App) -> Option<MTime> {
+        let project_path = self.project_path.as_ref()?;
+        self.worktree
+            .as_ref()?
+            .read(cx)
+            .entry_for_path(&project_path.path)?
+            .mtime
+    }
+
+    /// Loads the file again. Unless `force` is true, the edit history is kept if the file hasn't
+    /// changed.
+    fn reload_from_disk(
+        &mut self,
+        force: bool,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        cx.spawn(async move |this, cx| {
+            let mtime = fs.metadata(&abs_path).await?.map(|metadata| metadata.mtime);
+            let bytes = fs.load_bytes(&abs_path).await?;
+            this.update(cx, |this, cx| {
+                this.saved_mtime = mtime;
+                if !force && bytes.as_slice() == this.buffer.bytes() {
+                    return;
+                }
+                this.buffer.set_bytes(bytes);
+                this.saved_version = this.buffer.version();
+                let len = this.buffer.len();
+                this.cursor = this.cursor.min(len);
+                this.selection_anchor = this.selection_anchor.min(len);
+                this.typed_high_digit = false;
+                this.invalidate_search(cx);
+                cx.emit(HexEditorEvent::Reloaded);
+                cx.notify();
+            })
+        })
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.buffer.version() != self.saved_version
+    }
+
+    fn selection(&self) -> Range<usize> {
+        self.cursor.min(self.selection_anchor)..self.cursor.max(self.selection_anchor)
+    }
+
+    fn row_count(&self) -> usize {
+        // The last row always has room for the cursor at the end of the file.
+        self.buffer.len() / BYTES_PER_ROW + 1
+    }
+
+    /// Moves the cursor, extending the selection if `select` is true, and scrolls it into view.
+    pub fn move_cursor_to(&mut self, offset: usize, select: bool, cx: &mut Context<Self>) {
+        self.cursor = offset.min(self.buffer.len());
+        if !select {
+            self.selection_anchor = self.cursor;
+        }
+        self.typed_high_digit = false;
+        let row = self.cursor / BYTES_PER_ROW;
+        self.page_start = row / PAGE_ROWS * PAGE_ROWS;
+        self.scroll_handle
+            .scroll_to_item(row - self.page_start, ScrollStrategy::Nearest);
+        cx.notify();
+    }
+
+    /// Moves the cursor to a byte that was clicked or dragged over, so that a selection includes
+    /// it either way.
+    fn move_cursor_to_byte(&mut self, offset: usize, select: bool, cx: &mut Context<Self>) {
+        if select && offset >= self.selection_anchor {
+            self.move_cursor_to(offset + 1, true, cx);
+        } else {
+            self.move_cursor_to(offset, select, cx);
+        }
+    }
+
+    fn show_page(&mut self, page_start: usize, cx: &mut Context<Self>) {
+        self.move_cursor_to(page_start * BYTES_PER_ROW, false, cx);
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(1), false, cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + 1, false, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(BYTES_PER_ROW), false, cx);
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + BYTES_PER_ROW, false, cx);
+    }
+
+    fn select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(1), true, cx);
+    }
+
+    fn select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + 1, true, cx);
+    }
+
+    fn select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(BYTES_PER_ROW), true, cx);
+    }
+
+    fn select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + BYTES_PER_ROW, true, cx);
+    }
+
+    fn move_page_up(&mut self, _: &MovePageUp, _: &mut Window, cx: &mut Context<Self>) {
+        let distance = ROWS_PER_SCREEN * BYTES_PER_ROW;
+        self.move_cursor_to(self.cursor.saturating_sub(distance), false, cx);
+    }
+
+    fn move_page_down(&mut self, _: &MovePageDown, _: &mut Window, cx: &mut Context<Self>) {
+        let distance = ROWS_PER_SCREEN * BYTES_PER_ROW;
+        self.move_cursor_to(self.cursor + distance, false, cx);
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(0, false, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(usize::MAX, false, cx);
+    }
+
+    fn select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.selection_anchor = 0;
+        self.move_cursor_to(usize::MAX, true, cx);
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, _: &mut Window, cx: &mut Context<Self>) {
+        self.column = match self.column {
+            Column::Hex => Column::Text,
+            Column::Text => Column::Hex,
+        };
+        self.typed_high_digit = false;
+        cx.notify();
+    }
+
+    fn toggle_insert_mode(&mut self, _: &ToggleInsertMode, _: &mut Window, cx: &mut Context<Self>) {
+        self.insert_mode = !self.insert_mode;
+        self.typed_high_digit = false;
+        cx.notify();
+    }
+
+    fn toggle_endianness(&mut self, _: &ToggleEndianness, _: &mut Window, cx: &mut Context<Self>) {
+        self.endianness = self.endianness.toggled();
+        cx.notify();
+    }
+
+    fn go_to_offset(&mut self, _: &ToggleGoToLine, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workspace) = window.root::<Workspace>().flatten() else {
+            return;
+        };
+        let hex_editor = cx.weak_entity();
+        let cursor = self.cursor;
+        let len = self.buffer.len();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(window, cx, |window, cx| {
+                OffsetModal::new(hex_editor, cursor, len, window, cx)
+            });
+        });
+    }
+
+    /// The selected bytes, or the byte at the cursor if nothing is selected.
+    fn selected_bytes(&self) -> &[u8] {
+        let selection = self.selection();
+        let range = if selection.is_empty() {
+            self.cursor..(self.cursor + 1).min(self.buffer.len())
+        } else {
+            selection
+        };
+        &self.buffer.bytes()[range]
+    }
+
+    /// The selected bytes as text to copy: hex digits from the hex column, and text from the text
+    /// column.
+    fn selected_text(&self) -> String {
+        let bytes = self.selected_bytes();
+        match self.column {
+            Column::Hex => bytes
+                .iter()
+                .map(|byte| hex_byte(*byte).to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+            Column::Text => String::from_utf8_lossy(bytes).into_owned(),
+        }
+    }
+
+    fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        cx.write_to_clipboard(ClipboardItem::new_string(self.selected_text()));
+    }
+
+    fn cut(&mut self, _: &Cut, _: &mut Window, cx: &mut Context<Self>) {
+        let selection = self.selection();
+        if selection.is_empty() {
+            return;
+        }
+        cx.write_to_clipboard(ClipboardItem::new_string(self.selected_text()));
+        self.edit(selection, &[], cx);
+    }
+
+    fn paste(&mut self, _: &Paste, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        let bytes = match self.column {
+            Column::Hex => parse_hex(&text).unwrap_or_else(|| text.into_bytes()),
+            Column::Text => text.into_bytes(),
+        };
+        self.write_at_cursor(&bytes, cx);
+    }
+
+    fn backspace(&mut self, _: &Backspace, _: &mut Window, cx: &mut Context<Self>) {
+        let selection = self.selection();
+        if !selection.is_empty() {
+            self.edit(selection, &[], cx);
+        } else if self.cursor > 0 {
+            self.edit(self.cursor - 1..self.cursor, &[], cx);
+        }
+    }
+
+    fn delete(&mut self, _: &Delete, _: &mut Window, cx: &mut Context<Self>) {
+        let selection = self.selection();
+        if !selection.is_empty() {
+            self.edit(selection, &[], cx);
+        } else if self.cursor < self.buffer.len() {
+            self.edit(self.cursor..self.cursor + 1, &[], cx);
+        }
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(range) = self.buffer.undo() {
+            self.edited(range, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(range) = self.buffer.redo() {
+            self.edited(range, cx);
+        }
+    }
+
+    /// Replaces a range of bytes, leaving the cursor after the new ones.
+    fn edit(&mut self, range: Range<usize>, new_bytes: &[u8], cx: &mut Context<Self>) {
+        let start = range.start;
+        self.buffer.edit(range, new_bytes, false);
+        self.edited(start + new_bytes.len()..start + new_bytes.len(), cx);
+    }
+
+    /// Writes bytes over the selection, or at the cursor, where they're inserted or overwrite the
+    /// bytes after it depending on the mode.
+    fn write_at_cursor(&mut self, bytes: &[u8], cx: &mut Context<Self>) {
+        let selection = self.selection();
+        let range = if !selection.is_empty() {
+            selection
+        } else if self.insert_mode {
+            self.cursor..self.cursor
+        } else {
+            self.cursor..self.cursor + bytes.len()
+        };
+        self.edit(range, bytes, cx);
+    }
+
+    /// Types one hex digit. The first digit typed at a byte sets its high half, and the second
+    /// one completes it and moves on to the next byte.
+    fn type_hex_digit(&mut self, digit: u8, cx: &mut Context<Self>) {
+        if self.typed_high_digit {
+            let byte = self.buffer.byte(self.cursor).unwrap_or(0) & 0xF0 | digit;
+            self.buffer
+                .edit(self.cursor..self.cursor + 1, &[byte], true);
+            let next = self.cursor + 1;
+            self.edited(next..next, cx);
+            return;
+        }
+
+        let selection = self.selection();
+        let (range, byte) = if !selection.is_empty() {
+            (selection, digit << 4)
+        } else if self.insert_mode || self.cursor == self.buffer.len() {
+            (self.cursor..self.cursor, digit << 4)
+        } else {
+            let byte = self.buffer.byte(self.cursor).unwrap_or(0) & 0x0F | digit << 4;
+            (self.cursor..self.cursor + 1, byte)
+        };
+        let start = range.start;
+        self.buffer.edit(range, &[byte], false);
+        self.edited(start..start, cx);
+        self.typed_high_digit = true;
+    }
+
+    /// Selects a range after the bytes changed, and forgets search matches, whose offsets may no
+    /// longer line up with the bytes.
+    fn edited(&mut self, selection: Range<usize>, cx: &mut Context<Self>) {
+        self.selection_anchor = selection.start;
+        self.move_cursor_to(selection.end, true, cx);
+        self.invalidate_search(cx);
+        cx.emit(HexEditorEvent::Edited);
+    }
+
+    fn invalidate_search(&mut self, cx: &mut Context<Self>) {
+        if !self.search_matches.is_empty() {
+            self.search_matches.clear();
+            self.active_match = None;
+            cx.emit(SearchEvent::MatchesInvalidated);
+        }
+    }
+
+    fn handle_key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let modifiers = &event.keystroke.modifiers;
+        if modifiers.control || modifiers.platform || modifiers.function {
+            return;
+        }
+        let Some(&[character]) = event.keystroke.key_char.as_deref().map(str::as_bytes) else {
+            return;
+        };
+        match self.column {
+            Column::Hex => {
+                let Some(digit) = char::from(character).to_digit(16) else {
+                    return;
+                };
+                self.type_hex_digit(digit as u8, cx);
+            }
+            Column::Text => {
+                if !character.is_ascii_graphic() && character != b' ' {
+                    return;
+                }
+                self.write_at_cursor(&[character], cx);
+            }
+        }
+        cx.stop_propagation();
+    }
+
+    fn handle_byte_mouse_down(
+        &mut self,
+        offset: usize,
+        column: Column,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle, cx);
+        self.column = column;
+        self.selecting = true;
+        self.move_cursor_to_byte(offset, event.modifiers.shift, cx);
+    }
+
+    fn handle_byte_mouse_move(
+        &mut self,
+        offset: usize,
+        event: &MouseMoveEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.selecting {
+            return;
+        }
+        if event.pressed_button != Some(MouseButton::Left) {
+            self.selecting = false;
+            return;
+        }
+        if offset != self.cursor {
+            self.move_cursor_to_byte(offset, true, cx);
+        }
+    }
+
+    /// Whether an offset is in a search match, and if so, whether it's the active one.
+    fn search_match_at(&self, offset: usize) -> Option<bool> {
+        let ix = self
+            .search_matches
+            .partition_point(|search_match| search_match.end <= offset);
+        self.search_matches
+            .get(ix)
+            .filter(|search_match| search_match.contains(&offset))
+            .map(|_| self.active_match == Some(ix))
+    }
+
+    fn render_rows(&mut self, range: Range<usize>, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let colors = cx.theme().colors();
+        let offset_digits = offset_digits(self.buffer.len());
+        range
+            .map(|ix| {
+                let row_start = (self.page_start + ix) * BYTES_PER_ROW;
+                let is_cursor_row = (row_start..row_start + BYTES_PER_ROW).contains(&self.cursor);
+                h_flex()
+                    .id(ix)
+                    .gap_3()
+                    .child(
+                        div()
+                            .text_color(if is_cursor_row {
+                                colors.editor_active_line_number
+                            } else {
+                                colors.editor_line_number
+                            })
+                            .child(format!("{row_start:0offset_digits$X}")),
+                    )
+                    .child(h_flex().children((0..BYTES_PER_ROW).map(|byte_ix| {
+                        self.render_byte(row_start + byte_ix, Column::Hex, cx)
+                            .when(byte_ix == BYTES_PER_ROW / 2, |this| this.ml_2())
+                            .into_any_element()
+                    })))
+                    .child(h_flex().children((0..BYTES_PER_ROW).map(|byte_ix| {
+                        self.render_byte(row_start + byte_ix, Column::Text, cx)
+                            .into_any_element()
+                    })))
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_byte(&self, offset: usize, column: Column, cx: &mut Context<Self>) -> Stateful<Div> {
+        let colors = cx.theme().colors();
+        let player = cx.theme().players().local();
+        let byte = self.buffer.byte(offset);
+        let text = match (column, byte) {
+            (Column::Hex, Some(byte)) => hex_byte(byte),
+            (Column::Text, Some(byte)) => printable_char(byte).to_string().into(),
+            (Column::Hex, None) => "  ".into(),
+            (Column::Text, None) => " ".into(),
+        };
+        let id = match column {
+            Column::Hex => ("hex-byte", offset),
+            Column::Text => ("text-byte", offset),
+        };
+        let is_cursor = offset == self.cursor && offset <= self.buffer.len();
+        let background = if is_cursor {
+            Some(if column == self.column {
+                player.cursor.opacity(0.5)
+            } else {
+                player.cursor.opacity(0.2)
+            })
+        } else if self.selection().contains(&offset) {
+            Some(player.selection)
+        } else {
+            match self.search_match_at(offset) {
+                Some(true) => Some(colors.search_active_match_background),
+                Some(false) => Some(colors.search_match_background),
+                None => None,
+            }
+        };
+
+        div()
+            .id(id)
+            .when(column == Column::Hex, |this| this.px_0p5())
+            .when(byte == Some(0), |this| this.text_color(colors.text_muted))
+            .when_some(background, |this, background| this.bg(background))
+            .child(text)
+            .when(offset <= self.buffer.len(), |this| {
+                let offset = offset.min(self.buffer.len());
+                this.on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, event, window, cx| {
+                        this.handle_byte_mouse_down(offset, column, event, window, cx)
+                    }),
+                )
+                .on_mouse_move(cx.listener(move |this, event, _, cx| {
+                    this.handle_byte_mouse_move(offset, event, cx)
+                }))
+            })
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let row_count = self.row_count();
+        let page_end = (self.page_start + PAGE_ROWS).min(row_count);
+        let has_previous_page = self.page_start > 0;
+        let has_next_page = page_end < row_count;
+        let page_start = self.page_start;
+        let selection = self.selection();
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(format!(
+                            "Offset 0x{:X} · {}",
+                            self.cursor,
+                            format_file_size(self.buffer.len() as u64, false)
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .when(!selection.is_empty(), |this| {
+                        this.child(
+                            Label::new(format!("{} bytes selected", selection.len()))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .when(self.search_truncated, |this| {
+                        this.child(
+                            Label::new(format!("Showing the first {MAX_SEARCH_MATCHES} matches"))
+                                .size(LabelSize::Small)
+                                .color(Color::Warning),
+                        )
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new(
+                            "toggle-insert-mode",
+                            if self.insert_mode {
+                                "Insert"
+                            } else {
+                                "Overwrite"
+                            },
+                        )
+                        .label_size(LabelSize::Small)
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Switch Between Inserting and Overwriting",
+                            &ToggleInsertMode,
+                            &self.focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.toggle_insert_mode(&ToggleInsertMode, window, cx)
+                        })),
+                    )
+                    .when(has_previous_page || has_next_page, |this| {
+                        this.child(
+                            IconButton::new("previous-page", IconName::ChevronLeft)
+                                .icon_size(IconSize::Small)
+                                .disabled(!has_previous_page)
+                                .tooltip(Tooltip::text("Previous Page"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.show_page(page_start.saturating_sub(PAGE_ROWS), cx)
+                                })),
+                        )
+                        .child(
+                            IconButton::new("next-page", IconName::ChevronRight)
+                                .icon_size(IconSize::Small)
+                                .disabled(!has_next_page)
+                                .tooltip(Tooltip::text("Next Page"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.show_page(page_start + PAGE_ROWS, cx)
+                                })),
+                        )
+                    }),
+            )
+    }
+
+    /// Shows the values that the bytes at the cursor can be read as.
+    fn render_data_inspector(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let bytes = &self.buffer.bytes()[self.cursor..];
+        v_flex()
+            .flex_none()
+            .w(rems(18.))
+            .p_2()
+            .gap_1()
+            .border_l_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(
+                        Label::new("Data Inspector")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Button::new("toggle-endianness", self.endianness.label())
+                            .label_size(LabelSize::Small)
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Switch Byte Order",
+                                &ToggleEndianness,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_endianness(&ToggleEndianness, window, cx)
+                            })),
+                    ),
+            )
+            .children(
+                inspect(bytes, self.endianness)
+                    .into_iter()
+                    .map(|(name, value)| {
+                        h_flex()
+                            .gap_2()
+                            .justify_between()
+                            .child(Label::new(name).size(LabelSize::Small).color(Color::Muted))
+                            .child(
+                                Label::new(value.unwrap_or_else(|| "—".to_string()))
+                                    .size(LabelSize::Small)
+                                    .buffer_font(cx),
+                            )
+                    }),
+            )
+    }
+}
+
+/// The number of hex digits that offsets are shown with, so that they line up.
+fn offset_digits(len: usize) -> usize {
+    let digits = (usize::BITS - len.leading_zeros()).div_ceil(4) as usize;
+    digits.max(8)
+}
+
+impl EventEmitter<HexEditorEvent> for HexEditor {}
+
+impl EventEmitter<SearchEvent> for HexEditor {}
+
+impl Focusable for HexEditor {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let page_len = (self.row_count() - self.page_start).min(PAGE_ROWS);
+        let settings = ThemeSettings::get_global(cx);
+        let text_size = settings.buffer_font_size(cx);
+
+        v_flex()
+            .key_context("HexEditor")
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::handle_key_down))
+            .on_mouse_up(
+                MouseButton::Left,
+                cx.listener(|this, _, _, _| this.selecting = false),
+            )
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::select_left))
+            .on_action(cx.listener(Self::select_right))
+            .on_action(cx.listener(Self::select_up))
+            .on_action(cx.listener(Self::select_down))
+            .on_action(cx.listener(Self::move_page_up))
+            .on_action(cx.listener(Self::move_page_down))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::select_all))
+            .on_action(cx.listener(Self::switch_column))
+            .on_action(cx.listener(Self::toggle_insert_mode))
+            .on_action(cx.listener(Self::toggle_endianness))
+            .on_action(cx.listener(Self::go_to_offset))
+            .on_action(cx.listener(Self::copy))
+            .on_action(cx.listener(Self::cut))
+            .on_action(cx.listener(Self::paste))
+            .on_action(cx.listener(Self::backspace))
+            .on_action(cx.listener(Self::delete))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(
+                h_flex()
+                    .flex_grow()
+                    .min_h_0()
+                    .w_full()
+                    .items_start()
+                    .child(
+                        uniform_list(
+                            "hex-editor-rows",
+                            page_len,
+                            cx.processor(|this, range: Range<usize>, _, cx| {
+                                this.render_rows(range, cx)
+                            }),
+                        )
+                        .track_scroll(&self.scroll_handle)
+                        .font_buffer(cx)
+                        .text_size(text_size)
+                        .px_2()
+                        .flex_grow()
+                        .h_full(),
+                    )
+                    .child(self.render_data_inspector(cx)),
+            )
+    }
+}
+
+impl Item for HexEditor {
+    type Event = HexEditorEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            HexEditorEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+            HexEditorEvent::Saved | HexEditorEvent::Reloaded | HexEditorEvent::FileChanged => {
+                f(ItemEvent::UpdateTab)
+            }
+        }
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(
+            self.abs_path
+                .compact()
+                .to_string_lossy()
+                .into_owned()
+                .into(),
+        )
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(params.text_color())
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, _: &App) -> SharedString {
+        let file_name = self
+            .abs_path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("{file_name} (Hex)").into()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        workspace::ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(&self.abs_path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn breadcrumb_location(&self, cx: &App) -> ToolbarItemLocation {
+        if EditorSettings::get_global(cx).toolbar.breadcrumbs {
+            ToolbarItemLocation::PrimaryLeft
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+
+    fn breadcrumbs(&self, _: &Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        let project = self.project.read(cx);
+        let text = match &self.project_path {
+            Some(project_path) => project_path
+                .path
+                .display(project.path_style(cx))
+                .to_string(),
+            None => self.abs_path.compact().to_string_lossy().into_owned(),
+        };
+        Some(vec![BreadcrumbText {
+            text,
+            highlights: None,
+            font: Some(ThemeSettings::get_global(cx).buffer_font.clone()),
+        }])
+    }
+
+    fn as_searchable(
+        &self,
+        handle: &Entity<Self>,
+        _: &App,
+    ) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(handle.clone()))
+    }
+
+    fn is_dirty(&self, _: &App) -> bool {
+        self.is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.is_dirty()
+            && self
+                .disk_mtime(cx)
+                .zip(self.saved_mtime)
+                .is_some_and(|(disk_mtime, saved_mtime)| {
+                    disk_mtime.bad_is_greater_than(saved_mtime)
+                })
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _: SaveOptions,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        let bytes = self.buffer.snapshot();
+        let version = self.buffer.version();
+        cx.spawn(async move |this, cx| {
+            fs.atomic_write_bytes(abs_path.clone(), bytes.to_vec())
+                .await?;
+            let mtime = fs.metadata(&abs_path).await?.map(|metadata| metadata.mtime);
+            this.update(cx, |this, cx| {
+                this.saved_version = version;
+                this.saved_mtime = mtime;
+                cx.emit(HexEditorEvent::Saved);
+                cx.notify();
+            })
+        })
+    }
+
+    fn reload(
+        &mut self,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.reload_from_disk(true, cx)
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<WorkspaceId>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>>
+    where
+        Self: Sized,
+    {
+        Task::ready(None)
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+impl SearchableItem for HexEditor {
+    type Match = Range<usize>;
+
+    fn supported_options(&self) -> SearchOptions {
+        SearchOptions {
+            case: true,
+            word: false,
+            regex: false,
+            replacement: true,
+            selection: false,
+            find_in_results: false,
+        }
+    }
+
+    fn clear_matches(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        self.search_matches.clear();
+        self.active_match = None;
+        self.search_truncated = false;
+        cx.notify();
+    }
+
+    fn update_matches(
+        &mut self,
+        matches: &[Self::Match],
+        active_match_index: Option<usize>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.search_matches = matches.to_vec();
+        self.active_match = active_match_index;
+        cx.notify();
+    }
+
+    fn query_suggestion(&mut self, _: &mut Window, _: &mut Context<Self>) -> String {
+        let selection = self.selection();
+        if selection.is_empty() || selection.len() > MAX_QUERY_SUGGESTION_LEN {
+            return String::new();
+        }
+        self.selected_text()
+    }
+
+    fn activate_match(
+        &mut self,
+        index: usize,
+        matches: &[Self::Match],
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(search_match) = matches.get(index) else {
+            return;
+        };
+        self.active_match = Some(index);
+        self.selection_anchor = search_match.start;
+        self.move_cursor_to(search_match.end, true, cx);
+        cx.emit(SearchEvent::ActiveMatchChanged);
+    }
+
+    fn select_matches(
+        &mut self,
+        matches: &[Self::Match],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.activate_match(0, matches, window, cx);
+    }
+
+    fn replace(
+        &mut self,
+        search_match: &Self::Match,
+        query: &SearchQuery,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.replace_all(&mut std::iter::once(search_match), query, window, cx);
+    }
+
+    fn replace_all(
+        &mut self,
+        matches: &mut dyn Iterator<Item = &Self::Match>,
+        query: &SearchQuery,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(replacement) = query.replacement().map(parse_bytes) else {
+            return;
+        };
+        let mut matches = matches.cloned().collect::<Vec<_>>();
+        if matches.is_empty() {
+            return;
+        }
+        // Replace from the end, so that the earlier matches' offsets stay the same.
+        matches.sort_by_key(|search_match| Reverse(search_match.start));
+        for search_match in &matches {
+            self.buffer.edit(search_match.clone(), &replacement, false);
+        }
+        let first_start = matches.last().map_or(0, |search_match| search_match.start);
+        let end = first_start + replacement.len();
+        self.edited(end..end, cx);
+    }
+
+    fn find_matches(
+        &mut self,
+        query: Arc<SearchQuery>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<Self::Match>> {
+        let Some(pattern) = BytePattern::parse(query.as_str(), query.case_sensitive()) else {
+            return Task::ready(Vec::new());
+        };
+        let bytes = self.buffer.snapshot();
+        cx.spawn(async move |this, cx| {
+            let (matches, truncated) = cx
+                .background_spawn(async move { pattern.find(&bytes, MAX_SEARCH_MATCHES) })
+                .await;
+            this.update(cx, |this, cx| {
+                this.search_truncated = truncated;
+                cx.notify();
+            })
+            .ok();
+            matches
+        })
+    }
+
+    fn active_match_index(
+        &mut self,
+        direction: Direction,
+        matches: &[Self::Match],
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<usize> {
+        if matches.is_empty() {
+            return None;
+        }
+        let cursor = self.selection().start;
+        let next = matches.partition_point(|search_match| search_match.end <= cursor);
+        Some(match direction {
+            Direction::Next => next.min(matches.len() - 1),
+            Direction::Prev => next.saturating_sub(1),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_digits() {
+        assert_eq!(offset_digits(0), 8);
+        assert_eq!(offset_digits(0xFFFF_FFFF), 8);
+        assert_eq!(offset_digits(0x1_0000_0000), 9);
+    }
+
+    #[test]
+    fn test_byte_display() {
+        assert_eq!(hex_byte(0x0A), "0A");
+        assert_eq!(printable_char(b'A'), 'A');
+        assert_eq!(printable_char(b' '), '·');
+        assert_eq!(printable_char(0xFF), '·');
+    }
+}
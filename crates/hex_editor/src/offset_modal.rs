@@ -0,0 +1,143 @@
+use editor::Editor;
+use gpui::{
+    App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, WeakEntity, Window,
+};
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::ModalView;
+
+use crate::HexEditor;
+
+/// A prompt for an offset in a hex editor to move the cursor to.
+pub struct OffsetModal {
+    hex_editor: WeakEntity<HexEditor>,
+    offset_editor: Entity<Editor>,
+    cursor: usize,
+    len: usize,
+    error: Option<SharedString>,
+}
+
+impl OffsetModal {
+    pub fn new(
+        hex_editor: WeakEntity<HexEditor>,
+        cursor: usize,
+        len: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let offset_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text(
+                &format!("Offset, such as 0x{len:X} or {len}, or +/- to move relative"),
+                window,
+                cx,
+            );
+            editor
+        });
+        Self {
+            hex_editor,
+            offset_editor,
+            cursor,
+            len,
+            error: None,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        let text = self.offset_editor.read(cx).text(cx);
+        let Some(offset) = parse_offset(&text, self.cursor).filter(|offset| *offset <= self.len)
+        else {
+            self.error = Some(format!("Enter an offset from 0 to 0x{:X}", self.len).into());
+            cx.notify();
+            return;
+        };
+        self.hex_editor
+            .update(cx, |hex_editor, cx| {
+                hex_editor.move_cursor_to(offset, false, cx)
+            })
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+}
+
+/// Parses an offset in decimal or, with a `0x` prefix, hex. Offsets starting with `+` or `-` are
+/// relative to the cursor.
+pub(crate) fn parse_offset(text: &str, cursor: usize) -> Option<usize> {
+    let text = text.trim();
+    let (sign, text) = match text.split_at_checked(1) {
+        Some(("+", rest)) => (Some(true), rest.trim_start()),
+        Some(("-", rest)) => (Some(false), rest.trim_start()),
+        _ => (None, text),
+    };
+    let value = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok()?,
+        None => text.parse().ok()?,
+    };
+    match sign {
+        Some(true) => cursor.checked_add(value),
+        Some(false) => cursor.checked_sub(value),
+        None => Some(value),
+    }
+}
+
+impl EventEmitter<DismissEvent> for OffsetModal {}
+
+impl ModalView for OffsetModal {}
+
+impl Focusable for OffsetModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.offset_editor.focus_handle(cx)
+    }
+}
+
+impl Render for OffsetModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("HexEditorOffsetModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w(rems(34.))
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(format!("Go to Offset (currently 0x{:X})", self.cursor))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(div().px_2().py_1().child(self.offset_editor.clone()))
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .px_2()
+                        .pb_1()
+                        .child(Label::new(error).size(LabelSize::Small).color(Color::Error)),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("42", 10), Some(42));
+        assert_eq!(parse_offset(" 0x2A ", 10), Some(42));
+        assert_eq!(parse_offset("+0x10", 10), Some(26));
+        assert_eq!(parse_offset("- 4", 10), Some(6));
+        assert_eq!(parse_offset("-11", 10), None);
+        assert_eq!(parse_offset("0xZZ", 10), None);
+        assert_eq!(parse_offset("", 10), None);
+    }
+}
@@ -0,0 +1,298 @@
+use std::sync::Arc;
+
+use gpui::{
+    AnyElement, Bounds, ContentMask, Corners, Hsla, IntoElement, Pixels, Point, RenderImage, Size,
+    Styled, Window, canvas, fill, opaque_grey, point, px, size,
+};
+use util::ResultExt as _;
+
+/// The smallest and largest zoom levels, relative to the image's actual size.
+pub(crate) const MIN_ZOOM: f32 = 0.02;
+pub(crate) const MAX_ZOOM: f32 = 64.;
+/// The zoom level from which the outline of each pixel is drawn.
+const PIXEL_GRID_ZOOM: f32 = 8.;
+const CHECKERBOARD_SQUARE: f32 = 16.;
+
+/// How an image is placed in the viewport.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct ImageTransform {
+    /// The number of screen pixels per image pixel, or `None` to shrink the image to fit.
+    pub zoom: Option<f32>,
+    /// The offset of the image's center from the viewport's center.
+    pub pan: Point<Pixels>,
+}
+
+impl ImageTransform {
+    pub fn scale(&self, image_size: Size<Pixels>, viewport: Size<Pixels>) -> f32 {
+        self.zoom.unwrap_or_else(|| fit_scale(image_size, viewport))
+    }
+
+    pub fn image_bounds(
+        &self,
+        image_size: Size<Pixels>,
+        scale: f32,
+        viewport: Bounds<Pixels>,
+    ) -> Bounds<Pixels> {
+        Bounds::centered_at(
+            viewport.center() + self.pan,
+            size(image_size.width * scale, image_size.height * scale),
+        )
+    }
+
+    /// Changes the scale, keeping the point of the image under `anchor` in place.
+    pub fn zoom_around(
+        &mut self,
+        old_scale: f32,
+        new_scale: f32,
+        anchor: Point<Pixels>,
+        viewport: Bounds<Pixels>,
+    ) {
+        let center = viewport.center() + self.pan;
+        let new_center = anchor + (center - anchor) * (new_scale / old_scale);
+        self.pan = new_center - viewport.center();
+        self.zoom = Some(new_scale);
+    }
+}
+
+/// The scale at which an image fits the viewport, without enlarging it.
+pub(crate) fn fit_scale(image_size: Size<Pixels>, viewport: Size<Pixels>) -> f32 {
+    if image_size.width <= px(0.) || image_size.height <= px(0.) {
+        return 1.;
+    }
+    (viewport.width / image_size.width)
+        .min(viewport.height / image_size.height)
+        .min(1.)
+}
+
+/// Which part of the viewport an image is shown in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ViewportSide {
+    Full,
+    Left,
+    Right,
+}
+
+impl ViewportSide {
+    pub fn bounds(self, viewport: Bounds<Pixels>) -> Bounds<Pixels> {
+        let half = size(viewport.size.width / 2., viewport.size.height);
+        match self {
+            ViewportSide::Full => viewport,
+            ViewportSide::Left => Bounds::new(viewport.origin, half),
+            ViewportSide::Right => Bounds::new(
+                point(viewport.origin.x + half.width, viewport.origin.y),
+                half,
+            ),
+        }
+    }
+}
+
+/// The size of the first frame of an image, with one logical pixel per image pixel.
+pub(crate) fn image_size(image: &RenderImage) -> Size<Pixels> {
+    let size = image.size(0);
+    gpui::size(px(size.width.0 as f32), px(size.height.0 as f32))
+}
+
+/// The coordinates of the image pixel at a position on screen.
+pub(crate) fn pixel_at(
+    image_bounds: Bounds<Pixels>,
+    scale: f32,
+    position: Point<Pixels>,
+) -> Option<(u32, u32)> {
+    if !image_bounds.contains(&position) {
+        return None;
+    }
+    let offset = position - image_bounds.origin;
+    Some((
+        (offset.x / px(scale)).floor() as u32,
+        (offset.y / px(scale)).floor() as u32,
+    ))
+}
+
+/// The frame of an image to show for the frame index of an animation, which wraps around so that
+/// images with fewer frames loop.
+pub(crate) fn frame(image: &RenderImage, frame_index: usize) -> usize {
+    frame_index % image.frame_count().max(1)
+}
+
+/// The straight RGBA color of a pixel in a frame of an image.
+pub(crate) fn pixel_color(
+    image: &RenderImage,
+    frame_index: usize,
+    x: u32,
+    y: u32,
+) -> Option<[u8; 4]> {
+    let frame = frame(image, frame_index);
+    let size = image.size(frame);
+    let (width, height) = (size.width.0 as u32, size.height.0 as u32);
+    if x >= width || y >= height {
+        return None;
+    }
+    let offset = (y as usize * width as usize + x as usize) * 4;
+    // Frames are stored as BGRA.
+    let [b, g, r, a] = image
+        .as_bytes(frame)?
+        .get(offset..offset + 4)?
+        .try_into()
+        .ok()?;
+    Some([r, g, b, a])
+}
+
+/// An image painted into part of the viewport.
+pub(crate) struct ImageLayer {
+    pub image: Arc<RenderImage>,
+    pub frame_index: usize,
+    pub side: ViewportSide,
+    pub transform: ImageTransform,
+    /// The size of the image that fitting to the viewport is based on, so that images of
+    /// different sizes that are shown together are scaled alike.
+    pub fit_size: Size<Pixels>,
+    /// Whether to paint a checkerboard behind transparent pixels.
+    pub checkerboard: bool,
+    pub grid_color: Hsla,
+}
+
+impl ImageLayer {
+    pub fn into_any_element(self) -> AnyElement {
+        canvas(
+            |_, _, _| {},
+            move |bounds, _, window, _| self.paint(bounds, window),
+        )
+        .absolute()
+        .top_0()
+        .left_0()
+        .size_full()
+        .into_any_element()
+    }
+
+    fn paint(&self, viewport: Bounds<Pixels>, window: &mut Window) {
+        let viewport = self.side.bounds(viewport);
+        let scale = self.transform.scale(self.fit_size, viewport.size);
+        let image_bounds = self
+            .transform
+            .image_bounds(image_size(&self.image), scale, viewport);
+        let visible = image_bounds.intersect(&viewport);
+        if visible.size.width <= px(0.) || visible.size.height <= px(0.) {
+            return;
+        }
+
+        window.with_content_mask(Some(ContentMask { bounds: viewport }), |window| {
+            if self.checkerboard {
+                paint_checkerboard(image_bounds, visible, window);
+            }
+            window
+                .paint_image(
+                    image_bounds,
+                    Corners::default(),
+                    self.image.clone(),
+                    frame(&self.image, self.frame_index),
+                    false,
+                )
+                .log_err();
+            if scale >= PIXEL_GRID_ZOOM {
+                paint_pixel_grid(image_bounds, visible, scale, self.grid_color, window);
+            }
+        });
+    }
+}
+
+/// Paints a checkerboard over the visible part of the image, aligned to the image so that it
+/// moves along when panning.
+fn paint_checkerboard(image_bounds: Bounds<Pixels>, visible: Bounds<Pixels>, window: &mut Window) {
+    let square = px(CHECKERBOARD_SQUARE);
+    let first_column = ((visible.left() - image_bounds.left()) / square).floor() as i64;
+    let first_row = ((visible.top() - image_bounds.top()) / square).floor() as i64;
+    let mut row = first_row;
+    loop {
+        let y = image_bounds.top() + square * row as f32;
+        if y >= visible.bottom() {
+            break;
+        }
+        let mut column = first_column;
+        loop {
+            let x = image_bounds.left() + square * column as f32;
+            if x >= visible.right() {
+                break;
+            }
+            let color = if (row + column) % 2 == 0 {
+                opaque_grey(0.6, 0.4)
+            } else {
+                opaque_grey(0.7, 0.4)
+            };
+            let rect = Bounds::new(point(x, y), size(square, square)).intersect(&visible);
+            window.paint_quad(fill(rect, color));
+            column += 1;
+        }
+        row += 1;
+    }
+}
+
+/// Outlines each visible pixel of an image that's zoomed in far enough to tell them apart.
+fn paint_pixel_grid(
+    image_bounds: Bounds<Pixels>,
+    visible: Bounds<Pixels>,
+    scale: f32,
+    color: Hsla,
+    window: &mut Window,
+) {
+    let cell = px(scale);
+    let first_column = ((visible.left() - image_bounds.left()) / cell).ceil() as i64;
+    let last_column = ((visible.right() - image_bounds.left()) / cell).floor() as i64;
+    for column in first_column..=last_column {
+        let x = image_bounds.left() + cell * column as f32;
+        let line = Bounds::new(point(x, visible.top()), size(px(1.), visible.size.height));
+        window.paint_quad(fill(line, color));
+    }
+    let first_row = ((visible.top() - image_bounds.top()) / cell).ceil() as i64;
+    let last_row = ((visible.bottom() - image_bounds.top()) / cell).floor() as i64;
+    for row in first_row..=last_row {
+        let y = image_bounds.top() + cell * row as f32;
+        let line = Bounds::new(point(visible.left(), y), size(visible.size.width, px(1.)));
+        window.paint_quad(fill(line, color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_scale() {
+        let viewport = size(px(400.), px(300.));
+        assert_eq!(fit_scale(size(px(800.), px(300.)), viewport), 0.5);
+        assert_eq!(fit_scale(size(px(100.), px(600.)), viewport), 0.5);
+        // Small images aren't enlarged.
+        assert_eq!(fit_scale(size(px(40.), px(30.)), viewport), 1.);
+    }
+
+    #[test]
+    fn test_zoom_around_anchor() {
+        let viewport = Bounds::new(point(px(0.), px(0.)), size(px(400.), px(400.)));
+        let image = size(px(100.), px(100.));
+        let mut transform = ImageTransform {
+            zoom: Some(1.),
+            pan: Point::default(),
+        };
+        let anchor = point(px(175.), px(200.));
+        let before = pixel_at(transform.image_bounds(image, 1., viewport), 1., anchor);
+
+        transform.zoom_around(1., 4., anchor, viewport);
+        let bounds = transform.image_bounds(image, 4., viewport);
+        assert_eq!(bounds.size, size(px(400.), px(400.)));
+        assert_eq!(pixel_at(bounds, 4., anchor), before);
+        assert_eq!(before, Some((25, 50)));
+    }
+
+    #[test]
+    fn test_viewport_sides() {
+        let viewport = Bounds::new(point(px(10.), px(20.)), size(px(400.), px(300.)));
+        assert_eq!(ViewportSide::Full.bounds(viewport), viewport);
+        assert_eq!(
+            ViewportSide::Left.bounds(viewport),
+            Bounds::new(point(px(10.), px(20.)), size(px(200.), px(300.)))
+        );
+        assert_eq!(
+            ViewportSide::Right.bounds(viewport),
+            Bounds::new(point(px(210.), px(20.)), size(px(200.), px(300.)))
+        );
+    }
+}
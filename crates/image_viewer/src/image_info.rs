@@ -1,14 +1,15 @@
 use gpui::{Context, Entity, IntoElement, ParentElement, Render, Subscription, div};
-use project::image_store::{ImageFormat, ImageMetadata};
+use project::image_store::ImageMetadata;
 use settings::Settings;
 use ui::prelude::*;
 use util::size::format_file_size;
 use workspace::{ItemHandle, StatusItemView, Workspace};
 
-use crate::{ImageFileSizeUnit, ImageView, ImageViewerSettings};
+use crate::{HoveredPixel, ImageFileSizeUnit, ImageView, ImageViewerSettings};
 
 pub struct ImageInfo {
     metadata: Option<ImageMetadata>,
+    hovered_pixel: Option<HoveredPixel>,
     _observe_active_image: Option<Subscription>,
     observe_image_item: Option<Subscription>,
 }
@@ -17,13 +18,16 @@ impl ImageInfo {
     pub fn new(_workspace: &Workspace) -> Self {
         Self {
             metadata: None,
+            hovered_pixel: None,
             _observe_active_image: None,
             observe_image_item: None,
         }
     }
 
     fn update_metadata(&mut self, image_view: &Entity<ImageView>, cx: &mut Context<Self>) {
-        let image_item = image_view.read(cx).image_item.clone();
+        let image_view = image_view.read(cx);
+        self.hovered_pixel = image_view.hovered_pixel();
+        let image_item = image_view.image_item.clone();
         let current_metadata = image_item.read(cx).image_metadata;
         if current_metadata.is_some() {
             self.metadata = current_metadata;
@@ -62,22 +66,31 @@ impl Render for ImageInfo {
             ));
         }
 
-        components.push(
-            match metadata.format {
-                ImageFormat::Png => "PNG",
-                ImageFormat::Jpeg => "JPEG",
-                ImageFormat::Gif => "GIF",
-                ImageFormat::WebP => "WebP",
-                ImageFormat::Tiff => "TIFF",
-                ImageFormat::Bmp => "BMP",
-                ImageFormat::Ico => "ICO",
-                ImageFormat::Avif => "Avif",
-                _ => "Unknown",
-            }
-            .to_string(),
-        );
+        components.push(metadata.format.name().to_string());
 
-        div().child(Label::new(components.join(" • ")).size(LabelSize::Small))
+        h_flex()
+            .gap_2()
+            .when_some(self.hovered_pixel, |this, pixel| {
+                this.child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            div()
+                                .size_3()
+                                .rounded_xs()
+                                .border_1()
+                                .border_color(cx.theme().colors().border)
+                                .bg(pixel.color()),
+                        )
+                        .child(Label::new(pixel.hex()).size(LabelSize::Small))
+                        .child(
+                            Label::new(format!("({}, {})", pixel.x, pixel.y))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+            })
+            .child(Label::new(components.join(" • ")).size(LabelSize::Small))
     }
 }
 
@@ -99,6 +112,7 @@ impl StatusItemView for ImageInfo {
             }));
         } else {
             self.metadata = None;
+            self.hovered_pixel = None;
         }
         cx.notify();
     }
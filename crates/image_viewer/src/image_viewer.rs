@@ -1,23 +1,34 @@
+mod image_canvas;
 mod image_info;
 mod image_viewer_settings;
 
-use std::path::Path;
+use std::{cell::Cell, path::Path, rc::Rc, sync::Arc, time::Duration};
 
 use anyhow::Context as _;
 use editor::{EditorSettings, items::entry_git_aware_label_color};
 use file_icons::FileIcons;
 use gpui::{
-    AnyElement, App, Bounds, Context, Entity, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement, IntoElement, ObjectFit, ParentElement, Render, Styled, Task, WeakEntity,
-    Window, canvas, div, fill, img, opaque_grey, point, size,
+    Action, AnyElement, AnyView, App, Bounds, ClipboardItem, Context, Entity, EventEmitter,
+    FocusHandle, Focusable, Hsla, InteractiveElement, IntoElement, MouseButton, MouseDownEvent,
+    MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Render, RenderImage, Rgba,
+    ScrollWheelEvent, Styled, Task, WeakEntity, Window, actions, canvas, div,
+};
+use image_canvas::{
+    ImageLayer, ImageTransform, MAX_ZOOM, MIN_ZOOM, ViewportSide, image_size, pixel_at, pixel_color,
 };
 use language::File as _;
 use persistence::IMAGE_VIEWER;
-use project::{ImageItem, Project, ProjectPath, image_store::ImageItemEvent};
+use project::{
+    ImageItem, Project, ProjectPath,
+    image_store::{
+        DecodedImage, ImageContent, ImageItemEvent, load_image_content, render_image_from_rgba,
+        tone_map,
+    },
+};
 use settings::Settings;
 use theme::{Theme, ThemeSettings};
-use ui::prelude::*;
-use util::paths::PathExt;
+use ui::{Divider, Tooltip, prelude::*};
+use util::{ResultExt as _, paths::PathExt};
 use workspace::{
     ItemId, ItemSettings, Pane, ToolbarItemLocation, Workspace, WorkspaceId, delete_unloaded_items,
     invalid_item_view::InvalidItemView,
@@ -27,10 +38,113 @@ use workspace::{
 pub use crate::image_info::*;
 pub use crate::image_viewer_settings::*;
 
+actions!(
+    image_viewer,
+    [
+        /// Zooms in on the image.
+        ZoomIn,
+        /// Zooms out of the image.
+        ZoomOut,
+        /// Shrinks the image to fit the view.
+        ZoomToFit,
+        /// Shows the image at its actual size.
+        ZoomToActualSize,
+        /// Shows the image next to its version at HEAD.
+        ToggleSideBySideDiff,
+        /// Shows the image over its version at HEAD, blended together.
+        ToggleOnionSkinDiff,
+        /// Copies the color of the pixel under the mouse cursor.
+        CopyPixelColor,
+        /// Plays or pauses an animated image.
+        TogglePlayback,
+        /// Pauses an animated image and shows its next frame.
+        NextFrame,
+        /// Pauses an animated image and shows its previous frame.
+        PreviousFrame,
+        /// Brightens a high dynamic range image.
+        IncreaseExposure,
+        /// Darkens a high dynamic range image.
+        DecreaseExposure,
+        /// Shows a high dynamic range image at its original exposure.
+        ResetExposure,
+    ]
+);
+
+const ZOOM_STEP: f32 = 1.25;
+const ONION_SKIN_STEP: f32 = 0.25;
+/// The change in exposure per step, in stops.
+const EXPOSURE_STEP: f32 = 0.5;
+/// Frames of animated images that ask to be shown for this long or less are shown for
+/// `DEFAULT_FRAME_DELAY` instead, like browsers do.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(10);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// How the image is compared with its version at HEAD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffMode {
+    Off,
+    SideBySide,
+    OnionSkin,
+}
+
+/// The pixel of the image under the mouse cursor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HoveredPixel {
+    pub x: u32,
+    pub y: u32,
+    /// The color of the pixel, as RGBA that isn't premultiplied.
+    pub rgba: [u8; 4],
+}
+
+impl HoveredPixel {
+    /// The color as a hex string, which only includes the alpha channel if the pixel isn't opaque.
+    pub fn hex(&self) -> String {
+        let [r, g, b, a] = self.rgba;
+        if a == u8::MAX {
+            format!("#{r:02X}{g:02X}{b:02X}")
+        } else {
+            format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+        }
+    }
+
+    pub fn color(&self) -> Hsla {
+        let [r, g, b, a] = self.rgba.map(|channel| channel as f32 / 255.);
+        Rgba { r, g, b, a }.into()
+    }
+}
+
+/// A high dynamic range image, rendered at an exposure other than zero.
+struct ExposedImage {
+    source: Arc<DecodedImage>,
+    exposure: f32,
+    image: Arc<RenderImage>,
+}
+
 pub struct ImageView {
     image_item: Entity<ImageItem>,
     project: Entity<Project>,
     focus_handle: FocusHandle,
+    transform: ImageTransform,
+    /// The bounds of the area the image is shown in, as of the last frame.
+    viewport: Rc<Cell<Bounds<Pixels>>>,
+    /// The position of the mouse when a drag began, and the pan offset at the time.
+    drag_start: Option<(Point<Pixels>, Point<Pixels>)>,
+    hovered_pixel: Option<HoveredPixel>,
+    /// The image as of HEAD, if it differs from the file.
+    head_image: Option<ImageContent>,
+    diff_mode: DiffMode,
+    /// How opaque the file is over its version at HEAD in onion skin mode.
+    onion_skin_opacity: f32,
+    /// The frame of an animated image that's shown.
+    frame_index: usize,
+    playing: bool,
+    next_frame: Option<Task<()>>,
+    /// The exposure that high dynamic range images are shown at, in stops.
+    exposure: f32,
+    exposed_images: Vec<ExposedImage>,
+    /// The exposure that images are being rendered at in the background.
+    pending_exposure: Option<(f32, Task<()>)>,
+    _load_head_image: Task<()>,
 }
 
 impl ImageView {
@@ -40,23 +154,693 @@ impl ImageView {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
-        cx.subscribe(&image_item, Self::on_image_event).detach();
         cx.on_release_in(window, |this, window, cx| {
-            let image_data = this.image_item.read(cx).image.clone();
-            if let Some(image) = image_data.clone().get_render_image(window, cx) {
-                cx.drop_image(image, Some(window));
+            let image = this.image_item.read(cx).image.clone();
+            for content in [Some(image), this.head_image.clone()].into_iter().flatten() {
+                match content {
+                    ImageContent::Native(image_data) => {
+                        if let Some(image) = image_data.clone().get_render_image(window, cx) {
+                            cx.drop_image(image, Some(window));
+                        }
+                        image_data.remove_asset(cx);
+                    }
+                    ImageContent::Decoded(image) => {
+                        cx.drop_image(image.render_image.clone(), Some(window));
+                    }
+                }
+            }
+            for exposed in this.exposed_images.drain(..) {
+                cx.drop_image(exposed.image, Some(window));
             }
-            image_data.remove_asset(cx);
         })
         .detach();
 
-        Self {
+        Self::build(image_item, project, cx)
+    }
+
+    fn build(
+        image_item: Entity<ImageItem>,
+        project: Entity<Project>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        cx.subscribe(&image_item, Self::on_image_event).detach();
+        let mut this = Self {
             image_item,
             project,
             focus_handle: cx.focus_handle(),
+            transform: ImageTransform::default(),
+            viewport: Rc::default(),
+            drag_start: None,
+            hovered_pixel: None,
+            head_image: None,
+            diff_mode: DiffMode::Off,
+            onion_skin_opacity: 0.5,
+            frame_index: 0,
+            playing: true,
+            next_frame: None,
+            exposure: 0.,
+            exposed_images: Vec::new(),
+            pending_exposure: None,
+            _load_head_image: Task::ready(()),
+        };
+        this.load_head_image(cx);
+        this
+    }
+
+    pub fn hovered_pixel(&self) -> Option<HoveredPixel> {
+        self.hovered_pixel
+    }
+
+    fn load_head_image(&mut self, cx: &mut Context<Self>) {
+        let image_item = self.image_item.read(cx);
+        let image = image_item.image.clone();
+        let project_path = image_item.project_path(cx);
+        let extension = project_path.path.extension().map(str::to_owned);
+        let Some((repository, repo_path)) = self
+            .project
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_project_path(&project_path, cx)
+        else {
+            return;
+        };
+        let content = repository.update(cx, |repository, _| {
+            repository.load_committed_bytes(repo_path)
+        });
+        self._load_head_image = cx.spawn(async move |this, cx| {
+            let content = content
+                .await
+                .ok()
+                .and_then(|content| content.log_err())
+                .flatten()
+                .filter(|content| !image.matches(content));
+            let head_image = match content {
+                Some(content) => cx
+                    .background_spawn(
+                        async move { load_image_content(content, extension.as_deref()) },
+                    )
+                    .await
+                    .log_err(),
+                None => None,
+            };
+            this.update(cx, |this, cx| {
+                if let Some(ImageContent::Native(head_image)) = this.head_image.take() {
+                    head_image.remove_asset(cx);
+                }
+                this.head_image = head_image;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn diff_mode(&self) -> DiffMode {
+        if self.head_image.is_some() {
+            self.diff_mode
+        } else {
+            DiffMode::Off
+        }
+    }
+
+    /// The image to paint for some content. Images that GPUI decodes itself are only loaded when
+    /// `load` is true, and high dynamic range images are shown at the latest exposure they've
+    /// been rendered at.
+    fn render_image(
+        &self,
+        content: &ImageContent,
+        load: bool,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Arc<RenderImage>> {
+        match content {
+            ImageContent::Native(image) if load => image.clone().use_render_image(window, cx),
+            ImageContent::Native(image) => image.clone().get_render_image(window, cx),
+            ImageContent::Decoded(image) => Some(
+                self.exposed_images
+                    .iter()
+                    .find(|exposed| Arc::ptr_eq(&exposed.source, image))
+                    .map_or_else(
+                        || image.render_image.clone(),
+                        |exposed| exposed.image.clone(),
+                    ),
+            ),
+        }
+    }
+
+    fn is_hdr(&self, cx: &App) -> bool {
+        match &self.image_item.read(cx).image {
+            ImageContent::Decoded(image) => image.hdr.is_some(),
+            ImageContent::Native(_) => false,
         }
     }
 
+    /// Renders the high dynamic range images that are shown at the current exposure, in the
+    /// background.
+    fn update_exposed_images(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let exposure = self.exposure;
+        let image = self.image_item.read(cx).image.clone();
+        let sources = [Some(image), self.head_image.clone()]
+            .into_iter()
+            .flatten()
+            .filter_map(|content| match content {
+                ImageContent::Decoded(image) if image.hdr.is_some() => Some(image),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut stale_images = Vec::new();
+        self.exposed_images.retain(|exposed| {
+            let keep = exposure != 0.
+                && sources
+                    .iter()
+                    .any(|source| Arc::ptr_eq(source, &exposed.source));
+            if !keep {
+                stale_images.push(exposed.image.clone());
+            }
+            keep
+        });
+        for image in stale_images {
+            cx.drop_image(image, Some(window));
+        }
+
+        if exposure == 0. {
+            self.pending_exposure = None;
+            return;
+        }
+        if self
+            .pending_exposure
+            .as_ref()
+            .is_some_and(|(pending_exposure, _)| *pending_exposure == exposure)
+        {
+            return;
+        }
+        let sources = sources
+            .into_iter()
+            .filter(|source| {
+                !self.exposed_images.iter().any(|exposed| {
+                    Arc::ptr_eq(&exposed.source, source) && exposed.exposure == exposure
+                })
+            })
+            .collect::<Vec<_>>();
+        if sources.is_empty() {
+            return;
+        }
+
+        let task = cx.spawn_in(window, async move |this, cx| {
+            let exposed_images = cx
+                .background_spawn(async move {
+                    sources
+                        .into_iter()
+                        .filter_map(|source| {
+                            let image = tone_map(source.hdr.as_ref()?, exposure);
+                            Some(ExposedImage {
+                                source,
+                                exposure,
+                                image: Arc::new(render_image_from_rgba(image)),
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            this.update_in(cx, |this, window, cx| {
+                this.pending_exposure = None;
+                for exposed in exposed_images {
+                    if let Some(ix) = this
+                        .exposed_images
+                        .iter()
+                        .position(|existing| Arc::ptr_eq(&existing.source, &exposed.source))
+                    {
+                        let replaced = this.exposed_images.remove(ix);
+                        cx.drop_image(replaced.image, Some(window));
+                    }
+                    this.exposed_images.push(exposed);
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+        self.pending_exposure = Some((exposure, task));
+    }
+
+    fn change_exposure(&mut self, exposure: f32, cx: &mut Context<Self>) {
+        self.exposure = exposure;
+        self.hovered_pixel = None;
+        cx.notify();
+    }
+
+    fn increase_exposure(&mut self, _: &IncreaseExposure, _: &mut Window, cx: &mut Context<Self>) {
+        self.change_exposure(self.exposure + EXPOSURE_STEP, cx);
+    }
+
+    fn decrease_exposure(&mut self, _: &DecreaseExposure, _: &mut Window, cx: &mut Context<Self>) {
+        self.change_exposure(self.exposure - EXPOSURE_STEP, cx);
+    }
+
+    fn reset_exposure(&mut self, _: &ResetExposure, _: &mut Window, cx: &mut Context<Self>) {
+        self.change_exposure(0., cx);
+    }
+
+    /// Shows the next frame of an animated image once the current one has been shown for as long
+    /// as the image asks.
+    fn schedule_next_frame(&mut self, image: &RenderImage, cx: &mut Context<Self>) {
+        if !self.playing || image.frame_count() <= 1 || self.next_frame.is_some() {
+            return;
+        }
+        let delay = Duration::from(image.delay(self.frame_index));
+        let delay = if delay <= MIN_FRAME_DELAY {
+            DEFAULT_FRAME_DELAY
+        } else {
+            delay
+        };
+        self.next_frame = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(delay).await;
+            this.update(cx, |this, cx| {
+                this.next_frame = None;
+                this.frame_index += 1;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn frame_count(&self, window: &mut Window, cx: &mut App) -> usize {
+        let image = self.image_item.read(cx).image.clone();
+        self.render_image(&image, false, window, cx)
+            .map_or(1, |image| image.frame_count())
+    }
+
+    fn toggle_playback(&mut self, _: &TogglePlayback, _: &mut Window, cx: &mut Context<Self>) {
+        self.playing = !self.playing;
+        self.next_frame = None;
+        cx.notify();
+    }
+
+    fn step_frame(&mut self, forward: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let frame_count = self.frame_count(window, cx);
+        if frame_count <= 1 {
+            return;
+        }
+        self.playing = false;
+        self.next_frame = None;
+        self.frame_index = if forward {
+            (self.frame_index + 1) % frame_count
+        } else {
+            (self.frame_index + frame_count - 1) % frame_count
+        };
+        self.hovered_pixel = None;
+        cx.notify();
+    }
+
+    fn next_frame(&mut self, _: &NextFrame, window: &mut Window, cx: &mut Context<Self>) {
+        self.step_frame(true, window, cx);
+    }
+
+    fn previous_frame(&mut self, _: &PreviousFrame, window: &mut Window, cx: &mut Context<Self>) {
+        self.step_frame(false, window, cx);
+    }
+
+    fn side_at(&self, position: Point<Pixels>) -> ViewportSide {
+        match self.diff_mode() {
+            DiffMode::SideBySide if position.x < self.viewport.get().center().x => {
+                ViewportSide::Left
+            }
+            DiffMode::SideBySide => ViewportSide::Right,
+            DiffMode::Off | DiffMode::OnionSkin => ViewportSide::Full,
+        }
+    }
+
+    /// Changes the zoom level, keeping the point under `anchor` in place, or the center of the
+    /// view if there's no anchor.
+    fn zoom(
+        &mut self,
+        zoom: impl FnOnce(f32) -> f32,
+        anchor: Option<Point<Pixels>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let image = self.image_item.read(cx).image.clone();
+        let Some(image) = self.render_image(&image, false, window, cx) else {
+            return;
+        };
+        let side = match anchor {
+            Some(anchor) => self.side_at(anchor),
+            None if self.diff_mode() == DiffMode::SideBySide => ViewportSide::Right,
+            None => ViewportSide::Full,
+        };
+        let viewport = side.bounds(self.viewport.get());
+        let scale = self.transform.scale(image_size(&image), viewport.size);
+        let new_scale = zoom(scale).clamp(MIN_ZOOM, MAX_ZOOM);
+        let anchor = anchor.unwrap_or_else(|| viewport.center());
+        self.transform
+            .zoom_around(scale, new_scale, anchor, viewport);
+        cx.notify();
+    }
+
+    fn zoom_in(&mut self, _: &ZoomIn, window: &mut Window, cx: &mut Context<Self>) {
+        self.zoom(|scale| scale * ZOOM_STEP, None, window, cx);
+    }
+
+    fn zoom_out(&mut self, _: &ZoomOut, window: &mut Window, cx: &mut Context<Self>) {
+        self.zoom(|scale| scale / ZOOM_STEP, None, window, cx);
+    }
+
+    fn zoom_to_fit(&mut self, _: &ZoomToFit, _: &mut Window, cx: &mut Context<Self>) {
+        self.transform = ImageTransform::default();
+        cx.notify();
+    }
+
+    fn zoom_to_actual_size(
+        &mut self,
+        _: &ZoomToActualSize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.zoom(|_| 1., None, window, cx);
+    }
+
+    fn toggle_side_by_side_diff(
+        &mut self,
+        _: &ToggleSideBySideDiff,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_diff_mode(DiffMode::SideBySide, cx);
+    }
+
+    fn toggle_onion_skin_diff(
+        &mut self,
+        _: &ToggleOnionSkinDiff,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_diff_mode(DiffMode::OnionSkin, cx);
+    }
+
+    fn toggle_diff_mode(&mut self, mode: DiffMode, cx: &mut Context<Self>) {
+        self.diff_mode = if self.diff_mode == mode {
+            DiffMode::Off
+        } else {
+            mode
+        };
+        self.hovered_pixel = None;
+        cx.notify();
+    }
+
+    fn change_onion_skin_opacity(&mut self, delta: f32, cx: &mut Context<Self>) {
+        self.onion_skin_opacity = (self.onion_skin_opacity + delta).clamp(0., 1.);
+        cx.notify();
+    }
+
+    fn copy_pixel_color(&mut self, _: &CopyPixelColor, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(pixel) = self.hovered_pixel {
+            cx.write_to_clipboard(ClipboardItem::new_string(pixel.hex()));
+        }
+    }
+
+    fn handle_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let delta = event.delta.pixel_delta(window.line_height()).y;
+        let factor = 2f32.powf(f32::from(delta) / 200.);
+        self.zoom(|scale| scale * factor, Some(event.position), window, cx);
+        self.update_hovered_pixel(event.position, window, cx);
+    }
+
+    fn handle_mouse_down(
+        &mut self,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle, cx);
+        self.drag_start = Some((event.position, self.transform.pan));
+        cx.notify();
+    }
+
+    fn handle_mouse_move(
+        &mut self,
+        event: &MouseMoveEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some((start, pan)) = self.drag_start {
+            if event.pressed_button == Some(MouseButton::Left) {
+                self.transform.pan = pan + event.position - start;
+                cx.notify();
+                return;
+            }
+            self.drag_start = None;
+            cx.notify();
+        }
+        self.update_hovered_pixel(event.position, window, cx);
+    }
+
+    fn handle_mouse_up(&mut self, _: &MouseUpEvent, _: &mut Window, cx: &mut Context<Self>) {
+        if self.drag_start.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    fn update_hovered_pixel(
+        &mut self,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let hovered_pixel = self.pixel_at(position, window, cx);
+        if hovered_pixel != self.hovered_pixel {
+            self.hovered_pixel = hovered_pixel;
+            cx.notify();
+        }
+    }
+
+    fn pixel_at(
+        &self,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<HoveredPixel> {
+        let image = self.image_item.read(cx).image.clone();
+        let image = self.render_image(&image, false, window, cx)?;
+        let side = self.side_at(position);
+        let shown_image = if side == ViewportSide::Left {
+            self.render_image(self.head_image.as_ref()?, false, window, cx)?
+        } else {
+            image.clone()
+        };
+        let viewport = side.bounds(self.viewport.get());
+        let scale = self.transform.scale(image_size(&image), viewport.size);
+        let image_bounds = self
+            .transform
+            .image_bounds(image_size(&shown_image), scale, viewport);
+        let (x, y) = pixel_at(image_bounds, scale, position)?;
+        let rgba = pixel_color(&shown_image, self.frame_index, x, y)?;
+        Some(HoveredPixel { x, y, rgba })
+    }
+
+    fn render_controls(
+        &self,
+        scale: Option<f32>,
+        diff_mode: DiffMode,
+        frame_count: usize,
+        is_hdr: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let border_color = cx.theme().colors().border_variant;
+        let background = cx.theme().colors().elevated_surface_background;
+        h_flex()
+            .absolute()
+            .top_2()
+            .right_2()
+            .p_0p5()
+            .gap_0p5()
+            .rounded_md()
+            .border_1()
+            .border_color(border_color)
+            .bg(background)
+            .cursor_default()
+            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+            .child(
+                IconButton::new("zoom-out", IconName::Dash)
+                    .icon_size(IconSize::Small)
+                    .tooltip(action_tooltip("Zoom Out", ZoomOut, &self.focus_handle))
+                    .on_click(
+                        cx.listener(|this, _, window, cx| this.zoom_out(&ZoomOut, window, cx)),
+                    ),
+            )
+            .children(scale.map(|scale| {
+                Label::new(format!("{:.0}%", scale * 100.))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+            }))
+            .child(
+                IconButton::new("zoom-in", IconName::Plus)
+                    .icon_size(IconSize::Small)
+                    .tooltip(action_tooltip("Zoom In", ZoomIn, &self.focus_handle))
+                    .on_click(cx.listener(|this, _, window, cx| this.zoom_in(&ZoomIn, window, cx))),
+            )
+            .child(
+                Button::new("zoom-to-fit", "Fit")
+                    .label_size(LabelSize::Small)
+                    .toggle_state(self.transform.zoom.is_none())
+                    .tooltip(action_tooltip("Zoom to Fit", ZoomToFit, &self.focus_handle))
+                    .on_click(
+                        cx.listener(|this, _, window, cx| this.zoom_to_fit(&ZoomToFit, window, cx)),
+                    ),
+            )
+            .child(
+                Button::new("zoom-to-actual-size", "1:1")
+                    .label_size(LabelSize::Small)
+                    .toggle_state(self.transform.zoom == Some(1.))
+                    .tooltip(action_tooltip(
+                        "Zoom to Actual Size",
+                        ZoomToActualSize,
+                        &self.focus_handle,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.zoom_to_actual_size(&ZoomToActualSize, window, cx)
+                    })),
+            )
+            .when(frame_count > 1, |this| {
+                let (icon, title) = if self.playing {
+                    (IconName::DebugPause, "Pause")
+                } else {
+                    (IconName::PlayFilled, "Play")
+                };
+                this.child(Divider::vertical())
+                    .child(
+                        IconButton::new("previous-frame", IconName::ChevronLeft)
+                            .icon_size(IconSize::Small)
+                            .tooltip(action_tooltip(
+                                "Previous Frame",
+                                PreviousFrame,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.step_frame(false, window, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new("toggle-playback", icon)
+                            .icon_size(IconSize::Small)
+                            .tooltip(action_tooltip(title, TogglePlayback, &self.focus_handle))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_playback(&TogglePlayback, window, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new("next-frame", IconName::ChevronRight)
+                            .icon_size(IconSize::Small)
+                            .tooltip(action_tooltip("Next Frame", NextFrame, &self.focus_handle))
+                            .on_click(
+                                cx.listener(|this, _, window, cx| {
+                                    this.step_frame(true, window, cx)
+                                }),
+                            ),
+                    )
+                    .child(
+                        Label::new(format!("{}/{frame_count}", self.frame_index + 1))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+            })
+            .when(is_hdr, |this| {
+                this.child(Divider::vertical())
+                    .child(
+                        IconButton::new("decrease-exposure", IconName::Dash)
+                            .icon_size(IconSize::Small)
+                            .tooltip(action_tooltip(
+                                "Decrease Exposure",
+                                DecreaseExposure,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.decrease_exposure(&DecreaseExposure, window, cx)
+                            })),
+                    )
+                    .child(
+                        Button::new("reset-exposure", format!("{:+.1} EV", self.exposure))
+                            .label_size(LabelSize::Small)
+                            .tooltip(action_tooltip(
+                                "Reset Exposure",
+                                ResetExposure,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.reset_exposure(&ResetExposure, window, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new("increase-exposure", IconName::Plus)
+                            .icon_size(IconSize::Small)
+                            .tooltip(action_tooltip(
+                                "Increase Exposure",
+                                IncreaseExposure,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.increase_exposure(&IncreaseExposure, window, cx)
+                            })),
+                    )
+            })
+            .when(self.head_image.is_some(), |this| {
+                this.child(Divider::vertical())
+                    .child(
+                        IconButton::new("side-by-side-diff", IconName::Split)
+                            .icon_size(IconSize::Small)
+                            .toggle_state(diff_mode == DiffMode::SideBySide)
+                            .tooltip(action_tooltip(
+                                "Compare Side by Side with HEAD",
+                                ToggleSideBySideDiff,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.toggle_diff_mode(DiffMode::SideBySide, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new("onion-skin-diff", IconName::Diff)
+                            .icon_size(IconSize::Small)
+                            .toggle_state(diff_mode == DiffMode::OnionSkin)
+                            .tooltip(action_tooltip(
+                                "Blend with HEAD",
+                                ToggleOnionSkinDiff,
+                                &self.focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.toggle_diff_mode(DiffMode::OnionSkin, cx)
+                            })),
+                    )
+                    .when(diff_mode == DiffMode::OnionSkin, |this| {
+                        this.child(
+                            IconButton::new("fade-to-head", IconName::Dash)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Show More of HEAD"))
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.change_onion_skin_opacity(-ONION_SKIN_STEP, cx)
+                                })),
+                        )
+                        .child(
+                            Label::new(format!("{:.0}%", self.onion_skin_opacity * 100.))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            IconButton::new("fade-to-working-tree", IconName::Plus)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Show More of the Working Tree"))
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.change_onion_skin_opacity(ONION_SKIN_STEP, cx)
+                                })),
+                        )
+                    })
+            })
+    }
+
     fn on_image_event(
         &mut self,
         _: Entity<ImageItem>,
@@ -64,9 +848,15 @@ impl ImageView {
         cx: &mut Context<Self>,
     ) {
         match event {
-            ImageItemEvent::MetadataUpdated
-            | ImageItemEvent::FileHandleChanged
-            | ImageItemEvent::Reloaded => {
+            ImageItemEvent::MetadataUpdated => {
+                cx.emit(ImageViewEvent::TitleChanged);
+                cx.notify();
+            }
+            ImageItemEvent::FileHandleChanged | ImageItemEvent::Reloaded => {
+                self.hovered_pixel = None;
+                self.frame_index = 0;
+                self.next_frame = None;
+                self.load_head_image(cx);
                 cx.emit(ImageViewEvent::TitleChanged);
                 cx.notify();
             }
@@ -187,10 +977,15 @@ impl Item for ImageView {
     where
         Self: Sized,
     {
-        Task::ready(Some(cx.new(|cx| Self {
-            image_item: self.image_item.clone(),
-            project: self.project.clone(),
-            focus_handle: cx.focus_handle(),
+        Task::ready(Some(cx.new(|cx| {
+            let mut view = Self::build(self.image_item.clone(), self.project.clone(), cx);
+            view.transform = self.transform;
+            view.diff_mode = self.diff_mode;
+            view.onion_skin_opacity = self.onion_skin_opacity;
+            view.playing = self.playing;
+            view.frame_index = self.frame_index;
+            view.exposure = self.exposure;
+            view
         })))
     }
 
@@ -302,82 +1097,153 @@ impl Focusable for ImageView {
     }
 }
 
+fn action_tooltip(
+    title: &'static str,
+    action: impl Action,
+    focus_handle: &FocusHandle,
+) -> impl Fn(&mut Window, &mut App) -> AnyView + 'static {
+    let focus_handle = focus_handle.clone();
+    move |_, cx| Tooltip::for_action_in(title, &action, &focus_handle, cx)
+}
+
 impl Render for ImageView {
-    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        self.update_exposed_images(window, cx);
         let image = self.image_item.read(cx).image.clone();
-        let checkered_background =
-            |bounds: Bounds<Pixels>, _, window: &mut Window, _cx: &mut App| {
-                let square_size: f32 = 32.0;
-
-                let start_y = bounds.origin.y.into();
-                let height: f32 = bounds.size.height.into();
-                let start_x = bounds.origin.x.into();
-                let width: f32 = bounds.size.width.into();
-
-                let mut y = start_y;
-                let mut x = start_x;
-                let mut color_swapper = true;
-                // draw checkerboard pattern
-                while y < start_y + height {
-                    // Keeping track of the grid in order to be resilient to resizing
-                    let start_swap = color_swapper;
-                    while x < start_x + width {
-                        // Clamp square dimensions to not exceed bounds
-                        let square_width = square_size.min(start_x + width - x);
-                        let square_height = square_size.min(start_y + height - y);
-
-                        let rect = Bounds::new(
-                            point(px(x), px(y)),
-                            size(px(square_width), px(square_height)),
-                        );
-
-                        let color = if color_swapper {
-                            opaque_grey(0.6, 0.4)
-                        } else {
-                            opaque_grey(0.7, 0.4)
-                        };
+        let image = self.render_image(&image, true, window, cx);
+        let head_image = self
+            .head_image
+            .clone()
+            .and_then(|head_image| self.render_image(&head_image, true, window, cx));
+        let frame_count = image.as_ref().map_or(1, |image| image.frame_count());
+        self.frame_index %= frame_count.max(1);
+        if let Some(image) = &image {
+            self.schedule_next_frame(image, cx);
+        }
+        let frame_index = self.frame_index;
+        let is_hdr = self.is_hdr(cx);
+        let diff_mode = match head_image {
+            Some(_) => self.diff_mode(),
+            None => DiffMode::Off,
+        };
+        let transform = self.transform;
+        let grid_color = cx.theme().colors().border.opacity(0.5);
+        let divider_color = cx.theme().colors().border;
 
-                        window.paint_quad(fill(rect, color));
-                        color_swapper = !color_swapper;
-                        x += square_size;
-                    }
-                    x = start_x;
-                    color_swapper = !start_swap;
-                    y += square_size;
+        let mut layers = Vec::new();
+        let mut scale = None;
+        if let Some(image) = image {
+            let fit_size = image_size(&image);
+            let layer = |image, side, checkerboard| ImageLayer {
+                image,
+                frame_index,
+                side,
+                transform,
+                fit_size,
+                checkerboard,
+                grid_color,
+            };
+            let side = match (diff_mode, head_image) {
+                (DiffMode::SideBySide, Some(head_image)) => {
+                    layers.push(layer(head_image, ViewportSide::Left, true).into_any_element());
+                    layers.push(layer(image, ViewportSide::Right, true).into_any_element());
+                    ViewportSide::Right
+                }
+                (DiffMode::OnionSkin, Some(head_image)) => {
+                    layers.push(layer(head_image, ViewportSide::Full, true).into_any_element());
+                    layers.push(
+                        div()
+                            .absolute()
+                            .size_full()
+                            .opacity(self.onion_skin_opacity)
+                            .child(layer(image, ViewportSide::Full, false).into_any_element())
+                            .into_any_element(),
+                    );
+                    ViewportSide::Full
+                }
+                _ => {
+                    layers.push(layer(image, ViewportSide::Full, true).into_any_element());
+                    ViewportSide::Full
                 }
             };
+            scale = Some(transform.scale(fit_size, side.bounds(self.viewport.get()).size));
+        }
 
-        div().track_focus(&self.focus_handle(cx)).size_full().child(
-            div()
-                .flex()
-                .justify_center()
-                .items_center()
-                .w_full()
-                // TODO: In browser based Tailwind & Flex this would be h-screen and we'd use w-full
-                .h_full()
-                .child(
-                    div()
-                        .relative()
-                        .max_w_full()
-                        .max_h_full()
-                        .child(
-                            canvas(|_, _, _| (), checkered_background)
-                                .border_2()
-                                .border_color(cx.theme().styles.colors.border)
-                                .size_full()
+        let viewport = self.viewport.clone();
+        div()
+            .track_focus(&self.focus_handle(cx))
+            .key_context("ImageViewer")
+            .on_action(cx.listener(Self::zoom_in))
+            .on_action(cx.listener(Self::zoom_out))
+            .on_action(cx.listener(Self::zoom_to_fit))
+            .on_action(cx.listener(Self::zoom_to_actual_size))
+            .on_action(cx.listener(Self::toggle_side_by_side_diff))
+            .on_action(cx.listener(Self::toggle_onion_skin_diff))
+            .on_action(cx.listener(Self::copy_pixel_color))
+            .on_action(cx.listener(Self::toggle_playback))
+            .on_action(cx.listener(Self::next_frame))
+            .on_action(cx.listener(Self::previous_frame))
+            .on_action(cx.listener(Self::increase_exposure))
+            .on_action(cx.listener(Self::decrease_exposure))
+            .on_action(cx.listener(Self::reset_exposure))
+            .size_full()
+            .child(
+                div()
+                    .id("image-viewport")
+                    .relative()
+                    .size_full()
+                    .overflow_hidden()
+                    .map(|this| {
+                        if self.drag_start.is_some() {
+                            this.cursor_grabbing()
+                        } else {
+                            this.cursor_crosshair()
+                        }
+                    })
+                    .on_scroll_wheel(cx.listener(Self::handle_scroll_wheel))
+                    .on_mouse_down(MouseButton::Left, cx.listener(Self::handle_mouse_down))
+                    .on_mouse_move(cx.listener(Self::handle_mouse_move))
+                    .on_mouse_up(MouseButton::Left, cx.listener(Self::handle_mouse_up))
+                    .on_mouse_up_out(MouseButton::Left, cx.listener(Self::handle_mouse_up))
+                    .on_hover(cx.listener(|this, hovered: &bool, _, cx| {
+                        if !hovered && this.hovered_pixel.take().is_some() {
+                            cx.notify();
+                        }
+                    }))
+                    .child(
+                        canvas(move |bounds, _, _| viewport.set(bounds), |_, _, _, _| {})
+                            .absolute()
+                            .size_full(),
+                    )
+                    .children(layers)
+                    .when(diff_mode == DiffMode::SideBySide, |this| {
+                        this.child(
+                            div()
                                 .absolute()
                                 .top_0()
-                                .left_0(),
+                                .bottom_0()
+                                .left(relative(0.5))
+                                .w_px()
+                                .bg(divider_color),
                         )
                         .child(
-                            img(image)
-                                .object_fit(ObjectFit::ScaleDown)
-                                .max_w_full()
-                                .max_h_full()
-                                .id("img"),
-                        ),
-                ),
-        )
+                            div()
+                                .absolute()
+                                .top_2()
+                                .left_2()
+                                .child(Label::new("HEAD").size(LabelSize::Small)),
+                        )
+                        .child(
+                            div()
+                                .absolute()
+                                .top_2()
+                                .left(relative(0.5))
+                                .ml_2()
+                                .child(Label::new("Working Tree").size(LabelSize::Small)),
+                        )
+                    })
+                    .child(self.render_controls(scale, diff_mode, frame_count, is_hdr, cx)),
+            )
     }
 }
 
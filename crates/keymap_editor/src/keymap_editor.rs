@@ -9,6 +9,7 @@ use std::{
 
 mod action_completion_provider;
 mod ui_components;
+mod vscode_keybindings_import;
 
 use anyhow::{Context as _, anyhow};
 use collections::{HashMap, HashSet};
@@ -27,7 +28,8 @@ use language::{Language, LanguageConfig, ToOffset as _};
 use notifications::status_toast::{StatusToast, ToastIcon};
 use project::{CompletionDisplayOptions, Project};
 use settings::{
-    BaseKeymap, KeybindSource, KeymapFile, Settings as _, SettingsAssets, infer_json_indent_size,
+    BaseKeymap, KeybindSource, KeymapFile, Settings as _, SettingsAssets, VsCodeSettingsSource,
+    infer_json_indent_size,
 };
 use ui::{
     ActiveTheme as _, App, Banner, BorrowAppContext, ContextMenu, IconButtonShape, Indicator,
@@ -78,7 +80,11 @@ actions!(
         /// Toggles exact matching for keystroke search
         ToggleExactKeystrokeMatching,
         /// Shows matching keystrokes for the currently selected binding
-        ShowMatchingKeybinds
+        ShowMatchingKeybinds,
+        /// Imports key bindings from the VS Code keybindings.json file into the user keymap.
+        ImportVsCodeKeybindings,
+        /// Imports key bindings from the Cursor keybindings.json file into the user keymap.
+        ImportCursorKeybindings
     ]
 );
 
@@ -138,6 +144,22 @@ pub fn init(cx: &mut App) {
             })
             .register_action(|workspace, action: &ChangeKeybinding, window, cx| {
                 open_keymap_editor(Some(action.action.clone()), workspace, window, cx);
+            })
+            .register_action(|workspace, _: &ImportVsCodeKeybindings, window, cx| {
+                vscode_keybindings_import::import_keybindings(
+                    VsCodeSettingsSource::VsCode,
+                    workspace,
+                    window,
+                    cx,
+                );
+            })
+            .register_action(|workspace, _: &ImportCursorKeybindings, window, cx| {
+                vscode_keybindings_import::import_keybindings(
+                    VsCodeSettingsSource::Cursor,
+                    workspace,
+                    window,
+                    cx,
+                );
             });
     })
     .detach();
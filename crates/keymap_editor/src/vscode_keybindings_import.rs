@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use fs::Fs;
+use gpui::{
+    Action as _, AsyncWindowContext, DismissEvent, EventEmitter, FocusHandle, Focusable,
+    PromptLevel, WeakEntity,
+};
+use settings::{KeymapFile, VsCodeKeybindings, VsCodeKeymapImport, VsCodeSettingsSource};
+use ui::{
+    ElevationIndex, Headline, HeadlineSize, Modal, ModalFooter, ModalHeader, Section, prelude::*,
+};
+use util::truncate_and_remove_front;
+use workspace::{ModalView, Workspace, notifications::DetachAndPromptErr as _};
+use zed_actions::OpenKeymap;
+
+pub(crate) fn import_keybindings(
+    source: VsCodeSettingsSource,
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let fs = workspace.app_state().fs.clone();
+    cx.spawn_in(window, async move |workspace, cx| {
+        import_keybindings_inner(source, fs, workspace, cx).await
+    })
+    .detach_and_prompt_err(
+        &format!("Failed to import {source} key bindings"),
+        window,
+        cx,
+        |error, _, _| Some(format!("{error:#}")),
+    );
+}
+
+async fn import_keybindings_inner(
+    source: VsCodeSettingsSource,
+    fs: Arc<dyn Fs>,
+    workspace: WeakEntity<Workspace>,
+    cx: &mut AsyncWindowContext,
+) -> anyhow::Result<()> {
+    let keybindings = VsCodeKeybindings::load_user_keybindings(source, fs.clone()).await?;
+
+    let prompt = workspace.update_in(cx, |_, window, cx| {
+        window.prompt(
+            PromptLevel::Info,
+            &format!(
+                "Import {} {source} key bindings from {}?",
+                keybindings.len(),
+                truncate_and_remove_front(&keybindings.path.to_string_lossy(), 128),
+            ),
+            Some(
+                "Imported key bindings are appended to your keymap file, \
+                taking precedence over the default ones.",
+            ),
+            &["Import", "Cancel"],
+            cx,
+        )
+    })?;
+    if prompt.await.ok() != Some(0) {
+        return Ok(());
+    }
+
+    let import = keybindings.import();
+    if !import.imported.is_empty() {
+        let keymap_contents = KeymapFile::load_keymap_file(&fs)
+            .await
+            .context("Failed to load keymap file")?;
+        let tab_size = settings::infer_json_indent_size(&keymap_contents);
+        let updated_keymap_contents = import.append_to_keymap_text(&keymap_contents, tab_size);
+        fs.write(
+            paths::keymap_file().as_path(),
+            updated_keymap_contents.as_bytes(),
+        )
+        .await
+        .context("Failed to write keymap file")?;
+    }
+    log::info!(
+        "Imported {} of {} {source} key bindings from {}",
+        import.imported.len(),
+        keybindings.len(),
+        keybindings.path.display()
+    );
+    telemetry::event!(
+        "Keybindings Imported",
+        source = source.to_string(),
+        imported = import.imported.len(),
+        unmapped = import.unmapped.len()
+    );
+
+    workspace.update_in(cx, |workspace, window, cx| {
+        workspace.toggle_modal(window, cx, |_, cx| {
+            KeybindingsImportSummary::new(source, import, cx)
+        });
+    })?;
+    Ok(())
+}
+
+/// Summarizes an import, listing the bindings that need to be recreated by hand.
+pub struct KeybindingsImportSummary {
+    source: VsCodeSettingsSource,
+    import: VsCodeKeymapImport,
+    focus_handle: FocusHandle,
+}
+
+impl KeybindingsImportSummary {
+    fn new(source: VsCodeSettingsSource, import: VsCodeKeymapImport, cx: &mut App) -> Self {
+        Self {
+            source,
+            import,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_unmapped(&self, cx: &Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("unmapped-keybindings")
+            .max_h(rems(20.))
+            .overflow_y_scroll()
+            .gap_1()
+            .children(
+                self.import
+                    .unmapped
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, binding)| {
+                        v_flex()
+                            .id(ix)
+                            .p_1()
+                            .rounded_sm()
+                            .bg(cx.theme().colors().element_background)
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(Label::new(binding.key.clone()).buffer_font(cx))
+                                    .child(
+                                        Label::new(binding.command.clone())
+                                            .buffer_font(cx)
+                                            .color(Color::Muted),
+                                    ),
+                            )
+                            .child(
+                                Label::new(binding.reason.to_string())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Warning),
+                            )
+                    }),
+            )
+    }
+}
+
+impl EventEmitter<DismissEvent> for KeybindingsImportSummary {}
+
+impl Focusable for KeybindingsImportSummary {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl ModalView for KeybindingsImportSummary {}
+
+impl Render for KeybindingsImportSummary {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let imported = self.import.imported.len();
+        let unmapped = self.import.unmapped.len();
+        let message = if unmapped == 0 {
+            format!(
+                "Imported all {imported} of your {} key bindings.",
+                self.source
+            )
+        } else {
+            format!(
+                "Imported {imported} of {} {} key bindings. The following have no Zed \
+                equivalent and can be recreated in the keymap editor.",
+                imported + unmapped,
+                self.source
+            )
+        };
+
+        div()
+            .track_focus(&self.focus_handle)
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::cancel))
+            .w(rems(34.))
+            .child(
+                Modal::new("keybindings-import-summary", None)
+                    .header(
+                        ModalHeader::new().show_dismiss_button(true).child(
+                            Headline::new(format!("{} Key Bindings Imported", self.source))
+                                .size(HeadlineSize::Small),
+                        ),
+                    )
+                    .section(
+                        Section::new()
+                            .child(Label::new(message))
+                            .when(unmapped > 0, |section| {
+                                section.child(self.render_unmapped(cx))
+                            }),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Button::new("open-keymap-editor", "Open Keymap Editor")
+                                        .layer(ElevationIndex::ModalSurface)
+                                        .on_click(cx.listener(|_, _, window, cx| {
+                                            window.dispatch_action(OpenKeymap.boxed_clone(), cx);
+                                            cx.emit(DismissEvent);
+                                        })),
+                                )
+                                .child(
+                                    Button::new("done", "Done")
+                                        .style(ButtonStyle::Filled)
+                                        .layer(ElevationIndex::ModalSurface)
+                                        .on_click(cx.listener(|_, _, _, cx| {
+                                            cx.emit(DismissEvent);
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
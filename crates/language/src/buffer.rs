@@ -5,6 +5,7 @@ use crate::{
     RunnableTag, TextObject, TreeSitterOptions,
     diagnostic_set::{DiagnosticEntry, DiagnosticEntryRef, DiagnosticGroup},
     language_settings::{LanguageSettings, language_settings},
+    operation_log::{OperationLog, OperationLogEntry},
     outline::OutlineItem,
     row_chunk::RowChunks,
     syntax_map::{
@@ -143,6 +144,8 @@ pub struct LanguageBuffer {
     tree_sitter_data: Arc<TreeSitterData>,
     encoding: &'static Encoding,
     has_bom: bool,
+    /// The operations applied since a recording was started, if one is in progress.
+    operation_log: Option<OperationLog>,
 }
 
 #[derive(Debug)]
@@ -1150,6 +1153,7 @@ impl LanguageBuffer {
             _subscriptions: Vec::new(),
             encoding: encoding_rs::UTF_8,
             has_bom: false,
+            operation_log: None,
         }
     }
 
@@ -2850,6 +2854,15 @@ impl LanguageBuffer {
         let was_dirty = self.is_dirty();
         let old_version = self.version.clone();
         let mut deferred_ops = Vec::new();
+        let ops = ops.into_iter().collect::<Vec<_>>();
+        if let Some(operation_log) = &mut self.operation_log {
+            for op in &ops {
+                operation_log.push(OperationLogEntry::Operation {
+                    operation: op.clone(),
+                    is_local: false,
+                });
+            }
+        }
         let buffer_ops = ops
             .into_iter()
             .filter_map(|op| match op {
@@ -3012,12 +3025,71 @@ impl LanguageBuffer {
 
     fn send_operation(&mut self, operation: Operation, is_local: bool, cx: &mut Context<Self>) {
         self.was_changed();
+        // Operations from other replicas are recorded when they're received, in `apply_ops`.
+        if is_local && let Some(operation_log) = &mut self.operation_log {
+            operation_log.push(OperationLogEntry::Operation {
+                operation: operation.clone(),
+                is_local,
+            });
+        }
         cx.emit(BufferEvent::Operation {
             operation,
             is_local,
         });
     }
 
+    /// Starts recording the operations applied to the buffer, replacing any recording in
+    /// progress. The recording starts with the buffer's history, so that it can be replayed from
+    /// the buffer's base text.
+    pub fn start_recording_operations(&mut self, cx: &App) {
+        let history = self
+            .text
+            .operations()
+            .iter()
+            .map(|(_, operation)| Operation::Buffer(operation.clone()))
+            .chain(
+                self.remote_selections
+                    .iter()
+                    .map(|(_, set)| Operation::UpdateSelections {
+                        selections: set.selections.clone(),
+                        lamport_timestamp: set.lamport_timestamp,
+                        line_mode: set.line_mode,
+                        cursor_shape: set.cursor_shape,
+                    }),
+            );
+        self.operation_log = Some(OperationLog::new(
+            self.to_proto(cx),
+            self.replica_id(),
+            history,
+        ));
+    }
+
+    /// Stops recording operations, returning what was recorded.
+    pub fn stop_recording_operations(&mut self) -> Option<OperationLog> {
+        self.operation_log.take()
+    }
+
+    pub fn is_recording_operations(&self) -> bool {
+        self.operation_log.is_some()
+    }
+
+    /// Records the edits a language server requested, if operations are being recorded, so that
+    /// a recording shows which of its operations came from language servers.
+    pub fn record_language_server_edits(
+        &mut self,
+        server_id: LanguageServerId,
+        version: Option<i32>,
+        edits: &[lsp::TextEdit],
+    ) {
+        if let Some(operation_log) = &mut self.operation_log {
+            operation_log.push(OperationLogEntry::LanguageServerEdits {
+                server_id,
+                version,
+                edits: edits.to_vec(),
+            });
+        }
+    }
+
     /// Removes the selections for a given peer.
     pub fn remove_peer(&mut self, replica_id: ReplicaId, cx: &mut Context<Self>) {
         self.remote_selections.remove(&replica_id);
@@ -3024,6 +3024,101 @@ fn test_serialization(cx: &mut gpui::App) {
     assert_eq!(buffer2.read(cx).text(), "abcDF");
 }
 
+#[gpui::test]
+fn test_operation_log_replay(cx: &mut gpui::App) {
+    let buffer1 = cx.new(|cx| {
+        let mut buffer = LanguageBuffer::local("abc", cx);
+        buffer.edit([(3..3, "D")], None, EditType::Other, cx);
+        buffer.start_recording_operations(cx);
+        buffer.edit([(0..0, "1")], None, EditType::Other, cx);
+        buffer
+    });
+    let buffer2 = cx.new(|cx| {
+        let state = buffer1.read(cx).to_proto(cx);
+        LanguageBuffer::from_proto(ReplicaId::new(1), Capability::ReadWrite, state, None).unwrap()
+    });
+    let ops = cx
+        .background_executor()
+        .block(buffer1.read(cx).serialize_ops(None, cx));
+    buffer2.update(cx, |buffer, cx| {
+        buffer.apply_ops(
+            ops.into_iter().map(|op| deserialize_operation(op).unwrap()),
+            cx,
+        );
+        buffer.edit([(5..5, "E")], None, EditType::Other, cx);
+    });
+
+    // Exchange the replicas' concurrent edits.
+    let version1 = buffer1.read(cx).version();
+    buffer1.update(cx, |buffer, cx| {
+        buffer.edit([(2..3, "")], None, EditType::Other, cx);
+        let selections = [Selection {
+            id: 0,
+            start: buffer.anchor_before(1),
+            end: buffer.anchor_after(3),
+            reversed: false,
+            goal: SelectionGoal::None,
+        }];
+        buffer.set_active_selections(selections.into(), false, CursorShape::Bar, cx);
+        buffer.record_language_server_edits(
+            LanguageServerId(0),
+            Some(1),
+            &[lsp::TextEdit {
+                range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 1)),
+                new_text: "2".to_string(),
+            }],
+        );
+    });
+    let ops2 = cx
+        .background_executor()
+        .block(buffer2.read(cx).serialize_ops(Some(version1), cx));
+    buffer1.update(cx, |buffer, cx| {
+        buffer.apply_ops(
+            ops2.into_iter()
+                .map(|op| deserialize_operation(op).unwrap()),
+            cx,
+        );
+    });
+    assert_eq!(buffer1.read(cx).text(), "1acDE");
+    buffer1.update(cx, |buffer, cx| {
+        buffer.undo(cx);
+    });
+
+    let operation_log = buffer1
+        .update(cx, |buffer, _| buffer.stop_recording_operations())
+        .unwrap();
+    let operation_log = OperationLog::from_bytes(&operation_log.to_bytes()).unwrap();
+    assert!(operation_log.entries().iter().any(|entry| matches!(
+        entry,
+        OperationLogEntry::LanguageServerEdits { edits, .. } if edits.len() == 1
+    )));
+
+    let replayed = operation_log.replay(cx).unwrap();
+    assert_eq!(replayed.read(cx).text(), buffer1.read(cx).text());
+    assert_eq!(replayed.read(cx).version(), buffer1.read(cx).version());
+    let selection_ranges = |buffer: &Entity<LanguageBuffer>, cx: &App| {
+        let snapshot = buffer.read(cx).snapshot();
+        snapshot
+            .selections_in_range(Anchor::MIN..Anchor::MAX, true)
+            .flat_map(|(_, _, _, selections)| {
+                selections
+                    .map(|selection| selection.range().to_offset(&snapshot))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        selection_ranges(&replayed, cx),
+        selection_ranges(&buffer1, cx)
+    );
+
+    // Replaying part of the log reproduces the buffer at that point.
+    let mut partial_log = operation_log.clone();
+    partial_log.truncate(2);
+    let replayed = partial_log.replay(cx).unwrap();
+    assert_eq!(replayed.read(cx).text(), "1abcD");
+}
+
 #[gpui::test]
 fn test_branch_and_merge(cx: &mut TestAppContext) {
     cx.update(|cx| init_settings(cx, |_| {}));
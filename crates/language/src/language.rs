@@ -12,6 +12,7 @@ mod highlight_map;
 mod language_registry;
 pub mod language_settings;
 mod manifest;
+mod operation_log;
 mod outline;
 pub mod proto;
 mod syntax_map;
@@ -86,6 +87,7 @@ pub use language_registry::{
     QUERY_FILENAME_PREFIXES,
 };
 pub use lsp::{LanguageServerId, LanguageServerName};
+pub use operation_log::{OperationLog, OperationLogEntry};
 pub use outline::*;
 pub use syntax_map::{
     OwnedSyntaxLayer, SyntaxLayer, SyntaxMapMatches, ToTreeSitterPoint, TreeSitterOptions,
@@ -389,6 +389,8 @@ pub struct EditPredictionSettings {
     pub copilot: CopilotSettings,
     /// Settings specific to Codestral.
     pub codestral: CodestralSettings,
+    /// Settings for the model used by the `local` provider.
+    pub local: LocalEditPredictionSettings,
     /// Whether edit predictions are enabled in the assistant panel.
     /// This setting has no effect if globally disabled.
     pub enabled_in_text_threads: bool,
@@ -436,6 +438,16 @@ pub struct CodestralSettings {
     pub api_url: Option<String>,
 }
 
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LocalEditPredictionSettings {
+    /// Base URL of the OpenAI-compatible server to request completions from.
+    pub api_url: Option<String>,
+    /// Model to request completions from.
+    pub model: Option<String>,
+    /// Maximum tokens to generate.
+    pub max_tokens: Option<u32>,
+}
+
 impl AllLanguageSettings {
     /// Returns the [`LanguageSettings`] for the language with the specified name.
     pub fn language<'a>(
@@ -662,6 +674,13 @@ impl settings::Settings for AllLanguageSettings {
             api_url: codestral.api_url,
         };
 
+        let local = edit_predictions.local.unwrap();
+        let local_settings = LocalEditPredictionSettings {
+            api_url: local.api_url,
+            model: local.model,
+            max_tokens: local.max_tokens,
+        };
+
         let enabled_in_text_threads = edit_predictions.enabled_in_text_threads.unwrap();
 
         let mut file_types: FxHashMap<Arc<str>, (GlobSet, Vec<String>)> = FxHashMap::default();
@@ -700,6 +719,7 @@ impl settings::Settings for AllLanguageSettings {
                 mode: edit_predictions_mode,
                 copilot: copilot_settings,
                 codestral: codestral_settings,
+                local: local_settings,
                 enabled_in_text_threads,
                 examples_dir: edit_predictions.examples_dir,
                 example_capture_rate: edit_predictions.example_capture_rate,
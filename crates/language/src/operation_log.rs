@@ -0,0 +1,191 @@
+//! Recordings of the operations applied to a buffer, which can be replayed to reproduce bugs
+//! that depend on a particular sequence of edits, such as corruption while collaborating.
+
+use crate::{
+    Capability, LanguageBuffer, Operation,
+    proto::{deserialize_lsp_edit, deserialize_operation, serialize_lsp_edit, serialize_operation},
+};
+use anyhow::{Context as _, Result};
+use clock::ReplicaId;
+use gpui::{App, AppContext as _, Entity};
+use lsp::LanguageServerId;
+use rpc::proto::{self, Message as _, buffer_operation_log_entry};
+
+/// The operations applied to a buffer since a recording was started, along with the state
+/// needed to apply them again to a new buffer.
+#[derive(Clone, Debug)]
+pub struct OperationLog {
+    state: proto::BufferState,
+    replica_id: ReplicaId,
+    entries: Vec<OperationLogEntry>,
+}
+
+#[derive(Clone, Debug)]
+pub enum OperationLogEntry {
+    /// An operation applied to the buffer, which was either made by this replica or received
+    /// from another one.
+    Operation {
+        operation: Operation,
+        is_local: bool,
+    },
+    /// Edits requested by a language server. They're applied as separate operations, once
+    /// they've been resolved against the buffer version they were made for.
+    LanguageServerEdits {
+        server_id: LanguageServerId,
+        version: Option<i32>,
+        edits: Vec<lsp::TextEdit>,
+    },
+}
+
+impl OperationLog {
+    /// Starts a log from a buffer's base text and the operations already applied to it, so that
+    /// later operations can be resolved against the same history when the log is replayed.
+    pub(crate) fn new(
+        state: proto::BufferState,
+        replica_id: ReplicaId,
+        history: impl IntoIterator<Item = Operation>,
+    ) -> Self {
+        Self {
+            state,
+            replica_id,
+            entries: history
+                .into_iter()
+                .map(|operation| OperationLogEntry::Operation {
+                    is_local: operation_replica_id(&operation) == replica_id,
+                    operation,
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, entry: OperationLogEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn replica_id(&self) -> ReplicaId {
+        self.replica_id
+    }
+
+    pub fn entries(&self) -> &[OperationLogEntry] {
+        &self.entries
+    }
+
+    /// Drops the entries after the first `len`, such as to find the operation that a bug first
+    /// appears after.
+    pub fn truncate(&mut self, len: usize) {
+        self.entries.truncate(len);
+    }
+
+    /// Encodes the log as its header message followed by each entry, prefixed with its length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let header = proto::BufferOperationLog {
+            state: Some(self.state.clone()),
+            replica_id: self.replica_id.as_u16() as u32,
+        };
+        header.encode_length_delimited(&mut bytes).ok();
+        for entry in &self.entries {
+            let message = match entry {
+                OperationLogEntry::Operation {
+                    operation,
+                    is_local,
+                } => proto::BufferOperationLogEntry {
+                    variant: Some(buffer_operation_log_entry::Variant::Operation(
+                        serialize_operation(operation),
+                    )),
+                    is_local: *is_local,
+                },
+                OperationLogEntry::LanguageServerEdits {
+                    server_id,
+                    version,
+                    edits,
+                } => proto::BufferOperationLogEntry {
+                    variant: Some(buffer_operation_log_entry::Variant::LanguageServerEdits(
+                        buffer_operation_log_entry::LanguageServerEdits {
+                            server_id: server_id.to_proto(),
+                            version: *version,
+                            edits: edits.iter().cloned().map(serialize_lsp_edit).collect(),
+                        },
+                    )),
+                    is_local: false,
+                },
+            };
+            message.encode_length_delimited(&mut bytes).ok();
+        }
+        bytes
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        let header = proto::BufferOperationLog::decode_length_delimited(&mut bytes)
+            .context("decoding operation log header")?;
+        let mut entries = Vec::new();
+        while !bytes.is_empty() {
+            let entry = proto::BufferOperationLogEntry::decode_length_delimited(&mut bytes)
+                .with_context(|| format!("decoding operation log entry {}", entries.len()))?;
+            let entry = match entry.variant.context("missing operation log entry")? {
+                buffer_operation_log_entry::Variant::Operation(operation) => {
+                    OperationLogEntry::Operation {
+                        operation: deserialize_operation(operation)?,
+                        is_local: entry.is_local,
+                    }
+                }
+                buffer_operation_log_entry::Variant::LanguageServerEdits(edits) => {
+                    OperationLogEntry::LanguageServerEdits {
+                        server_id: LanguageServerId::from_proto(edits.server_id),
+                        version: edits.version,
+                        edits: edits
+                            .edits
+                            .into_iter()
+                            .filter_map(deserialize_lsp_edit)
+                            .collect(),
+                    }
+                }
+            };
+            entries.push(entry);
+        }
+        Ok(Self {
+            state: header.state.context("missing buffer state")?,
+            replica_id: ReplicaId::new(header.replica_id as u16),
+            entries,
+        })
+    }
+
+    /// Creates a buffer from the log's initial state and applies each of its operations in the
+    /// order they were recorded, which reproduces the recorded buffer's contents, history and
+    /// selections.
+    pub fn replay(&self, cx: &mut App) -> Result<Entity<LanguageBuffer>> {
+        let buffer = LanguageBuffer::from_proto(
+            self.replica_id,
+            Capability::ReadWrite,
+            self.state.clone(),
+            None,
+        )?;
+        let buffer = cx.new(|_| buffer);
+        buffer.update(cx, |buffer, cx| {
+            for entry in &self.entries {
+                if let OperationLogEntry::Operation { operation, .. } = entry {
+                    buffer.apply_ops([operation.clone()], cx);
+                }
+            }
+        });
+        Ok(buffer)
+    }
+}
+
+fn operation_replica_id(operation: &Operation) -> ReplicaId {
+    match operation {
+        Operation::Buffer(operation) => operation.timestamp().replica_id,
+        Operation::UpdateSelections {
+            lamport_timestamp, ..
+        }
+        | Operation::UpdateDiagnostics {
+            lamport_timestamp, ..
+        }
+        | Operation::UpdateCompletionTriggers {
+            lamport_timestamp, ..
+        }
+        | Operation::UpdateLineEnding {
+            lamport_timestamp, ..
+        } => lamport_timestamp.replica_id,
+    }
+}
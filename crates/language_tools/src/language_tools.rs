@@ -1,6 +1,8 @@
 mod key_context_view;
+mod log_view;
 pub mod lsp_button;
 pub mod lsp_log_view;
+mod mcp_inspector;
 mod syntax_tree_view;
 
 #[cfg(test)]
@@ -17,6 +19,8 @@ pub fn init(cx: &mut App) {
     lsp_log_view::init(false, cx);
     syntax_tree_view::init(cx);
     key_context_view::init(cx);
+    log_view::init(cx);
+    mcp_inspector::init(cx);
 }
 
 fn get_or_create_tool<T>(
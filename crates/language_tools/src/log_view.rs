@@ -0,0 +1,648 @@
+use std::{collections::VecDeque, fmt::Write as _, ops::Range, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use editor::{Editor, EditorEvent};
+use fs::Fs;
+use gpui::{
+    App, AppContext as _, ClipboardItem, Corner, DismissEvent, Entity, EventEmitter, FocusHandle,
+    Focusable, MouseButton, MouseDownEvent, Pixels, Point, Render, ScrollStrategy, Subscription,
+    Task, UniformListScrollHandle, WeakEntity, actions, anchored, deferred, uniform_list,
+};
+use regex::Regex;
+use ui::{ContextMenu, DropdownMenu, DropdownStyle, Tooltip, prelude::*};
+use workspace::{Item, SplitDirection, Toast, Workspace, notifications::NotificationId};
+use zlog::sink::{MemoryRecord, SINK_MEMORY_RECORDS_MAX};
+
+use crate::get_or_create_tool;
+
+actions!(
+    dev,
+    [
+        /// Opens a view of Zed's logs that can be filtered by level, scope and contents.
+        OpenLogView
+    ]
+);
+
+/// How often the view checks for new log messages.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+const LEVELS: [log::LevelFilter; 6] = [
+    log::LevelFilter::Off,
+    log::LevelFilter::Error,
+    log::LevelFilter::Warn,
+    log::LevelFilter::Info,
+    log::LevelFilter::Debug,
+    log::LevelFilter::Trace,
+];
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &OpenLogView, window, cx| {
+            let fs = workspace.app_state().fs.clone();
+            let workspace_handle = cx.weak_entity();
+            get_or_create_tool(
+                workspace,
+                SplitDirection::Right,
+                window,
+                cx,
+                move |window, cx| LogView::new(fs, workspace_handle, window, cx),
+            );
+        });
+    })
+    .detach();
+}
+
+/// Which log messages are shown.
+#[derive(Debug)]
+struct LogFilter {
+    /// The most verbose level shown.
+    level: log::LevelFilter,
+    /// A prefix of the scopes or module paths shown, such as `project` or `editor::element`.
+    scope: String,
+    query: Option<LogQuery>,
+}
+
+#[derive(Debug)]
+enum LogQuery {
+    /// Case-insensitive text, stored in lowercase.
+    Text(String),
+    Regex(Regex),
+}
+
+impl LogFilter {
+    fn matches(&self, record: &MemoryRecord) -> bool {
+        if record.level > self.level || !record.scope.starts_with(&self.scope) {
+            return false;
+        }
+        match &self.query {
+            Some(LogQuery::Text(text)) => record.message.to_lowercase().contains(text),
+            Some(LogQuery::Regex(regex)) => regex.is_match(&record.message),
+            None => true,
+        }
+    }
+}
+
+pub struct LogView {
+    fs: Arc<dyn Fs>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    query_editor: Entity<Editor>,
+    scope_editor: Entity<Editor>,
+    is_regex: bool,
+    query_error: Option<SharedString>,
+    filter: LogFilter,
+    /// The messages received since the view was opened or cleared, oldest first.
+    records: VecDeque<MemoryRecord>,
+    /// The ids of the messages that match the filter, in the order they were logged.
+    filtered_ids: Vec<u64>,
+    last_record_id: Option<u64>,
+    /// Whether to keep the newest message in view as messages are logged.
+    follow: bool,
+    scroll_handle: UniformListScrollHandle,
+    context_menu: Option<(Entity<ContextMenu>, Point<Pixels>, Subscription)>,
+    _poll_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LogView {
+    pub fn new(
+        fs: Arc<dyn Fs>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let query_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Filter messages…", window, cx);
+            editor
+        });
+        let scope_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Scope or module, such as project::lsp_store", window, cx);
+            editor
+        });
+        let subscriptions = vec![
+            cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    this.update_filter(cx);
+                }
+            }),
+            cx.subscribe(&scope_editor, |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    this.update_filter(cx);
+                }
+            }),
+        ];
+        let poll_task = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(POLL_INTERVAL).await;
+                if this.update(cx, |this, cx| this.poll(cx)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut this = Self {
+            fs,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            query_editor,
+            scope_editor,
+            is_regex: false,
+            query_error: None,
+            filter: LogFilter {
+                level: log::LevelFilter::Trace,
+                scope: String::new(),
+                query: None,
+            },
+            records: VecDeque::new(),
+            filtered_ids: Vec::new(),
+            last_record_id: None,
+            follow: true,
+            scroll_handle: UniformListScrollHandle::new(),
+            context_menu: None,
+            _poll_task: poll_task,
+            _subscriptions: subscriptions,
+        };
+        this.poll(cx);
+        this
+    }
+
+    fn poll(&mut self, cx: &mut Context<Self>) {
+        let new_records = zlog::sink::memory_records_after(self.last_record_id);
+        let Some(last_record) = new_records.last() else {
+            return;
+        };
+        self.last_record_id = Some(last_record.id);
+        for record in new_records {
+            if self.filter.matches(&record) {
+                self.filtered_ids.push(record.id);
+            }
+            self.records.push_back(record);
+        }
+        if self.records.len() > SINK_MEMORY_RECORDS_MAX {
+            let dropped = self.records.len() - SINK_MEMORY_RECORDS_MAX;
+            self.records.drain(..dropped);
+            let first_id = self.records.front().map_or(0, |record| record.id);
+            let dropped_filtered = self.filtered_ids.partition_point(|id| *id < first_id);
+            self.filtered_ids.drain(..dropped_filtered);
+        }
+        self.scroll_to_end_if_following();
+        cx.notify();
+    }
+
+    fn record(&self, id: u64) -> Option<&MemoryRecord> {
+        let first_id = self.records.front()?.id;
+        self.records.get(id.checked_sub(first_id)? as usize)
+    }
+
+    fn update_filter(&mut self, cx: &mut Context<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        self.query_error = None;
+        self.filter.query = if query.is_empty() {
+            None
+        } else if self.is_regex {
+            match Regex::new(&query) {
+                Ok(regex) => Some(LogQuery::Regex(regex)),
+                Err(error) => {
+                    self.query_error = Some(error.to_string().into());
+                    None
+                }
+            }
+        } else {
+            Some(LogQuery::Text(query.to_lowercase()))
+        };
+        self.filter.scope = self.scope_editor.read(cx).text(cx).trim().to_string();
+        self.filtered_ids = self
+            .records
+            .iter()
+            .filter(|record| self.filter.matches(record))
+            .map(|record| record.id)
+            .collect();
+        self.scroll_to_end_if_following();
+        cx.notify();
+    }
+
+    fn scroll_to_end_if_following(&self) {
+        if self.follow && !self.filtered_ids.is_empty() {
+            self.scroll_handle
+                .scroll_to_item(self.filtered_ids.len() - 1, ScrollStrategy::Bottom);
+        }
+    }
+
+    fn set_level(&mut self, level: log::LevelFilter, cx: &mut Context<Self>) {
+        self.filter.level = level;
+        self.update_filter(cx);
+    }
+
+    fn toggle_regex(&mut self, cx: &mut Context<Self>) {
+        self.is_regex = !self.is_regex;
+        self.update_filter(cx);
+    }
+
+    fn toggle_follow(&mut self, cx: &mut Context<Self>) {
+        self.follow = !self.follow;
+        self.scroll_to_end_if_following();
+        cx.notify();
+    }
+
+    fn clear(&mut self, cx: &mut Context<Self>) {
+        self.records.clear();
+        self.filtered_ids.clear();
+        cx.notify();
+    }
+
+    fn show_only_scope(&mut self, scope: &str, window: &mut Window, cx: &mut Context<Self>) {
+        self.scope_editor.update(cx, |editor, cx| {
+            editor.set_text(scope, window, cx);
+        });
+    }
+
+    /// Saves the messages that match the current filter, in the same format as the log file.
+    fn export(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let mut text = String::new();
+        for id in &self.filtered_ids {
+            if let Some(record) = self.record(*id) {
+                format_record(record, &mut text);
+            }
+        }
+        let fs = self.fs.clone();
+        let workspace = self.workspace.clone();
+        let save_path = cx.prompt_for_new_path(paths::logs_dir(), Some("Zed-filtered.log"));
+        cx.spawn_in(window, async move |_, cx| {
+            let Some(path) = save_path.await?? else {
+                return Ok(());
+            };
+            fs.write(&path, text.as_bytes())
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            workspace.update(cx, |workspace, cx| {
+                struct LogExported;
+                workspace.show_toast(
+                    Toast::new(
+                        NotificationId::unique::<LogExported>(),
+                        format!("Saved logs to {}", path.display()),
+                    )
+                    .autohide(),
+                    cx,
+                );
+            })
+        })
+        .detach_and_prompt_err("Failed to export logs", window, cx, |_, _, _| None);
+    }
+
+    fn deploy_context_menu(
+        &mut self,
+        id: u64,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(record) = self.record(id) else {
+            return;
+        };
+        let scope = record.scope.clone();
+        let message = record.message.clone();
+        let level_override = zlog::filter::scope_level_overrides()
+            .into_iter()
+            .find_map(|(override_scope, level)| (override_scope == scope).then_some(level));
+        let this = cx.weak_entity();
+        let context_menu = ContextMenu::build(window, cx, move |mut menu, _, _| {
+            menu = menu
+                .entry("Copy Message", None, move |_, cx| {
+                    cx.write_to_clipboard(ClipboardItem::new_string(message.clone()));
+                })
+                .entry("Show Only This Scope", None, {
+                    let scope = scope.clone();
+                    let this = this.clone();
+                    move |window, cx| {
+                        this.update(cx, |this, cx| this.show_only_scope(&scope, window, cx))
+                            .ok();
+                    }
+                })
+                .separator()
+                .header(format!("Log {scope} At"));
+            for level in LEVELS.into_iter().rev() {
+                let scope = scope.clone();
+                menu = menu.toggleable_entry(
+                    level_label(level),
+                    level_override == Some(level),
+                    IconPosition::Start,
+                    None,
+                    move |_, _| zlog::filter::set_scope_level_override(&scope, Some(level)),
+                );
+            }
+            menu.when(level_override.is_some(), |menu| {
+                menu.entry("Use Level From Settings", None, move |_, _| {
+                    zlog::filter::set_scope_level_override(&scope, None)
+                })
+            })
+        });
+        window.focus(&context_menu.focus_handle(cx), cx);
+        let subscription = cx.subscribe(&context_menu, |this, _, _: &DismissEvent, cx| {
+            this.context_menu.take();
+            cx.notify();
+        });
+        self.context_menu = Some((context_menu, position, subscription));
+        cx.notify();
+    }
+
+    fn render_rows(&self, range: Range<usize>, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        self.filtered_ids[range]
+            .iter()
+            .filter_map(|id| {
+                let record = self.record(*id)?;
+                let id = *id;
+                let (level, color) = level_label_and_color(record.level);
+                Some(
+                    h_flex()
+                        .id(("log-record", id as usize))
+                        .w_full()
+                        .px_2()
+                        .gap_2()
+                        .hover(|style| style.bg(cx.theme().colors().element_hover))
+                        .on_mouse_down(
+                            MouseButton::Right,
+                            cx.listener(move |this, event: &MouseDownEvent, window, cx| {
+                                this.deploy_context_menu(id, event.position, window, cx);
+                            }),
+                        )
+                        .child(
+                            Label::new(record.timestamp.format("%H:%M:%S%.3f").to_string())
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            Label::new(level)
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .color(color),
+                        )
+                        .child(
+                            Label::new(format!("[{}]", record.scope))
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .color(Color::Accent),
+                        )
+                        .child(
+                            Label::new(record.message.replace('\n', " "))
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .single_line()
+                                .truncate(),
+                        )
+                        .into_any_element(),
+                )
+            })
+            .collect()
+    }
+
+    fn render_level_picker(&self, window: &mut Window, cx: &mut Context<Self>) -> DropdownMenu {
+        let this = cx.weak_entity();
+        let selected_level = self.filter.level;
+        DropdownMenu::new(
+            "log-level-picker",
+            level_label(selected_level),
+            ContextMenu::build(window, cx, move |mut menu, _, _| {
+                menu = menu.header("Show Messages Up To");
+                for level in LEVELS.into_iter().skip(1) {
+                    let this = this.clone();
+                    menu = menu.toggleable_entry(
+                        level_label(level),
+                        level == selected_level,
+                        IconPosition::Start,
+                        None,
+                        move |_, cx| {
+                            this.update(cx, |this, cx| this.set_level(level, cx)).ok();
+                        },
+                    );
+                }
+                menu
+            }),
+        )
+        .style(DropdownStyle::Outlined)
+    }
+
+    fn render_toolbar(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let override_count = zlog::filter::scope_level_overrides().len();
+        let input = |editor: Entity<Editor>, cx: &mut Context<Self>| {
+            h_flex()
+                .h_7()
+                .px_2()
+                .border_1()
+                .rounded_md()
+                .border_color(cx.theme().colors().border)
+                .child(editor)
+        };
+        h_flex()
+            .p_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                input(self.query_editor.clone(), cx)
+                    .flex_1()
+                    .when(self.query_error.is_some(), |this| {
+                        this.border_color(Color::Error.color(cx))
+                    })
+                    .child(
+                        IconButton::new("log-view-regex", IconName::Regex)
+                            .icon_size(IconSize::Small)
+                            .toggle_state(self.is_regex)
+                            .tooltip(Tooltip::text("Use Regular Expression"))
+                            .on_click(cx.listener(|this, _, _, cx| this.toggle_regex(cx))),
+                    ),
+            )
+            .child(input(self.scope_editor.clone(), cx).w(rems(18.)))
+            .child(self.render_level_picker(window, cx))
+            .when(override_count > 0, |this| {
+                this.child(
+                    Button::new(
+                        "log-view-reset-overrides",
+                        format!("Reset Scope Levels ({override_count})"),
+                    )
+                    .label_size(LabelSize::Small)
+                    .tooltip(Tooltip::text(
+                        "Use the log levels from your settings for all scopes",
+                    ))
+                    .on_click(|_, _, _| zlog::filter::clear_scope_level_overrides()),
+                )
+            })
+            .child(
+                IconButton::new("log-view-follow", IconName::ArrowDown)
+                    .icon_size(IconSize::Small)
+                    .toggle_state(self.follow)
+                    .tooltip(Tooltip::text("Scroll to New Messages"))
+                    .on_click(cx.listener(|this, _, _, cx| this.toggle_follow(cx))),
+            )
+            .child(
+                IconButton::new("log-view-export", IconName::Download)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Save Shown Messages"))
+                    .on_click(cx.listener(|this, _, window, cx| this.export(window, cx))),
+            )
+            .child(
+                IconButton::new("log-view-clear", IconName::Trash)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Clear Messages"))
+                    .on_click(cx.listener(|this, _, _, cx| this.clear(cx))),
+            )
+    }
+}
+
+/// Appends a log message to `text` in the format of the log file.
+fn format_record(record: &MemoryRecord, text: &mut String) {
+    let (level, _) = level_label_and_color(record.level);
+    write!(
+        text,
+        "{} {level:<5} [{}",
+        record.timestamp.format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+        record.scope
+    )
+    .ok();
+    if let Some(line) = record.line {
+        write!(text, ":{line}").ok();
+    }
+    writeln!(text, "] {}", record.message).ok();
+}
+
+fn level_label(level: log::LevelFilter) -> &'static str {
+    match level {
+        log::LevelFilter::Off => "Off",
+        log::LevelFilter::Error => "Error",
+        log::LevelFilter::Warn => "Warn",
+        log::LevelFilter::Info => "Info",
+        log::LevelFilter::Debug => "Debug",
+        log::LevelFilter::Trace => "Trace",
+    }
+}
+
+fn level_label_and_color(level: log::Level) -> (&'static str, Color) {
+    match level {
+        log::Level::Error => ("ERROR", Color::Error),
+        log::Level::Warn => ("WARN", Color::Warning),
+        log::Level::Info => ("INFO", Color::Info),
+        log::Level::Debug => ("DEBUG", Color::Muted),
+        log::Level::Trace => ("TRACE", Color::Hint),
+    }
+}
+
+impl EventEmitter<()> for LogView {}
+
+impl Focusable for LogView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for LogView {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(workspace::item::ItemEvent)) {}
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Logs".into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl Render for LogView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let count = self.filtered_ids.len();
+        v_flex()
+            .key_context("LogView")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_toolbar(window, cx))
+            .when_some(self.query_error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .px_2()
+                        .pb_1()
+                        .child(Label::new(error).size(LabelSize::Small).color(Color::Error)),
+                )
+            })
+            .child(
+                uniform_list(
+                    "log-view-records",
+                    count,
+                    cx.processor(|this, range, _, cx| this.render_rows(range, cx)),
+                )
+                .track_scroll(&self.scroll_handle)
+                .flex_1()
+                .py_1(),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(format!(
+                            "Showing {count} of {} messages",
+                            self.records.len()
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    ),
+            )
+            .children(self.context_menu.as_ref().map(|(menu, position, _)| {
+                deferred(
+                    anchored()
+                        .position(*position)
+                        .anchor(Corner::TopLeft)
+                        .child(menu.clone()),
+                )
+                .with_priority(1)
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: log::Level, scope: &str, message: &str) -> MemoryRecord {
+        MemoryRecord {
+            id: 0,
+            timestamp: chrono::Local::now(),
+            level,
+            scope: scope.to_string(),
+            line: None,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let mut filter = LogFilter {
+            level: log::LevelFilter::Info,
+            scope: String::new(),
+            query: None,
+        };
+        assert!(filter.matches(&record(log::Level::Warn, "project", "a")));
+        assert!(!filter.matches(&record(log::Level::Debug, "project", "a")));
+
+        filter.scope = "project::lsp".to_string();
+        assert!(filter.matches(&record(log::Level::Info, "project::lsp_store", "a")));
+        assert!(!filter.matches(&record(log::Level::Info, "editor", "a")));
+
+        filter.query = Some(LogQuery::Text("started".to_string()));
+        assert!(filter.matches(&record(
+            log::Level::Info,
+            "project::lsp_store",
+            "Language server STARTED"
+        )));
+        assert!(!filter.matches(&record(log::Level::Info, "project::lsp_store", "stopped")));
+
+        filter.query = Some(LogQuery::Regex(Regex::new(r"^id \d+$").unwrap()));
+        assert!(filter.matches(&record(log::Level::Info, "project::lsp_store", "id 12")));
+        assert!(!filter.matches(&record(log::Level::Info, "project::lsp_store", "id x")));
+    }
+}
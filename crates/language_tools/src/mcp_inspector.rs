@@ -0,0 +1,1150 @@
+use std::{collections::VecDeque, ops::Range, sync::Arc};
+
+use collections::HashMap;
+use context_server::{
+    ContextServerId,
+    client::IoKind,
+    protocol::{InitializedContextServerProtocol, ServerCapability},
+    types::{
+        CallToolParams, LoggingLevel, LoggingSetLevelParams, MessageParams, Notification as _,
+        Prompt, PromptsGetParams, Resource, ResourceTemplate, ResourcesReadParams, Tool,
+        notifications, requests,
+    },
+};
+use editor::{EditType, Editor, MultiBufferOffset, scroll::Autoscroll};
+use gpui::{
+    App, AppContext as _, Entity, EventEmitter, FocusHandle, Focusable, Render, ScrollStrategy,
+    Subscription, Task, UniformListScrollHandle, WeakEntity, actions, uniform_list,
+};
+use language::language_settings::SoftWrap;
+use project::{
+    Project,
+    context_server_store::{self, ContextServerStatus, ContextServerStore},
+};
+use serde_json::Value;
+use ui::{ContextMenu, DropdownMenu, DropdownStyle, ListItem, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{Item, SplitDirection, Workspace};
+
+use crate::get_or_create_tool;
+
+actions!(
+    dev,
+    [
+        /// Opens an inspector showing the traffic, capabilities and logs of context (MCP) servers.
+        OpenMcpInspector
+    ]
+);
+
+/// How many raw messages are kept per server.
+const TRAFFIC_LIMIT: usize = 2048;
+/// How many `notifications/message` logs are kept per server.
+const LOG_LIMIT: usize = 2048;
+/// Messages longer than this are truncated in the traffic view.
+const MAX_SHOWN_MESSAGE_LEN: usize = 16 * 1024;
+
+const LEVELS: [LoggingLevel; 8] = [
+    LoggingLevel::Debug,
+    LoggingLevel::Info,
+    LoggingLevel::Notice,
+    LoggingLevel::Warning,
+    LoggingLevel::Error,
+    LoggingLevel::Critical,
+    LoggingLevel::Alert,
+    LoggingLevel::Emergency,
+];
+
+pub fn init(cx: &mut App) {
+    let log_store = cx.new(|_| McpLogStore::default());
+    cx.observe_new(move |workspace: &mut Workspace, _, cx| {
+        let project = workspace.project().clone();
+        log_store.update(cx, |log_store, cx| log_store.add_project(&project, cx));
+
+        let log_store = log_store.clone();
+        workspace.register_action(move |workspace, _: &OpenMcpInspector, window, cx| {
+            let project = workspace.project().clone();
+            let log_store = log_store.clone();
+            get_or_create_tool(
+                workspace,
+                SplitDirection::Right,
+                window,
+                cx,
+                move |window, cx| McpInspector::new(project, log_store, window, cx),
+            );
+        });
+    })
+    .detach();
+}
+
+/// Records what every context server in the open projects sends and receives, so the inspector
+/// can show what happened before it was opened.
+#[derive(Default)]
+pub struct McpLogStore {
+    projects: HashMap<WeakEntity<Project>, ProjectLogs>,
+}
+
+struct ProjectLogs {
+    servers: HashMap<ContextServerId, ServerLog>,
+    _subscriptions: [Subscription; 2],
+}
+
+#[derive(Default)]
+struct ServerLog {
+    /// Raw messages, oldest first.
+    traffic: VecDeque<TrafficEntry>,
+    /// How many messages were ever recorded, including the ones dropped from `traffic`.
+    traffic_count: usize,
+    /// Messages the server logged with `notifications/message`, oldest first.
+    logs: VecDeque<MessageParams>,
+}
+
+struct TrafficEntry {
+    kind: IoKind,
+    message: Arc<str>,
+}
+
+impl ServerLog {
+    fn push(&mut self, kind: IoKind, message: Arc<str>) {
+        if kind == IoKind::Receive
+            && let Some(log) = parse_log_message(&message)
+        {
+            if self.logs.len() == LOG_LIMIT {
+                self.logs.pop_front();
+            }
+            self.logs.push_back(log);
+        }
+        if self.traffic.len() == TRAFFIC_LIMIT {
+            self.traffic.pop_front();
+        }
+        self.traffic.push_back(TrafficEntry { kind, message });
+        self.traffic_count += 1;
+    }
+
+    /// The messages recorded after the first `count`, as far as they're still kept.
+    fn traffic_since(&self, count: usize) -> impl Iterator<Item = &TrafficEntry> {
+        let dropped = self.traffic_count - self.traffic.len();
+        self.traffic.iter().skip(count.saturating_sub(dropped))
+    }
+}
+
+impl McpLogStore {
+    fn add_project(&mut self, project: &Entity<Project>, cx: &mut Context<Self>) {
+        let weak_project = project.downgrade();
+        let context_server_store = project.read(cx).context_server_store();
+        self.projects.insert(
+            weak_project.clone(),
+            ProjectLogs {
+                servers: HashMap::default(),
+                _subscriptions: [
+                    cx.observe_release(project, {
+                        let weak_project = weak_project.clone();
+                        move |this, _, _| {
+                            this.projects.remove(&weak_project);
+                        }
+                    }),
+                    cx.subscribe(&context_server_store, move |this, _, event, cx| {
+                        if let context_server_store::Event::ServerMessage {
+                            server_id,
+                            kind,
+                            message,
+                        } = event
+                        {
+                            this.add_message(&weak_project, server_id, *kind, message.clone(), cx);
+                        }
+                    }),
+                ],
+            },
+        );
+    }
+
+    fn add_message(
+        &mut self,
+        project: &WeakEntity<Project>,
+        server_id: &ContextServerId,
+        kind: IoKind,
+        message: Arc<str>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project_logs) = self.projects.get_mut(project) else {
+            return;
+        };
+        project_logs
+            .servers
+            .entry(server_id.clone())
+            .or_default()
+            .push(kind, message);
+        cx.notify();
+    }
+
+    fn server_log(
+        &self,
+        project: &WeakEntity<Project>,
+        server_id: &ContextServerId,
+    ) -> Option<&ServerLog> {
+        self.projects.get(project)?.servers.get(server_id)
+    }
+
+    fn clear(&mut self, project: &WeakEntity<Project>, server_id: &ContextServerId) {
+        if let Some(project_logs) = self.projects.get_mut(project) {
+            project_logs.servers.remove(server_id);
+        }
+    }
+}
+
+/// Extracts the payload of a `notifications/message` log.
+fn parse_log_message(message: &str) -> Option<MessageParams> {
+    let message: Value = serde_json::from_str(message).ok()?;
+    if message.get("method")?.as_str()? != notifications::Message::METHOD {
+        return None;
+    }
+    serde_json::from_value(message.get("params")?.clone()).ok()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InspectorTab {
+    Traffic,
+    Capabilities,
+    Tools,
+    Prompts,
+    Resources,
+    Logs,
+}
+
+impl InspectorTab {
+    const ALL: [Self; 6] = [
+        Self::Traffic,
+        Self::Capabilities,
+        Self::Tools,
+        Self::Prompts,
+        Self::Resources,
+        Self::Logs,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Traffic => "Traffic",
+            Self::Capabilities => "Capabilities",
+            Self::Tools => "Tools",
+            Self::Prompts => "Prompts",
+            Self::Resources => "Resources",
+            Self::Logs => "Logs",
+        }
+    }
+}
+
+/// What the server offers, as listed when the inspector connected to it.
+#[derive(Default)]
+struct Catalog {
+    tools: Vec<Tool>,
+    prompts: Vec<Prompt>,
+    resources: Vec<Resource>,
+    resource_templates: Vec<ResourceTemplate>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CatalogEntry {
+    Tool(usize),
+    Prompt(usize),
+    Resource(usize),
+    ResourceTemplate(usize),
+}
+
+impl Catalog {
+    fn entries(&self, tab: InspectorTab) -> Vec<CatalogEntry> {
+        match tab {
+            InspectorTab::Tools => (0..self.tools.len()).map(CatalogEntry::Tool).collect(),
+            InspectorTab::Prompts => (0..self.prompts.len()).map(CatalogEntry::Prompt).collect(),
+            InspectorTab::Resources => (0..self.resources.len())
+                .map(CatalogEntry::Resource)
+                .chain((0..self.resource_templates.len()).map(CatalogEntry::ResourceTemplate))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn label(&self, entry: CatalogEntry) -> Option<(String, Option<&str>)> {
+        Some(match entry {
+            CatalogEntry::Tool(ix) => {
+                let tool = self.tools.get(ix)?;
+                (tool.name.clone(), tool.title.as_deref())
+            }
+            CatalogEntry::Prompt(ix) => {
+                let prompt = self.prompts.get(ix)?;
+                (prompt.name.clone(), prompt.title.as_deref())
+            }
+            CatalogEntry::Resource(ix) => {
+                let resource = self.resources.get(ix)?;
+                (resource.uri.to_string(), Some(resource.name.as_str()))
+            }
+            CatalogEntry::ResourceTemplate(ix) => {
+                let template = self.resource_templates.get(ix)?;
+                (template.uri_template.clone(), Some(template.name.as_str()))
+            }
+        })
+    }
+
+    /// The entry's definition, as the server sent it.
+    fn definition(&self, entry: CatalogEntry) -> Option<String> {
+        match entry {
+            CatalogEntry::Tool(ix) => serde_json::to_string_pretty(self.tools.get(ix)?),
+            CatalogEntry::Prompt(ix) => serde_json::to_string_pretty(self.prompts.get(ix)?),
+            CatalogEntry::Resource(ix) => serde_json::to_string_pretty(self.resources.get(ix)?),
+            CatalogEntry::ResourceTemplate(ix) => {
+                serde_json::to_string_pretty(self.resource_templates.get(ix)?)
+            }
+        }
+        .ok()
+    }
+
+    /// What to prefill the input with when the entry is selected.
+    fn input_template(&self, entry: CatalogEntry) -> Option<String> {
+        Some(match entry {
+            CatalogEntry::Tool(ix) => {
+                let properties = self.tools.get(ix)?.input_schema.get("properties");
+                let arguments = properties
+                    .and_then(Value::as_object)
+                    .map(|properties| {
+                        properties
+                            .keys()
+                            .map(|name| (name.clone(), Value::Null))
+                            .collect::<serde_json::Map<_, _>>()
+                    })
+                    .unwrap_or_default();
+                serde_json::to_string_pretty(&arguments).ok()?
+            }
+            CatalogEntry::Prompt(ix) => {
+                let arguments = self
+                    .prompts
+                    .get(ix)?
+                    .arguments
+                    .iter()
+                    .flatten()
+                    .map(|argument| (argument.name.clone(), Value::String(String::new())))
+                    .collect::<serde_json::Map<_, _>>();
+                serde_json::to_string_pretty(&arguments).ok()?
+            }
+            CatalogEntry::Resource(ix) => self.resources.get(ix)?.uri.to_string(),
+            CatalogEntry::ResourceTemplate(ix) => {
+                self.resource_templates.get(ix)?.uri_template.clone()
+            }
+        })
+    }
+}
+
+/// Shows what a context server is doing, and lets you call it by hand.
+pub struct McpInspector {
+    project: Entity<Project>,
+    log_store: Entity<McpLogStore>,
+    focus_handle: FocusHandle,
+    server_id: Option<ContextServerId>,
+    tab: InspectorTab,
+    traffic_editor: Entity<Editor>,
+    /// How many of the server's messages are in `traffic_editor`.
+    shown_traffic_count: usize,
+    capabilities_editor: Entity<Editor>,
+    catalog: Catalog,
+    catalog_error: Option<SharedString>,
+    selected_entry: Option<CatalogEntry>,
+    definition_editor: Entity<Editor>,
+    input_editor: Entity<Editor>,
+    result_editor: Entity<Editor>,
+    log_level: LoggingLevel,
+    /// How many logs were shown, to follow new ones as they arrive.
+    shown_log_count: usize,
+    log_scroll_handle: UniformListScrollHandle,
+    _load_task: Task<()>,
+    _invoke_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl McpInspector {
+    fn new(
+        project: Entity<Project>,
+        log_store: Entity<McpLogStore>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let context_server_store = project.read(cx).context_server_store();
+        let subscriptions = vec![
+            cx.observe_in(&log_store, window, |this, _, window, cx| {
+                this.show_new_traffic(window, cx);
+                this.follow_logs(cx);
+                cx.notify();
+            }),
+            cx.subscribe_in(
+                &context_server_store,
+                window,
+                |this, _, event, window, cx| {
+                    if let context_server_store::Event::ServerStatusChanged { server_id, .. } =
+                        event
+                        && this.server_id.as_ref() == Some(server_id)
+                    {
+                        this.reload(window, cx);
+                    }
+                },
+            ),
+        ];
+
+        let input_editor = cx.new(|cx| {
+            let mut editor = Editor::auto_height(1, 12, window, cx);
+            editor.set_placeholder_text("Arguments as JSON, or a resource URI", window, cx);
+            editor.set_show_gutter(false, cx);
+            editor
+        });
+        let server_id = context_server_store
+            .read(cx)
+            .server_ids(cx)
+            .into_iter()
+            .min_by(|a, b| a.0.cmp(&b.0));
+        let mut this = Self {
+            traffic_editor: read_only_editor(window, cx),
+            capabilities_editor: read_only_editor(window, cx),
+            definition_editor: read_only_editor(window, cx),
+            result_editor: read_only_editor(window, cx),
+            input_editor,
+            project,
+            log_store,
+            focus_handle: cx.focus_handle(),
+            server_id: None,
+            tab: InspectorTab::Traffic,
+            shown_traffic_count: 0,
+            catalog: Catalog::default(),
+            catalog_error: None,
+            selected_entry: None,
+            log_level: LoggingLevel::Debug,
+            shown_log_count: 0,
+            log_scroll_handle: UniformListScrollHandle::new(),
+            _load_task: Task::ready(()),
+            _invoke_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        if let Some(server_id) = server_id {
+            this.select_server(server_id, window, cx);
+        }
+        this
+    }
+
+    fn context_server_store(&self, cx: &App) -> Entity<ContextServerStore> {
+        self.project.read(cx).context_server_store()
+    }
+
+    fn protocol(&self, cx: &App) -> Option<Arc<InitializedContextServerProtocol>> {
+        self.context_server_store(cx)
+            .read(cx)
+            .get_running_server(self.server_id.as_ref()?)?
+            .client()
+    }
+
+    fn select_server(
+        &mut self,
+        server_id: ContextServerId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.server_id = Some(server_id);
+        self.shown_traffic_count = 0;
+        set_text(&self.traffic_editor, "", window, cx);
+        self.show_new_traffic(window, cx);
+        self.reload(window, cx);
+    }
+
+    /// Fetches the server's capabilities and what it offers.
+    fn reload(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.catalog = Catalog::default();
+        self.catalog_error = None;
+        self.selected_entry = None;
+        set_text(&self.definition_editor, "", window, cx);
+        set_text(&self.result_editor, "", window, cx);
+
+        let Some(protocol) = self.protocol(cx) else {
+            let status = self
+                .server_id
+                .as_ref()
+                .and_then(|id| self.context_server_store(cx).read(cx).status_for_server(id));
+            let message = match status {
+                Some(ContextServerStatus::Error(error)) => format!("The server failed: {error}"),
+                Some(ContextServerStatus::Starting) => "The server is starting…".to_string(),
+                _ => "The server isn't running.".to_string(),
+            };
+            set_text(&self.capabilities_editor, &message, window, cx);
+            self._load_task = Task::ready(());
+            cx.notify();
+            return;
+        };
+
+        let capabilities = serde_json::to_string_pretty(&protocol.initialize).unwrap_or_default();
+        set_text(&self.capabilities_editor, &capabilities, window, cx);
+        self._load_task = cx.spawn_in(window, async move |this, cx| {
+            let mut catalog = Catalog::default();
+            let mut errors = Vec::new();
+            if protocol.capable(ServerCapability::Tools) {
+                match protocol.request::<requests::ListTools>(()).await {
+                    Ok(response) => catalog.tools = response.tools,
+                    Err(error) => errors.push(format!("listing tools: {error:#}")),
+                }
+            }
+            if protocol.capable(ServerCapability::Prompts) {
+                match protocol.request::<requests::PromptsList>(()).await {
+                    Ok(response) => catalog.prompts = response.prompts,
+                    Err(error) => errors.push(format!("listing prompts: {error:#}")),
+                }
+            }
+            if protocol.capable(ServerCapability::Resources) {
+                match protocol.request::<requests::ResourcesList>(()).await {
+                    Ok(response) => catalog.resources = response.resources,
+                    Err(error) => errors.push(format!("listing resources: {error:#}")),
+                }
+                match protocol
+                    .request::<requests::ListResourceTemplates>(())
+                    .await
+                {
+                    Ok(response) => catalog.resource_templates = response.resource_templates,
+                    Err(error) => errors.push(format!("listing resource templates: {error:#}")),
+                }
+            }
+            this.update(cx, |this, cx| {
+                this.catalog = catalog;
+                this.catalog_error = (!errors.is_empty()).then(|| errors.join("\n").into());
+                cx.notify();
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn show_new_traffic(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(server_id) = self.server_id.as_ref() else {
+            return;
+        };
+        let log_store = self.log_store.read(cx);
+        let Some(server_log) = log_store.server_log(&self.project.downgrade(), server_id) else {
+            return;
+        };
+        if server_log.traffic_count == self.shown_traffic_count {
+            return;
+        }
+
+        let mut text = String::new();
+        for entry in server_log.traffic_since(self.shown_traffic_count) {
+            format_traffic_entry(entry, &mut text);
+        }
+        self.shown_traffic_count = server_log.traffic_count;
+        self.traffic_editor.update(cx, |editor, cx| {
+            let end = editor.buffer().read(cx).len(cx);
+            let cursor_is_at_end = editor
+                .selections
+                .newest::<MultiBufferOffset>(&editor.display_snapshot(cx))
+                .start
+                >= end;
+            editor.set_read_only(false);
+            editor.edit([(end..end, text)], EditType::Other, cx);
+            editor.set_read_only(true);
+            if cursor_is_at_end {
+                editor.move_to_end(&editor::actions::MoveToEnd, window, cx);
+                editor.request_autoscroll(Autoscroll::bottom(), cx);
+            }
+        });
+    }
+
+    fn clear_traffic(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(server_id) = self.server_id.clone() {
+            let project = self.project.downgrade();
+            self.log_store
+                .update(cx, |log_store, _| log_store.clear(&project, &server_id));
+        }
+        self.shown_traffic_count = 0;
+        set_text(&self.traffic_editor, "", window, cx);
+        cx.notify();
+    }
+
+    fn select_tab(&mut self, tab: InspectorTab, window: &mut Window, cx: &mut Context<Self>) {
+        if self.tab != tab {
+            self.tab = tab;
+            self.selected_entry = None;
+            set_text(&self.definition_editor, "", window, cx);
+            set_text(&self.result_editor, "", window, cx);
+            cx.notify();
+        }
+    }
+
+    fn select_entry(&mut self, entry: CatalogEntry, window: &mut Window, cx: &mut Context<Self>) {
+        self.selected_entry = Some(entry);
+        let definition = self.catalog.definition(entry).unwrap_or_default();
+        set_text(&self.definition_editor, &definition, window, cx);
+        let input = self.catalog.input_template(entry).unwrap_or_default();
+        self.input_editor
+            .update(cx, |editor, cx| editor.set_text(input, window, cx));
+        set_text(&self.result_editor, "", window, cx);
+        cx.notify();
+    }
+
+    /// Calls the selected tool or prompt, or reads the selected resource, with the given input.
+    fn invoke(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(entry), Some(protocol)) = (self.selected_entry, self.protocol(cx)) else {
+            return;
+        };
+        let input = self.input_editor.read(cx).text(cx);
+        let tool = match entry {
+            CatalogEntry::Tool(ix) => self.catalog.tools.get(ix).cloned(),
+            _ => None,
+        };
+        let prompt_name = match entry {
+            CatalogEntry::Prompt(ix) => self.catalog.prompts.get(ix).map(|p| p.name.clone()),
+            _ => None,
+        };
+
+        set_text(&self.result_editor, "Waiting for the server…", window, cx);
+        self._invoke_task = cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                let response = if let Some(tool) = tool {
+                    let arguments = parse_arguments(&input)?;
+                    let response = protocol
+                        .call_tool(
+                            &tool,
+                            CallToolParams {
+                                name: tool.name.clone(),
+                                arguments,
+                                meta: None,
+                            },
+                        )
+                        .await?;
+                    serde_json::to_string_pretty(&response)?
+                } else if let Some(name) = prompt_name {
+                    let arguments = parse_arguments(&input)?
+                        .map(serde_json::from_value::<HashMap<String, String>>)
+                        .transpose()?;
+                    let response = protocol
+                        .request::<requests::PromptsGet>(PromptsGetParams {
+                            name,
+                            arguments: arguments.map(|arguments| arguments.into_iter().collect()),
+                            meta: None,
+                        })
+                        .await?;
+                    serde_json::to_string_pretty(&response)?
+                } else {
+                    let response = protocol
+                        .request::<requests::ResourcesRead>(ResourcesReadParams {
+                            uri: input.trim().parse()?,
+                            meta: None,
+                        })
+                        .await?;
+                    serde_json::to_string_pretty(&response)?
+                };
+                anyhow::Ok(response)
+            }
+            .await;
+            let text = result.unwrap_or_else(|error| format!("Error: {error:#}"));
+            this.update_in(cx, |this, window, cx| {
+                set_text(&this.result_editor, &text, window, cx);
+            })
+            .log_err();
+        });
+    }
+
+    fn set_log_level(&mut self, level: LoggingLevel, cx: &mut Context<Self>) {
+        self.log_level = level;
+        // Servers may hold back messages below the level they were last asked for.
+        if let Some(protocol) = self.protocol(cx)
+            && protocol.capable(ServerCapability::Logging)
+        {
+            cx.spawn(async move |_, _| {
+                protocol
+                    .request::<requests::LoggingSetLevel>(LoggingSetLevelParams { level })
+                    .await
+                    .log_err();
+            })
+            .detach();
+        }
+        cx.notify();
+    }
+
+    fn follow_logs(&mut self, cx: &App) {
+        let count = self.shown_logs(cx).len();
+        if count != self.shown_log_count {
+            self.shown_log_count = count;
+            if count > 0 {
+                self.log_scroll_handle
+                    .scroll_to_item(count - 1, ScrollStrategy::Bottom);
+            }
+        }
+    }
+
+    fn shown_logs(&self, cx: &App) -> Vec<(LoggingLevel, String)> {
+        let Some(server_id) = self.server_id.as_ref() else {
+            return Vec::new();
+        };
+        let log_store = self.log_store.read(cx);
+        let Some(server_log) = log_store.server_log(&self.project.downgrade(), server_id) else {
+            return Vec::new();
+        };
+        server_log
+            .logs
+            .iter()
+            .filter(|log| log.level >= self.log_level)
+            .map(|log| (log.level, format_log_message(log)))
+            .collect()
+    }
+
+    fn render_server_picker(&self, window: &mut Window, cx: &mut Context<Self>) -> DropdownMenu {
+        let context_server_store = self.context_server_store(cx);
+        let mut server_ids = context_server_store
+            .read(cx)
+            .server_ids(cx)
+            .into_iter()
+            .collect::<Vec<_>>();
+        server_ids.sort_by(|a, b| a.0.cmp(&b.0));
+        let selected = self.server_id.clone();
+        let this = cx.weak_entity();
+        DropdownMenu::new(
+            "mcp-inspector-server-picker",
+            selected
+                .as_ref()
+                .map_or_else(|| "No Servers".to_string(), |id| id.0.to_string()),
+            ContextMenu::build(window, cx, move |mut menu, _, cx| {
+                menu = menu.header("Context Servers");
+                for server_id in server_ids {
+                    let status = context_server_store.read(cx).status_for_server(&server_id);
+                    let label = match status {
+                        Some(ContextServerStatus::Running) | None => server_id.0.to_string(),
+                        Some(ContextServerStatus::Starting) => format!("{} (starting)", server_id),
+                        Some(ContextServerStatus::Stopped) => format!("{} (stopped)", server_id),
+                        Some(ContextServerStatus::Error(_)) => format!("{} (failed)", server_id),
+                    };
+                    let this = this.clone();
+                    menu = menu.toggleable_entry(
+                        label,
+                        selected.as_ref() == Some(&server_id),
+                        IconPosition::Start,
+                        None,
+                        move |window, cx| {
+                            let server_id = server_id.clone();
+                            this.update(cx, |this, cx| this.select_server(server_id, window, cx))
+                                .ok();
+                        },
+                    );
+                }
+                menu
+            }),
+        )
+        .style(DropdownStyle::Outlined)
+    }
+
+    fn render_level_picker(&self, window: &mut Window, cx: &mut Context<Self>) -> DropdownMenu {
+        let this = cx.weak_entity();
+        let selected_level = self.log_level;
+        DropdownMenu::new(
+            "mcp-inspector-level-picker",
+            level_label(selected_level),
+            ContextMenu::build(window, cx, move |mut menu, _, _| {
+                menu = menu.header("Show Messages From");
+                for level in LEVELS {
+                    let this = this.clone();
+                    menu = menu.toggleable_entry(
+                        level_label(level),
+                        level == selected_level,
+                        IconPosition::Start,
+                        None,
+                        move |_, cx| {
+                            this.update(cx, |this, cx| this.set_log_level(level, cx))
+                                .ok();
+                        },
+                    );
+                }
+                menu
+            }),
+        )
+        .style(DropdownStyle::Outlined)
+    }
+
+    fn render_toolbar(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .p_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(self.render_server_picker(window, cx))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .children(InspectorTab::ALL.into_iter().map(|tab| {
+                        Button::new(tab.label(), tab.label())
+                            .label_size(LabelSize::Small)
+                            .toggle_state(self.tab == tab)
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.select_tab(tab, window, cx)
+                            }))
+                    })),
+            )
+            .child(div().flex_1())
+            .when(self.tab == InspectorTab::Logs, |this| {
+                this.child(self.render_level_picker(window, cx))
+            })
+            .child(
+                IconButton::new("mcp-inspector-reload", IconName::RotateCw)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Reload Capabilities"))
+                    .on_click(cx.listener(|this, _, window, cx| this.reload(window, cx))),
+            )
+            .child(
+                IconButton::new("mcp-inspector-clear", IconName::Trash)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Clear Traffic"))
+                    .on_click(cx.listener(|this, _, window, cx| this.clear_traffic(window, cx))),
+            )
+    }
+
+    fn render_catalog(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let entries = self.catalog.entries(self.tab);
+        let selected_entry = self.selected_entry;
+        let list = v_flex()
+            .id("mcp-inspector-catalog")
+            .w(rems(20.))
+            .h_full()
+            .overflow_y_scroll()
+            .border_r_1()
+            .border_color(cx.theme().colors().border_variant)
+            .when_some(self.catalog_error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .p_2()
+                        .child(Label::new(error).size(LabelSize::Small).color(Color::Error)),
+                )
+            })
+            .when(entries.is_empty(), |this| {
+                this.child(
+                    div().p_2().child(
+                        Label::new("Nothing to show")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+                )
+            })
+            .children(entries.into_iter().enumerate().filter_map(|(ix, entry)| {
+                let (name, title) = self.catalog.label(entry)?;
+                let is_template = matches!(entry, CatalogEntry::ResourceTemplate(_));
+                Some(
+                    ListItem::new(("mcp-inspector-entry", ix))
+                        .toggle_state(selected_entry == Some(entry))
+                        .child(
+                            v_flex()
+                                .child(Label::new(name).size(LabelSize::Small).buffer_font(cx))
+                                .when_some(title, |this, title| {
+                                    this.child(
+                                        Label::new(title.to_string())
+                                            .size(LabelSize::XSmall)
+                                            .color(Color::Muted),
+                                    )
+                                }),
+                        )
+                        .when(is_template, |this| {
+                            this.end_slot(
+                                Label::new("template")
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted),
+                            )
+                        })
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.select_entry(entry, window, cx)
+                        })),
+                )
+            }));
+
+        let action_label = match self.selected_entry {
+            Some(CatalogEntry::Tool(_)) => "Call Tool",
+            Some(CatalogEntry::Prompt(_)) => "Get Prompt",
+            _ => "Read Resource",
+        };
+        let section =
+            |label: &'static str| Label::new(label).size(LabelSize::Small).color(Color::Muted);
+        let details =
+            v_flex()
+                .flex_1()
+                .h_full()
+                .p_2()
+                .gap_2()
+                .when(self.selected_entry.is_some(), |this| {
+                    this.child(section("Definition"))
+                        .child(div().flex_1().child(self.definition_editor.clone()))
+                        .child(section("Input"))
+                        .child(
+                            div()
+                                .p_1()
+                                .border_1()
+                                .rounded_md()
+                                .border_color(cx.theme().colors().border)
+                                .child(self.input_editor.clone()),
+                        )
+                        .child(
+                            h_flex().child(
+                                Button::new("mcp-inspector-invoke", action_label)
+                                    .style(ButtonStyle::Filled)
+                                    .on_click(
+                                        cx.listener(|this, _, window, cx| this.invoke(window, cx)),
+                                    ),
+                            ),
+                        )
+                        .child(section("Result"))
+                        .child(div().flex_1().child(self.result_editor.clone()))
+                });
+
+        h_flex().flex_1().size_full().child(list).child(details)
+    }
+
+    fn render_logs(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let logs = Arc::new(self.shown_logs(cx));
+        let count = logs.len();
+        uniform_list(
+            "mcp-inspector-logs",
+            count,
+            move |range: Range<usize>, _, cx| {
+                logs[range]
+                    .iter()
+                    .map(|(level, message)| {
+                        h_flex()
+                            .w_full()
+                            .px_2()
+                            .gap_2()
+                            .child(
+                                Label::new(level_label(*level))
+                                    .buffer_font(cx)
+                                    .size(LabelSize::Small)
+                                    .color(level_color(*level)),
+                            )
+                            .child(
+                                Label::new(message.clone())
+                                    .buffer_font(cx)
+                                    .size(LabelSize::Small)
+                                    .single_line()
+                                    .truncate(),
+                            )
+                            .into_any_element()
+                    })
+                    .collect()
+            },
+        )
+        .track_scroll(&self.log_scroll_handle)
+        .flex_1()
+        .py_1()
+    }
+}
+
+fn read_only_editor(window: &mut Window, cx: &mut App) -> Entity<Editor> {
+    cx.new(|cx| {
+        let mut editor = Editor::multi_line(window, cx);
+        editor.hide_minimap_by_default(window, cx);
+        editor.set_show_git_diff_gutter(false, cx);
+        editor.set_show_runnables(false, cx);
+        editor.set_show_breakpoints(false, cx);
+        editor.set_show_edit_predictions(Some(false), window, cx);
+        editor.set_soft_wrap_mode(SoftWrap::EditorWidth, cx);
+        editor.set_read_only(true);
+        editor
+    })
+}
+
+fn set_text(editor: &Entity<Editor>, text: &str, window: &mut Window, cx: &mut App) {
+    editor.update(cx, |editor, cx| {
+        editor.set_read_only(false);
+        editor.set_text(text, window, cx);
+        editor.set_read_only(true);
+    });
+}
+
+/// Parses the arguments for a tool or prompt; blank input means no arguments.
+fn parse_arguments(input: &str) -> anyhow::Result<Option<Value>> {
+    if input.trim().is_empty() {
+        return Ok(None);
+    }
+    let arguments: Value = serde_json::from_str(input)?;
+    anyhow::ensure!(arguments.is_object(), "arguments must be a JSON object");
+    Ok(Some(arguments))
+}
+
+fn format_traffic_entry(entry: &TrafficEntry, text: &mut String) {
+    let label = match entry.kind {
+        IoKind::Send => "// Send",
+        IoKind::Receive => "// Receive",
+        IoKind::StdErr => "// Stderr",
+    };
+    text.push_str(label);
+    text.push('\n');
+    let pretty = serde_json::from_str::<Value>(&entry.message)
+        .ok()
+        .and_then(|message| serde_json::to_string_pretty(&message).ok());
+    let message = pretty.as_deref().unwrap_or(entry.message.trim_end());
+    let truncated = util::truncate_to_byte_limit(message, MAX_SHOWN_MESSAGE_LEN);
+    text.push_str(truncated);
+    if truncated.len() < message.len() {
+        text.push_str("\n… (truncated)");
+    }
+    text.push_str("\n\n");
+}
+
+fn format_log_message(log: &MessageParams) -> String {
+    let data = match &log.data {
+        Value::String(message) => message.replace('\n', " "),
+        data => data.to_string(),
+    };
+    match &log.logger {
+        Some(logger) => format!("[{logger}] {data}"),
+        None => data,
+    }
+}
+
+fn level_label(level: LoggingLevel) -> &'static str {
+    match level {
+        LoggingLevel::Debug => "Debug",
+        LoggingLevel::Info => "Info",
+        LoggingLevel::Notice => "Notice",
+        LoggingLevel::Warning => "Warning",
+        LoggingLevel::Error => "Error",
+        LoggingLevel::Critical => "Critical",
+        LoggingLevel::Alert => "Alert",
+        LoggingLevel::Emergency => "Emergency",
+    }
+}
+
+fn level_color(level: LoggingLevel) -> Color {
+    match level {
+        LoggingLevel::Debug => Color::Muted,
+        LoggingLevel::Info | LoggingLevel::Notice => Color::Info,
+        LoggingLevel::Warning => Color::Warning,
+        LoggingLevel::Error
+        | LoggingLevel::Critical
+        | LoggingLevel::Alert
+        | LoggingLevel::Emergency => Color::Error,
+    }
+}
+
+impl EventEmitter<()> for McpInspector {}
+
+impl Focusable for McpInspector {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for McpInspector {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(workspace::item::ItemEvent)) {}
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "MCP Inspector".into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl Render for McpInspector {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = match self.tab {
+            InspectorTab::Traffic => div()
+                .flex_1()
+                .size_full()
+                .child(self.traffic_editor.clone())
+                .into_any_element(),
+            InspectorTab::Capabilities => div()
+                .flex_1()
+                .size_full()
+                .child(self.capabilities_editor.clone())
+                .into_any_element(),
+            InspectorTab::Tools | InspectorTab::Prompts | InspectorTab::Resources => {
+                self.render_catalog(cx).into_any_element()
+            }
+            InspectorTab::Logs => self.render_logs(cx).into_any_element(),
+        };
+        v_flex()
+            .key_context("McpInspector")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_toolbar(window, cx))
+            .child(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_notification(level: &str, data: &str) -> String {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": { "level": level, "logger": "db", "data": data },
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_server_log() {
+        let mut server_log = ServerLog::default();
+        server_log.push(
+            IoKind::Send,
+            r#"{"jsonrpc":"2.0","id":0,"method":"ping"}"#.into(),
+        );
+        server_log.push(IoKind::Receive, log_notification("warning", "slow").into());
+        // Logs only count when the server sent them.
+        server_log.push(IoKind::Send, log_notification("error", "echo").into());
+        server_log.push(IoKind::StdErr, "not json".into());
+
+        assert_eq!(server_log.traffic_count, 4);
+        assert_eq!(server_log.logs.len(), 1);
+        assert_eq!(server_log.logs[0].level, LoggingLevel::Warning);
+        assert_eq!(format_log_message(&server_log.logs[0]), "[db] slow");
+        assert_eq!(
+            server_log
+                .traffic_since(2)
+                .map(|entry| entry.kind)
+                .collect::<Vec<_>>(),
+            [IoKind::Send, IoKind::StdErr]
+        );
+
+        for _ in 0..TRAFFIC_LIMIT {
+            server_log.push(IoKind::Receive, log_notification("debug", "tick").into());
+        }
+        assert_eq!(server_log.traffic.len(), TRAFFIC_LIMIT);
+        assert_eq!(server_log.logs.len(), LOG_LIMIT);
+        assert_eq!(server_log.traffic_count, TRAFFIC_LIMIT + 4);
+        // Messages that were dropped before being shown are skipped.
+        assert_eq!(server_log.traffic_since(1).count(), TRAFFIC_LIMIT);
+        assert_eq!(server_log.traffic_since(TRAFFIC_LIMIT + 3).count(), 1);
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(parse_arguments("  ").unwrap(), None);
+        assert_eq!(
+            parse_arguments(r#"{"city": "Paris"}"#).unwrap(),
+            Some(serde_json::json!({"city": "Paris"}))
+        );
+        assert!(parse_arguments("[1]").is_err());
+        assert!(parse_arguments("{").is_err());
+    }
+
+    #[test]
+    fn test_format_traffic_entry() {
+        let mut text = String::new();
+        format_traffic_entry(
+            &TrafficEntry {
+                kind: IoKind::Receive,
+                message: r#"{"id":1,"result":{}}"#.into(),
+            },
+            &mut text,
+        );
+        format_traffic_entry(
+            &TrafficEntry {
+                kind: IoKind::StdErr,
+                message: "starting up\n".into(),
+            },
+            &mut text,
+        );
+        assert_eq!(
+            text,
+            "// Receive\n{\n  \"id\": 1,\n  \"result\": {}\n}\n\n// Stderr\nstarting up\n\n"
+        );
+    }
+}
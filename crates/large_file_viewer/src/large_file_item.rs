@@ -0,0 +1,213 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use language::File as _;
+use project::{Project, ProjectEntryId, ProjectPath};
+use regex::bytes::Regex;
+use settings::Settings as _;
+use worktree::{File, Worktree};
+
+use crate::{
+    LargeFileViewerSettings,
+    mapped_file::{self, MappedFile, PendingWrite},
+};
+
+pub enum LargeFileItemEvent {
+    Reloaded,
+}
+
+/// A local file that is too large for the editor, memory-mapped instead of loaded.
+pub struct LargeFileItem {
+    pub file: Arc<File>,
+    /// `None` while the file is being replaced, so that it isn't mapped at the time.
+    contents: Option<Arc<MappedFile>>,
+    reload_task: Option<Task<()>>,
+}
+
+impl EventEmitter<LargeFileItemEvent> for LargeFileItem {}
+
+impl LargeFileItem {
+    fn load(
+        worktree: Entity<Worktree>,
+        entry: worktree::Entry,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let file = File::for_entry(entry, worktree.clone());
+        let abs_path = file.worktree.read(cx).absolutize(&file.path);
+        cx.spawn(async move |cx| {
+            let contents = cx
+                .background_spawn(async move { MappedFile::open(&abs_path) })
+                .await?;
+            Ok(cx.new(|cx| {
+                cx.subscribe(&worktree, Self::on_worktree_event).detach();
+                Self {
+                    file,
+                    contents: Some(Arc::new(contents)),
+                    reload_task: None,
+                }
+            }))
+        })
+    }
+
+    /// The mapped file, or `None` while it is being written or after it changed on disk,
+    /// until it has been mapped again.
+    pub fn contents(&self) -> Option<&Arc<MappedFile>> {
+        self.contents
+            .as_ref()
+            .filter(|contents| !contents.is_stale())
+    }
+
+    pub fn abs_path(&self, cx: &App) -> PathBuf {
+        self.file.worktree.read(cx).absolutize(&self.file.path)
+    }
+
+    pub fn project_path(&self, cx: &App) -> ProjectPath {
+        ProjectPath {
+            worktree_id: self.file.worktree_id(cx),
+            path: self.file.path.clone(),
+        }
+    }
+
+    /// Replaces byte ranges of the file on disk, then maps it again. The edits must be sorted
+    /// and must not overlap.
+    pub fn edit(
+        &mut self,
+        edits: Vec<(Range<u64>, String)>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.write(
+            move |path, contents| mapped_file::write_edits(path, contents, &edits),
+            cx,
+        )
+    }
+
+    /// Replaces every match of `regex` in the whole file on disk, then maps it again.
+    pub fn replace_all(
+        &mut self,
+        regex: Regex,
+        replacement_for: impl Fn(&str) -> Option<String> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.write(
+            move |path, contents| mapped_file::replace_all(path, contents, &regex, replacement_for),
+            cx,
+        )
+    }
+
+    fn write(
+        &mut self,
+        write: impl FnOnce(&Path, &MappedFile) -> Result<Option<PendingWrite>> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let abs_path = self.abs_path(cx);
+        let Some(contents) = self.contents().cloned() else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "{abs_path:?} changed on disk and is being reloaded"
+            )));
+        };
+        cx.spawn(async move |this, cx| {
+            let pending_write = cx
+                .background_spawn({
+                    let abs_path = abs_path.clone();
+                    async move { write(&abs_path, &contents) }
+                })
+                .await?;
+            let committed = match pending_write {
+                Some(pending_write) => {
+                    // Release the mapping before replacing the file it maps.
+                    this.update(cx, |this, cx| {
+                        this.contents = None;
+                        this.reload_task = None;
+                        cx.notify();
+                    })?;
+                    cx.background_spawn(async move { pending_write.commit() })
+                        .await
+                }
+                None => Ok(()),
+            };
+            let contents = cx
+                .background_spawn(async move { MappedFile::open(&abs_path) })
+                .await;
+            this.update(cx, |this, cx| {
+                contents.map(|contents| this.set_contents(contents, cx))
+            })??;
+            committed
+        })
+    }
+
+    fn on_worktree_event(
+        &mut self,
+        _: Entity<Worktree>,
+        event: &worktree::Event,
+        cx: &mut Context<Self>,
+    ) {
+        if let worktree::Event::UpdatedEntries(changes) = event
+            && changes.iter().any(|(path, _, _)| path == &self.file.path)
+        {
+            self.reload(cx);
+        }
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let abs_path = self.abs_path(cx);
+        self.reload_task = Some(cx.spawn(async move |this, cx| {
+            let contents = cx
+                .background_spawn(async move { MappedFile::open(&abs_path) })
+                .await;
+            match contents {
+                Ok(contents) => {
+                    this.update(cx, |this, cx| this.set_contents(contents, cx))
+                        .ok();
+                }
+                Err(error) => log::error!("Failed to reload large file: {error:#}"),
+            }
+        }));
+    }
+
+    fn set_contents(&mut self, contents: MappedFile, cx: &mut Context<Self>) {
+        self.contents = Some(Arc::new(contents));
+        cx.emit(LargeFileItemEvent::Reloaded);
+        cx.notify();
+    }
+}
+
+impl project::ProjectItem for LargeFileItem {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let settings = LargeFileViewerSettings::get_global(cx);
+        if !settings.enabled {
+            return None;
+        }
+        let worktree = project.read(cx).worktree_for_id(path.worktree_id, cx)?;
+        // Remote files can't be memory-mapped.
+        if !worktree.read(cx).is_local() {
+            return None;
+        }
+        let entry = worktree
+            .read(cx)
+            .entry_for_path(&path.path)
+            .filter(|entry| entry.is_file() && entry.size >= settings.threshold_bytes())?
+            .clone();
+        Some(Self::load(worktree, entry, cx))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.file.entry_id
+    }
+
+    fn project_path(&self, cx: &App) -> Option<ProjectPath> {
+        Some(self.project_path(cx))
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
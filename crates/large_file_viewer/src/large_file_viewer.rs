@@ -0,0 +1,813 @@
+mod large_file_item;
+mod large_file_viewer_settings;
+mod line_modal;
+mod mapped_file;
+
+use std::{borrow::Cow, ops::Range, path::Path, sync::Arc};
+
+use editor::{
+    EditorSettings,
+    actions::{
+        Copy, MoveDown, MovePageDown, MovePageUp, MoveToBeginning, MoveToEnd, MoveUp,
+        ToggleGoToLine,
+    },
+    items::entry_git_aware_label_color,
+};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, ClipboardItem, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    HighlightStyle, InteractiveElement, IntoElement, ParentElement, Render, ScrollStrategy,
+    StyledText, Task, UniformListScrollHandle, Window, actions, uniform_list,
+};
+use language::File as _;
+use project::{Project, search::SearchQuery};
+use regex::bytes::{Regex, RegexBuilder};
+use settings::Settings;
+use theme::{Theme, ThemeSettings};
+use ui::{Tooltip, prelude::*};
+use util::{paths::PathExt, size::format_file_size};
+use workspace::{
+    Pane, Toast, ToolbarItemLocation, Workspace, WorkspaceId,
+    invalid_item_view::InvalidItemView,
+    item::{BreadcrumbText, Item, ItemEvent, ProjectItem, TabContentParams},
+    notifications::{DetachAndPromptErr as _, NotificationId},
+    searchable::{Direction, SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
+};
+
+pub use crate::large_file_item::*;
+pub use crate::large_file_viewer_settings::*;
+use crate::{
+    line_modal::{LineModal, LineModalKind},
+    mapped_file::{MAX_DISPLAYED_LINE_LEN, MappedFile},
+};
+
+actions!(
+    large_file_viewer,
+    [
+        /// Edits the selected line of a large file.
+        EditLine
+    ]
+);
+
+/// The number of lines shown at once. The list is paged so that its pixel offsets stay within
+/// `f32` precision for files with hundreds of millions of lines.
+const PAGE_LINES: u64 = 100_000;
+
+/// The number of rows moved by page up and page down.
+const ROWS_PER_SCREEN: u64 = 40;
+
+/// Searching stops after this many matches, since each one is kept in memory.
+const MAX_SEARCH_MATCHES: usize = 10_000;
+
+/// A read-mostly view of a file that is too large to open in the editor.
+///
+/// The file is memory-mapped and shown without syntax highlighting. Lines can be searched and
+/// edited one at a time, and every edit is written straight to disk.
+pub struct LargeFileView {
+    item: Entity<LargeFileItem>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    /// The first row of the page being shown.
+    page_start: u64,
+    selected_row: u64,
+    search_matches: Vec<Range<u64>>,
+    active_match: Option<usize>,
+    search_truncated: bool,
+}
+
+pub enum LargeFileViewEvent {
+    Reloaded,
+}
+
+impl LargeFileView {
+    pub fn new(
+        item: Entity<LargeFileItem>,
+        project: Entity<Project>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        cx.subscribe(&item, Self::on_item_event).detach();
+        Self {
+            item,
+            project,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            page_start: 0,
+            selected_row: 0,
+            search_matches: Vec::new(),
+            active_match: None,
+            search_truncated: false,
+        }
+    }
+
+    fn on_item_event(
+        &mut self,
+        _: Entity<LargeFileItem>,
+        event: &LargeFileItemEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            LargeFileItemEvent::Reloaded => {
+                if let Some(contents) = self.contents(cx) {
+                    let last_row = contents.line_count() - 1;
+                    self.selected_row = self.selected_row.min(last_row);
+                    self.page_start = self.page_start.min(last_row / PAGE_LINES * PAGE_LINES);
+                }
+                self.search_matches.clear();
+                self.active_match = None;
+                cx.emit(LargeFileViewEvent::Reloaded);
+                cx.emit(SearchEvent::MatchesInvalidated);
+                cx.notify();
+            }
+        }
+    }
+
+    /// The mapped file, unless it's being written or reloaded, see [`LargeFileItem::contents`].
+    fn contents(&self, cx: &App) -> Option<Arc<MappedFile>> {
+        self.item.read(cx).contents().cloned()
+    }
+
+    /// Selects a row, switching to its page and scrolling it into view.
+    pub fn select_row(&mut self, row: u64, cx: &mut Context<Self>) {
+        let Some(contents) = self.contents(cx) else {
+            return;
+        };
+        let row = row.min(contents.line_count() - 1);
+        self.selected_row = row;
+        self.page_start = row / PAGE_LINES * PAGE_LINES;
+        self.scroll_handle
+            .scroll_to_item((row - self.page_start) as usize, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    /// Replaces the text of a row on disk, keeping its line ending.
+    pub fn replace_line(
+        &mut self,
+        row: u64,
+        text: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(contents) = self.contents(cx) else {
+            return;
+        };
+        let range = contents.line_range(row);
+        self.item
+            .update(cx, |item, cx| item.edit(vec![(range, text)], cx))
+            .detach_and_prompt_err("Failed to edit line", window, cx, |_, _, _| None);
+    }
+
+    fn show_page(&mut self, page_start: u64, cx: &mut Context<Self>) {
+        self.select_row(page_start, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_row(self.selected_row.saturating_sub(1), cx);
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_row(self.selected_row.saturating_add(1), cx);
+    }
+
+    fn move_page_up(&mut self, _: &MovePageUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_row(self.selected_row.saturating_sub(ROWS_PER_SCREEN), cx);
+    }
+
+    fn move_page_down(&mut self, _: &MovePageDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_row(self.selected_row.saturating_add(ROWS_PER_SCREEN), cx);
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_row(0, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_row(u64::MAX, cx);
+    }
+
+    fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(contents) = self.contents(cx) else {
+            return;
+        };
+        let text = contents.text_for_range(contents.line_range(self.selected_row));
+        cx.write_to_clipboard(ClipboardItem::new_string(text.into_owned()));
+    }
+
+    fn go_to_line(&mut self, _: &ToggleGoToLine, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(contents) = self.contents(cx) else {
+            return;
+        };
+        let line_count = contents.line_count();
+        self.toggle_line_modal(
+            LineModalKind::GoToLine,
+            String::new(),
+            line_count,
+            window,
+            cx,
+        );
+    }
+
+    fn edit_line(&mut self, _: &EditLine, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(contents) = self.contents(cx) else {
+            return;
+        };
+        let range = contents.line_range(self.selected_row);
+        if range.end - range.start > MAX_DISPLAYED_LINE_LEN as u64 {
+            if let Some(workspace) = window.root::<Workspace>().flatten() {
+                workspace.update(cx, |workspace, cx| {
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<EditLine>(),
+                            format!(
+                                "Lines longer than {MAX_DISPLAYED_LINE_LEN} bytes can't be edited"
+                            ),
+                        ),
+                        cx,
+                    )
+                });
+            }
+            return;
+        }
+        let text = contents.text_for_range(range).into_owned();
+        let kind = LineModalKind::EditLine {
+            row: self.selected_row,
+        };
+        self.toggle_line_modal(kind, text, contents.line_count(), window, cx);
+    }
+
+    fn toggle_line_modal(
+        &mut self,
+        kind: LineModalKind,
+        text: String,
+        line_count: u64,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = window.root::<Workspace>().flatten() else {
+            return;
+        };
+        let view = cx.weak_entity();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(window, cx, |window, cx| {
+                LineModal::new(kind, text, view, line_count, window, cx)
+            });
+        });
+    }
+
+    fn render_rows(&mut self, range: Range<usize>, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let Some(contents) = self.contents(cx) else {
+            return Vec::new();
+        };
+        let colors = cx.theme().colors();
+        let match_style = HighlightStyle {
+            background_color: Some(colors.search_match_background),
+            ..Default::default()
+        };
+        let active_match_style = HighlightStyle {
+            background_color: Some(colors.search_active_match_background),
+            ..Default::default()
+        };
+        let active_match = self
+            .active_match
+            .and_then(|ix| self.search_matches.get(ix))
+            .cloned();
+        let gutter_width = contents.line_count().to_string().len() + 1;
+
+        range
+            .map(|ix| {
+                let row = self.page_start + ix as u64;
+                let is_selected = row == self.selected_row;
+                let line_range = contents.line_range(row);
+                let text = contents.line_text(row);
+                let highlights = if let Cow::Borrowed(text) = &text {
+                    self.matches_in_line(line_range.start, text)
+                        .map(|(range, search_match)| {
+                            let style = if Some(search_match) == active_match.as_ref() {
+                                active_match_style
+                            } else {
+                                match_style
+                            };
+                            (range, style)
+                        })
+                        .collect()
+                } else {
+                    // Byte offsets don't line up with text that had invalid UTF-8 replaced.
+                    Vec::new()
+                };
+
+                h_flex()
+                    .id(ix)
+                    .w_full()
+                    .gap_2()
+                    .when(is_selected, |this| {
+                        this.bg(colors.editor_active_line_background)
+                    })
+                    .on_click(cx.listener(move |this, _, _, cx| this.select_row(row, cx)))
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(rems(gutter_width as f32 * 0.6))
+                            .text_right()
+                            .text_color(if is_selected {
+                                colors.editor_active_line_number
+                            } else {
+                                colors.editor_line_number
+                            })
+                            .child((row + 1).to_string()),
+                    )
+                    .child(
+                        div()
+                            .whitespace_nowrap()
+                            .child(StyledText::new(text.into_owned()).with_highlights(highlights)),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    /// The search matches overlapping a line, as ranges into its displayed text.
+    fn matches_in_line<'a>(
+        &'a self,
+        line_start: u64,
+        text: &'a str,
+    ) -> impl Iterator<Item = (Range<usize>, &'a Range<u64>)> + 'a {
+        let line_end = line_start + text.len() as u64;
+        let first = self
+            .search_matches
+            .partition_point(|search_match| search_match.end <= line_start);
+        self.search_matches[first..]
+            .iter()
+            .take_while(move |search_match| search_match.start < line_end)
+            .filter_map(move |search_match| {
+                let mut start = (search_match.start.max(line_start) - line_start) as usize;
+                let mut end = (search_match.end.min(line_end) - line_start) as usize;
+                while !text.is_char_boundary(start) {
+                    start -= 1;
+                }
+                while !text.is_char_boundary(end) {
+                    end += 1;
+                }
+                (start < end).then_some((start..end, search_match))
+            })
+    }
+
+    fn render_header(&self, contents: &MappedFile, cx: &mut Context<Self>) -> impl IntoElement {
+        let line_count = contents.line_count();
+        let page_end = (self.page_start + PAGE_LINES).min(line_count);
+        let has_previous_page = self.page_start > 0;
+        let has_next_page = page_end < line_count;
+        let page_start = self.page_start;
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(format!(
+                            "Lines {}–{} of {} · {}",
+                            self.page_start + 1,
+                            page_end,
+                            line_count,
+                            format_file_size(contents.len(), false)
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new("Opened as a large file, without syntax highlighting")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .when(self.search_truncated, |this| {
+                        this.child(
+                            Label::new(format!("Showing the first {MAX_SEARCH_MATCHES} matches"))
+                                .size(LabelSize::Small)
+                                .color(Color::Warning),
+                        )
+                    }),
+            )
+            .when(has_previous_page || has_next_page, |this| {
+                this.child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            IconButton::new("previous-page", IconName::ChevronLeft)
+                                .icon_size(IconSize::Small)
+                                .disabled(!has_previous_page)
+                                .tooltip(Tooltip::text("Previous Page"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.show_page(page_start.saturating_sub(PAGE_LINES), cx)
+                                })),
+                        )
+                        .child(
+                            IconButton::new("next-page", IconName::ChevronRight)
+                                .icon_size(IconSize::Small)
+                                .disabled(!has_next_page)
+                                .tooltip(Tooltip::text("Next Page"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.show_page(page_start + PAGE_LINES, cx)
+                                })),
+                        ),
+                )
+            })
+    }
+}
+
+impl EventEmitter<LargeFileViewEvent> for LargeFileView {}
+
+impl EventEmitter<SearchEvent> for LargeFileView {}
+
+impl Focusable for LargeFileView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LargeFileView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(contents) = self.contents(cx) else {
+            return v_flex()
+                .track_focus(&self.focus_handle)
+                .size_full()
+                .items_center()
+                .justify_center()
+                .bg(cx.theme().colors().editor_background)
+                .child(Label::new("Reloading…").color(Color::Muted))
+                .into_any_element();
+        };
+        let page_len = (contents.line_count() - self.page_start).min(PAGE_LINES) as usize;
+        let settings = ThemeSettings::get_global(cx);
+        let text_size = settings.buffer_font_size(cx);
+
+        v_flex()
+            .key_context("LargeFileView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::move_page_up))
+            .on_action(cx.listener(Self::move_page_down))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::copy))
+            .on_action(cx.listener(Self::go_to_line))
+            .on_action(cx.listener(Self::edit_line))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(&contents, cx))
+            .child(
+                uniform_list(
+                    "large-file-lines",
+                    page_len,
+                    cx.processor(|this, range: Range<usize>, _, cx| this.render_rows(range, cx)),
+                )
+                .track_scroll(&self.scroll_handle)
+                .font_buffer(cx)
+                .text_size(text_size)
+                .flex_grow()
+                .w_full(),
+            )
+            .into_any_element()
+    }
+}
+
+impl Item for LargeFileView {
+    type Event = LargeFileViewEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            LargeFileViewEvent::Reloaded => {
+                f(ItemEvent::UpdateTab);
+                f(ItemEvent::UpdateBreadcrumbs);
+            }
+        }
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.item.entity_id(), self.item.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.item.read(cx).abs_path(cx);
+        Some(abs_path.compact().to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        let project_path = self.item.read(cx).project_path(cx);
+        let label_color = if workspace::ItemSettings::get_global(cx).git_status {
+            let git_status = self
+                .project
+                .read(cx)
+                .project_path_git_status(&project_path, cx)
+                .map(|status| status.summary())
+                .unwrap_or_default();
+
+            self.project
+                .read(cx)
+                .entry_for_path(&project_path, cx)
+                .map(|entry| {
+                    entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                })
+                .unwrap_or_else(|| params.text_color())
+        } else {
+            params.text_color()
+        };
+
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.item.read(cx).file.file_name(cx).to_string().into()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.item.read(cx).abs_path(cx);
+        workspace::ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(&path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn breadcrumb_location(&self, cx: &App) -> ToolbarItemLocation {
+        if EditorSettings::get_global(cx).toolbar.breadcrumbs {
+            ToolbarItemLocation::PrimaryLeft
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+
+    fn breadcrumbs(&self, _: &Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        let project = self.project.read(cx);
+        let item = self.item.read(cx);
+        let mut path = item.file.path().clone();
+        if project.visible_worktrees(cx).count() > 1
+            && let Some(worktree) = project.worktree_for_id(item.project_path(cx).worktree_id, cx)
+        {
+            path = worktree.read(cx).root_name().join(&path);
+        }
+
+        Some(vec![BreadcrumbText {
+            text: path.display(project.path_style(cx)).to_string(),
+            highlights: None,
+            font: Some(ThemeSettings::get_global(cx).buffer_font.clone()),
+        }])
+    }
+
+    fn as_searchable(
+        &self,
+        handle: &Entity<Self>,
+        _: &App,
+    ) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(handle.clone()))
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<WorkspaceId>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>>
+    where
+        Self: Sized,
+    {
+        Task::ready(Some(
+            cx.new(|cx| Self::new(self.item.clone(), self.project.clone(), cx)),
+        ))
+    }
+
+    fn has_deleted_file(&self, cx: &App) -> bool {
+        self.item.read(cx).file.disk_state().is_deleted()
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+impl SearchableItem for LargeFileView {
+    type Match = Range<u64>;
+
+    fn supported_options(&self) -> SearchOptions {
+        SearchOptions {
+            case: true,
+            word: true,
+            regex: true,
+            replacement: true,
+            selection: false,
+            find_in_results: false,
+        }
+    }
+
+    fn clear_matches(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        self.search_matches.clear();
+        self.active_match = None;
+        self.search_truncated = false;
+        cx.notify();
+    }
+
+    fn update_matches(
+        &mut self,
+        matches: &[Self::Match],
+        active_match_index: Option<usize>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.search_matches = matches.to_vec();
+        self.active_match = active_match_index;
+        cx.notify();
+    }
+
+    fn query_suggestion(&mut self, _: &mut Window, _: &mut Context<Self>) -> String {
+        String::new()
+    }
+
+    fn activate_match(
+        &mut self,
+        index: usize,
+        matches: &[Self::Match],
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(search_match) = matches.get(index) else {
+            return;
+        };
+        let Some(contents) = self.contents(cx) else {
+            return;
+        };
+        self.active_match = Some(index);
+        let row = contents.row_for_offset(search_match.start);
+        self.select_row(row, cx);
+        cx.emit(SearchEvent::ActiveMatchChanged);
+    }
+
+    fn select_matches(
+        &mut self,
+        matches: &[Self::Match],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.activate_match(0, matches, window, cx);
+    }
+
+    fn replace(
+        &mut self,
+        search_match: &Self::Match,
+        query: &SearchQuery,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(contents) = self.contents(cx) else {
+            return;
+        };
+        let text = contents.text_for_range(search_match.clone());
+        let Some(replacement) = query.replacement_for(&text) else {
+            return;
+        };
+        let edits = vec![(search_match.clone(), replacement.into_owned())];
+        self.item
+            .update(cx, |item, cx| item.edit(edits, cx))
+            .detach_and_prompt_err("Failed to replace", window, cx, |_, _, _| None);
+    }
+
+    /// Replaces every match in the file rather than only the given ones, which stop at
+    /// [`MAX_SEARCH_MATCHES`].
+    fn replace_all(
+        &mut self,
+        matches: &mut dyn Iterator<Item = &Self::Match>,
+        query: &SearchQuery,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if matches.next().is_none() {
+            return;
+        }
+        let Some(regex) = regex_for_query(query) else {
+            return;
+        };
+        let query = query.clone();
+        self.item
+            .update(cx, |item, cx| {
+                item.replace_all(
+                    regex,
+                    move |text| Some(query.replacement_for(text)?.into_owned()),
+                    cx,
+                )
+            })
+            .detach_and_prompt_err("Failed to replace", window, cx, |_, _, _| None);
+    }
+
+    fn find_matches(
+        &mut self,
+        query: Arc<SearchQuery>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<Self::Match>> {
+        let Some(regex) = regex_for_query(&query) else {
+            return Task::ready(Vec::new());
+        };
+        let Some(contents) = self.contents(cx) else {
+            return Task::ready(Vec::new());
+        };
+        cx.spawn(async move |this, cx| {
+            let (matches, truncated) = cx
+                .background_spawn(async move { contents.find(&regex, MAX_SEARCH_MATCHES) })
+                .await;
+            this.update(cx, |this, cx| {
+                this.search_truncated = truncated;
+                cx.notify();
+            })
+            .ok();
+            matches
+        })
+    }
+
+    fn active_match_index(
+        &mut self,
+        direction: Direction,
+        matches: &[Self::Match],
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<usize> {
+        if matches.is_empty() {
+            return None;
+        }
+        let line_start = self.contents(cx)?.line_range(self.selected_row).start;
+        let next = matches.partition_point(|search_match| search_match.end <= line_start);
+        Some(match direction {
+            Direction::Next => next.min(matches.len() - 1),
+            Direction::Prev => next.saturating_sub(1),
+        })
+    }
+}
+
+/// Builds a byte regex for a buffer search query, matching how the editor interprets its options.
+fn regex_for_query(query: &SearchQuery) -> Option<Regex> {
+    let mut pattern = if query.is_regex() {
+        query.as_str().to_string()
+    } else {
+        regex::escape(query.as_str())
+    };
+    if pattern.is_empty() {
+        return None;
+    }
+    if query.whole_word() {
+        pattern = format!(r"\b(?:{pattern})\b");
+    }
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!query.case_sensitive())
+        .multi_line(true)
+        .build()
+        .ok()
+}
+
+impl ProjectItem for LargeFileView {
+    type Item = LargeFileItem;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, cx)
+    }
+
+    fn for_broken_project_item(
+        abs_path: &Path,
+        is_local: bool,
+        e: &anyhow::Error,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<InvalidItemView>
+    where
+        Self: Sized,
+    {
+        Some(InvalidItemView::new(abs_path, is_local, e, window, cx))
+    }
+}
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<LargeFileView>(cx);
+}
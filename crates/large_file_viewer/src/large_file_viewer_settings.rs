@@ -0,0 +1,30 @@
+use settings::{RegisterSetting, Settings};
+
+/// The settings for the large file viewer.
+#[derive(Clone, Debug, RegisterSetting)]
+pub struct LargeFileViewerSettings {
+    /// Whether files over the threshold open in the large file viewer instead of the editor.
+    ///
+    /// Default: true
+    pub enabled: bool,
+    /// The size, in megabytes, from which files open in the large file viewer.
+    ///
+    /// Default: 256
+    pub threshold_mb: u64,
+}
+
+impl LargeFileViewerSettings {
+    pub fn threshold_bytes(&self) -> u64 {
+        self.threshold_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+impl Settings for LargeFileViewerSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let large_file_viewer = content.large_file_viewer.clone().unwrap();
+        Self {
+            enabled: large_file_viewer.enabled.unwrap(),
+            threshold_mb: large_file_viewer.threshold_mb.unwrap(),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use editor::Editor;
+use gpui::{
+    App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, WeakEntity, Window,
+};
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::ModalView;
+
+use crate::LargeFileView;
+
+/// What a [`LineModal`] does with the text it's confirmed with.
+pub enum LineModalKind {
+    GoToLine,
+    /// Replaces the given row.
+    EditLine {
+        row: u64,
+    },
+}
+
+/// A single-line prompt for a large file view: going to a line, or editing one.
+pub struct LineModal {
+    kind: LineModalKind,
+    view: WeakEntity<LargeFileView>,
+    line_editor: Entity<Editor>,
+    line_count: u64,
+    error: Option<SharedString>,
+}
+
+impl LineModal {
+    pub fn new(
+        kind: LineModalKind,
+        text: String,
+        view: WeakEntity<LargeFileView>,
+        line_count: u64,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let line_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            if matches!(kind, LineModalKind::GoToLine) {
+                editor.set_placeholder_text(&format!("Line number (1–{line_count})"), window, cx);
+            }
+            editor.set_text(text, window, cx);
+            editor.select_all(&editor::actions::SelectAll, window, cx);
+            editor
+        });
+        Self {
+            kind,
+            view,
+            line_editor,
+            line_count,
+            error: None,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let text = self.line_editor.read(cx).text(cx);
+        match self.kind {
+            LineModalKind::GoToLine => {
+                let row = text
+                    .trim()
+                    .split(':')
+                    .next()
+                    .and_then(|row| row.parse::<u64>().ok())
+                    .filter(|row| (1..=self.line_count).contains(row));
+                let Some(row) = row else {
+                    self.error =
+                        Some(format!("Enter a line number from 1 to {}", self.line_count).into());
+                    cx.notify();
+                    return;
+                };
+                self.view
+                    .update(cx, |view, cx| view.select_row(row - 1, cx))
+                    .log_err();
+            }
+            LineModalKind::EditLine { row } => {
+                if text.contains('\n') {
+                    self.error = Some("A line can't contain a newline".into());
+                    cx.notify();
+                    return;
+                }
+                self.view
+                    .update(cx, |view, cx| view.replace_line(row, text, window, cx))
+                    .log_err();
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for LineModal {}
+
+impl ModalView for LineModal {}
+
+impl Focusable for LineModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.line_editor.focus_handle(cx)
+    }
+}
+
+impl Render for LineModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let title = match self.kind {
+            LineModalKind::GoToLine => "Go to Line".to_string(),
+            LineModalKind::EditLine { row } => format!("Edit Line {}", row + 1),
+        };
+        v_flex()
+            .key_context("LargeFileLineModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w(rems(34.))
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(title).size(LabelSize::Small).color(Color::Muted)),
+            )
+            .child(div().px_2().py_1().child(self.line_editor.clone()))
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .px_2()
+                        .pb_1()
+                        .child(Label::new(error).size(LabelSize::Small).color(Color::Error)),
+                )
+            })
+    }
+}
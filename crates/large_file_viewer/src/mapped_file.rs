@@ -0,0 +1,431 @@
+use std::{
+    borrow::Cow,
+    fs,
+    io::{BufWriter, Write as _},
+    ops::Range,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context as _, Result};
+use memmap2::Mmap;
+use regex::bytes::Regex;
+
+/// The index keeps the offset of every `LINES_PER_CHECKPOINT`-th line, which bounds both its
+/// size and the number of bytes scanned to find any line.
+const LINES_PER_CHECKPOINT: u64 = 1024;
+
+/// Longer lines are cut off when displayed, so that a file without newlines doesn't have to
+/// be laid out in one piece.
+pub const MAX_DISPLAYED_LINE_LEN: usize = 4096;
+
+enum Contents {
+    Mapped {
+        mmap: Mmap,
+        /// The mapped file, and when it was last modified as of mapping it, to detect it
+        /// being changed or truncated behind the mapping's back.
+        file: fs::File,
+        modified: Option<SystemTime>,
+    },
+    Owned(Vec<u8>),
+}
+
+/// A file that is memory-mapped instead of loaded, with a sparse index of its lines.
+///
+/// Only the pages that are read are loaded, and the OS can evict them again, so memory use
+/// doesn't grow with the size of the file.
+pub struct MappedFile {
+    contents: Contents,
+    /// The byte offset of every `LINES_PER_CHECKPOINT`-th line, starting with the first.
+    checkpoints: Vec<u64>,
+    line_count: u64,
+}
+
+impl MappedFile {
+    /// Maps the file at `path` and indexes its lines. This reads the whole file once, so it
+    /// should be done on a background thread.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path).with_context(|| format!("Failed to open {path:?}"))?;
+        let metadata = file.metadata()?;
+        // Mapping an empty file fails on some platforms.
+        if metadata.len() == 0 {
+            return Ok(Self::from_bytes(Vec::new()));
+        }
+        // SAFETY: The mapping is read-only. If another process truncates the file while it's
+        // mapped, reading past the new end faults, so callers check `is_stale` before reading
+        // and drop stale mappings. Edits made through Zed release the mapping first.
+        let mmap =
+            unsafe { Mmap::map(&file) }.with_context(|| format!("Failed to map {path:?}"))?;
+        Ok(Self::new(Contents::Mapped {
+            mmap,
+            file,
+            modified: metadata.modified().ok(),
+        }))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self::new(Contents::Owned(bytes))
+    }
+
+    fn new(contents: Contents) -> Self {
+        let bytes = match &contents {
+            Contents::Mapped { mmap, .. } => &mmap[..],
+            Contents::Owned(bytes) => &bytes[..],
+        };
+        let mut checkpoints = vec![0];
+        let mut line_count = 1;
+        for newline in memchr::memchr_iter(b'\n', bytes) {
+            if line_count % LINES_PER_CHECKPOINT == 0 {
+                checkpoints.push(newline as u64 + 1);
+            }
+            line_count += 1;
+        }
+        Self {
+            contents,
+            checkpoints,
+            line_count,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        match &self.contents {
+            Contents::Mapped { mmap, .. } => mmap,
+            Contents::Owned(bytes) => bytes,
+        }
+    }
+
+    /// Whether the file was changed on disk since it was mapped. Reading a mapping whose file
+    /// was truncated faults, so a stale file must be reopened instead of read.
+    pub fn is_stale(&self) -> bool {
+        match &self.contents {
+            Contents::Mapped {
+                mmap,
+                file,
+                modified,
+            } => !file.metadata().is_ok_and(|metadata| {
+                metadata.len() == mmap.len() as u64 && metadata.modified().ok() == *modified
+            }),
+            Contents::Owned(_) => false,
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.bytes().len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes().is_empty()
+    }
+
+    /// The number of lines, counting the empty line after a trailing newline like the editor.
+    pub fn line_count(&self) -> u64 {
+        self.line_count
+    }
+
+    /// The byte range of a line, without its line ending.
+    pub fn line_range(&self, row: u64) -> Range<u64> {
+        let bytes = self.bytes();
+        let row = row.min(self.line_count - 1);
+        let mut start = self.checkpoints[(row / LINES_PER_CHECKPOINT) as usize] as usize;
+        for _ in 0..row % LINES_PER_CHECKPOINT {
+            start += memchr::memchr(b'\n', &bytes[start..]).map_or(0, |ix| ix + 1);
+        }
+        let mut end = memchr::memchr(b'\n', &bytes[start..]).map_or(bytes.len(), |ix| start + ix);
+        if end > start && bytes[end - 1] == b'\r' {
+            end -= 1;
+        }
+        start as u64..end as u64
+    }
+
+    /// The text of a line for display, cut off at [`MAX_DISPLAYED_LINE_LEN`] bytes. Invalid
+    /// UTF-8 is replaced.
+    pub fn line_text(&self, row: u64) -> Cow<'_, str> {
+        let range = self.line_range(row);
+        let end = range.end.min(range.start + MAX_DISPLAYED_LINE_LEN as u64);
+        self.text_for_range(range.start..end)
+    }
+
+    pub fn text_for_range(&self, range: Range<u64>) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.bytes()[range.start as usize..range.end as usize])
+    }
+
+    /// The zero-based row containing a byte offset.
+    pub fn row_for_offset(&self, offset: u64) -> u64 {
+        let offset = offset.min(self.len());
+        let checkpoint = self.checkpoints.partition_point(|start| *start <= offset) - 1;
+        let start = self.checkpoints[checkpoint] as usize;
+        let newlines = memchr::memchr_iter(b'\n', &self.bytes()[start..offset as usize]).count();
+        checkpoint as u64 * LINES_PER_CHECKPOINT + newlines as u64
+    }
+
+    /// Finds up to `limit` matches of `regex`, in order. Returns whether there were more.
+    pub fn find(&self, regex: &Regex, limit: usize) -> (Vec<Range<u64>>, bool) {
+        let mut matches = regex
+            .find_iter(self.bytes())
+            .filter(|found| !found.is_empty())
+            .map(|found| found.start() as u64..found.end() as u64)
+            .take(limit + 1)
+            .collect::<Vec<_>>();
+        let truncated = matches.len() > limit;
+        matches.truncate(limit);
+        (matches, truncated)
+    }
+}
+
+/// A rewritten file that has been written next to the original, but not moved over it yet.
+///
+/// Replacing a file that is still mapped fails on Windows, so the mapping of the original has
+/// to be released before committing.
+#[must_use]
+pub struct PendingWrite {
+    temp_file: tempfile::NamedTempFile,
+    path: PathBuf,
+}
+
+impl PendingWrite {
+    pub fn commit(self) -> Result<()> {
+        if let Ok(metadata) = fs::metadata(&self.path) {
+            fs::set_permissions(self.temp_file.path(), metadata.permissions()).ok();
+        }
+        self.temp_file
+            .persist(&self.path)
+            .with_context(|| format!("Failed to replace {:?}", self.path))?;
+        Ok(())
+    }
+}
+
+/// Replaces ranges of the file at `path`, whose current contents are `file`. The edits must be
+/// sorted and must not overlap.
+///
+/// If every replacement has the same length as its range, they're written in place. Otherwise
+/// the file is rewritten into a [`PendingWrite`].
+pub fn write_edits(
+    path: &Path,
+    file: &MappedFile,
+    edits: &[(Range<u64>, String)],
+) -> Result<Option<PendingWrite>> {
+    anyhow::ensure!(!file.is_stale(), "{path:?} changed on disk");
+    let mut previous_end = 0;
+    for (range, _) in edits {
+        anyhow::ensure!(
+            previous_end <= range.start && range.start <= range.end && range.end <= file.len(),
+            "Edits are out of bounds or overlap"
+        );
+        previous_end = range.end;
+    }
+
+    if edits
+        .iter()
+        .all(|(range, new_text)| range.end - range.start == new_text.len() as u64)
+    {
+        write_in_place(path, edits)?;
+        return Ok(None);
+    }
+    rewrite(path, file, edits.iter().cloned()).map(Some)
+}
+
+/// Replaces every match of `regex` in the file at `path`, whose current contents are `file`,
+/// with the text `replacement_for` returns for it. Matches it returns `None` for are kept.
+///
+/// Returns `None` if nothing was replaced.
+pub fn replace_all(
+    path: &Path,
+    file: &MappedFile,
+    regex: &Regex,
+    replacement_for: impl Fn(&str) -> Option<String>,
+) -> Result<Option<PendingWrite>> {
+    anyhow::ensure!(!file.is_stale(), "{path:?} changed on disk");
+    let mut edits = regex
+        .find_iter(file.bytes())
+        .filter(|found| !found.is_empty())
+        .filter_map(|found| {
+            let replacement = replacement_for(&String::from_utf8_lossy(found.as_bytes()))?;
+            Some((found.start() as u64..found.end() as u64, replacement))
+        })
+        .peekable();
+    if edits.peek().is_none() {
+        return Ok(None);
+    }
+    rewrite(path, file, edits).map(Some)
+}
+
+/// Writes the file with the given edits applied to a temporary file next to it, streaming
+/// from the mapping so that it is never fully loaded.
+fn rewrite(
+    path: &Path,
+    file: &MappedFile,
+    edits: impl Iterator<Item = (Range<u64>, String)>,
+) -> Result<PendingWrite> {
+    let directory = path.parent().context("File has no parent directory")?;
+    let temp_file = tempfile::NamedTempFile::new_in(directory)
+        .context("Failed to create a temporary file next to the edited file")?;
+    let mut writer = BufWriter::new(temp_file.as_file());
+    let bytes = file.bytes();
+    let mut copied_up_to = 0;
+    for (range, new_text) in edits {
+        writer.write_all(&bytes[copied_up_to..range.start as usize])?;
+        writer.write_all(new_text.as_bytes())?;
+        copied_up_to = range.end as usize;
+    }
+    writer.write_all(&bytes[copied_up_to..])?;
+    writer.flush()?;
+    drop(writer);
+    Ok(PendingWrite {
+        temp_file,
+        path: path.to_path_buf(),
+    })
+}
+
+fn write_in_place(path: &Path, edits: &[(Range<u64>, String)]) -> Result<()> {
+    use std::io::{Seek as _, SeekFrom};
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {path:?} for writing"))?;
+    for (range, new_text) in edits {
+        file.seek(SeekFrom::Start(range.start))?;
+        file.write_all(new_text.as_bytes())?;
+    }
+    file.sync_data()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn numbered_lines(count: u64) -> String {
+        (0..count).map(|row| format!("line {row}\n")).collect()
+    }
+
+    #[test]
+    fn test_line_index() {
+        let file = MappedFile::from_bytes(numbered_lines(5000).into_bytes());
+        assert_eq!(file.line_count(), 5001);
+        for row in [0, 1, 1023, 1024, 1025, 2048, 4999] {
+            assert_eq!(file.line_text(row), format!("line {row}"));
+            let range = file.line_range(row);
+            assert_eq!(file.row_for_offset(range.start), row);
+            assert_eq!(file.row_for_offset(range.end), row);
+        }
+        assert_eq!(file.line_text(5000), "");
+        assert_eq!(file.row_for_offset(file.len()), 5000);
+
+        let file = MappedFile::from_bytes(b"a\r\nb".to_vec());
+        assert_eq!(file.line_count(), 2);
+        assert_eq!(file.line_text(0), "a");
+        assert_eq!(file.line_text(1), "b");
+
+        let file = MappedFile::from_bytes(Vec::new());
+        assert_eq!(file.line_count(), 1);
+        assert_eq!(file.line_text(0), "");
+    }
+
+    #[test]
+    fn test_long_lines_are_cut_off() {
+        let file = MappedFile::from_bytes("x".repeat(MAX_DISPLAYED_LINE_LEN * 2).into_bytes());
+        assert_eq!(file.line_text(0).len(), MAX_DISPLAYED_LINE_LEN);
+        assert_eq!(file.line_range(0), 0..MAX_DISPLAYED_LINE_LEN as u64 * 2);
+    }
+
+    #[test]
+    fn test_find() {
+        let file = MappedFile::from_bytes(numbered_lines(20).into_bytes());
+        let regex = Regex::new(r"line 1\d").unwrap();
+        let (matches, truncated) = file.find(&regex, 3);
+        assert!(truncated);
+        assert_eq!(
+            matches
+                .iter()
+                .map(|range| file.text_for_range(range.clone()))
+                .collect::<Vec<_>>(),
+            vec!["line 10", "line 11", "line 12"]
+        );
+        assert_eq!(file.row_for_offset(matches[0].start), 10);
+
+        let (matches, truncated) = file.find(&regex, 100);
+        assert!(!truncated);
+        assert_eq!(matches.len(), 10);
+    }
+
+    #[test]
+    fn test_write_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dump.sql");
+        fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+        let file = MappedFile::open(&path).unwrap();
+        let edits = [(file.line_range(1), "TWO".to_string())];
+        assert!(write_edits(&path, &file, &edits).unwrap().is_none());
+        drop(file);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\nTWO\nthree\n");
+
+        let file = MappedFile::open(&path).unwrap();
+        let edits = [
+            (file.line_range(0), "1".to_string()),
+            (file.line_range(2), "3".to_string()),
+        ];
+        let pending = write_edits(&path, &file, &edits).unwrap().unwrap();
+        drop(file);
+        pending.commit().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1\nTWO\n3\n");
+
+        let file = MappedFile::open(&path).unwrap();
+        assert!(write_edits(&path, &file, &[(0..100, String::new())]).is_err());
+        let overlapping = [(0..3, String::new()), (1..2, String::new())];
+        assert!(write_edits(&path, &file, &overlapping).is_err());
+    }
+
+    #[test]
+    fn test_replace_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dump.sql");
+        // More lines than the view keeps search matches for.
+        let line_count = 20_000;
+        fs::write(&path, numbered_lines(line_count)).unwrap();
+
+        let file = MappedFile::open(&path).unwrap();
+        let regex = Regex::new(r"line (\d+)").unwrap();
+        let pending = replace_all(&path, &file, &regex, |text| {
+            let row = text.strip_prefix("line ")?;
+            (row != "3").then(|| format!("row {row}"))
+        })
+        .unwrap()
+        .unwrap();
+        drop(file);
+        pending.commit().unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count() as u64, line_count);
+        assert!(contents.starts_with("row 0\nrow 1\nrow 2\nline 3\nrow 4\n"));
+        assert!(contents.ends_with(&format!("row {}\n", line_count - 1)));
+
+        let file = MappedFile::open(&path).unwrap();
+        let regex = Regex::new("missing").unwrap();
+        assert!(
+            replace_all(&path, &file, &regex, |_| Some(String::new()))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_stale_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dump.sql");
+        fs::write(&path, numbered_lines(10)).unwrap();
+
+        let file = MappedFile::open(&path).unwrap();
+        assert!(!file.is_stale());
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(4)
+            .unwrap();
+        assert!(file.is_stale());
+        let edits = [(0..4, "LINE".to_string())];
+        assert!(write_edits(&path, &file, &edits).is_err());
+    }
+}
@@ -3,9 +3,14 @@ use gpui::{
     UnderlineStyle, px,
 };
 use language::HighlightId;
-use std::{fmt::Display, ops::Range, path::PathBuf};
+use std::{fmt::Display, ops::Range, path::PathBuf, sync::Arc};
 use urlencoding;
 
+use crate::{
+    markdown_math::{MathError, MathNode},
+    markdown_mermaid::{MermaidDiagram, MermaidError},
+};
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum ParsedMarkdownElement {
@@ -14,10 +19,16 @@ pub enum ParsedMarkdownElement {
     Table(ParsedMarkdownTable),
     BlockQuote(ParsedMarkdownBlockQuote),
     CodeBlock(ParsedMarkdownCodeBlock),
+    /// A fenced code block in the `mermaid` language, drawn as a diagram.
+    Mermaid(ParsedMarkdownMermaid),
     /// A paragraph of text and other inline elements.
     Paragraph(MarkdownParagraph),
     HorizontalRule(Range<usize>),
     Image(Image),
+    /// Terms followed by their definitions.
+    DefinitionList(ParsedMarkdownDefinitionList),
+    /// The text of a footnote, which is moved to the end of the document.
+    FootnoteDefinition(ParsedMarkdownFootnoteDefinition),
 }
 
 impl ParsedMarkdownElement {
@@ -28,18 +39,65 @@ impl ParsedMarkdownElement {
             Self::Table(table) => table.source_range.clone(),
             Self::BlockQuote(block_quote) => block_quote.source_range.clone(),
             Self::CodeBlock(code_block) => code_block.source_range.clone(),
+            Self::Mermaid(mermaid) => mermaid.source_range.clone(),
             Self::Paragraph(text) => match text.get(0)? {
                 MarkdownParagraphChunk::Text(t) => t.source_range.clone(),
                 MarkdownParagraphChunk::Image(image) => image.source_range.clone(),
+                MarkdownParagraphChunk::Math(math) => math.source_range.clone(),
             },
             Self::HorizontalRule(range) => range.clone(),
             Self::Image(image) => image.source_range.clone(),
+            Self::DefinitionList(list) => list.source_range.clone(),
+            Self::FootnoteDefinition(footnote) => footnote.source_range.clone(),
         })
     }
 
     pub fn is_list_item(&self) -> bool {
         matches!(self, Self::ListItem(_))
     }
+
+    /// Whether this element, or one nested in it, links to the footnote with the given label.
+    pub fn references_footnote(&self, label: &str) -> bool {
+        let in_paragraph = |paragraph: &MarkdownParagraph| {
+            paragraph.iter().any(|chunk| match chunk {
+                MarkdownParagraphChunk::Text(text) => {
+                    text.regions.iter().any(|(_, region)| match &region.link {
+                        Some(Link::Footnote { label: link_label }) => link_label.as_ref() == label,
+                        _ => false,
+                    })
+                }
+                MarkdownParagraphChunk::Image(_) | MarkdownParagraphChunk::Math(_) => false,
+            })
+        };
+        let in_elements = |elements: &[ParsedMarkdownElement]| {
+            elements
+                .iter()
+                .any(|element| element.references_footnote(label))
+        };
+        match self {
+            Self::Heading(heading) => in_paragraph(&heading.contents),
+            Self::Paragraph(paragraph) => in_paragraph(paragraph),
+            Self::ListItem(list_item) => in_elements(&list_item.content),
+            Self::BlockQuote(block_quote) => in_elements(&block_quote.children),
+            Self::FootnoteDefinition(footnote) => in_elements(&footnote.children),
+            Self::Table(table) => table
+                .header
+                .iter()
+                .chain(&table.body)
+                .flat_map(|row| &row.columns)
+                .any(|column| in_paragraph(&column.children)),
+            Self::DefinitionList(list) => list.items.iter().any(|item| {
+                in_paragraph(&item.term)
+                    || item
+                        .definitions
+                        .iter()
+                        .any(|definition| in_elements(definition))
+            }),
+            Self::CodeBlock(_) | Self::Mermaid(_) | Self::HorizontalRule(_) | Self::Image(_) => {
+                false
+            }
+        }
+    }
 }
 
 pub type MarkdownParagraph = Vec<MarkdownParagraphChunk>;
@@ -49,6 +107,8 @@ pub type MarkdownParagraph = Vec<MarkdownParagraphChunk>;
 pub enum MarkdownParagraphChunk {
     Text(ParsedMarkdownText),
     Image(Image),
+    /// Display math, which is typeset on its own line.
+    Math(ParsedMarkdownMath),
 }
 
 #[derive(Debug)]
@@ -86,6 +146,23 @@ pub struct ParsedMarkdownCodeBlock {
     pub highlights: Option<Vec<(Range<usize>, HighlightId)>>,
 }
 
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownMermaid {
+    pub source_range: Range<usize>,
+    pub contents: SharedString,
+    pub diagram: Result<Arc<MermaidDiagram>, MermaidError>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownMath {
+    pub source_range: Range<usize>,
+    /// The TeX source, without the surrounding `$$`.
+    pub source: SharedString,
+    pub node: Result<Arc<MathNode>, MathError>,
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct ParsedMarkdownHeading {
@@ -161,6 +238,54 @@ impl ParsedMarkdownTableRow {
 pub struct ParsedMarkdownBlockQuote {
     pub source_range: Range<usize>,
     pub children: Vec<ParsedMarkdownElement>,
+    /// The kind of GitHub alert, like `> [!NOTE]`, that the block quote is.
+    pub alert: Option<ParsedMarkdownAlertKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParsedMarkdownAlertKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl ParsedMarkdownAlertKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Note => "Note",
+            Self::Tip => "Tip",
+            Self::Important => "Important",
+            Self::Warning => "Warning",
+            Self::Caution => "Caution",
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownDefinitionList {
+    pub source_range: Range<usize>,
+    pub items: Vec<ParsedMarkdownDefinitionListItem>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownDefinitionListItem {
+    pub term: MarkdownParagraph,
+    /// The definitions of the term, each of which can hold several blocks.
+    pub definitions: Vec<Vec<ParsedMarkdownElement>>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownFootnoteDefinition {
+    pub source_range: Range<usize>,
+    pub label: SharedString,
+    /// The number shown for the footnote, in the order footnotes are first referenced.
+    pub number: usize,
+    pub children: Vec<ParsedMarkdownElement>,
 }
 
 #[derive(Debug, Clone)]
@@ -275,6 +400,11 @@ pub enum Link {
         /// The absolute path to the item.
         path: PathBuf,
     },
+    /// A reference to a footnote in the same document.
+    Footnote {
+        /// The label the footnote is defined with.
+        label: SharedString,
+    },
 }
 
 impl Link {
@@ -313,6 +443,7 @@ impl Display for Link {
         match self {
             Link::Web { url } => write!(f, "{}", url),
             Link::Path { display_path, .. } => write!(f, "{}", display_path.display()),
+            Link::Footnote { label } => write!(f, "[^{}]", label),
         }
     }
 }
@@ -0,0 +1,754 @@
+//! Exporting parsed Markdown as a standalone HTML document.
+//!
+//! The document carries its own stylesheet, derived from the active theme, and embeds local
+//! images as data URIs so that it can be shared as a single file. The PDF export in
+//! [`crate::markdown_pdf`] shares the theme and text styling defined here.
+
+use std::{collections::HashSet, fmt::Write as _, ops::Range, path::Path, sync::Arc};
+
+use base64::Engine as _;
+use gpui::{AbsoluteLength, DefiniteLength, FontStyle, FontWeight, Hsla};
+use theme::SyntaxTheme;
+
+use crate::{
+    markdown_elements::{
+        HeadingLevel, Image, Link, MarkdownHighlight, MarkdownParagraph, MarkdownParagraphChunk,
+        ParsedMarkdown, ParsedMarkdownAlertKind, ParsedMarkdownCodeBlock, ParsedMarkdownElement,
+        ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownMath,
+        ParsedMarkdownMermaid, ParsedMarkdownTable, ParsedMarkdownTableAlignment,
+        ParsedMarkdownText,
+    },
+    markdown_math::{math_to_unicode, render_math_svg},
+    markdown_mermaid::{MermaidColors, escape_xml, hex_color, render_mermaid_svg},
+    markdown_renderer::list_item_prefix,
+};
+
+/// The colors and fonts an export is styled with, taken from the active theme.
+#[derive(Clone)]
+pub struct ExportTheme {
+    pub text: Hsla,
+    pub muted_text: Hsla,
+    pub background: Hsla,
+    pub surface_background: Hsla,
+    pub code_span_background: Hsla,
+    pub border: Hsla,
+    pub link: Hsla,
+    pub info: Hsla,
+    pub success: Hsla,
+    pub hint: Hsla,
+    pub warning: Hsla,
+    pub error: Hsla,
+    pub syntax: Arc<SyntaxTheme>,
+    pub ui_font_family: String,
+    pub buffer_font_family: String,
+}
+
+impl ExportTheme {
+    pub(crate) fn alert_color(&self, kind: ParsedMarkdownAlertKind) -> Hsla {
+        match kind {
+            ParsedMarkdownAlertKind::Note => self.info,
+            ParsedMarkdownAlertKind::Tip => self.success,
+            ParsedMarkdownAlertKind::Important => self.hint,
+            ParsedMarkdownAlertKind::Warning => self.warning,
+            ParsedMarkdownAlertKind::Caution => self.error,
+        }
+    }
+
+    pub(crate) fn mermaid_colors(&self) -> MermaidColors {
+        MermaidColors {
+            text: self.text,
+            border: self.muted_text,
+            background: self.surface_background,
+            note_background: self.code_span_background,
+        }
+    }
+}
+
+/// How a run of inline text is styled, with Markdown emphasis and code highlights merged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct RunStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub code: bool,
+    pub color: Option<Hsla>,
+    pub link: Option<String>,
+}
+
+/// Splits text into runs that each have a single style.
+pub(crate) fn styled_runs(
+    text: &ParsedMarkdownText,
+    syntax: &SyntaxTheme,
+) -> Vec<(Range<usize>, RunStyle)> {
+    let len = text.contents.len();
+    let mut boundaries = vec![0, len];
+    for range in text
+        .highlights
+        .iter()
+        .map(|(range, _)| range)
+        .chain(text.regions.iter().map(|(range, _)| range))
+    {
+        boundaries.push(range.start.min(len));
+        boundaries.push(range.end.min(len));
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut runs: Vec<(Range<usize>, RunStyle)> = Vec::new();
+    for window in boundaries.windows(2) {
+        let segment = window[0]..window[1];
+        if segment.is_empty() || !text.contents.is_char_boundary(segment.start) {
+            continue;
+        }
+        let contains =
+            |range: &Range<usize>| range.start <= segment.start && segment.end <= range.end;
+
+        let mut style = RunStyle::default();
+        for (_, highlight) in text.highlights.iter().filter(|(range, _)| contains(range)) {
+            match highlight {
+                MarkdownHighlight::Style(highlight) => {
+                    style.bold |= highlight.weight > FontWeight::NORMAL;
+                    style.italic |= highlight.italic || highlight.oblique;
+                    style.underline |= highlight.underline || highlight.link;
+                    style.strikethrough |= highlight.strikethrough;
+                }
+                MarkdownHighlight::Code(id) => {
+                    if let Some(highlight) = id.style(syntax) {
+                        style.color = highlight.color.or(style.color);
+                        style.bold |= highlight
+                            .font_weight
+                            .is_some_and(|weight| weight > FontWeight::NORMAL);
+                        style.italic |= highlight.font_style == Some(FontStyle::Italic);
+                    }
+                }
+            }
+        }
+        for (_, region) in text.regions.iter().filter(|(range, _)| contains(range)) {
+            style.code |= region.code;
+            if let Some(link) = &region.link {
+                style.link = Some(link_target(link));
+            }
+        }
+
+        match runs.last_mut() {
+            Some((range, last_style)) if range.end == segment.start && *last_style == style => {
+                range.end = segment.end;
+            }
+            _ => runs.push((segment, style)),
+        }
+    }
+    runs
+}
+
+/// Where a link points in an exported document, which is relative for files, as written.
+pub(crate) fn link_target(link: &Link) -> String {
+    match link {
+        Link::Web { url } => url.clone(),
+        Link::Path { display_path, .. } => display_path.to_string_lossy().into_owned(),
+        Link::Footnote { label } => format!("#{}", footnote_anchor(label)),
+    }
+}
+
+/// The id of a footnote's definition in an HTML export.
+fn footnote_anchor(label: &str) -> String {
+    let slug = label
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+    format!("fn-{slug}")
+}
+
+/// The highlight color of each run of a code block.
+pub(crate) fn code_block_runs(
+    code_block: &ParsedMarkdownCodeBlock,
+    syntax: &SyntaxTheme,
+) -> Vec<(Range<usize>, Option<Hsla>)> {
+    let len = code_block.contents.len();
+    let mut runs = Vec::new();
+    let mut offset = 0;
+    for (range, id) in code_block.highlights.iter().flatten() {
+        if range.start < offset || range.end > len {
+            continue;
+        }
+        if offset < range.start {
+            runs.push((offset..range.start, None));
+        }
+        let color = id.style(syntax).and_then(|style| style.color);
+        runs.push((range.clone(), color));
+        offset = range.end;
+    }
+    if offset < len {
+        runs.push((offset..len, None));
+    }
+    runs
+}
+
+/// The Markdown bullet or number for a list item.
+pub(crate) fn list_marker(item: &ParsedMarkdownListItem) -> String {
+    let depth = item.depth.saturating_sub(1) as usize;
+    match &item.item_type {
+        ParsedMarkdownListItemType::Ordered(order) => {
+            list_item_prefix(*order as usize, true, depth)
+                .trim_end()
+                .to_string()
+        }
+        ParsedMarkdownListItemType::Unordered => {
+            list_item_prefix(1, false, depth).trim_end().to_string()
+        }
+        ParsedMarkdownListItemType::Task(checked, _) => {
+            if *checked { "☑" } else { "☐" }.to_string()
+        }
+    }
+}
+
+pub(crate) fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+/// Renders a parsed Markdown document as a standalone HTML page.
+pub fn export_html(parsed: &ParsedMarkdown, title: &str, theme: &ExportTheme) -> String {
+    let mut html = String::new();
+    write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<main>\n",
+        escape_xml(title),
+        stylesheet(theme),
+    )
+    .ok();
+    let mut writer = HtmlWriter {
+        html,
+        theme,
+        referenced_footnotes: HashSet::default(),
+    };
+    for element in &parsed.children {
+        writer.element(element);
+    }
+    writer.html.push_str("</main>\n</body>\n</html>\n");
+    writer.html
+}
+
+fn stylesheet(theme: &ExportTheme) -> String {
+    let text = hex_color(theme.text);
+    let muted = hex_color(theme.muted_text);
+    let background = hex_color(theme.background);
+    let surface = hex_color(theme.surface_background);
+    let code_span = hex_color(theme.code_span_background);
+    let border = hex_color(theme.border);
+    let link = hex_color(theme.link);
+    let error = hex_color(theme.error);
+    let note = hex_color(theme.info);
+    let tip = hex_color(theme.success);
+    let important = hex_color(theme.hint);
+    let warning = hex_color(theme.warning);
+    let ui_font = css_font_family(&theme.ui_font_family);
+    let buffer_font = css_font_family(&theme.buffer_font_family);
+    format!(
+        "body {{ margin: 0; background: {background}; color: {text}; font-family: {ui_font}, -apple-system, BlinkMacSystemFont, \"Segoe UI\", sans-serif; line-height: 1.6; }}
+main {{ max-width: 48rem; margin: 0 auto; padding: 2rem; }}
+a {{ color: {link}; }}
+h1, h2 {{ border-bottom: 1px solid {border}; padding-bottom: 0.3em; }}
+code, pre {{ font-family: {buffer_font}, ui-monospace, monospace; font-size: 0.9em; }}
+code {{ background: {code_span}; padding: 0.1em 0.3em; border-radius: 3px; }}
+pre {{ background: {surface}; padding: 1rem; border-radius: 4px; overflow-x: auto; line-height: 1.45; }}
+pre code {{ background: none; padding: 0; font-size: 1em; }}
+blockquote {{ margin: 0 0 1rem; padding-left: 1rem; border-left: 4px solid {border}; }}
+table {{ border-collapse: collapse; margin-bottom: 1rem; }}
+caption {{ color: {muted}; }}
+th, td {{ border: 1px solid {border}; padding: 0.25rem 0.75rem; }}
+th {{ background: {surface}; }}
+hr {{ border: none; height: 2px; background: {border}; }}
+img, svg {{ max-width: 100%; height: auto; }}
+.list-item {{ display: flex; gap: 0.5rem; }}
+.list-item > div > p:first-child {{ margin-top: 0; }}
+.list-item > div > p:last-child {{ margin-bottom: 0.25rem; }}
+.list-marker {{ flex: none; }}
+.math {{ text-align: center; margin: 1rem 0; }}
+.diagram {{ overflow-x: auto; margin: 1rem 0; }}
+.error {{ color: {error}; }}
+.alert-title {{ font-weight: 600; }}
+.alert-note {{ border-left-color: {note}; }} .alert-note .alert-title {{ color: {note}; }}
+.alert-tip {{ border-left-color: {tip}; }} .alert-tip .alert-title {{ color: {tip}; }}
+.alert-important {{ border-left-color: {important}; }} .alert-important .alert-title {{ color: {important}; }}
+.alert-warning {{ border-left-color: {warning}; }} .alert-warning .alert-title {{ color: {warning}; }}
+.alert-caution {{ border-left-color: {error}; }} .alert-caution .alert-title {{ color: {error}; }}
+dt {{ font-weight: 600; }}
+dd {{ margin-left: 1.5rem; }}
+.footnote {{ display: flex; gap: 0.5rem; color: {muted}; font-size: 0.875em; }}
+.footnote > div > p {{ margin: 0 0 0.25rem; }}
+"
+    )
+}
+
+fn css_font_family(family: &str) -> String {
+    format!("\"{}\"", family.replace(['"', '\\', '<'], ""))
+}
+
+struct HtmlWriter<'a> {
+    html: String,
+    theme: &'a ExportTheme,
+    /// Footnotes whose first reference has been written, which is what their back-link points to.
+    referenced_footnotes: HashSet<String>,
+}
+
+impl HtmlWriter<'_> {
+    fn element(&mut self, element: &ParsedMarkdownElement) {
+        match element {
+            ParsedMarkdownElement::Heading(heading) => {
+                let level = match heading.level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                write!(self.html, "<h{level}>").ok();
+                self.inline(&heading.contents);
+                writeln!(self.html, "</h{level}>").ok();
+            }
+            ParsedMarkdownElement::Paragraph(paragraph) => self.paragraph(paragraph),
+            ParsedMarkdownElement::ListItem(item) => self.list_item(item),
+            ParsedMarkdownElement::Table(table) => self.table(table),
+            ParsedMarkdownElement::BlockQuote(block_quote) => {
+                match block_quote.alert {
+                    Some(kind) => {
+                        let title = kind.title();
+                        writeln!(
+                            self.html,
+                            "<blockquote class=\"alert alert-{}\">\n<p class=\"alert-title\">{title}</p>",
+                            title.to_lowercase()
+                        )
+                        .ok();
+                    }
+                    None => self.html.push_str("<blockquote>\n"),
+                }
+                for child in &block_quote.children {
+                    self.element(child);
+                }
+                self.html.push_str("</blockquote>\n");
+            }
+            ParsedMarkdownElement::DefinitionList(list) => {
+                self.html.push_str("<dl>\n");
+                for item in &list.items {
+                    self.html.push_str("<dt>");
+                    self.inline(&item.term);
+                    self.html.push_str("</dt>\n");
+                    for definition in &item.definitions {
+                        self.html.push_str("<dd>\n");
+                        for child in definition {
+                            self.element(child);
+                        }
+                        self.html.push_str("</dd>\n");
+                    }
+                }
+                self.html.push_str("</dl>\n");
+            }
+            ParsedMarkdownElement::FootnoteDefinition(footnote) => {
+                let anchor = footnote_anchor(&footnote.label);
+                writeln!(
+                    self.html,
+                    "<div class=\"footnote\" id=\"{}\"><span>{}.</span><div>",
+                    escape_xml(&anchor),
+                    footnote.number
+                )
+                .ok();
+                for child in &footnote.children {
+                    self.element(child);
+                }
+                self.html.push_str("</div>");
+                if self.referenced_footnotes.contains(footnote.label.as_ref()) {
+                    write!(
+                        self.html,
+                        "<a href=\"#{}\" aria-label=\"Back to reference\">↩</a>",
+                        escape_xml(&format!("{anchor}-ref"))
+                    )
+                    .ok();
+                }
+                self.html.push_str("</div>\n");
+            }
+            ParsedMarkdownElement::CodeBlock(code_block) => self.code_block(code_block),
+            ParsedMarkdownElement::Mermaid(mermaid) => self.mermaid(mermaid),
+            ParsedMarkdownElement::HorizontalRule(_) => self.html.push_str("<hr>\n"),
+            ParsedMarkdownElement::Image(image) => {
+                self.html.push_str("<p>");
+                self.image(image);
+                self.html.push_str("</p>\n");
+            }
+        }
+    }
+
+    /// Writes a paragraph, breaking it around display math, which can't be inside a `<p>`.
+    fn paragraph(&mut self, paragraph: &MarkdownParagraph) {
+        let mut in_paragraph = false;
+        for chunk in paragraph {
+            match chunk {
+                MarkdownParagraphChunk::Math(math) => {
+                    if in_paragraph {
+                        self.html.push_str("</p>\n");
+                        in_paragraph = false;
+                    }
+                    self.math(math);
+                }
+                chunk => {
+                    if !in_paragraph {
+                        self.html.push_str("<p>");
+                        in_paragraph = true;
+                    }
+                    self.inline_chunk(chunk);
+                }
+            }
+        }
+        if in_paragraph {
+            self.html.push_str("</p>\n");
+        }
+    }
+
+    fn inline(&mut self, paragraph: &MarkdownParagraph) {
+        for chunk in paragraph {
+            self.inline_chunk(chunk);
+        }
+    }
+
+    fn inline_chunk(&mut self, chunk: &MarkdownParagraphChunk) {
+        match chunk {
+            MarkdownParagraphChunk::Text(text) => self.text(text),
+            MarkdownParagraphChunk::Image(image) => self.image(image),
+            MarkdownParagraphChunk::Math(math) => match &math.node {
+                Ok(node) => {
+                    write!(self.html, "<em>{}</em>", escape_xml(&math_to_unicode(node))).ok();
+                }
+                Err(_) => {
+                    write!(self.html, "$${}$$", escape_xml(&math.source)).ok();
+                }
+            },
+        }
+    }
+
+    fn text(&mut self, text: &ParsedMarkdownText) {
+        let footnote_labels = text
+            .regions
+            .iter()
+            .filter_map(|(range, region)| match &region.link {
+                Some(Link::Footnote { label }) => Some((range.clone(), label)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (range, style) in styled_runs(text, &self.theme.syntax) {
+            let mut closing = Vec::new();
+            if let Some(link) = &style.link {
+                write!(self.html, "<a href=\"{}\"", escape_xml(link)).ok();
+                let footnote = footnote_labels.iter().find(|(footnote_range, _)| {
+                    footnote_range.start <= range.start && range.end <= footnote_range.end
+                });
+                if let Some((_, label)) = footnote
+                    && self.referenced_footnotes.insert(label.to_string())
+                {
+                    write!(self.html, " id=\"{}-ref\"", escape_xml(&link[1..])).ok();
+                }
+                self.html.push('>');
+                closing.push("</a>");
+            }
+            for (enabled, open, close) in [
+                (style.code, "<code>", "</code>"),
+                (style.bold, "<strong>", "</strong>"),
+                (style.italic, "<em>", "</em>"),
+                (style.underline && style.link.is_none(), "<u>", "</u>"),
+                (style.strikethrough, "<s>", "</s>"),
+            ] {
+                if enabled {
+                    self.html.push_str(open);
+                    closing.push(close);
+                }
+            }
+            if let Some(color) = style.color {
+                write!(self.html, "<span style=\"color: {}\">", hex_color(color)).ok();
+                closing.push("</span>");
+            }
+            self.html
+                .push_str(&escape_xml(&text.contents[range]).replace('\n', "<br>\n"));
+            for close in closing.into_iter().rev() {
+                self.html.push_str(close);
+            }
+        }
+    }
+
+    fn image(&mut self, image: &Image) {
+        let source = match &image.link {
+            Link::Web { url } => url.clone(),
+            Link::Path { path, display_path } => {
+                match (std::fs::read(path).ok(), image_mime_type(path)) {
+                    (Some(bytes), Some(mime_type)) => format!(
+                        "data:{mime_type};base64,{}",
+                        base64::engine::general_purpose::STANDARD.encode(bytes)
+                    ),
+                    _ => display_path.to_string_lossy().into_owned(),
+                }
+            }
+        };
+        let mut style = String::new();
+        if let Some(width) = image.width.and_then(css_length) {
+            write!(style, "width: {width};").ok();
+        }
+        if let Some(height) = image.height.and_then(css_length) {
+            write!(style, "height: {height};").ok();
+        }
+        write!(
+            self.html,
+            "<img src=\"{}\" alt=\"{}\"",
+            escape_xml(&source),
+            escape_xml(image.alt_text.as_deref().unwrap_or_default()),
+        )
+        .ok();
+        if !style.is_empty() {
+            write!(self.html, " style=\"{style}\"").ok();
+        }
+        self.html.push('>');
+    }
+
+    fn math(&mut self, math: &ParsedMarkdownMath) {
+        match &math.node {
+            Ok(node) => {
+                let svg = render_math_svg(node, 20., self.theme.text);
+                writeln!(self.html, "<div class=\"math\">{}</div>", svg.svg).ok();
+            }
+            Err(error) => {
+                writeln!(
+                    self.html,
+                    "<pre><code>$${}$$</code></pre>\n<p class=\"error\">{}</p>",
+                    escape_xml(&math.source),
+                    escape_xml(&error.to_string()),
+                )
+                .ok();
+            }
+        }
+    }
+
+    fn mermaid(&mut self, mermaid: &ParsedMarkdownMermaid) {
+        match &mermaid.diagram {
+            Ok(diagram) => {
+                let svg = render_mermaid_svg(diagram, &self.theme.mermaid_colors());
+                writeln!(self.html, "<div class=\"diagram\">{}</div>", svg.svg).ok();
+            }
+            Err(error) => {
+                writeln!(
+                    self.html,
+                    "<pre><code>{}</code></pre>\n<p class=\"error\">{}</p>",
+                    escape_xml(&mermaid.contents),
+                    escape_xml(&error.to_string()),
+                )
+                .ok();
+            }
+        }
+    }
+
+    fn list_item(&mut self, item: &ParsedMarkdownListItem) {
+        let indent = if item.nested {
+            0
+        } else {
+            item.depth.saturating_sub(1)
+        };
+        write!(
+            self.html,
+            "<div class=\"list-item\" style=\"margin-left: {}em\"><span class=\"list-marker\">",
+            f32::from(indent) * 1.5
+        )
+        .ok();
+        match &item.item_type {
+            ParsedMarkdownListItemType::Task(checked, _) => {
+                let checked = if *checked { " checked" } else { "" };
+                write!(self.html, "<input type=\"checkbox\" disabled{checked}>").ok();
+            }
+            _ => self.html.push_str(&escape_xml(&list_marker(item))),
+        }
+        self.html.push_str("</span><div>\n");
+        for child in &item.content {
+            self.element(child);
+        }
+        self.html.push_str("</div></div>\n");
+    }
+
+    fn table(&mut self, table: &ParsedMarkdownTable) {
+        self.html.push_str("<table>\n");
+        if let Some(caption) = &table.caption {
+            self.html.push_str("<caption>");
+            self.inline(caption);
+            self.html.push_str("</caption>\n");
+        }
+        for (rows, in_header) in [(&table.header, true), (&table.body, false)] {
+            if rows.is_empty() {
+                continue;
+            }
+            self.html
+                .push_str(if in_header { "<thead>\n" } else { "<tbody>\n" });
+            for row in rows {
+                self.html.push_str("<tr>");
+                for column in &row.columns {
+                    let tag = if in_header || column.is_header {
+                        "th"
+                    } else {
+                        "td"
+                    };
+                    write!(self.html, "<{tag}").ok();
+                    if column.col_span > 1 {
+                        write!(self.html, " colspan=\"{}\"", column.col_span).ok();
+                    }
+                    if column.row_span > 1 {
+                        write!(self.html, " rowspan=\"{}\"", column.row_span).ok();
+                    }
+                    let alignment = match column.alignment {
+                        ParsedMarkdownTableAlignment::None => None,
+                        ParsedMarkdownTableAlignment::Left => Some("left"),
+                        ParsedMarkdownTableAlignment::Center => Some("center"),
+                        ParsedMarkdownTableAlignment::Right => Some("right"),
+                    };
+                    if let Some(alignment) = alignment {
+                        write!(self.html, " style=\"text-align: {alignment}\"").ok();
+                    }
+                    self.html.push('>');
+                    self.inline(&column.children);
+                    write!(self.html, "</{tag}>").ok();
+                }
+                self.html.push_str("</tr>\n");
+            }
+            self.html.push_str(if in_header {
+                "</thead>\n"
+            } else {
+                "</tbody>\n"
+            });
+        }
+        self.html.push_str("</table>\n");
+    }
+
+    fn code_block(&mut self, code_block: &ParsedMarkdownCodeBlock) {
+        self.html.push_str("<pre><code");
+        if let Some(language) = &code_block.language {
+            write!(self.html, " class=\"language-{}\"", escape_xml(language)).ok();
+        }
+        self.html.push('>');
+        for (range, color) in code_block_runs(code_block, &self.theme.syntax) {
+            let text = escape_xml(&code_block.contents[range]);
+            match color {
+                Some(color) => {
+                    write!(
+                        self.html,
+                        "<span style=\"color: {}\">{text}</span>",
+                        hex_color(color)
+                    )
+                    .ok();
+                }
+                None => self.html.push_str(&text),
+            }
+        }
+        self.html.push_str("</code></pre>\n");
+    }
+}
+
+fn css_length(length: DefiniteLength) -> Option<String> {
+    match length {
+        DefiniteLength::Absolute(AbsoluteLength::Pixels(pixels)) => {
+            Some(format!("{}px", f32::from(pixels)))
+        }
+        DefiniteLength::Absolute(AbsoluteLength::Rems(rems)) => Some(format!("{}rem", rems.0)),
+        DefiniteLength::Fraction(fraction) => Some(format!("{}%", fraction * 100.)),
+    }
+}
+
+/// A theme with fixed colors, for testing exports.
+#[cfg(test)]
+pub(crate) fn test_theme() -> ExportTheme {
+    ExportTheme {
+        text: gpui::black(),
+        muted_text: gpui::black(),
+        background: gpui::white(),
+        surface_background: gpui::white(),
+        code_span_background: gpui::white(),
+        border: gpui::black(),
+        link: gpui::black(),
+        info: gpui::blue(),
+        success: gpui::green(),
+        hint: gpui::blue(),
+        warning: gpui::yellow(),
+        error: gpui::red(),
+        syntax: Arc::new(SyntaxTheme {
+            highlights: vec![(
+                "keyword".into(),
+                gpui::HighlightStyle {
+                    color: Some(gpui::red()),
+                    ..Default::default()
+                },
+            )],
+        }),
+        ui_font_family: "Zed Plex Sans".into(),
+        buffer_font_family: "Zed Plex Mono".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_parser::parse_markdown;
+    use gpui::red;
+    use language::HighlightId;
+
+    #[gpui::test]
+    async fn test_export_html() {
+        let parsed = parse_markdown(
+            "# A <Title>\n\nSome **bold** and [a link](https://zed.dev) with `code`.\n\n\
+             - [x] done\n- item\n\n| a | b |\n|---|--:|\n| 1 | 2 |\n\n$$x^2$$\n\n---\n",
+            None,
+            None,
+        )
+        .await;
+        let html = export_html(&parsed, "Doc & Notes", &test_theme());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Doc &amp; Notes</title>"));
+        assert!(html.contains("color: #000000"));
+        assert!(html.contains("<h1>A &lt;Title&gt;</h1>"));
+        assert!(html.contains(
+            "<p>Some <strong>bold</strong> and <a href=\"https://zed.dev\">a link</a> with <code>code</code>.</p>"
+        ));
+        assert!(html.contains("<input type=\"checkbox\" disabled checked>"));
+        assert!(html.contains("<span class=\"list-marker\">•</span>"));
+        assert!(html.contains("<th>a</th><th style=\"text-align: right\">b</th>"));
+        assert!(html.contains("<div class=\"math\"><svg"));
+        assert!(html.contains("<hr>"));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn test_code_block_runs() {
+        let code_block = ParsedMarkdownCodeBlock {
+            source_range: 0..0,
+            language: Some("rust".into()),
+            contents: "fn main() {}".into(),
+            highlights: Some(vec![(0..2, HighlightId(0))]),
+        };
+        let theme = test_theme();
+        assert_eq!(
+            code_block_runs(&code_block, &theme.syntax),
+            vec![(0..2, Some(red())), (2..12, None)]
+        );
+
+        let mut html = HtmlWriter {
+            html: String::new(),
+            theme: &theme,
+            referenced_footnotes: HashSet::default(),
+        };
+        html.code_block(&code_block);
+        assert_eq!(
+            html.html,
+            "<pre><code class=\"language-rust\"><span style=\"color: #ff0000\">fn</span> main() {}</code></pre>\n"
+        );
+    }
+}
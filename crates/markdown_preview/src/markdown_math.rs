@@ -0,0 +1,2216 @@
+//! Typesetting for the subset of TeX math used in Markdown documents.
+//!
+//! Display math (`$$...$$`) is parsed into a [`MathNode`] tree, laid out into positioned glyphs,
+//! rules and strokes by [`layout_math`], and painted by [`MathElement`]. Inline math (`$...$`)
+//! has to flow with the text around it, so [`math_to_unicode`] writes it out with Unicode math
+//! characters instead.
+
+use std::{fmt, fmt::Write as _, sync::Arc};
+
+use gpui::{
+    App, Bounds, Element, ElementId, Font, FontStyle, FontWeight, GlobalElementId, Hsla,
+    InspectorElementId, IntoElement, LayoutId, PathBuilder, Pixels, SharedString, Style, TextAlign,
+    TextRun, Window, WindowTextSystem, fill, point, px, size,
+};
+use util::ResultExt as _;
+
+use crate::markdown_mermaid::{escape_xml, hex_color};
+
+/// How far the math axis, which fractions and operators are centered on, sits above the
+/// baseline, in ems.
+const AXIS_HEIGHT: f32 = 0.25;
+/// The approximate height of a glyph above the baseline, in ems.
+const GLYPH_ASCENT: f32 = 0.72;
+/// The approximate depth of a glyph below the baseline, in ems.
+const GLYPH_DESCENT: f32 = 0.22;
+/// The thickness of fraction bars and radical strokes, in ems.
+const RULE_THICKNESS: f32 = 0.05;
+/// How deeply groups, arguments and environments may nest. Parsing and layout recurse once per
+/// level, so deeper formulas are rejected and shown as source instead of overflowing the stack.
+const MAX_NESTING_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MathNode {
+    Row(Vec<MathNode>),
+    Atom {
+        text: String,
+        class: AtomClass,
+        variant: MathVariant,
+    },
+    Scripts {
+        base: Box<MathNode>,
+        superscript: Option<Box<MathNode>>,
+        subscript: Option<Box<MathNode>>,
+    },
+    Fraction {
+        numerator: Box<MathNode>,
+        denominator: Box<MathNode>,
+    },
+    Sqrt {
+        index: Option<Box<MathNode>>,
+        radicand: Box<MathNode>,
+    },
+    /// A body between delimiters that grow to its height, from `\left` and `\right`.
+    Delimited {
+        left: String,
+        body: Box<MathNode>,
+        right: String,
+    },
+    Accent {
+        accent: Accent,
+        base: Box<MathNode>,
+    },
+    Overline(Box<MathNode>),
+    /// The rows and columns of an environment like `pmatrix`, `cases` or `aligned`.
+    Matrix {
+        rows: Vec<Vec<MathNode>>,
+        alignment: MatrixAlignment,
+        left: String,
+        right: String,
+    },
+    /// Horizontal space, in ems.
+    Space(f32),
+}
+
+/// The TeX atom classes, which determine the spacing between atoms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtomClass {
+    Ordinary,
+    /// A named function like `\sin`, or a large operator like `\sum`. Operators with `limits`
+    /// have their scripts above and below them in display math.
+    Operator {
+        large: bool,
+        limits: bool,
+    },
+    Binary,
+    Relation,
+    Open,
+    Close,
+    Punctuation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathVariant {
+    Italic,
+    Upright,
+    Bold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accent {
+    Hat,
+    Bar,
+    Vec,
+    Dot,
+    DoubleDot,
+    Tilde,
+}
+
+impl Accent {
+    fn glyph(self) -> &'static str {
+        match self {
+            Accent::Hat => "^",
+            Accent::Bar => "¯",
+            Accent::Vec => "→",
+            Accent::Dot => "˙",
+            Accent::DoubleDot => "¨",
+            Accent::Tilde => "~",
+        }
+    }
+
+    /// How far the middle of the accent glyph is above its baseline, in ems.
+    fn glyph_center(self) -> f32 {
+        match self {
+            Accent::Vec | Accent::Tilde => 0.3,
+            Accent::Hat | Accent::Bar | Accent::Dot | Accent::DoubleDot => 0.6,
+        }
+    }
+
+    fn combining_char(self) -> char {
+        match self {
+            Accent::Hat => '\u{302}',
+            Accent::Bar => '\u{304}',
+            Accent::Vec => '\u{20d7}',
+            Accent::Dot => '\u{307}',
+            Accent::DoubleDot => '\u{308}',
+            Accent::Tilde => '\u{303}',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixAlignment {
+    Center,
+    Left,
+    /// Columns alternate between right and left alignment, as in `aligned`.
+    Alternating,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MathError {
+    /// The byte offset in the math source where the error was found.
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for MathError {}
+
+/// Parses TeX math, without the surrounding `$` delimiters.
+pub fn parse_math(source: &str) -> Result<MathNode, MathError> {
+    let mut parser = MathParser {
+        source,
+        offset: 0,
+        depth: 0,
+    };
+    let mut rows = vec![vec![parser.parse_row(false)?]];
+    let mut has_columns = false;
+    // Line breaks and alignment points outside of an environment make the whole formula
+    // behave like `gathered` or `aligned`.
+    loop {
+        if parser.eat("&") {
+            has_columns = true;
+            let cell = parser.parse_row(false)?;
+            rows.last_mut().unwrap().push(cell);
+        } else if parser.eat("\\\\") {
+            let cell = parser.parse_row(false)?;
+            rows.push(vec![cell]);
+        } else {
+            break;
+        }
+    }
+    parser.skip_whitespace();
+    if parser.offset < source.len() {
+        return Err(parser.error(format!("Unexpected `{}`", parser.peek_token())));
+    }
+    if rows.len() == 1 && !has_columns {
+        return Ok(rows.pop().unwrap().pop().unwrap());
+    }
+    Ok(MathNode::Matrix {
+        rows,
+        alignment: if has_columns {
+            MatrixAlignment::Alternating
+        } else {
+            MatrixAlignment::Center
+        },
+        left: String::new(),
+        right: String::new(),
+    })
+}
+
+struct MathParser<'a> {
+    source: &'a str,
+    offset: usize,
+    depth: usize,
+}
+
+impl MathParser<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.offset..]
+    }
+
+    fn error(&self, message: impl Into<String>) -> MathError {
+        MathError {
+            offset: self.offset,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.offset += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek_token(&self) -> &str {
+        let rest = self.rest();
+        if let Some(command) = rest.strip_prefix('\\') {
+            let len = command
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(command.len())
+                .max(command.chars().next().map_or(0, char::len_utf8));
+            &rest[..1 + len]
+        } else {
+            rest.chars().next().map_or("", |c| &rest[..c.len_utf8()])
+        }
+    }
+
+    fn at_row_end(&self, in_brackets: bool) -> bool {
+        let rest = self.rest();
+        rest.is_empty()
+            || rest.starts_with('}')
+            || rest.starts_with('&')
+            || rest.starts_with("\\\\")
+            || rest.starts_with("\\end")
+            || (rest.starts_with("\\right") && !rest.starts_with("\\rightarrow"))
+            || (in_brackets && rest.starts_with(']'))
+    }
+
+    /// Parses atoms up to the end of the enclosing group, cell or delimited body.
+    fn parse_row(&mut self, in_brackets: bool) -> Result<MathNode, MathError> {
+        let mut children = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.at_row_end(in_brackets) {
+                break;
+            }
+            if let Some(node) = self.parse_atom()? {
+                children.push(node);
+            }
+        }
+        Ok(if children.len() == 1 {
+            children.pop().unwrap()
+        } else {
+            MathNode::Row(children)
+        })
+    }
+
+    /// Parses one atom along with any scripts attached to it.
+    fn parse_atom(&mut self) -> Result<Option<MathNode>, MathError> {
+        let base = if self.rest().starts_with('^') || self.rest().starts_with('_') {
+            MathNode::Row(Vec::new())
+        } else {
+            match self.parse_primary()? {
+                Some(base) => base,
+                None => return Ok(None),
+            }
+        };
+
+        let mut superscript = None;
+        let mut subscript = None;
+        loop {
+            self.skip_whitespace();
+            if self.eat("^") {
+                if superscript.is_some() {
+                    return Err(self.error("Double superscript"));
+                }
+                superscript = Some(Box::new(self.parse_argument()?));
+            } else if self.eat("_") {
+                if subscript.is_some() {
+                    return Err(self.error("Double subscript"));
+                }
+                subscript = Some(Box::new(self.parse_argument()?));
+            } else if self.eat("'") {
+                let prime = atom("′", AtomClass::Ordinary, MathVariant::Upright);
+                superscript = Some(Box::new(match superscript.take() {
+                    Some(existing) => MathNode::Row(vec![prime, *existing]),
+                    None => prime,
+                }));
+            } else {
+                break;
+            }
+        }
+
+        Ok(Some(if superscript.is_none() && subscript.is_none() {
+            base
+        } else {
+            MathNode::Scripts {
+                base: Box::new(base),
+                superscript,
+                subscript,
+            }
+        }))
+    }
+
+    /// Parses a braced group or a single atom without scripts, as taken by `^` or `\frac`.
+    fn parse_argument(&mut self) -> Result<MathNode, MathError> {
+        self.skip_whitespace();
+        if self.rest().is_empty() || self.at_row_end(false) {
+            return Err(self.error("Missing argument"));
+        }
+        loop {
+            if let Some(node) = self.parse_primary()? {
+                return Ok(node);
+            }
+            self.skip_whitespace();
+            if self.rest().is_empty() {
+                return Err(self.error("Missing argument"));
+            }
+        }
+    }
+
+    fn parse_group(&mut self) -> Result<MathNode, MathError> {
+        if !self.eat("{") {
+            return Err(self.error("Expected `{`"));
+        }
+        let row = self.parse_row(false)?;
+        if !self.eat("}") {
+            return Err(self.error("Expected `}`"));
+        }
+        Ok(row)
+    }
+
+    /// Reads the raw contents of a braced group, as taken by `\text` and `\begin`.
+    fn parse_raw_group(&mut self) -> Result<&str, MathError> {
+        if !self.eat("{") {
+            return Err(self.error("Expected `{`"));
+        }
+        let start = self.offset;
+        let mut depth = 0;
+        for (ix, c) in self.rest().char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => {
+                    self.offset = start + ix + 1;
+                    return Ok(&self.source[start..start + ix]);
+                }
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        Err(self.error("Expected `}`"))
+    }
+
+    fn parse_delimiter(&mut self) -> Result<String, MathError> {
+        self.skip_whitespace();
+        let token = self.peek_token().to_string();
+        let delimiter = match token.as_str() {
+            "." => "",
+            "(" | ")" | "[" | "]" | "|" | "/" => token.as_str(),
+            "\\{" | "\\lbrace" => "{",
+            "\\}" | "\\rbrace" => "}",
+            "\\|" | "\\Vert" => "‖",
+            "\\vert" | "\\lvert" | "\\rvert" => "|",
+            "\\langle" => "⟨",
+            "\\rangle" => "⟩",
+            "\\lfloor" => "⌊",
+            "\\rfloor" => "⌋",
+            "\\lceil" => "⌈",
+            "\\rceil" => "⌉",
+            "" => return Err(self.error("Missing delimiter")),
+            _ => return Err(self.error(format!("`{token}` is not a delimiter"))),
+        }
+        .to_string();
+        self.offset += token.len();
+        Ok(delimiter)
+    }
+
+    /// Every nested construct is parsed through here, so this is where nesting is limited.
+    fn parse_primary(&mut self) -> Result<Option<MathNode>, MathError> {
+        if self.depth == MAX_NESTING_DEPTH {
+            return Err(self.error("Formula is nested too deeply"));
+        }
+        self.depth += 1;
+        let result = self.parse_primary_unchecked();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_primary_unchecked(&mut self) -> Result<Option<MathNode>, MathError> {
+        self.skip_whitespace();
+        let Some(c) = self.rest().chars().next() else {
+            return Ok(None);
+        };
+
+        if c == '{' {
+            return self.parse_group().map(Some);
+        }
+        if c == '}' {
+            return Err(self.error("Unexpected `}`"));
+        }
+        if c == '\\' {
+            return self.parse_command();
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            let rest = self.rest();
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            let number = rest[..len].to_string();
+            self.offset += len;
+            return Ok(Some(atom(
+                &number,
+                AtomClass::Ordinary,
+                MathVariant::Upright,
+            )));
+        }
+
+        self.offset += c.len_utf8();
+        let node = match c {
+            '+' => atom("+", AtomClass::Binary, MathVariant::Upright),
+            '-' => atom("−", AtomClass::Binary, MathVariant::Upright),
+            '*' => atom("∗", AtomClass::Binary, MathVariant::Upright),
+            '=' | '<' | '>' | ':' => {
+                atom(&c.to_string(), AtomClass::Relation, MathVariant::Upright)
+            }
+            '(' | '[' => atom(&c.to_string(), AtomClass::Open, MathVariant::Upright),
+            ')' | ']' => atom(&c.to_string(), AtomClass::Close, MathVariant::Upright),
+            ',' | ';' => atom(&c.to_string(), AtomClass::Punctuation, MathVariant::Upright),
+            '~' => MathNode::Space(0.33),
+            c if c.is_alphabetic() => {
+                atom(&c.to_string(), AtomClass::Ordinary, MathVariant::Italic)
+            }
+            c => atom(&c.to_string(), AtomClass::Ordinary, MathVariant::Upright),
+        };
+        Ok(Some(node))
+    }
+
+    fn parse_command(&mut self) -> Result<Option<MathNode>, MathError> {
+        let start = self.offset;
+        let token = self.peek_token().to_string();
+        self.offset += token.len();
+        let name = &token[1..];
+
+        if let Some(space) = space_for_command(name) {
+            return Ok(Some(MathNode::Space(space)));
+        }
+        if let Some((text, class)) = symbol_for_command(name) {
+            let variant = match class {
+                AtomClass::Ordinary if is_lowercase_greek(name) => MathVariant::Italic,
+                _ => MathVariant::Upright,
+            };
+            return Ok(Some(atom(text, class, variant)));
+        }
+        if let Some(limits) = function_limits(name) {
+            return Ok(Some(atom(
+                name,
+                AtomClass::Operator {
+                    large: false,
+                    limits,
+                },
+                MathVariant::Upright,
+            )));
+        }
+
+        let node = match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.parse_argument()?;
+                let denominator = self.parse_argument()?;
+                MathNode::Fraction {
+                    numerator: Box::new(numerator),
+                    denominator: Box::new(denominator),
+                }
+            }
+            "sqrt" => {
+                let index = if self.eat("[") {
+                    let index = self.parse_row(true)?;
+                    if !self.eat("]") {
+                        return Err(self.error("Expected `]`"));
+                    }
+                    Some(Box::new(index))
+                } else {
+                    None
+                };
+                MathNode::Sqrt {
+                    index,
+                    radicand: Box::new(self.parse_argument()?),
+                }
+            }
+            "left" => {
+                let left = self.parse_delimiter()?;
+                let body = self.parse_row(false)?;
+                if !self.eat("\\right") {
+                    return Err(self.error("Missing `\\right`"));
+                }
+                let right = self.parse_delimiter()?;
+                MathNode::Delimited {
+                    left,
+                    body: Box::new(body),
+                    right,
+                }
+            }
+            "right" => return Err(self.error("Unexpected `\\right`")),
+            "text" | "textrm" | "textit" | "textbf" | "mbox" | "operatorname" => {
+                let text = self.parse_raw_group()?.to_string();
+                let variant = if name == "textbf" {
+                    MathVariant::Bold
+                } else if name == "textit" {
+                    MathVariant::Italic
+                } else {
+                    MathVariant::Upright
+                };
+                let class = if name == "operatorname" {
+                    AtomClass::Operator {
+                        large: false,
+                        limits: false,
+                    }
+                } else {
+                    AtomClass::Ordinary
+                };
+                atom(&text, class, variant)
+            }
+            "mathrm" | "mathbf" | "mathit" | "boldsymbol" | "bm" => {
+                let variant = match name {
+                    "mathrm" => MathVariant::Upright,
+                    "mathit" => MathVariant::Italic,
+                    _ => MathVariant::Bold,
+                };
+                with_variant(self.parse_argument()?, variant)
+            }
+            "mathbb" => map_letters(self.parse_argument()?, double_struck),
+            "mathcal" => map_letters(self.parse_argument()?, script_letter),
+            "hat" | "widehat" | "bar" | "vec" | "dot" | "ddot" | "tilde" | "widetilde" => {
+                let accent = match name {
+                    "hat" | "widehat" => Accent::Hat,
+                    "bar" => Accent::Bar,
+                    "vec" => Accent::Vec,
+                    "dot" => Accent::Dot,
+                    "ddot" => Accent::DoubleDot,
+                    _ => Accent::Tilde,
+                };
+                MathNode::Accent {
+                    accent,
+                    base: Box::new(self.parse_argument()?),
+                }
+            }
+            "overline" => MathNode::Overline(Box::new(self.parse_argument()?)),
+            "begin" => self.parse_environment()?,
+            "end" => return Err(self.error("Unexpected `\\end`")),
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => return Ok(None),
+            _ => {
+                return Err(MathError {
+                    offset: start,
+                    message: format!("Unknown command `{token}`"),
+                });
+            }
+        };
+        Ok(Some(node))
+    }
+
+    fn parse_environment(&mut self) -> Result<MathNode, MathError> {
+        let name = self.parse_raw_group()?.to_string();
+        let (alignment, left, right) = match name.as_str() {
+            "matrix" | "smallmatrix" => (MatrixAlignment::Center, "", ""),
+            "pmatrix" => (MatrixAlignment::Center, "(", ")"),
+            "bmatrix" => (MatrixAlignment::Center, "[", "]"),
+            "Bmatrix" => (MatrixAlignment::Center, "{", "}"),
+            "vmatrix" => (MatrixAlignment::Center, "|", "|"),
+            "Vmatrix" => (MatrixAlignment::Center, "‖", "‖"),
+            "cases" => (MatrixAlignment::Left, "{", ""),
+            "aligned" | "align" | "align*" | "split" => (MatrixAlignment::Alternating, "", ""),
+            "gathered" | "gather" | "gather*" => (MatrixAlignment::Center, "", ""),
+            _ => return Err(self.error(format!("Unknown environment `{name}`"))),
+        };
+
+        let mut rows = vec![Vec::new()];
+        loop {
+            let cell = self.parse_row(false)?;
+            rows.last_mut().unwrap().push(cell);
+            if self.eat("&") {
+                continue;
+            } else if self.eat("\\\\") {
+                rows.push(Vec::new());
+            } else if self.eat("\\end") {
+                let end = self.parse_raw_group()?;
+                if end != name {
+                    return Err(self.error(format!("Expected `\\end{{{name}}}`")));
+                }
+                break;
+            } else {
+                return Err(self.error(format!("Missing `\\end{{{name}}}`")));
+            }
+        }
+        // A trailing `\\` leaves an empty row behind.
+        if rows.len() > 1
+            && rows
+                .last()
+                .is_some_and(|row| row.len() == 1 && row[0] == MathNode::Row(Vec::new()))
+        {
+            rows.pop();
+        }
+
+        Ok(MathNode::Matrix {
+            rows,
+            alignment,
+            left: left.to_string(),
+            right: right.to_string(),
+        })
+    }
+}
+
+fn atom(text: &str, class: AtomClass, variant: MathVariant) -> MathNode {
+    MathNode::Atom {
+        text: text.to_string(),
+        class,
+        variant,
+    }
+}
+
+fn with_variant(node: MathNode, variant: MathVariant) -> MathNode {
+    map_atoms(node, &mut |text, class, _| MathNode::Atom {
+        text,
+        class,
+        variant,
+    })
+}
+
+fn map_letters(node: MathNode, map: fn(char) -> Option<char>) -> MathNode {
+    map_atoms(node, &mut |text, class, variant| {
+        let mapped = text
+            .chars()
+            .map(|c| map(c).unwrap_or(c))
+            .collect::<String>();
+        let variant = if mapped != text {
+            MathVariant::Upright
+        } else {
+            variant
+        };
+        MathNode::Atom {
+            text: mapped,
+            class,
+            variant,
+        }
+    })
+}
+
+fn map_atoms(
+    node: MathNode,
+    f: &mut dyn FnMut(String, AtomClass, MathVariant) -> MathNode,
+) -> MathNode {
+    match node {
+        MathNode::Atom {
+            text,
+            class,
+            variant,
+        } => f(text, class, variant),
+        MathNode::Row(children) => MathNode::Row(
+            children
+                .into_iter()
+                .map(|child| map_atoms(child, f))
+                .collect(),
+        ),
+        MathNode::Scripts {
+            base,
+            superscript,
+            subscript,
+        } => MathNode::Scripts {
+            base: Box::new(map_atoms(*base, f)),
+            superscript,
+            subscript,
+        },
+        node => node,
+    }
+}
+
+fn double_struck(c: char) -> Option<char> {
+    Some(match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        'A'..='Z' => char::from_u32(0x1d538 + (c as u32 - 'A' as u32))?,
+        '0'..='9' => char::from_u32(0x1d7d8 + (c as u32 - '0' as u32))?,
+        _ => return None,
+    })
+}
+
+fn script_letter(c: char) -> Option<char> {
+    Some(match c {
+        'B' => 'ℬ',
+        'E' => 'ℰ',
+        'F' => 'ℱ',
+        'H' => 'ℋ',
+        'I' => 'ℐ',
+        'L' => 'ℒ',
+        'M' => 'ℳ',
+        'R' => 'ℛ',
+        'A'..='Z' => char::from_u32(0x1d49c + (c as u32 - 'A' as u32))?,
+        _ => return None,
+    })
+}
+
+fn is_lowercase_greek(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && symbol_for_command(name).is_some_and(|(text, _)| {
+            text.chars()
+                .next()
+                .is_some_and(|c| ('\u{3b1}'..='\u{3f5}').contains(&c))
+        })
+}
+
+fn space_for_command(name: &str) -> Option<f32> {
+    Some(match name {
+        "," | "thinspace" => 3. / 18.,
+        ":" | ">" | "medspace" => 4. / 18.,
+        ";" | "thickspace" => 5. / 18.,
+        "!" | "negthinspace" => -3. / 18.,
+        " " => 0.33,
+        "quad" => 1.,
+        "qquad" => 2.,
+        _ => return None,
+    })
+}
+
+fn function_limits(name: &str) -> Option<bool> {
+    match name {
+        "lim" | "liminf" | "limsup" | "max" | "min" | "sup" | "inf" | "det" | "gcd" | "Pr" => {
+            Some(true)
+        }
+        "sin" | "cos" | "tan" | "cot" | "sec" | "csc" | "arcsin" | "arccos" | "arctan" | "sinh"
+        | "cosh" | "tanh" | "coth" | "log" | "ln" | "lg" | "exp" | "dim" | "ker" | "deg"
+        | "arg" | "hom" => Some(false),
+        _ => None,
+    }
+}
+
+fn symbol_for_command(name: &str) -> Option<(&'static str, AtomClass)> {
+    use AtomClass::*;
+    let large = |limits| Operator {
+        large: true,
+        limits,
+    };
+    Some(match name {
+        "alpha" => ("α", Ordinary),
+        "beta" => ("β", Ordinary),
+        "gamma" => ("γ", Ordinary),
+        "delta" => ("δ", Ordinary),
+        "epsilon" => ("ϵ", Ordinary),
+        "varepsilon" => ("ε", Ordinary),
+        "zeta" => ("ζ", Ordinary),
+        "eta" => ("η", Ordinary),
+        "theta" => ("θ", Ordinary),
+        "vartheta" => ("ϑ", Ordinary),
+        "iota" => ("ι", Ordinary),
+        "kappa" => ("κ", Ordinary),
+        "lambda" => ("λ", Ordinary),
+        "mu" => ("μ", Ordinary),
+        "nu" => ("ν", Ordinary),
+        "xi" => ("ξ", Ordinary),
+        "pi" => ("π", Ordinary),
+        "varpi" => ("ϖ", Ordinary),
+        "rho" => ("ρ", Ordinary),
+        "varrho" => ("ϱ", Ordinary),
+        "sigma" => ("σ", Ordinary),
+        "varsigma" => ("ς", Ordinary),
+        "tau" => ("τ", Ordinary),
+        "upsilon" => ("υ", Ordinary),
+        "phi" => ("ϕ", Ordinary),
+        "varphi" => ("φ", Ordinary),
+        "chi" => ("χ", Ordinary),
+        "psi" => ("ψ", Ordinary),
+        "omega" => ("ω", Ordinary),
+        "Gamma" => ("Γ", Ordinary),
+        "Delta" => ("Δ", Ordinary),
+        "Theta" => ("Θ", Ordinary),
+        "Lambda" => ("Λ", Ordinary),
+        "Xi" => ("Ξ", Ordinary),
+        "Pi" => ("Π", Ordinary),
+        "Sigma" => ("Σ", Ordinary),
+        "Upsilon" => ("Υ", Ordinary),
+        "Phi" => ("Φ", Ordinary),
+        "Psi" => ("Ψ", Ordinary),
+        "Omega" => ("Ω", Ordinary),
+
+        "infty" => ("∞", Ordinary),
+        "partial" => ("∂", Ordinary),
+        "nabla" => ("∇", Ordinary),
+        "forall" => ("∀", Ordinary),
+        "exists" => ("∃", Ordinary),
+        "nexists" => ("∄", Ordinary),
+        "emptyset" | "varnothing" => ("∅", Ordinary),
+        "neg" | "lnot" => ("¬", Ordinary),
+        "aleph" => ("ℵ", Ordinary),
+        "hbar" => ("ℏ", Ordinary),
+        "ell" => ("ℓ", Ordinary),
+        "Re" => ("ℜ", Ordinary),
+        "Im" => ("ℑ", Ordinary),
+        "wp" => ("℘", Ordinary),
+        "angle" => ("∠", Ordinary),
+        "triangle" => ("△", Ordinary),
+        "prime" => ("′", Ordinary),
+        "top" => ("⊤", Ordinary),
+        "bot" => ("⊥", Ordinary),
+        "degree" => ("°", Ordinary),
+        "square" | "Box" => ("□", Ordinary),
+        "cdots" => ("⋯", Ordinary),
+        "ldots" | "dots" => ("…", Ordinary),
+        "vdots" => ("⋮", Ordinary),
+        "ddots" => ("⋱", Ordinary),
+        "%" => ("%", Ordinary),
+        "$" => ("$", Ordinary),
+        "#" => ("#", Ordinary),
+        "&" => ("&", Ordinary),
+        "_" => ("_", Ordinary),
+
+        "pm" => ("±", Binary),
+        "mp" => ("∓", Binary),
+        "times" => ("×", Binary),
+        "div" => ("÷", Binary),
+        "cdot" => ("⋅", Binary),
+        "ast" => ("∗", Binary),
+        "star" => ("⋆", Binary),
+        "circ" => ("∘", Binary),
+        "bullet" => ("∙", Binary),
+        "oplus" => ("⊕", Binary),
+        "ominus" => ("⊖", Binary),
+        "otimes" => ("⊗", Binary),
+        "cup" => ("∪", Binary),
+        "cap" => ("∩", Binary),
+        "setminus" => ("∖", Binary),
+        "wedge" | "land" => ("∧", Binary),
+        "vee" | "lor" => ("∨", Binary),
+
+        "leq" | "le" => ("≤", Relation),
+        "geq" | "ge" => ("≥", Relation),
+        "neq" | "ne" => ("≠", Relation),
+        "approx" => ("≈", Relation),
+        "equiv" => ("≡", Relation),
+        "sim" => ("∼", Relation),
+        "simeq" => ("≃", Relation),
+        "cong" => ("≅", Relation),
+        "propto" => ("∝", Relation),
+        "ll" => ("≪", Relation),
+        "gg" => ("≫", Relation),
+        "subset" => ("⊂", Relation),
+        "supset" => ("⊃", Relation),
+        "subseteq" => ("⊆", Relation),
+        "supseteq" => ("⊇", Relation),
+        "in" => ("∈", Relation),
+        "notin" => ("∉", Relation),
+        "ni" => ("∋", Relation),
+        "to" | "rightarrow" => ("→", Relation),
+        "leftarrow" | "gets" => ("←", Relation),
+        "leftrightarrow" => ("↔", Relation),
+        "Rightarrow" => ("⇒", Relation),
+        "Leftarrow" => ("⇐", Relation),
+        "Leftrightarrow" => ("⇔", Relation),
+        "longrightarrow" => ("⟶", Relation),
+        "longleftarrow" => ("⟵", Relation),
+        "implies" => ("⟹", Relation),
+        "iff" => ("⟺", Relation),
+        "mapsto" => ("↦", Relation),
+        "uparrow" => ("↑", Relation),
+        "downarrow" => ("↓", Relation),
+        "perp" => ("⊥", Relation),
+        "parallel" => ("∥", Relation),
+        "mid" => ("∣", Relation),
+        "models" => ("⊨", Relation),
+        "vdash" => ("⊢", Relation),
+        "coloneqq" => ("≔", Relation),
+
+        "sum" => ("∑", large(true)),
+        "prod" => ("∏", large(true)),
+        "coprod" => ("∐", large(true)),
+        "bigcup" => ("⋃", large(true)),
+        "bigcap" => ("⋂", large(true)),
+        "bigoplus" => ("⨁", large(true)),
+        "bigotimes" => ("⨂", large(true)),
+        "bigvee" => ("⋁", large(true)),
+        "bigwedge" => ("⋀", large(true)),
+        "int" => ("∫", large(false)),
+        "iint" => ("∬", large(false)),
+        "iiint" => ("∭", large(false)),
+        "oint" => ("∮", large(false)),
+
+        "{" | "lbrace" => ("{", Open),
+        "}" | "rbrace" => ("}", Close),
+        "langle" => ("⟨", Open),
+        "rangle" => ("⟩", Close),
+        "lfloor" => ("⌊", Open),
+        "rfloor" => ("⌋", Close),
+        "lceil" => ("⌈", Open),
+        "rceil" => ("⌉", Close),
+        "lvert" => ("|", Open),
+        "rvert" => ("|", Close),
+        "|" | "Vert" => ("‖", Ordinary),
+        "vert" => ("|", Ordinary),
+        _ => return None,
+    })
+}
+
+/// Writes math out as plain text, using Unicode superscripts, subscripts and symbols where
+/// they exist. This is how inline math is shown, since it has to wrap along with its paragraph.
+pub fn math_to_unicode(node: &MathNode) -> String {
+    let mut output = String::new();
+    write_unicode(node, &mut output);
+    output.trim().to_string()
+}
+
+fn write_unicode(node: &MathNode, output: &mut String) {
+    match node {
+        MathNode::Row(children) => {
+            let classes = spacing_classes(children);
+            for (child, class) in children.iter().zip(classes) {
+                match class {
+                    Some(AtomClass::Binary | AtomClass::Relation) => {
+                        output.push(' ');
+                        write_unicode(child, output);
+                        output.push(' ');
+                    }
+                    Some(AtomClass::Punctuation) => {
+                        write_unicode(child, output);
+                        output.push(' ');
+                    }
+                    Some(AtomClass::Operator { .. }) => {
+                        write_unicode(child, output);
+                        output.push('\u{2009}');
+                    }
+                    _ => write_unicode(child, output),
+                }
+            }
+        }
+        MathNode::Atom { text, .. } => output.push_str(text),
+        MathNode::Scripts {
+            base,
+            superscript,
+            subscript,
+        } => {
+            write_unicode(base, output);
+            if let Some(subscript) = subscript {
+                write_script(subscript, subscript_char, '_', output);
+            }
+            if let Some(superscript) = superscript {
+                write_script(superscript, superscript_char, '^', output);
+            }
+        }
+        MathNode::Fraction {
+            numerator,
+            denominator,
+        } => {
+            write_grouped(numerator, output);
+            output.push('/');
+            write_grouped(denominator, output);
+        }
+        MathNode::Sqrt { index, radicand } => {
+            if let Some(index) = index {
+                write_script(index, superscript_char, '^', output);
+            }
+            output.push('√');
+            write_grouped(radicand, output);
+        }
+        MathNode::Delimited { left, body, right } => {
+            output.push_str(left);
+            write_unicode(body, output);
+            output.push_str(right);
+        }
+        MathNode::Accent { accent, base } => {
+            write_unicode(base, output);
+            output.push(accent.combining_char());
+        }
+        MathNode::Overline(base) => {
+            let mut text = String::new();
+            write_unicode(base, &mut text);
+            for c in text.chars() {
+                output.push(c);
+                output.push('\u{305}');
+            }
+        }
+        MathNode::Matrix {
+            rows, left, right, ..
+        } => {
+            output.push_str(left);
+            for (row_ix, row) in rows.iter().enumerate() {
+                if row_ix > 0 {
+                    output.push_str("; ");
+                }
+                for (column_ix, cell) in row.iter().enumerate() {
+                    if column_ix > 0 {
+                        output.push_str(", ");
+                    }
+                    write_unicode(cell, output);
+                }
+            }
+            output.push_str(right);
+        }
+        MathNode::Space(ems) => {
+            if *ems >= 0.15 {
+                output.push(' ');
+            }
+        }
+    }
+}
+
+/// Writes a node, wrapped in parentheses unless it's a single atom.
+fn write_grouped(node: &MathNode, output: &mut String) {
+    let mut text = String::new();
+    write_unicode(node, &mut text);
+    let text = text.trim();
+    if matches!(node, MathNode::Atom { .. }) || text.chars().all(|c| c.is_alphanumeric()) {
+        output.push_str(text);
+    } else {
+        output.push('(');
+        output.push_str(text);
+        output.push(')');
+    }
+}
+
+fn write_script(node: &MathNode, map: fn(char) -> Option<char>, marker: char, output: &mut String) {
+    let mut text = String::new();
+    write_unicode(node, &mut text);
+    let text = text.trim();
+    if let Some(mapped) = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(map)
+        .collect::<Option<String>>()
+    {
+        output.push_str(&mapped);
+    } else {
+        output.push(marker);
+        if text.chars().count() == 1 {
+            output.push_str(text);
+        } else {
+            output.push('(');
+            output.push_str(text);
+            output.push(')');
+        }
+    }
+}
+
+fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '−' | '-' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        '′' => '′',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'T' => 'ᵀ',
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '−' | '-' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+/// The TeX styles, which shrink scripts and nested fractions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathStyle {
+    Display,
+    Text,
+    Script,
+    ScriptScript,
+}
+
+impl MathStyle {
+    fn scale(self) -> f32 {
+        match self {
+            MathStyle::Display | MathStyle::Text => 1.,
+            MathStyle::Script => 0.7,
+            MathStyle::ScriptScript => 0.5,
+        }
+    }
+
+    fn script(self) -> Self {
+        match self {
+            MathStyle::Display | MathStyle::Text => MathStyle::Script,
+            MathStyle::Script | MathStyle::ScriptScript => MathStyle::ScriptScript,
+        }
+    }
+
+    fn fraction(self) -> Self {
+        match self {
+            MathStyle::Display => MathStyle::Text,
+            MathStyle::Text => MathStyle::Script,
+            MathStyle::Script | MathStyle::ScriptScript => MathStyle::ScriptScript,
+        }
+    }
+
+    fn is_script(self) -> bool {
+        matches!(self, MathStyle::Script | MathStyle::ScriptScript)
+    }
+}
+
+/// Measures the advance width of text, so that layout doesn't depend on a window.
+pub trait MathMeasure {
+    fn measure(&mut self, text: &str, font_size: f32, variant: MathVariant) -> f32;
+}
+
+/// Something to paint, positioned relative to the left edge and baseline of its box, with `y`
+/// growing downwards.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MathItem {
+    Glyphs {
+        text: SharedString,
+        x: f32,
+        y: f32,
+        font_size: f32,
+        variant: MathVariant,
+    },
+    Rule {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    Stroke {
+        points: Vec<(f32, f32)>,
+        thickness: f32,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MathBox {
+    pub width: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    pub items: Vec<MathItem>,
+}
+
+impl MathBox {
+    fn height(&self) -> f32 {
+        self.ascent + self.descent
+    }
+
+    /// Places another box with its baseline at `(dx, dy)` relative to this box's baseline.
+    fn place(&mut self, other: MathBox, dx: f32, dy: f32) {
+        self.ascent = self.ascent.max(other.ascent - dy);
+        self.descent = self.descent.max(other.descent + dy);
+        self.items.extend(
+            other
+                .items
+                .into_iter()
+                .map(|item| offset_item(item, dx, dy)),
+        );
+    }
+
+    fn push(&mut self, item: MathItem) {
+        self.items.push(item);
+    }
+}
+
+fn offset_item(item: MathItem, dx: f32, dy: f32) -> MathItem {
+    match item {
+        MathItem::Glyphs {
+            text,
+            x,
+            y,
+            font_size,
+            variant,
+        } => MathItem::Glyphs {
+            text,
+            x: x + dx,
+            y: y + dy,
+            font_size,
+            variant,
+        },
+        MathItem::Rule {
+            x,
+            y,
+            width,
+            height,
+        } => MathItem::Rule {
+            x: x + dx,
+            y: y + dy,
+            width,
+            height,
+        },
+        MathItem::Stroke { points, thickness } => MathItem::Stroke {
+            points: points.into_iter().map(|(x, y)| (x + dx, y + dy)).collect(),
+            thickness,
+        },
+    }
+}
+
+/// Lays out math at `font_size` pixels.
+pub fn layout_math(
+    node: &MathNode,
+    style: MathStyle,
+    font_size: f32,
+    measure: &mut dyn MathMeasure,
+) -> MathBox {
+    MathLayout {
+        base_size: font_size,
+        measure,
+    }
+    .layout(node, style)
+}
+
+struct MathLayout<'a> {
+    base_size: f32,
+    measure: &'a mut dyn MathMeasure,
+}
+
+impl MathLayout<'_> {
+    fn layout(&mut self, node: &MathNode, style: MathStyle) -> MathBox {
+        let size = self.base_size * style.scale();
+        match node {
+            MathNode::Row(children) => self.layout_row(children, style),
+            MathNode::Atom {
+                text,
+                class,
+                variant,
+            } => self.layout_atom(text, *class, *variant, style),
+            MathNode::Scripts {
+                base,
+                superscript,
+                subscript,
+            } => self.layout_scripts(base, superscript.as_deref(), subscript.as_deref(), style),
+            MathNode::Fraction {
+                numerator,
+                denominator,
+            } => self.layout_fraction(numerator, denominator, style),
+            MathNode::Sqrt { index, radicand } => {
+                self.layout_sqrt(index.as_deref(), radicand, style)
+            }
+            MathNode::Delimited { left, body, right } => {
+                let body = self.layout(body, style);
+                self.layout_delimited(left, body, right, style)
+            }
+            MathNode::Accent { accent, base } => {
+                let mut result = self.layout(base, style);
+                let glyph_width = self
+                    .measure
+                    .measure(accent.glyph(), size, MathVariant::Upright);
+                let center_y = -(result.ascent + 0.15 * size);
+                result.push(MathItem::Glyphs {
+                    text: accent.glyph().into(),
+                    x: (result.width - glyph_width) / 2.,
+                    y: center_y + accent.glyph_center() * size,
+                    font_size: size,
+                    variant: MathVariant::Upright,
+                });
+                result.ascent += 0.3 * size;
+                result
+            }
+            MathNode::Overline(base) => {
+                let mut result = self.layout(base, style);
+                let thickness = rule_thickness(size);
+                let y = -(result.ascent + 0.12 * size + thickness);
+                result.push(MathItem::Rule {
+                    x: 0.,
+                    y,
+                    width: result.width,
+                    height: thickness,
+                });
+                result.ascent = -y + 0.05 * size;
+                result
+            }
+            MathNode::Matrix {
+                rows,
+                alignment,
+                left,
+                right,
+            } => {
+                let body = self.layout_matrix(rows, *alignment, style);
+                if left.is_empty() && right.is_empty() {
+                    body
+                } else {
+                    self.layout_delimited(left, body, right, style)
+                }
+            }
+            MathNode::Space(ems) => MathBox {
+                width: ems * size,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn layout_row(&mut self, children: &[MathNode], style: MathStyle) -> MathBox {
+        let size = self.base_size * style.scale();
+        let classes = spacing_classes(children);
+        let mut result = MathBox::default();
+        let mut previous_class = None;
+        for (child, class) in children.iter().zip(classes) {
+            if let (Some(previous), Some(class)) = (previous_class, class) {
+                result.width += spacing_between(previous, class, style) * size;
+            }
+            let child_box = self.layout(child, style);
+            let width = child_box.width;
+            result.place(child_box, result.width, 0.);
+            result.width += width;
+            if class.is_some() {
+                previous_class = class;
+            }
+        }
+        result
+    }
+
+    fn layout_atom(
+        &mut self,
+        text: &str,
+        class: AtomClass,
+        variant: MathVariant,
+        style: MathStyle,
+    ) -> MathBox {
+        let size = self.base_size * style.scale();
+        let (font_size, y) = match class {
+            AtomClass::Operator { large: true, .. } => {
+                let font_size = if style == MathStyle::Display {
+                    size * 1.6
+                } else {
+                    size * 1.2
+                };
+                // Center the operator on the math axis.
+                let y = -AXIS_HEIGHT * size + (GLYPH_ASCENT - GLYPH_DESCENT) / 2. * font_size;
+                (font_size, y)
+            }
+            _ => (size, 0.),
+        };
+        let width = self.measure.measure(text, font_size, variant);
+        MathBox {
+            width,
+            ascent: GLYPH_ASCENT * font_size - y,
+            descent: GLYPH_DESCENT * font_size + y,
+            items: vec![MathItem::Glyphs {
+                text: text.to_string().into(),
+                x: 0.,
+                y,
+                font_size,
+                variant,
+            }],
+        }
+    }
+
+    fn layout_scripts(
+        &mut self,
+        base: &MathNode,
+        superscript: Option<&MathNode>,
+        subscript: Option<&MathNode>,
+        style: MathStyle,
+    ) -> MathBox {
+        let size = self.base_size * style.scale();
+        let has_limits = style == MathStyle::Display
+            && matches!(
+                base,
+                MathNode::Atom {
+                    class: AtomClass::Operator { limits: true, .. },
+                    ..
+                }
+            );
+        let base = self.layout(base, style);
+        let superscript = superscript.map(|node| self.layout(node, style.script()));
+        let subscript = subscript.map(|node| self.layout(node, style.script()));
+
+        if has_limits {
+            let width = base
+                .width
+                .max(superscript.as_ref().map_or(0., |b| b.width))
+                .max(subscript.as_ref().map_or(0., |b| b.width));
+            let gap = 0.15 * size;
+            let mut result = MathBox {
+                width,
+                ..Default::default()
+            };
+            if let Some(superscript) = superscript {
+                let dy = -(base.ascent + gap + superscript.descent);
+                let dx = (width - superscript.width) / 2.;
+                result.place(superscript, dx, dy);
+            }
+            if let Some(subscript) = subscript {
+                let dy = base.descent + gap + subscript.ascent;
+                let dx = (width - subscript.width) / 2.;
+                result.place(subscript, dx, dy);
+            }
+            let dx = (width - base.width) / 2.;
+            result.place(base, dx, 0.);
+            return result;
+        }
+
+        let base_width = base.width;
+        let mut superscript_shift = (base.ascent - 0.25 * size).max(0.4 * size);
+        let mut subscript_shift = (base.descent + 0.05 * size).max(0.2 * size);
+        if let (Some(superscript), Some(subscript)) = (&superscript, &subscript) {
+            subscript_shift = subscript_shift.max(0.3 * size);
+            let gap =
+                (superscript_shift - superscript.descent) - (subscript.ascent - subscript_shift);
+            if gap < 0.1 * size {
+                subscript_shift += 0.1 * size - gap;
+            }
+        }
+        let mut result = base;
+        let mut scripts_width: f32 = 0.;
+        if let Some(superscript) = superscript {
+            superscript_shift = superscript_shift.max(superscript.descent + 0.1 * size);
+            scripts_width = scripts_width.max(superscript.width);
+            result.place(superscript, base_width + 0.05 * size, -superscript_shift);
+        }
+        if let Some(subscript) = subscript {
+            scripts_width = scripts_width.max(subscript.width);
+            result.place(subscript, base_width, subscript_shift);
+        }
+        result.width = base_width + scripts_width + 0.1 * size;
+        result
+    }
+
+    fn layout_fraction(
+        &mut self,
+        numerator: &MathNode,
+        denominator: &MathNode,
+        style: MathStyle,
+    ) -> MathBox {
+        let size = self.base_size * style.scale();
+        let numerator = self.layout(numerator, style.fraction());
+        let denominator = self.layout(denominator, style.fraction());
+        let thickness = rule_thickness(size);
+        let axis = AXIS_HEIGHT * size;
+        let gap = if style == MathStyle::Display {
+            0.2 * size
+        } else {
+            0.12 * size
+        };
+        let padding = 0.1 * size;
+        let width = numerator.width.max(denominator.width) + 2. * padding;
+
+        let mut result = MathBox {
+            width,
+            ..Default::default()
+        };
+        result.push(MathItem::Rule {
+            x: padding / 2.,
+            y: -axis - thickness / 2.,
+            width: width - padding,
+            height: thickness,
+        });
+        let numerator_shift = axis + thickness / 2. + gap + numerator.descent;
+        let denominator_shift = -axis + thickness / 2. + gap + denominator.ascent;
+        let dx = (width - numerator.width) / 2.;
+        result.place(numerator, dx, -numerator_shift);
+        let dx = (width - denominator.width) / 2.;
+        result.place(denominator, dx, denominator_shift);
+        result
+    }
+
+    fn layout_sqrt(
+        &mut self,
+        index: Option<&MathNode>,
+        radicand: &MathNode,
+        style: MathStyle,
+    ) -> MathBox {
+        let size = self.base_size * style.scale();
+        let radicand = self.layout(radicand, style);
+        let thickness = rule_thickness(size);
+        let top = radicand.ascent.max(GLYPH_ASCENT * size) + 0.15 * size;
+        let bottom = radicand.descent.max(GLYPH_DESCENT * size);
+        let height = top + bottom;
+        let sign_width = 0.55 * size;
+
+        let index = index.map(|index| self.layout(index, MathStyle::ScriptScript));
+        let offset = index
+            .as_ref()
+            .map_or(0., |index| (index.width - 0.5 * sign_width).max(0.));
+
+        let mut result = MathBox {
+            width: offset + sign_width + radicand.width + 0.1 * size,
+            ascent: top + thickness,
+            descent: bottom,
+            items: Vec::new(),
+        };
+        let tick_y = bottom - 0.4 * height;
+        result.push(MathItem::Stroke {
+            points: vec![
+                (offset, tick_y + 0.05 * size),
+                (offset + 0.2 * sign_width, tick_y - 0.05 * size),
+                (offset + 0.55 * sign_width, bottom),
+                (offset + sign_width, -top),
+                (result.width, -top),
+            ],
+            thickness,
+        });
+        if let Some(index) = index {
+            let dx = offset + 0.5 * sign_width - index.width;
+            let dy = tick_y - 0.1 * size - index.descent;
+            result.place(index, dx.max(0.), dy);
+        }
+        result.place(radicand, offset + sign_width, 0.);
+        result
+    }
+
+    fn layout_delimited(
+        &mut self,
+        left: &str,
+        body: MathBox,
+        right: &str,
+        style: MathStyle,
+    ) -> MathBox {
+        let size = self.base_size * style.scale();
+        let axis = AXIS_HEIGHT * size;
+        // Delimiters are centered on the axis and cover the body on both sides of it.
+        let half_height = (body.ascent - axis).max(body.descent + axis);
+        let font_size = (2. * half_height * 1.05 / (GLYPH_ASCENT + GLYPH_DESCENT)).max(size);
+        let y = -axis + (GLYPH_ASCENT - GLYPH_DESCENT) / 2. * font_size;
+
+        let mut result = MathBox::default();
+        let push_delimiter = |this: &mut Self, result: &mut MathBox, delimiter: &str| {
+            if delimiter.is_empty() {
+                return;
+            }
+            let width = this
+                .measure
+                .measure(delimiter, font_size, MathVariant::Upright);
+            result.place(
+                MathBox {
+                    width,
+                    ascent: GLYPH_ASCENT * font_size - y,
+                    descent: GLYPH_DESCENT * font_size + y,
+                    items: vec![MathItem::Glyphs {
+                        text: delimiter.to_string().into(),
+                        x: 0.,
+                        y,
+                        font_size,
+                        variant: MathVariant::Upright,
+                    }],
+                },
+                result.width,
+                0.,
+            );
+            result.width += width;
+        };
+
+        push_delimiter(self, &mut result, left);
+        let body_width = body.width;
+        result.place(body, result.width, 0.);
+        result.width += body_width;
+        push_delimiter(self, &mut result, right);
+        result
+    }
+
+    fn layout_matrix(
+        &mut self,
+        rows: &[Vec<MathNode>],
+        alignment: MatrixAlignment,
+        style: MathStyle,
+    ) -> MathBox {
+        let size = self.base_size * style.scale();
+        let cell_style = if style == MathStyle::Display {
+            MathStyle::Text
+        } else {
+            style
+        };
+        let cells = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| self.layout(cell, cell_style))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let column_count = cells.iter().map(Vec::len).max().unwrap_or(0);
+        let mut column_widths = vec![0f32; column_count];
+        for row in &cells {
+            for (column_ix, cell) in row.iter().enumerate() {
+                column_widths[column_ix] = column_widths[column_ix].max(cell.width);
+            }
+        }
+        let column_gap = |column_ix: usize| match alignment {
+            MatrixAlignment::Alternating if column_ix % 2 == 1 => 0.,
+            MatrixAlignment::Alternating => 1.5 * size,
+            MatrixAlignment::Center | MatrixAlignment::Left => size,
+        };
+        let mut column_xs = Vec::with_capacity(column_count);
+        let mut width = 0.;
+        for (column_ix, column_width) in column_widths.iter().enumerate() {
+            if column_ix > 0 {
+                width += column_gap(column_ix);
+            }
+            column_xs.push(width);
+            width += column_width;
+        }
+
+        let row_gap = 0.35 * size;
+        let row_extents = cells
+            .iter()
+            .map(|row| {
+                let ascent = row
+                    .iter()
+                    .map(|cell| cell.ascent)
+                    .fold(GLYPH_ASCENT * size, f32::max);
+                let descent = row
+                    .iter()
+                    .map(|cell| cell.descent)
+                    .fold(GLYPH_DESCENT * size, f32::max);
+                (ascent, descent)
+            })
+            .collect::<Vec<_>>();
+        let height = row_extents
+            .iter()
+            .map(|(ascent, descent)| ascent + descent)
+            .sum::<f32>()
+            + row_gap * row_extents.len().saturating_sub(1) as f32;
+
+        let padding = 0.15 * size;
+        let mut result = MathBox {
+            width: width + 2. * padding,
+            ..Default::default()
+        };
+        let mut y = -AXIS_HEIGHT * size - height / 2.;
+        for (row, (ascent, descent)) in cells.into_iter().zip(row_extents) {
+            let baseline = y + ascent;
+            for (column_ix, cell) in row.into_iter().enumerate() {
+                let free_space = column_widths[column_ix] - cell.width;
+                let align_offset = match alignment {
+                    MatrixAlignment::Center => free_space / 2.,
+                    MatrixAlignment::Left => 0.,
+                    MatrixAlignment::Alternating if column_ix % 2 == 0 => free_space,
+                    MatrixAlignment::Alternating => 0.,
+                };
+                result.place(
+                    cell,
+                    padding + column_xs[column_ix] + align_offset,
+                    baseline,
+                );
+            }
+            y = baseline + descent + row_gap;
+        }
+        result.ascent = result.ascent.max(AXIS_HEIGHT * size + height / 2.);
+        result.descent = result.descent.max(height / 2. - AXIS_HEIGHT * size);
+        result
+    }
+}
+
+fn rule_thickness(size: f32) -> f32 {
+    (RULE_THICKNESS * size).max(1.)
+}
+
+fn node_class(node: &MathNode) -> Option<AtomClass> {
+    match node {
+        MathNode::Atom { class, .. } => Some(*class),
+        MathNode::Scripts { base, .. } => node_class(base),
+        MathNode::Space(_) => None,
+        MathNode::Row(children) if children.is_empty() => None,
+        _ => Some(AtomClass::Ordinary),
+    }
+}
+
+/// The class of each child for spacing, where binary operators that have nothing to operate
+/// on, like a leading minus sign, are treated as ordinary atoms.
+fn spacing_classes(children: &[MathNode]) -> Vec<Option<AtomClass>> {
+    let mut classes = children.iter().map(node_class).collect::<Vec<_>>();
+    let mut previous = None;
+    for ix in 0..classes.len() {
+        let Some(class) = classes[ix] else {
+            continue;
+        };
+        let next = classes[ix + 1..].iter().flatten().next().copied();
+        if class == AtomClass::Binary
+            && (matches!(
+                previous,
+                None | Some(
+                    AtomClass::Binary
+                        | AtomClass::Relation
+                        | AtomClass::Open
+                        | AtomClass::Punctuation
+                        | AtomClass::Operator { .. }
+                )
+            ) || matches!(
+                next,
+                None | Some(AtomClass::Relation | AtomClass::Close | AtomClass::Punctuation)
+            ))
+        {
+            classes[ix] = Some(AtomClass::Ordinary);
+        }
+        previous = classes[ix];
+    }
+    classes
+}
+
+/// The space between two atoms, in ems, following TeX's spacing table.
+fn spacing_between(left: AtomClass, right: AtomClass, style: MathStyle) -> f32 {
+    use AtomClass::*;
+    const THIN: f32 = 3. / 18.;
+    const MEDIUM: f32 = 4. / 18.;
+    const THICK: f32 = 5. / 18.;
+    match (left, right) {
+        (Ordinary | Close | Operator { .. }, Operator { .. }) | (Operator { .. }, Ordinary) => THIN,
+        _ if style.is_script() => 0.,
+        (Binary, _) | (_, Binary) => MEDIUM,
+        (Relation, Relation) | (Open, Relation) | (Relation, Close | Punctuation) => 0.,
+        (Relation, _) | (_, Relation) => THICK,
+        (Punctuation, _) => THIN,
+        _ => 0.,
+    }
+}
+
+struct TextSystemMeasure {
+    text_system: Arc<WindowTextSystem>,
+    font: Font,
+}
+
+impl MathMeasure for TextSystemMeasure {
+    fn measure(&mut self, text: &str, font_size: f32, variant: MathVariant) -> f32 {
+        let run = text_run(&self.font, text, variant, Hsla::default());
+        let line = self.text_system.shape_line(
+            SharedString::from(text.to_string()),
+            px(font_size),
+            &[run],
+            None,
+        );
+        line.width.into()
+    }
+}
+
+fn text_run(font: &Font, text: &str, variant: MathVariant, color: Hsla) -> TextRun {
+    let mut font = font.clone();
+    match variant {
+        MathVariant::Italic => font.style = FontStyle::Italic,
+        MathVariant::Upright => font.style = FontStyle::Normal,
+        MathVariant::Bold => {
+            font.style = FontStyle::Normal;
+            font.weight = FontWeight::BOLD;
+        }
+    }
+    TextRun {
+        len: text.len(),
+        font,
+        color,
+        background_color: None,
+        underline: None,
+        strikethrough: None,
+    }
+}
+
+/// Paints display math that was parsed into a [`MathNode`].
+pub struct MathElement {
+    node: Arc<MathNode>,
+    font: Font,
+    font_size: Pixels,
+    color: Hsla,
+}
+
+impl MathElement {
+    pub fn new(node: Arc<MathNode>, font: Font, font_size: Pixels, color: Hsla) -> Self {
+        Self {
+            node,
+            font,
+            font_size,
+            color,
+        }
+    }
+}
+
+impl IntoElement for MathElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for MathElement {
+    type RequestLayoutState = MathBox;
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static std::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut measure = TextSystemMeasure {
+            text_system: window.text_system().clone(),
+            font: self.font.clone(),
+        };
+        let layout = layout_math(
+            &self.node,
+            MathStyle::Display,
+            self.font_size.into(),
+            &mut measure,
+        );
+        let mut style = Style::default();
+        style.size.width = px(layout.width.ceil()).into();
+        style.size.height = px(layout.height().ceil()).into();
+        (window.request_layout(style, [], cx), layout)
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        _: &mut Window,
+        _: &mut App,
+    ) {
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        layout: &mut Self::RequestLayoutState,
+        _: &mut (),
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let origin_x = bounds.origin.x;
+        let baseline = bounds.origin.y + px(layout.ascent);
+        for item in &layout.items {
+            match item {
+                MathItem::Glyphs {
+                    text,
+                    x,
+                    y,
+                    font_size,
+                    variant,
+                } => {
+                    let run = text_run(&self.font, text, *variant, self.color);
+                    let line =
+                        window
+                            .text_system()
+                            .shape_line(text.clone(), px(*font_size), &[run], None);
+                    let origin = point(origin_x + px(*x), baseline + px(*y) - line.ascent);
+                    line.paint(
+                        origin,
+                        line.ascent + line.descent,
+                        TextAlign::Left,
+                        None,
+                        window,
+                        cx,
+                    )
+                    .log_err();
+                }
+                MathItem::Rule {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => {
+                    let bounds = Bounds::new(
+                        point(origin_x + px(*x), baseline + px(*y)),
+                        size(px(*width), px(*height)),
+                    );
+                    window.paint_quad(fill(bounds, self.color));
+                }
+                MathItem::Stroke { points, thickness } => {
+                    let mut builder = PathBuilder::stroke(px(*thickness));
+                    for (ix, (x, y)) in points.iter().enumerate() {
+                        let vertex = point(origin_x + px(*x), baseline + px(*y));
+                        if ix == 0 {
+                            builder.move_to(vertex);
+                        } else {
+                            builder.line_to(vertex);
+                        }
+                    }
+                    if let Some(path) = builder.build().log_err() {
+                        window.paint_path(path, self.color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Measures text with a fixed advance, for output where the window's text system isn't
+/// available.
+struct ApproximateMeasure;
+
+impl MathMeasure for ApproximateMeasure {
+    fn measure(&mut self, text: &str, font_size: f32, variant: MathVariant) -> f32 {
+        let advance = match variant {
+            MathVariant::Bold => 0.6,
+            MathVariant::Italic | MathVariant::Upright => 0.55,
+        };
+        text.chars().count() as f32 * advance * font_size
+    }
+}
+
+/// An SVG rendering of display math, along with its size in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathSvg {
+    pub svg: String,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Writes display math out as SVG, for exports that can't use the window's text system.
+pub fn render_math_svg(node: &MathNode, font_size: f32, color: Hsla) -> MathSvg {
+    const PADDING: f32 = 2.;
+    let layout = layout_math(node, MathStyle::Display, font_size, &mut ApproximateMeasure);
+    let width = (layout.width + 2. * PADDING).ceil();
+    let height = (layout.height() + 2. * PADDING).ceil();
+    let color = hex_color(color);
+    let baseline = PADDING + layout.ascent;
+
+    let mut body = String::new();
+    for item in &layout.items {
+        match item {
+            MathItem::Glyphs {
+                text,
+                x,
+                y,
+                font_size,
+                variant,
+            } => {
+                let style = match variant {
+                    MathVariant::Italic => " font-style=\"italic\"",
+                    MathVariant::Bold => " font-weight=\"bold\"",
+                    MathVariant::Upright => "",
+                };
+                write!(
+                    body,
+                    "<text x=\"{}\" y=\"{}\" font-family=\"serif\" font-size=\"{font_size}\"{style} fill=\"{color}\">{}</text>",
+                    PADDING + x,
+                    baseline + y,
+                    escape_xml(text),
+                )
+                .ok();
+            }
+            MathItem::Rule {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                write!(
+                    body,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{width}\" height=\"{height}\" fill=\"{color}\"/>",
+                    PADDING + x,
+                    baseline + y,
+                )
+                .ok();
+            }
+            MathItem::Stroke { points, thickness } => {
+                let points = points
+                    .iter()
+                    .map(|(x, y)| format!("{},{}", PADDING + x, baseline + y))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                write!(
+                    body,
+                    "<polyline points=\"{points}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"{thickness}\"/>"
+                )
+                .ok();
+            }
+        }
+    }
+
+    MathSvg {
+        svg: format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">{body}</svg>"
+        ),
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Measures every character as half an em wide.
+    struct FixedMeasure;
+
+    impl MathMeasure for FixedMeasure {
+        fn measure(&mut self, text: &str, font_size: f32, _: MathVariant) -> f32 {
+            text.chars().count() as f32 * font_size / 2.
+        }
+    }
+
+    fn layout(source: &str, style: MathStyle) -> MathBox {
+        layout_math(&parse_math(source).unwrap(), style, 20., &mut FixedMeasure)
+    }
+
+    #[test]
+    fn test_parse_math() {
+        assert_eq!(
+            parse_math("x^2").unwrap(),
+            MathNode::Scripts {
+                base: Box::new(atom("x", AtomClass::Ordinary, MathVariant::Italic)),
+                superscript: Some(Box::new(atom(
+                    "2",
+                    AtomClass::Ordinary,
+                    MathVariant::Upright
+                ))),
+                subscript: None,
+            }
+        );
+        assert_eq!(
+            parse_math(r"\frac{a}{b}").unwrap(),
+            MathNode::Fraction {
+                numerator: Box::new(atom("a", AtomClass::Ordinary, MathVariant::Italic)),
+                denominator: Box::new(atom("b", AtomClass::Ordinary, MathVariant::Italic)),
+            }
+        );
+        assert_eq!(
+            parse_math(r"\mathbb{R}").unwrap(),
+            atom("ℝ", AtomClass::Ordinary, MathVariant::Upright)
+        );
+
+        let MathNode::Matrix {
+            rows,
+            alignment,
+            left,
+            right,
+        } = parse_math(r"\begin{pmatrix} 1 & 0 \\ 0 & 1 \\ \end{pmatrix}").unwrap()
+        else {
+            panic!("expected a matrix");
+        };
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.len() == 2));
+        assert_eq!(alignment, MatrixAlignment::Center);
+        assert_eq!((left.as_str(), right.as_str()), ("(", ")"));
+
+        let MathNode::Matrix {
+            rows, alignment, ..
+        } = parse_math(r"a &= b \\ &= c").unwrap()
+        else {
+            panic!("expected aligned rows");
+        };
+        assert_eq!(rows.len(), 2);
+        assert_eq!(alignment, MatrixAlignment::Alternating);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_math(r"x + \foo").unwrap_err(),
+            MathError {
+                offset: 4,
+                message: "Unknown command `\\foo`".into()
+            }
+        );
+        assert!(parse_math(r"\frac{a}").is_err());
+        assert!(parse_math(r"x^2^3").is_err());
+        assert!(parse_math(r"\left( x").is_err());
+        assert!(parse_math(r"\begin{pmatrix} 1").is_err());
+        assert!(parse_math("{x").is_err());
+        assert!(parse_math("x}").is_err());
+
+        let nested = |depth| format!("{}x{}", "{".repeat(depth), "}".repeat(depth));
+        assert!(parse_math(&nested(MAX_NESTING_DEPTH - 1)).is_ok());
+        assert_eq!(
+            parse_math(&nested(100_000)).unwrap_err().message,
+            "Formula is nested too deeply"
+        );
+        assert!(parse_math(&r"\sqrt".repeat(100_000)).is_err());
+    }
+
+    #[test]
+    fn test_math_to_unicode() {
+        let unicode = |source| math_to_unicode(&parse_math(source).unwrap());
+        assert_eq!(unicode(r"E = mc^2"), "E = mc²");
+        assert_eq!(unicode(r"x_{i+1} \leq \alpha"), "xᵢ₊₁ ≤ α");
+        assert_eq!(unicode(r"\frac{a+b}{2}"), "(a + b)/2");
+        assert_eq!(unicode(r"\sqrt{x}"), "√x");
+        assert_eq!(unicode(r"-x"), "−x");
+        assert_eq!(unicode(r"e^{i\pi}"), "e^(iπ)");
+    }
+
+    #[test]
+    fn test_layout_math() {
+        let atom = layout("x", MathStyle::Display);
+        assert_eq!(atom.width, 10.);
+
+        // Binary operators get medium spaces, unless they're unary.
+        let sum = layout("a+b", MathStyle::Display);
+        assert!(sum.width > 30.);
+        assert_eq!(layout("-b", MathStyle::Display).width, 20.);
+
+        let fraction = layout(r"\frac{a}{b}", MathStyle::Display);
+        assert!(fraction.ascent > atom.ascent);
+        assert!(fraction.descent > atom.descent);
+        assert!(
+            fraction
+                .items
+                .iter()
+                .any(|item| matches!(item, MathItem::Rule { .. }))
+        );
+
+        // Scripts are smaller and shifted off the baseline.
+        let script = layout("x^2", MathStyle::Display);
+        let MathItem::Glyphs { y, font_size, .. } = &script.items[1] else {
+            panic!("expected the superscript glyphs");
+        };
+        assert!(*y < 0.);
+        assert_eq!(*font_size, 14.);
+
+        // Large operators take limits above and below in display style only.
+        let display_sum = layout(r"\sum_{i=0}^n i", MathStyle::Display);
+        let text_sum = layout(r"\sum_{i=0}^n i", MathStyle::Text);
+        assert!(display_sum.height() > text_sum.height());
+
+        let sqrt = layout(r"\sqrt[3]{x}", MathStyle::Display);
+        assert!(
+            sqrt.items
+                .iter()
+                .any(|item| matches!(item, MathItem::Stroke { .. }))
+        );
+        assert!(sqrt.width > atom.width);
+
+        let matrix = layout(
+            r"\begin{bmatrix} a \\ b \\ c \end{bmatrix}",
+            MathStyle::Display,
+        );
+        assert!(matrix.height() > 3. * atom.height());
+    }
+}
@@ -0,0 +1,1583 @@
+//! Parsing, layout and SVG output for Mermaid flowcharts and sequence diagrams.
+//!
+//! Only the core of each diagram type is supported: nodes, edges and their labels for
+//! flowcharts, and participants, messages and notes for sequence diagrams. Styling statements
+//! are accepted and ignored, so that diagrams written for other renderers still display.
+
+use std::{fmt, fmt::Write as _};
+
+use collections::HashMap;
+use gpui::{Hsla, Rgba};
+
+const FONT_SIZE: f32 = 14.;
+/// The assumed advance of a character, since the SVG is rasterized with whatever sans-serif
+/// font the system provides.
+const CHAR_WIDTH: f32 = FONT_SIZE * 0.6;
+const LINE_HEIGHT: f32 = FONT_SIZE * 1.3;
+const MARGIN: f32 = 8.;
+
+const NODE_PADDING_X: f32 = 16.;
+const NODE_PADDING_Y: f32 = 10.;
+const NODE_GAP: f32 = 32.;
+const RANK_GAP: f32 = 56.;
+
+const PARTICIPANT_MIN_WIDTH: f32 = 80.;
+const PARTICIPANT_HEIGHT: f32 = 36.;
+const PARTICIPANT_GAP: f32 = 48.;
+const MESSAGE_HEIGHT: f32 = 40.;
+const SELF_MESSAGE_WIDTH: f32 = 30.;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MermaidDiagram {
+    Flowchart(Flowchart),
+    Sequence(SequenceDiagram),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MermaidError {
+    /// The 1-based line of the diagram source the error was found on.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MermaidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on line {}", self.message, self.line)
+    }
+}
+
+impl std::error::Error for MermaidError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    TopDown,
+    BottomUp,
+    LeftRight,
+    RightLeft,
+}
+
+impl FlowDirection {
+    fn is_horizontal(self) -> bool {
+        matches!(self, FlowDirection::LeftRight | FlowDirection::RightLeft)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeShape {
+    Rectangle,
+    Rounded,
+    Stadium,
+    Subroutine,
+    Circle,
+    Diamond,
+    Asymmetric,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowNode {
+    pub id: String,
+    pub label: String,
+    pub shape: NodeShape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    Solid,
+    Dotted,
+    Thick,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowEdge {
+    pub from: usize,
+    pub to: usize,
+    pub label: Option<String>,
+    pub style: LineStyle,
+    pub arrow: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flowchart {
+    pub direction: FlowDirection,
+    pub nodes: Vec<FlowNode>,
+    pub edges: Vec<FlowEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageHead {
+    None,
+    Arrow,
+    Cross,
+    Async,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotePlacement {
+    LeftOf,
+    RightOf,
+    Over,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SequenceEvent {
+    Message {
+        from: usize,
+        to: usize,
+        text: String,
+        dashed: bool,
+        head: MessageHead,
+    },
+    Note {
+        placement: NotePlacement,
+        /// The first and last participant the note covers, which are the same unless the note
+        /// is over two participants.
+        participants: (usize, usize),
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceDiagram {
+    pub participants: Vec<Participant>,
+    pub events: Vec<SequenceEvent>,
+}
+
+pub fn parse_mermaid(source: &str) -> Result<MermaidDiagram, MermaidError> {
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(ix, line)| (ix + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with("%%"));
+    let Some((header_line, header)) = lines.next() else {
+        return Err(MermaidError {
+            line: 1,
+            message: "The diagram is empty".into(),
+        });
+    };
+
+    let mut header_words = header.split_whitespace();
+    match header_words.next() {
+        Some("graph" | "flowchart") => {
+            let direction = match header_words.next().map(|word| word.trim_end_matches(';')) {
+                None | Some("TD" | "TB") => FlowDirection::TopDown,
+                Some("BT") => FlowDirection::BottomUp,
+                Some("LR") => FlowDirection::LeftRight,
+                Some("RL") => FlowDirection::RightLeft,
+                Some(direction) => {
+                    return Err(MermaidError {
+                        line: header_line,
+                        message: format!("Unknown flowchart direction `{direction}`"),
+                    });
+                }
+            };
+            parse_flowchart(direction, lines).map(MermaidDiagram::Flowchart)
+        }
+        Some("sequenceDiagram") => parse_sequence(lines).map(MermaidDiagram::Sequence),
+        Some(kind) => Err(MermaidError {
+            line: header_line,
+            message: format!(
+                "`{kind}` diagrams aren't supported; use `flowchart` or `sequenceDiagram`"
+            ),
+        }),
+        None => unreachable!("empty lines are filtered out"),
+    }
+}
+
+fn parse_flowchart<'a>(
+    direction: FlowDirection,
+    lines: impl Iterator<Item = (usize, &'a str)>,
+) -> Result<Flowchart, MermaidError> {
+    let mut flowchart = Flowchart {
+        direction,
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    let mut node_ids = HashMap::default();
+    for (line, text) in lines {
+        for statement in text.split(';').map(str::trim) {
+            let keyword = statement.split_whitespace().next().unwrap_or_default();
+            if statement.is_empty()
+                || matches!(
+                    keyword,
+                    "classDef"
+                        | "class"
+                        | "style"
+                        | "linkStyle"
+                        | "click"
+                        | "subgraph"
+                        | "end"
+                        | "direction"
+                )
+            {
+                continue;
+            }
+            FlowStatementParser {
+                source: statement,
+                offset: 0,
+                line,
+                flowchart: &mut flowchart,
+                node_ids: &mut node_ids,
+            }
+            .parse()?;
+        }
+    }
+    Ok(flowchart)
+}
+
+struct FlowStatementParser<'a> {
+    source: &'a str,
+    offset: usize,
+    line: usize,
+    flowchart: &'a mut Flowchart,
+    node_ids: &'a mut HashMap<String, usize>,
+}
+
+impl FlowStatementParser<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.offset..]
+    }
+
+    fn error(&self, message: impl Into<String>) -> MermaidError {
+        MermaidError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    /// Parses a chain of node groups joined by edges, like `A & B --> C -- text --> D`.
+    fn parse(mut self) -> Result<(), MermaidError> {
+        let mut previous = self.parse_node_group()?;
+        loop {
+            self.skip_whitespace();
+            if self.rest().is_empty() {
+                return Ok(());
+            }
+            let (style, arrow, mut label) = self.parse_edge()?;
+            self.skip_whitespace();
+            if let Some(rest) = self.rest().strip_prefix('|') {
+                let Some(end) = rest.find('|') else {
+                    return Err(self.error("Unterminated edge label"));
+                };
+                label = Some(unquote(&rest[..end]).to_string());
+                self.offset += end + 2;
+            }
+            let next = self.parse_node_group()?;
+            for &from in &previous {
+                for &to in &next {
+                    self.flowchart.edges.push(FlowEdge {
+                        from,
+                        to,
+                        label: label.clone(),
+                        style,
+                        arrow,
+                    });
+                }
+            }
+            previous = next;
+        }
+    }
+
+    fn parse_node_group(&mut self) -> Result<Vec<usize>, MermaidError> {
+        let mut nodes = vec![self.parse_node()?];
+        loop {
+            self.skip_whitespace();
+            if let Some(rest) = self.rest().strip_prefix('&') {
+                self.offset = self.source.len() - rest.len();
+                nodes.push(self.parse_node()?);
+            } else {
+                return Ok(nodes);
+            }
+        }
+    }
+
+    fn parse_node(&mut self) -> Result<usize, MermaidError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let id_len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if id_len == 0 {
+            return Err(match rest.chars().next() {
+                Some(c) => self.error(format!("Expected a node, found `{c}`")),
+                None => self.error("Expected a node"),
+            });
+        }
+        let id = rest[..id_len].to_string();
+        self.offset += id_len;
+
+        const SHAPES: &[(&str, &str, NodeShape)] = &[
+            ("((", "))", NodeShape::Circle),
+            ("([", "])", NodeShape::Stadium),
+            ("[[", "]]", NodeShape::Subroutine),
+            ("[", "]", NodeShape::Rectangle),
+            ("(", ")", NodeShape::Rounded),
+            ("{", "}", NodeShape::Diamond),
+            (">", "]", NodeShape::Asymmetric),
+        ];
+        let mut shape_and_label = None;
+        for (open, close, shape) in SHAPES {
+            let Some(rest) = self.rest().strip_prefix(open) else {
+                continue;
+            };
+            let Some(end) = rest.find(close) else {
+                return Err(self.error(format!("Expected `{close}` to close node `{id}`")));
+            };
+            shape_and_label = Some((*shape, unquote(&rest[..end]).to_string()));
+            self.offset += open.len() + end + close.len();
+            break;
+        }
+        // Skip class shorthands like `A:::warning`.
+        if let Some(rest) = self.rest().strip_prefix(":::") {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or(rest.len());
+            self.offset += 3 + len;
+        }
+
+        if let Some(&ix) = self.node_ids.get(&id) {
+            if let Some((shape, label)) = shape_and_label {
+                let node = &mut self.flowchart.nodes[ix];
+                node.shape = shape;
+                node.label = label;
+            }
+            return Ok(ix);
+        }
+        let (shape, label) = shape_and_label.unwrap_or_else(|| (NodeShape::Rectangle, id.clone()));
+        let ix = self.flowchart.nodes.len();
+        self.node_ids.insert(id.clone(), ix);
+        self.flowchart.nodes.push(FlowNode { id, label, shape });
+        Ok(ix)
+    }
+
+    /// Parses an edge like `-->`, `-.->`, `==>` or `---`, including labels written inline, as
+    /// in `-- text -->`.
+    fn parse_edge(&mut self) -> Result<(LineStyle, bool, Option<String>), MermaidError> {
+        let operator = self.take_edge_operator();
+        if operator.len() < 2 {
+            return Err(match self.rest().chars().next() {
+                Some(c) => self.error(format!("Expected an edge, found `{c}`")),
+                None => self.error("Expected an edge"),
+            });
+        }
+
+        // An operator without an end, like `--` or `-.`, starts an inline label.
+        let (operator, label) = if matches!(operator.as_str(), "--" | "==" | "-.") {
+            let rest = self.rest();
+            let Some(end) = rest.char_indices().map(|(ix, _)| ix).find(|&ix| {
+                let closing = &rest[ix..];
+                closing.starts_with("--")
+                    || closing.starts_with("==")
+                    || closing.starts_with(".-")
+                    || closing.starts_with("-.")
+            }) else {
+                return Err(self.error("Expected an edge after the edge label"));
+            };
+            let label = unquote(rest[..end].trim()).to_string();
+            self.offset += end;
+            let closing = self.take_edge_operator();
+            (format!("{operator}{closing}"), Some(label))
+        } else {
+            (operator, None)
+        };
+
+        let style = if operator.contains('.') {
+            LineStyle::Dotted
+        } else if operator.contains('=') {
+            LineStyle::Thick
+        } else {
+            LineStyle::Solid
+        };
+        Ok((style, operator.ends_with('>'), label))
+    }
+
+    fn take_edge_operator(&mut self) -> String {
+        let rest = self.rest();
+        let rest = rest.strip_prefix('<').unwrap_or(rest);
+        let len = rest
+            .find(|c: char| !matches!(c, '-' | '=' | '.' | '>'))
+            .unwrap_or(rest.len());
+        let operator = rest[..len].to_string();
+        self.offset = self.source.len() - rest.len() + len;
+        operator
+    }
+}
+
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+fn parse_sequence<'a>(
+    lines: impl Iterator<Item = (usize, &'a str)>,
+) -> Result<SequenceDiagram, MermaidError> {
+    let mut diagram = SequenceDiagram {
+        participants: Vec::new(),
+        events: Vec::new(),
+    };
+    for (line, text) in lines {
+        let error = |message: String| MermaidError { line, message };
+        let (keyword, rest) = text
+            .split_once(char::is_whitespace)
+            .map_or((text, ""), |(keyword, rest)| (keyword, rest.trim()));
+
+        match keyword {
+            "participant" | "actor" => {
+                let (id, label) = match rest.split_once(" as ") {
+                    Some((id, label)) => (id.trim(), label.trim()),
+                    None => (rest, rest),
+                };
+                if id.is_empty() {
+                    return Err(error(format!("Expected a name after `{keyword}`")));
+                }
+                let ix = participant_ix(&mut diagram, id);
+                diagram.participants[ix].label = label.to_string();
+            }
+            "Note" | "note" => {
+                let Some((target, text)) = rest.split_once(':') else {
+                    return Err(error("Expected `:` before the note text".into()));
+                };
+                let (placement, names) = if let Some(names) = target.strip_prefix("left of") {
+                    (NotePlacement::LeftOf, names)
+                } else if let Some(names) = target.strip_prefix("right of") {
+                    (NotePlacement::RightOf, names)
+                } else if let Some(names) = target.strip_prefix("over") {
+                    (NotePlacement::Over, names)
+                } else {
+                    return Err(error(
+                        "Expected `left of`, `right of` or `over` after `Note`".into(),
+                    ));
+                };
+                let mut names = names.split(',').map(str::trim);
+                let first = names.next().filter(|name| !name.is_empty());
+                let Some(first) = first else {
+                    return Err(error("Expected a participant for the note".into()));
+                };
+                let first = participant_ix(&mut diagram, first);
+                let last = match names.next() {
+                    Some(name) => participant_ix(&mut diagram, name),
+                    None => first,
+                };
+                diagram.events.push(SequenceEvent::Note {
+                    placement,
+                    participants: (first.min(last), first.max(last)),
+                    text: text.trim().to_string(),
+                });
+            }
+            "loop" | "alt" | "else" | "opt" | "par" | "and" | "critical" | "option" | "break"
+            | "rect" | "end" | "activate" | "deactivate" | "autonumber" | "title" | "box" => {}
+            _ => {
+                let (arrow, message) = text.split_once(':').unwrap_or((text, ""));
+                const ARROWS: &[(&str, bool, MessageHead)] = &[
+                    ("-->>", true, MessageHead::Arrow),
+                    ("->>", false, MessageHead::Arrow),
+                    ("--x", true, MessageHead::Cross),
+                    ("-x", false, MessageHead::Cross),
+                    ("--)", true, MessageHead::Async),
+                    ("-)", false, MessageHead::Async),
+                    ("-->", true, MessageHead::None),
+                    ("->", false, MessageHead::None),
+                ];
+                let found = ARROWS
+                    .iter()
+                    .filter_map(|&(token, dashed, head)| {
+                        Some((arrow.find(token)?, token, dashed, head))
+                    })
+                    .min_by_key(|(position, token, ..)| (*position, usize::MAX - token.len()));
+                let Some((position, token, dashed, head)) = found else {
+                    return Err(error(format!("Unrecognized statement `{text}`")));
+                };
+                let from = arrow[..position].trim();
+                let to = arrow[position + token.len()..]
+                    .trim()
+                    .trim_start_matches(['+', '-'])
+                    .trim();
+                if from.is_empty() || to.is_empty() {
+                    return Err(error(
+                        "Expected a participant on both sides of the message".into(),
+                    ));
+                }
+                let from = participant_ix(&mut diagram, from);
+                let to = participant_ix(&mut diagram, to);
+                diagram.events.push(SequenceEvent::Message {
+                    from,
+                    to,
+                    text: message.trim().to_string(),
+                    dashed,
+                    head,
+                });
+            }
+        }
+    }
+
+    if diagram.participants.is_empty() {
+        return Err(MermaidError {
+            line: 1,
+            message: "The diagram has no participants".into(),
+        });
+    }
+    Ok(diagram)
+}
+
+/// The index of the participant with the given id, which is added if it hasn't been declared.
+fn participant_ix(diagram: &mut SequenceDiagram, id: &str) -> usize {
+    if let Some(ix) = diagram.participants.iter().position(|p| p.id == id) {
+        return ix;
+    }
+    diagram.participants.push(Participant {
+        id: id.to_string(),
+        label: id.to_string(),
+    });
+    diagram.participants.len() - 1
+}
+
+/// The theme colors a diagram is drawn with.
+#[derive(Debug, Clone, Copy)]
+pub struct MermaidColors {
+    pub text: Hsla,
+    pub border: Hsla,
+    pub background: Hsla,
+    pub note_background: Hsla,
+}
+
+/// An SVG rendering of a diagram, along with its size in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MermaidSvg {
+    pub svg: String,
+    pub width: f32,
+    pub height: f32,
+}
+
+pub fn render_mermaid_svg(diagram: &MermaidDiagram, colors: &MermaidColors) -> MermaidSvg {
+    let mut svg = SvgWriter::new(colors);
+    let (width, height) = match diagram {
+        MermaidDiagram::Flowchart(flowchart) => {
+            render_flowchart(&layout_flowchart(flowchart), flowchart, &mut svg)
+        }
+        MermaidDiagram::Sequence(diagram) => render_sequence(diagram, &mut svg),
+    };
+    MermaidSvg {
+        svg: svg.finish(width, height),
+        width,
+        height,
+    }
+}
+
+fn text_width(text: &str) -> f32 {
+    label_lines(text)
+        .map(|line| line.chars().count() as f32 * CHAR_WIDTH)
+        .fold(0., f32::max)
+}
+
+fn text_height(text: &str) -> f32 {
+    label_lines(text).count() as f32 * LINE_HEIGHT
+}
+
+fn label_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.split("<br/>")
+        .flat_map(|line| line.split("<br>"))
+        .flat_map(|line| line.split("\\n"))
+        .map(str::trim)
+}
+
+/// The position and size of each flowchart node, with each node's center.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowchartLayout {
+    pub nodes: Vec<NodeLayout>,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeLayout {
+    pub rank: usize,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Places nodes in ranks along the flow direction, so that edges point forward wherever the
+/// graph allows it, and orders each rank to keep connected nodes close to each other.
+pub fn layout_flowchart(flowchart: &Flowchart) -> FlowchartLayout {
+    let node_count = flowchart.nodes.len();
+    let forward_edges = forward_edges(flowchart);
+
+    // Longest-path ranking over the acyclic edges.
+    let mut ranks = vec![0; node_count];
+    let mut in_degree = vec![0; node_count];
+    for &(_, to) in &forward_edges {
+        in_degree[to] += 1;
+    }
+    let mut queue = (0..node_count)
+        .filter(|&ix| in_degree[ix] == 0)
+        .collect::<Vec<_>>();
+    while let Some(ix) = queue.pop() {
+        for &(from, to) in &forward_edges {
+            if from == ix {
+                ranks[to] = ranks[to].max(ranks[from] + 1);
+                in_degree[to] -= 1;
+                if in_degree[to] == 0 {
+                    queue.push(to);
+                }
+            }
+        }
+    }
+
+    let rank_count = ranks.iter().max().map_or(0, |max| max + 1);
+    let mut rank_members = vec![Vec::new(); rank_count];
+    for (ix, &rank) in ranks.iter().enumerate() {
+        rank_members[rank].push(ix);
+    }
+    order_ranks(&mut rank_members, &forward_edges, node_count);
+
+    let horizontal = flowchart.direction.is_horizontal();
+    let sizes = flowchart
+        .nodes
+        .iter()
+        .map(|node| {
+            let width = text_width(&node.label) + 2. * NODE_PADDING_X;
+            let height = text_height(&node.label) + 2. * NODE_PADDING_Y;
+            match node.shape {
+                NodeShape::Circle => {
+                    let diameter = width.max(height);
+                    (diameter, diameter)
+                }
+                NodeShape::Diamond => (width + height, height * 1.6),
+                NodeShape::Asymmetric => (width + height / 2., height),
+                _ => (width, height),
+            }
+        })
+        .collect::<Vec<_>>();
+    // Each node's extent along the flow direction, and across it.
+    let along = |ix: usize| if horizontal { sizes[ix].0 } else { sizes[ix].1 };
+    let across = |ix: usize| if horizontal { sizes[ix].1 } else { sizes[ix].0 };
+
+    let rank_extents = rank_members
+        .iter()
+        .map(|members| members.iter().map(|&ix| along(ix)).fold(0., f32::max))
+        .collect::<Vec<_>>();
+    let rank_lengths = rank_members
+        .iter()
+        .map(|members| {
+            members.iter().map(|&ix| across(ix)).sum::<f32>()
+                + NODE_GAP * members.len().saturating_sub(1) as f32
+        })
+        .collect::<Vec<_>>();
+    let total_across = rank_lengths.iter().copied().fold(0., f32::max);
+    let total_along =
+        rank_extents.iter().sum::<f32>() + RANK_GAP * rank_extents.len().saturating_sub(1) as f32;
+
+    let mut nodes = vec![
+        NodeLayout {
+            rank: 0,
+            x: 0.,
+            y: 0.,
+            width: 0.,
+            height: 0.,
+        };
+        node_count
+    ];
+    let mut rank_start = 0.;
+    for (rank, members) in rank_members.iter().enumerate() {
+        let mut position = (total_across - rank_lengths[rank]) / 2.;
+        let rank_center = rank_start + rank_extents[rank] / 2.;
+        for &ix in members {
+            let across_center = position + across(ix) / 2.;
+            let along_center = match flowchart.direction {
+                FlowDirection::TopDown | FlowDirection::LeftRight => rank_center,
+                FlowDirection::BottomUp | FlowDirection::RightLeft => total_along - rank_center,
+            };
+            let (x, y) = if horizontal {
+                (along_center, across_center)
+            } else {
+                (across_center, along_center)
+            };
+            nodes[ix] = NodeLayout {
+                rank,
+                x: x + MARGIN,
+                y: y + MARGIN,
+                width: sizes[ix].0,
+                height: sizes[ix].1,
+            };
+            position += across(ix) + NODE_GAP;
+        }
+        rank_start += rank_extents[rank] + RANK_GAP;
+    }
+
+    let (width, height) = if horizontal {
+        (total_along, total_across)
+    } else {
+        (total_across, total_along)
+    };
+    FlowchartLayout {
+        nodes,
+        width: width + 2. * MARGIN,
+        height: height + 2. * MARGIN,
+    }
+}
+
+/// The edges of the flowchart with cycles broken, by dropping the edges that lead back to a
+/// node that's still being visited in a depth-first search.
+fn forward_edges(flowchart: &Flowchart) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        Active,
+        Done,
+    }
+
+    let node_count = flowchart.nodes.len();
+    let mut outgoing = vec![Vec::new(); node_count];
+    for edge in &flowchart.edges {
+        if edge.from != edge.to {
+            outgoing[edge.from].push(edge.to);
+        }
+    }
+
+    let mut state = vec![Visit::New; node_count];
+    let mut edges = Vec::new();
+    for root in 0..node_count {
+        if state[root] != Visit::New {
+            continue;
+        }
+        state[root] = Visit::Active;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next_child)) = stack.last_mut() {
+            let node = *node;
+            if let Some(&child) = outgoing[node].get(*next_child) {
+                *next_child += 1;
+                match state[child] {
+                    Visit::New => {
+                        edges.push((node, child));
+                        state[child] = Visit::Active;
+                        stack.push((child, 0));
+                    }
+                    Visit::Done => edges.push((node, child)),
+                    Visit::Active => {}
+                }
+            } else {
+                state[node] = Visit::Done;
+                stack.pop();
+            }
+        }
+    }
+    edges
+}
+
+/// Reorders nodes within each rank by the average position of their neighbors in the adjacent
+/// rank, sweeping down and then up a few times to reduce edge crossings.
+fn order_ranks(rank_members: &mut [Vec<usize>], edges: &[(usize, usize)], node_count: usize) {
+    const SWEEPS: usize = 4;
+    let mut positions = vec![0.; node_count];
+    let update_positions = |rank_members: &[Vec<usize>], positions: &mut Vec<f32>| {
+        for members in rank_members {
+            for (position, &ix) in members.iter().enumerate() {
+                positions[ix] = position as f32;
+            }
+        }
+    };
+    update_positions(rank_members, &mut positions);
+
+    for sweep in 0..SWEEPS {
+        let downward = sweep % 2 == 0;
+        let rank_order = if downward {
+            (1..rank_members.len()).collect::<Vec<_>>()
+        } else {
+            (0..rank_members.len().saturating_sub(1)).rev().collect()
+        };
+        for rank in rank_order {
+            let neighbor_rank = if downward { rank - 1 } else { rank + 1 };
+            let neighbors = &rank_members[neighbor_rank];
+            let mut keys = rank_members[rank]
+                .iter()
+                .map(|&ix| {
+                    let neighbor_positions = edges
+                        .iter()
+                        .filter_map(|&(from, to)| {
+                            if to == ix && neighbors.contains(&from) {
+                                Some(positions[from])
+                            } else if from == ix && neighbors.contains(&to) {
+                                Some(positions[to])
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    let key = if neighbor_positions.is_empty() {
+                        positions[ix]
+                    } else {
+                        neighbor_positions.iter().sum::<f32>() / neighbor_positions.len() as f32
+                    };
+                    (key, ix)
+                })
+                .collect::<Vec<_>>();
+            keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+            rank_members[rank] = keys.into_iter().map(|(_, ix)| ix).collect();
+            update_positions(rank_members, &mut positions);
+        }
+    }
+}
+
+/// Where the line from the center of a node towards `(dx, dy)` leaves the node's outline.
+fn boundary_point(node: &NodeLayout, shape: NodeShape, dx: f32, dy: f32) -> (f32, f32) {
+    let half_width = node.width / 2.;
+    let half_height = node.height / 2.;
+    if dx == 0. && dy == 0. {
+        return (node.x, node.y);
+    }
+    let scale = match shape {
+        NodeShape::Circle => half_width / (dx * dx + dy * dy).sqrt(),
+        NodeShape::Diamond => 1. / (dx.abs() / half_width + dy.abs() / half_height),
+        _ => {
+            let x_scale = if dx == 0. {
+                f32::INFINITY
+            } else {
+                half_width / dx.abs()
+            };
+            let y_scale = if dy == 0. {
+                f32::INFINITY
+            } else {
+                half_height / dy.abs()
+            };
+            x_scale.min(y_scale)
+        }
+    };
+    (node.x + dx * scale, node.y + dy * scale)
+}
+
+fn render_flowchart(
+    layout: &FlowchartLayout,
+    flowchart: &Flowchart,
+    svg: &mut SvgWriter,
+) -> (f32, f32) {
+    for edge in &flowchart.edges {
+        let from = &layout.nodes[edge.from];
+        let to = &layout.nodes[edge.to];
+        let (stroke_width, dash) = match edge.style {
+            LineStyle::Solid => (1.5, None),
+            LineStyle::Dotted => (1.5, Some("4 3")),
+            LineStyle::Thick => (3., None),
+        };
+
+        let label_position;
+        if edge.from == edge.to {
+            // Loop around the node's top right corner.
+            let start = (from.x + from.width / 2., from.y);
+            let end = (from.x, from.y - from.height / 2.);
+            let corner = (start.0 + 24., end.1 - 24.);
+            svg.path(
+                &format!(
+                    "M {} {} C {} {} {} {} {} {}",
+                    start.0, start.1, corner.0, start.1, end.0, corner.1, end.0, end.1
+                ),
+                stroke_width,
+                dash,
+            );
+            if edge.arrow {
+                svg.arrowhead((end.0, end.1 - 10.), end);
+            }
+            label_position = corner;
+        } else {
+            let start = boundary_point(
+                from,
+                flowchart.nodes[edge.from].shape,
+                to.x - from.x,
+                to.y - from.y,
+            );
+            let end = boundary_point(
+                to,
+                flowchart.nodes[edge.to].shape,
+                from.x - to.x,
+                from.y - to.y,
+            );
+            svg.line(start, end, stroke_width, dash);
+            if edge.arrow {
+                svg.arrowhead(start, end);
+            }
+            label_position = ((start.0 + end.0) / 2., (start.1 + end.1) / 2.);
+        }
+
+        if let Some(label) = edge.label.as_deref().filter(|label| !label.is_empty()) {
+            let width = text_width(label) + 8.;
+            let height = text_height(label) + 4.;
+            svg.rect(
+                label_position.0 - width / 2.,
+                label_position.1 - height / 2.,
+                width,
+                height,
+                0.,
+                svg.colors.background,
+                None,
+            );
+            svg.text(label_position.0, label_position.1, label);
+        }
+    }
+
+    for (node, node_layout) in flowchart.nodes.iter().zip(&layout.nodes) {
+        let NodeLayout {
+            x,
+            y,
+            width,
+            height,
+            ..
+        } = *node_layout;
+        let left = x - width / 2.;
+        let top = y - height / 2.;
+        let fill = svg.colors.background;
+        let border = Some(svg.colors.border);
+        match node.shape {
+            NodeShape::Rectangle => svg.rect(left, top, width, height, 0., fill, border),
+            NodeShape::Rounded => svg.rect(left, top, width, height, 8., fill, border),
+            NodeShape::Stadium => svg.rect(left, top, width, height, height / 2., fill, border),
+            NodeShape::Subroutine => {
+                svg.rect(left, top, width, height, 0., fill, border);
+                svg.line((left + 8., top), (left + 8., top + height), 1., None);
+                svg.line(
+                    (left + width - 8., top),
+                    (left + width - 8., top + height),
+                    1.,
+                    None,
+                );
+            }
+            NodeShape::Circle => svg.circle(x, y, width / 2.),
+            NodeShape::Diamond => {
+                svg.polygon(&[(x, top), (left + width, y), (x, top + height), (left, y)])
+            }
+            NodeShape::Asymmetric => svg.polygon(&[
+                (left, top),
+                (left + width, top),
+                (left + width, top + height),
+                (left, top + height),
+                (left + height / 2., y),
+            ]),
+        }
+        svg.text(x, y, &node.label);
+    }
+
+    (layout.width, layout.height)
+}
+
+fn render_sequence(diagram: &SequenceDiagram, svg: &mut SvgWriter) -> (f32, f32) {
+    let participant_count = diagram.participants.len();
+    let box_widths = diagram
+        .participants
+        .iter()
+        .map(|participant| {
+            (text_width(&participant.label) + 2. * NODE_PADDING_X).max(PARTICIPANT_MIN_WIDTH)
+        })
+        .collect::<Vec<_>>();
+
+    // Space the lifelines so that boxes don't overlap and message labels fit between them.
+    let mut gaps = (0..participant_count.saturating_sub(1))
+        .map(|ix| (box_widths[ix] + box_widths[ix + 1]) / 2. + PARTICIPANT_GAP)
+        .collect::<Vec<_>>();
+    for event in &diagram.events {
+        let (first, last, required) = match event {
+            SequenceEvent::Message { from, to, text, .. } if from != to => {
+                let (first, last) = ((*from).min(*to), (*from).max(*to));
+                (first, last, text_width(text) + 24.)
+            }
+            SequenceEvent::Message { from, text, .. } => (
+                *from,
+                *from + 1,
+                text_width(text) + SELF_MESSAGE_WIDTH + 16.,
+            ),
+            SequenceEvent::Note {
+                placement: NotePlacement::RightOf,
+                participants: (ix, _),
+                text,
+            } => (*ix, *ix + 1, text_width(text) + 2. * NODE_PADDING_X + 16.),
+            SequenceEvent::Note {
+                placement: NotePlacement::LeftOf,
+                participants: (ix, _),
+                text,
+            } if *ix > 0 => (ix - 1, *ix, text_width(text) + 2. * NODE_PADDING_X + 16.),
+            SequenceEvent::Note { .. } => continue,
+        };
+        if last >= participant_count {
+            continue;
+        }
+        let current = gaps[first..last].iter().sum::<f32>();
+        if current < required {
+            let extra = (required - current) / (last - first) as f32;
+            for gap in &mut gaps[first..last] {
+                *gap += extra;
+            }
+        }
+    }
+
+    // Notes and self messages beside the outermost lifelines need room outside of them.
+    let last_ix = participant_count - 1;
+    let mut note_margins = (0_f32, 0_f32);
+    for event in &diagram.events {
+        match event {
+            SequenceEvent::Note {
+                placement: NotePlacement::LeftOf,
+                participants: (0, _),
+                text,
+            } => {
+                let width = text_width(text) + 2. * NODE_PADDING_X + 16.;
+                note_margins.0 = note_margins.0.max(width - box_widths[0] / 2.);
+            }
+            SequenceEvent::Note {
+                placement: NotePlacement::RightOf,
+                participants: (ix, _),
+                text,
+            } if *ix == last_ix => {
+                let width = text_width(text) + 2. * NODE_PADDING_X + 16.;
+                note_margins.1 = note_margins.1.max(width - box_widths[last_ix] / 2.);
+            }
+            SequenceEvent::Message { from, to, text, .. } if from == to && *from == last_ix => {
+                let width = text_width(text) + SELF_MESSAGE_WIDTH + 16.;
+                note_margins.1 = note_margins.1.max(width - box_widths[last_ix] / 2.);
+            }
+            _ => {}
+        }
+    }
+
+    let mut centers = Vec::with_capacity(participant_count);
+    let mut x = MARGIN + note_margins.0.max(0.) + box_widths[0] / 2.;
+    centers.push(x);
+    for gap in &gaps {
+        x += gap;
+        centers.push(x);
+    }
+    let width = x + box_widths[last_ix] / 2. + note_margins.1.max(0.) + MARGIN;
+
+    let mut y = MARGIN + PARTICIPANT_HEIGHT + 16.;
+    let mut rows = Vec::with_capacity(diagram.events.len());
+    for event in &diagram.events {
+        let height = match event {
+            SequenceEvent::Message { from, to, text, .. } => {
+                text_height(text) + if from == to { MESSAGE_HEIGHT } else { 16. }
+            }
+            SequenceEvent::Note { text, .. } => text_height(text) + 2. * NODE_PADDING_Y + 8.,
+        };
+        rows.push(y);
+        y += height;
+    }
+    let lifeline_end = y + 8.;
+    let height = lifeline_end + PARTICIPANT_HEIGHT + MARGIN;
+
+    for (ix, participant) in diagram.participants.iter().enumerate() {
+        let center = centers[ix];
+        let left = center - box_widths[ix] / 2.;
+        svg.line(
+            (center, MARGIN + PARTICIPANT_HEIGHT),
+            (center, lifeline_end),
+            1.,
+            Some("3 3"),
+        );
+        for top in [MARGIN, lifeline_end] {
+            svg.rect(
+                left,
+                top,
+                box_widths[ix],
+                PARTICIPANT_HEIGHT,
+                4.,
+                svg.colors.background,
+                Some(svg.colors.border),
+            );
+            svg.text(center, top + PARTICIPANT_HEIGHT / 2., &participant.label);
+        }
+    }
+
+    for (event, top) in diagram.events.iter().zip(rows) {
+        match event {
+            SequenceEvent::Message {
+                from,
+                to,
+                text,
+                dashed,
+                head,
+            } => {
+                let dash = dashed.then_some("5 3");
+                let label_height = text_height(text);
+                let line_y = top + label_height + 4.;
+                let start_x = centers[*from];
+                let (start, end, label_x) = if from == to {
+                    let right = start_x + SELF_MESSAGE_WIDTH;
+                    let bottom = line_y + MESSAGE_HEIGHT / 2.;
+                    svg.line((start_x, line_y), (right, line_y), 1.5, dash);
+                    svg.line((right, line_y), (right, bottom), 1.5, dash);
+                    svg.line((right, bottom), (start_x, bottom), 1.5, dash);
+                    (
+                        (right, bottom),
+                        (start_x, bottom),
+                        start_x + 4. + text_width(text) / 2.,
+                    )
+                } else {
+                    let end_x = centers[*to];
+                    svg.line((start_x, line_y), (end_x, line_y), 1.5, dash);
+                    ((start_x, line_y), (end_x, line_y), (start_x + end_x) / 2.)
+                };
+                match head {
+                    MessageHead::None => {}
+                    MessageHead::Arrow => svg.arrowhead(start, end),
+                    MessageHead::Cross => svg.cross(end),
+                    MessageHead::Async => svg.open_arrowhead(start, end),
+                }
+                svg.text(label_x, top + label_height / 2., text);
+            }
+            SequenceEvent::Note {
+                placement,
+                participants: (first, last),
+                text,
+            } => {
+                let note_width = text_width(text) + 2. * NODE_PADDING_X;
+                let note_height = text_height(text) + 2. * NODE_PADDING_Y;
+                let left = match placement {
+                    NotePlacement::LeftOf => centers[*first] - 8. - note_width,
+                    NotePlacement::RightOf => centers[*first] + 8.,
+                    NotePlacement::Over => {
+                        let span = centers[*last] - centers[*first];
+                        let width = note_width.max(span + 24.);
+                        (centers[*first] + centers[*last] - width) / 2.
+                    }
+                };
+                let note_width = match placement {
+                    NotePlacement::Over => note_width.max(centers[*last] - centers[*first] + 24.),
+                    NotePlacement::LeftOf | NotePlacement::RightOf => note_width,
+                };
+                svg.rect(
+                    left,
+                    top + 4.,
+                    note_width,
+                    note_height,
+                    0.,
+                    svg.colors.note_background,
+                    Some(svg.colors.border),
+                );
+                svg.text(left + note_width / 2., top + 4. + note_height / 2., text);
+            }
+        }
+    }
+
+    (width, height)
+}
+
+struct SvgWriter<'a> {
+    colors: &'a MermaidColors,
+    body: String,
+}
+
+impl<'a> SvgWriter<'a> {
+    fn new(colors: &'a MermaidColors) -> Self {
+        Self {
+            colors,
+            body: String::new(),
+        }
+    }
+
+    fn finish(self, width: f32, height: f32) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">{}</svg>",
+            self.body
+        )
+    }
+
+    fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, dash: Option<&str>) {
+        write!(
+            self.body,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{width}\"{}/>",
+            from.0,
+            from.1,
+            to.0,
+            to.1,
+            hex_color(self.colors.border),
+            dash_attribute(dash),
+        )
+        .ok();
+    }
+
+    fn path(&mut self, data: &str, width: f32, dash: Option<&str>) {
+        write!(
+            self.body,
+            "<path d=\"{data}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{width}\"{}/>",
+            hex_color(self.colors.border),
+            dash_attribute(dash),
+        )
+        .ok();
+    }
+
+    fn rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+        fill: Hsla,
+        stroke: Option<Hsla>,
+    ) {
+        write!(
+            self.body,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" rx=\"{radius}\" fill=\"{}\"{}/>",
+            hex_color(fill),
+            stroke_attributes(stroke),
+        )
+        .ok();
+    }
+
+    fn circle(&mut self, x: f32, y: f32, radius: f32) {
+        write!(
+            self.body,
+            "<circle cx=\"{x}\" cy=\"{y}\" r=\"{radius}\" fill=\"{}\"{}/>",
+            hex_color(self.colors.background),
+            stroke_attributes(Some(self.colors.border)),
+        )
+        .ok();
+    }
+
+    fn polygon(&mut self, points: &[(f32, f32)]) {
+        self.filled_polygon(points, self.colors.background, Some(self.colors.border));
+    }
+
+    fn filled_polygon(&mut self, points: &[(f32, f32)], fill: Hsla, stroke: Option<Hsla>) {
+        let points = points
+            .iter()
+            .map(|(x, y)| format!("{x},{y}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(
+            self.body,
+            "<polygon points=\"{points}\" fill=\"{}\"{}/>",
+            hex_color(fill),
+            stroke_attributes(stroke),
+        )
+        .ok();
+    }
+
+    /// Draws a filled arrowhead at `to`, pointing away from `from`.
+    fn arrowhead(&mut self, from: (f32, f32), to: (f32, f32)) {
+        let (back, side) = arrow_vectors(from, to);
+        self.filled_polygon(
+            &[
+                to,
+                (to.0 + back.0 + side.0, to.1 + back.1 + side.1),
+                (to.0 + back.0 - side.0, to.1 + back.1 - side.1),
+            ],
+            self.colors.border,
+            None,
+        );
+    }
+
+    fn open_arrowhead(&mut self, from: (f32, f32), to: (f32, f32)) {
+        let (back, side) = arrow_vectors(from, to);
+        self.line(
+            to,
+            (to.0 + back.0 + side.0, to.1 + back.1 + side.1),
+            1.5,
+            None,
+        );
+        self.line(
+            to,
+            (to.0 + back.0 - side.0, to.1 + back.1 - side.1),
+            1.5,
+            None,
+        );
+    }
+
+    fn cross(&mut self, at: (f32, f32)) {
+        let size = 5.;
+        self.line(
+            (at.0 - size, at.1 - size),
+            (at.0 + size, at.1 + size),
+            1.5,
+            None,
+        );
+        self.line(
+            (at.0 - size, at.1 + size),
+            (at.0 + size, at.1 - size),
+            1.5,
+            None,
+        );
+    }
+
+    /// Writes text centered on `(x, y)`, with one line for each `<br>` in it.
+    fn text(&mut self, x: f32, y: f32, text: &str) {
+        let lines = label_lines(text).collect::<Vec<_>>();
+        let first_center = y - (lines.len() - 1) as f32 * LINE_HEIGHT / 2.;
+        for (ix, line) in lines.into_iter().enumerate() {
+            let center = first_center + ix as f32 * LINE_HEIGHT;
+            write!(
+                self.body,
+                "<text x=\"{x}\" y=\"{}\" text-anchor=\"middle\" font-family=\"sans-serif\" font-size=\"{FONT_SIZE}\" fill=\"{}\">{}</text>",
+                center + FONT_SIZE * 0.35,
+                hex_color(self.colors.text),
+                escape_xml(line),
+            )
+            .ok();
+        }
+    }
+}
+
+/// The vectors from an arrow's tip back along its shaft, and from the shaft out to one of the
+/// arrowhead's corners.
+fn arrow_vectors(from: (f32, f32), to: (f32, f32)) -> ((f32, f32), (f32, f32)) {
+    const LENGTH: f32 = 9.;
+    const HALF_WIDTH: f32 = 4.5;
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+    let (ux, uy) = (dx / length, dy / length);
+    (
+        (-ux * LENGTH, -uy * LENGTH),
+        (-uy * HALF_WIDTH, ux * HALF_WIDTH),
+    )
+}
+
+fn dash_attribute(dash: Option<&str>) -> String {
+    dash.map(|dash| format!(" stroke-dasharray=\"{dash}\""))
+        .unwrap_or_default()
+}
+
+fn stroke_attributes(stroke: Option<Hsla>) -> String {
+    stroke
+        .map(|stroke| format!(" stroke=\"{}\" stroke-width=\"1.5\"", hex_color(stroke)))
+        .unwrap_or_default()
+}
+
+pub(crate) fn hex_color(color: Hsla) -> String {
+    let Rgba { r, g, b, .. } = color.to_rgb();
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        (r * 255.).round() as u8,
+        (g * 255.).round() as u8,
+        (b * 255.).round() as u8
+    )
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{black, white};
+    use pretty_assertions::assert_eq;
+
+    fn flowchart(source: &str) -> Flowchart {
+        match parse_mermaid(source).unwrap() {
+            MermaidDiagram::Flowchart(flowchart) => flowchart,
+            diagram => panic!("expected a flowchart, got {diagram:?}"),
+        }
+    }
+
+    fn colors() -> MermaidColors {
+        MermaidColors {
+            text: black(),
+            border: black(),
+            background: white(),
+            note_background: white(),
+        }
+    }
+
+    #[test]
+    fn test_parse_flowchart() {
+        let flowchart = flowchart(
+            "graph LR\n\
+             %% a comment\n\
+             A[Start] --> B{Is it?}\n\
+             B -- Yes --> C((Done)); B -.->|No| A\n\
+             C & B ==> D>\"Flag\"]\n\
+             classDef warning fill:#f00\n\
+             D:::warning --- E([Stadium])",
+        );
+        assert_eq!(flowchart.direction, FlowDirection::LeftRight);
+        assert_eq!(
+            flowchart
+                .nodes
+                .iter()
+                .map(|node| (node.id.as_str(), node.label.as_str(), node.shape))
+                .collect::<Vec<_>>(),
+            vec![
+                ("A", "Start", NodeShape::Rectangle),
+                ("B", "Is it?", NodeShape::Diamond),
+                ("C", "Done", NodeShape::Circle),
+                ("D", "Flag", NodeShape::Asymmetric),
+                ("E", "Stadium", NodeShape::Stadium),
+            ]
+        );
+        assert_eq!(
+            flowchart
+                .edges
+                .iter()
+                .map(|edge| (
+                    edge.from,
+                    edge.to,
+                    edge.label.as_deref(),
+                    edge.style,
+                    edge.arrow
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 1, None, LineStyle::Solid, true),
+                (1, 2, Some("Yes"), LineStyle::Solid, true),
+                (1, 0, Some("No"), LineStyle::Dotted, true),
+                (2, 3, None, LineStyle::Thick, true),
+                (1, 3, None, LineStyle::Thick, true),
+                (3, 4, None, LineStyle::Solid, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_mermaid("pie\n\"a\": 1").unwrap_err(),
+            MermaidError {
+                line: 1,
+                message: "`pie` diagrams aren't supported; use `flowchart` or `sequenceDiagram`"
+                    .into(),
+            }
+        );
+        assert_eq!(
+            parse_mermaid("graph TD\nA --> B\nB --> C[oops").unwrap_err(),
+            MermaidError {
+                line: 3,
+                message: "Expected `]` to close node `C`".into(),
+            }
+        );
+        assert_eq!(parse_mermaid("graph TD\nA -->").unwrap_err().line, 2);
+        assert_eq!(
+            parse_mermaid("sequenceDiagram\nAlice says hi").unwrap_err(),
+            MermaidError {
+                line: 2,
+                message: "Unrecognized statement `Alice says hi`".into(),
+            }
+        );
+        assert!(parse_mermaid("  \n").is_err());
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let MermaidDiagram::Sequence(diagram) = parse_mermaid(
+            "sequenceDiagram\n\
+             participant A as Alice\n\
+             actor B\n\
+             A->>+B: Hello\n\
+             loop Every minute\n\
+             B-->>-A: Hi\n\
+             end\n\
+             A-xC: Lost\n\
+             Note over A,B: Greeting",
+        )
+        .unwrap() else {
+            panic!("expected a sequence diagram");
+        };
+        assert_eq!(
+            diagram.participants,
+            vec![
+                Participant {
+                    id: "A".into(),
+                    label: "Alice".into()
+                },
+                Participant {
+                    id: "B".into(),
+                    label: "B".into()
+                },
+                Participant {
+                    id: "C".into(),
+                    label: "C".into()
+                },
+            ]
+        );
+        assert_eq!(
+            diagram.events,
+            vec![
+                SequenceEvent::Message {
+                    from: 0,
+                    to: 1,
+                    text: "Hello".into(),
+                    dashed: false,
+                    head: MessageHead::Arrow,
+                },
+                SequenceEvent::Message {
+                    from: 1,
+                    to: 0,
+                    text: "Hi".into(),
+                    dashed: true,
+                    head: MessageHead::Arrow,
+                },
+                SequenceEvent::Message {
+                    from: 0,
+                    to: 2,
+                    text: "Lost".into(),
+                    dashed: false,
+                    head: MessageHead::Cross,
+                },
+                SequenceEvent::Note {
+                    placement: NotePlacement::Over,
+                    participants: (0, 1),
+                    text: "Greeting".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_flowchart() {
+        let flowchart = flowchart("graph TD\nA --> B\nA --> C\nB --> D\nC --> D\nD --> A");
+        let layout = layout_flowchart(&flowchart);
+        let ranks = layout
+            .nodes
+            .iter()
+            .map(|node| node.rank)
+            .collect::<Vec<_>>();
+        // The edge from D back to A is ignored for ranking.
+        assert_eq!(ranks, vec![0, 1, 1, 2]);
+        assert!(layout.nodes[0].y < layout.nodes[1].y);
+        assert_eq!(layout.nodes[1].y, layout.nodes[2].y);
+        assert!(layout.nodes[1].x < layout.nodes[2].x);
+        assert!(layout.nodes[2].y < layout.nodes[3].y);
+
+        let flowchart = self::flowchart("graph RL\nA --> B");
+        let layout = layout_flowchart(&flowchart);
+        assert!(layout.nodes[0].x > layout.nodes[1].x);
+        assert_eq!(layout.nodes[0].y, layout.nodes[1].y);
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let diagram = parse_mermaid("graph TD\nA[\"a < b & c\"] -->|yes| B").unwrap();
+        let svg = render_mermaid_svg(&diagram, &colors());
+        assert!(svg.svg.starts_with("<svg"));
+        assert!(svg.svg.contains("a &lt; b &amp; c"));
+        assert!(svg.svg.contains(">yes</text>"));
+        assert!(svg.svg.contains("fill=\"#ffffff\""));
+        assert!(svg.width > 0. && svg.height > 0.);
+
+        let diagram = parse_mermaid(
+            "sequenceDiagram\nAlice->>Bob: Hello<br>there\nBob->>Bob: Think\nNote right of Bob: Done",
+        )
+        .unwrap();
+        let svg = render_mermaid_svg(&diagram, &colors());
+        assert!(svg.svg.contains(">Hello</text>"));
+        assert!(svg.svg.contains(">there</text>"));
+        assert!(svg.svg.contains(">Done</text>"));
+    }
+}
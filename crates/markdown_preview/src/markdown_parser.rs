@@ -1,5 +1,7 @@
 use crate::{
     markdown_elements::*,
+    markdown_math::{math_to_unicode, parse_math},
+    markdown_mermaid::parse_mermaid,
     markdown_minifier::{Minifier, MinifierOptions},
 };
 use async_recursion::async_recursion;
@@ -8,7 +10,7 @@ use gpui::{DefiniteLength, FontWeight, px, relative};
 use html5ever::{ParseOpts, local_name, parse_document, tendril::TendrilSink};
 use language::LanguageRegistry;
 use markup5ever_rcdom::RcDom;
-use pulldown_cmark::{Alignment, Event, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{Alignment, BlockQuoteKind, Event, Options, Parser, Tag, TagEnd};
 use std::{
     cell::RefCell, collections::HashMap, mem, ops::Range, path::PathBuf, rc::Rc, sync::Arc, vec,
 };
@@ -19,9 +21,7 @@ pub async fn parse_markdown(
     file_location_directory: Option<PathBuf>,
     language_registry: Option<Arc<LanguageRegistry>>,
 ) -> ParsedMarkdown {
-    let mut options = Options::all();
-    options.remove(pulldown_cmark::Options::ENABLE_DEFINITION_LIST);
-
+    let options = Options::all();
     let parser = Parser::new_ext(markdown_input, options);
     let parser = MarkdownParser::new(
         parser.into_offset_iter().collect(),
@@ -60,6 +60,10 @@ struct MarkdownParser<'a> {
     parsed: Vec<ParsedMarkdownElement>,
     file_location_directory: Option<PathBuf>,
     language_registry: Option<Arc<LanguageRegistry>>,
+    /// The number of each footnote, assigned in the order they're first referenced.
+    footnote_numbers: HashMap<String, usize>,
+    /// Footnote definitions, which are moved to the end of the document.
+    footnote_definitions: Vec<ParsedMarkdownFootnoteDefinition>,
 }
 
 #[derive(Debug)]
@@ -99,6 +103,8 @@ impl<'a> MarkdownParser<'a> {
             language_registry,
             cursor: 0,
             parsed: vec![],
+            footnote_numbers: HashMap::default(),
+            footnote_definitions: Vec::new(),
         }
     }
 
@@ -136,6 +142,8 @@ impl<'a> MarkdownParser<'a> {
             Event::Text(_)
             // Represent an inline code block
             | Event::Code(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
             | Event::Html(_)
             | Event::InlineHtml(_)
             | Event::FootnoteReference(_)
@@ -158,9 +166,34 @@ impl<'a> MarkdownParser<'a> {
                 self.cursor += 1;
             }
         }
+
+        // Like GitHub, list footnotes after the document in the order they're referenced.
+        let mut footnotes = mem::take(&mut self.footnote_definitions);
+        for footnote in &mut footnotes {
+            footnote.number = self.footnote_number(&footnote.label);
+        }
+        footnotes.sort_by_key(|footnote| footnote.number);
+        if let Some(first) = footnotes.first() {
+            let start = first.source_range.start;
+            self.parsed
+                .push(ParsedMarkdownElement::HorizontalRule(start..start));
+        }
+        self.parsed.extend(
+            footnotes
+                .into_iter()
+                .map(ParsedMarkdownElement::FootnoteDefinition),
+        );
         self
     }
 
+    fn footnote_number(&mut self, label: &str) -> usize {
+        let next_number = self.footnote_numbers.len() + 1;
+        *self
+            .footnote_numbers
+            .entry(label.to_string())
+            .or_insert(next_number)
+    }
+
     #[async_recursion]
     async fn parse_block(&mut self) -> Option<Vec<ParsedMarkdownElement>> {
         let (current, source_range) = self.current().unwrap();
@@ -190,11 +223,36 @@ impl<'a> MarkdownParser<'a> {
                     let list = self.parse_list(order).await;
                     Some(list)
                 }
-                Tag::BlockQuote(_kind) => {
+                Tag::BlockQuote(kind) => {
+                    let alert = kind.map(|kind| match kind {
+                        BlockQuoteKind::Note => ParsedMarkdownAlertKind::Note,
+                        BlockQuoteKind::Tip => ParsedMarkdownAlertKind::Tip,
+                        BlockQuoteKind::Important => ParsedMarkdownAlertKind::Important,
+                        BlockQuoteKind::Warning => ParsedMarkdownAlertKind::Warning,
+                        BlockQuoteKind::Caution => ParsedMarkdownAlertKind::Caution,
+                    });
                     self.cursor += 1;
-                    let block_quote = self.parse_block_quote().await;
+                    let block_quote = self.parse_block_quote(alert).await;
                     Some(vec![ParsedMarkdownElement::BlockQuote(block_quote)])
                 }
+                Tag::DefinitionList => {
+                    self.cursor += 1;
+                    let list = self.parse_definition_list(source_range).await;
+                    Some(vec![ParsedMarkdownElement::DefinitionList(list)])
+                }
+                Tag::FootnoteDefinition(label) => {
+                    let label = label.to_lowercase();
+                    self.cursor += 1;
+                    let children = self.parse_blocks_until(TagEnd::FootnoteDefinition).await;
+                    self.footnote_definitions
+                        .push(ParsedMarkdownFootnoteDefinition {
+                            source_range,
+                            label: label.into(),
+                            number: 0,
+                            children,
+                        });
+                    Some(Vec::new())
+                }
                 Tag::CodeBlock(kind) => {
                     let language = match kind {
                         pulldown_cmark::CodeBlockKind::Indented => None,
@@ -209,6 +267,18 @@ impl<'a> MarkdownParser<'a> {
 
                     self.cursor += 1;
 
+                    if language.as_deref() == Some("mermaid") {
+                        let code_block = self.parse_code_block(None).await?;
+                        let diagram = parse_mermaid(&code_block.contents).map(Arc::new);
+                        return Some(vec![ParsedMarkdownElement::Mermaid(
+                            ParsedMarkdownMermaid {
+                                source_range: code_block.source_range,
+                                contents: code_block.contents,
+                                diagram,
+                            },
+                        )]);
+                    }
+
                     let code_block = self.parse_code_block(language).await?;
                     Some(vec![ParsedMarkdownElement::CodeBlock(code_block)])
                 }
@@ -255,7 +325,7 @@ impl<'a> MarkdownParser<'a> {
                 break;
             }
 
-            let (current, _) = self.current().unwrap();
+            let (current, current_range) = self.current().unwrap();
             let prev_len = text.len();
             match current {
                 Event::SoftBreak => {
@@ -379,6 +449,60 @@ impl<'a> MarkdownParser<'a> {
                         },
                     ));
                 }
+                Event::FootnoteReference(label) => {
+                    let label = label.to_lowercase();
+                    let number = self.footnote_number(&label);
+                    text.push_str(&format!("[{number}]"));
+                    let range = prev_len..text.len();
+                    highlights.push((
+                        range.clone(),
+                        MarkdownHighlight::Style(MarkdownHighlightStyle {
+                            link: true,
+                            ..Default::default()
+                        }),
+                    ));
+                    regions.push((
+                        range,
+                        ParsedRegion {
+                            code: false,
+                            link: Some(Link::Footnote {
+                                label: label.into(),
+                            }),
+                        },
+                    ));
+                }
+                Event::InlineMath(source) => match parse_math(source) {
+                    Ok(node) => {
+                        text.push_str(&math_to_unicode(&node));
+                        highlights.push((
+                            prev_len..text.len(),
+                            MarkdownHighlight::Style(MarkdownHighlightStyle {
+                                italic: true,
+                                ..Default::default()
+                            }),
+                        ));
+                    }
+                    Err(_) => {
+                        text.push('$');
+                        text.push_str(source);
+                        text.push('$');
+                    }
+                },
+                Event::DisplayMath(source) => {
+                    if !text.is_empty() {
+                        markdown_text_like.push(MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                            source_range: source_range.clone(),
+                            contents: mem::take(&mut text).into(),
+                            highlights: mem::take(&mut highlights),
+                            regions: mem::take(&mut regions),
+                        }));
+                    }
+                    markdown_text_like.push(MarkdownParagraphChunk::Math(ParsedMarkdownMath {
+                        source_range: current_range.clone(),
+                        source: source.to_string().into(),
+                        node: parse_math(source).map(Arc::new),
+                    }));
+                }
                 Event::Start(tag) => match tag {
                     Tag::Emphasis => italic_depth += 1,
                     Tag::Strong => bold_depth += 1,
@@ -704,7 +828,88 @@ impl<'a> MarkdownParser<'a> {
     }
 
     #[async_recursion]
-    async fn parse_block_quote(&mut self) -> ParsedMarkdownBlockQuote {
+    async fn parse_definition_list(
+        &mut self,
+        source_range: Range<usize>,
+    ) -> ParsedMarkdownDefinitionList {
+        let mut items: Vec<ParsedMarkdownDefinitionListItem> = Vec::new();
+
+        while !self.eof() {
+            let (current, range) = self.current().unwrap();
+            let range = range.clone();
+            match current {
+                Event::Start(Tag::DefinitionListTitle) => {
+                    self.cursor += 1;
+                    let term = self.parse_text(false, Some(range));
+                    if self.current_event() == Some(&Event::End(TagEnd::DefinitionListTitle)) {
+                        self.cursor += 1;
+                    }
+                    items.push(ParsedMarkdownDefinitionListItem {
+                        term,
+                        definitions: Vec::new(),
+                    });
+                }
+                Event::Start(Tag::DefinitionListDefinition) => {
+                    self.cursor += 1;
+                    let definition = self
+                        .parse_blocks_until(TagEnd::DefinitionListDefinition)
+                        .await;
+                    if let Some(item) = items.last_mut() {
+                        item.definitions.push(definition);
+                    }
+                }
+                Event::End(TagEnd::DefinitionList) => {
+                    self.cursor += 1;
+                    break;
+                }
+                _ => self.cursor += 1,
+            }
+        }
+
+        ParsedMarkdownDefinitionList {
+            source_range,
+            items,
+        }
+    }
+
+    /// Parses blocks, and text that isn't wrapped in a paragraph, up to and including the given
+    /// end tag.
+    #[async_recursion]
+    async fn parse_blocks_until(&mut self, end: TagEnd) -> Vec<ParsedMarkdownElement> {
+        let mut children = Vec::new();
+
+        while !self.eof() {
+            let (current, range) = self.current().unwrap();
+            if current == &Event::End(end) {
+                self.cursor += 1;
+                break;
+            }
+
+            if MarkdownParser::is_text_like(current) {
+                let range = range.clone();
+                let cursor = self.cursor;
+                let text = self.parse_text(false, Some(range));
+                if !text.is_empty() {
+                    children.push(ParsedMarkdownElement::Paragraph(text));
+                }
+                if self.cursor == cursor {
+                    self.cursor += 1;
+                }
+            } else if let Some(blocks) = self.parse_block().await {
+                children.extend(blocks);
+            } else {
+                self.cursor += 1;
+            }
+        }
+
+        children
+    }
+
+    #[async_recursion]
+    async fn parse_block_quote(
+        &mut self,
+        alert: Option<ParsedMarkdownAlertKind>,
+    ) -> ParsedMarkdownBlockQuote {
         let (_event, source_range) = self.previous().unwrap();
         let source_range = source_range.clone();
         let mut nested_depth = 1;
@@ -747,6 +952,7 @@ impl<'a> MarkdownParser<'a> {
         ParsedMarkdownBlockQuote {
             source_range,
             children,
+            alert,
         }
     }
 
@@ -879,6 +1085,9 @@ impl<'a> MarkdownParser<'a> {
                 ]));
             }
             markup5ever_rcdom::NodeData::Comment { .. } => {}
+            // The contents of style sheets and scripts aren't meant to be shown.
+            markup5ever_rcdom::NodeData::Element { name, .. }
+                if matches!(name.local, local_name!("style") | local_name!("script")) => {}
             markup5ever_rcdom::NodeData::Element { name, attrs, .. } => {
                 let mut styles = if let Some(styles) = Self::markdown_style_from_html_styles(
                     Self::extract_styles_from_attributes(attrs),
@@ -1400,6 +1609,7 @@ impl<'a> MarkdownParser<'a> {
             Some(ParsedMarkdownBlockQuote {
                 children,
                 source_range,
+                alert: None,
             })
         }
     }
@@ -2284,6 +2494,39 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_html_table_with_style_and_script() {
+        // The way pandas renders a data frame in a notebook.
+        let parsed = parse(
+            "<div>
+<style scoped>
+    .dataframe tbody tr th {
+        vertical-align: top;
+    }
+</style>
+<script>console.log(\"hidden\")</script>
+<table border=\"1\" class=\"dataframe\">
+  <thead>
+    <tr><th></th><th>Name</th></tr>
+  </thead>
+  <tbody>
+    <tr><th>0</th><td>Chris</td></tr>
+  </tbody>
+</table>
+</div>",
+        )
+        .await;
+
+        assert!(
+            matches!(
+                parsed.children.as_slice(),
+                [ParsedMarkdownElement::Table(_)]
+            ),
+            "{:?}",
+            parsed.children
+        );
+    }
+
     #[gpui::test]
     async fn test_html_table_with_caption() {
         let parsed = parse(
@@ -3024,6 +3267,99 @@ More text
         );
     }
 
+    #[gpui::test]
+    async fn test_alert_block_quote() {
+        let parsed = parse("> [!WARNING]\n> Mind the gap\n\n> [!TIP]\n").await;
+
+        let ParsedMarkdownElement::BlockQuote(block_quote) = &parsed.children[0] else {
+            panic!("expected a block quote");
+        };
+        assert_eq!(block_quote.alert, Some(ParsedMarkdownAlertKind::Warning));
+        let [ParsedMarkdownElement::Paragraph(paragraph)] = block_quote.children.as_slice() else {
+            panic!("expected a single paragraph");
+        };
+        let [MarkdownParagraphChunk::Text(text)] = paragraph.as_slice() else {
+            panic!("expected text");
+        };
+        assert_eq!(text.contents.as_ref(), "Mind the gap");
+
+        let ParsedMarkdownElement::BlockQuote(block_quote) = &parsed.children[1] else {
+            panic!("expected a block quote");
+        };
+        assert_eq!(block_quote.alert, Some(ParsedMarkdownAlertKind::Tip));
+    }
+
+    #[gpui::test]
+    async fn test_footnotes() {
+        let parsed = parse(
+            "\
+[^b]: Second.
+
+First[^a] and second[^b].
+
+[^a]: First.
+",
+        )
+        .await;
+
+        let [
+            ParsedMarkdownElement::Paragraph(paragraph),
+            ParsedMarkdownElement::HorizontalRule(_),
+            ParsedMarkdownElement::FootnoteDefinition(first),
+            ParsedMarkdownElement::FootnoteDefinition(second),
+        ] = parsed.children.as_slice()
+        else {
+            panic!("expected footnotes after the body: {:?}", parsed.children);
+        };
+
+        let [MarkdownParagraphChunk::Text(text)] = paragraph.as_slice() else {
+            panic!("expected text");
+        };
+        assert_eq!(text.contents.as_ref(), "First[1] and second[2].");
+        let footnote_links = text
+            .regions
+            .iter()
+            .filter_map(|(_, region)| region.link.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            footnote_links,
+            vec![
+                Link::Footnote { label: "a".into() },
+                Link::Footnote { label: "b".into() },
+            ]
+        );
+
+        assert_eq!((first.label.as_ref(), first.number), ("a", 1));
+        assert_eq!(plain_text(&first.children), vec!["First."]);
+        assert_eq!((second.label.as_ref(), second.number), ("b", 2));
+        assert_eq!(plain_text(&second.children), vec!["Second."]);
+        assert!(parsed.children[0].references_footnote("b"));
+        assert!(!parsed.children[0].references_footnote("c"));
+    }
+
+    #[gpui::test]
+    async fn test_definition_list() {
+        let parsed = parse("Apple\n: A fruit.\n: A company.\n\nPear\n: Another fruit.\n").await;
+
+        let [ParsedMarkdownElement::DefinitionList(list)] = parsed.children.as_slice() else {
+            panic!("expected a definition list: {:?}", parsed.children);
+        };
+        assert_eq!(list.items.len(), 2);
+        let terms = list
+            .items
+            .iter()
+            .map(|item| ParsedMarkdownElement::Paragraph(item.term.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(plain_text(&terms), vec!["Apple", "Pear"]);
+        let definitions = list.items[0]
+            .definitions
+            .iter()
+            .flat_map(|definition| plain_text(definition))
+            .collect::<Vec<_>>();
+        assert_eq!(definitions, vec!["A fruit.", "A company."]);
+        assert_eq!(list.items[1].definitions.len(), 1);
+    }
+
     #[gpui::test]
     async fn test_code_block() {
         let parsed = parse(
@@ -3077,6 +3413,76 @@ fn main() {
         );
     }
 
+    #[gpui::test]
+    async fn test_math() {
+        let parsed = parse("Energy is $E = mc^2$, or $\\oops$.\n\n$$\\frac{a}{b}$$ and").await;
+
+        let ParsedMarkdownElement::Paragraph(chunks) = &parsed.children[0] else {
+            panic!("expected a paragraph");
+        };
+        let MarkdownParagraphChunk::Text(text) = &chunks[0] else {
+            panic!("expected text");
+        };
+        assert_eq!(text.contents.as_ref(), "Energy is E = mc², or $\\oops$.");
+        assert_eq!(
+            text.highlights,
+            vec![(
+                10..18,
+                MarkdownHighlight::Style(MarkdownHighlightStyle {
+                    italic: true,
+                    ..Default::default()
+                })
+            )]
+        );
+
+        let ParsedMarkdownElement::Paragraph(chunks) = &parsed.children[1] else {
+            panic!("expected a paragraph");
+        };
+        assert_eq!(chunks.len(), 2);
+        let MarkdownParagraphChunk::Math(math) = &chunks[0] else {
+            panic!("expected display math");
+        };
+        assert_eq!(math.source.as_ref(), "\\frac{a}{b}");
+        assert_eq!(math.source_range, 35..50);
+        assert!(matches!(
+            math.node.as_deref(),
+            Ok(crate::markdown_math::MathNode::Fraction { .. })
+        ));
+    }
+
+    #[gpui::test]
+    async fn test_mermaid_block() {
+        let parsed = parse("```mermaid\ngraph TD\nA --> B\n```\n\n```mermaid\npie\n```\n").await;
+
+        let ParsedMarkdownElement::Mermaid(mermaid) = &parsed.children[0] else {
+            panic!("expected a diagram");
+        };
+        assert_eq!(mermaid.contents.as_ref(), "graph TD\nA --> B");
+        assert_eq!(mermaid.source_range, 0..31);
+        assert!(mermaid.diagram.is_ok());
+
+        let ParsedMarkdownElement::Mermaid(mermaid) = &parsed.children[1] else {
+            panic!("expected a diagram");
+        };
+        assert_eq!(mermaid.diagram.as_ref().unwrap_err().line, 1);
+    }
+
+    fn plain_text(elements: &[ParsedMarkdownElement]) -> Vec<String> {
+        elements
+            .iter()
+            .map(|element| match element {
+                ParsedMarkdownElement::Paragraph(paragraph) => paragraph
+                    .iter()
+                    .filter_map(|chunk| match chunk {
+                        MarkdownParagraphChunk::Text(text) => Some(text.contents.to_string()),
+                        _ => None,
+                    })
+                    .collect(),
+                element => panic!("expected a paragraph: {element:?}"),
+            })
+            .collect()
+    }
+
     fn h1(contents: MarkdownParagraph, source_range: Range<usize>) -> ParsedMarkdownElement {
         ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
             source_range,
@@ -3121,6 +3527,7 @@ fn main() {
         ParsedMarkdownElement::BlockQuote(ParsedMarkdownBlockQuote {
             source_range,
             children,
+            alert: None,
         })
     }
 
@@ -0,0 +1,1466 @@
+//! Exporting parsed Markdown as a PDF document.
+//!
+//! The writer lays out text itself using Zed's bundled IBM Plex Sans and Lilex fonts, and
+//! embeds the glyphs it used from them, so that any text those fonts cover can be shown.
+//! Characters they lack are drawn as the fonts' missing glyph box. Images, math and diagrams
+//! are embedded as JPEG images.
+
+use std::{collections::BTreeMap, fmt::Write as _, mem, ops::Range};
+
+use anyhow::{Context as _, Result};
+use gpui::{AssetSource, DefiniteLength, Hsla, Rgba};
+use image::{RgbImage, RgbaImage, codecs::jpeg::JpegEncoder};
+
+use crate::{
+    markdown_elements::{
+        HeadingLevel, Image, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
+        ParsedMarkdownCodeBlock, ParsedMarkdownElement, ParsedMarkdownListItem,
+        ParsedMarkdownListItemType, ParsedMarkdownMath, ParsedMarkdownMermaid, ParsedMarkdownTable,
+        ParsedMarkdownTableAlignment, ParsedMarkdownText,
+    },
+    markdown_export::{ExportTheme, code_block_runs, link_target, styled_runs},
+    markdown_math::{math_to_unicode, render_math_svg},
+    markdown_mermaid::render_mermaid_svg,
+    markdown_pdf_fonts::TrueTypeFont,
+};
+
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 56.;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2. * MARGIN;
+const BODY_SIZE: f32 = 11.;
+const CODE_SIZE: f32 = 9.;
+const LINE_HEIGHT: f32 = 1.45;
+const BLOCK_GAP: f32 = 8.;
+const LIST_INDENT: f32 = 18.;
+const QUOTE_INDENT: f32 = 16.;
+const FOOTNOTE_SIZE: f32 = 9.;
+const CELL_PADDING: f32 = 4.;
+const CODE_PADDING: f32 = 6.;
+/// PDF points per CSS pixel.
+const POINTS_PER_PIXEL: f32 = 0.75;
+
+/// Renders a parsed Markdown document as a PDF file.
+///
+/// `rasterize_svg` turns the SVG generated for math and diagrams into pixels. They're written
+/// as their source text when it returns `None`.
+pub fn export_pdf(
+    parsed: &ParsedMarkdown,
+    title: &str,
+    theme: &ExportTheme,
+    fonts: &PdfFonts,
+    rasterize_svg: &dyn Fn(&str) -> Option<RgbaImage>,
+) -> Result<Vec<u8>> {
+    let mut writer = PdfWriter {
+        theme,
+        fonts,
+        rasterize_svg,
+        pages: Vec::new(),
+        images: Vec::new(),
+        used_glyphs: Default::default(),
+        y: MARGIN,
+        indent: 0.,
+        list_base: 0.,
+        quote_bars: Vec::new(),
+    };
+    writer.new_page();
+    for element in &parsed.children {
+        writer.element(element);
+    }
+    writer.finish(title)
+}
+
+/// The fonts a PDF's text is written in, one for each [`PdfFont`].
+pub struct PdfFonts([TrueTypeFont; 5]);
+
+impl PdfFonts {
+    pub fn load(assets: &dyn AssetSource) -> Result<Self> {
+        let load = |font: PdfFont| -> Result<TrueTypeFont> {
+            let path = font.asset_path();
+            let data = assets
+                .load(path)?
+                .with_context(|| format!("Missing font {path}"))?;
+            TrueTypeFont::parse(data).with_context(|| format!("Failed to load font {path}"))
+        };
+        Ok(Self([
+            load(PdfFont::Regular)?,
+            load(PdfFont::Bold)?,
+            load(PdfFont::Italic)?,
+            load(PdfFont::BoldItalic)?,
+            load(PdfFont::Mono)?,
+        ]))
+    }
+
+    fn get(&self, font: PdfFont) -> &TrueTypeFont {
+        &self.0[font as usize]
+    }
+
+    fn text_width(&self, font: PdfFont, text: &str, size: f32) -> f32 {
+        self.get(font).text_width(text, size)
+    }
+}
+
+/// The order of the variants matches the fonts in [`PdfFonts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PdfFont {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+    Mono,
+}
+
+impl PdfFont {
+    const ALL: [PdfFont; 5] = [
+        PdfFont::Regular,
+        PdfFont::Bold,
+        PdfFont::Italic,
+        PdfFont::BoldItalic,
+        PdfFont::Mono,
+    ];
+
+    fn new(bold: bool, italic: bool, code: bool) -> Self {
+        match (code, bold, italic) {
+            (true, _, _) => PdfFont::Mono,
+            (false, true, true) => PdfFont::BoldItalic,
+            (false, true, false) => PdfFont::Bold,
+            (false, false, true) => PdfFont::Italic,
+            (false, false, false) => PdfFont::Regular,
+        }
+    }
+
+    fn resource_name(self) -> &'static str {
+        match self {
+            PdfFont::Regular => "F1",
+            PdfFont::Bold => "F2",
+            PdfFont::Italic => "F3",
+            PdfFont::BoldItalic => "F4",
+            PdfFont::Mono => "F5",
+        }
+    }
+
+    fn asset_path(self) -> &'static str {
+        match self {
+            PdfFont::Regular => "fonts/ibm-plex-sans/IBMPlexSans-Regular.ttf",
+            PdfFont::Bold => "fonts/ibm-plex-sans/IBMPlexSans-Bold.ttf",
+            PdfFont::Italic => "fonts/ibm-plex-sans/IBMPlexSans-Italic.ttf",
+            PdfFont::BoldItalic => "fonts/ibm-plex-sans/IBMPlexSans-BoldItalic.ttf",
+            PdfFont::Mono => "fonts/lilex/Lilex-Regular.ttf",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TextStyle {
+    font: PdfFont,
+    size: f32,
+    color: Hsla,
+    underline: bool,
+    strikethrough: bool,
+    code_background: bool,
+    link: Option<String>,
+}
+
+/// A run of text on a single line.
+#[derive(Debug)]
+struct Piece {
+    text: String,
+    style: TextStyle,
+    width: f32,
+}
+
+/// Breaks styled text into lines that fit within `max_width`.
+fn wrap_text(
+    fonts: &PdfFonts,
+    fragments: &[(String, TextStyle)],
+    max_width: f32,
+) -> Vec<Vec<Piece>> {
+    let mut lines = vec![Vec::new()];
+    let mut line_width = 0.;
+    for (text, style) in fragments {
+        for token in tokenize(text) {
+            let line = lines.last_mut().unwrap();
+            match token {
+                "\n" => {
+                    lines.push(Vec::new());
+                    line_width = 0.;
+                }
+                token if token.trim().is_empty() => {
+                    if !line.is_empty() {
+                        line_width += push_piece(fonts, line, " ", style);
+                    }
+                }
+                word => {
+                    let width = fonts.text_width(style.font, word, style.size);
+                    if line_width + width > max_width && !line.is_empty() {
+                        trim_line_end(fonts, line);
+                        lines.push(Vec::new());
+                        line_width = 0.;
+                    }
+                    let mut word = word;
+                    // Split words that don't fit on a line of their own.
+                    while fonts.text_width(style.font, word, style.size) > max_width - line_width {
+                        let mut split = 0;
+                        for (ix, c) in word.char_indices() {
+                            let end = ix + c.len_utf8();
+                            if fonts.text_width(style.font, &word[..end], style.size)
+                                > max_width - line_width
+                            {
+                                break;
+                            }
+                            split = end;
+                        }
+                        if split == 0 {
+                            split = word.chars().next().map_or(word.len(), char::len_utf8);
+                        }
+                        push_piece(fonts, lines.last_mut().unwrap(), &word[..split], style);
+                        lines.push(Vec::new());
+                        line_width = 0.;
+                        word = &word[split..];
+                    }
+                    if !word.is_empty() {
+                        line_width += push_piece(fonts, lines.last_mut().unwrap(), word, style);
+                    }
+                }
+            }
+        }
+    }
+    for line in &mut lines {
+        trim_line_end(fonts, line);
+    }
+    if lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Splits text into words, runs of whitespace and line breaks.
+fn tokenize(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let len = if first == '\n' {
+            1
+        } else {
+            let is_space = first.is_whitespace();
+            rest.find(|c: char| c == '\n' || c.is_whitespace() != is_space)
+                .unwrap_or(rest.len())
+        };
+        let (token, remaining) = rest.split_at(len);
+        rest = remaining;
+        Some(token)
+    })
+}
+
+fn push_piece(fonts: &PdfFonts, line: &mut Vec<Piece>, text: &str, style: &TextStyle) -> f32 {
+    let width = fonts.text_width(style.font, text, style.size);
+    match line.last_mut() {
+        Some(piece) if piece.style == *style => {
+            piece.text.push_str(text);
+            piece.width += width;
+        }
+        _ => line.push(Piece {
+            text: text.to_string(),
+            style: style.clone(),
+            width,
+        }),
+    }
+    width
+}
+
+fn trim_line_end(fonts: &PdfFonts, line: &mut Vec<Piece>) {
+    while let Some(piece) = line.last_mut() {
+        let trimmed_len = piece.text.trim_end().len();
+        if trimmed_len == piece.text.len() {
+            break;
+        }
+        piece.text.truncate(trimmed_len);
+        piece.width = fonts.text_width(piece.style.font, &piece.text, piece.style.size);
+        if piece.text.is_empty() {
+            line.pop();
+        }
+    }
+}
+
+fn line_height(line: &[Piece], default_size: f32) -> f32 {
+    line.iter()
+        .map(|piece| piece.style.size)
+        .fold(default_size, f32::max)
+        * LINE_HEIGHT
+}
+
+struct PdfPage {
+    content: String,
+    links: Vec<([f32; 4], String)>,
+}
+
+struct PdfImage {
+    width: u32,
+    height: u32,
+    jpeg: Vec<u8>,
+}
+
+struct PdfWriter<'a> {
+    theme: &'a ExportTheme,
+    fonts: &'a PdfFonts,
+    rasterize_svg: &'a dyn Fn(&str) -> Option<RgbaImage>,
+    pages: Vec<PdfPage>,
+    images: Vec<PdfImage>,
+    /// The glyphs written in each font, and a character each of them shows.
+    used_glyphs: [BTreeMap<u16, char>; 5],
+    /// Distance of the layout position from the top of the page.
+    y: f32,
+    /// Indentation of the current block from the left margin.
+    indent: f32,
+    /// Indentation of the outermost list being written.
+    list_base: f32,
+    /// Horizontal positions and colors of the bars of the block quotes being written.
+    quote_bars: Vec<(f32, Hsla)>,
+}
+
+impl PdfWriter<'_> {
+    fn body_style(&self) -> TextStyle {
+        TextStyle {
+            font: PdfFont::Regular,
+            size: BODY_SIZE,
+            color: self.theme.text,
+            underline: false,
+            strikethrough: false,
+            code_background: false,
+            link: None,
+        }
+    }
+
+    fn element(&mut self, element: &ParsedMarkdownElement) {
+        match element {
+            ParsedMarkdownElement::Heading(heading) => {
+                let size = match heading.level {
+                    HeadingLevel::H1 => 22.,
+                    HeadingLevel::H2 => 18.,
+                    HeadingLevel::H3 => 15.,
+                    HeadingLevel::H4 => 13.,
+                    HeadingLevel::H5 => 12.,
+                    HeadingLevel::H6 => 11.,
+                };
+                self.gap(size * 0.4);
+                let style = TextStyle {
+                    font: PdfFont::Bold,
+                    size,
+                    ..self.body_style()
+                };
+                self.paragraph(&heading.contents, &style);
+                if matches!(heading.level, HeadingLevel::H1 | HeadingLevel::H2) {
+                    let top = self.advance(4.);
+                    let x = MARGIN + self.indent;
+                    self.fill_rect(
+                        x,
+                        top + 1.,
+                        CONTENT_WIDTH - self.indent,
+                        1.,
+                        self.theme.border,
+                    );
+                }
+                self.gap(BLOCK_GAP);
+            }
+            ParsedMarkdownElement::Paragraph(paragraph) => {
+                let style = self.body_style();
+                self.paragraph(paragraph, &style);
+                self.gap(BLOCK_GAP);
+            }
+            ParsedMarkdownElement::ListItem(item) => self.list_item(item),
+            ParsedMarkdownElement::Table(table) => {
+                self.table(table);
+                self.gap(BLOCK_GAP);
+            }
+            ParsedMarkdownElement::BlockQuote(block_quote) => {
+                let bar_color = block_quote
+                    .alert
+                    .map_or(self.theme.border, |kind| self.theme.alert_color(kind));
+                self.quote_bars.push((MARGIN + self.indent, bar_color));
+                self.indent += QUOTE_INDENT;
+                if let Some(kind) = block_quote.alert {
+                    let style = TextStyle {
+                        font: PdfFont::Bold,
+                        color: bar_color,
+                        ..self.body_style()
+                    };
+                    let fragments = vec![(kind.title().to_string(), style)];
+                    self.text_block(&fragments, BODY_SIZE);
+                    self.gap(BLOCK_GAP / 2.);
+                }
+                for child in &block_quote.children {
+                    self.element(child);
+                }
+                self.indent -= QUOTE_INDENT;
+                self.quote_bars.pop();
+            }
+            ParsedMarkdownElement::DefinitionList(list) => {
+                for item in &list.items {
+                    let style = TextStyle {
+                        font: PdfFont::Bold,
+                        ..self.body_style()
+                    };
+                    self.paragraph(&item.term, &style);
+                    self.indent += LIST_INDENT;
+                    for definition in &item.definitions {
+                        for child in definition {
+                            self.element(child);
+                        }
+                    }
+                    self.indent -= LIST_INDENT;
+                }
+            }
+            ParsedMarkdownElement::FootnoteDefinition(footnote) => {
+                let style = TextStyle {
+                    size: FOOTNOTE_SIZE,
+                    color: self.theme.muted_text,
+                    ..self.body_style()
+                };
+                let marker_x = MARGIN + self.indent;
+                let height = FOOTNOTE_SIZE * LINE_HEIGHT;
+                self.ensure(height);
+                let baseline = self.y + (height + FOOTNOTE_SIZE * 0.7) / 2.;
+                self.show_text(
+                    marker_x,
+                    baseline,
+                    &format!("{}.", footnote.number),
+                    PdfFont::Regular,
+                    FOOTNOTE_SIZE,
+                    style.color,
+                );
+                let previous_indent = mem::replace(&mut self.indent, self.indent + LIST_INDENT);
+                for child in &footnote.children {
+                    match child {
+                        ParsedMarkdownElement::Paragraph(paragraph) => {
+                            self.paragraph(paragraph, &style);
+                        }
+                        child => self.element(child),
+                    }
+                }
+                self.indent = previous_indent;
+                self.gap(2.);
+            }
+            ParsedMarkdownElement::CodeBlock(code_block) => {
+                self.code_block(code_block);
+                self.gap(BLOCK_GAP);
+            }
+            ParsedMarkdownElement::Mermaid(mermaid) => {
+                self.mermaid(mermaid);
+                self.gap(BLOCK_GAP);
+            }
+            ParsedMarkdownElement::HorizontalRule(_) => {
+                let top = self.advance(16.);
+                let x = MARGIN + self.indent;
+                self.fill_rect(
+                    x,
+                    top + 7.,
+                    CONTENT_WIDTH - self.indent,
+                    2.,
+                    self.theme.border,
+                );
+            }
+            ParsedMarkdownElement::Image(image) => {
+                self.image(image);
+                self.gap(BLOCK_GAP);
+            }
+        }
+    }
+
+    /// Writes a paragraph, placing its images and display math on lines of their own.
+    fn paragraph(&mut self, paragraph: &MarkdownParagraph, style: &TextStyle) {
+        let mut fragments = Vec::new();
+        for chunk in paragraph {
+            match chunk {
+                MarkdownParagraphChunk::Text(text) => {
+                    fragments.extend(self.text_fragments(text, style));
+                }
+                MarkdownParagraphChunk::Image(image) => {
+                    self.text_block(&mem::take(&mut fragments), BODY_SIZE);
+                    self.image(image);
+                }
+                MarkdownParagraphChunk::Math(math) => {
+                    self.text_block(&mem::take(&mut fragments), BODY_SIZE);
+                    self.math(math);
+                }
+            }
+        }
+        self.text_block(&fragments, style.size);
+    }
+
+    /// The styled text of a paragraph, with images and math written as text.
+    fn inline_fragments(
+        &self,
+        paragraph: &MarkdownParagraph,
+        style: &TextStyle,
+    ) -> Vec<(String, TextStyle)> {
+        let mut fragments = Vec::new();
+        for chunk in paragraph {
+            match chunk {
+                MarkdownParagraphChunk::Text(text) => {
+                    fragments.extend(self.text_fragments(text, style));
+                }
+                MarkdownParagraphChunk::Image(image) => {
+                    fragments.push(self.image_placeholder(image));
+                }
+                MarkdownParagraphChunk::Math(math) => {
+                    let text = match &math.node {
+                        Ok(node) => math_to_unicode(node),
+                        Err(_) => format!("$${}$$", math.source),
+                    };
+                    fragments.push((text, style.clone()));
+                }
+            }
+        }
+        fragments
+    }
+
+    fn text_fragments(
+        &self,
+        text: &ParsedMarkdownText,
+        base: &TextStyle,
+    ) -> Vec<(String, TextStyle)> {
+        styled_runs(text, &self.theme.syntax)
+            .into_iter()
+            .map(|(range, run)| {
+                let style = TextStyle {
+                    font: PdfFont::new(
+                        run.bold || base.font == PdfFont::Bold,
+                        run.italic,
+                        run.code,
+                    ),
+                    size: if run.code { base.size * 0.9 } else { base.size },
+                    color: run.color.unwrap_or(if run.link.is_some() {
+                        self.theme.link
+                    } else {
+                        base.color
+                    }),
+                    underline: run.underline,
+                    strikethrough: run.strikethrough,
+                    code_background: run.code,
+                    link: run.link,
+                };
+                (text.contents[range].to_string(), style)
+            })
+            .collect()
+    }
+
+    fn text_block(&mut self, fragments: &[(String, TextStyle)], default_size: f32) {
+        if fragments.is_empty() {
+            return;
+        }
+        let x = MARGIN + self.indent;
+        for line in wrap_text(self.fonts, fragments, CONTENT_WIDTH - self.indent) {
+            let height = line_height(&line, default_size);
+            let top = self.advance(height);
+            self.draw_line(&line, x, top, height);
+        }
+    }
+
+    fn draw_line(&mut self, line: &[Piece], mut x: f32, top: f32, height: f32) {
+        let size = line.iter().map(|piece| piece.style.size).fold(0., f32::max);
+        let baseline = top + (height + size * 0.7) / 2.;
+        for piece in line {
+            let style = &piece.style;
+            if style.code_background {
+                let background_height = style.size * 1.3;
+                self.fill_rect(
+                    x - 1.,
+                    baseline - style.size * 0.95,
+                    piece.width + 2.,
+                    background_height,
+                    self.theme.code_span_background,
+                );
+            }
+            self.show_text(
+                x,
+                baseline,
+                &piece.text,
+                style.font,
+                style.size,
+                style.color,
+            );
+            let thickness = style.size * 0.06;
+            if style.underline {
+                self.fill_rect(
+                    x,
+                    baseline + style.size * 0.12,
+                    piece.width,
+                    thickness,
+                    style.color,
+                );
+            }
+            if style.strikethrough {
+                self.fill_rect(
+                    x,
+                    baseline - style.size * 0.3,
+                    piece.width,
+                    thickness,
+                    style.color,
+                );
+            }
+            // Footnote references point within the document, which an annotation can't express.
+            if let Some(link) = style.link.as_ref().filter(|link| !link.starts_with('#')) {
+                let rect = [
+                    x,
+                    PAGE_HEIGHT - top - height,
+                    x + piece.width,
+                    PAGE_HEIGHT - top,
+                ];
+                self.page().links.push((rect, link.clone()));
+            }
+            x += piece.width;
+        }
+    }
+
+    fn list_item(&mut self, item: &ParsedMarkdownListItem) {
+        if item.depth <= 1 {
+            self.list_base = self.indent;
+        }
+        let depth = item.depth.saturating_sub(1);
+        let marker_x = MARGIN + self.list_base + LIST_INDENT * f32::from(depth);
+        let height = BODY_SIZE * LINE_HEIGHT;
+        self.ensure(height);
+        let top = self.y;
+        let baseline = top + (height + BODY_SIZE * 0.7) / 2.;
+        let color = self.theme.text;
+        match &item.item_type {
+            ParsedMarkdownListItemType::Ordered(order) => {
+                let marker = format!("{order}.");
+                self.show_text(
+                    marker_x,
+                    baseline,
+                    &marker,
+                    PdfFont::Regular,
+                    BODY_SIZE,
+                    color,
+                );
+            }
+            ParsedMarkdownListItemType::Unordered => {
+                let marker = if depth % 2 == 0 { "•" } else { "–" };
+                self.show_text(
+                    marker_x,
+                    baseline,
+                    marker,
+                    PdfFont::Regular,
+                    BODY_SIZE,
+                    color,
+                );
+            }
+            ParsedMarkdownListItemType::Task(checked, _) => {
+                let box_size = BODY_SIZE * 0.8;
+                let box_top = baseline - box_size;
+                self.stroke_rect(marker_x, box_top, box_size, box_size, 0.8, color);
+                if *checked {
+                    self.stroke_polyline(
+                        &[
+                            (marker_x + box_size * 0.2, box_top + box_size * 0.5),
+                            (marker_x + box_size * 0.42, box_top + box_size * 0.75),
+                            (marker_x + box_size * 0.8, box_top + box_size * 0.22),
+                        ],
+                        1.2,
+                        color,
+                    );
+                }
+            }
+        }
+
+        let previous_indent = mem::replace(&mut self.indent, marker_x - MARGIN + LIST_INDENT);
+        for (ix, child) in item.content.iter().enumerate() {
+            match child {
+                // Paragraphs in list items are tight, so only separate them from each other.
+                ParsedMarkdownElement::Paragraph(paragraph) => {
+                    if ix > 0 {
+                        self.gap(BLOCK_GAP / 2.);
+                    }
+                    let style = self.body_style();
+                    self.paragraph(paragraph, &style);
+                }
+                child => self.element(child),
+            }
+        }
+        self.indent = previous_indent;
+        self.gap(2.);
+    }
+
+    fn table(&mut self, table: &ParsedMarkdownTable) {
+        let column_count = table
+            .header
+            .iter()
+            .chain(&table.body)
+            .map(|row| {
+                row.columns
+                    .iter()
+                    .map(|column| column.col_span.max(1))
+                    .sum::<usize>()
+            })
+            .max()
+            .unwrap_or(0);
+        if column_count == 0 {
+            return;
+        }
+        let left = MARGIN + self.indent;
+        let column_width = (CONTENT_WIDTH - self.indent) / column_count as f32;
+
+        for (row, in_header) in table
+            .header
+            .iter()
+            .map(|row| (row, true))
+            .chain(table.body.iter().map(|row| (row, false)))
+        {
+            let mut cells = Vec::new();
+            let mut x = left;
+            for column in &row.columns {
+                let width = column_width * column.col_span.max(1) as f32;
+                let is_header = in_header || column.is_header;
+                let style = TextStyle {
+                    font: if is_header {
+                        PdfFont::Bold
+                    } else {
+                        PdfFont::Regular
+                    },
+                    ..self.body_style()
+                };
+                let fragments = self.inline_fragments(&column.children, &style);
+                let lines = wrap_text(self.fonts, &fragments, width - 2. * CELL_PADDING);
+                cells.push((x, width, is_header, column.alignment, lines));
+                x += width;
+            }
+            let row_height = cells
+                .iter()
+                .map(|(.., lines)| {
+                    lines
+                        .iter()
+                        .map(|line| line_height(line, BODY_SIZE))
+                        .sum::<f32>()
+                })
+                .fold(BODY_SIZE * LINE_HEIGHT, f32::max)
+                + 2. * CELL_PADDING;
+
+            let top = self.advance(row_height);
+            for (x, width, is_header, alignment, lines) in cells {
+                if is_header {
+                    self.fill_rect(x, top, width, row_height, self.theme.surface_background);
+                }
+                self.stroke_rect(x, top, width, row_height, 0.5, self.theme.border);
+                let mut line_top = top + CELL_PADDING;
+                for line in lines {
+                    let height = line_height(&line, BODY_SIZE);
+                    let line_width: f32 = line.iter().map(|piece| piece.width).sum();
+                    let free = width - 2. * CELL_PADDING - line_width;
+                    let offset = match alignment {
+                        ParsedMarkdownTableAlignment::Center => free / 2.,
+                        ParsedMarkdownTableAlignment::Right => free,
+                        ParsedMarkdownTableAlignment::None | ParsedMarkdownTableAlignment::Left => {
+                            0.
+                        }
+                    };
+                    self.draw_line(&line, x + CELL_PADDING + offset, line_top, height);
+                    line_top += height;
+                }
+            }
+        }
+
+        if let Some(caption) = &table.caption {
+            let style = TextStyle {
+                color: self.theme.muted_text,
+                ..self.body_style()
+            };
+            self.gap(4.);
+            let fragments = self.inline_fragments(caption, &style);
+            self.text_block(&fragments, BODY_SIZE);
+        }
+    }
+
+    fn code_block(&mut self, code_block: &ParsedMarkdownCodeBlock) {
+        let runs = code_block_runs(code_block, &self.theme.syntax)
+            .into_iter()
+            .map(|(range, color)| (range, color.unwrap_or(self.theme.text)))
+            .collect::<Vec<_>>();
+        self.preformatted(&code_block.contents, &runs);
+    }
+
+    /// Writes monospace text on a shaded background, wrapping lines at the content width.
+    fn preformatted(&mut self, text: &str, runs: &[(Range<usize>, Hsla)]) {
+        let left = MARGIN + self.indent;
+        let width = CONTENT_WIDTH - self.indent;
+        let char_width = self.fonts.text_width(PdfFont::Mono, " ", CODE_SIZE);
+        let max_columns = (((width - 2. * CODE_PADDING) / char_width) as usize).max(1);
+        let style = TextStyle {
+            font: PdfFont::Mono,
+            size: CODE_SIZE,
+            ..self.body_style()
+        };
+
+        let mut lines = vec![Vec::new()];
+        let mut column = 0;
+        for (range, color) in runs {
+            let style = TextStyle {
+                color: *color,
+                ..style.clone()
+            };
+            for c in text[range.clone()].chars() {
+                if c == '\n' || column == max_columns {
+                    lines.push(Vec::new());
+                    column = 0;
+                    if c == '\n' {
+                        continue;
+                    }
+                }
+                let c = if c == '\t' { ' ' } else { c };
+                push_piece(
+                    self.fonts,
+                    lines.last_mut().unwrap(),
+                    c.encode_utf8(&mut [0; 4]),
+                    &style,
+                );
+                column += 1;
+            }
+        }
+        if lines.len() > 1 && lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+
+        let background = self.theme.surface_background;
+        let top = self.advance(CODE_PADDING);
+        self.fill_rect(left, top, width, CODE_PADDING, background);
+        for line in lines {
+            let height = CODE_SIZE * LINE_HEIGHT;
+            let top = self.advance(height);
+            self.fill_rect(left, top, width, height, background);
+            self.draw_line(&line, left + CODE_PADDING, top, height);
+        }
+        let top = self.advance(CODE_PADDING);
+        self.fill_rect(left, top, width, CODE_PADDING, background);
+    }
+
+    fn error_text(&mut self, message: String) {
+        let style = TextStyle {
+            color: self.theme.error,
+            ..self.body_style()
+        };
+        self.gap(4.);
+        self.text_block(&[(message, style)], BODY_SIZE);
+    }
+
+    fn math(&mut self, math: &ParsedMarkdownMath) {
+        match &math.node {
+            Ok(node) => {
+                let svg = render_math_svg(node, 20., self.theme.text);
+                let drawn = (self.rasterize_svg)(&svg.svg).is_some_and(|image| {
+                    self.draw_image(
+                        &image,
+                        svg.width * POINTS_PER_PIXEL,
+                        svg.height * POINTS_PER_PIXEL,
+                        true,
+                    )
+                });
+                if !drawn {
+                    let style = TextStyle {
+                        font: PdfFont::Italic,
+                        ..self.body_style()
+                    };
+                    self.text_block(&[(math_to_unicode(node), style)], BODY_SIZE);
+                }
+            }
+            Err(error) => {
+                let source = format!("$${}$$", math.source);
+                self.preformatted(&source, &[(0..source.len(), self.theme.text)]);
+                self.error_text(error.to_string());
+            }
+        }
+    }
+
+    fn mermaid(&mut self, mermaid: &ParsedMarkdownMermaid) {
+        let error = match &mermaid.diagram {
+            Ok(diagram) => {
+                let svg = render_mermaid_svg(diagram, &self.theme.mermaid_colors());
+                let drawn = (self.rasterize_svg)(&svg.svg).is_some_and(|image| {
+                    self.draw_image(
+                        &image,
+                        svg.width * POINTS_PER_PIXEL,
+                        svg.height * POINTS_PER_PIXEL,
+                        false,
+                    )
+                });
+                if drawn {
+                    return;
+                }
+                None
+            }
+            Err(error) => Some(error.to_string()),
+        };
+        let contents = &mermaid.contents;
+        self.preformatted(contents, &[(0..contents.len(), self.theme.text)]);
+        if let Some(error) = error {
+            self.error_text(error);
+        }
+    }
+
+    fn image(&mut self, image: &Image) {
+        if let Link::Path { path, .. } = &image.link
+            && let Some(decoded) = std::fs::read(path)
+                .ok()
+                .and_then(|bytes| image::load_from_memory(&bytes).ok())
+        {
+            let decoded = decoded.into_rgba8();
+            let natural_width = decoded.width() as f32 * POINTS_PER_PIXEL;
+            let natural_height = decoded.height() as f32 * POINTS_PER_PIXEL;
+            let available = CONTENT_WIDTH - self.indent;
+            let length = |length: Option<DefiniteLength>| match length? {
+                DefiniteLength::Absolute(length) => {
+                    Some(f32::from(length.to_pixels(BODY_SIZE.into())) * POINTS_PER_PIXEL)
+                }
+                DefiniteLength::Fraction(fraction) => Some(available * fraction),
+            };
+            let (width, height) = match (length(image.width), length(image.height)) {
+                (Some(width), Some(height)) => (width, height),
+                (Some(width), None) => (width, width * natural_height / natural_width),
+                (None, Some(height)) => (height * natural_width / natural_height, height),
+                (None, None) => (natural_width, natural_height),
+            };
+            if self.draw_image(&decoded, width, height, false) {
+                return;
+            }
+        }
+        let fragment = self.image_placeholder(image);
+        self.text_block(&[fragment], BODY_SIZE);
+    }
+
+    fn image_placeholder(&self, image: &Image) -> (String, TextStyle) {
+        let text = format!("[{}]", image.alt_text.as_deref().unwrap_or("image"));
+        let style = TextStyle {
+            color: self.theme.link,
+            underline: true,
+            link: Some(link_target(&image.link)),
+            ..self.body_style()
+        };
+        (text, style)
+    }
+
+    /// Embeds an image and draws it at the given size, scaled down to fit on a page.
+    fn draw_image(&mut self, image: &RgbaImage, width: f32, height: f32, centered: bool) -> bool {
+        if image.width() == 0 || image.height() == 0 || width <= 0. || height <= 0. {
+            return false;
+        }
+        let available_width = CONTENT_WIDTH - self.indent;
+        let available_height = PAGE_HEIGHT - 2. * MARGIN;
+        let scale = (available_width / width)
+            .min(available_height / height)
+            .min(1.);
+        let (width, height) = (width * scale, height * scale);
+
+        let background = self.theme.background.to_rgb();
+        let rgb = RgbImage::from_fn(image.width(), image.height(), |x, y| {
+            let [r, g, b, a] = image.get_pixel(x, y).0;
+            let alpha = a as f32 / 255.;
+            let blend = |channel: u8, background: f32| {
+                (channel as f32 * alpha + background * 255. * (1. - alpha)).round() as u8
+            };
+            image::Rgb([
+                blend(r, background.r),
+                blend(g, background.g),
+                blend(b, background.b),
+            ])
+        });
+        let mut jpeg = Vec::new();
+        if JpegEncoder::new_with_quality(&mut jpeg, 90)
+            .encode_image(&rgb)
+            .is_err()
+        {
+            return false;
+        }
+        let index = self.images.len();
+        self.images.push(PdfImage {
+            width: rgb.width(),
+            height: rgb.height(),
+            jpeg,
+        });
+
+        let top = self.advance(height);
+        let x = if centered {
+            MARGIN + self.indent + (available_width - width) / 2.
+        } else {
+            MARGIN + self.indent
+        };
+        writeln!(
+            self.page().content,
+            "q {width:.2} 0 0 {height:.2} {x:.2} {:.2} cm /Im{index} Do Q",
+            PAGE_HEIGHT - top - height
+        )
+        .ok();
+        true
+    }
+
+    fn page(&mut self) -> &mut PdfPage {
+        self.pages.last_mut().unwrap()
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(PdfPage {
+            content: String::new(),
+            links: Vec::new(),
+        });
+        self.y = MARGIN;
+        self.fill_rect(0., 0., PAGE_WIDTH, PAGE_HEIGHT, self.theme.background);
+    }
+
+    /// Starts a new page if a block of the given height doesn't fit on the current one.
+    fn ensure(&mut self, height: f32) {
+        if self.y + height > PAGE_HEIGHT - MARGIN && self.y > MARGIN {
+            self.new_page();
+        }
+    }
+
+    /// Reserves vertical space for a block, returning its top.
+    fn advance(&mut self, height: f32) -> f32 {
+        self.ensure(height);
+        let top = self.y;
+        self.draw_quote_bars(top, height);
+        self.y += height;
+        top
+    }
+
+    /// Adds space between blocks, which is dropped at the end of a page.
+    fn gap(&mut self, height: f32) {
+        if self.y + height > PAGE_HEIGHT - MARGIN {
+            return;
+        }
+        self.draw_quote_bars(self.y, height);
+        self.y += height;
+    }
+
+    fn draw_quote_bars(&mut self, top: f32, height: f32) {
+        for (x, color) in self.quote_bars.clone() {
+            self.fill_rect(x, top, 3., height, color);
+        }
+    }
+
+    /// The color as PDF color components, composited over the page background.
+    fn color_components(&self, color: Hsla) -> String {
+        let Rgba { r, g, b, a } = color.to_rgb();
+        let background = self.theme.background.to_rgb();
+        let blend = |channel: f32, background: f32| channel * a + background * (1. - a);
+        format!(
+            "{:.3} {:.3} {:.3}",
+            blend(r, background.r),
+            blend(g, background.g),
+            blend(b, background.b)
+        )
+    }
+
+    fn fill_rect(&mut self, x: f32, top: f32, width: f32, height: f32, color: Hsla) {
+        let color = self.color_components(color);
+        writeln!(
+            self.page().content,
+            "{color} rg {x:.2} {:.2} {width:.2} {height:.2} re f",
+            PAGE_HEIGHT - top - height
+        )
+        .ok();
+    }
+
+    fn stroke_rect(&mut self, x: f32, top: f32, width: f32, height: f32, line: f32, color: Hsla) {
+        let color = self.color_components(color);
+        writeln!(
+            self.page().content,
+            "{color} RG {line:.2} w {x:.2} {:.2} {width:.2} {height:.2} re S",
+            PAGE_HEIGHT - top - height
+        )
+        .ok();
+    }
+
+    fn stroke_polyline(&mut self, points: &[(f32, f32)], line: f32, color: Hsla) {
+        let color = self.color_components(color);
+        let content = &mut self.pages.last_mut().unwrap().content;
+        write!(content, "{color} RG {line:.2} w").ok();
+        for (ix, (x, y)) in points.iter().enumerate() {
+            let operator = if ix == 0 { "m" } else { "l" };
+            write!(content, " {x:.2} {:.2} {operator}", PAGE_HEIGHT - y).ok();
+        }
+        content.push_str(" S\n");
+    }
+
+    fn show_text(
+        &mut self,
+        x: f32,
+        baseline: f32,
+        text: &str,
+        font: PdfFont,
+        size: f32,
+        color: Hsla,
+    ) {
+        if text.is_empty() {
+            return;
+        }
+        let color = self.color_components(color);
+        let content = &mut self.pages.last_mut().unwrap().content;
+        write!(
+            content,
+            "BT /{} {size:.2} Tf {color} rg {x:.2} {:.2} Td <",
+            font.resource_name(),
+            PAGE_HEIGHT - baseline
+        )
+        .ok();
+        let font_data = self.fonts.get(font);
+        let used_glyphs = &mut self.used_glyphs[font as usize];
+        for c in text.chars() {
+            let glyph = font_data.glyph(c);
+            used_glyphs.entry(glyph).or_insert(c);
+            write!(content, "{glyph:04X}").ok();
+        }
+        content.push_str("> Tj ET\n");
+    }
+
+    /// Writes the document's objects, cross-reference table and trailer.
+    fn finish(self, title: &str) -> Result<Vec<u8>> {
+        const FIRST_FONT: usize = 4;
+        /// Each font is written as a composite font, its descendant font, font descriptor,
+        /// font file and a map from its glyphs to Unicode, for copying text.
+        const OBJECTS_PER_FONT: usize = 5;
+        let first_image = FIRST_FONT + OBJECTS_PER_FONT * PdfFont::ALL.len();
+        let first_page = first_image + self.images.len();
+
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        let mut page_ids = Vec::new();
+        let mut page_objects = Vec::new();
+        let mut next_id = first_page;
+        for page in &self.pages {
+            let page_id = next_id;
+            let content_id = page_id + 1;
+            let link_ids = content_id + 1..content_id + 1 + page.links.len();
+            next_id = link_ids.end;
+            page_ids.push(page_id);
+
+            let fonts = PdfFont::ALL
+                .iter()
+                .enumerate()
+                .map(|(ix, font)| {
+                    let id = FIRST_FONT + OBJECTS_PER_FONT * ix;
+                    format!("/{} {id} 0 R", font.resource_name())
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let images = (0..self.images.len())
+                .map(|ix| format!("/Im{ix} {} 0 R", first_image + ix))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let annotations = link_ids
+                .clone()
+                .map(|id| format!("{id} 0 R"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            page_objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                     /Resources << /Font << {fonts} >> /XObject << {images} >> >> \
+                     /Contents {content_id} 0 R /Annots [{annotations}] >>"
+                )
+                .into_bytes(),
+            );
+            page_objects.push(stream_object("", page.content.as_bytes()));
+            for ([x1, y1, x2, y2], uri) in &page.links {
+                page_objects.push(
+                    format!(
+                        "<< /Type /Annot /Subtype /Link /Rect [{x1:.2} {y1:.2} {x2:.2} {y2:.2}] \
+                         /Border [0 0 0] /A << /S /URI /URI ({}) >> >>",
+                        escape_pdf_string(uri)
+                    )
+                    .into_bytes(),
+                );
+            }
+        }
+
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        let kids = page_ids
+            .iter()
+            .map(|id| format!("{id} 0 R"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{kids}] /Count {} >>",
+                self.pages.len()
+            )
+            .into_bytes(),
+        );
+        let mut encoded_title = String::from("FEFF");
+        for unit in title.encode_utf16() {
+            write!(encoded_title, "{unit:04X}").ok();
+        }
+        objects.push(format!("<< /Title <{encoded_title}> /Producer (Zed) >>").into_bytes());
+        for (ix, font) in PdfFont::ALL.into_iter().enumerate() {
+            let id = FIRST_FONT + OBJECTS_PER_FONT * ix;
+            objects.extend(font_objects(self.fonts, font, &self.used_glyphs[ix], id)?);
+        }
+        for image in &self.images {
+            objects.push(stream_object(
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                     /BitsPerComponent 8 /Filter /DCTDecode",
+                    image.width, image.height
+                ),
+                &image.jpeg,
+            ));
+        }
+        objects.extend(page_objects);
+
+        let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (ix, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n", ix + 1).as_bytes());
+            pdf.extend_from_slice(object);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+        let xref_offset = pdf.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            writeln!(xref, "{offset:010} 00000 n ").ok();
+        }
+        write!(
+            xref,
+            "trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .ok();
+        pdf.extend_from_slice(xref.as_bytes());
+        Ok(pdf)
+    }
+}
+
+/// Writes the objects of an embedded font, starting with the composite font that pages refer
+/// to as `id`. Text is written as two byte glyph ids, so only the glyphs it uses are embedded.
+fn font_objects(
+    fonts: &PdfFonts,
+    kind: PdfFont,
+    used_glyphs: &BTreeMap<u16, char>,
+    id: usize,
+) -> Result<[Vec<u8>; 5]> {
+    let font = fonts.get(kind);
+    // Subset fonts are named with a tag that's unique within the document.
+    let name = format!(
+        "ZEDPD{}+{}",
+        (b'A' + kind as u8) as char,
+        font.postscript_name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>()
+    );
+    let subset = font.subset(used_glyphs.keys().copied())?;
+
+    let widths = used_glyphs
+        .keys()
+        .map(|glyph| format!("{glyph} [{}]", font.glyph_width(*glyph)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut flags = 4;
+    if kind == PdfFont::Mono {
+        flags |= 1;
+    }
+    if font.italic_angle != 0. {
+        flags |= 64;
+    }
+    let [x_min, y_min, x_max, y_max] = font.bbox;
+
+    // The missing glyph is used for many characters, so it can't be mapped to one of them.
+    let mapped_glyphs = used_glyphs
+        .iter()
+        .filter(|(glyph, _)| **glyph != 0)
+        .collect::<Vec<_>>();
+    let mut to_unicode = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    for chunk in mapped_glyphs.chunks(100) {
+        writeln!(to_unicode, "{} beginbfchar", chunk.len()).ok();
+        for (glyph, c) in chunk {
+            write!(to_unicode, "<{glyph:04X}> <").ok();
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                write!(to_unicode, "{unit:04X}").ok();
+            }
+            to_unicode.push_str(">\n");
+        }
+        to_unicode.push_str("endbfchar\n");
+    }
+    to_unicode.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+
+    Ok([
+        format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /{name} /Encoding /Identity-H \
+             /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+            id + 1,
+            id + 4
+        )
+        .into_bytes(),
+        format!(
+            "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{name} \
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+             /FontDescriptor {} 0 R /DW {} /W [{widths}] /CIDToGIDMap /Identity >>",
+            id + 2,
+            font.glyph_width(0)
+        )
+        .into_bytes(),
+        format!(
+            "<< /Type /FontDescriptor /FontName /{name} /Flags {flags} \
+             /FontBBox [{x_min} {y_min} {x_max} {y_max}] /ItalicAngle {} /Ascent {} \
+             /Descent {} /CapHeight {} /StemV 80 /FontFile2 {} 0 R >>",
+            font.italic_angle,
+            font.ascent,
+            font.descent,
+            font.cap_height,
+            id + 3
+        )
+        .into_bytes(),
+        stream_object(&format!("/Length1 {}", subset.len()), &subset),
+        stream_object("", to_unicode.as_bytes()),
+    ])
+}
+
+fn stream_object(dictionary: &str, data: &[u8]) -> Vec<u8> {
+    let mut object = format!("<< {dictionary} /Length {} >>\nstream\n", data.len()).into_bytes();
+    object.extend_from_slice(data);
+    object.extend_from_slice(b"\nendstream");
+    object
+}
+
+fn escape_pdf_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            c => {
+                for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                    write!(escaped, "%{byte:02X}").ok();
+                }
+            }
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{markdown_export::test_theme, markdown_parser::parse_markdown};
+    use assets::Assets;
+
+    fn test_fonts() -> PdfFonts {
+        PdfFonts::load(&Assets).unwrap()
+    }
+
+    fn object_offsets(pdf: &[u8]) -> Vec<usize> {
+        let text = String::from_utf8_lossy(pdf);
+        let xref = text.rfind("xref\n").unwrap();
+        text[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_export_pdf() {
+        let parsed = parse_markdown(
+            "# Notes\n\nSome **bold** text and [a link](https://zed.dev).\n\n- one\n- [x] two\n\n\
+             | a | b |\n|---|---|\n| 1 | 2 |\n\n```\nlet x = 1;\n```\n",
+            None,
+            None,
+        )
+        .await;
+        let pdf = export_pdf(&parsed, "Notes", &test_theme(), &test_fonts(), &|_| None).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Count 1"));
+        assert!(text.contains("/BaseFont /ZEDPDB+IBMPlexSans-Bold /Encoding /Identity-H"));
+        assert!(text.contains("/URI (https://zed.dev)"));
+        assert!(text.contains("/Title <FEFF004E006F007400650073>"));
+
+        let offsets = object_offsets(&pdf);
+        assert!(!offsets.is_empty());
+        for (ix, offset) in offsets.into_iter().enumerate() {
+            let header = format!("{} 0 obj\n", ix + 1);
+            assert_eq!(&pdf[offset..offset + header.len()], header.as_bytes());
+        }
+        let startxref = text.rfind("startxref\n").unwrap();
+        let xref_offset: usize = text[startxref + 10..]
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(text[xref_offset..].starts_with("xref\n"));
+    }
+
+    #[gpui::test]
+    async fn test_export_pdf_page_breaks() {
+        let markdown = (0..200)
+            .map(|ix| format!("Paragraph number {ix}, long enough to be worth a line of its own."))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let parsed = parse_markdown(&markdown, None, None).await;
+        let pdf = export_pdf(&parsed, "Long", &test_theme(), &test_fonts(), &|_| None).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        let count = text
+            .split("/Count ")
+            .nth(1)
+            .and_then(|rest| rest.split(' ').next())
+            .and_then(|count| count.parse::<usize>().ok())
+            .unwrap();
+        assert!(count > 5, "expected several pages, got {count}");
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        let fonts = test_fonts();
+        let style = TextStyle {
+            font: PdfFont::Regular,
+            size: 10.,
+            color: gpui::black(),
+            underline: false,
+            strikethrough: false,
+            code_background: false,
+            link: None,
+        };
+        let lines = wrap_text(&fonts, &[("aaa bbb ccc\nddd".into(), style.clone())], 40.);
+        let lines = lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|piece| piece.text.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, ["aaa bbb", "ccc", "ddd"]);
+
+        let lines = wrap_text(&fonts, &[("mmmmmmmmmm".into(), style)], 40.);
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line[0].width <= 40.));
+    }
+
+    #[gpui::test]
+    async fn test_export_pdf_unicode_text() {
+        let fonts = test_fonts();
+        assert_eq!(fonts.text_width(PdfFont::Mono, "Hello", 10.), 30.);
+
+        let parsed = parse_markdown("Ωμέγα, Привет and café\n", None, None).await;
+        let pdf = export_pdf(&parsed, "Unicode", &test_theme(), &fonts, &|_| None).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        // Every character is written as a glyph of the embedded font, and can be copied.
+        let regular = fonts.get(PdfFont::Regular);
+        for c in "ΩПé".chars() {
+            let glyph = regular.glyph(c);
+            assert_ne!(glyph, 0, "no glyph for {c:?}");
+            assert!(text.contains(&format!("<{glyph:04X}> <{:04X}>", c as u32)));
+        }
+        assert!(text.contains("/Subtype /CIDFontType2"));
+        assert!(text.contains("/FontFile2 "));
+    }
+}
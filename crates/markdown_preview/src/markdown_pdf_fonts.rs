@@ -0,0 +1,274 @@
+//! TrueType fonts embedded in exported PDFs.
+//!
+//! Text is written as glyph ids, so any character the font has a glyph for can be shown, and
+//! only the glyphs a document uses are embedded.
+
+use std::{borrow::Cow, collections::BTreeSet, ops::Range};
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use ttf_parser::{Face, Tag};
+
+/// Tables of the font that are needed to draw its glyphs. The others, such as `cmap`, are
+/// only used to look up glyphs, which the PDF does itself.
+const SUBSET_TABLES: [&[u8; 4]; 6] = [b"cvt ", b"fpgm", b"hhea", b"hmtx", b"maxp", b"prep"];
+
+pub struct TrueTypeFont {
+    data: Cow<'static, [u8]>,
+    pub postscript_name: String,
+    units_per_em: f32,
+    /// The font's ascent, descent, cap height and bounding box, scaled to 1000 units per em.
+    pub ascent: i32,
+    pub descent: i32,
+    pub cap_height: i32,
+    pub bbox: [i32; 4],
+    pub italic_angle: f32,
+    /// The glyph id of every character the font maps.
+    glyphs: HashMap<char, u16>,
+    /// The advance width of every glyph, in font units.
+    advances: Vec<u16>,
+}
+
+impl TrueTypeFont {
+    pub fn parse(data: Cow<'static, [u8]>) -> Result<Self> {
+        let face = Face::parse(&data, 0).context("Invalid font")?;
+        anyhow::ensure!(
+            face.tables().glyf.is_some(),
+            "Only fonts with TrueType outlines can be embedded"
+        );
+
+        let mut glyphs = HashMap::default();
+        for subtable in face
+            .tables()
+            .cmap
+            .into_iter()
+            .flat_map(|cmap| cmap.subtables)
+            .filter(|subtable| subtable.is_unicode())
+        {
+            subtable.codepoints(|codepoint| {
+                if let Some(c) = char::from_u32(codepoint)
+                    && let Some(glyph) = subtable.glyph_index(codepoint)
+                {
+                    glyphs.entry(c).or_insert(glyph.0);
+                }
+            });
+        }
+
+        let units_per_em = face.units_per_em() as f32;
+        let scale = |value: i16| (value as f32 * 1000. / units_per_em).round() as i32;
+        let bbox = face.global_bounding_box();
+        let postscript_name = face
+            .names()
+            .into_iter()
+            .filter(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+            .filter_map(|name| name.to_string())
+            .find(|name| !name.is_empty())
+            .unwrap_or_else(|| "Font".to_string());
+        let advances = (0..face.number_of_glyphs())
+            .map(|glyph| {
+                face.glyph_hor_advance(ttf_parser::GlyphId(glyph))
+                    .unwrap_or(0)
+            })
+            .collect();
+        Ok(Self {
+            postscript_name,
+            units_per_em,
+            ascent: scale(face.ascender()),
+            descent: scale(face.descender()),
+            cap_height: scale(face.capital_height().unwrap_or(face.ascender())),
+            bbox: [
+                scale(bbox.x_min),
+                scale(bbox.y_min),
+                scale(bbox.x_max),
+                scale(bbox.y_max),
+            ],
+            italic_angle: face.italic_angle(),
+            glyphs,
+            advances,
+            data,
+        })
+    }
+
+    /// The glyph id for a character, which is the missing glyph box when the font has none.
+    /// Tabs are drawn as spaces.
+    pub fn glyph(&self, c: char) -> u16 {
+        let c = if c == '\t' { ' ' } else { c };
+        self.glyphs.get(&c).copied().unwrap_or(0)
+    }
+
+    /// The advance width of a glyph, scaled to 1000 units per em.
+    pub fn glyph_width(&self, glyph: u16) -> u32 {
+        (self.advance(glyph) as f32 * 1000. / self.units_per_em).round() as u32
+    }
+
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = text
+            .chars()
+            .map(|c| self.advance(self.glyph(c)) as u32)
+            .sum();
+        units as f32 * size / self.units_per_em
+    }
+
+    fn advance(&self, glyph: u16) -> u16 {
+        self.advances.get(glyph as usize).copied().unwrap_or(0)
+    }
+
+    /// Builds a copy of the font in which every glyph is empty, except for `glyphs` and the
+    /// glyphs they're composed of. Glyph ids are unchanged, so text can keep using them.
+    pub fn subset(&self, glyphs: impl IntoIterator<Item = u16>) -> Result<Vec<u8>> {
+        let face = Face::parse(&self.data, 0).context("Invalid font")?;
+        let raw_face = face.raw_face();
+        let table = |tag: &[u8; 4]| raw_face.table(Tag::from_bytes(tag));
+        let head = table(b"head").context("Font has no head table")?;
+        let glyf = table(b"glyf").context("Font has no glyf table")?;
+        let loca = table(b"loca").context("Font has no loca table")?;
+        anyhow::ensure!(head.len() >= 54, "Font has an invalid head table");
+        let long_offsets = i16::from_be_bytes([head[50], head[51]]) != 0;
+        let glyph_count = face.number_of_glyphs();
+
+        let glyph_range = |glyph: u16| -> Option<Range<usize>> {
+            let (start, end) = if long_offsets {
+                let offset = |ix: usize| {
+                    let bytes = loca.get(ix * 4..ix * 4 + 4)?;
+                    Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+                };
+                (offset(glyph as usize)?, offset(glyph as usize + 1)?)
+            } else {
+                let offset = |ix: usize| {
+                    let bytes = loca.get(ix * 2..ix * 2 + 2)?;
+                    Some(u16::from_be_bytes(bytes.try_into().ok()?) as usize * 2)
+                };
+                (offset(glyph as usize)?, offset(glyph as usize + 1)?)
+            };
+            (start < end && end <= glyf.len()).then_some(start..end)
+        };
+
+        // The missing glyph has to be kept, since it's drawn for any character the font lacks.
+        let mut pending = glyphs.into_iter().chain([0]).collect::<Vec<_>>();
+        let mut kept = BTreeSet::new();
+        while let Some(glyph) = pending.pop() {
+            if glyph >= glyph_count || !kept.insert(glyph) {
+                continue;
+            }
+            if let Some(range) = glyph_range(glyph) {
+                pending.extend(composite_glyph_components(&glyf[range]));
+            }
+        }
+
+        let mut subset_glyf = Vec::new();
+        let mut subset_loca = Vec::with_capacity((glyph_count as usize + 1) * 4);
+        for glyph in 0..glyph_count {
+            subset_loca.extend_from_slice(&(subset_glyf.len() as u32).to_be_bytes());
+            if kept.contains(&glyph)
+                && let Some(range) = glyph_range(glyph)
+            {
+                subset_glyf.extend_from_slice(&glyf[range]);
+                subset_glyf.resize(subset_glyf.len().next_multiple_of(4), 0);
+            }
+        }
+        subset_loca.extend_from_slice(&(subset_glyf.len() as u32).to_be_bytes());
+
+        // The offsets are always written in the long format, and the checksum adjustment is
+        // filled in once the whole font has been written.
+        let mut subset_head = head.to_vec();
+        subset_head[8..12].fill(0);
+        subset_head[50..52].copy_from_slice(&1i16.to_be_bytes());
+
+        let mut tables = vec![
+            (*b"glyf", Cow::Owned(subset_glyf)),
+            (*b"head", Cow::Owned(subset_head)),
+            (*b"loca", Cow::Owned(subset_loca)),
+        ];
+        for tag in SUBSET_TABLES {
+            if let Some(data) = table(tag) {
+                tables.push((*tag, Cow::Borrowed(data)));
+            }
+        }
+        tables.sort_by_key(|(tag, _)| *tag);
+        Ok(write_font(&tables))
+    }
+}
+
+/// The glyphs a composite glyph is made of. Simple glyphs have none.
+fn composite_glyph_components(glyph: &[u8]) -> Vec<u16> {
+    const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+    const WE_HAVE_A_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+    const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+    let mut components = Vec::new();
+    let is_composite = glyph
+        .get(..2)
+        .is_some_and(|contours| i16::from_be_bytes([contours[0], contours[1]]) < 0);
+    if !is_composite {
+        return components;
+    }
+    // Components follow the glyph header with the contour count and bounding box.
+    let mut offset = 10;
+    while let Some(component) = glyph.get(offset..offset + 4) {
+        let flags = u16::from_be_bytes([component[0], component[1]]);
+        components.push(u16::from_be_bytes([component[2], component[3]]));
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            8
+        } else {
+            6
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    components
+}
+
+/// Writes a TrueType font file from its tables, which must be sorted by tag.
+fn write_font(tables: &[([u8; 4], Cow<[u8]>)]) -> Vec<u8> {
+    let table_count = tables.len() as u16;
+    let entry_selector = table_count.max(1).ilog2() as u16;
+    let search_range = 16u16 << entry_selector;
+
+    let mut font = Vec::new();
+    font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    font.extend_from_slice(&table_count.to_be_bytes());
+    font.extend_from_slice(&search_range.to_be_bytes());
+    font.extend_from_slice(&entry_selector.to_be_bytes());
+    font.extend_from_slice(&(table_count * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = None;
+    for (tag, data) in tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in tables {
+        font.extend_from_slice(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
@@ -2,8 +2,13 @@ use gpui::{App, actions};
 use workspace::Workspace;
 
 pub mod markdown_elements;
+pub mod markdown_export;
+pub mod markdown_math;
+pub mod markdown_mermaid;
 mod markdown_minifier;
 pub mod markdown_parser;
+pub mod markdown_pdf;
+mod markdown_pdf_fonts;
 pub mod markdown_preview_view;
 pub mod markdown_renderer;
 
@@ -29,7 +34,11 @@ actions!(
         /// Opens a markdown preview in a split pane.
         OpenPreviewToTheSide,
         /// Opens a following markdown preview that syncs with the editor.
-        OpenFollowingPreview
+        OpenFollowingPreview,
+        /// Exports the markdown preview as a standalone HTML file.
+        ExportToHtml,
+        /// Exports the markdown preview as a PDF file.
+        ExportToPdf
     ]
 );
 
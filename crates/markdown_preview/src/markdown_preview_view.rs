@@ -3,27 +3,33 @@ use std::sync::Arc;
 use std::time::Duration;
 use std::{ops::Range, path::PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use editor::scroll::Autoscroll;
 use editor::{EditType, Editor, EditorEvent, MultiBufferOffset, SelectionEffects};
 use gpui::{
     App, ClickEvent, Context, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
-    IntoElement, IsZero, ListState, ParentElement, Render, RetainAllImageCache, Styled,
-    Subscription, Task, WeakEntity, Window, list,
+    IntoElement, IsZero, ListOffset, ListState, ParentElement, Render, RetainAllImageCache, Styled,
+    Subscription, SvgRenderer, Task, WeakEntity, Window, list,
 };
+use image::RgbaImage;
 use language::LanguageRegistry;
 use settings::Settings;
 use theme::ThemeSettings;
 use ui::{WithScrollbar, prelude::*};
+use util::{ResultExt as _, paths};
 use workspace::item::{Item, ItemHandle};
-use workspace::{Pane, Workspace};
+use workspace::notifications::{DetachAndPromptErr as _, NotificationId};
+use workspace::{Pane, Toast, Workspace};
 
 use crate::markdown_elements::ParsedMarkdownElement;
-use crate::markdown_renderer::CheckboxClickedEvent;
+use crate::markdown_renderer::{CheckboxClickedEvent, FootnoteClickedEvent};
 use crate::{
-    OpenFollowingPreview, OpenPreview, OpenPreviewToTheSide, ScrollPageDown, ScrollPageUp,
+    ExportToHtml, ExportToPdf, OpenFollowingPreview, OpenPreview, OpenPreviewToTheSide,
+    ScrollPageDown, ScrollPageUp,
     markdown_elements::ParsedMarkdown,
+    markdown_export::{ExportTheme, export_html},
     markdown_parser::parse_markdown,
+    markdown_pdf::{PdfFonts, export_pdf},
     markdown_renderer::{RenderContext, render_markdown_block},
 };
 use crate::{ScrollDown, ScrollDownByItem, ScrollUp, ScrollUpByItem};
@@ -388,6 +394,28 @@ impl MarkdownPreviewView {
         }
     }
 
+    /// Scrolls a footnote's definition, or the first reference to it, to the top of the preview.
+    fn scroll_to_footnote(&mut self, event: &FootnoteClickedEvent, cx: &mut Context<Self>) {
+        let Some(contents) = &self.contents else {
+            return;
+        };
+        let target = contents.children.iter().position(|block| match event {
+            FootnoteClickedEvent::Definition { label } => match block {
+                ParsedMarkdownElement::FootnoteDefinition(footnote) => &footnote.label == label,
+                _ => false,
+            },
+            FootnoteClickedEvent::Reference { label } => block.references_footnote(label),
+        });
+        if let Some(ix) = target {
+            self.selected_block = ix;
+            self.list_state.scroll_to(ListOffset {
+                item_ix: ix,
+                offset_in_item: px(0.),
+            });
+            cx.notify();
+        }
+    }
+
     fn get_block_index_under_cursor(&self, selection_range: Range<MultiBufferOffset>) -> usize {
         let mut block_index = None;
         let cursor = selection_range.start.0;
@@ -501,6 +529,156 @@ impl MarkdownPreviewView {
         }
         cx.notify();
     }
+
+    fn export_to_html(&mut self, _: &ExportToHtml, window: &mut Window, cx: &mut Context<Self>) {
+        self.export(ExportFormat::Html, window, cx);
+    }
+
+    fn export_to_pdf(&mut self, _: &ExportToPdf, window: &mut Window, cx: &mut Context<Self>) {
+        self.export(ExportFormat::Pdf, window, cx);
+    }
+
+    /// Asks where to save the previewed document, then writes it in the given format, styled
+    /// with the current theme.
+    fn export(&mut self, format: ExportFormat, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(editor) = self
+            .active_editor
+            .as_ref()
+            .map(|state| state.editor.clone())
+        else {
+            return;
+        };
+        let Some(fs) = self
+            .workspace
+            .read_with(cx, |workspace, _| workspace.app_state().fs.clone())
+            .ok()
+        else {
+            return;
+        };
+        let workspace = self.workspace.clone();
+        let language_registry = self.language_registry.clone();
+        let (contents, file_location, title) = {
+            let editor = editor.read(cx);
+            let contents = editor.buffer().read(cx).snapshot(cx).text();
+            let file_location = Self::get_folder_for_active_editor(editor, cx);
+            let title = editor
+                .buffer()
+                .read(cx)
+                .as_singleton()
+                .and_then(|buffer| {
+                    let file = buffer.read(cx).file()?;
+                    let stem = file.path().file_stem()?;
+                    Some(stem.to_string())
+                })
+                .unwrap_or_else(|| "Untitled".to_string());
+            (contents, file_location, title)
+        };
+        let theme = export_theme(cx);
+        let svg_renderer = cx.svg_renderer();
+        let asset_source = cx.asset_source().clone();
+
+        let directory = file_location
+            .clone()
+            .unwrap_or_else(|| paths::home_dir().clone());
+        let suggested_name = format!("{title}.{}", format.extension());
+        let save_path = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+        cx.spawn_in(window, async move |_, cx| {
+            let Some(path) = save_path.await?? else {
+                return Ok(());
+            };
+            let output = cx
+                .background_spawn(async move {
+                    let parsed =
+                        parse_markdown(&contents, file_location, Some(language_registry)).await;
+                    anyhow::Ok(match format {
+                        ExportFormat::Html => export_html(&parsed, &title, &theme).into_bytes(),
+                        ExportFormat::Pdf => {
+                            let fonts = PdfFonts::load(asset_source.as_ref())?;
+                            export_pdf(&parsed, &title, &theme, &fonts, &|svg| {
+                                rasterize_svg(&svg_renderer, svg)
+                            })?
+                        }
+                    })
+                })
+                .await?;
+            fs.write(&path, &output)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            workspace.update(cx, |workspace, cx| {
+                workspace.show_toast(
+                    Toast::new(
+                        NotificationId::unique::<MarkdownPreviewView>(),
+                        format!("Exported the preview to {}", path.display()),
+                    )
+                    .autohide(),
+                    cx,
+                );
+            })
+        })
+        .detach_and_prompt_err(
+            "Failed to export the preview",
+            window,
+            cx,
+            |error, _, _| Some(format!("{error:#}")),
+        );
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    Html,
+    Pdf,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+fn export_theme(cx: &App) -> ExportTheme {
+    let theme = cx.theme();
+    let settings = ThemeSettings::get_global(cx);
+    ExportTheme {
+        text: theme.colors().text,
+        muted_text: theme.colors().text_muted,
+        background: theme.colors().editor_background,
+        surface_background: theme.colors().surface_background,
+        code_span_background: theme.colors().editor_document_highlight_read_background,
+        border: theme.colors().border,
+        link: theme.colors().text_accent,
+        info: theme.status().info,
+        success: theme.status().success,
+        hint: theme.status().hint,
+        warning: theme.status().warning,
+        error: theme.status().error,
+        syntax: theme.syntax().clone(),
+        ui_font_family: settings.ui_font.family.to_string(),
+        buffer_font_family: settings.buffer_font.family.to_string(),
+    }
+}
+
+/// Renders an SVG to unpremultiplied RGBA pixels. The renderer always rasterizes at
+/// [`gpui::SMOOTH_SVG_SCALE_FACTOR`], twice the SVG's size, which keeps it sharp when printed,
+/// so no further scale is applied.
+fn rasterize_svg(svg_renderer: &SvgRenderer, svg: &str) -> Option<RgbaImage> {
+    let image = svg_renderer
+        .render_single_frame(svg.as_bytes(), 1., false)
+        .log_err()?;
+    let size = image.size(0);
+    let mut pixels = image.as_bytes(0)?.to_vec();
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha > 0 {
+            for channel in &mut pixel[..3] {
+                *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+            }
+        }
+    }
+    RgbaImage::from_raw(size.width.0 as u32, size.height.0 as u32, pixels)
 }
 
 impl Focusable for MarkdownPreviewView {
@@ -557,6 +735,8 @@ impl Render for MarkdownPreviewView {
             .on_action(cx.listener(MarkdownPreviewView::scroll_down))
             .on_action(cx.listener(MarkdownPreviewView::scroll_up_by_item))
             .on_action(cx.listener(MarkdownPreviewView::scroll_down_by_item))
+            .on_action(cx.listener(MarkdownPreviewView::export_to_html))
+            .on_action(cx.listener(MarkdownPreviewView::export_to_pdf))
             .size_full()
             .bg(cx.theme().colors().editor_background)
             .p_4()
@@ -604,6 +784,11 @@ impl Render for MarkdownPreviewView {
                                                 cx.notify();
                                             }
                                         },
+                                    ))
+                                    .with_footnote_clicked_callback(cx.listener(
+                                        |this, event: &FootnoteClickedEvent, _, cx| {
+                                            this.scroll_to_footnote(event, cx);
+                                        },
                                     ));
 
                             let block = contents.children.get(ix).unwrap();
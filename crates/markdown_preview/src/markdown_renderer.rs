@@ -1,15 +1,21 @@
-use crate::markdown_elements::{
-    HeadingLevel, Image, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
-    ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock, ParsedMarkdownElement,
-    ParsedMarkdownHeading, ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownTable,
-    ParsedMarkdownTableAlignment, ParsedMarkdownTableRow,
+use crate::{
+    markdown_elements::{
+        HeadingLevel, Image, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
+        ParsedMarkdownAlertKind, ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock,
+        ParsedMarkdownDefinitionList, ParsedMarkdownElement, ParsedMarkdownFootnoteDefinition,
+        ParsedMarkdownHeading, ParsedMarkdownListItem, ParsedMarkdownListItemType,
+        ParsedMarkdownMath, ParsedMarkdownMermaid, ParsedMarkdownTable,
+        ParsedMarkdownTableAlignment, ParsedMarkdownTableRow,
+    },
+    markdown_math::MathElement,
+    markdown_mermaid::{MermaidColors, render_mermaid_svg},
 };
 use fs::normalize_path;
 use gpui::{
     AbsoluteLength, AnyElement, App, AppContext as _, Context, Div, Element, ElementId, Entity,
-    HighlightStyle, Hsla, ImageSource, InteractiveText, IntoElement, Keystroke, Modifiers,
-    ParentElement, Render, Resource, SharedString, Styled, StyledText, TextStyle, WeakEntity,
-    Window, div, img, px, rems,
+    FontWeight, HighlightStyle, Hsla, ImageSource, InteractiveText, IntoElement, Keystroke,
+    Modifiers, ParentElement, Render, Resource, SharedString, Styled, StyledText, TextStyle,
+    WeakEntity, Window, div, img, px, rems,
 };
 use settings::Settings;
 use std::{
@@ -18,7 +24,7 @@ use std::{
     vec,
 };
 use theme::{ActiveTheme, SyntaxTheme, ThemeSettings};
-use ui::{CopyButton, LinkPreview, ToggleState, prelude::*, tooltip_container};
+use ui::{CopyButton, LinkPreview, ToggleState, Tooltip, prelude::*, tooltip_container};
 use workspace::{OpenOptions, OpenVisible, Workspace};
 
 pub struct CheckboxClickedEvent {
@@ -38,6 +44,16 @@ impl CheckboxClickedEvent {
 
 type CheckboxClickedCallback = Arc<Box<dyn Fn(&CheckboxClickedEvent, &mut Window, &mut App)>>;
 
+/// Where clicking a footnote link should take the reader.
+pub enum FootnoteClickedEvent {
+    /// From a reference to the footnote's definition.
+    Definition { label: SharedString },
+    /// From a footnote's definition back to where it's first referenced.
+    Reference { label: SharedString },
+}
+
+type FootnoteClickedCallback = Arc<dyn Fn(&FootnoteClickedEvent, &mut Window, &mut App)>;
+
 #[derive(Clone)]
 pub struct RenderContext {
     workspace: Option<WeakEntity<Workspace>>,
@@ -52,11 +68,17 @@ pub struct RenderContext {
     link_color: Hsla,
     window_rem_size: Pixels,
     text_muted_color: Hsla,
+    error_color: Hsla,
+    warning_color: Hsla,
+    info_color: Hsla,
+    hint_color: Hsla,
+    success_color: Hsla,
     code_block_background_color: Hsla,
     code_span_background_color: Hsla,
     syntax_theme: Arc<SyntaxTheme>,
     indent: usize,
     checkbox_clicked_callback: Option<CheckboxClickedCallback>,
+    footnote_clicked_callback: Option<FootnoteClickedCallback>,
     is_last_child: bool,
 }
 
@@ -91,9 +113,15 @@ impl RenderContext {
             link_color: theme.colors().text_accent,
             window_rem_size: window.rem_size(),
             text_muted_color: theme.colors().text_muted,
+            error_color: theme.status().error,
+            warning_color: theme.status().warning,
+            info_color: theme.status().info,
+            hint_color: theme.status().hint,
+            success_color: theme.status().success,
             code_block_background_color: theme.colors().surface_background,
             code_span_background_color: theme.colors().editor_document_highlight_read_background,
             checkbox_clicked_callback: None,
+            footnote_clicked_callback: None,
             is_last_child: false,
         }
     }
@@ -106,6 +134,24 @@ impl RenderContext {
         self
     }
 
+    pub fn with_footnote_clicked_callback(
+        mut self,
+        callback: impl Fn(&FootnoteClickedEvent, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.footnote_clicked_callback = Some(Arc::new(callback));
+        self
+    }
+
+    fn alert_color(&self, kind: ParsedMarkdownAlertKind) -> Hsla {
+        match kind {
+            ParsedMarkdownAlertKind::Note => self.info_color,
+            ParsedMarkdownAlertKind::Tip => self.success_color,
+            ParsedMarkdownAlertKind::Important => self.hint_color,
+            ParsedMarkdownAlertKind::Warning => self.warning_color,
+            ParsedMarkdownAlertKind::Caution => self.error_color,
+        }
+    }
+
     fn next_id(&mut self, span: &Range<usize>) -> ElementId {
         let id = format!("markdown-{}-{}-{}", self.next_id, span.start, span.end);
         self.next_id += 1;
@@ -181,8 +227,11 @@ pub fn render_markdown_block(block: &ParsedMarkdownElement, cx: &mut RenderConte
         Table(table) => render_markdown_table(table, cx),
         BlockQuote(block_quote) => render_markdown_block_quote(block_quote, cx),
         CodeBlock(code_block) => render_markdown_code_block(code_block, cx),
+        Mermaid(mermaid) => render_markdown_mermaid(mermaid, cx),
         HorizontalRule(_) => render_markdown_rule(cx),
         Image(image) => render_markdown_image(image, cx),
+        DefinitionList(list) => render_markdown_definition_list(list, cx),
+        FootnoteDefinition(footnote) => render_markdown_footnote_definition(footnote, cx),
     }
 }
 
@@ -260,24 +309,21 @@ fn render_markdown_list_item(
                                     _ => return,
                                 };
 
-                                if window.modifiers().secondary() {
-                                    callback(
-                                        &CheckboxClickedEvent {
-                                            checked,
-                                            source_range: range.clone(),
-                                        },
-                                        window,
-                                        cx,
-                                    );
-                                }
+                                callback(
+                                    &CheckboxClickedEvent {
+                                        checked,
+                                        source_range: range.clone(),
+                                    },
+                                    window,
+                                    cx,
+                                );
                             }
                         })
                     },
                 ),
             )
-            .hover(|s| s.cursor_pointer())
-            .tooltip(|_, cx| {
-                InteractiveMarkdownElementTooltip::new(None, "toggle checkbox", cx).into()
+            .when(cx.checkbox_clicked_callback.is_some(), |this| {
+                this.cursor_pointer().tooltip(Tooltip::text("Toggle Task"))
             })
             .into_any_element(),
     };
@@ -593,17 +639,130 @@ fn render_markdown_block_quote(
 
     cx.indent -= 1;
 
+    let border_color = parsed
+        .alert
+        .map_or(cx.border_color, |kind| cx.alert_color(kind));
+    let title = parsed.alert.map(|kind| {
+        let icon = match kind {
+            ParsedMarkdownAlertKind::Note => IconName::Info,
+            ParsedMarkdownAlertKind::Tip => IconName::Sparkle,
+            ParsedMarkdownAlertKind::Important => IconName::BellRing,
+            ParsedMarkdownAlertKind::Warning => IconName::Warning,
+            ParsedMarkdownAlertKind::Caution => IconName::XCircle,
+        };
+        h_flex()
+            .gap(cx.scaled_rems(0.5))
+            .pb(cx.scaled_rems(0.25))
+            .text_color(border_color)
+            .font_weight(FontWeight::SEMIBOLD)
+            .child(
+                Icon::new(icon)
+                    .size(IconSize::Custom(cx.scaled_rems(1.)))
+                    .color(Color::Custom(border_color)),
+            )
+            .child(kind.title())
+    });
+
     cx.with_common_p(div())
         .child(
             div()
                 .border_l_4()
-                .border_color(cx.border_color)
+                .border_color(border_color)
                 .pl_3()
+                .children(title)
                 .children(children),
         )
         .into_any()
 }
 
+fn render_markdown_definition_list(
+    parsed: &ParsedMarkdownDefinitionList,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    let mut items = Vec::with_capacity(parsed.items.len());
+    for item in &parsed.items {
+        let term = div()
+            .font_weight(FontWeight::SEMIBOLD)
+            .children(render_markdown_text(&item.term, cx));
+
+        cx.indent += 1;
+        let definitions: Vec<AnyElement> = item
+            .definitions
+            .iter()
+            .map(|definition| {
+                let blocks: Vec<AnyElement> = definition
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, block)| {
+                        cx.with_last_child(ix + 1 == definition.len(), |cx| {
+                            render_markdown_block(block, cx)
+                        })
+                    })
+                    .collect();
+                v_flex()
+                    .pl(cx.scaled_rems(1.5))
+                    .children(blocks)
+                    .into_any_element()
+            })
+            .collect();
+        cx.indent -= 1;
+
+        items.push(v_flex().child(term).children(definitions));
+    }
+
+    cx.with_common_p(v_flex())
+        .gap(cx.scaled_rems(0.5))
+        .children(items)
+        .into_any()
+}
+
+fn render_markdown_footnote_definition(
+    parsed: &ParsedMarkdownFootnoteDefinition,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    cx.indent += 1;
+    let children: Vec<AnyElement> = parsed
+        .children
+        .iter()
+        .enumerate()
+        .map(|(ix, child)| {
+            cx.with_last_child(ix + 1 == parsed.children.len(), |cx| {
+                render_markdown_block(child, cx)
+            })
+        })
+        .collect();
+    cx.indent -= 1;
+
+    let back_link = cx.footnote_clicked_callback.clone().map(|callback| {
+        let label = parsed.label.clone();
+        div()
+            .id(cx.next_id(&parsed.source_range))
+            .text_color(cx.link_color)
+            .cursor_pointer()
+            .child("↩")
+            .tooltip(Tooltip::text("Back to reference"))
+            .on_click(move |_, window, cx| {
+                callback(
+                    &FootnoteClickedEvent::Reference {
+                        label: label.clone(),
+                    },
+                    window,
+                    cx,
+                )
+            })
+    });
+
+    h_flex()
+        .items_start()
+        .gap(cx.scaled_rems(0.5))
+        .text_size(cx.scaled_rems(0.875))
+        .text_color(cx.text_muted_color)
+        .child(format!("{}.", parsed.number))
+        .child(div().flex_1().children(children))
+        .children(back_link)
+        .into_any()
+}
+
 fn render_markdown_code_block(
     parsed: &ParsedMarkdownCodeBlock,
     cx: &mut RenderContext,
@@ -649,6 +808,86 @@ fn render_markdown_code_block(
         .into_any()
 }
 
+fn render_markdown_mermaid(parsed: &ParsedMarkdownMermaid, cx: &mut RenderContext) -> AnyElement {
+    let diagram = match &parsed.diagram {
+        Ok(diagram) => diagram,
+        Err(error) => {
+            return render_source_with_error(&parsed.contents, error.to_string(), cx);
+        }
+    };
+
+    let colors = MermaidColors {
+        text: cx.text_color,
+        border: cx.text_muted_color,
+        background: cx.code_block_background_color,
+        note_background: cx.title_bar_background_color,
+    };
+    let svg = render_mermaid_svg(diagram, &colors);
+    let image = gpui::Image::from_bytes(gpui::ImageFormat::Svg, svg.svg.into_bytes());
+
+    cx.with_common_p(div())
+        .w_full()
+        .overflow_hidden()
+        .child(
+            img(ImageSource::Image(Arc::new(image)))
+                .w(px(svg.width))
+                .h(px(svg.height))
+                .max_w_full()
+                .with_fallback({
+                    let contents = parsed.contents.clone();
+                    move || div().child(contents.clone()).into_any_element()
+                }),
+        )
+        .into_any()
+}
+
+fn render_markdown_math(parsed: &ParsedMarkdownMath, cx: &mut RenderContext) -> AnyElement {
+    let node = match &parsed.node {
+        Ok(node) => node.clone(),
+        Err(error) => {
+            return render_source_with_error(
+                &format!("$${}$$", parsed.source),
+                error.to_string(),
+                cx,
+            );
+        }
+    };
+
+    let font_size = cx.text_style.font_size.to_pixels(cx.window_rem_size) * 1.2;
+    div()
+        .w_full()
+        .py_1()
+        .flex()
+        .justify_center()
+        .overflow_hidden()
+        .child(MathElement::new(
+            node,
+            cx.text_style.font(),
+            font_size,
+            cx.text_color,
+        ))
+        .into_any()
+}
+
+/// Shows the source of a math formula or diagram that couldn't be parsed, along with why.
+fn render_source_with_error(source: &str, error: String, cx: &mut RenderContext) -> AnyElement {
+    let font = gpui::Font {
+        family: cx.buffer_font_family.clone(),
+        features: cx.buffer_text_style.font_features.clone(),
+        ..Default::default()
+    };
+
+    cx.with_common_p(v_flex())
+        .gap_1()
+        .px_3()
+        .py_3()
+        .bg(cx.code_block_background_color)
+        .rounded_sm()
+        .child(div().font(font).child(source.to_string()))
+        .child(div().text_color(cx.error_color).child(error))
+        .into_any()
+}
+
 fn render_markdown_paragraph(parsed: &MarkdownParagraph, cx: &mut RenderContext) -> AnyElement {
     cx.with_common_p(div())
         .children(render_markdown_text(parsed, cx))
@@ -708,6 +947,7 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                     }
                 }
                 let workspace = workspace_clone.clone();
+                let footnote_clicked_callback = cx.footnote_clicked_callback.clone();
                 let element = div()
                     .child(
                         InteractiveText::new(
@@ -731,6 +971,17 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                             link_ranges,
                             move |clicked_range_ix, window, cx| match &links[clicked_range_ix] {
                                 Link::Web { url } => cx.open_url(url),
+                                Link::Footnote { label } => {
+                                    if let Some(callback) = &footnote_clicked_callback {
+                                        callback(
+                                            &FootnoteClickedEvent::Definition {
+                                                label: label.clone(),
+                                            },
+                                            window,
+                                            cx,
+                                        );
+                                    }
+                                }
                                 Link::Path { path, .. } => {
                                     if let Some(workspace) = &workspace {
                                         _ = workspace.update(cx, |workspace, cx| {
@@ -758,6 +1009,10 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
             MarkdownParagraphChunk::Image(image) => {
                 any_element.push(render_markdown_image(image, cx));
             }
+
+            MarkdownParagraphChunk::Math(math) => {
+                any_element.push(render_markdown_math(math, cx));
+            }
         }
     }
 
@@ -773,6 +1028,8 @@ fn render_markdown_image(image: &Image, cx: &mut RenderContext) -> AnyElement {
     let image_resource = match image.link.clone() {
         Link::Web { url } => Resource::Uri(url.into()),
         Link::Path { path, .. } => Resource::Path(Arc::from(path)),
+        // Images are never footnotes, so there is nothing to load.
+        Link::Footnote { .. } => return div().children(image.alt_text.clone()).into_any(),
     };
 
     let element_id = cx.next_id(&image.source_range);
@@ -826,6 +1083,7 @@ fn render_markdown_image(image: &Image, cx: &mut RenderContext) -> AnyElement {
                                 });
                             }
                         }
+                        Link::Footnote { .. } => {}
                     }
                 }
             }
@@ -881,7 +1139,7 @@ impl Render for InteractiveMarkdownElementTooltip {
 }
 
 /// Returns the prefix for a list item.
-fn list_item_prefix(order: usize, ordered: bool, depth: usize) -> String {
+pub(crate) fn list_item_prefix(order: usize, ordered: bool, depth: usize) -> String {
     let ix = order.saturating_sub(1);
     const NUMBERED_PREFIXES_1: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
     const NUMBERED_PREFIXES_2: &str = "abcdefghijklmnopqrstuvwxyz";
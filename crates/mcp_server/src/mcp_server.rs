@@ -0,0 +1,107 @@
+//! Exposes the running editor to external agents and CLIs over the Model Context Protocol.
+//!
+//! When enabled, Zed listens on [`paths::mcp_socket_file`], and clients start `zed --mcp` to
+//! talk to it over stdio. Every tool that touches a project checks that its worktree is
+//! trusted first.
+
+mod mcp_server_settings;
+mod tools;
+
+use anyhow::Result;
+use context_server::{
+    listener::McpServer,
+    types::{
+        Implementation, InitializeParams, InitializeResponse, LATEST_PROTOCOL_VERSION,
+        ProtocolVersion, ServerCapabilities, ToolsCapabilities, VERSION_2024_11_05,
+        VERSION_2025_03_26, requests,
+    },
+};
+use gpui::{App, AsyncApp, Global, Task};
+use release_channel::AppVersion;
+use settings::{Settings as _, SettingsStore};
+
+pub use mcp_server_settings::McpServerSettings;
+
+pub fn init(cx: &mut App) {
+    cx.set_global(GlobalMcpServer::default());
+    update_server(cx);
+    cx.observe_global::<SettingsStore>(update_server).detach();
+    // Dropping the server removes its socket, so that clients don't try to connect to it.
+    cx.on_app_quit(|cx| {
+        cx.global_mut::<GlobalMcpServer>().stop();
+        async {}
+    })
+    .detach();
+}
+
+#[derive(Default)]
+struct GlobalMcpServer {
+    enabled: bool,
+    start_task: Option<Task<()>>,
+    /// Keeps the server listening until it's dropped.
+    server: Option<McpServer>,
+}
+
+impl GlobalMcpServer {
+    fn stop(&mut self) {
+        self.start_task = None;
+        self.server = None;
+    }
+}
+
+impl Global for GlobalMcpServer {}
+
+fn update_server(cx: &mut App) {
+    let enabled = McpServerSettings::get_global(cx).enabled;
+    let global = cx.global_mut::<GlobalMcpServer>();
+    if enabled == global.enabled {
+        return;
+    }
+    global.enabled = enabled;
+    global.stop();
+    if !enabled {
+        return;
+    }
+
+    let start_task = cx.spawn(async move |cx| match start_server(cx).await {
+        Ok(server) => {
+            log::info!("MCP server listening on {:?}", server.socket_path());
+            cx.update_global::<GlobalMcpServer, _>(|global, _| global.server = Some(server));
+        }
+        Err(error) => log::error!("failed to start the MCP server: {error:#}"),
+    });
+    cx.global_mut::<GlobalMcpServer>().start_task = Some(start_task);
+}
+
+async fn start_server(cx: &mut AsyncApp) -> Result<McpServer> {
+    let mut server = McpServer::listen(paths::mcp_socket_file().clone(), cx).await?;
+    server.handle_request::<requests::Initialize>(|params, cx| {
+        Task::ready(Ok(initialize_response(params, cx)))
+    });
+    tools::register(&mut server);
+    Ok(server)
+}
+
+fn initialize_response(params: InitializeParams, cx: &App) -> InitializeResponse {
+    let protocol_version =
+        if [VERSION_2024_11_05, VERSION_2025_03_26].contains(&params.protocol_version.0.as_str()) {
+            params.protocol_version
+        } else {
+            ProtocolVersion(LATEST_PROTOCOL_VERSION.into())
+        };
+    InitializeResponse {
+        protocol_version,
+        capabilities: ServerCapabilities {
+            tools: Some(ToolsCapabilities {
+                list_changed: Some(false),
+            }),
+            ..ServerCapabilities::default()
+        },
+        server_info: Implementation {
+            name: "zed".into(),
+            title: Some("Zed".into()),
+            version: AppVersion::global(cx).to_string(),
+        },
+        meta: None,
+    }
+}
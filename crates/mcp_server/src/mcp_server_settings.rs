@@ -0,0 +1,19 @@
+use settings::{RegisterSetting, Settings};
+
+/// The settings for Zed's MCP server.
+#[derive(Clone, Debug, RegisterSetting)]
+pub struct McpServerSettings {
+    /// Whether external tools can connect to Zed over the Model Context Protocol.
+    ///
+    /// Default: false
+    pub enabled: bool,
+}
+
+impl Settings for McpServerSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let mcp_server = content.mcp_server.clone().unwrap();
+        Self {
+            enabled: mcp_server.enabled.unwrap(),
+        }
+    }
+}
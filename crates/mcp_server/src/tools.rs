@@ -0,0 +1,804 @@
+use std::ops::Range;
+
+use anyhow::{Context as _, Result};
+use collections::BTreeMap;
+use context_server::{
+    listener::{McpServer, McpServerTool, ToolResponse},
+    types::{ToolAnnotations, ToolResponseContent},
+};
+use editor::Editor;
+use gpui::{App, AsyncApp, Entity, WindowHandle};
+use language::{Buffer, BufferSnapshot, DiagnosticSeverity, Location, Point, ToPoint as _};
+use project::{Project, ProjectPath, WorktreeId, trusted_worktrees::TrustedWorktrees};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use task::TaskContext;
+use workspace::Workspace;
+
+pub(crate) fn register(server: &mut McpServer) {
+    server.add_tool(ListOpenBuffersTool);
+    server.add_tool(ReadBufferTool);
+    server.add_tool(GetDiagnosticsTool);
+    server.add_tool(GoToDefinitionTool);
+    server.add_tool(FindReferencesTool);
+    server.add_tool(RunTaskTool);
+    server.add_tool(OpenFileTool);
+}
+
+/// A position in a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Position {
+    /// The 1-based line number.
+    pub line: u32,
+    /// The 1-based column, counted in characters.
+    pub column: u32,
+}
+
+#[derive(Clone)]
+struct ListOpenBuffersTool;
+
+/// Lists the files that are open in Zed, including whether they have unsaved edits.
+#[derive(Deserialize, JsonSchema)]
+struct ListOpenBuffersInput {}
+
+#[derive(Serialize, JsonSchema)]
+struct ListOpenBuffersOutput {
+    buffers: Vec<OpenBuffer>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct OpenBuffer {
+    /// The absolute path of the file.
+    path: String,
+    /// The name of the file's language, if it has one.
+    language: Option<String>,
+    /// Whether the buffer has edits that haven't been saved to disk.
+    has_unsaved_edits: bool,
+    line_count: u32,
+}
+
+impl McpServerTool for ListOpenBuffersTool {
+    type Input = ListOpenBuffersInput;
+    type Output = ListOpenBuffersOutput;
+
+    const NAME: &'static str = "list_open_buffers";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("List Open Buffers")
+    }
+
+    async fn run(&self, _: Self::Input, cx: &mut AsyncApp) -> Result<ToolResponse<Self::Output>> {
+        let buffers = cx.update(|cx| {
+            let projects = workspaces(cx)
+                .into_iter()
+                .filter_map(|workspace| Some(workspace.read(cx).ok()?.project().clone()))
+                .collect::<Vec<_>>();
+            let mut buffers = Vec::new();
+            for project in projects {
+                for buffer in project.read(cx).opened_buffers(cx) {
+                    let Some(worktree_id) = buffer.read(cx).file().map(|file| file.worktree_id(cx))
+                    else {
+                        continue;
+                    };
+                    if !is_trusted(&project, worktree_id, cx) {
+                        continue;
+                    }
+                    let buffer = buffer.read(cx);
+                    if let Some(path) = buffer_path(buffer, cx) {
+                        buffers.push(OpenBuffer {
+                            path,
+                            language: buffer
+                                .language()
+                                .map(|language| language.name().0.to_string()),
+                            has_unsaved_edits: buffer.is_dirty(),
+                            line_count: buffer.max_point().row + 1,
+                        });
+                    }
+                }
+            }
+            buffers
+        });
+        tool_response(ListOpenBuffersOutput { buffers })
+    }
+}
+
+#[derive(Clone)]
+struct ReadBufferTool;
+
+/// Reads a file as it is in Zed, including edits that haven't been saved to disk yet.
+#[derive(Deserialize, JsonSchema)]
+struct ReadBufferInput {
+    /// The absolute path of the file, or a path relative to the root of an open project.
+    path: String,
+    /// The 1-based line to start reading from. Defaults to the first line.
+    start_line: Option<u32>,
+    /// The 1-based line to stop reading at, inclusive. Defaults to the last line.
+    end_line: Option<u32>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ReadBufferOutput {
+    path: String,
+    text: String,
+    /// The 1-based line the text starts at.
+    start_line: u32,
+    /// The 1-based line the text ends at, inclusive.
+    end_line: u32,
+    line_count: u32,
+    /// Whether the buffer has edits that haven't been saved to disk.
+    has_unsaved_edits: bool,
+}
+
+impl McpServerTool for ReadBufferTool {
+    type Input = ReadBufferInput;
+    type Output = ReadBufferOutput;
+
+    const NAME: &'static str = "read_buffer";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Read Buffer")
+    }
+
+    async fn run(
+        &self,
+        input: Self::Input,
+        cx: &mut AsyncApp,
+    ) -> Result<ToolResponse<Self::Output>> {
+        let file = cx.update(|cx| ProjectFile::find(&input.path, cx))?;
+        let buffer = file.open_buffer(cx).await?;
+        let output = cx.update(|cx| {
+            let buffer = buffer.read(cx);
+            let snapshot = buffer.snapshot();
+            let rows = line_range(&snapshot, input.start_line, input.end_line);
+            let range =
+                Point::new(rows.start, 0)..Point::new(rows.end, snapshot.line_len(rows.end));
+            ReadBufferOutput {
+                path: buffer_path(buffer, cx).unwrap_or(input.path),
+                text: snapshot.text_for_range(range).collect(),
+                start_line: rows.start + 1,
+                end_line: rows.end + 1,
+                line_count: snapshot.max_point().row + 1,
+                has_unsaved_edits: buffer.is_dirty(),
+            }
+        });
+        tool_response(output)
+    }
+}
+
+#[derive(Clone)]
+struct GetDiagnosticsTool;
+
+/// Gets the errors and warnings that language servers reported. Given a path, returns each
+/// diagnostic in that file. Otherwise, returns the number of errors and warnings in every
+/// file of the open projects that has any.
+#[derive(Deserialize, JsonSchema)]
+struct GetDiagnosticsInput {
+    /// The absolute path of a file, or a path relative to the root of an open project.
+    path: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct GetDiagnosticsOutput {
+    files: Vec<FileDiagnostics>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct FileDiagnostics {
+    path: String,
+    error_count: usize,
+    warning_count: usize,
+    /// The diagnostics in the file, when it was requested by path.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Diagnostic {
+    /// One of "error", "warning", "information" or "hint".
+    severity: &'static str,
+    message: String,
+    /// The tool that reported the diagnostic, such as the name of a language server.
+    source: Option<String>,
+    start: Position,
+    end: Position,
+}
+
+impl McpServerTool for GetDiagnosticsTool {
+    type Input = GetDiagnosticsInput;
+    type Output = GetDiagnosticsOutput;
+
+    const NAME: &'static str = "get_diagnostics";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Get Diagnostics")
+    }
+
+    async fn run(
+        &self,
+        input: Self::Input,
+        cx: &mut AsyncApp,
+    ) -> Result<ToolResponse<Self::Output>> {
+        let Some(path) = input.path else {
+            let files = cx.update(project_diagnostic_summaries);
+            return tool_response(GetDiagnosticsOutput { files });
+        };
+
+        let file = cx.update(|cx| ProjectFile::find(&path, cx))?;
+        let buffer = file.open_buffer(cx).await?;
+        let file_diagnostics = cx.update(|cx| {
+            let buffer = buffer.read(cx);
+            let snapshot = buffer.snapshot();
+            let diagnostics = snapshot
+                .diagnostics_in_range::<_, Point>(0..snapshot.len(), false)
+                .filter(|entry| entry.diagnostic.is_primary)
+                .map(|entry| Diagnostic {
+                    severity: severity_name(entry.diagnostic.severity),
+                    message: entry.diagnostic.message.clone(),
+                    source: entry.diagnostic.source.clone(),
+                    start: position_for_point(&snapshot, entry.range.start),
+                    end: position_for_point(&snapshot, entry.range.end),
+                })
+                .collect::<Vec<_>>();
+            FileDiagnostics {
+                path: buffer_path(buffer, cx).unwrap_or(path),
+                error_count: diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.severity == "error")
+                    .count(),
+                warning_count: diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.severity == "warning")
+                    .count(),
+                diagnostics,
+            }
+        });
+        tool_response(GetDiagnosticsOutput {
+            files: vec![file_diagnostics],
+        })
+    }
+}
+
+fn project_diagnostic_summaries(cx: &mut App) -> Vec<FileDiagnostics> {
+    let projects = workspaces(cx)
+        .into_iter()
+        .filter_map(|workspace| Some(workspace.read(cx).ok()?.project().clone()))
+        .collect::<Vec<_>>();
+    let mut counts = BTreeMap::<String, (usize, usize)>::default();
+    for project in projects {
+        let summaries = project
+            .read(cx)
+            .diagnostic_summaries(false, cx)
+            .filter_map(|(project_path, _, summary)| {
+                let abs_path = project.read(cx).absolute_path(&project_path, cx)?;
+                Some((project_path.worktree_id, abs_path, summary))
+            })
+            .collect::<Vec<_>>();
+        for (worktree_id, abs_path, summary) in summaries {
+            if is_trusted(&project, worktree_id, cx) {
+                let (errors, warnings) = counts
+                    .entry(abs_path.to_string_lossy().into_owned())
+                    .or_default();
+                *errors += summary.error_count;
+                *warnings += summary.warning_count;
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(path, (error_count, warning_count))| FileDiagnostics {
+            path,
+            error_count,
+            warning_count,
+            diagnostics: Vec::new(),
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+struct GoToDefinitionTool;
+
+/// Finds where the symbol at a position in a file is defined, using the file's language
+/// servers.
+#[derive(Deserialize, JsonSchema)]
+struct GoToDefinitionInput {
+    /// The absolute path of the file, or a path relative to the root of an open project.
+    path: String,
+    /// The 1-based line of the symbol.
+    line: u32,
+    /// The 1-based column of the symbol, counted in characters.
+    column: u32,
+}
+
+#[derive(Clone)]
+struct FindReferencesTool;
+
+/// Finds the references to the symbol at a position in a file, using the file's language
+/// servers.
+#[derive(Deserialize, JsonSchema)]
+struct FindReferencesInput {
+    /// The absolute path of the file, or a path relative to the root of an open project.
+    path: String,
+    /// The 1-based line of the symbol.
+    line: u32,
+    /// The 1-based column of the symbol, counted in characters.
+    column: u32,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SymbolLocations {
+    locations: Vec<SymbolLocation>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SymbolLocation {
+    path: String,
+    start: Position,
+    end: Position,
+    /// The text of the line that the location starts on.
+    line_text: String,
+}
+
+impl McpServerTool for GoToDefinitionTool {
+    type Input = GoToDefinitionInput;
+    type Output = SymbolLocations;
+
+    const NAME: &'static str = "go_to_definition";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Go to Definition")
+    }
+
+    async fn run(
+        &self,
+        input: Self::Input,
+        cx: &mut AsyncApp,
+    ) -> Result<ToolResponse<Self::Output>> {
+        let position = Position {
+            line: input.line,
+            column: input.column,
+        };
+        let (file, buffer, point) = buffer_at_position(&input.path, position, cx).await?;
+        let links = file
+            .project
+            .update(cx, |project, cx| project.definitions(&buffer, point, cx))
+            .await?
+            .unwrap_or_default();
+        let locations = cx.update(|cx| {
+            links
+                .iter()
+                .filter_map(|link| symbol_location(&link.target, cx))
+                .collect()
+        });
+        tool_response(SymbolLocations { locations })
+    }
+}
+
+impl McpServerTool for FindReferencesTool {
+    type Input = FindReferencesInput;
+    type Output = SymbolLocations;
+
+    const NAME: &'static str = "find_references";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Find References")
+    }
+
+    async fn run(
+        &self,
+        input: Self::Input,
+        cx: &mut AsyncApp,
+    ) -> Result<ToolResponse<Self::Output>> {
+        let position = Position {
+            line: input.line,
+            column: input.column,
+        };
+        let (file, buffer, point) = buffer_at_position(&input.path, position, cx).await?;
+        let references = file
+            .project
+            .update(cx, |project, cx| project.references(&buffer, point, cx))
+            .await?
+            .unwrap_or_default();
+        let locations = cx.update(|cx| {
+            references
+                .iter()
+                .filter_map(|location| symbol_location(location, cx))
+                .collect()
+        });
+        tool_response(SymbolLocations { locations })
+    }
+}
+
+async fn buffer_at_position(
+    path: &str,
+    position: Position,
+    cx: &mut AsyncApp,
+) -> Result<(ProjectFile, Entity<Buffer>, Point)> {
+    let file = cx.update(|cx| ProjectFile::find(path, cx))?;
+    let buffer = file.open_buffer(cx).await?;
+    let point = cx.update(|cx| point_for_position(&buffer.read(cx).snapshot(), position));
+    Ok((file, buffer, point))
+}
+
+fn symbol_location(location: &Location, cx: &App) -> Option<SymbolLocation> {
+    let buffer = location.buffer.read(cx);
+    let snapshot = buffer.snapshot();
+    let start = location.range.start.to_point(&snapshot);
+    let end = location.range.end.to_point(&snapshot);
+    let line_range = Point::new(start.row, 0)..Point::new(start.row, snapshot.line_len(start.row));
+    Some(SymbolLocation {
+        path: buffer_path(buffer, cx)?,
+        start: position_for_point(&snapshot, start),
+        end: position_for_point(&snapshot, end),
+        line_text: snapshot.text_for_range(line_range).collect(),
+    })
+}
+
+#[derive(Clone)]
+struct RunTaskTool;
+
+/// Runs a task defined for the project in Zed's active window, in a terminal in Zed. When no
+/// task has the given label, fails with the labels of the tasks that can be run.
+#[derive(Deserialize, JsonSchema)]
+struct RunTaskInput {
+    /// The label of the task to run.
+    label: String,
+}
+
+impl McpServerTool for RunTaskTool {
+    type Input = RunTaskInput;
+    type Output = ();
+
+    const NAME: &'static str = "run_task";
+
+    fn annotations(&self) -> ToolAnnotations {
+        ToolAnnotations {
+            title: Some("Run Task".into()),
+            read_only_hint: Some(false),
+            destructive_hint: Some(true),
+            idempotent_hint: Some(false),
+            open_world_hint: Some(true),
+        }
+    }
+
+    async fn run(
+        &self,
+        input: Self::Input,
+        cx: &mut AsyncApp,
+    ) -> Result<ToolResponse<Self::Output>> {
+        let workspace = cx
+            .update(|cx| workspaces(cx).into_iter().next())
+            .context("No project is open in Zed")?;
+        let task_contexts = workspace
+            .update(cx, |workspace, window, cx| {
+                tasks_ui::task_contexts(workspace, window, cx)
+            })?
+            .await;
+        let worktree_id = task_contexts
+            .worktree()
+            .context("The project in Zed's active window has no folder to run tasks in")?;
+        let tasks = workspace
+            .update(cx, |workspace, _, cx| {
+                ensure_trusted(workspace.project(), worktree_id, cx)?;
+                let task_inventory = workspace
+                    .project()
+                    .read(cx)
+                    .task_store()
+                    .read(cx)
+                    .task_inventory()
+                    .cloned()
+                    .context("Tasks aren't available in this project")?;
+                let (file, language) = task_contexts
+                    .location()
+                    .map(|location| {
+                        let buffer = location.buffer.read(cx);
+                        (
+                            buffer.file().cloned(),
+                            buffer.language_at(location.range.start),
+                        )
+                    })
+                    .unwrap_or_default();
+                anyhow::Ok(task_inventory.read(cx).list_tasks(
+                    file,
+                    language,
+                    Some(worktree_id),
+                    cx,
+                ))
+            })??
+            .await;
+
+        let Some((task_source_kind, task_template)) = tasks
+            .iter()
+            .find(|(_, template)| template.label == input.label)
+            .cloned()
+        else {
+            let labels = tasks
+                .iter()
+                .map(|(_, template)| format!("{:?}", template.label))
+                .collect::<Vec<_>>();
+            anyhow::bail!(
+                "No task is labeled {:?}. The available tasks are: {}",
+                input.label,
+                labels.join(", ")
+            );
+        };
+        workspace.update(cx, |workspace, window, cx| {
+            let default_context = TaskContext::default();
+            let active_context = task_contexts.active_context().unwrap_or(&default_context);
+            workspace.schedule_task(
+                task_source_kind,
+                &task_template,
+                active_context,
+                false,
+                window,
+                cx,
+            );
+        })?;
+
+        text_response(format!("Started the task {:?}", input.label))
+    }
+}
+
+#[derive(Clone)]
+struct OpenFileTool;
+
+/// Opens a file in Zed's editor so that the user can see it, optionally moving the cursor
+/// to a line and column.
+#[derive(Deserialize, JsonSchema)]
+struct OpenFileInput {
+    /// The absolute path of the file, or a path relative to the root of an open project.
+    path: String,
+    /// The 1-based line to move the cursor to.
+    line: Option<u32>,
+    /// The 1-based column to move the cursor to, counted in characters. Defaults to the
+    /// start of the line.
+    column: Option<u32>,
+}
+
+impl McpServerTool for OpenFileTool {
+    type Input = OpenFileInput;
+    type Output = ();
+
+    const NAME: &'static str = "open_file";
+
+    fn annotations(&self) -> ToolAnnotations {
+        ToolAnnotations {
+            title: Some("Open File".into()),
+            read_only_hint: Some(false),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+        }
+    }
+
+    async fn run(
+        &self,
+        input: Self::Input,
+        cx: &mut AsyncApp,
+    ) -> Result<ToolResponse<Self::Output>> {
+        let file = cx.update(|cx| ProjectFile::find(&input.path, cx))?;
+        let item = file
+            .workspace
+            .update(cx, |workspace, window, cx| {
+                window.activate_window();
+                workspace.open_path(file.project_path.clone(), None, true, window, cx)
+            })?
+            .await?;
+
+        if let Some(line) = input.line
+            && let Some(editor) = cx.update(|cx| item.act_as::<Editor>(cx))
+        {
+            let position = Position {
+                line,
+                column: input.column.unwrap_or(1),
+            };
+            file.workspace.update(cx, |_, window, cx| {
+                editor.update(cx, |editor, cx| {
+                    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+                        return;
+                    };
+                    let point = point_for_position(&buffer.read(cx).snapshot(), position);
+                    editor.go_to_singleton_buffer_point(point, window, cx);
+                })
+            })?;
+        }
+
+        text_response(format!("Opened {}", input.path))
+    }
+}
+
+/// A file in one of the projects that are open in Zed.
+struct ProjectFile {
+    workspace: WindowHandle<Workspace>,
+    project: Entity<Project>,
+    project_path: ProjectPath,
+}
+
+impl ProjectFile {
+    /// Finds the file in the open projects, preferring the one in the active window, and
+    /// checks that its worktree is trusted.
+    fn find(path: &str, cx: &mut App) -> Result<Self> {
+        for workspace in workspaces(cx) {
+            let Ok(project) = workspace
+                .read(cx)
+                .map(|workspace| workspace.project().clone())
+            else {
+                continue;
+            };
+            if let Some(project_path) = project.read(cx).find_project_path(path, cx) {
+                ensure_trusted(&project, project_path.worktree_id, cx)?;
+                return Ok(Self {
+                    workspace,
+                    project,
+                    project_path,
+                });
+            }
+        }
+        anyhow::bail!("{path} is not in a project that is open in Zed")
+    }
+
+    async fn open_buffer(&self, cx: &mut AsyncApp) -> Result<Entity<Buffer>> {
+        self.project
+            .update(cx, |project, cx| {
+                project.open_buffer(self.project_path.clone(), cx)
+            })
+            .await
+    }
+}
+
+/// The workspaces of local projects, with the one in the active window first.
+fn workspaces(cx: &App) -> Vec<WindowHandle<Workspace>> {
+    let active_window_id = cx.active_window().map(|window| window.window_id());
+    let mut workspaces = workspace::local_workspace_windows(cx);
+    workspaces.sort_by_key(|workspace| Some(workspace.window_id()) != active_window_id);
+    workspaces
+}
+
+fn is_trusted(project: &Entity<Project>, worktree_id: WorktreeId, cx: &mut App) -> bool {
+    let Some(trusted_worktrees) = TrustedWorktrees::try_get_global(cx) else {
+        return true;
+    };
+    let worktree_store = project.read(cx).worktree_store();
+    trusted_worktrees.update(cx, |trusted_worktrees, cx| {
+        trusted_worktrees.can_trust(&worktree_store, worktree_id, cx)
+    })
+}
+
+fn ensure_trusted(project: &Entity<Project>, worktree_id: WorktreeId, cx: &mut App) -> Result<()> {
+    anyhow::ensure!(
+        is_trusted(project, worktree_id, cx),
+        "The project isn't trusted. Trust it in Zed to let external tools access it."
+    );
+    Ok(())
+}
+
+fn buffer_path(buffer: &Buffer, cx: &App) -> Option<String> {
+    let file = buffer.file()?.as_local()?;
+    Some(file.abs_path(cx).to_string_lossy().into_owned())
+}
+
+fn severity_name(severity: DiagnosticSeverity) -> &'static str {
+    match severity {
+        DiagnosticSeverity::ERROR => "error",
+        DiagnosticSeverity::WARNING => "warning",
+        DiagnosticSeverity::INFORMATION => "information",
+        _ => "hint",
+    }
+}
+
+/// Returns the 0-based rows between the given 1-based lines, clamped to the buffer.
+fn line_range(
+    snapshot: &BufferSnapshot,
+    start_line: Option<u32>,
+    end_line: Option<u32>,
+) -> Range<u32> {
+    let max_row = snapshot.max_point().row;
+    let start = start_line.unwrap_or(1).saturating_sub(1).min(max_row);
+    let end = end_line
+        .map_or(max_row, |line| line.saturating_sub(1))
+        .clamp(start, max_row);
+    start..end
+}
+
+fn point_for_position(snapshot: &BufferSnapshot, position: Position) -> Point {
+    let row = position
+        .line
+        .saturating_sub(1)
+        .min(snapshot.max_point().row);
+    let column = snapshot
+        .chars_at(Point::new(row, 0))
+        .take_while(|character| *character != '\n')
+        .take(position.column.saturating_sub(1) as usize)
+        .map(|character| character.len_utf8() as u32)
+        .sum();
+    Point::new(row, column)
+}
+
+fn position_for_point(snapshot: &BufferSnapshot, point: Point) -> Position {
+    let column = snapshot
+        .text_for_range(Point::new(point.row, 0)..point)
+        .map(|chunk| chunk.chars().count())
+        .sum::<usize>();
+    Position {
+        line: point.row + 1,
+        column: column as u32 + 1,
+    }
+}
+
+fn read_only_annotations(title: &str) -> ToolAnnotations {
+    ToolAnnotations {
+        title: Some(title.into()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    }
+}
+
+/// Responds with the output as structured content, and as JSON text for clients that only
+/// read text content.
+fn tool_response<T: Serialize>(output: T) -> Result<ToolResponse<T>> {
+    Ok(ToolResponse {
+        content: vec![ToolResponseContent::Text {
+            text: serde_json::to_string(&output)?,
+        }],
+        structured_content: output,
+    })
+}
+
+fn text_response(text: String) -> Result<ToolResponse<()>> {
+    Ok(ToolResponse {
+        content: vec![ToolResponseContent::Text { text }],
+        structured_content: (),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+
+    #[gpui::test]
+    fn test_positions(cx: &mut TestAppContext) {
+        let buffer = cx.new(|cx| Buffer::local("fn main() {\n    let café = 1;\n}", cx));
+        buffer.read_with(cx, |buffer, _| {
+            let snapshot = buffer.snapshot();
+
+            let position = Position {
+                line: 2,
+                column: 14,
+            };
+            let point = point_for_position(&snapshot, position);
+            assert_eq!(point, Point::new(1, 14), "columns count characters");
+            assert_eq!(position_for_point(&snapshot, point), position);
+
+            assert_eq!(
+                point_for_position(
+                    &snapshot,
+                    Position {
+                        line: 1,
+                        column: 100
+                    }
+                ),
+                Point::new(0, 11),
+                "columns past the end of the line are clamped"
+            );
+            assert_eq!(
+                point_for_position(&snapshot, Position { line: 9, column: 1 }),
+                Point::new(2, 0),
+                "lines past the end of the buffer are clamped"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_line_range(cx: &mut TestAppContext) {
+        let buffer = cx.new(|cx| Buffer::local("one\ntwo\nthree\nfour", cx));
+        buffer.read_with(cx, |buffer, _| {
+            let snapshot = buffer.snapshot();
+            assert_eq!(line_range(&snapshot, None, None), 0..3);
+            assert_eq!(line_range(&snapshot, Some(2), Some(3)), 1..2);
+            assert_eq!(line_range(&snapshot, Some(0), Some(10)), 0..3);
+            assert_eq!(line_range(&snapshot, Some(3), Some(1)), 2..2);
+        });
+    }
+}
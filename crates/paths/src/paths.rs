@@ -191,12 +191,39 @@ pub fn old_log_file() -> &'static PathBuf {
     OLD_LOG_FILE.get_or_init(|| logs_dir().join("Zed.log.old"))
 }
 
+/// Returns the path to the `Zed.log.jsonl` file, which has the same messages as `Zed.log` with
+/// one JSON object per line.
+pub fn json_log_file() -> &'static PathBuf {
+    static JSON_LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+    JSON_LOG_FILE.get_or_init(|| logs_dir().join("Zed.log.jsonl"))
+}
+
+/// Returns the path to the `Zed.log.jsonl.old` file.
+pub fn old_json_log_file() -> &'static PathBuf {
+    static OLD_JSON_LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+    OLD_JSON_LOG_FILE.get_or_init(|| logs_dir().join("Zed.log.jsonl.old"))
+}
+
+/// Returns the path to the `Zed.traces.jsonl` file, which spans are exported to in the
+/// OpenTelemetry (OTLP) JSON file format.
+pub fn traces_file() -> &'static PathBuf {
+    static TRACES_FILE: OnceLock<PathBuf> = OnceLock::new();
+    TRACES_FILE.get_or_init(|| logs_dir().join("Zed.traces.jsonl"))
+}
+
 /// Returns the path to the database directory.
 pub fn database_dir() -> &'static PathBuf {
     static DATABASE_DIR: OnceLock<PathBuf> = OnceLock::new();
     DATABASE_DIR.get_or_init(|| data_dir().join("db"))
 }
 
+/// Returns the path to the socket that external tools connect to when Zed's MCP server is
+/// enabled.
+pub fn mcp_socket_file() -> &'static PathBuf {
+    static MCP_SOCKET_FILE: OnceLock<PathBuf> = OnceLock::new();
+    MCP_SOCKET_FILE.get_or_init(|| data_dir().join("mcp.sock"))
+}
+
 /// Returns the path to the crashes directory, if it exists for the current platform.
 pub fn crashes_dir() -> &'static Option<PathBuf> {
     static CRASHES_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
@@ -408,6 +435,14 @@ pub fn remote_servers_dir() -> &'static PathBuf {
     REMOTE_SERVERS_DIR.get_or_init(|| data_dir().join("remote_servers"))
 }
 
+/// Returns the path to the settings sync directory.
+///
+/// This is where the last synced configuration and the clone of the sync repository are stored.
+pub fn settings_sync_dir() -> &'static PathBuf {
+    static SETTINGS_SYNC_DIR: OnceLock<PathBuf> = OnceLock::new();
+    SETTINGS_SYNC_DIR.get_or_init(|| data_dir().join("settings_sync"))
+}
+
 /// Returns the path to the directory where the devcontainer CLI is installed.
 pub fn devcontainer_dir() -> &'static PathBuf {
     static DEVCONTAINER_DIR: OnceLock<PathBuf> = OnceLock::new();
@@ -498,6 +533,24 @@ pub fn cursor_settings_file_paths() -> Vec<PathBuf> {
     paths
 }
 
+/// Returns candidate paths for the vscode user keybindings file
+pub fn vscode_keybindings_file_paths() -> Vec<PathBuf> {
+    let mut paths = vscode_user_data_paths();
+    for path in paths.iter_mut() {
+        path.push("User/keybindings.json");
+    }
+    paths
+}
+
+/// Returns candidate paths for the cursor user keybindings file
+pub fn cursor_keybindings_file_paths() -> Vec<PathBuf> {
+    let mut paths = cursor_user_data_paths();
+    for path in paths.iter_mut() {
+        path.push("User/keybindings.json");
+    }
+    paths
+}
+
 fn vscode_user_data_paths() -> Vec<PathBuf> {
     // https://github.com/microsoft/vscode/blob/23e7148cdb6d8a27f0109ff77e5b1e019f8da051/src/vs/platform/environment/node/userDataPath.ts#L45
     const VSCODE_PRODUCT_NAMES: &[&str] = &[
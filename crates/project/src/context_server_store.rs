@@ -6,8 +6,17 @@ use std::time::Duration;
 
 use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet};
-use context_server::{ContextServer, ContextServerCommand, ContextServerId};
-use futures::{FutureExt as _, future::join_all};
+use context_server::{
+    ContextServer, ContextServerCommand, ContextServerId,
+    client::IoKind,
+    oauth::KeychainOAuthDelegate,
+    types::{ElicitationCreateParams, ElicitationCreateResponse},
+};
+use futures::{
+    FutureExt as _, StreamExt as _,
+    channel::{mpsc, oneshot},
+    future::join_all,
+};
 use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity, actions};
 use registry::ContextServerDescriptorRegistry;
 use settings::{Settings as _, SettingsStore};
@@ -180,14 +189,39 @@ pub struct ContextServerStore {
     update_servers_task: Option<Task<Result<()>>>,
     context_server_factory: Option<ContextServerFactory>,
     needs_server_update: bool,
+    pending_elicitations: HashMap<ElicitationId, PendingElicitation>,
+    next_elicitation_id: ElicitationId,
+    io_tx: mpsc::UnboundedSender<(ContextServerId, IoKind, String)>,
     _subscriptions: Vec<Subscription>,
 }
 
+/// Identifies an `elicitation/create` request awaiting an answer from the user.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ElicitationId(usize);
+
+struct PendingElicitation {
+    server_id: ContextServerId,
+    response_tx: oneshot::Sender<ElicitationCreateResponse>,
+}
+
 pub enum Event {
     ServerStatusChanged {
         server_id: ContextServerId,
         status: ContextServerStatus,
     },
+    /// A server asked the user for input. Answer it with
+    /// [`ContextServerStore::respond_to_elicitation`].
+    ElicitationRequested {
+        server_id: ContextServerId,
+        elicitation_id: ElicitationId,
+        params: Arc<ElicitationCreateParams>,
+    },
+    /// A JSON-RPC message was exchanged with a server, or it wrote to stderr.
+    ServerMessage {
+        server_id: ContextServerId,
+        kind: IoKind,
+        message: Arc<str>,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerStore {}
@@ -272,6 +306,24 @@ impl ContextServerStore {
             Vec::new()
         };
 
+        // Servers report traffic from background threads, so it's funneled through a channel.
+        let (io_tx, mut io_rx) = mpsc::unbounded::<(ContextServerId, IoKind, String)>();
+        cx.spawn(async move |this, cx| {
+            while let Some((server_id, kind, message)) = io_rx.next().await {
+                let emitted = this.update(cx, |_, cx| {
+                    cx.emit(Event::ServerMessage {
+                        server_id,
+                        kind,
+                        message: message.into(),
+                    })
+                });
+                if emitted.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+
         let mut this = Self {
             _subscriptions: subscriptions,
             context_server_settings: Self::resolve_project_settings(&worktree_store, cx)
@@ -281,6 +333,9 @@ impl ContextServerStore {
             project: weak_project,
             registry,
             needs_server_update: false,
+            pending_elicitations: HashMap::default(),
+            next_elicitation_id: ElicitationId::default(),
+            io_tx,
             servers: HashMap::default(),
             update_servers_task: None,
             context_server_factory,
@@ -375,6 +430,40 @@ impl ContextServerStore {
         .detach_and_log_err(cx);
     }
 
+    fn request_elicitation(
+        &mut self,
+        server_id: ContextServerId,
+        params: ElicitationCreateParams,
+        response_tx: oneshot::Sender<ElicitationCreateResponse>,
+        cx: &mut Context<Self>,
+    ) {
+        let elicitation_id = self.next_elicitation_id;
+        self.next_elicitation_id.0 += 1;
+        self.pending_elicitations.insert(
+            elicitation_id,
+            PendingElicitation {
+                server_id: server_id.clone(),
+                response_tx,
+            },
+        );
+        cx.emit(Event::ElicitationRequested {
+            server_id,
+            elicitation_id,
+            params: Arc::new(params),
+        });
+    }
+
+    /// Sends the user's answer to an [`Event::ElicitationRequested`] back to the server.
+    pub fn respond_to_elicitation(
+        &mut self,
+        elicitation_id: ElicitationId,
+        response: ElicitationCreateResponse,
+    ) {
+        if let Some(elicitation) = self.pending_elicitations.remove(&elicitation_id) {
+            elicitation.response_tx.send(response).ok();
+        }
+    }
+
     pub fn stop_server(&mut self, id: &ContextServerId, cx: &mut Context<Self>) -> Result<()> {
         if matches!(
             self.servers.get(id),
@@ -395,6 +484,8 @@ impl ContextServerStore {
             result = server.stop();
         }
         drop(state);
+        self.pending_elicitations
+            .retain(|_, elicitation| &elicitation.server_id != id);
 
         self.update_server_state(
             id.clone(),
@@ -421,6 +512,34 @@ impl ContextServerStore {
         ) {
             self.stop_server(&id, cx).log_err();
         }
+        server.set_elicitation_handler(Arc::new({
+            let this = cx.weak_entity();
+            let id = id.clone();
+            move |params: ElicitationCreateParams, cx: AsyncApp| {
+                let (response_tx, response_rx) = oneshot::channel();
+                let this = this.clone();
+                let id = id.clone();
+                cx.spawn(async move |cx| {
+                    this.update(cx, |this, cx| {
+                        this.request_elicitation(id, params, response_tx, cx)
+                    })?;
+                    // Nobody answering (e.g. the server was stopped) is the same as the user
+                    // dismissing the form.
+                    Ok(response_rx
+                        .await
+                        .unwrap_or_else(|_| ElicitationCreateResponse::cancel()))
+                })
+            }
+        }));
+        server.set_io_handler(Arc::new({
+            let io_tx = self.io_tx.clone();
+            let id = id.clone();
+            move |kind: IoKind, message: &str| {
+                io_tx
+                    .unbounded_send((id.clone(), kind, message.to_string()))
+                    .ok();
+            }
+        }));
         let task = cx.spawn({
             let id = server.id();
             let server = server.clone();
@@ -513,6 +632,7 @@ impl ContextServerStore {
                 Some(Duration::from_secs(
                     timeout.unwrap_or(global_timeout).min(MAX_TIMEOUT_SECS),
                 )),
+                Some(KeychainOAuthDelegate::new(url, cx)),
             )?)),
             _ => {
                 let root_path = self
@@ -778,6 +898,93 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_context_server_elicitation(cx: &mut TestAppContext) {
+        const SERVER_ID: &str = "mcp-1";
+
+        let (_fs, project) = setup_context_server_test(
+            cx,
+            json!({"code.rs": ""}),
+            vec![(SERVER_ID.into(), dummy_server_settings())],
+        )
+        .await;
+
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let store = cx.new(|cx| {
+            ContextServerStore::test(
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+
+        let server_id = ContextServerId(SERVER_ID.into());
+        let transport = Arc::new(create_fake_transport(SERVER_ID, cx.executor()));
+        let server = Arc::new(ContextServer::new(server_id.clone(), transport.clone()));
+        store.update(cx, |store, cx| store.start_server(server, cx));
+        cx.run_until_parked();
+
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let requests = requests.clone();
+            cx.subscribe(&store, move |_, event, _| {
+                if let Event::ElicitationRequested {
+                    server_id,
+                    elicitation_id,
+                    params,
+                } = event
+                {
+                    requests.borrow_mut().push((
+                        server_id.clone(),
+                        *elicitation_id,
+                        params.clone(),
+                    ));
+                }
+            })
+        });
+
+        let response = cx.background_spawn(
+            transport.request::<context_server::types::requests::ElicitationCreate>(
+                ElicitationCreateParams {
+                    message: "Which branch?".into(),
+                    requested_schema: serde_json::from_value(json!({
+                        "type": "object",
+                        "properties": {"branch": {"type": "string"}},
+                        "required": ["branch"],
+                    }))
+                    .unwrap(),
+                    meta: None,
+                },
+            ),
+        );
+        cx.run_until_parked();
+
+        let (requested_server_id, elicitation_id, params) = requests
+            .borrow_mut()
+            .pop()
+            .expect("no elicitation requested");
+        assert_eq!(requested_server_id, server_id);
+        assert_eq!(params.message, "Which branch?");
+        assert_eq!(params.requested_schema.required, vec!["branch".to_string()]);
+
+        store.update(cx, |store, _| {
+            store.respond_to_elicitation(
+                elicitation_id,
+                ElicitationCreateResponse::accept(HashMap::from_iter([(
+                    "branch".to_string(),
+                    json!("main"),
+                )])),
+            )
+        });
+        let response = response.await.unwrap();
+        assert_eq!(
+            response.action,
+            context_server::types::ElicitationAction::Accept
+        );
+        assert_eq!(response.content.unwrap()["branch"], json!("main"));
+    }
+
     #[gpui::test]
     async fn test_context_server_status_events(cx: &mut TestAppContext) {
         const SERVER_1_ID: &str = "mcp-1";
@@ -1551,6 +1758,7 @@ mod tests {
                         ix += 1;
                         *received_event_count.borrow_mut() += 1;
                     }
+                    Event::ElicitationRequested { .. } | Event::ServerMessage { .. } => {}
                 }
             });
             ServerEvents {
@@ -4101,6 +4101,21 @@ impl Repository {
         })
     }
 
+    /// Loads the contents of a file as of HEAD, for files such as images that may not be text.
+    pub fn load_committed_bytes(
+        &mut self,
+        repo_path: RepoPath,
+    ) -> oneshot::Receiver<Result<Option<Vec<u8>>>> {
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
+                    Ok(backend.load_committed_bytes(repo_path).await)
+                }
+                RepositoryState::Remote(..) => anyhow::bail!("not implemented yet"),
+            }
+        })
+    }
+
     pub fn load_commit_diff(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDiff>> {
         let id = self.id;
         self.send_job(None, move |git_repo, cx| async move {
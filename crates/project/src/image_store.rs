@@ -6,12 +6,18 @@ use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet, hash_map};
 use futures::{StreamExt, channel::oneshot};
 use gpui::{
-    App, AsyncApp, Context, Entity, EventEmitter, Img, Subscription, Task, WeakEntity, prelude::*,
+    App, AsyncApp, Context, Entity, EventEmitter, Img, RenderImage, Subscription, Task, WeakEntity,
+    prelude::*,
 };
 pub use image::ImageFormat;
-use image::{ExtendedColorType, GenericImageView, ImageReader};
+use image::{
+    AnimationDecoder as _, ExtendedColorType, Frame, GenericImageView, ImageReader, Rgba32FImage,
+    RgbaImage, codecs::png::PngDecoder,
+};
 use language::{DiskState, File};
 use rpc::{AnyProtoClient, ErrorExt as _, TypedEnvelope, proto};
+use std::borrow::Cow;
+use std::hash::{DefaultHasher, Hash as _, Hasher as _};
 use std::num::NonZeroU64;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -61,9 +67,72 @@ pub struct ImageMetadata {
     pub height: u32,
     pub file_size: u64,
     pub colors: Option<ImageColorInfo>,
-    pub format: ImageFormat,
+    pub format: ImageFileFormat,
+}
+
+/// The file format of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFileFormat {
+    Raster(ImageFormat),
+    /// A Photoshop document, which is shown as its flattened composite image.
+    Psd,
 }
 
+impl ImageFileFormat {
+    /// Detects the format from the image's signature, falling back to its extension for formats
+    /// that don't have one, such as TGA.
+    pub fn detect(content: &[u8], extension: Option<&str>) -> Option<Self> {
+        if content.starts_with(PSD_SIGNATURE) {
+            return Some(Self::Psd);
+        }
+        image::guess_format(content)
+            .ok()
+            .or_else(|| ImageFormat::from_extension(extension?))
+            .map(Self::Raster)
+    }
+
+    pub fn name(&self) -> &'static str {
+        let format = match self {
+            Self::Raster(format) => format,
+            Self::Psd => return "PSD",
+        };
+        match format {
+            ImageFormat::Png => "PNG",
+            ImageFormat::Jpeg => "JPEG",
+            ImageFormat::Gif => "GIF",
+            ImageFormat::WebP => "WebP",
+            ImageFormat::Tiff => "TIFF",
+            ImageFormat::Bmp => "BMP",
+            ImageFormat::Ico => "ICO",
+            ImageFormat::Avif => "Avif",
+            ImageFormat::Tga => "TGA",
+            ImageFormat::Dds => "DDS",
+            ImageFormat::OpenExr => "OpenEXR",
+            ImageFormat::Hdr => "Radiance HDR",
+            ImageFormat::Qoi => "QOI",
+            ImageFormat::Pnm => "PNM",
+            ImageFormat::Farbfeld => "Farbfeld",
+            _ => "Unknown",
+        }
+    }
+
+    /// The format that GPUI decodes itself, if any.
+    fn gpui_format(&self) -> Option<gpui::ImageFormat> {
+        match self {
+            Self::Raster(ImageFormat::Png) => Some(gpui::ImageFormat::Png),
+            Self::Raster(ImageFormat::Jpeg) => Some(gpui::ImageFormat::Jpeg),
+            Self::Raster(ImageFormat::WebP) => Some(gpui::ImageFormat::Webp),
+            Self::Raster(ImageFormat::Gif) => Some(gpui::ImageFormat::Gif),
+            Self::Raster(ImageFormat::Bmp) => Some(gpui::ImageFormat::Bmp),
+            Self::Raster(ImageFormat::Tiff) => Some(gpui::ImageFormat::Tiff),
+            Self::Raster(ImageFormat::Ico) => Some(gpui::ImageFormat::Ico),
+            _ => None,
+        }
+    }
+}
+
+const PSD_SIGNATURE: &[u8] = b"8BPS";
+
 #[derive(Debug, Clone, Copy)]
 pub struct ImageColorInfo {
     pub channels: u8,
@@ -85,6 +154,8 @@ impl ImageColorInfo {
             ExtendedColorType::Bgr8 => (3, 8),
             ExtendedColorType::Bgra8 => (4, 8),
             ExtendedColorType::Cmyk8 => (4, 8),
+            ExtendedColorType::Rgb32F => (3, 32),
+            ExtendedColorType::Rgba32F => (4, 32),
             _ => return None,
         };
 
@@ -94,35 +165,78 @@ impl ImageColorInfo {
         })
     }
 
-    pub const fn bits_per_pixel(&self) -> u8 {
-        self.channels * self.bits_per_channel
+    pub const fn bits_per_pixel(&self) -> u16 {
+        self.channels as u16 * self.bits_per_channel as u16
     }
 }
 
+/// The content of an image file, ready to be shown.
+#[derive(Clone)]
+pub enum ImageContent {
+    /// An image in a format that GPUI decodes itself.
+    Native(Arc<gpui::Image>),
+    /// An image in a format that GPUI can't decode, decoded when the file was loaded.
+    Decoded(Arc<DecodedImage>),
+}
+
+impl ImageContent {
+    /// Whether this is the content of a file with the given bytes.
+    pub fn matches(&self, content: &[u8]) -> bool {
+        match self {
+            ImageContent::Native(image) => image.bytes() == content,
+            ImageContent::Decoded(image) => image.content_hash == hash_content(content),
+        }
+    }
+}
+
+pub struct DecodedImage {
+    pub render_image: Arc<RenderImage>,
+    /// The linear color values of a high dynamic range image, which `render_image` shows at an
+    /// exposure of zero. See [`tone_map`] for showing it at other exposures.
+    pub hdr: Option<Arc<Rgba32FImage>>,
+    content_hash: u64,
+}
+
 pub struct ImageItem {
     pub id: ImageId,
     pub file: Arc<worktree::File>,
-    pub image: Arc<gpui::Image>,
+    pub image: ImageContent,
     reload_task: Option<Task<()>>,
     pub image_metadata: Option<ImageMetadata>,
 }
 
 impl ImageItem {
-    fn compute_metadata_from_bytes(image_bytes: &[u8]) -> Result<ImageMetadata> {
-        let image_format = image::guess_format(image_bytes)?;
-
-        let mut image_reader = ImageReader::new(std::io::Cursor::new(image_bytes));
-        image_reader.set_format(image_format);
-        let image = image_reader.decode()?;
-
-        let (width, height) = image.dimensions();
+    fn compute_metadata_from_bytes(
+        image_bytes: &[u8],
+        extension: Option<&str>,
+    ) -> Result<ImageMetadata> {
+        let format =
+            ImageFileFormat::detect(image_bytes, extension).context("unrecognized image format")?;
+
+        let (width, height, colors) = match format {
+            ImageFileFormat::Raster(image_format) => {
+                let mut image_reader = ImageReader::new(std::io::Cursor::new(image_bytes));
+                image_reader.set_format(image_format);
+                let image = image_reader.decode()?;
+                let (width, height) = image.dimensions();
+                (
+                    width,
+                    height,
+                    ImageColorInfo::from_color_type(image.color()),
+                )
+            }
+            ImageFileFormat::Psd => {
+                let (width, height, colors) = psd_metadata(image_bytes)?;
+                (width, height, Some(colors))
+            }
+        };
 
         Ok(ImageMetadata {
             width,
             height,
             file_size: image_bytes.len() as u64,
-            format: image_format,
-            colors: ImageColorInfo::from_color_type(image.color()),
+            format,
+            colors,
         })
     }
 
@@ -131,17 +245,19 @@ impl ImageItem {
         project: Entity<Project>,
         cx: &mut AsyncApp,
     ) -> Result<ImageMetadata> {
-        let (fs, image_path) = cx.update(|cx| {
+        let (fs, image_path, extension) = cx.update(|cx| {
             let fs = project.read(cx).fs().clone();
-            let image_path = image
-                .read(cx)
-                .abs_path(cx)
-                .context("absolutizing image file path")?;
-            anyhow::Ok((fs, image_path))
+            let image = image.read(cx);
+            let image_path = image.abs_path(cx).context("absolutizing image file path")?;
+            let extension = image.file.path().extension().map(str::to_owned);
+            anyhow::Ok((fs, image_path, extension))
         })?;
 
         let image_bytes = fs.load_bytes(&image_path).await?;
-        Self::compute_metadata_from_bytes(&image_bytes)
+        cx.background_spawn(async move {
+            Self::compute_metadata_from_bytes(&image_bytes, extension.as_deref())
+        })
+        .await
     }
 
     pub fn project_path(&self, cx: &App) -> ProjectPath {
@@ -184,13 +300,18 @@ impl ImageItem {
         let (tx, rx) = futures::channel::oneshot::channel();
 
         let content = local_file.load_bytes(cx);
+        let extension = self.file.path().extension().map(str::to_owned);
         self.reload_task = Some(cx.spawn(async move |this, cx| {
-            if let Some(image) = content
-                .await
-                .context("Failed to load image content")
-                .and_then(create_gpui_image)
-                .log_err()
-            {
+            let image = match content.await.context("Failed to load image content") {
+                Ok(content) => {
+                    cx.background_spawn(
+                        async move { load_image_content(content, extension.as_deref()) },
+                    )
+                    .await
+                }
+                Err(error) => Err(error),
+            };
+            if let Some(image) = image.log_err() {
                 this.update(cx, |this, cx| {
                     this.image = image;
                     cx.emit(ImageItemEvent::Reloaded);
@@ -483,17 +604,19 @@ impl ImageStore {
     ) -> Result<()> {
         if let Some(remote) = self.state.as_remote() {
             let worktree_store = self.worktree_store.clone();
-            let image = remote.update(cx, |remote, cx| {
+            let load_image = remote.update(cx, |remote, cx| {
                 remote.handle_create_image_for_peer(envelope, &worktree_store, cx)
             })?;
-            if let Some(image) = image {
-                remote.update(cx, |this, cx| {
-                    let image = image.clone();
-                    let image_id = image.read(cx).id;
-                    this.loaded_images.insert(image_id, image)
-                });
-
-                self.add_image(image, cx)?;
+            if let Some(load_image) = load_image {
+                cx.spawn(async move |this, cx| {
+                    let image = load_image.await?;
+                    remote.update(cx, |remote, cx| {
+                        let image_id = image.read(cx).id;
+                        remote.loaded_images.insert(image_id, image.clone())
+                    });
+                    this.update(cx, |this, cx| this.add_image(image, cx))?
+                })
+                .detach_and_log_err(cx);
             }
         }
 
@@ -525,7 +648,7 @@ impl RemoteImageStore {
         envelope: TypedEnvelope<proto::CreateImageForPeer>,
         worktree_store: &Entity<WorktreeStore>,
         cx: &mut Context<Self>,
-    ) -> Result<Option<Entity<ImageItem>>> {
+    ) -> Result<Option<Task<Result<Entity<ImageItem>>>>> {
         use proto::create_image_for_peer::Variant;
         match envelope.payload.variant {
             Some(Variant::State(state)) => {
@@ -562,9 +685,6 @@ impl RemoteImageStore {
                         content.extend_from_slice(&chunk_data);
                     }
 
-                    let image_metadata = ImageItem::compute_metadata_from_bytes(&content).log_err();
-                    let image = create_gpui_image(content)?;
-
                     let proto_file = loading.state.file.context("missing file in image state")?;
                     let worktree_id = WorktreeId::from_proto(proto_file.worktree_id);
                     let worktree = worktree_store
@@ -577,21 +697,47 @@ impl RemoteImageStore {
                             .context("invalid file in image state")?,
                     );
 
-                    let entity = cx.new(|_cx| ImageItem {
-                        id: image_id,
-                        file,
-                        image,
-                        image_metadata,
-                        reload_task: None,
-                    });
-
-                    if let Some(listeners) = self.remote_image_listeners.remove(&image_id) {
-                        for listener in listeners {
-                            listener.send(Ok(entity.clone())).ok();
-                        }
-                    }
-
-                    Ok(Some(entity))
+                    let extension = file.path().extension().map(str::to_owned);
+                    Ok(Some(cx.spawn(async move |this, cx| {
+                        let loaded = cx
+                            .background_spawn(async move {
+                                let extension = extension.as_deref();
+                                let image_metadata =
+                                    ImageItem::compute_metadata_from_bytes(&content, extension)
+                                        .log_err();
+                                let image = load_image_content(content, extension)?;
+                                anyhow::Ok((image, image_metadata))
+                            })
+                            .await;
+
+                        this.update(cx, |this, cx| {
+                            let listeners = this
+                                .remote_image_listeners
+                                .remove(&image_id)
+                                .unwrap_or_default();
+                            let (image, image_metadata) = match loaded {
+                                Ok(loaded) => loaded,
+                                Err(error) => {
+                                    for listener in listeners {
+                                        listener.send(Err(anyhow::anyhow!("{error:#}"))).ok();
+                                    }
+                                    return Err(error);
+                                }
+                            };
+
+                            let entity = cx.new(|_cx| ImageItem {
+                                id: image_id,
+                                file,
+                                image,
+                                image_metadata,
+                                reload_task: None,
+                            });
+                            for listener in listeners {
+                                listener.send(Ok(entity.clone())).ok();
+                            }
+                            Ok(entity)
+                        })?
+                    })))
                 } else {
                     Ok(None)
                 }
@@ -620,7 +766,10 @@ impl ImageStoreImpl for Entity<LocalImageStore> {
         });
         cx.spawn(async move |image_store, cx| {
             let LoadedBinaryFile { file, content } = load_file.await?;
-            let image = create_gpui_image(content)?;
+            let extension = file.path().extension().map(str::to_owned);
+            let image = cx
+                .background_spawn(async move { load_image_content(content, extension.as_deref()) })
+                .await?;
 
             let entity = cx.new(|cx| ImageItem {
                 id: cx.entity_id().as_non_zero_u64().into(),
@@ -887,22 +1036,249 @@ impl LocalImageStore {
     }
 }
 
-fn create_gpui_image(content: Vec<u8>) -> anyhow::Result<Arc<gpui::Image>> {
-    let format = image::guess_format(&content)?;
+/// Prepares the content of an image file to be shown, decoding the formats that GPUI can't decode
+/// itself. This can be slow for large images, so it should be run in the background.
+pub fn load_image_content(content: Vec<u8>, extension: Option<&str>) -> Result<ImageContent> {
+    let format =
+        ImageFileFormat::detect(&content, extension).context("unrecognized image format")?;
+    // GPUI only shows the first frame of animated PNGs, so those are decoded here.
+    let animated_png = format == ImageFileFormat::Raster(ImageFormat::Png)
+        && PngDecoder::new(std::io::Cursor::new(&content))?.is_apng()?;
+    if let Some(gpui_format) = format.gpui_format()
+        && !animated_png
+    {
+        return Ok(ImageContent::Native(Arc::new(gpui::Image::from_bytes(
+            gpui_format,
+            content,
+        ))));
+    }
 
-    Ok(Arc::new(gpui::Image::from_bytes(
-        match format {
-            image::ImageFormat::Png => gpui::ImageFormat::Png,
-            image::ImageFormat::Jpeg => gpui::ImageFormat::Jpeg,
-            image::ImageFormat::WebP => gpui::ImageFormat::Webp,
-            image::ImageFormat::Gif => gpui::ImageFormat::Gif,
-            image::ImageFormat::Bmp => gpui::ImageFormat::Bmp,
-            image::ImageFormat::Tiff => gpui::ImageFormat::Tiff,
-            image::ImageFormat::Ico => gpui::ImageFormat::Ico,
-            format => anyhow::bail!("Image format {format:?} not supported"),
-        },
+    let (frames, hdr) = match format {
+        ImageFileFormat::Psd => (vec![Frame::new(decode_psd(&content)?)], None),
+        ImageFileFormat::Raster(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(std::io::Cursor::new(&content))?;
+            (decoder.apng()?.into_frames().collect_frames()?, None)
+        }
+        ImageFileFormat::Raster(format @ (ImageFormat::Hdr | ImageFormat::OpenExr)) => {
+            let hdr = image::load_from_memory_with_format(&content, format)?.into_rgba32f();
+            (vec![Frame::new(tone_map(&hdr, 0.))], Some(Arc::new(hdr)))
+        }
+        ImageFileFormat::Raster(format) => (
+            vec![Frame::new(
+                image::load_from_memory_with_format(&content, format)?.into_rgba8(),
+            )],
+            None,
+        ),
+    };
+
+    Ok(ImageContent::Decoded(Arc::new(DecodedImage {
+        render_image: Arc::new(render_image_from_frames(frames)),
+        hdr,
+        content_hash: hash_content(&content),
+    })))
+}
+
+/// Decodes the flattened composite image of a Photoshop document. Only RGB and grayscale documents
+/// with 8 or 16 bits per channel are supported.
+fn decode_psd(content: &[u8]) -> Result<RgbaImage> {
+    const GRAYSCALE: u16 = 1;
+    const RGB: u16 = 3;
+    // Photoshop documents are at most 30,000 pixels wide and high, larger images use PSB instead.
+    const MAX_SIZE: u32 = 30_000;
+
+    let (width, height, colors) = psd_metadata(content)?;
+    anyhow::ensure!(content[4..6] == [0, 1], "PSB documents aren't supported");
+    anyhow::ensure!(
+        width <= MAX_SIZE && height <= MAX_SIZE,
+        "PSD is larger than {MAX_SIZE} pixels"
+    );
+    let mut reader = PsdReader {
         content,
-    )))
+        offset: 24,
+    };
+    let color_channels = match reader.read_u16()? {
+        GRAYSCALE => 1,
+        RGB => 3,
+        mode => anyhow::bail!("unsupported PSD color mode {mode}"),
+    };
+    let bytes_per_sample = match colors.bits_per_channel {
+        8 => 1,
+        16 => 2,
+        bits => anyhow::bail!("unsupported PSD bit depth {bits}"),
+    };
+    let channel_count = usize::from(colors.channels);
+    anyhow::ensure!(
+        channel_count >= color_channels,
+        "PSD has {channel_count} channels"
+    );
+
+    // Skip the color mode data, image resources and layers, each of which starts with its length.
+    for _ in 0..3 {
+        let len = reader.read_u32()?;
+        reader.read(len as usize)?;
+    }
+
+    // The channel after the color channels is the alpha channel, any further ones are masks.
+    let shown_channels = channel_count.min(color_channels + 1);
+    let (width, height) = (width as usize, height as usize);
+    let row_len = width * bytes_per_sample;
+    let channels = match reader.read_u16()? {
+        0 => (0..shown_channels)
+            .map(|_| Ok(Cow::Borrowed(reader.read(row_len * height)?)))
+            .collect::<Result<Vec<_>>>()?,
+        1 => {
+            let row_byte_counts = reader.read(2 * channel_count * height)?;
+            let mut row_byte_counts = row_byte_counts
+                .chunks_exact(2)
+                .map(|count| usize::from(u16::from_be_bytes([count[0], count[1]])));
+            (0..shown_channels)
+                .map(|_| {
+                    let mut channel = Vec::new();
+                    for row_byte_count in row_byte_counts.by_ref().take(height) {
+                        unpack_bits(reader.read(row_byte_count)?, row_len, &mut channel)?;
+                    }
+                    Ok(Cow::Owned(channel))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        compression => anyhow::bail!("unsupported PSD compression {compression}"),
+    };
+
+    let mut image = RgbaImage::new(width as u32, height as u32);
+    for (index, pixel) in image.pixels_mut().enumerate() {
+        // 16-bit samples are big-endian, so their first byte is the most significant one.
+        let sample = |channel: usize| channels[channel][index * bytes_per_sample];
+        let alpha = if shown_channels > color_channels {
+            sample(color_channels)
+        } else {
+            u8::MAX
+        };
+        pixel.0 = if color_channels == 1 {
+            [sample(0), sample(0), sample(0), alpha]
+        } else {
+            [sample(0), sample(1), sample(2), alpha]
+        };
+    }
+    Ok(image)
+}
+
+struct PsdReader<'a> {
+    content: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> PsdReader<'a> {
+    fn read(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .content
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..len))
+            .context("PSD is truncated")?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.read(2)?.try_into()?))
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.read(4)?.try_into()?))
+    }
+}
+
+/// Appends a row compressed with PackBits, which is how PSD compresses image data, to `output`.
+fn unpack_bits(mut input: &[u8], row_len: usize, output: &mut Vec<u8>) -> Result<()> {
+    let end = output.len() + row_len;
+    while let [header, rest @ ..] = input {
+        input = match *header as i8 {
+            -128 => rest,
+            header @ 0.. => {
+                let literal_len = header as usize + 1;
+                anyhow::ensure!(rest.len() >= literal_len, "PSD row is truncated");
+                output.extend_from_slice(&rest[..literal_len]);
+                &rest[literal_len..]
+            }
+            header => {
+                let (&byte, rest) = rest.split_first().context("PSD row is truncated")?;
+                output.extend(std::iter::repeat_n(byte, (1 - header as isize) as usize));
+                rest
+            }
+        };
+        anyhow::ensure!(output.len() <= end, "PSD row is longer than the image");
+    }
+    anyhow::ensure!(output.len() == end, "PSD row is shorter than the image");
+    Ok(())
+}
+
+/// Converts an RGBA image into one GPUI can paint.
+pub fn render_image_from_rgba(image: RgbaImage) -> RenderImage {
+    render_image_from_frames(vec![Frame::new(image)])
+}
+
+fn render_image_from_frames(mut frames: Vec<Frame>) -> RenderImage {
+    // GPUI expects BGRA pixels.
+    for frame in &mut frames {
+        for pixel in frame.buffer_mut().pixels_mut() {
+            pixel.0.swap(0, 2);
+        }
+    }
+    RenderImage::new(frames)
+}
+
+/// Maps the linear colors of a high dynamic range image to sRGB, after scaling them by two to the
+/// power of `exposure`. Colors that are still too bright are clipped.
+pub fn tone_map(image: &Rgba32FImage, exposure: f32) -> RgbaImage {
+    let scale = exposure.exp2();
+    let mut output = RgbaImage::new(image.width(), image.height());
+    for (output, input) in output.pixels_mut().zip(image.pixels()) {
+        let [r, g, b, a] = input.0;
+        output.0 = [
+            encode_srgb(r * scale),
+            encode_srgb(g * scale),
+            encode_srgb(b * scale),
+            (a.clamp(0., 1.) * 255.).round() as u8,
+        ];
+    }
+    output
+}
+
+fn encode_srgb(linear: f32) -> u8 {
+    let linear = linear.clamp(0., 1.);
+    let encoded = if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1. / 2.4) - 0.055
+    };
+    (encoded * 255.).round() as u8
+}
+
+/// Reads the size and color depth from the header of a Photoshop document, without decoding it.
+fn psd_metadata(content: &[u8]) -> Result<(u32, u32, ImageColorInfo)> {
+    anyhow::ensure!(
+        content.len() >= 26 && content.starts_with(PSD_SIGNATURE),
+        "invalid PSD header"
+    );
+    let read_u16 = |offset: usize| u16::from_be_bytes([content[offset], content[offset + 1]]);
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes([
+            content[offset],
+            content[offset + 1],
+            content[offset + 2],
+            content[offset + 3],
+        ])
+    };
+    let colors = ImageColorInfo {
+        channels: read_u16(12).try_into()?,
+        bits_per_channel: read_u16(22).try_into()?,
+    };
+    Ok((read_u32(18), read_u32(14), colors))
+}
+
+fn hash_content(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
 }
 
 #[cfg(test)]
@@ -976,12 +1352,155 @@ mod tests {
             0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
         ];
 
-        let metadata = ImageItem::compute_metadata_from_bytes(&png_bytes).unwrap();
+        let metadata = ImageItem::compute_metadata_from_bytes(&png_bytes, None).unwrap();
 
         assert_eq!(metadata.width, 1);
         assert_eq!(metadata.height, 1);
         assert_eq!(metadata.file_size, png_bytes.len() as u64);
-        assert_eq!(metadata.format, image::ImageFormat::Png);
+        assert_eq!(
+            metadata.format,
+            ImageFileFormat::Raster(image::ImageFormat::Png)
+        );
         assert!(metadata.colors.is_some());
     }
+
+    #[gpui::test]
+    fn test_load_decoded_image_content() {
+        // A 2x1 uncompressed true-color TGA, which has no signature to detect it by.
+        let mut tga_bytes = vec![0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0, 32, 0x28];
+        tga_bytes.extend_from_slice(&[0, 0, 255, 255, 255, 0, 0, 128]);
+
+        assert!(load_image_content(tga_bytes.clone(), None).is_err());
+        let ImageContent::Decoded(image) =
+            load_image_content(tga_bytes.clone(), Some("tga")).unwrap()
+        else {
+            panic!("expected TGA to be decoded ahead of time");
+        };
+        assert!(image.hdr.is_none());
+        // Pixels are stored as BGRA.
+        assert_eq!(
+            image.render_image.as_bytes(0).unwrap(),
+            &[0, 0, 255, 255, 255, 0, 0, 128]
+        );
+        assert!(ImageContent::Decoded(image).matches(&tga_bytes));
+
+        let metadata = ImageItem::compute_metadata_from_bytes(&tga_bytes, Some("tga")).unwrap();
+        assert_eq!((metadata.width, metadata.height), (2, 1));
+        assert_eq!(metadata.format.name(), "TGA");
+    }
+
+    #[test]
+    fn test_load_animated_png() {
+        // A 1x1 animated PNG with a red and a blue frame.
+        #[rustfmt::skip]
+        let apng_bytes = vec![
+            0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+            0x89, 0x00, 0x00, 0x00, 0x08, 0x61, 0x63, 0x54, 0x4C, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+            0x00, 0xF3, 0x8D, 0x93, 0x70, 0x00, 0x00, 0x00, 0x1A, 0x66, 0x63, 0x54, 0x4C, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x01, 0x00, 0x0A, 0x00, 0x00, 0x5A, 0x7F, 0x30, 0xD0, 0x00, 0x00, 0x00, 0x0D, 0x49,
+            0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0xF8, 0xCF, 0xC0, 0xF0, 0x1F, 0x00, 0x05, 0x00, 0x01, 0xFF,
+            0x89, 0x99, 0x3D, 0x1D, 0x00, 0x00, 0x00, 0x1A, 0x66, 0x63, 0x54, 0x4C, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x0A, 0x00, 0x00, 0xC1, 0x0C, 0xDA, 0x04, 0x00, 0x00, 0x00, 0x11, 0x66, 0x64,
+            0x41, 0x54, 0x00, 0x00, 0x00, 0x02, 0x78, 0x9C, 0x63, 0x60, 0x60, 0xF8, 0xFF, 0x1F, 0x00, 0x03,
+            0x02, 0x01, 0xFF, 0xF5, 0x7B, 0xA5, 0xD7, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE,
+            0x42, 0x60, 0x82,
+        ];
+
+        let ImageContent::Decoded(image) = load_image_content(apng_bytes, None).unwrap() else {
+            panic!("expected animated PNG to be decoded ahead of time");
+        };
+        assert_eq!(image.render_image.frame_count(), 2);
+        // Pixels are stored as BGRA.
+        assert_eq!(image.render_image.as_bytes(0).unwrap(), &[0, 0, 255, 255]);
+        assert_eq!(image.render_image.as_bytes(1).unwrap(), &[255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_load_psd() {
+        let psd = |channels: u8, color_mode: u8, image_data: &[u8]| {
+            let mut psd_bytes = PSD_SIGNATURE.to_vec();
+            psd_bytes.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+            // The channel count, a height of 1, a width of 2, the bit depth and the color mode.
+            psd_bytes.extend_from_slice(&[0, channels, 0, 0, 0, 1, 0, 0, 0, 2]);
+            psd_bytes.extend_from_slice(&[0, 8, 0, color_mode]);
+            // Empty color mode data, image resources and layers.
+            psd_bytes.extend_from_slice(&[0; 12]);
+            psd_bytes.extend_from_slice(image_data);
+            psd_bytes
+        };
+        let pixels = |psd_bytes: Vec<u8>| {
+            let ImageContent::Decoded(image) = load_image_content(psd_bytes, None).unwrap() else {
+                panic!("expected PSD to be decoded ahead of time");
+            };
+            image.render_image.as_bytes(0).unwrap().to_vec()
+        };
+
+        // An uncompressed grayscale document, whose extra channel is alpha.
+        let grayscale = psd(2, 1, &[0, 0, 10, 20, 255, 128]);
+        // Pixels are stored as BGRA.
+        assert_eq!(pixels(grayscale), [10, 10, 10, 255, 20, 20, 20, 128]);
+
+        // An RGB document compressed with PackBits, with a repeated red run and literal blues.
+        let rgb = psd(3, 3, &[0, 1, 0, 2, 0, 2, 0, 3, 0xff, 200, 0xff, 0, 1, 5, 6]);
+        assert_eq!(pixels(rgb), [5, 0, 200, 255, 6, 0, 200, 255]);
+
+        // Rows that decompress to more or fewer pixels than the image is wide.
+        let too_long = psd(3, 3, &[0, 1, 0, 2, 0, 2, 0, 2, 0xfe, 200, 0xff, 0, 0xff, 0]);
+        assert!(decode_psd(&too_long).is_err());
+        let too_short = psd(3, 3, &[0, 1, 0, 2, 0, 2, 0, 2, 0, 200, 0xff, 0, 0xff, 0]);
+        assert!(decode_psd(&too_short).is_err());
+    }
+
+    #[test]
+    fn test_load_malformed_psd() {
+        // A header whose sections are longer than the file.
+        let mut psd_bytes = b"8BPS".to_vec();
+        psd_bytes.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0, 1]);
+        psd_bytes.extend_from_slice(&[0, 8, 0, 3, 0, 0, 1, 0]);
+
+        assert!(load_image_content(psd_bytes, None).is_err());
+    }
+
+    #[test]
+    fn test_tone_map() {
+        let hdr = Rgba32FImage::from_raw(
+            3,
+            1,
+            vec![
+                0., 0., 0., 1., //
+                0.5, 0.5, 0.5, 1., //
+                4., 1., 0.25, 0.5,
+            ],
+        )
+        .unwrap();
+
+        let pixels = |image: RgbaImage| image.pixels().map(|pixel| pixel.0).collect::<Vec<_>>();
+        assert_eq!(
+            pixels(tone_map(&hdr, 0.)),
+            [[0, 0, 0, 255], [188, 188, 188, 255], [255, 255, 137, 128]]
+        );
+        assert_eq!(
+            pixels(tone_map(&hdr, 1.)),
+            [[0, 0, 0, 255], [255, 255, 255, 255], [255, 255, 188, 128]]
+        );
+    }
+
+    #[test]
+    fn test_psd_metadata() {
+        let mut header = PSD_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+        header.extend_from_slice(&[0, 4, 0, 0, 0, 30, 0, 0, 0, 40, 0, 16, 0, 3]);
+
+        assert_eq!(
+            ImageFileFormat::detect(&header, Some("png")),
+            Some(ImageFileFormat::Psd)
+        );
+        let (width, height, colors) = psd_metadata(&header).unwrap();
+        assert_eq!((width, height), (40, 30));
+        assert_eq!((colors.channels, colors.bits_per_channel), (4, 16));
+        assert!(psd_metadata(&header[..20]).is_err());
+    }
 }
@@ -130,6 +130,7 @@ use util::{
     redact::redact_command,
     rel_path::RelPath,
 };
+use ztracing::Instrument as _;
 
 pub use fs::*;
 pub use language::Location;
@@ -555,6 +556,10 @@ impl LocalLspStore {
 
                     anyhow::Ok(language_server)
                 }
+                .instrument(ztracing::info_span!(
+                    "start_language_server",
+                    server = &*server_name
+                ))
                 .await;
 
                 match result {
@@ -3019,6 +3024,10 @@ impl LocalLspStore {
         cx: &mut Context<LspStore>,
     ) -> Task<Result<Vec<(Range<Anchor>, Arc<str>)>>> {
         let snapshot = self.buffer_snapshot_for_lsp_version(buffer, server_id, version, cx);
+        let lsp_edits = lsp_edits.into_iter().collect::<Vec<_>>();
+        buffer.update(cx, |buffer, _| {
+            buffer.record_language_server_edits(server_id, version, &lsp_edits)
+        });
         cx.background_spawn(async move {
             let snapshot = snapshot?;
             let mut lsp_edits = lsp_edits
@@ -0,0 +1,641 @@
+//! Forwards TCP ports from the local machine to the remote host.
+//!
+//! Unlike the static `port_forwards` in the SSH connection settings, which are
+//! handed to `ssh -L` when the connection is established, the forwards managed
+//! here are added and removed at runtime and tunnel their traffic over the
+//! existing remote server connection, so they work for every transport.
+
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use futures::{
+    AsyncReadExt as _, AsyncWriteExt as _, FutureExt as _, StreamExt as _, channel::mpsc,
+};
+use gpui::{AsyncApp, Context, Entity, EventEmitter, SharedString, Task};
+use rpc::{
+    AnyProtoClient, TypedEnvelope,
+    proto::{self, REMOTE_SERVER_PROJECT_ID},
+};
+use smol::net::{TcpListener, TcpStream};
+use util::ResultExt as _;
+
+const TUNNEL_READ_BUFFER_SIZE: usize = 16 * 1024;
+/// How many data messages either end of a tunnel may send before the other end
+/// acknowledges writing them, which bounds the memory buffered per tunnel.
+const TUNNEL_WINDOW_SIZE: usize = 16;
+
+pub struct PortForwardStore {
+    client: AnyProtoClient,
+    mode: PortForwardStoreMode,
+    tunnels: HashMap<u64, Tunnel>,
+}
+
+struct Tunnel {
+    data_tx: mpsc::Sender<Vec<u8>>,
+    window_tx: mpsc::Sender<u32>,
+}
+
+struct TunnelReceivers {
+    data_rx: mpsc::Receiver<Vec<u8>>,
+    window_rx: mpsc::Receiver<u32>,
+}
+
+enum PortForwardStoreMode {
+    /// Runs on the remote server, connecting tunnels to the ports they target.
+    Local,
+    /// Runs in the client, accepting connections on forwarded local ports.
+    Remote {
+        forwards: Vec<PortForward>,
+        listening_ports: Vec<ListeningPort>,
+        next_tunnel_id: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortForwardSource {
+    /// Added by hand from the ports panel.
+    Manual,
+    /// Added from a port that was detected listening on the remote host.
+    Detected,
+}
+
+pub struct PortForward {
+    pub local_port: u16,
+    pub remote_host: SharedString,
+    pub remote_port: u16,
+    pub source: PortForwardSource,
+    _accept_connections: Task<()>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListeningPort {
+    pub address: SharedString,
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub process_name: Option<SharedString>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortForwardStoreEvent {
+    ForwardsChanged,
+    ListeningPortsChanged,
+}
+
+impl EventEmitter<PortForwardStoreEvent> for PortForwardStore {}
+
+impl PortForwardStore {
+    pub fn init(client: &AnyProtoClient) {
+        client.add_entity_request_handler(Self::handle_list_listening_ports);
+        client.add_entity_request_handler(Self::handle_open_port_tunnel);
+        client.add_entity_message_handler(Self::handle_port_tunnel_data);
+        client.add_entity_message_handler(Self::handle_close_port_tunnel);
+        client.add_entity_message_handler(Self::handle_port_tunnel_window_update);
+    }
+
+    pub fn local(client: AnyProtoClient) -> Self {
+        Self {
+            client,
+            mode: PortForwardStoreMode::Local,
+            tunnels: HashMap::default(),
+        }
+    }
+
+    pub fn remote(client: AnyProtoClient) -> Self {
+        Self {
+            client,
+            mode: PortForwardStoreMode::Remote {
+                forwards: Vec::new(),
+                listening_ports: Vec::new(),
+                next_tunnel_id: 0,
+            },
+            tunnels: HashMap::default(),
+        }
+    }
+
+    pub fn forwards(&self) -> &[PortForward] {
+        match &self.mode {
+            PortForwardStoreMode::Remote { forwards, .. } => forwards,
+            PortForwardStoreMode::Local => &[],
+        }
+    }
+
+    /// Ports most recently reported as listening on the remote host.
+    pub fn listening_ports(&self) -> &[ListeningPort] {
+        match &self.mode {
+            PortForwardStoreMode::Remote {
+                listening_ports, ..
+            } => listening_ports,
+            PortForwardStoreMode::Local => &[],
+        }
+    }
+
+    pub fn forward_for_remote_port(&self, remote_port: u16) -> Option<&PortForward> {
+        self.forwards()
+            .iter()
+            .find(|forward| forward.remote_port == remote_port)
+    }
+
+    pub fn refresh_listening_ports(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        if !matches!(self.mode, PortForwardStoreMode::Remote { .. }) {
+            return Task::ready(Ok(()));
+        }
+        let request = self.client.request(proto::ListListeningPorts {
+            project_id: REMOTE_SERVER_PROJECT_ID,
+        });
+        cx.spawn(async move |this, cx| {
+            let response = request.await?;
+            let ports = response
+                .ports
+                .into_iter()
+                .filter_map(|port| {
+                    Some(ListeningPort {
+                        address: port.address.into(),
+                        port: u16::try_from(port.port).ok()?,
+                        pid: port.pid,
+                        process_name: port.process_name.map(SharedString::from),
+                    })
+                })
+                .collect::<Vec<_>>();
+            this.update(cx, |this, cx| {
+                if let PortForwardStoreMode::Remote {
+                    listening_ports, ..
+                } = &mut this.mode
+                    && *listening_ports != ports
+                {
+                    *listening_ports = ports;
+                    cx.emit(PortForwardStoreEvent::ListeningPortsChanged);
+                }
+            })
+        })
+    }
+
+    /// Starts forwarding a local port to `remote_host:remote_port` on the remote host.
+    ///
+    /// When no local port is given, the remote port is reused if it is free locally,
+    /// and an ephemeral port is picked otherwise. Resolves to the bound local port.
+    pub fn add_forward(
+        &mut self,
+        local_port: Option<u16>,
+        remote_host: SharedString,
+        remote_port: u16,
+        source: PortForwardSource,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<u16>> {
+        if !matches!(self.mode, PortForwardStoreMode::Remote { .. }) {
+            return Task::ready(Err(anyhow::anyhow!(
+                "ports can only be forwarded to a remote host"
+            )));
+        }
+        cx.spawn(async move |this, cx| {
+            let listener = match local_port {
+                Some(local_port) => bind_local_port(local_port)
+                    .await
+                    .with_context(|| format!("binding local port {local_port}"))?,
+                None => match bind_local_port(remote_port).await {
+                    Ok(listener) => listener,
+                    Err(_) => bind_local_port(0)
+                        .await
+                        .context("binding an ephemeral local port")?,
+                },
+            };
+            let local_port = listener.local_addr()?.port();
+
+            this.update(cx, |this, cx| {
+                let accept_connections = cx.spawn({
+                    let remote_host = remote_host.clone();
+                    async move |this, cx| {
+                        loop {
+                            let stream = match listener.accept().await {
+                                Ok((stream, _)) => stream,
+                                Err(error) => {
+                                    log::error!(
+                                        "failed to accept connection on port {local_port}: {error}"
+                                    );
+                                    break;
+                                }
+                            };
+                            let Ok(open_tunnel) = this.update(cx, |this, cx| {
+                                this.open_tunnel(stream, remote_host.clone(), remote_port, cx)
+                            }) else {
+                                break;
+                            };
+                            open_tunnel.detach_and_log_err(cx);
+                        }
+                    }
+                });
+
+                if let PortForwardStoreMode::Remote { forwards, .. } = &mut this.mode {
+                    forwards.push(PortForward {
+                        local_port,
+                        remote_host,
+                        remote_port,
+                        source,
+                        _accept_connections: accept_connections,
+                    });
+                    cx.emit(PortForwardStoreEvent::ForwardsChanged);
+                }
+                local_port
+            })
+        })
+    }
+
+    /// Stops listening on the given local port. Connections that are already open stay alive.
+    pub fn remove_forward(&mut self, local_port: u16, cx: &mut Context<Self>) {
+        if let PortForwardStoreMode::Remote { forwards, .. } = &mut self.mode {
+            let len = forwards.len();
+            forwards.retain(|forward| forward.local_port != local_port);
+            if forwards.len() != len {
+                cx.emit(PortForwardStoreEvent::ForwardsChanged);
+            }
+        }
+    }
+
+    fn open_tunnel(
+        &mut self,
+        stream: TcpStream,
+        remote_host: SharedString,
+        remote_port: u16,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let PortForwardStoreMode::Remote { next_tunnel_id, .. } = &mut self.mode else {
+            return Task::ready(Ok(()));
+        };
+        let tunnel_id = *next_tunnel_id;
+        *next_tunnel_id += 1;
+
+        // The remote end starts sending as soon as it has connected, which can be before
+        // the response to the open request arrives, so the tunnel must already be registered.
+        let receivers = self.register_tunnel(tunnel_id);
+        let request = self.client.request(proto::OpenPortTunnel {
+            project_id: REMOTE_SERVER_PROJECT_ID,
+            tunnel_id,
+            remote_host: remote_host.to_string(),
+            remote_port: remote_port as u32,
+        });
+        cx.spawn(async move |this, cx| {
+            if let Err(error) = request.await {
+                this.update(cx, |this, _| this.tunnels.remove(&tunnel_id))
+                    .ok();
+                return Err(error.context(format!("connecting to {remote_host}:{remote_port}")));
+            }
+            this.update(cx, |this, cx| {
+                this.run_tunnel(tunnel_id, stream, receivers, cx)
+            })
+        })
+    }
+
+    fn register_tunnel(&mut self, tunnel_id: u64) -> TunnelReceivers {
+        let (data_tx, data_rx) = mpsc::channel(TUNNEL_WINDOW_SIZE);
+        let (window_tx, window_rx) = mpsc::channel(TUNNEL_WINDOW_SIZE);
+        self.tunnels
+            .insert(tunnel_id, Tunnel { data_tx, window_tx });
+        TunnelReceivers { data_rx, window_rx }
+    }
+
+    /// Pumps bytes between `stream` and the other end of the tunnel until either side closes.
+    ///
+    /// Reading from `stream` pauses while [`TUNNEL_WINDOW_SIZE`] data messages are still
+    /// waiting to be written on the other end, so a slow reader applies backpressure to
+    /// the sender instead of growing the buffers in between.
+    fn run_tunnel(
+        &mut self,
+        tunnel_id: u64,
+        stream: TcpStream,
+        receivers: TunnelReceivers,
+        cx: &mut Context<Self>,
+    ) {
+        let TunnelReceivers {
+            mut data_rx,
+            mut window_rx,
+        } = receivers;
+        let client = self.client.clone();
+        cx.spawn(async move |this, cx| {
+            let mut reader = stream.clone();
+            let mut writer = stream.clone();
+            let read = async {
+                let mut buffer = vec![0; TUNNEL_READ_BUFFER_SIZE];
+                let mut unacknowledged = 0;
+                loop {
+                    while let Ok(Some(messages)) = window_rx.try_next() {
+                        unacknowledged = unacknowledged.saturating_sub(messages as usize);
+                    }
+                    while unacknowledged >= TUNNEL_WINDOW_SIZE {
+                        let Some(messages) = window_rx.next().await else {
+                            return anyhow::Ok(());
+                        };
+                        unacknowledged = unacknowledged.saturating_sub(messages as usize);
+                    }
+                    let len = reader.read(&mut buffer).await?;
+                    if len == 0 {
+                        break;
+                    }
+                    client.send(proto::PortTunnelData {
+                        project_id: REMOTE_SERVER_PROJECT_ID,
+                        tunnel_id,
+                        data: buffer[..len].to_vec(),
+                    })?;
+                    unacknowledged += 1;
+                }
+                anyhow::Ok(())
+            };
+            let write = async {
+                while let Some(data) = data_rx.next().await {
+                    writer.write_all(&data).await?;
+                    client.send(proto::PortTunnelWindowUpdate {
+                        project_id: REMOTE_SERVER_PROJECT_ID,
+                        tunnel_id,
+                        messages: 1,
+                    })?;
+                }
+                anyhow::Ok(())
+            };
+            futures::select_biased! {
+                result = read.fuse() => result.log_err(),
+                result = write.fuse() => result.log_err(),
+            };
+            stream.shutdown(std::net::Shutdown::Both).ok();
+
+            this.update(cx, |this, _| {
+                if this.tunnels.remove(&tunnel_id).is_some() {
+                    client
+                        .send(proto::ClosePortTunnel {
+                            project_id: REMOTE_SERVER_PROJECT_ID,
+                            tunnel_id,
+                        })
+                        .log_err();
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    async fn handle_list_listening_ports(
+        _: Entity<Self>,
+        _: TypedEnvelope<proto::ListListeningPorts>,
+        cx: AsyncApp,
+    ) -> Result<proto::ListListeningPortsResponse> {
+        let ports = cx
+            .background_spawn(async move { listening_ports() })
+            .await?
+            .into_iter()
+            .map(|port| proto::ListeningPort {
+                address: port.address.to_string(),
+                port: port.port as u32,
+                pid: port.pid,
+                process_name: port.process_name.map(|name| name.to_string()),
+            })
+            .collect();
+        Ok(proto::ListListeningPortsResponse { ports })
+    }
+
+    async fn handle_open_port_tunnel(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::OpenPortTunnel>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let remote_port =
+            u16::try_from(envelope.payload.remote_port).context("invalid remote port")?;
+        let stream =
+            TcpStream::connect((envelope.payload.remote_host.as_str(), remote_port)).await?;
+        this.update(&mut cx, |this, cx| {
+            let tunnel_id = envelope.payload.tunnel_id;
+            let receivers = this.register_tunnel(tunnel_id);
+            this.run_tunnel(tunnel_id, stream, receivers, cx)
+        });
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_port_tunnel_data(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::PortTunnelData>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, _| {
+            let tunnel_id = envelope.payload.tunnel_id;
+            let Some(tunnel) = this.tunnels.get_mut(&tunnel_id) else {
+                return;
+            };
+            if let Err(error) = tunnel.data_tx.try_send(envelope.payload.data) {
+                this.tunnels.remove(&tunnel_id);
+                if error.is_full() {
+                    log::error!("closing port tunnel {tunnel_id}: peer exceeded its window");
+                    this.client
+                        .send(proto::ClosePortTunnel {
+                            project_id: REMOTE_SERVER_PROJECT_ID,
+                            tunnel_id,
+                        })
+                        .log_err();
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_port_tunnel_window_update(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::PortTunnelWindowUpdate>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, _| {
+            if let Some(tunnel) = this.tunnels.get_mut(&envelope.payload.tunnel_id) {
+                tunnel.window_tx.try_send(envelope.payload.messages).ok();
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_close_port_tunnel(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ClosePortTunnel>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, _| {
+            this.tunnels.remove(&envelope.payload.tunnel_id);
+        });
+        Ok(())
+    }
+}
+
+async fn bind_local_port(port: u16) -> std::io::Result<TcpListener> {
+    TcpListener::bind(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port))).await
+}
+
+/// TCP ports in the `LISTEN` state that are owned by the current user.
+#[cfg(target_os = "linux")]
+fn listening_ports() -> Result<Vec<ListeningPort>> {
+    use std::os::unix::fs::MetadataExt as _;
+
+    let uid = std::fs::metadata("/proc/self")?.uid();
+    let mut sockets = Vec::new();
+    for (path, is_ipv6) in [("/proc/net/tcp", false), ("/proc/net/tcp6", true)] {
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            sockets.extend(
+                parse_proc_net_tcp(&contents, is_ipv6)
+                    .into_iter()
+                    .filter(|socket| socket.uid == uid),
+            );
+        }
+    }
+
+    let processes = socket_owners();
+    let mut ports = sockets
+        .into_iter()
+        .map(|socket| {
+            let process = processes.get(&socket.inode);
+            ListeningPort {
+                address: socket.address.into(),
+                port: socket.port,
+                pid: process.map(|(pid, _)| *pid),
+                process_name: process.map(|(_, name)| name.clone()),
+            }
+        })
+        .collect::<Vec<_>>();
+    ports.sort_by_key(|port| port.port);
+    ports.dedup_by(|a, b| a.port == b.port && a.pid == b.pid);
+    Ok(ports)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn listening_ports() -> Result<Vec<ListeningPort>> {
+    Ok(Vec::new())
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug, PartialEq, Eq)]
+struct ProcNetTcpSocket {
+    address: String,
+    port: u16,
+    uid: u32,
+    inode: u64,
+}
+
+/// Parses the listening sockets out of `/proc/net/tcp` or `/proc/net/tcp6`.
+#[cfg(target_os = "linux")]
+fn parse_proc_net_tcp(contents: &str, is_ipv6: bool) -> Vec<ProcNetTcpSocket> {
+    const TCP_LISTEN: &str = "0A";
+
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.get(3) != Some(&TCP_LISTEN) {
+                return None;
+            }
+            let (address, port) = fields.get(1)?.split_once(':')?;
+            let address = if is_ipv6 {
+                parse_ipv6_address(address)?.to_string()
+            } else {
+                parse_ipv4_address(address)?.to_string()
+            };
+            Some(ProcNetTcpSocket {
+                address,
+                port: u16::from_str_radix(port, 16).ok()?,
+                uid: fields.get(7)?.parse().ok()?,
+                inode: fields.get(9)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Addresses are printed as the in-memory representation of each 32-bit word.
+#[cfg(target_os = "linux")]
+fn parse_ipv4_address(hex: &str) -> Option<Ipv4Addr> {
+    let word = u32::from_str_radix(hex, 16).ok()?;
+    Some(Ipv4Addr::from(word.to_ne_bytes()))
+}
+
+#[cfg(target_os = "linux")]
+fn parse_ipv6_address(hex: &str) -> Option<std::net::Ipv6Addr> {
+    if hex.len() != 32 {
+        return None;
+    }
+    let mut bytes = [0; 16];
+    for (ix, chunk) in bytes.chunks_mut(4).enumerate() {
+        let word = u32::from_str_radix(hex.get(ix * 8..(ix + 1) * 8)?, 16).ok()?;
+        chunk.copy_from_slice(&word.to_ne_bytes());
+    }
+    Some(std::net::Ipv6Addr::from(bytes))
+}
+
+/// Maps socket inodes to the pid and name of a process holding them open.
+#[cfg(target_os = "linux")]
+fn socket_owners() -> HashMap<u64, (u32, SharedString)> {
+    let mut owners = HashMap::default();
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return owners;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|pid| pid.parse().ok()) else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let name = std::fs::read_to_string(entry.path().join("comm"))
+            .map(|comm| SharedString::from(comm.trim().to_string()))
+            .unwrap_or_default();
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            if let Some(inode) = target
+                .to_str()
+                .and_then(|target| target.strip_prefix("socket:["))
+                .and_then(|target| target.strip_suffix(']'))
+                .and_then(|inode| inode.parse().ok())
+            {
+                owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(all(test, target_os = "linux", target_endian = "little"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_tcp() {
+        let tcp = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 31337 1 0000000000000000 100 0 0 10 0
+   1: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1234 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:0BB8 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 0 1 0000000000000000 20 4 30 10 -1
+";
+        assert_eq!(
+            parse_proc_net_tcp(tcp, false),
+            vec![
+                ProcNetTcpSocket {
+                    address: "127.0.0.1".into(),
+                    port: 3000,
+                    uid: 1000,
+                    inode: 31337,
+                },
+                ProcNetTcpSocket {
+                    address: "0.0.0.0".into(),
+                    port: 22,
+                    uid: 0,
+                    inode: 1234,
+                },
+            ]
+        );
+
+        let tcp6 = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0
+";
+        assert_eq!(
+            parse_proc_net_tcp(tcp6, true),
+            vec![ProcNetTcpSocket {
+                address: "::1".into(),
+                port: 8080,
+                uid: 1000,
+                inode: 4242,
+            }]
+        );
+    }
+}
@@ -9,6 +9,7 @@ pub mod image_store;
 pub mod lsp_command;
 pub mod lsp_store;
 mod manifest_tree;
+pub mod port_forward_store;
 pub mod prettier_store;
 pub mod project_search;
 pub mod project_settings;
@@ -98,6 +99,7 @@ use lsp_store::{CompletionDocumentation, LspFormatTarget, OpenLspBufferHandle};
 pub use manifest_tree::ManifestProvidersStore;
 use node_runtime::NodeRuntime;
 use parking_lot::Mutex;
+use port_forward_store::PortForwardStore;
 pub use prettier_store::PrettierStore;
 use project_settings::{ProjectSettings, SettingsObserver, SettingsObserverEvent};
 use remote::{RemoteClient, RemoteConnectionOptions};
@@ -216,6 +218,7 @@ pub struct Project {
     environment: Entity<ProjectEnvironment>,
     settings_observer: Entity<SettingsObserver>,
     toolchain_store: Option<Entity<ToolchainStore>>,
+    port_forward_store: Option<Entity<PortForwardStore>>,
     agent_location: Option<AgentLocation>,
 }
 
@@ -1243,6 +1246,7 @@ impl Project {
 
                 toolchain_store: Some(toolchain_store),
 
+                port_forward_store: None,
                 agent_location: None,
             }
         })
@@ -1401,6 +1405,8 @@ impl Project {
                 )
             });
 
+            let port_forward_store = cx.new(|_| PortForwardStore::remote(remote_proto.clone()));
+
             cx.subscribe(&remote, Self::on_remote_client_event).detach();
 
             let this = Self {
@@ -1459,6 +1465,7 @@ impl Project {
                 search_excluded_history: Self::new_search_history(),
 
                 toolchain_store: Some(toolchain_store),
+                port_forward_store: Some(port_forward_store),
                 agent_location: None,
             };
 
@@ -1471,6 +1478,7 @@ impl Project {
             remote_proto.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &this.breakpoint_store);
             remote_proto.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &this.settings_observer);
             remote_proto.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &this.git_store);
+            remote_proto.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &port_forward_store);
 
             remote_proto.add_entity_message_handler(Self::handle_create_buffer_for_peer);
             remote_proto.add_entity_message_handler(Self::handle_create_image_for_peer);
@@ -1493,6 +1501,7 @@ impl Project {
             DapStore::init(&remote_proto, cx);
             BreakpointStore::init(&remote_proto);
             GitStore::init(&remote_proto);
+            PortForwardStore::init(&remote_proto);
 
             this
         })
@@ -1726,6 +1735,7 @@ impl Project {
                 environment,
                 remotely_created_models: Arc::new(Mutex::new(RemotelyCreatedModels::default())),
                 toolchain_store: None,
+                port_forward_store: None,
                 agent_location: None,
             };
             project.set_role(role, cx);
@@ -5423,6 +5433,11 @@ impl Project {
         &self.git_store
     }
 
+    /// Runtime port forwards to the remote host, present only for remote projects.
+    pub fn port_forward_store(&self) -> Option<&Entity<PortForwardStore>> {
+        self.port_forward_store.as_ref()
+    }
+
     #[cfg(test)]
     fn git_scans_complete(&self, cx: &Context<Self>) -> Task<()> {
         cx.spawn(async move |this, cx| {
@@ -630,6 +630,7 @@ struct Worker {
 }
 
 impl Worker {
+    #[ztracing::instrument(name = "project_search", skip_all)]
     async fn run(self) {
         let (
             input_paths_rx,
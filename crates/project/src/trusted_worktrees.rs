@@ -162,6 +162,10 @@ impl From<RemoteConnectionOptions> for RemoteHostLocation {
                 Some(SharedString::new(docker_connection_options.name)),
                 SharedString::new(docker_connection_options.container_id),
             ),
+            RemoteConnectionOptions::Container(container_connection_options) => (
+                Some(SharedString::new(container_connection_options.name)),
+                SharedString::new(container_connection_options.target),
+            ),
             RemoteConnectionOptions::Mock(mock) => {
                 (None, SharedString::new(format!("mock-{}", mock.id)))
             }
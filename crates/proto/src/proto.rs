@@ -347,6 +347,12 @@ messages!(
     (GetSharedAgentThreadResponse, Foreground),
     (FindSearchCandidatesChunk, Background),
     (FindSearchCandidatesCancelled, Background),
+    (ListListeningPorts, Background),
+    (ListListeningPortsResponse, Background),
+    (OpenPortTunnel, Background),
+    (PortTunnelData, Background),
+    (ClosePortTunnel, Background),
+    (PortTunnelWindowUpdate, Background),
 );
 
 request_messages!(
@@ -537,6 +543,8 @@ request_messages!(
     (TrustWorktrees, Ack),
     (RestrictWorktrees, Ack),
     (FindSearchCandidatesChunk, Ack),
+    (ListListeningPorts, ListListeningPortsResponse),
+    (OpenPortTunnel, Ack),
 );
 
 lsp_messages!(
@@ -714,6 +722,11 @@ entity_messages!(
     RestrictWorktrees,
     FindSearchCandidatesChunk,
     FindSearchCandidatesCancelled,
+    ListListeningPorts,
+    OpenPortTunnel,
+    PortTunnelData,
+    ClosePortTunnel,
+    PortTunnelWindowUpdate,
 );
 
 entity_messages!(
@@ -0,0 +1,554 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
+use gpui::{
+    Action, App, AsyncWindowContext, Context, Entity, EventEmitter, FocusHandle, Focusable, Pixels,
+    SharedString, Subscription, Task, WeakEntity, Window, actions, px,
+};
+use project::{
+    Project,
+    port_forward_store::{PortForwardSource, PortForwardStore, PortForwardStoreEvent},
+};
+use remote::RemoteConnectionOptions;
+use serde::{Deserialize, Serialize};
+use ui::{ListItem, ListItemSpacing, ListSubHeader, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+const PORTS_PANEL_KEY: &str = "PortsPanel";
+const LISTENING_PORTS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+actions!(
+    ports_panel,
+    [
+        /// Toggles focus on the ports panel.
+        ToggleFocus,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<PortsPanel>(window, cx);
+        });
+    })
+    .detach();
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedPortsPanel {
+    width: Option<Pixels>,
+    position: Option<SerializedDockPosition>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum SerializedDockPosition {
+    Left,
+    Bottom,
+    Right,
+}
+
+/// Lists the ports forwarded to the remote host and the ports detected listening there.
+pub struct PortsPanel {
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    port_editor: Entity<Editor>,
+    width: Option<Pixels>,
+    position: DockPosition,
+    error: Option<SharedString>,
+    poll_listening_ports: Option<Task<()>>,
+    pending_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PortsPanel {
+    pub async fn load(
+        workspace: WeakEntity<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<Entity<Self>> {
+        let serialized_panel = cx
+            .background_spawn(async move { KEY_VALUE_STORE.read_kvp(PORTS_PANEL_KEY) })
+            .await
+            .context("loading ports panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedPortsPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            let panel = Self::new(workspace, window, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    if let Some(position) = serialized_panel.position {
+                        panel.position = match position {
+                            SerializedDockPosition::Left => DockPosition::Left,
+                            SerializedDockPosition::Bottom => DockPosition::Bottom,
+                            SerializedDockPosition::Right => DockPosition::Right,
+                        };
+                    }
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let project = workspace.project().clone();
+        cx.new(|cx| {
+            let port_editor = cx.new(|cx| {
+                let mut editor = Editor::single_line(window, cx);
+                editor.set_placeholder_text(
+                    "Forward a port, e.g. 3000 or 8080:localhost:3000",
+                    window,
+                    cx,
+                );
+                editor
+            });
+            let mut subscriptions = Vec::new();
+            if let Some(store) = project.read(cx).port_forward_store() {
+                subscriptions
+                    .push(cx.subscribe(store, |_, _, _: &PortForwardStoreEvent, cx| cx.notify()));
+            }
+            Self {
+                project,
+                focus_handle: cx.focus_handle(),
+                port_editor,
+                width: None,
+                position: DockPosition::Bottom,
+                error: None,
+                poll_listening_ports: None,
+                pending_serialization: Task::ready(None),
+                _subscriptions: subscriptions,
+            }
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let width = self.width;
+        let position = Some(match self.position {
+            DockPosition::Left => SerializedDockPosition::Left,
+            DockPosition::Bottom => SerializedDockPosition::Bottom,
+            DockPosition::Right => SerializedDockPosition::Right,
+        });
+        self.pending_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        PORTS_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedPortsPanel { width, position })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn port_forward_store(&self, cx: &App) -> Option<Entity<PortForwardStore>> {
+        self.project.read(cx).port_forward_store().cloned()
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let input = self.port_editor.read(cx).text(cx);
+        let Some(request) = parse_forward_request(&input) else {
+            self.error = Some(format!("Invalid port: {}", input.trim()).into());
+            cx.notify();
+            return;
+        };
+        self.port_editor
+            .update(cx, |editor, cx| editor.clear(window, cx));
+        self.add_forward(
+            request.local_port,
+            request.remote_host,
+            request.remote_port,
+            PortForwardSource::Manual,
+            cx,
+        );
+    }
+
+    fn add_forward(
+        &mut self,
+        local_port: Option<u16>,
+        remote_host: SharedString,
+        remote_port: u16,
+        source: PortForwardSource,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(store) = self.port_forward_store(cx) else {
+            return;
+        };
+        self.error = None;
+        let task = store.update(cx, |store, cx| {
+            store.add_forward(local_port, remote_host, remote_port, source, cx)
+        });
+        cx.spawn(async move |this, cx| {
+            if let Err(error) = task.await {
+                this.update(cx, |this, cx| {
+                    this.error = Some(format!("{error:#}").into());
+                    cx.notify();
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    fn remove_forward(&mut self, local_port: u16, cx: &mut Context<Self>) {
+        if let Some(store) = self.port_forward_store(cx) {
+            store.update(cx, |store, cx| store.remove_forward(local_port, cx));
+        }
+    }
+
+    fn start_polling(&mut self, cx: &mut Context<Self>) {
+        let Some(store) = self.port_forward_store(cx) else {
+            return;
+        };
+        self.poll_listening_ports = Some(cx.spawn(async move |_, cx| {
+            loop {
+                let refresh = store.update(cx, |store, cx| store.refresh_listening_ports(cx));
+                refresh.await.log_err();
+                cx.background_executor()
+                    .timer(LISTENING_PORTS_POLL_INTERVAL)
+                    .await;
+            }
+        }));
+    }
+
+    fn render_configured_forwards(&self, cx: &App) -> Option<impl IntoElement> {
+        let remote_client = self.project.read(cx).remote_client()?;
+        let RemoteConnectionOptions::Ssh(options) = remote_client.read(cx).connection_options()
+        else {
+            return None;
+        };
+        let port_forwards = options
+            .port_forwards
+            .filter(|forwards| !forwards.is_empty())?;
+        Some(
+            v_flex()
+                .child(ListSubHeader::new("From Connection Settings"))
+                .children(port_forwards.into_iter().enumerate().map(|(ix, forward)| {
+                    ListItem::new(("configured-forward", ix))
+                        .spacing(ListItemSpacing::Sparse)
+                        .start_slot(Icon::new(IconName::Server).color(Color::Muted))
+                        .child(Label::new(format!(
+                            "{}:{} → {}:{}",
+                            forward.local_host.as_deref().unwrap_or("localhost"),
+                            forward.local_port,
+                            forward.remote_host.as_deref().unwrap_or("localhost"),
+                            forward.remote_port,
+                        )))
+                })),
+        )
+    }
+
+    fn render_forwards(&self, store: &PortForwardStore, cx: &Context<Self>) -> impl IntoElement {
+        v_flex()
+            .child(ListSubHeader::new("Forwarded Ports"))
+            .when(store.forwards().is_empty(), |this| {
+                this.child(
+                    ListItem::new("no-forwards").disabled(true).child(
+                        Label::new("No ports are forwarded yet")
+                            .color(Color::Muted)
+                            .size(LabelSize::Small),
+                    ),
+                )
+            })
+            .children(store.forwards().iter().map(|forward| {
+                let local_port = forward.local_port;
+                let url = format!("http://localhost:{local_port}");
+                ListItem::new(("forward", local_port as usize))
+                    .spacing(ListItemSpacing::Sparse)
+                    .start_slot(Icon::new(IconName::Link).color(Color::Accent))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(format!(
+                                "localhost:{local_port} → {}:{}",
+                                forward.remote_host, forward.remote_port
+                            )))
+                            .when(forward.source == PortForwardSource::Detected, |this| {
+                                this.child(
+                                    Label::new("auto")
+                                        .color(Color::Muted)
+                                        .size(LabelSize::Small),
+                                )
+                            }),
+                    )
+                    .end_slot(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                IconButton::new(
+                                    ("open-forward", local_port as usize),
+                                    IconName::ArrowUpRight,
+                                )
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Open in Browser"))
+                                .on_click(move |_, _, cx| cx.open_url(&url)),
+                            )
+                            .child(
+                                IconButton::new(
+                                    ("remove-forward", local_port as usize),
+                                    IconName::Close,
+                                )
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Stop Forwarding"))
+                                .on_click(cx.listener(
+                                    move |this, _, _, cx| this.remove_forward(local_port, cx),
+                                )),
+                            ),
+                    )
+            }))
+    }
+
+    fn render_listening_ports(
+        &self,
+        store: &PortForwardStore,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let detected = store
+            .listening_ports()
+            .iter()
+            .filter(|port| store.forward_for_remote_port(port.port).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .child(ListSubHeader::new("Detected on Remote"))
+            .when(detected.is_empty(), |this| {
+                this.child(
+                    ListItem::new("no-listening-ports").disabled(true).child(
+                        Label::new("No other listening ports detected")
+                            .color(Color::Muted)
+                            .size(LabelSize::Small),
+                    ),
+                )
+            })
+            .children(detected.into_iter().map(|port| {
+                let remote_port = port.port;
+                let process = match (&port.process_name, port.pid) {
+                    (Some(name), Some(pid)) => format!("{name} ({pid})"),
+                    (Some(name), None) => name.to_string(),
+                    (None, Some(pid)) => pid.to_string(),
+                    (None, None) => String::new(),
+                };
+                ListItem::new(("listening-port", remote_port as usize))
+                    .spacing(ListItemSpacing::Sparse)
+                    .start_slot(Icon::new(IconName::Server).color(Color::Muted))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(format!("{}:{}", port.address, remote_port)))
+                            .child(
+                                Label::new(process)
+                                    .color(Color::Muted)
+                                    .size(LabelSize::Small),
+                            ),
+                    )
+                    .end_slot(
+                        Button::new(("forward-port", remote_port as usize), "Forward")
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.add_forward(
+                                    None,
+                                    "localhost".into(),
+                                    remote_port,
+                                    PortForwardSource::Detected,
+                                    cx,
+                                )
+                            })),
+                    )
+            }))
+    }
+}
+
+struct ForwardRequest {
+    local_port: Option<u16>,
+    remote_host: SharedString,
+    remote_port: u16,
+}
+
+/// Accepts `PORT`, `HOST:PORT` and `LOCAL_PORT:HOST:PORT`, mirroring `ssh -L`.
+fn parse_forward_request(input: &str) -> Option<ForwardRequest> {
+    let parts = input.trim().split(':').collect::<Vec<_>>();
+    let (local_port, remote_host, remote_port) = match parts.as_slice() {
+        [remote_port] => (None, "localhost", *remote_port),
+        [remote_host, remote_port] => (None, *remote_host, *remote_port),
+        [local_port, remote_host, remote_port] => {
+            (Some(local_port.parse().ok()?), *remote_host, *remote_port)
+        }
+        _ => return None,
+    };
+    if remote_host.is_empty() {
+        return None;
+    }
+    Some(ForwardRequest {
+        local_port,
+        remote_host: remote_host.to_string().into(),
+        remote_port: remote_port.parse().ok().filter(|port| *port != 0)?,
+    })
+}
+
+impl Panel for PortsPanel {
+    fn persistent_name() -> &'static str {
+        "Ports Panel"
+    }
+
+    fn panel_key() -> &'static str {
+        PORTS_PANEL_KEY
+    }
+
+    fn position(&self, _: &Window, _: &App) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        self.position = position;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn size(&self, _: &Window, _: &App) -> Pixels {
+        self.width.unwrap_or(px(300.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        cx.notify();
+        cx.defer_in(window, |this, _, cx| {
+            this.serialize(cx);
+        });
+    }
+
+    fn icon(&self, _: &Window, _: &App) -> Option<IconName> {
+        Some(IconName::Link)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Ports Panel")
+    }
+
+    fn icon_label(&self, _: &Window, cx: &App) -> Option<String> {
+        let count = self.port_forward_store(cx)?.read(cx).forwards().len();
+        (count > 0).then(|| count.to_string())
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn set_active(&mut self, active: bool, _: &mut Window, cx: &mut Context<Self>) {
+        if active {
+            self.start_polling(cx);
+        } else {
+            self.poll_listening_ports = None;
+        }
+    }
+
+    fn activation_priority(&self) -> u32 {
+        11
+    }
+
+    fn enabled(&self, cx: &App) -> bool {
+        self.project.read(cx).port_forward_store().is_some()
+    }
+}
+
+impl Focusable for PortsPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for PortsPanel {}
+
+impl Render for PortsPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entries = self.port_forward_store(cx).map(|store| {
+            let store = store.read(cx);
+            (
+                self.render_forwards(store, cx),
+                self.render_listening_ports(store, cx),
+            )
+        });
+        v_flex()
+            .id("ports-panel")
+            .key_context("PortsPanel")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .bg(cx.theme().colors().panel_background)
+            .child(
+                h_flex()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(self.port_editor.clone()),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    h_flex()
+                        .px_2()
+                        .py_1()
+                        .child(Label::new(error).color(Color::Error).size(LabelSize::Small)),
+                )
+            })
+            .child(
+                v_flex()
+                    .id("ports-panel-entries")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .p_1()
+                    .children(self.render_configured_forwards(cx))
+                    .when_some(entries, |this, (forwards, listening_ports)| {
+                        this.child(forwards).child(listening_ports)
+                    }),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forward_request() {
+        let request = parse_forward_request("3000").unwrap();
+        assert_eq!(request.local_port, None);
+        assert_eq!(request.remote_host.as_ref(), "localhost");
+        assert_eq!(request.remote_port, 3000);
+
+        let request = parse_forward_request(" db:5432 ").unwrap();
+        assert_eq!(request.local_port, None);
+        assert_eq!(request.remote_host.as_ref(), "db");
+        assert_eq!(request.remote_port, 5432);
+
+        let request = parse_forward_request("8080:127.0.0.1:80").unwrap();
+        assert_eq!(request.local_port, Some(8080));
+        assert_eq!(request.remote_host.as_ref(), "127.0.0.1");
+        assert_eq!(request.remote_port, 80);
+
+        assert!(parse_forward_request("").is_none());
+        assert!(parse_forward_request("0").is_none());
+        assert!(parse_forward_request(":3000").is_none());
+        assert!(parse_forward_request("http").is_none());
+        assert!(parse_forward_request("1:2:3:4").is_none());
+    }
+}
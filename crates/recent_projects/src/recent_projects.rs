@@ -1,6 +1,7 @@
 mod dev_container;
 mod dev_container_suggest;
 pub mod disconnected_overlay;
+pub mod ports_panel;
 mod remote_connections;
 mod remote_servers;
 mod ssh_config;
@@ -25,6 +26,7 @@ use picker::{
     highlighted_match_with_paths::{HighlightedMatch, HighlightedMatchWithPaths},
 };
 pub use remote_connections::RemoteSettings;
+pub use ports_panel::PortsPanel;
 pub use remote_servers::RemoteServerProjects;
 use settings::Settings;
 use std::{path::Path, sync::Arc};
@@ -165,6 +167,7 @@ pub fn init(cx: &mut App) {
     });
 
     cx.observe_new(DisconnectedOverlay::register).detach();
+    ports_panel::init(cx);
 
     cx.on_action(|_: &OpenDevContainer, cx| {
         with_active_or_new_workspace(cx, move |workspace, window, cx| {
@@ -718,7 +721,8 @@ impl PickerDelegate for RecentProjectsDelegate {
                                     Icon::new(match options {
                                         RemoteConnectionOptions::Ssh { .. } => IconName::Server,
                                         RemoteConnectionOptions::Wsl { .. } => IconName::Linux,
-                                        RemoteConnectionOptions::Docker(_) => IconName::Box,
+                                        RemoteConnectionOptions::Docker(_)
+                                        | RemoteConnectionOptions::Container(_) => IconName::Box,
                                         RemoteConnectionOptions::Mock(_) => IconName::Server,
                                     })
                                     .color(Color::Muted)
@@ -24,7 +24,10 @@ use remote::{
 };
 use semver::Version;
 pub use settings::SshConnection;
-use settings::{DevContainerConnection, ExtendingVec, RegisterSetting, Settings, WslConnection};
+use settings::{
+    ContainerConnection, DevContainerConnection, ExtendingVec, RegisterSetting, Settings,
+    WslConnection,
+};
 use theme::ThemeSettings;
 use ui::{
     ActiveTheme, Color, CommonAnimationExt, Context, InteractiveElement, IntoElement, KeyBinding,
@@ -37,6 +40,7 @@ use workspace::{AppState, ModalView, Workspace};
 pub struct RemoteSettings {
     pub ssh_connections: ExtendingVec<SshConnection>,
     pub wsl_connections: ExtendingVec<WslConnection>,
+    pub container_connections: ExtendingVec<ContainerConnection>,
     /// Whether to read ~/.ssh/config for ssh connection sources.
     pub read_ssh_config: bool,
 }
@@ -50,6 +54,10 @@ impl RemoteSettings {
         self.wsl_connections.clone().0.into_iter()
     }
 
+    pub fn container_connections(&self) -> impl Iterator<Item = ContainerConnection> + use<> {
+        self.container_connections.clone().0.into_iter()
+    }
+
     pub fn fill_connection_options_from_settings(&self, options: &mut SshConnectionOptions) {
         for conn in self.ssh_connections() {
             if conn.host == options.host.to_string()
@@ -87,6 +95,7 @@ pub enum Connection {
     Ssh(SshConnection),
     Wsl(WslConnection),
     DevContainer(DevContainerConnection),
+    Container(ContainerConnection),
 }
 
 impl From<Connection> for RemoteConnectionOptions {
@@ -101,6 +110,7 @@ impl From<Connection> for RemoteConnectionOptions {
                     upload_binary_over_docker_exec: false,
                 })
             }
+            Connection::Container(conn) => RemoteConnectionOptions::Container(conn.into()),
         }
     }
 }
@@ -117,12 +127,23 @@ impl From<WslConnection> for Connection {
     }
 }
 
+impl From<ContainerConnection> for Connection {
+    fn from(val: ContainerConnection) -> Self {
+        Connection::Container(val)
+    }
+}
+
 impl Settings for RemoteSettings {
     fn from_settings(content: &settings::SettingsContent) -> Self {
         let remote = &content.remote;
         Self {
             ssh_connections: remote.ssh_connections.clone().unwrap_or_default().into(),
             wsl_connections: remote.wsl_connections.clone().unwrap_or_default().into(),
+            container_connections: remote
+                .container_connections
+                .clone()
+                .unwrap_or_default()
+                .into(),
             read_ssh_config: remote.read_ssh_config.unwrap(),
         }
     }
@@ -309,6 +330,9 @@ impl RemoteConnectionModal {
                 (options.distro_name.clone(), None, true, false)
             }
             RemoteConnectionOptions::Docker(options) => (options.name.clone(), None, false, true),
+            RemoteConnectionOptions::Container(options) => {
+                (options.name.clone(), None, false, true)
+            }
             RemoteConnectionOptions::Mock(options) => {
                 (format!("mock-{}", options.id), None, false, false)
             }
@@ -723,6 +747,9 @@ pub async fn open_remote_project(
                                     RemoteConnectionOptions::Docker(_) => {
                                         "Failed to connect to Dev Container"
                                     }
+                                    RemoteConnectionOptions::Container(_) => {
+                                        "Failed to connect to container"
+                                    }
                                     RemoteConnectionOptions::Mock(_) => {
                                         "Failed to connect to mock server"
                                     }
@@ -785,6 +812,9 @@ pub async fn open_remote_project(
                                 RemoteConnectionOptions::Docker(_) => {
                                     "Failed to connect to Dev Container"
                                 }
+                                RemoteConnectionOptions::Container(_) => {
+                                    "Failed to connect to container"
+                                }
                                 RemoteConnectionOptions::Mock(_) => {
                                     "Failed to connect to mock server"
                                 }
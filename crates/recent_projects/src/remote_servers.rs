@@ -20,8 +20,8 @@ use paths::{global_ssh_config_file, user_ssh_config_file};
 use picker::Picker;
 use project::{Fs, Project};
 use remote::{
-    RemoteClient, RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions,
-    remote_client::ConnectionIdentifier,
+    ContainerConnectionOptions, RemoteClient, RemoteConnectionOptions, SshConnectionOptions,
+    WslConnectionOptions, remote_client::ConnectionIdentifier,
 };
 use settings::{
     RemoteProject, RemoteSettingsContent, Settings as _, SettingsStore, update_settings_file,
@@ -168,6 +168,10 @@ enum ProjectPickerData {
     Wsl {
         distro_name: SharedString,
     },
+    Container {
+        name: SharedString,
+        target: SharedString,
+    },
 }
 
 struct ProjectPicker {
@@ -245,6 +249,10 @@ impl ProjectPicker {
                 connection_string: "".into(),
                 nickname: None,
             },
+            RemoteConnectionOptions::Container(connection) => ProjectPickerData::Container {
+                name: connection.name.clone().into(),
+                target: connection.target.clone().into(),
+            },
             RemoteConnectionOptions::Mock(options) => ProjectPickerData::Ssh {
                 connection_string: format!("mock-{}", options.id).into(),
                 nickname: None,
@@ -312,6 +320,16 @@ impl ProjectPicker {
                                         server.projects.insert(RemoteProject { paths });
                                     };
                                 }
+                                ServerIndex::Container(index) => {
+                                    if let Some(server) = settings
+                                        .remote
+                                        .container_connections
+                                        .as_mut()
+                                        .and_then(|connections| connections.get_mut(index.0))
+                                    {
+                                        server.projects.insert(RemoteProject { paths });
+                                    };
+                                }
                             }
                         });
                     })
@@ -400,6 +418,14 @@ impl gpui::Render for ProjectPicker {
                     is_devcontainer: false,
                 }
                 .render(window, cx),
+                ProjectPickerData::Container { name, target } => SshConnectionHeader {
+                    connection_string: target.clone(),
+                    paths: Default::default(),
+                    nickname: Some(name.clone()),
+                    is_wsl: false,
+                    is_devcontainer: true,
+                }
+                .render(window, cx),
             })
             .child(
                 div()
@@ -428,10 +454,20 @@ impl std::fmt::Display for WslServerIndex {
     }
 }
 
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct ContainerServerIndex(usize);
+impl std::fmt::Display for ContainerServerIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum ServerIndex {
     Ssh(SshServerIndex),
     Wsl(WslServerIndex),
+    Container(ContainerServerIndex),
 }
 impl From<SshServerIndex> for ServerIndex {
     fn from(index: SshServerIndex) -> Self {
@@ -443,6 +479,11 @@ impl From<WslServerIndex> for ServerIndex {
         Self::Wsl(index)
     }
 }
+impl From<ContainerServerIndex> for ServerIndex {
+    fn from(index: ContainerServerIndex) -> Self {
+        Self::Container(index)
+    }
+}
 
 #[derive(Clone)]
 enum RemoteEntry {
@@ -537,7 +578,31 @@ impl DefaultState {
                 }
             });
 
-        let mut servers = ssh_servers.chain(wsl_servers).collect::<Vec<RemoteEntry>>();
+        let container_servers =
+            ssh_settings
+                .container_connections()
+                .enumerate()
+                .map(|(index, connection)| {
+                    let open_folder = NavigableEntry::new(&handle, cx);
+                    let configure = NavigableEntry::new(&handle, cx);
+                    let projects = connection
+                        .projects
+                        .iter()
+                        .map(|project| (NavigableEntry::new(&handle, cx), project.clone()))
+                        .collect();
+                    RemoteEntry::Project {
+                        open_folder,
+                        configure,
+                        projects,
+                        index: ServerIndex::Container(ContainerServerIndex(index)),
+                        connection: connection.into(),
+                    }
+                });
+
+        let mut servers = ssh_servers
+            .chain(wsl_servers)
+            .chain(container_servers)
+            .collect::<Vec<RemoteEntry>>();
 
         if read_ssh_config {
             let mut extra_servers_from_config = ssh_config_servers.clone();
@@ -580,6 +645,11 @@ enum ViewServerOptionsState {
         server_index: WslServerIndex,
         entries: [NavigableEntry; 2],
     },
+    Container {
+        connection: ContainerConnectionOptions,
+        server_index: ContainerServerIndex,
+        entries: [NavigableEntry; 2],
+    },
 }
 
 impl ViewServerOptionsState {
@@ -587,6 +657,7 @@ impl ViewServerOptionsState {
         match self {
             Self::Ssh { entries, .. } => entries,
             Self::Wsl { entries, .. } => entries,
+            Self::Container { entries, .. } => entries,
         }
     }
 }
@@ -933,6 +1004,14 @@ impl RemoteServerProjects {
                     entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
                 }
             }
+            (
+                ServerIndex::Container(server_index),
+                RemoteConnectionOptions::Container(connection),
+            ) => ViewServerOptionsState::Container {
+                connection,
+                server_index,
+                entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
+            },
             _ => {
                 log::error!("server index and connection options mismatch");
                 self.mode = Mode::default_mode(&BTreeSet::default(), cx);
@@ -1139,6 +1218,14 @@ impl RemoteServerProjects {
             Connection::DevContainer(dev_container_options) => {
                 (dev_container_options.name.clone(), None, false)
             }
+            Connection::Container(container_connection) => {
+                let runtime = ContainerConnectionOptions::from(container_connection.clone())
+                    .runtime
+                    .display_name();
+                let aux_label =
+                    SharedString::from(format!("({runtime}: {})", container_connection.target));
+                (container_connection.name.clone(), Some(aux_label), false)
+            }
         };
         v_flex()
             .w_full()
@@ -1332,6 +1419,7 @@ impl RemoteServerProjects {
             match server_ix {
                 ServerIndex::Ssh(index) => format!("ssh-{index}"),
                 ServerIndex::Wsl(index) => format!("wsl-{index}"),
+                ServerIndex::Container(index) => format!("container-{index}"),
             }
         ));
         let container_element_id_base =
@@ -1477,6 +1565,9 @@ impl RemoteServerProjects {
             ServerIndex::Wsl(server) => {
                 self.delete_wsl_project(server, project, cx);
             }
+            ServerIndex::Container(server) => {
+                self.delete_container_project(server, project, cx);
+            }
         }
     }
 
@@ -1524,6 +1615,32 @@ impl RemoteServerProjects {
         });
     }
 
+    fn delete_container_project(
+        &mut self,
+        server: ContainerServerIndex,
+        project: &RemoteProject,
+        cx: &mut Context<Self>,
+    ) {
+        let project = project.clone();
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(server) = setting
+                .container_connections
+                .as_mut()
+                .and_then(|connections| connections.get_mut(server.0))
+            {
+                server.projects.remove(&project);
+            }
+        });
+    }
+
+    fn delete_container(&mut self, server: ContainerServerIndex, cx: &mut Context<Self>) {
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(connections) = setting.container_connections.as_mut() {
+                connections.remove(server.0);
+            }
+        });
+    }
+
     fn add_ssh_server(
         &mut self,
         connection_options: remote::SshConnectionOptions,
@@ -2016,6 +2133,15 @@ impl RemoteServerProjects {
                     }
                     .render(window, cx)
                     .into_any_element(),
+                    ViewServerOptionsState::Container { connection, .. } => SshConnectionHeader {
+                        connection_string: connection.target.clone().into(),
+                        paths: Default::default(),
+                        nickname: Some(connection.name.clone().into()),
+                        is_wsl: false,
+                        is_devcontainer: true,
+                    }
+                    .render(window, cx)
+                    .into_any_element(),
                 })
                 .child(
                     v_flex()
@@ -2044,6 +2170,17 @@ impl RemoteServerProjects {
                                 window,
                                 cx,
                             )),
+                            ViewServerOptionsState::Container {
+                                connection,
+                                entries,
+                                server_index,
+                            } => this.child(self.render_edit_container(
+                                connection,
+                                *server_index,
+                                entries,
+                                window,
+                                cx,
+                            )),
                         })
                         .child(ListSeparator)
                         .child({
@@ -2152,6 +2289,79 @@ impl RemoteServerProjects {
         })
     }
 
+    fn render_edit_container(
+        &self,
+        connection: &ContainerConnectionOptions,
+        index: ContainerServerIndex,
+        entries: &[NavigableEntry],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let container_name = SharedString::new(connection.name.clone());
+
+        v_flex().child({
+            fn remove_container(
+                remote_servers: Entity<RemoteServerProjects>,
+                index: ContainerServerIndex,
+                container_name: SharedString,
+                window: &mut Window,
+                cx: &mut App,
+            ) {
+                let prompt_message = format!("Remove container `{}`?", container_name);
+
+                let confirmation = window.prompt(
+                    PromptLevel::Warning,
+                    &prompt_message,
+                    None,
+                    &["Yes, remove it", "No, keep it"],
+                    cx,
+                );
+
+                cx.spawn(async move |cx| {
+                    if confirmation.await.ok() == Some(0) {
+                        remote_servers.update(cx, |this, cx| {
+                            this.delete_container(index, cx);
+                        });
+                        remote_servers.update(cx, |this, cx| {
+                            this.mode = Mode::default_mode(&this.ssh_config_servers, cx);
+                            cx.notify();
+                        });
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+            div()
+                .id("container-options-remove-container")
+                .track_focus(&entries[0].focus_handle)
+                .on_action(cx.listener({
+                    let container_name = container_name.clone();
+                    move |_, _: &menu::Confirm, window, cx| {
+                        remove_container(cx.entity(), index, container_name.clone(), window, cx);
+                        cx.focus_self(window);
+                    }
+                }))
+                .child(
+                    ListItem::new("remove-container")
+                        .toggle_state(entries[0].focus_handle.contains_focused(window, cx))
+                        .inset(true)
+                        .spacing(ui::ListItemSpacing::Sparse)
+                        .start_slot(Icon::new(IconName::Trash).color(Color::Error))
+                        .child(Label::new("Remove Container").color(Color::Error))
+                        .on_click(cx.listener(move |_, _, window, cx| {
+                            remove_container(
+                                cx.entity(),
+                                index,
+                                container_name.clone(),
+                                window,
+                                cx,
+                            );
+                            cx.focus_self(window);
+                        })),
+                )
+        })
+    }
+
     fn render_edit_ssh(
         &self,
         connection: &SshConnectionOptions,
@@ -2387,7 +2597,18 @@ impl RemoteServerProjects {
                 _ => None,
             }));
 
-        if ssh_connections_changed || wsl_connections_changed {
+        let container_connections_changed = ssh_settings.container_connections.0.iter().ne(state
+            .servers
+            .iter()
+            .filter_map(|server| match server {
+                RemoteEntry::Project {
+                    connection: Connection::Container(connection),
+                    ..
+                } => Some(connection),
+                _ => None,
+            }));
+
+        if ssh_connections_changed || wsl_connections_changed || container_connections_changed {
             should_rebuild = true;
         };
 
@@ -11,6 +11,9 @@ pub use remote_client::{
     RemoteClientEvent, RemoteConnection, RemoteConnectionOptions, RemoteOs, RemotePlatform,
     connect,
 };
+pub use transport::container::{
+    ContainerCommandTemplate, ContainerConnectionOptions, ContainerRuntime,
+};
 pub use transport::docker::DockerConnectionOptions;
 pub use transport::ssh::{SshConnectionOptions, SshPortForwardOption};
 pub use transport::wsl::WslConnectionOptions;
@@ -12,6 +12,7 @@ use gpui::{AppContext as _, AsyncApp, Task};
 use rpc::proto::Envelope;
 use smol::process::Child;
 
+pub mod container;
 pub mod docker;
 #[cfg(any(test, feature = "test-support"))]
 pub mod mock;
@@ -0,0 +1,420 @@
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use util::shell::ShellKind;
+
+/// Options for connecting to a container or pod by running a container runtime's CLI,
+/// e.g. `podman exec` or `kubectl exec`.
+///
+/// The remote server is bootstrapped the same way as for [`crate::DockerConnectionOptions`],
+/// with every `docker` invocation replaced by the runtime's [`ContainerCommandTemplate`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContainerConnectionOptions {
+    pub name: String,
+    pub target: String,
+    pub runtime: ContainerRuntime,
+    pub upload_binary_over_exec: bool,
+}
+
+impl From<settings::ContainerConnection> for ContainerConnectionOptions {
+    fn from(val: settings::ContainerConnection) -> Self {
+        let runtime = match val.runtime {
+            settings::ContainerRuntimeKind::Podman => ContainerRuntime::Podman,
+            settings::ContainerRuntimeKind::Nerdctl => ContainerRuntime::Nerdctl,
+            settings::ContainerRuntimeKind::Kubectl => ContainerRuntime::Kubectl {
+                namespace: val.namespace,
+                container: val.container,
+            },
+            settings::ContainerRuntimeKind::Custom => {
+                ContainerRuntime::Custom(val.command.unwrap_or_default().into())
+            }
+        };
+        ContainerConnectionOptions {
+            name: val.name.into(),
+            target: val.target.into(),
+            runtime,
+            upload_binary_over_exec: val.upload_binary_over_exec.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerRuntime {
+    Podman,
+    Nerdctl,
+    Kubectl {
+        namespace: Option<String>,
+        container: Option<String>,
+    },
+    Custom(ContainerCommandTemplate),
+}
+
+impl ContainerRuntime {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Podman => "Podman",
+            ContainerRuntime::Nerdctl => "nerdctl",
+            ContainerRuntime::Kubectl { .. } => "kubectl",
+            ContainerRuntime::Custom(_) => "Container",
+        }
+    }
+
+    pub fn command_template(&self) -> ContainerCommandTemplate {
+        match self {
+            ContainerRuntime::Podman => ContainerCommandTemplate::podman(),
+            ContainerRuntime::Nerdctl => ContainerCommandTemplate::nerdctl(),
+            ContainerRuntime::Kubectl {
+                namespace,
+                container,
+            } => ContainerCommandTemplate::kubectl(namespace.as_deref(), container.as_deref()),
+            ContainerRuntime::Custom(template) => template.clone(),
+        }
+    }
+}
+
+/// Describes how to run a command inside a container and how to copy files into it.
+///
+/// `{target}` in `exec_args` and `copy_args` is replaced with the container (or pod) name.
+/// The stdin, TTY, working directory and environment flags are inserted right before
+/// the argument containing `{target}`, and the command to run is appended at the end.
+/// Runtimes without working directory or environment flags get the command wrapped in
+/// `sh -c 'cd <dir> && exec env <NAME=value> "$@"'` instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct ContainerCommandTemplate {
+    pub program: String,
+    pub exec_args: Vec<String>,
+    pub copy_args: Vec<String>,
+    pub stdin_args: Vec<String>,
+    pub tty_args: Vec<String>,
+    pub workdir_args: Option<Vec<String>>,
+    pub env_args: Option<Vec<String>>,
+}
+
+impl From<settings::ContainerCommandContent> for ContainerCommandTemplate {
+    fn from(val: settings::ContainerCommandContent) -> Self {
+        ContainerCommandTemplate {
+            program: val.program,
+            exec_args: val.exec_args,
+            copy_args: val.copy_args,
+            stdin_args: val.stdin_args.unwrap_or_else(|| vec!["-i".to_string()]),
+            tty_args: val.tty_args.unwrap_or_else(|| vec!["-t".to_string()]),
+            workdir_args: val.workdir_args,
+            env_args: val.env_args,
+        }
+    }
+}
+
+impl ContainerCommandTemplate {
+    pub fn docker() -> Self {
+        Self {
+            // Archive mode is required to assign the file ownership to the default docker exec user
+            copy_args: args(["cp", "-a", "{src}", "{target}:{dest}"]),
+            ..Self::docker_compatible("docker")
+        }
+    }
+
+    pub fn podman() -> Self {
+        Self::docker_compatible("podman")
+    }
+
+    pub fn nerdctl() -> Self {
+        Self::docker_compatible("nerdctl")
+    }
+
+    pub fn kubectl(namespace: Option<&str>, container: Option<&str>) -> Self {
+        let mut selector = Vec::new();
+        if let Some(namespace) = namespace {
+            selector.extend(["-n".to_string(), namespace.to_string()]);
+        }
+        if let Some(container) = container {
+            selector.extend(["-c".to_string(), container.to_string()]);
+        }
+
+        let mut exec_args = vec!["exec".to_string()];
+        exec_args.extend(selector.iter().cloned());
+        exec_args.extend(args(["{target}", "--"]));
+
+        let mut copy_args = vec!["cp".to_string()];
+        copy_args.extend(selector);
+        copy_args.extend(args(["{src}", "{target}:{dest}"]));
+
+        Self {
+            program: "kubectl".to_string(),
+            exec_args,
+            copy_args,
+            stdin_args: args(["-i"]),
+            tty_args: args(["-t"]),
+            workdir_args: None,
+            env_args: None,
+        }
+    }
+
+    fn docker_compatible(program: &str) -> Self {
+        Self {
+            program: program.to_string(),
+            exec_args: args(["exec", "{target}"]),
+            copy_args: args(["cp", "{src}", "{target}:{dest}"]),
+            stdin_args: args(["-i"]),
+            tty_args: args(["-t"]),
+            workdir_args: Some(args(["-w", "{dir}"])),
+            env_args: Some(args(["-e", "{env}"])),
+        }
+    }
+
+    /// Builds the arguments (excluding [`Self::program`]) that run `program` in the container.
+    pub(crate) fn exec_args(
+        &self,
+        target: &str,
+        working_dir: Option<&str>,
+        env: &HashMap<String, String>,
+        stdin: bool,
+        tty: bool,
+        program: &str,
+        program_args: &[impl AsRef<str>],
+    ) -> Result<Vec<String>> {
+        let mut flags = Vec::new();
+        if stdin {
+            flags.extend(self.stdin_args.iter().cloned());
+        }
+        if tty {
+            flags.extend(self.tty_args.iter().cloned());
+        }
+
+        let mut wrapped_working_dir = None;
+        if let Some(dir) = working_dir {
+            match &self.workdir_args {
+                Some(workdir_args) => {
+                    flags.extend(workdir_args.iter().map(|arg| arg.replace("{dir}", dir)))
+                }
+                None => wrapped_working_dir = Some(dir),
+            }
+        }
+
+        let mut wrapped_env = Vec::new();
+        for (name, value) in env {
+            let declaration = format!("{name}={value}");
+            match &self.env_args {
+                Some(env_args) => flags.extend(
+                    env_args
+                        .iter()
+                        .map(|arg| arg.replace("{env}", &declaration)),
+                ),
+                None => wrapped_env.push(declaration),
+            }
+        }
+
+        let mut args = Vec::with_capacity(self.exec_args.len() + flags.len());
+        let mut inserted_flags = false;
+        for arg in &self.exec_args {
+            if !inserted_flags && arg.contains("{target}") {
+                args.append(&mut flags);
+                inserted_flags = true;
+            }
+            args.push(arg.replace("{target}", target));
+        }
+        anyhow::ensure!(
+            inserted_flags,
+            "container exec arguments {:?} must contain {{target}}",
+            self.exec_args
+        );
+
+        if wrapped_working_dir.is_none() && wrapped_env.is_empty() {
+            args.push(program.to_string());
+            args.extend(program_args.iter().map(|arg| arg.as_ref().to_string()));
+            return Ok(args);
+        }
+
+        let shell_kind = ShellKind::Posix;
+        let mut script = String::new();
+        if let Some(dir) = wrapped_working_dir {
+            let dir = match dir.strip_prefix("$HOME/") {
+                Some(relative) => format!(
+                    "\"$HOME\"/{}",
+                    shell_kind.try_quote(relative).context("shell quoting")?
+                ),
+                None => shell_kind
+                    .try_quote(dir)
+                    .context("shell quoting")?
+                    .into_owned(),
+            };
+            script.push_str(&format!("cd {dir} && "));
+        }
+        script.push_str("exec ");
+        if !wrapped_env.is_empty() {
+            script.push_str("env ");
+            for declaration in &wrapped_env {
+                let declaration = shell_kind.try_quote(declaration).context("shell quoting")?;
+                script.push_str(&format!("{declaration} "));
+            }
+        }
+        script.push_str("\"$@\"");
+
+        args.extend(["sh".to_string(), "-c".to_string(), script, "sh".to_string()]);
+        args.push(program.to_string());
+        args.extend(program_args.iter().map(|arg| arg.as_ref().to_string()));
+        Ok(args)
+    }
+
+    /// Builds the arguments (excluding [`Self::program`]) that copy `src` into the container at `dest`.
+    pub(crate) fn copy_args(&self, target: &str, src: &str, dest: &str) -> Vec<String> {
+        self.copy_args
+            .iter()
+            .map(|arg| {
+                arg.replace("{target}", target)
+                    .replace("{src}", src)
+                    .replace("{dest}", dest)
+            })
+            .collect()
+    }
+}
+
+fn args<const N: usize>(args: [&str; N]) -> Vec<String> {
+    args.into_iter().map(ToString::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docker_compatible_exec_args() -> Result<()> {
+        let mut env = HashMap::default();
+        env.insert("RUST_LOG".to_string(), "info".to_string());
+
+        let args = ContainerCommandTemplate::podman().exec_args(
+            "my-container",
+            Some("/home/user"),
+            &env,
+            true,
+            false,
+            "/home/user/.zed_server/zed-remote-server",
+            &["proxy", "--identifier", "abc"],
+        )?;
+        assert_eq!(
+            args,
+            [
+                "exec",
+                "-i",
+                "-w",
+                "/home/user",
+                "-e",
+                "RUST_LOG=info",
+                "my-container",
+                "/home/user/.zed_server/zed-remote-server",
+                "proxy",
+                "--identifier",
+                "abc",
+            ]
+        );
+
+        let args = ContainerCommandTemplate::docker().exec_args(
+            "abc123",
+            None,
+            &HashMap::default(),
+            true,
+            true,
+            "/bin/bash",
+            &["-l"],
+        )?;
+        assert_eq!(args, ["exec", "-i", "-t", "abc123", "/bin/bash", "-l"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_kubectl_exec_args_wrap_working_dir_and_env() -> Result<()> {
+        let template = ContainerCommandTemplate::kubectl(Some("dev"), Some("app"));
+
+        let args = template.exec_args(
+            "web-0",
+            None,
+            &HashMap::default(),
+            false,
+            false,
+            "uname",
+            &["-sm"],
+        )?;
+        assert_eq!(
+            args,
+            [
+                "exec", "-n", "dev", "-c", "app", "web-0", "--", "uname", "-sm"
+            ]
+        );
+
+        let mut env = HashMap::default();
+        env.insert("FOO".to_string(), "bar baz".to_string());
+        let args = template.exec_args(
+            "web-0",
+            Some("$HOME/my project"),
+            &env,
+            true,
+            true,
+            "/bin/zsh",
+            &["-l"],
+        )?;
+        assert_eq!(
+            args,
+            [
+                "exec",
+                "-n",
+                "dev",
+                "-c",
+                "app",
+                "-i",
+                "-t",
+                "web-0",
+                "--",
+                "sh",
+                "-c",
+                "cd \"$HOME\"/'my project' && exec env 'FOO=bar baz' \"$@\"",
+                "sh",
+                "/bin/zsh",
+                "-l",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_args() {
+        assert_eq!(
+            ContainerCommandTemplate::docker().copy_args("abc123", "/tmp/server.gz", "/root/x.gz"),
+            ["cp", "-a", "/tmp/server.gz", "abc123:/root/x.gz"]
+        );
+        assert_eq!(
+            ContainerCommandTemplate::kubectl(Some("dev"), None).copy_args(
+                "web-0",
+                "/tmp/server.gz",
+                "/root/x.gz"
+            ),
+            ["cp", "-n", "dev", "/tmp/server.gz", "web-0:/root/x.gz"]
+        );
+    }
+
+    #[test]
+    fn test_custom_template_requires_target() {
+        let template = ContainerCommandTemplate {
+            program: "lxc".to_string(),
+            exec_args: args(["exec", "--"]),
+            copy_args: Vec::new(),
+            stdin_args: Vec::new(),
+            tty_args: Vec::new(),
+            workdir_args: None,
+            env_args: None,
+        };
+        assert!(
+            template
+                .exec_args(
+                    "c1",
+                    None,
+                    &HashMap::default(),
+                    false,
+                    false,
+                    "true",
+                    &[] as &[&str]
+                )
+                .is_err()
+        );
+    }
+}
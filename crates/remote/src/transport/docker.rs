@@ -25,7 +25,11 @@ use rpc::proto::Envelope;
 
 use crate::{
     RemoteClientDelegate, RemoteConnection, RemoteConnectionOptions, RemoteOs, RemotePlatform,
-    remote_client::CommandTemplate, transport::parse_platform,
+    remote_client::CommandTemplate,
+    transport::{
+        container::{ContainerCommandTemplate, ContainerConnectionOptions},
+        parse_platform,
+    },
 };
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
@@ -35,11 +39,16 @@ pub struct DockerConnectionOptions {
     pub upload_binary_over_docker_exec: bool,
 }
 
+/// A connection that runs the remote server through `docker exec`, or through any other
+/// container CLI described by a [`ContainerCommandTemplate`].
 pub(crate) struct DockerExecConnection {
     proxy_process: Mutex<Option<u32>>,
     remote_dir_for_server: String,
     remote_binary_relpath: Option<Arc<RelPath>>,
-    connection_options: DockerConnectionOptions,
+    connection_options: RemoteConnectionOptions,
+    commands: ContainerCommandTemplate,
+    target: String,
+    upload_binary_over_exec: bool,
     remote_platform: Option<RemotePlatform>,
     path_style: Option<PathStyle>,
     shell: Option<String>,
@@ -50,12 +59,50 @@ impl DockerExecConnection {
         connection_options: DockerConnectionOptions,
         delegate: Arc<dyn RemoteClientDelegate>,
         cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        Self::new_with_commands(
+            ContainerCommandTemplate::docker(),
+            connection_options.container_id.clone(),
+            connection_options.upload_binary_over_docker_exec,
+            RemoteConnectionOptions::Docker(connection_options),
+            delegate,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn new_container(
+        connection_options: ContainerConnectionOptions,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        Self::new_with_commands(
+            connection_options.runtime.command_template(),
+            connection_options.target.clone(),
+            connection_options.upload_binary_over_exec,
+            RemoteConnectionOptions::Container(connection_options),
+            delegate,
+            cx,
+        )
+        .await
+    }
+
+    async fn new_with_commands(
+        commands: ContainerCommandTemplate,
+        target: String,
+        upload_binary_over_exec: bool,
+        connection_options: RemoteConnectionOptions,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
     ) -> Result<Self> {
         let mut this = Self {
             proxy_process: Mutex::new(None),
             remote_dir_for_server: "/".to_string(),
             remote_binary_relpath: None,
             connection_options,
+            commands,
+            target,
+            upload_binary_over_exec,
             remote_platform: None,
             path_style: None,
             shell: None,
@@ -210,7 +257,7 @@ impl DockerExecConnection {
             ))
             .unwrap(),
         );
-        if !self.connection_options.upload_binary_over_docker_exec
+        if !self.upload_binary_over_exec
             && let Some(url) = delegate
                 .get_download_url(remote_platform, release_channel, wanted_version.clone(), cx)
                 .await?
@@ -366,13 +413,11 @@ impl DockerExecConnection {
         let src_path_display = src_path.display().to_string();
         let dest_path_str = dest_path.display(self.path_style());
 
-        let mut command = util::command::new_smol_command("docker");
-        command.arg("cp");
-        command.arg("-a");
-        command.arg(&src_path_display);
-        command.arg(format!(
-            "{}:{}/{}",
-            &self.connection_options.container_id, remote_dir_for_server, dest_path_str
+        let mut command = util::command::new_smol_command(&self.commands.program);
+        command.args(self.commands.copy_args(
+            &self.target,
+            &src_path_display,
+            &format!("{}/{}", remote_dir_for_server, dest_path_str),
         ));
 
         let output = command.output().await?;
@@ -381,25 +426,22 @@ impl DockerExecConnection {
             return Ok(());
         }
 
+        let program = &self.commands.program;
         let stderr = String::from_utf8_lossy(&output.stderr);
         log::debug!(
-            "failed to upload file via docker cp {src_path_display} -> {dest_path_str}: {stderr}",
+            "failed to upload file via {program} cp {src_path_display} -> {dest_path_str}: {stderr}",
         );
         anyhow::bail!(
-            "failed to upload file via docker cp {} -> {}: {}",
+            "failed to upload file via {} cp {} -> {}: {}",
+            program,
             src_path_display,
             dest_path_str,
             stderr,
         );
     }
 
-    async fn run_docker_command(
-        &self,
-        subcommand: &str,
-        args: &[impl AsRef<str>],
-    ) -> Result<String> {
-        let mut command = util::command::new_smol_command("docker");
-        command.arg(subcommand);
+    async fn run_docker_command(&self, args: &[impl AsRef<str>]) -> Result<String> {
+        let mut command = util::command::new_smol_command(&self.commands.program);
         for arg in args {
             command.arg(arg.as_ref());
         }
@@ -419,24 +461,16 @@ impl DockerExecConnection {
         env: &HashMap<String, String>,
         program_args: &[impl AsRef<str>],
     ) -> Result<String> {
-        let mut args = match working_directory {
-            Some(dir) => vec!["-w".to_string(), dir.to_string()],
-            None => vec![],
-        };
-
-        for (k, v) in env.iter() {
-            args.push("-e".to_string());
-            let env_declaration = format!("{}={}", k, v);
-            args.push(env_declaration);
-        }
-
-        args.push(self.connection_options.container_id.clone());
-        args.push(inner_program.to_string());
-
-        for arg in program_args {
-            args.push(arg.as_ref().to_owned());
-        }
-        self.run_docker_command("exec", args.as_ref()).await
+        let args = self.commands.exec_args(
+            &self.target,
+            working_directory,
+            env,
+            false,
+            false,
+            inner_program,
+            program_args,
+        )?;
+        self.run_docker_command(&args).await
     }
 
     async fn download_binary_on_server(
@@ -556,32 +590,35 @@ impl RemoteConnection for DockerExecConnection {
             return Task::ready(Err(anyhow!("Remote binary path not set")));
         };
 
-        let mut docker_args = vec!["exec".to_string()];
+        let mut env = HashMap::default();
         for env_var in ["RUST_LOG", "RUST_BACKTRACE", "ZED_GENERATE_MINIDUMPS"] {
             if let Some(value) = std::env::var(env_var).ok() {
-                docker_args.push("-e".to_string());
-                docker_args.push(format!("{}='{}'", env_var, value));
+                env.insert(env_var.to_string(), value);
             }
         }
 
-        docker_args.extend([
-            "-w".to_string(),
-            self.remote_dir_for_server.clone(),
-            "-i".to_string(),
-            self.connection_options.container_id.to_string(),
-        ]);
-
-        let val = remote_binary_relpath
-            .display(self.path_style())
-            .into_owned();
-        docker_args.push(val);
-        docker_args.push("proxy".to_string());
-        docker_args.push("--identifier".to_string());
-        docker_args.push(unique_identifier);
+        let mut proxy_args = vec![
+            "proxy".to_string(),
+            "--identifier".to_string(),
+            unique_identifier,
+        ];
         if reconnect {
-            docker_args.push("--reconnect".to_string());
+            proxy_args.push("--reconnect".to_string());
         }
-        let mut command = util::command::new_smol_command("docker");
+
+        let docker_args = match self.commands.exec_args(
+            &self.target,
+            Some(&self.remote_dir_for_server),
+            &env,
+            true,
+            false,
+            &remote_binary_relpath.display(self.path_style()),
+            proxy_args.as_slice(),
+        ) {
+            Ok(args) => args,
+            Err(e) => return Task::ready(Err(e)),
+        };
+        let mut command = util::command::new_smol_command(&self.commands.program);
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
@@ -616,14 +653,11 @@ impl RemoteConnection for DockerExecConnection {
         let dest_path_str = dest_path.to_string();
         let src_path_display = src_path.display().to_string();
 
-        let mut command = util::command::new_smol_command("docker");
-        command.arg("cp");
-        command.arg("-a"); // Archive mode is required to assign the file ownership to the default docker exec user
-        command.arg(src_path_display);
-        command.arg(format!(
-            "{}:{}",
-            self.connection_options.container_id, dest_path_str
-        ));
+        let mut command = util::command::new_smol_command(&self.commands.program);
+        command.args(
+            self.commands
+                .copy_args(&self.target, &src_path_display, &dest_path_str),
+        );
 
         cx.background_spawn(async move {
             let output = command.output().await?;
@@ -680,25 +714,19 @@ impl RemoteConnection for DockerExecConnection {
             inner_program.push("-l".to_string());
         };
 
-        let mut docker_args = vec!["exec".to_string()];
-
-        if let Some(parsed_working_dir) = parsed_working_dir {
-            docker_args.push("-w".to_string());
-            docker_args.push(parsed_working_dir);
-        }
-
-        for (k, v) in env.iter() {
-            docker_args.push("-e".to_string());
-            docker_args.push(format!("{}={}", k, v));
-        }
-
-        docker_args.push("-it".to_string());
-        docker_args.push(self.connection_options.container_id.to_string());
-
-        docker_args.append(&mut inner_program);
+        let (inner_program, inner_args) = inner_program.split_first().context("no program")?;
+        let docker_args = self.commands.exec_args(
+            &self.target,
+            parsed_working_dir.as_deref(),
+            env,
+            true,
+            true,
+            inner_program,
+            inner_args,
+        )?;
 
         Ok(CommandTemplate {
-            program: "docker".to_string(),
+            program: self.commands.program.clone(),
             args: docker_args,
             // Docker-exec pipes in environment via the "-e" argument
             env: Default::default(),
@@ -713,7 +741,7 @@ impl RemoteConnection for DockerExecConnection {
     }
 
     fn connection_options(&self) -> RemoteConnectionOptions {
-        RemoteConnectionOptions::Docker(self.connection_options.clone())
+        self.connection_options.clone()
     }
 
     fn path_style(&self) -> PathStyle {
@@ -731,3 +759,160 @@ impl RemoteConnection for DockerExecConnection {
         String::from("/bin/sh")
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::transport::container::ContainerRuntime;
+
+    /// A "container runtime" that runs commands on this machine, so that the exec and copy
+    /// round trips can be exercised without a container engine.
+    fn local_connection() -> DockerExecConnection {
+        let commands = ContainerCommandTemplate {
+            program: "sh".to_string(),
+            exec_args: vec![
+                "-c".to_string(),
+                "exec \"$@\"".to_string(),
+                "{target}".to_string(),
+            ],
+            copy_args: vec![
+                "-c".to_string(),
+                "cp \"$1\" \"$2\"".to_string(),
+                "{target}".to_string(),
+                "{src}".to_string(),
+                "{dest}".to_string(),
+            ],
+            stdin_args: Vec::new(),
+            tty_args: Vec::new(),
+            workdir_args: None,
+            env_args: None,
+        };
+        let connection_options = ContainerConnectionOptions {
+            name: "local".to_string(),
+            target: "local".to_string(),
+            runtime: ContainerRuntime::Custom(commands.clone()),
+            upload_binary_over_exec: true,
+        };
+
+        DockerExecConnection {
+            proxy_process: Mutex::new(None),
+            remote_dir_for_server: "/".to_string(),
+            remote_binary_relpath: None,
+            connection_options: RemoteConnectionOptions::Container(connection_options),
+            commands,
+            target: "local".to_string(),
+            upload_binary_over_exec: true,
+            remote_platform: None,
+            path_style: Some(PathStyle::Posix),
+            shell: Some("sh".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_exec_round_trip() -> Result<()> {
+        let connection = local_connection();
+        let dir = tempfile::tempdir()?;
+        let dir_path = dir.path().canonicalize()?;
+        let dir_path = dir_path.to_str().context("non-UTF-8 temp dir")?;
+
+        smol::block_on(async {
+            connection.check_remote_platform().await?;
+
+            let mut env = HashMap::default();
+            env.insert("GREETING".to_string(), "hello world".to_string());
+            let output = connection
+                .run_docker_exec(
+                    "sh",
+                    Some(dir_path),
+                    &env,
+                    &["-c", "echo \"$GREETING\" && pwd"],
+                )
+                .await?;
+            assert_eq!(output, format!("hello world\n{dir_path}\n"));
+
+            let error = connection
+                .run_docker_exec(
+                    "sh",
+                    None,
+                    &HashMap::default(),
+                    &["-c", "echo 'no such file' >&2; exit 3"],
+                )
+                .await
+                .unwrap_err();
+            assert!(
+                error.to_string().contains("no such file"),
+                "unexpected error: {error}"
+            );
+
+            anyhow::Ok(())
+        })
+    }
+
+    #[test]
+    fn test_upload_file() -> Result<()> {
+        let connection = local_connection();
+        let src_dir = tempfile::tempdir()?;
+        let remote_dir = tempfile::tempdir()?;
+        let remote_dir_path = remote_dir.path().to_str().context("non-UTF-8 temp dir")?;
+        let src_path = src_dir.path().join("server.gz");
+        std::fs::write(&src_path, "server contents")?;
+
+        smol::block_on(async {
+            let dest_path = RelPath::unix(".zed_server/server.gz")?;
+            connection
+                .run_docker_exec(
+                    "mkdir",
+                    Some(remote_dir_path),
+                    &HashMap::default(),
+                    &["-p", ".zed_server"],
+                )
+                .await?;
+            connection
+                .upload_file(&src_path, dest_path, remote_dir_path)
+                .await?;
+            assert_eq!(
+                std::fs::read_to_string(remote_dir.path().join(".zed_server/server.gz"))?,
+                "server contents"
+            );
+
+            let missing = src_dir.path().join("missing.gz");
+            assert!(
+                connection
+                    .upload_file(&missing, dest_path, remote_dir_path)
+                    .await
+                    .is_err()
+            );
+
+            anyhow::Ok(())
+        })
+    }
+
+    #[test]
+    fn test_build_command_runs_in_container() -> Result<()> {
+        let connection = local_connection();
+        let dir = tempfile::tempdir()?;
+        let dir_path = dir.path().canonicalize()?;
+
+        let mut env = HashMap::default();
+        env.insert("ZED_TERM".to_string(), "true".to_string());
+        let command = connection.build_command(
+            Some("sh".to_string()),
+            &["-c".to_string(), "echo \"$ZED_TERM\" && pwd".to_string()],
+            &env,
+            Some(dir_path.to_string_lossy().into_owned()),
+            None,
+        )?;
+        assert_eq!(command.program, "sh");
+
+        let output = std::process::Command::new(&command.program)
+            .args(&command.args)
+            .envs(&command.env)
+            .output()?;
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("true\n{}\n", dir_path.display())
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+//! Adds the kernel's description of the symbol under the mouse to hovers in editors with a
+//! REPL session, so values defined at runtime can be inspected alongside the language server's
+//! documentation.
+
+use std::{ops::Range, rc::Rc, time::Duration};
+
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use editor::{Editor, GotoDefinitionKind, SemanticsProvider};
+use gpui::{App, Context, Entity, EntityId, Task};
+use language::{Anchor, BufferId, BufferRow, LanguageBuffer, Point, ToPoint as _};
+use project::{
+    DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint, InvalidationStrategy,
+    LocationLink, ProjectTransaction, lsp_store::CacheInlayHints,
+};
+use runtimelib::{InspectReply, InspectRequest, JupyterMessage, JupyterMessageContent, MimeType};
+
+use crate::{JupyterSettings, KernelStatus, repl_store::ReplStore};
+
+/// How long to wait for the kernel before showing the hover without its inspection.
+const INSPECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Wraps the editor's semantics provider, adding kernel inspections to its hovers.
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let inner = editor.semantics_provider();
+    editor.set_semantics_provider(Some(Rc::new(KernelInspector {
+        editor_id: cx.entity_id(),
+        inner,
+    })));
+}
+
+struct KernelInspector {
+    editor_id: EntityId,
+    inner: Option<Rc<dyn SemanticsProvider>>,
+}
+
+impl KernelInspector {
+    fn inspect(
+        &self,
+        buffer: &Entity<LanguageBuffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Option<Hover>>> {
+        if !JupyterSettings::enabled(cx) {
+            return None;
+        }
+
+        let session = ReplStore::global(cx)
+            .read(cx)
+            .get_session(self.editor_id)?
+            .clone();
+
+        // A busy kernel only answers once the code it's running has finished.
+        if !matches!(session.read(cx).kernel.status(), KernelStatus::Idle) {
+            return None;
+        }
+
+        let snapshot = buffer.read(cx).snapshot();
+        let point = position.to_point(&snapshot);
+        let line = snapshot
+            .text_for_range(
+                Point::new(point.row, 0)..Point::new(point.row, snapshot.line_len(point.row)),
+            )
+            .collect::<String>();
+        let cursor_pos = line.get(..point.column as usize)?.chars().count();
+
+        let request: JupyterMessage = InspectRequest {
+            code: line,
+            cursor_pos,
+            detail_level: Some(0),
+        }
+        .into();
+        let response = session.update(cx, |session, cx| session.request(request, cx));
+        let timeout = cx.background_executor().timer(INSPECT_TIMEOUT);
+
+        Some(cx.background_spawn(async move {
+            let response = smol::future::or(async move { response.await.ok() }, async move {
+                timeout.await;
+                None
+            })
+            .await?;
+
+            let JupyterMessageContent::InspectReply(reply) = response.reply else {
+                return None;
+            };
+            let (text, kind) = inspection(&reply)?;
+            Some(Hover {
+                contents: vec![HoverBlock {
+                    text: strip_ansi(text),
+                    kind,
+                }],
+                range: None,
+                language: None,
+            })
+        }))
+    }
+}
+
+/// Returns the richest text the kernel found for an inspection.
+pub(crate) fn inspection(reply: &InspectReply) -> Option<(&str, HoverBlockKind)> {
+    if !reply.found {
+        return None;
+    }
+
+    let rank = |mimetype: &MimeType| match mimetype {
+        MimeType::Markdown(_) => 2,
+        MimeType::Plain(_) => 1,
+        _ => 0,
+    };
+    match reply.data.richest(rank)? {
+        MimeType::Markdown(text) => Some((text, HoverBlockKind::Markdown)),
+        MimeType::Plain(text) => Some((text, HoverBlockKind::PlainText)),
+        _ => None,
+    }
+}
+
+/// Removes the ANSI escape sequences kernels use to color their inspections.
+fn strip_ansi(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        if char != '\x1b' {
+            result.push(char);
+            continue;
+        }
+
+        // Skip a control sequence up to and including its final byte.
+        if chars.next() == Some('[') {
+            for char in chars.by_ref() {
+                if ('\x40'..='\x7e').contains(&char) {
+                    break;
+                }
+            }
+        }
+    }
+    result
+}
+
+impl SemanticsProvider for KernelInspector {
+    fn hover(
+        &self,
+        buffer: &Entity<LanguageBuffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Option<Vec<Hover>>>> {
+        let hover = self
+            .inner
+            .as_ref()
+            .and_then(|inner| inner.hover(buffer, position, cx));
+        let Some(inspection) = self.inspect(buffer, position, cx) else {
+            return hover;
+        };
+
+        Some(cx.background_spawn(async move {
+            let mut hovers = match hover {
+                Some(hover) => hover.await.unwrap_or_default(),
+                None => Vec::new(),
+            };
+            hovers.extend(inspection.await);
+            (!hovers.is_empty()).then_some(hovers)
+        }))
+    }
+
+    fn inline_values(
+        &self,
+        buffer_handle: Entity<LanguageBuffer>,
+        range: Range<Anchor>,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<InlayHint>>>> {
+        self.inner.as_ref()?.inline_values(buffer_handle, range, cx)
+    }
+
+    fn applicable_inlay_chunks(
+        &self,
+        buffer: &Entity<LanguageBuffer>,
+        ranges: &[Range<Anchor>],
+        cx: &mut App,
+    ) -> Vec<Range<BufferRow>> {
+        self.inner
+            .as_ref()
+            .map(|inner| inner.applicable_inlay_chunks(buffer, ranges, cx))
+            .unwrap_or_default()
+    }
+
+    fn invalidate_inlay_hints(&self, for_buffers: &HashSet<BufferId>, cx: &mut App) {
+        if let Some(inner) = &self.inner {
+            inner.invalidate_inlay_hints(for_buffers, cx);
+        }
+    }
+
+    fn inlay_hints(
+        &self,
+        invalidate: InvalidationStrategy,
+        buffer: Entity<LanguageBuffer>,
+        ranges: Vec<Range<Anchor>>,
+        known_chunks: Option<(clock::Global, HashSet<Range<BufferRow>>)>,
+        cx: &mut App,
+    ) -> Option<HashMap<Range<BufferRow>, Task<Result<CacheInlayHints>>>> {
+        self.inner
+            .as_ref()?
+            .inlay_hints(invalidate, buffer, ranges, known_chunks, cx)
+    }
+
+    fn supports_inlay_hints(&self, buffer: &Entity<LanguageBuffer>, cx: &mut App) -> bool {
+        self.inner
+            .as_ref()
+            .is_some_and(|inner| inner.supports_inlay_hints(buffer, cx))
+    }
+
+    fn document_highlights(
+        &self,
+        buffer: &Entity<LanguageBuffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<DocumentHighlight>>>> {
+        self.inner
+            .as_ref()?
+            .document_highlights(buffer, position, cx)
+    }
+
+    fn definitions(
+        &self,
+        buffer: &Entity<LanguageBuffer>,
+        position: Anchor,
+        kind: GotoDefinitionKind,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Vec<LocationLink>>>>> {
+        self.inner.as_ref()?.definitions(buffer, position, kind, cx)
+    }
+
+    fn range_for_rename(
+        &self,
+        buffer: &Entity<LanguageBuffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Range<Anchor>>>>> {
+        self.inner.as_ref()?.range_for_rename(buffer, position, cx)
+    }
+
+    fn perform_rename(
+        &self,
+        buffer: &Entity<LanguageBuffer>,
+        position: Anchor,
+        new_name: String,
+        cx: &mut App,
+    ) -> Option<Task<Result<ProjectTransaction>>> {
+        self.inner
+            .as_ref()?
+            .perform_rename(buffer, position, new_name, cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            strip_ansi("\x1b[0;31mType:\x1b[0m      int\n\x1b[1mString form:\x1b[22m 1"),
+            "Type:      int\nString form: 1"
+        );
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+}
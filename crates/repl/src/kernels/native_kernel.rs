@@ -227,9 +227,18 @@ impl NativeRunningKernel {
             let shell_task = cx.background_spawn({
                 async move {
                     while let Some(message) = shell_request_rx.next().await {
+                        // Comm messages, which sync widgets, don't get a reply
+                        let expects_reply = !matches!(
+                            message.content,
+                            JupyterMessageContent::CommOpen(_)
+                                | JupyterMessageContent::CommMsg(_)
+                                | JupyterMessageContent::CommClose(_)
+                        );
                         shell_socket.send(message).await.ok();
-                        let reply = shell_socket.read().await?;
-                        shell_reply_tx.send(reply).await?;
+                        if expects_reply {
+                            let reply = shell_socket.read().await?;
+                            shell_reply_tx.send(reply).await?;
+                        }
                     }
                     anyhow::Ok(())
                 }
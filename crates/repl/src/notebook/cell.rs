@@ -1,10 +1,11 @@
 #![allow(unused, dead_code)]
 use std::sync::Arc;
 
-use editor::{Editor, EditorMode, MultiBuffer};
+use editor::{Editor, EditorEvent, EditorMode, MultiBuffer};
 use futures::future::Shared;
 use gpui::{
-    App, Entity, Hsla, RetainAllImageCache, Task, TextStyleRefinement, image_cache, prelude::*,
+    App, Entity, EventEmitter, Hsla, RetainAllImageCache, Subscription, Task, TextStyleRefinement,
+    image_cache, prelude::*,
 };
 use language::{LanguageBuffer, Language, LanguageRegistry};
 use markdown_preview::{markdown_parser::parse_markdown, markdown_renderer::render_markdown_block};
@@ -16,7 +17,7 @@ use util::ResultExt;
 
 use crate::{
     notebook::{CODE_BLOCK_INSET, GUTTER_WIDTH},
-    outputs::{Output, plain::TerminalOutput, user_error::ErrorView},
+    outputs::{ExecutionView, Output, plain::TerminalOutput, user_error::ErrorView},
 };
 
 #[derive(Copy, Clone, PartialEq, PartialOrd)]
@@ -97,10 +98,10 @@ fn convert_outputs(
                 content: cx.new(|cx| TerminalOutput::from(&text.0, window, cx)),
             },
             nbformat::v4::Output::DisplayData(display_data) => {
-                Output::new(&display_data.data, None, window, cx)
+                Output::new(&display_data.data, None, None, window, cx)
             }
             nbformat::v4::Output::ExecuteResult(execute_result) => {
-                Output::new(&execute_result.data, None, window, cx)
+                Output::new(&execute_result.data, None, None, window, cx)
             }
             nbformat::v4::Output::Error(error) => Output::ErrorOutput(ErrorView {
                 ename: error.ename.clone(),
@@ -205,6 +206,13 @@ impl Cell {
                     editor
                 });
 
+                let editor_subscription =
+                    cx.subscribe(&editor_view, |_, _, event: &EditorEvent, cx| {
+                        if let EditorEvent::BufferEdited = event {
+                            cx.emit(CodeCellEvent::Edited);
+                        }
+                    });
+
                 let buffer = buffer.clone();
                 let language_task = cx.spawn_in(window, async move |this, cx| {
                     let language = notebook_language.await;
@@ -221,9 +229,12 @@ impl Cell {
                     source: source.join(""),
                     editor: editor_view,
                     outputs: convert_outputs(outputs, window, cx),
+                    execution: None,
                     selected: false,
+                    stale: false,
                     language_task,
                     cell_position: None,
+                    _editor_subscription: editor_subscription,
                 }
             })),
             nbformat::v4::Cell::Raw {
@@ -428,28 +439,98 @@ impl Render for MarkdownCell {
     }
 }
 
+pub enum CodeCellEvent {
+    /// The user asked for the cell to be run.
+    Run,
+    /// The cell's source was edited.
+    Edited,
+}
+
 pub struct CodeCell {
     id: CellId,
     metadata: CellMetadata,
     execution_count: Option<i32>,
     source: String,
     editor: Entity<editor::Editor>,
+    /// Outputs loaded from the notebook file.
     outputs: Vec<Output>,
+    /// The outputs of the cell's latest run, which replace the loaded ones.
+    execution: Option<Entity<ExecutionView>>,
     selected: bool,
+    /// Whether a cell above this one was edited after this cell last ran.
+    stale: bool,
     cell_position: Option<CellPosition>,
     language_task: Task<()>,
+    _editor_subscription: Subscription,
 }
 
+impl EventEmitter<CodeCellEvent> for CodeCell {}
+
 impl CodeCell {
+    /// Whether the cell's source differs from the source it was loaded with.
     pub fn is_dirty(&self, cx: &App) -> bool {
-        self.editor.read(cx).buffer().read(cx).is_dirty(cx)
+        self.editor.read(cx).text(cx) != self.source
     }
+
     pub fn has_outputs(&self) -> bool {
-        !self.outputs.is_empty()
+        !self.outputs.is_empty() || self.execution.is_some()
     }
 
     pub fn clear_outputs(&mut self) {
         self.outputs.clear();
+        self.execution = None;
+    }
+
+    /// The code to send to the kernel when the cell is run.
+    pub fn code(&self, cx: &App) -> String {
+        self.editor.read(cx).text(cx)
+    }
+
+    /// Shows the outputs of a new run of the cell in place of its previous outputs.
+    pub fn set_execution(&mut self, execution: Entity<ExecutionView>, cx: &mut Context<Self>) {
+        self.outputs.clear();
+        self.execution = Some(execution);
+        cx.notify();
+    }
+
+    pub fn set_stale(&mut self, stale: bool) -> &mut Self {
+        self.stale = stale;
+        self
+    }
+
+    fn render_output_header(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let execution_count = self.execution_count();
+        if execution_count.is_none() && !self.stale {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .gap_2()
+                .pb_1()
+                .children(execution_count.map(|count| {
+                    Label::new(format!("[{count}]"))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .buffer_font(cx)
+                }))
+                .when(self.stale, |this| {
+                    this.child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Icon::new(IconName::Warning)
+                                    .size(IconSize::Small)
+                                    .color(Color::Warning),
+                            )
+                            .child(
+                                Label::new("A cell above was edited after this cell ran")
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                }),
+        )
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -526,10 +607,9 @@ impl RenderableCell for CodeCell {
             CellControl::new("rerun-cell", CellControlType::RerunCell)
         } else {
             CellControl::new("run-cell", CellControlType::RunCell)
-                .on_click(cx.listener(move |this, _, window, cx| this.run(window, cx)))
         };
 
-        Some(cell_control)
+        Some(cell_control.on_click(cx.listener(move |this, _, window, cx| this.run(window, cx))))
     }
 
     fn selected(&self) -> bool {
@@ -553,7 +633,8 @@ impl RenderableCell for CodeCell {
 
 impl RunnableCell for CodeCell {
     fn run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Running code cell: {}", self.id);
+        // The notebook owns the kernel, so it runs the cell.
+        cx.emit(CodeCellEvent::Run);
     }
 
     fn execution_count(&self) -> Option<i32> {
@@ -621,45 +702,58 @@ impl Render for CodeCell {
                                 .border_1()
                                 // .border_color(cx.theme().colors().border)
                                 // .bg(cx.theme().colors().editor_background)
-                                .child(div().w_full().children(self.outputs.iter().map(
-                                    |output| {
-                                        let content = match output {
-                                            Output::Plain { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::Markdown { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::Stream { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::Image { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::Message(message) => Some(
-                                                div().child(message.clone()).into_any_element(),
-                                            ),
-                                            Output::Table { content, .. } => {
-                                                Some(content.clone().into_any_element())
-                                            }
-                                            Output::ErrorOutput(error_view) => {
-                                                error_view.render(window, cx)
-                                            }
-                                            Output::ClearOutputWaitMarker => None,
-                                        };
-
-                                        div()
-                                            // .w_full()
-                                            // .mt_3()
-                                            // .p_3()
-                                            // .rounded_sm()
-                                            // .bg(cx.theme().colors().editor_background)
-                                            // .border(px(1.))
-                                            // .border_color(cx.theme().colors().border)
-                                            // .shadow_xs()
-                                            .children(content)
-                                    },
-                                ))),
+                                .child(
+                                    div()
+                                        .w_full()
+                                        .children(self.render_output_header(cx))
+                                        .children(self.outputs.iter().map(|output| {
+                                            let content = match output {
+                                                Output::Plain { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Markdown { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Stream { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Image { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Message(message) => Some(
+                                                    div().child(message.clone()).into_any_element(),
+                                                ),
+                                                Output::Table { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Svg { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Json { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::Widget { content, .. } => {
+                                                    Some(content.clone().into_any_element())
+                                                }
+                                                Output::ErrorOutput(error_view) => {
+                                                    error_view.render(window, cx)
+                                                }
+                                                Output::ClearOutputWaitMarker => None,
+                                            };
+
+                                            div()
+                                                // .w_full()
+                                                // .mt_3()
+                                                // .p_3()
+                                                // .rounded_sm()
+                                                // .bg(cx.theme().colors().editor_background)
+                                                // .border(px(1.))
+                                                // .border_color(cx.theme().colors().border)
+                                                // .shadow_xs()
+                                                .children(content)
+                                        }))
+                                        .children(self.execution.clone()),
+                                ),
                         ),
                     ),
             )
@@ -4,23 +4,29 @@ use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{Context as _, Result};
 use client::proto::ViewId;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use feature_flags::{FeatureFlagAppExt as _, NotebookFeatureFlag};
 use futures::FutureExt;
 use futures::future::Shared;
 use gpui::{
-    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ListScrollEvent, ListState,
-    Point, Task, actions, list, prelude::*,
+    AnyElement, App, Corner, Entity, EventEmitter, FocusHandle, Focusable, ListScrollEvent,
+    ListState, Point, Subscription, Task, WeakEntity, actions, list, prelude::*,
 };
 use language::{Language, LanguageRegistry};
 use project::{Project, ProjectEntryId, ProjectPath};
-use ui::{Tooltip, prelude::*};
+use ui::{ContextMenu, PopoverMenu, Tooltip, prelude::*};
+use util::ResultExt as _;
 use workspace::item::{ItemEvent, SaveOptions, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
-use workspace::{Item, ItemHandle, Pane, ProjectItem, ToolbarItemLocation};
+use workspace::{Item, ItemHandle, Pane, ProjectItem, ToolbarItemLocation, Workspace};
 use workspace::{ToolbarItemEvent, ToolbarItemView};
 
-use super::{Cell, CellPosition, RenderableCell};
+use super::{Cell, CellPosition, CodeCell, CodeCellEvent, RenderableCell, RunnableCell};
+use crate::{
+    Session,
+    outputs::{ExecutionView, output_json},
+    repl_store::ReplStore,
+};
 
 use nbformat::v4::CellId;
 use nbformat::v4::Metadata as NotebookMetadata;
@@ -32,6 +38,14 @@ actions!(
         OpenNotebook,
         /// Runs all cells in the notebook.
         RunAll,
+        /// Runs the cells above the current cell.
+        RunAbove,
+        /// Runs the current cell and the cells below it.
+        RunBelow,
+        /// Restarts the kernel and runs all cells in the notebook.
+        RestartAndRunAll,
+        /// Interrupts the cell the kernel is running.
+        Interrupt,
         /// Clears all cell outputs.
         ClearOutputs,
         /// Moves the current cell up.
@@ -71,12 +85,23 @@ pub fn init(cx: &mut App) {
     .detach();
 }
 
+/// When a code cell was last edited and last run, as a position in the order of the notebook's
+/// edits and runs.
+#[derive(Default, Clone, Copy)]
+struct CellHistory {
+    edited_at: Option<usize>,
+    ran_at: Option<usize>,
+}
+
 pub struct NotebookEditor {
     languages: Arc<LanguageRegistry>,
     project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
 
     focus_handle: FocusHandle,
     notebook_item: Entity<NotebookItem>,
+    /// The kernel running the notebook's cells, started when a cell is first run.
+    session: Option<Entity<Session>>,
 
     remote_id: Option<ViewId>,
     cell_list: ListState,
@@ -84,6 +109,15 @@ pub struct NotebookEditor {
     selected_cell_index: usize,
     cell_order: Vec<CellId>,
     cell_map: HashMap<CellId, Cell>,
+
+    cell_history: HashMap<CellId, CellHistory>,
+    next_history_position: usize,
+    /// Cells whose outputs may be out of date, because a cell above them was edited after
+    /// they ran.
+    stale_cells: HashSet<CellId>,
+    execution_subscriptions: HashMap<CellId, Subscription>,
+    cell_subscriptions: Vec<Subscription>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl NotebookEditor {
@@ -98,47 +132,143 @@ impl NotebookEditor {
         let languages = project.read(cx).languages().clone();
         let language_name = notebook_item.read(cx).language_name();
 
-        let notebook_language = notebook_item.read(cx).notebook_language();
+        let workspace = Workspace::for_window(window, cx)
+            .map(|workspace| workspace.downgrade())
+            .unwrap_or_else(WeakEntity::new_invalid);
+
+        let mut editor = Self {
+            project,
+            languages,
+            workspace,
+            focus_handle,
+            notebook_item,
+            session: None,
+            remote_id: None,
+            cell_list: ListState::new(0, gpui::ListAlignment::Top, px(1000.)),
+            selected_cell_index: 0,
+            cell_order: Vec::new(),
+            cell_map: HashMap::default(),
+            cell_history: HashMap::default(),
+            next_history_position: 0,
+            stale_cells: HashSet::default(),
+            execution_subscriptions: HashMap::default(),
+            cell_subscriptions: Vec::new(),
+            _subscriptions: Vec::new(),
+        };
+        editor.load_cells(window, cx);
+        editor
+    }
+
+    /// Replaces the editor's cells with the notebook's, forgetting what was run.
+    fn load_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let notebook_language = self.notebook_item.read(cx).notebook_language();
         let notebook_language = cx
             .spawn_in(window, async move |_, _| notebook_language.await)
             .shared();
 
-        let mut cell_order = vec![]; // Vec<CellId>
-        let mut cell_map = HashMap::default(); // HashMap<CellId, Cell>
-
-        for (index, cell) in notebook_item
-            .read(cx)
-            .notebook
-            .clone()
-            .cells
-            .iter()
-            .enumerate()
-        {
-            let cell_id = cell.id();
-            cell_order.push(cell_id.clone());
-            cell_map.insert(
-                cell_id.clone(),
-                Cell::load(cell, &languages, notebook_language.clone(), window, cx),
+        self.cell_order.clear();
+        self.cell_map.clear();
+        self.cell_subscriptions.clear();
+        for cell in self.notebook_item.read(cx).notebook.cells.clone() {
+            let cell_id = cell.id().clone();
+            let cell = Cell::load(
+                &cell,
+                &self.languages,
+                notebook_language.clone(),
+                window,
+                cx,
             );
+            if let Cell::Code(code_cell) = &cell {
+                self.cell_subscriptions.push(cx.subscribe_in(
+                    code_cell,
+                    window,
+                    Self::handle_code_cell_event,
+                ));
+            }
+            self.cell_order.push(cell_id.clone());
+            self.cell_map.insert(cell_id, cell);
         }
 
-        let notebook_handle = cx.entity().downgrade();
-        let cell_count = cell_order.len();
+        self.cell_list.reset(self.cell_order.len());
+        self.selected_cell_index = self
+            .selected_cell_index
+            .min(self.cell_order.len().saturating_sub(1));
+        self.cell_history.clear();
+        self.stale_cells.clear();
+        self.execution_subscriptions.clear();
+    }
 
-        let this = cx.entity();
-        let cell_list = ListState::new(cell_count, gpui::ListAlignment::Top, px(1000.));
+    fn handle_code_cell_event(
+        &mut self,
+        cell: &Entity<CodeCell>,
+        event: &CodeCellEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cell_id = cell.read(cx).id().clone();
+        match event {
+            CodeCellEvent::Run => self.run_cells(vec![cell_id], window, cx),
+            CodeCellEvent::Edited => {
+                let source = cell.read(cx).code(cx);
+                self.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.set_cell_source(&cell_id, &source);
+                });
+                let position = self.next_history_position();
+                self.cell_history.entry(cell_id).or_default().edited_at = Some(position);
+                self.stale_cells = stale_cells(&self.cell_order, &self.cell_history);
+                cx.emit(());
+                cx.notify();
+            }
+        }
+    }
 
-        Self {
-            project,
-            languages: languages.clone(),
-            focus_handle,
-            notebook_item,
-            remote_id: None,
-            cell_list,
-            selected_cell_index: 0,
-            cell_order: cell_order.clone(),
-            cell_map: cell_map.clone(),
+    fn next_history_position(&mut self) -> usize {
+        let position = self.next_history_position;
+        self.next_history_position += 1;
+        position
+    }
+
+    /// Returns the notebook's kernel session, starting a kernel for the notebook's language if
+    /// there isn't one yet.
+    fn session(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Result<Entity<Session>> {
+        if let Some(session) = &self.session {
+            return Ok(session.clone());
         }
+
+        let notebook_item = self.notebook_item.read(cx);
+        let kernel_name = notebook_item
+            .notebook
+            .metadata
+            .kernelspec
+            .as_ref()
+            .map(|kernelspec| kernelspec.name.clone());
+        let language = notebook_item.language_name();
+        let worktree_id = notebook_item.project_path.worktree_id;
+        let working_directory = notebook_item
+            .path
+            .parent()
+            .context("notebook has no parent directory")?
+            .to_path_buf();
+
+        let store = ReplStore::global(cx);
+        let kernel_specification = store
+            .read(cx)
+            .kernelspec_for_notebook(worktree_id, kernel_name.as_deref(), language.as_deref(), cx)
+            .with_context(|| {
+                format!(
+                    "No kernel found for notebook language: {}",
+                    language.as_deref().unwrap_or("unknown")
+                )
+            })?;
+        let fs = store.read(cx).fs().clone();
+
+        let session = cx.new(|cx| {
+            Session::for_notebook(working_directory, fs, kernel_specification, window, cx)
+        });
+        self._subscriptions
+            .push(cx.observe(&session, |_, _, cx| cx.notify()));
+        self.session = Some(session.clone());
+        Ok(session)
     }
 
     fn has_outputs(&self, window: &mut Window, cx: &mut Context<Self>) -> bool {
@@ -159,10 +289,143 @@ impl NotebookEditor {
                 });
             }
         }
+        self.notebook_item.update(cx, |notebook_item, _| {
+            for cell_id in &self.cell_order {
+                notebook_item.set_cell_outputs(cell_id, Vec::new());
+            }
+        });
+        self.execution_subscriptions.clear();
+        cx.emit(());
+        cx.notify();
     }
 
-    fn run_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Cells would all run here, if that was implemented!");
+    /// Queues the given cells to run in order. Empty cells and cells that aren't code are
+    /// skipped.
+    fn run_cells(&mut self, cell_ids: Vec<CellId>, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(session) = self.session(window, cx).log_err() else {
+            return;
+        };
+
+        for cell_id in cell_ids {
+            let Some(Cell::Code(cell)) = self.cell_map.get(&cell_id).cloned() else {
+                continue;
+            };
+            let code = cell.read(cx).code(cx);
+            if code.trim().is_empty() {
+                continue;
+            }
+
+            let workspace = self.workspace.clone();
+            let execution_view =
+                session.update(cx, |session, cx| session.execute_cell(code, workspace, cx));
+            cell.update(cx, |cell, cx| {
+                cell.set_execution(execution_view.clone(), cx)
+            });
+
+            let position = self.next_history_position();
+            self.cell_history.entry(cell_id.clone()).or_default().ran_at = Some(position);
+
+            let subscription = cx.observe(&execution_view, {
+                let cell_id = cell_id.clone();
+                move |this, execution_view, cx| this.record_execution(&cell_id, &execution_view, cx)
+            });
+            self.execution_subscriptions.insert(cell_id, subscription);
+        }
+
+        self.stale_cells = stale_cells(&self.cell_order, &self.cell_history);
+        cx.notify();
+    }
+
+    fn run_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.run_cells(self.cell_order.clone(), window, cx);
+    }
+
+    fn run_above(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index.min(self.cell_order.len());
+        self.run_cells(self.cell_order[..index].to_vec(), window, cx);
+    }
+
+    fn run_below(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index.min(self.cell_order.len());
+        self.run_cells(self.cell_order[index..].to_vec(), window, cx);
+    }
+
+    fn restart_and_run_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        // Without a session, running starts a fresh kernel anyway.
+        if let Some(session) = self.session.clone() {
+            session.update(cx, |session, cx| session.restart(window, cx));
+        }
+        self.clear_outputs(window, cx);
+        self.run_all(window, cx);
+    }
+
+    fn interrupt(&mut self, cx: &mut Context<Self>) {
+        if let Some(session) = &self.session {
+            session.update(cx, |session, cx| session.interrupt(cx));
+        }
+    }
+
+    /// Copies the outputs of a cell's run into the notebook, and the kernel's execution count
+    /// into the cell and the notebook.
+    fn record_execution(
+        &mut self,
+        cell_id: &CellId,
+        execution_view: &Entity<ExecutionView>,
+        cx: &mut Context<Self>,
+    ) {
+        let outputs = execution_view
+            .read(cx)
+            .output_messages()
+            .iter()
+            .filter_map(output_json)
+            .filter_map(|output| serde_json::from_value(output).log_err())
+            .collect();
+        self.notebook_item.update(cx, |notebook_item, _| {
+            notebook_item.set_cell_outputs(cell_id, outputs);
+        });
+        cx.emit(());
+
+        let Some(execution_count) = execution_view.read(cx).execution_count else {
+            return;
+        };
+        let execution_count = execution_count as i32;
+        let Some(Cell::Code(cell)) = self.cell_map.get(cell_id) else {
+            return;
+        };
+        if cell.read(cx).execution_count() == Some(execution_count) {
+            return;
+        }
+
+        cell.update(cx, |cell, cx| {
+            cell.set_execution_count(execution_count);
+            cx.notify();
+        });
+        self.notebook_item.update(cx, |notebook_item, _| {
+            notebook_item.set_execution_count(cell_id, execution_count);
+        });
+    }
+
+    /// Writes the notebook to `abs_path`.
+    fn write_notebook(
+        &self,
+        abs_path: PathBuf,
+        project: &Entity<Project>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let contents = match serialize_notebook(&self.notebook_item.read(cx).notebook) {
+            Ok(contents) => contents,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let fs = project.read(cx).fs().clone();
+        cx.spawn(async move |this, cx| {
+            fs.atomic_write(abs_path, contents).await?;
+            this.update(cx, |this, cx| {
+                this.notebook_item
+                    .update(cx, |notebook_item, _| notebook_item.dirty = false);
+                cx.emit(());
+                cx.notify();
+            })
+        })
     }
 
     fn open_notebook(&mut self, _: &OpenNotebook, _window: &mut Window, _cx: &mut Context<Self>) {
@@ -332,6 +595,39 @@ impl NotebookEditor {
                                     window.dispatch_action(Box::new(RunAll), cx);
                                 }),
                             )
+                            .child(
+                                Self::render_notebook_control(
+                                    "restart-and-run-all-cells",
+                                    IconName::RotateCw,
+                                    window,
+                                    cx,
+                                )
+                                .tooltip(move |window, cx| {
+                                    Tooltip::for_action(
+                                        "Restart kernel and execute all cells",
+                                        &RestartAndRunAll,
+                                        cx,
+                                    )
+                                })
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(Box::new(RestartAndRunAll), cx);
+                                }),
+                            )
+                            .child(
+                                Self::render_notebook_control(
+                                    "interrupt-kernel",
+                                    IconName::Stop,
+                                    window,
+                                    cx,
+                                )
+                                .disabled(self.session.is_none())
+                                .tooltip(move |window, cx| {
+                                    Tooltip::for_action("Interrupt kernel", &Interrupt, cx)
+                                })
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(Box::new(Interrupt), cx);
+                                }),
+                            )
                             .child(
                                 Self::render_notebook_control(
                                     "clear-all-outputs",
@@ -415,12 +711,7 @@ impl NotebookEditor {
                 v_flex()
                     .gap(DynamicSpacing::Base08.rems(cx))
                     .items_center()
-                    .child(Self::render_notebook_control(
-                        "more-menu",
-                        IconName::Ellipsis,
-                        window,
-                        cx,
-                    ))
+                    .child(self.render_more_menu(window, cx))
                     .child(
                         Self::button_group(window, cx)
                             .child(IconButton::new("repl", IconName::ReplNeutral)),
@@ -428,6 +719,26 @@ impl NotebookEditor {
             )
     }
 
+    fn render_more_menu(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle.clone();
+
+        PopoverMenu::new("notebook-more-menu")
+            .trigger(Self::render_notebook_control(
+                "more-menu",
+                IconName::Ellipsis,
+                window,
+                cx,
+            ))
+            .anchor(Corner::BottomRight)
+            .menu(move |window, cx| {
+                Some(ContextMenu::build(window, cx, |menu, _, _| {
+                    menu.context(focus_handle.clone())
+                        .action("Run Cells Above", Box::new(RunAbove))
+                        .action("Run Cell and Below", Box::new(RunBelow))
+                }))
+            })
+    }
+
     fn cell_position(&self, index: usize) -> CellPosition {
         match index {
             0 => CellPosition::First,
@@ -449,9 +760,11 @@ impl NotebookEditor {
 
         match cell {
             Cell::Code(cell) => {
+                let is_stale = self.stale_cells.contains(cell.read(cx).id());
                 cell.update(cx, |cell, _cx| {
                     cell.set_selected(is_selected)
-                        .set_cell_position(cell_position);
+                        .set_cell_position(cell_position)
+                        .set_stale(is_stale);
                 });
                 cell.clone().into_any_element()
             }
@@ -484,7 +797,13 @@ impl Render for NotebookEditor {
             .on_action(
                 cx.listener(|this, &ClearOutputs, window, cx| this.clear_outputs(window, cx)),
             )
-            .on_action(cx.listener(|this, &RunAll, window, cx| this.run_cells(window, cx)))
+            .on_action(cx.listener(|this, &RunAll, window, cx| this.run_all(window, cx)))
+            .on_action(cx.listener(|this, &RunAbove, window, cx| this.run_above(window, cx)))
+            .on_action(cx.listener(|this, &RunBelow, window, cx| this.run_below(window, cx)))
+            .on_action(cx.listener(|this, &RestartAndRunAll, window, cx| {
+                this.restart_and_run_all(window, cx)
+            }))
+            .on_action(cx.listener(|this, &Interrupt, _, cx| this.interrupt(cx)))
             .on_action(cx.listener(|this, &MoveCellUp, window, cx| this.move_cell_up(window, cx)))
             .on_action(
                 cx.listener(|this, &MoveCellDown, window, cx| this.move_cell_down(window, cx)),
@@ -543,8 +862,10 @@ pub struct NotebookItem {
     languages: Arc<LanguageRegistry>,
     // Raw notebook data
     notebook: nbformat::v4::Notebook,
+    /// Whether the notebook has changed since it was loaded or saved.
+    dirty: bool,
     // Store our version of the notebook in memory (cell_order, cell_map)
-    id: ProjectEntryId,
+    id: Option<ProjectEntryId>,
 }
 
 impl project::ProjectItem for NotebookItem {
@@ -566,22 +887,7 @@ impl project::ProjectItem for NotebookItem {
 
                 // todo: watch for changes to the file
                 let file_content = fs.load(abs_path.as_path()).await?;
-                let notebook = nbformat::parse_notebook(&file_content);
-
-                let notebook = match notebook {
-                    Ok(nbformat::Notebook::V4(notebook)) => notebook,
-                    // 4.1 - 4.4 are converted to 4.5
-                    Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
-                        // TODO: Decide if we want to mutate the notebook by including Cell IDs
-                        // and any other conversions
-
-                        nbformat::upgrade_legacy_notebook(legacy_notebook)?
-                    }
-                    // Bad notebooks and notebooks v4.0 and below are not supported
-                    Err(e) => {
-                        anyhow::bail!("Failed to parse notebook: {:?}", e);
-                    }
-                };
+                let notebook = parse_notebook(&file_content)?;
 
                 let id = project
                     .update(cx, |project, cx| {
@@ -594,7 +900,8 @@ impl project::ProjectItem for NotebookItem {
                     project_path: path,
                     languages,
                     notebook,
-                    id,
+                    dirty: false,
+                    id: Some(id),
                 }))
             }))
         } else {
@@ -603,7 +910,7 @@ impl project::ProjectItem for NotebookItem {
     }
 
     fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
-        Some(self.id)
+        self.id
     }
 
     fn project_path(&self, _: &App) -> Option<ProjectPath> {
@@ -611,11 +918,49 @@ impl project::ProjectItem for NotebookItem {
     }
 
     fn is_dirty(&self) -> bool {
-        false
+        self.dirty
     }
 }
 
 impl NotebookItem {
+    fn cell_mut(&mut self, cell_id: &CellId) -> Option<&mut nbformat::v4::Cell> {
+        self.notebook
+            .cells
+            .iter_mut()
+            .find(|cell| cell.id() == cell_id)
+    }
+
+    fn set_execution_count(&mut self, cell_id: &CellId, count: i32) {
+        if let Some(nbformat::v4::Cell::Code {
+            execution_count, ..
+        }) = self.cell_mut(cell_id)
+        {
+            *execution_count = Some(count);
+            self.dirty = true;
+        }
+    }
+
+    fn set_cell_source(&mut self, cell_id: &CellId, text: &str) {
+        if let Some(
+            nbformat::v4::Cell::Code { source, .. }
+            | nbformat::v4::Cell::Markdown { source, .. }
+            | nbformat::v4::Cell::Raw { source, .. },
+        ) = self.cell_mut(cell_id)
+        {
+            *source = text.split_inclusive('\n').map(str::to_string).collect();
+            self.dirty = true;
+        }
+    }
+
+    fn set_cell_outputs(&mut self, cell_id: &CellId, new_outputs: Vec<nbformat::v4::Output>) {
+        if let Some(nbformat::v4::Cell::Code { outputs, .. }) = self.cell_mut(cell_id)
+            && (!outputs.is_empty() || !new_outputs.is_empty())
+        {
+            *outputs = new_outputs;
+            self.dirty = true;
+        }
+    }
+
     pub fn language_name(&self) -> Option<String> {
         self.notebook
             .metadata
@@ -646,6 +991,50 @@ impl NotebookItem {
 
 impl EventEmitter<()> for NotebookEditor {}
 
+fn parse_notebook(contents: &str) -> Result<nbformat::v4::Notebook> {
+    match nbformat::parse_notebook(contents) {
+        Ok(nbformat::Notebook::V4(notebook)) => Ok(notebook),
+        // 4.1 - 4.4 are converted to 4.5
+        Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+            // TODO: Decide if we want to mutate the notebook by including Cell IDs
+            // and any other conversions
+
+            Ok(nbformat::upgrade_legacy_notebook(legacy_notebook)?)
+        }
+        // Bad notebooks and notebooks v4.0 and below are not supported
+        Err(e) => {
+            anyhow::bail!("Failed to parse notebook: {:?}", e);
+        }
+    }
+}
+
+fn serialize_notebook(notebook: &nbformat::v4::Notebook) -> Result<String> {
+    let mut contents = serde_json::to_string_pretty(notebook)?;
+    contents.push('\n');
+    Ok(contents)
+}
+
+/// Returns the cells that last ran before a cell above them was edited.
+fn stale_cells(
+    cell_order: &[CellId],
+    cell_history: &HashMap<CellId, CellHistory>,
+) -> HashSet<CellId> {
+    let mut stale_cells = HashSet::default();
+    let mut last_edit_above = None;
+    for cell_id in cell_order {
+        let Some(history) = cell_history.get(cell_id) else {
+            continue;
+        };
+        if let Some(ran_at) = history.ran_at
+            && last_edit_above.is_some_and(|edited_at| edited_at > ran_at)
+        {
+            stale_cells.insert(cell_id.clone());
+        }
+        last_edit_above = last_edit_above.max(history.edited_at);
+    }
+    stale_cells
+}
+
 // pub struct NotebookControls {
 //     pane_focused: bool,
 //     active_item: Option<Box<dyn ItemHandle>>,
@@ -694,6 +1083,10 @@ impl EventEmitter<()> for NotebookEditor {}
 impl Item for NotebookEditor {
     type Event = ();
 
+    fn to_item_events(_: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(ItemEvent::UpdateTab)
+    }
+
     fn can_split(&self) -> bool {
         true
     }
@@ -769,49 +1162,82 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
     fn can_save(&self, _cx: &App) -> bool {
-        false
+        true
     }
-    // TODO
+
+    fn can_save_as(&self, _cx: &App) -> bool {
+        true
+    }
+
     fn save(
         &mut self,
         _options: SaveOptions,
-        _project: Entity<Project>,
+        project: Entity<Project>,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        self.write_notebook(abs_path, &project, cx)
     }
 
-    // TODO
     fn save_as(
         &mut self,
-        _project: Entity<Project>,
-        _path: ProjectPath,
+        project: Entity<Project>,
+        path: ProjectPath,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save_as() must be implemented if can_save() returns true")
+        let contents = match serialize_notebook(&self.notebook_item.read(cx).notebook) {
+            Ok(contents) => contents,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let Some(worktree) = project.read(cx).worktree_for_id(path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow::anyhow!("no worktree for {path:?}")));
+        };
+        let abs_path = worktree.read(cx).absolutize(&path.path);
+        let create_entry = worktree.update(cx, |worktree, cx| {
+            worktree.create_entry(path.path.clone(), false, Some(contents.into_bytes()), cx)
+        });
+        cx.spawn(async move |this, cx| {
+            let entry = create_entry.await?;
+            this.update(cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.path = abs_path;
+                    notebook_item.project_path = path;
+                    notebook_item.id = entry.into_included().map(|entry| entry.id);
+                    notebook_item.dirty = false;
+                });
+                cx.emit(());
+                cx.notify();
+            })
+        })
     }
-    // TODO
+
     fn reload(
         &mut self,
-        _project: Entity<Project>,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("reload() must be implemented if can_save() returns true")
+        let fs = project.read(cx).fs().clone();
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let notebook = parse_notebook(&fs.load(&abs_path).await?)?;
+            this.update_in(cx, |this, window, cx| {
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.notebook = notebook;
+                    notebook_item.dirty = false;
+                });
+                this.load_cells(window, cx);
+                cx.emit(());
+                cx.notify();
+            })
+        })
     }
 
     fn is_dirty(&self, cx: &App) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
-            }
-        })
+        self.notebook_item.read(cx).dirty
     }
 }
 
@@ -834,3 +1260,97 @@ impl ProjectItem for NotebookEditor {
         Self::new(project, item, window, cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell_id(id: &str) -> CellId {
+        serde_json::from_value(serde_json::json!(id)).unwrap()
+    }
+
+    #[test]
+    fn test_serialize_notebook_round_trip() {
+        let contents = serde_json::json!({
+            "cells": [{
+                "cell_type": "code",
+                "id": "a",
+                "metadata": {},
+                "source": ["x = 1\n", "x"],
+                "execution_count": 1,
+                "outputs": [{
+                    "output_type": "execute_result",
+                    "execution_count": 1,
+                    "data": { "text/plain": "1" },
+                    "metadata": {},
+                }],
+            }],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5,
+        })
+        .to_string();
+
+        let notebook = parse_notebook(&contents).unwrap();
+        let serialized = serialize_notebook(&notebook).unwrap();
+        assert!(serialized.ends_with('\n'));
+        let reparsed = parse_notebook(&serialized).unwrap();
+        assert_eq!(
+            serde_json::to_value(&reparsed).unwrap(),
+            serde_json::to_value(&notebook).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_stale_cells() {
+        let cell_order = vec![cell_id("a"), cell_id("b"), cell_id("c")];
+        let history = |entries: &[(&str, Option<usize>, Option<usize>)]| {
+            entries
+                .iter()
+                .map(|(id, edited_at, ran_at)| {
+                    (
+                        cell_id(id),
+                        CellHistory {
+                            edited_at: *edited_at,
+                            ran_at: *ran_at,
+                        },
+                    )
+                })
+                .collect::<HashMap<_, _>>()
+        };
+
+        // Editing a cell after running the cells below it makes them stale.
+        let stale = stale_cells(
+            &cell_order,
+            &history(&[
+                ("a", Some(3), Some(0)),
+                ("b", None, Some(1)),
+                ("c", None, Some(2)),
+            ]),
+        );
+        assert_eq!(stale, HashSet::from_iter([cell_id("b"), cell_id("c")]));
+
+        // Running a cell again after the edit brings it up to date, and cells that never ran
+        // aren't stale.
+        let stale = stale_cells(
+            &cell_order,
+            &history(&[
+                ("a", Some(1), Some(0)),
+                ("b", None, Some(2)),
+                ("c", None, None),
+            ]),
+        );
+        assert!(stale.is_empty());
+
+        // Editing a cell doesn't make the cells above it stale.
+        let stale = stale_cells(
+            &cell_order,
+            &history(&[
+                ("a", None, Some(0)),
+                ("b", None, Some(1)),
+                ("c", Some(2), None),
+            ]),
+        );
+        assert!(stale.is_empty());
+    }
+}
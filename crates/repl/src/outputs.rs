@@ -13,9 +13,11 @@
 //!
 //! The module supports several output types, including:
 //! - Plain text
-//! - Markdown
-//! - Images (PNG and JPEG)
+//! - Markdown, HTML and LaTeX
+//! - Images (PNG, JPEG and SVG)
 //! - Tables
+//! - JSON, as a collapsible tree
+//! - Jupyter widgets
 //! - Error messages
 //!
 //! ## Clipboard Support
@@ -36,16 +38,23 @@
 use editor::{Editor, MultiBuffer};
 use gpui::{AnyElement, ClipboardItem, Entity, EventEmitter, Render, WeakEntity};
 use language::LanguageBuffer;
-use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType};
+use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType, Stdio};
+use serde_json::json;
 use ui::{CommonAnimationExt, CopyButton, IconButton, Tooltip, prelude::*};
 
 mod image;
 use image::ImageView;
 
+mod json;
+use json::JsonView;
+
 mod markdown;
 use markdown::MarkdownView;
 
-mod table;
+mod svg;
+use svg::SvgView;
+
+pub mod table;
 use table::TableView;
 
 pub mod plain;
@@ -53,6 +62,9 @@ use plain::TerminalOutput;
 
 pub(crate) mod user_error;
 use user_error::ErrorView;
+
+pub mod widgets;
+use widgets::{WidgetStore, WidgetView};
 use workspace::Workspace;
 
 use crate::repl_settings::ReplSettings;
@@ -61,16 +73,30 @@ use settings::Settings;
 /// When deciding what to render from a collection of mediatypes, we need to rank them in order of importance
 fn rank_mime_type(mimetype: &MimeType) -> usize {
     match mimetype {
-        MimeType::DataTable(_) => 6,
-        MimeType::Png(_) => 4,
-        MimeType::Jpeg(_) => 3,
-        MimeType::Markdown(_) => 2,
+        MimeType::DataTable(_) => 9,
+        MimeType::Png(_) => 8,
+        MimeType::Jpeg(_) => 7,
+        MimeType::Svg(_) => 6,
+        // Scripted HTML, like a plotly figure, renders as nothing without a browser
+        MimeType::Html(html) if !html.contains("<script") => 5,
+        MimeType::Latex(_) => 4,
+        MimeType::Markdown(_) => 3,
+        MimeType::Json(_) => 2,
         MimeType::Plain(_) => 1,
         // All other media types are not supported in Zed at this time
         _ => 0,
     }
 }
 
+/// Widgets can only be displayed while their kernel is running, so they're only ranked when
+/// there is one to sync them with.
+fn rank_mime_type_with_widgets(mimetype: &MimeType) -> usize {
+    match mimetype {
+        MimeType::WidgetView(_) => 10,
+        mimetype => rank_mime_type(mimetype),
+    }
+}
+
 pub(crate) trait OutputContent {
     fn clipboard_content(&self, window: &Window, cx: &App) -> Option<ClipboardItem>;
     fn has_clipboard_content(&self, _window: &Window, _cx: &App) -> bool {
@@ -124,6 +150,18 @@ pub enum Output {
         content: Entity<MarkdownView>,
         display_id: Option<String>,
     },
+    Svg {
+        content: Entity<SvgView>,
+        display_id: Option<String>,
+    },
+    Json {
+        content: Entity<JsonView>,
+        display_id: Option<String>,
+    },
+    Widget {
+        content: Entity<WidgetView>,
+        display_id: Option<String>,
+    },
     ClearOutputWaitMarker,
 }
 
@@ -213,6 +251,9 @@ impl Output {
             Self::Image { content, .. } => Some(content.clone().into_any_element()),
             Self::Message(message) => Some(div().child(message.clone()).into_any_element()),
             Self::Table { content, .. } => Some(content.clone().into_any_element()),
+            Self::Svg { content, .. } => Some(content.clone().into_any_element()),
+            Self::Json { content, .. } => Some(content.clone().into_any_element()),
+            Self::Widget { content, .. } => Some(content.clone().into_any_element()),
             Self::ErrorOutput(error_view) => error_view.render(window, cx),
             Self::ClearOutputWaitMarker => None,
         };
@@ -296,6 +337,13 @@ impl Output {
                 Self::Table { content, .. } => {
                     Self::render_output_controls(content.clone(), workspace, window, cx)
                 }
+                Self::Svg { content, .. } => {
+                    Self::render_output_controls(content.clone(), workspace, window, cx)
+                }
+                Self::Json { content, .. } => {
+                    Self::render_output_controls(content.clone(), workspace, window, cx)
+                }
+                Self::Widget { .. } => None,
                 Self::ClearOutputWaitMarker => None,
             })
     }
@@ -309,17 +357,28 @@ impl Output {
             Output::Message(_) => None,
             Output::Table { display_id, .. } => display_id.clone(),
             Output::Markdown { display_id, .. } => display_id.clone(),
+            Output::Svg { display_id, .. } => display_id.clone(),
+            Output::Json { display_id, .. } => display_id.clone(),
+            Output::Widget { display_id, .. } => display_id.clone(),
             Output::ClearOutputWaitMarker => None,
         }
     }
 
+    /// Creates the output for the richest media type in `data`. Widgets are only displayed
+    /// when their models are available from `widgets`.
     pub fn new(
         data: &MimeBundle,
         display_id: Option<String>,
+        widgets: Option<&Entity<WidgetStore>>,
         window: &mut Window,
         cx: &mut App,
     ) -> Self {
-        match data.richest(rank_mime_type) {
+        let richest = if widgets.is_some() {
+            data.richest(rank_mime_type_with_widgets)
+        } else {
+            data.richest(rank_mime_type)
+        };
+        match richest {
             Some(MimeType::Plain(text)) => Output::Plain {
                 content: cx.new(|cx| TerminalOutput::from(text, window, cx)),
                 display_id,
@@ -342,6 +401,32 @@ impl Output {
                 content: cx.new(|cx| TableView::new(data, window, cx)),
                 display_id,
             },
+            Some(MimeType::Html(html)) => Output::Markdown {
+                content: cx.new(|cx| MarkdownView::from_html(html.clone(), cx)),
+                display_id,
+            },
+            Some(MimeType::Latex(latex)) => Output::Markdown {
+                content: cx.new(|cx| MarkdownView::from_latex(latex.clone(), cx)),
+                display_id,
+            },
+            Some(MimeType::Svg(source)) => Output::Svg {
+                content: cx.new(|_| SvgView::from(source)),
+                display_id,
+            },
+            Some(MimeType::Json(json)) => Output::Json {
+                content: cx.new(|_| JsonView::new(serde_json::Value::from(json.clone()))),
+                display_id,
+            },
+            Some(MimeType::WidgetView(view)) => {
+                let view = serde_json::Value::from(view.clone());
+                match (widgets, widgets::model_id_for_view(&view)) {
+                    (Some(widgets), Some(model_id)) => Output::Widget {
+                        content: cx.new(|cx| WidgetView::new(widgets.clone(), model_id, cx)),
+                        display_id,
+                    },
+                    _ => Output::Message("Invalid widget output".to_string()),
+                }
+            }
             // Any other media types are not supported
             _ => Output::Message("Unsupported media type".to_string()),
         }
@@ -371,8 +456,14 @@ pub struct ExecutionViewFinishedSmall(pub String);
 pub struct ExecutionView {
     #[allow(unused)]
     workspace: WeakEntity<Workspace>,
+    widgets: Entity<WidgetStore>,
     pub outputs: Vec<Output>,
     pub status: ExecutionStatus,
+    /// The kernel's count for this execution, once it has started running.
+    pub execution_count: Option<usize>,
+    /// The messages behind the outputs, kept so the outputs can be exported to a notebook.
+    output_messages: Vec<JupyterMessageContent>,
+    clear_output_messages_on_next_output: bool,
 }
 
 impl EventEmitter<ExecutionViewFinishedEmpty> for ExecutionView {}
@@ -382,12 +473,67 @@ impl ExecutionView {
     pub fn new(
         status: ExecutionStatus,
         workspace: WeakEntity<Workspace>,
+        widgets: Entity<WidgetStore>,
         _cx: &mut Context<Self>,
     ) -> Self {
         Self {
             workspace,
+            widgets,
             outputs: Default::default(),
             status,
+            execution_count: None,
+            output_messages: Vec::new(),
+            clear_output_messages_on_next_output: false,
+        }
+    }
+
+    pub fn output_messages(&self) -> &[JupyterMessageContent] {
+        &self.output_messages
+    }
+
+    fn record_output_message(&mut self, message: &JupyterMessageContent) {
+        match message {
+            JupyterMessageContent::ExecuteResult(_)
+            | JupyterMessageContent::DisplayData(_)
+            | JupyterMessageContent::StreamContent(_)
+            | JupyterMessageContent::ErrorOutput(_) => {
+                if self.clear_output_messages_on_next_output {
+                    self.clear_output_messages_on_next_output = false;
+                    self.output_messages.clear();
+                }
+                self.output_messages.push(message.clone());
+            }
+            JupyterMessageContent::ClearOutput(options) => {
+                if options.wait {
+                    self.clear_output_messages_on_next_output = true;
+                } else {
+                    self.output_messages.clear();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replaces the data of recorded outputs shown with `display_id`, as
+    /// [`Self::update_display_data`] does for the rendered ones.
+    fn record_display_data_update(&mut self, data: &MimeBundle, display_id: &str) {
+        for message in &mut self.output_messages {
+            let (transient, output_data) = match message {
+                JupyterMessageContent::ExecuteResult(result) => {
+                    (&result.transient, &mut result.data)
+                }
+                JupyterMessageContent::DisplayData(display) => {
+                    (&display.transient, &mut display.data)
+                }
+                _ => continue,
+            };
+            if transient
+                .as_ref()
+                .and_then(|transient| transient.display_id.as_deref())
+                == Some(display_id)
+            {
+                *output_data = data.clone();
+            }
         }
     }
 
@@ -398,16 +544,20 @@ impl ExecutionView {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.record_output_message(message);
+
         let output: Output = match message {
             JupyterMessageContent::ExecuteResult(result) => Output::new(
                 &result.data,
                 result.transient.as_ref().and_then(|t| t.display_id.clone()),
+                Some(&self.widgets),
                 window,
                 cx,
             ),
             JupyterMessageContent::DisplayData(result) => Output::new(
                 &result.data,
                 result.transient.as_ref().and_then(|t| t.display_id.clone()),
+                Some(&self.widgets),
                 window,
                 cx,
             ),
@@ -429,10 +579,16 @@ impl ExecutionView {
                     traceback: terminal,
                 })
             }
+            JupyterMessageContent::ExecuteInput(input) => {
+                self.execution_count = Some(input.execution_count.0);
+                cx.notify();
+                return;
+            }
             JupyterMessageContent::ExecuteReply(reply) => {
+                self.execution_count = Some(reply.execution_count.0);
                 for payload in reply.payload.iter() {
                     if let runtimelib::Payload::Page { data, .. } = payload {
-                        let output = Output::new(data, None, window, cx);
+                        let output = Output::new(data, None, Some(&self.widgets), window, cx);
                         self.outputs.push(output);
                     }
                 }
@@ -499,13 +655,22 @@ impl ExecutionView {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.record_display_data_update(data, display_id);
+
         let mut any = false;
+        let widgets = self.widgets.clone();
 
         self.outputs.iter_mut().for_each(|output| {
             if let Some(other_display_id) = output.display_id().as_ref()
                 && other_display_id == display_id
             {
-                *output = Output::new(data, Some(display_id.to_owned()), window, cx);
+                *output = Output::new(
+                    data,
+                    Some(display_id.to_owned()),
+                    Some(&widgets),
+                    window,
+                    cx,
+                );
                 any = true;
             }
         });
@@ -570,6 +735,41 @@ impl ExecutionView {
     }
 }
 
+/// Converts an output message from a kernel to its representation in a notebook.
+pub fn output_json(message: &JupyterMessageContent) -> Option<serde_json::Value> {
+    match message {
+        JupyterMessageContent::ExecuteResult(result) => Some(json!({
+            "output_type": "execute_result",
+            "execution_count": result.execution_count.0,
+            "data": result.data,
+            "metadata": result.metadata,
+        })),
+        JupyterMessageContent::DisplayData(display) => Some(json!({
+            "output_type": "display_data",
+            "data": display.data,
+            "metadata": display.metadata,
+        })),
+        JupyterMessageContent::StreamContent(stream) => {
+            let name = match stream.name {
+                Stdio::Stdout => "stdout",
+                Stdio::Stderr => "stderr",
+            };
+            Some(json!({
+                "output_type": "stream",
+                "name": name,
+                "text": stream.text,
+            }))
+        }
+        JupyterMessageContent::ErrorOutput(error) => Some(json!({
+            "output_type": "error",
+            "ename": error.ename,
+            "evalue": error.evalue,
+            "traceback": error.traceback,
+        })),
+        _ => None,
+    }
+}
+
 impl Render for ExecutionView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let status = match &self.status {
@@ -0,0 +1,176 @@
+use collections::HashSet;
+use gpui::{App, ClipboardItem, Entity, Window};
+use language::LanguageBuffer;
+use serde_json::Value;
+use ui::{Disclosure, prelude::*};
+
+use crate::outputs::OutputContent;
+
+/// Nodes nested deeper than this start out collapsed.
+const EXPANDED_DEPTH: usize = 1;
+
+/// JsonView renders an `application/json` output as a tree whose objects and arrays can be
+/// expanded and collapsed.
+pub struct JsonView {
+    value: Value,
+    /// The JSON pointers of the nodes the user has expanded or collapsed, relative to their
+    /// initial state.
+    toggled: HashSet<String>,
+}
+
+impl JsonView {
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            toggled: HashSet::default(),
+        }
+    }
+
+    fn is_expanded(&self, pointer: &str, depth: usize) -> bool {
+        (depth < EXPANDED_DEPTH) != self.toggled.contains(pointer)
+    }
+
+    fn toggle(&mut self, pointer: String, cx: &mut Context<Self>) {
+        if !self.toggled.remove(&pointer) {
+            self.toggled.insert(pointer);
+        }
+        cx.notify();
+    }
+
+    fn render_node(
+        &self,
+        key: Option<String>,
+        value: &Value,
+        pointer: String,
+        depth: usize,
+        rows: &mut Vec<AnyElement>,
+        cx: &mut Context<Self>,
+    ) {
+        let key_label = key.map(|key| {
+            Label::new(format!("{key}:"))
+                .buffer_font(cx)
+                .color(Color::Accent)
+        });
+        let indent = px(16.) * depth as f32;
+
+        let children: Vec<(String, &Value)> = match value {
+            Value::Object(object) => object
+                .iter()
+                .map(|(key, value)| (key.clone(), value))
+                .collect(),
+            Value::Array(array) => array
+                .iter()
+                .enumerate()
+                .map(|(ix, value)| (ix.to_string(), value))
+                .collect(),
+            _ => {
+                let (text, color) = match value {
+                    Value::String(string) => (format!("{string:?}"), Color::Success),
+                    Value::Number(number) => (number.to_string(), Color::Info),
+                    Value::Bool(bool) => (bool.to_string(), Color::Warning),
+                    _ => ("null".to_string(), Color::Muted),
+                };
+                rows.push(
+                    h_flex()
+                        .pl(indent + px(20.))
+                        .gap_1()
+                        .children(key_label)
+                        .child(Label::new(text).buffer_font(cx).color(color))
+                        .into_any_element(),
+                );
+                return;
+            }
+        };
+
+        let expanded = self.is_expanded(&pointer, depth);
+        let summary = match value {
+            Value::Array(_) => format!("[{} items]", children.len()),
+            _ => format!("{{{} keys}}", children.len()),
+        };
+        rows.push(
+            h_flex()
+                .pl(indent)
+                .gap_1()
+                .child(
+                    Disclosure::new(SharedString::from(format!("json{pointer}")), expanded)
+                        .on_click(cx.listener({
+                            let pointer = pointer.clone();
+                            move |this, _, _, cx| this.toggle(pointer.clone(), cx)
+                        })),
+                )
+                .children(key_label)
+                .child(Label::new(summary).buffer_font(cx).color(Color::Muted))
+                .into_any_element(),
+        );
+
+        if expanded {
+            for (key, child) in children {
+                let child_pointer = format!("{pointer}/{}", escape_pointer_token(&key));
+                self.render_node(Some(key), child, child_pointer, depth + 1, rows, cx);
+            }
+        }
+    }
+}
+
+/// Escapes a key for use in a JSON pointer, as described in RFC 6901.
+fn escape_pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+impl Render for JsonView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut rows = Vec::new();
+        let value = self.value.clone();
+        self.render_node(None, &value, String::new(), 0, &mut rows, cx);
+        v_flex().py_1().children(rows)
+    }
+}
+
+impl OutputContent for JsonView {
+    fn clipboard_content(&self, _window: &Window, _cx: &App) -> Option<ClipboardItem> {
+        let text = serde_json::to_string_pretty(&self.value).ok()?;
+        Some(ClipboardItem::new_string(text))
+    }
+
+    fn has_clipboard_content(&self, _window: &Window, _cx: &App) -> bool {
+        true
+    }
+
+    fn has_buffer_content(&self, _window: &Window, _cx: &App) -> bool {
+        true
+    }
+
+    fn buffer_content(&mut self, _: &mut Window, cx: &mut App) -> Option<Entity<LanguageBuffer>> {
+        let text = serde_json::to_string_pretty(&self.value).ok()?;
+        let buffer = cx.new(|cx| {
+            let mut buffer =
+                LanguageBuffer::local(text, cx).with_language(language::PLAIN_TEXT.clone(), cx);
+            buffer.set_capability(language::Capability::ReadOnly, cx);
+            buffer
+        });
+        Some(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_expansion() {
+        let mut view = JsonView::new(json!({ "a": { "b": [1, 2] } }));
+        assert!(view.is_expanded("", 0));
+        assert!(!view.is_expanded("/a", 1));
+
+        view.toggled.insert("/a".into());
+        view.toggled.insert("".into());
+        assert!(!view.is_expanded("", 0));
+        assert!(view.is_expanded("/a", 1));
+    }
+
+    #[test]
+    fn test_escape_pointer_token() {
+        assert_eq!(escape_pointer_token("a/b~c"), "a~1b~0c");
+    }
+}
@@ -20,10 +20,42 @@ pub struct MarkdownView {
 
 impl MarkdownView {
     pub fn from(text: String, cx: &mut Context<Self>) -> Self {
-        let parsed = {
-            let text = text.clone();
-            cx.background_spawn(async move { parse_markdown(&text.clone(), None, None).await })
-        };
+        Self::new(text.clone(), text, cx)
+    }
+
+    /// Renders `text/html` with the HTML subset that Markdown previews support, such as the
+    /// tables pandas outputs.
+    pub fn from_html(html: String, cx: &mut Context<Self>) -> Self {
+        // A blank line would end the HTML block that Markdown parses it as.
+        let source = html
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self::new(html, source, cx)
+    }
+
+    /// Typesets `text/latex` as display math.
+    pub fn from_latex(latex: String, cx: &mut Context<Self>) -> Self {
+        let math = latex.trim();
+        let math = math
+            .strip_prefix("$$")
+            .and_then(|math| math.strip_suffix("$$"))
+            .or_else(|| {
+                math.strip_prefix('$')
+                    .and_then(|math| math.strip_suffix('$'))
+            })
+            .or_else(|| {
+                math.strip_prefix("\\[")
+                    .and_then(|math| math.strip_suffix("\\]"))
+            })
+            .unwrap_or(math);
+        let source = format!("$$\n{}\n$$", math.trim());
+        Self::new(latex, source, cx)
+    }
+
+    fn new(raw_text: String, source: String, cx: &mut Context<Self>) -> Self {
+        let parsed = cx.background_spawn(async move { parse_markdown(&source, None, None).await });
         let task = cx.spawn(async move |markdown_view, cx| {
             let content = parsed.await;
 
@@ -35,7 +67,7 @@ impl MarkdownView {
         });
 
         Self {
-            raw_text: text,
+            raw_text,
             image_cache: RetainAllImageCache::new(cx),
             contents: None,
             parsing_markdown_task: Some(task),
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use gpui::{App, ClipboardItem, Image, ImageFormat, Window, img};
+use ui::prelude::*;
+
+use crate::outputs::OutputContent;
+
+/// SvgView renders an `image/svg+xml` output, which gpui rasterizes at the display's scale.
+pub struct SvgView {
+    source: String,
+    image: Arc<Image>,
+}
+
+impl SvgView {
+    pub fn from(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            image: Arc::new(Image::from_bytes(
+                ImageFormat::Svg,
+                source.as_bytes().to_vec(),
+            )),
+        }
+    }
+}
+
+impl Render for SvgView {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        div().max_w_full().child(img(self.image.clone()))
+    }
+}
+
+impl OutputContent for SvgView {
+    fn clipboard_content(&self, _window: &Window, _cx: &App) -> Option<ClipboardItem> {
+        Some(ClipboardItem::new_string(self.source.clone()))
+    }
+
+    fn has_clipboard_content(&self, _window: &Window, _cx: &App) -> bool {
+        true
+    }
+}
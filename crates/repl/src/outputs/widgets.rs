@@ -0,0 +1,622 @@
+//! Native rendering of [Jupyter widgets](https://ipywidgets.readthedocs.io).
+//!
+//! The kernel creates a widget model by opening a comm with the `jupyter.widget` target, and
+//! keeps it in sync with `update` messages on that comm. A `widget-view+json` output then
+//! references the model to display. When the user interacts with a widget, we send the changed
+//! state back over the same comm, which is how the kernel's observers get notified.
+
+use std::{cell::RefCell, rc::Rc};
+
+use collections::HashMap;
+use gpui::{
+    Bounds, ClipboardItem, Entity, EventEmitter, MouseButton, MouseDownEvent, MouseMoveEvent,
+    Pixels, Subscription, canvas,
+};
+use runtimelib::{CommId, CommMsg, JupyterMessageContent};
+use serde_json::{Map, Value, json};
+use ui::{Checkbox, ContextMenu, DropdownMenu, DropdownStyle, ProgressBar, Tooltip, prelude::*};
+
+use crate::outputs::OutputContent;
+
+const WIDGET_TARGET_NAME: &str = "jupyter.widget";
+/// The prefix a widget's state uses to reference another widget, e.g. a box's children.
+const MODEL_REFERENCE_PREFIX: &str = "IPY_MODEL_";
+
+/// The state of a widget, as last synced with the kernel.
+#[derive(Clone)]
+pub struct WidgetModel {
+    pub state: Map<String, Value>,
+}
+
+impl WidgetModel {
+    fn model_name(&self) -> &str {
+        self.str("_model_name").unwrap_or_default()
+    }
+
+    fn str(&self, key: &str) -> Option<&str> {
+        self.state.get(key)?.as_str()
+    }
+
+    fn f64(&self, key: &str) -> Option<f64> {
+        self.state.get(key)?.as_f64()
+    }
+
+    fn bool(&self, key: &str) -> bool {
+        self.state
+            .get(key)
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    fn description(&self) -> Option<SharedString> {
+        self.str("description")
+            .filter(|description| !description.is_empty())
+            .map(|description| SharedString::from(description.to_string()))
+    }
+}
+
+pub enum WidgetStoreEvent {
+    /// A message for the kernel, sent when the user changes a widget.
+    SendMessage(CommMsg),
+}
+
+/// The widget models of a kernel, keyed by the id of the comm that syncs them.
+#[derive(Default)]
+pub struct WidgetStore {
+    models: HashMap<String, WidgetModel>,
+}
+
+impl EventEmitter<WidgetStoreEvent> for WidgetStore {}
+
+impl WidgetStore {
+    pub fn model(&self, model_id: &str) -> Option<&WidgetModel> {
+        self.models.get(model_id)
+    }
+
+    /// Applies a comm message from the kernel, returning whether it was a widget message.
+    pub fn handle_message(
+        &mut self,
+        content: &JupyterMessageContent,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        match content {
+            JupyterMessageContent::CommOpen(open) if open.target_name == WIDGET_TARGET_NAME => {
+                let state = match open.data.get("state") {
+                    Some(Value::Object(state)) => state.clone(),
+                    _ => Map::new(),
+                };
+                self.models
+                    .insert(open.comm_id.0.clone(), WidgetModel { state });
+            }
+            JupyterMessageContent::CommMsg(message) => {
+                let Some(model) = self.models.get_mut(&message.comm_id.0) else {
+                    return false;
+                };
+                match message.data.get("method").and_then(Value::as_str) {
+                    Some("update" | "echo_update") => {
+                        if let Some(Value::Object(state)) = message.data.get("state") {
+                            model.state.extend(state.clone());
+                        }
+                    }
+                    // Custom messages are specific to each widget, and none that we render
+                    // need them.
+                    _ => {}
+                }
+            }
+            JupyterMessageContent::CommClose(close) => {
+                if self.models.remove(&close.comm_id.0).is_none() {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+        cx.notify();
+        true
+    }
+
+    /// Drops every model, e.g. because the kernel that owned them was restarted.
+    pub fn clear(&mut self, cx: &mut Context<Self>) {
+        self.models.clear();
+        cx.notify();
+    }
+
+    /// Updates a widget's state in response to the user, and syncs it to the kernel.
+    pub fn update_state(
+        &mut self,
+        model_id: &str,
+        state: Map<String, Value>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(model) = self.models.get_mut(model_id) else {
+            return;
+        };
+        model.state.extend(state.clone());
+        self.send(
+            model_id,
+            json!({ "method": "update", "state": state, "buffer_paths": [] }),
+            cx,
+        );
+        cx.notify();
+    }
+
+    /// Sends a widget-specific message to the kernel, such as a button's click event.
+    pub fn send_custom(&mut self, model_id: &str, content: Value, cx: &mut Context<Self>) {
+        self.send(
+            model_id,
+            json!({ "method": "custom", "content": content }),
+            cx,
+        );
+    }
+
+    fn send(&mut self, model_id: &str, data: Value, cx: &mut Context<Self>) {
+        let Value::Object(data) = data else {
+            return;
+        };
+        cx.emit(WidgetStoreEvent::SendMessage(CommMsg {
+            comm_id: CommId(model_id.to_string()),
+            data,
+        }));
+    }
+}
+
+/// Returns the id of the model that a `widget-view+json` output displays.
+pub fn model_id_for_view(data: &Value) -> Option<String> {
+    Some(data.get("model_id")?.as_str()?.to_string())
+}
+
+/// Displays a widget and its children, if it's a container.
+pub struct WidgetView {
+    store: Entity<WidgetStore>,
+    model_id: String,
+    slider_bounds: Rc<RefCell<HashMap<String, Bounds<Pixels>>>>,
+    _subscription: Subscription,
+}
+
+impl WidgetView {
+    pub fn new(store: Entity<WidgetStore>, model_id: String, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.observe(&store, |_, _, cx| cx.notify());
+        Self {
+            store,
+            model_id,
+            slider_bounds: Rc::default(),
+            _subscription: subscription,
+        }
+    }
+
+    fn render_widget(
+        &self,
+        model_id: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let Some(model) = self.store.read(cx).model(model_id).cloned() else {
+            return Label::new("Loading widget…")
+                .color(Color::Muted)
+                .into_any_element();
+        };
+
+        if model.str("_view_name").is_none() {
+            // Layout and style models are referenced by other widgets, and aren't displayed.
+            return div().into_any_element();
+        }
+
+        let description = model.description();
+        let disabled = model.bool("disabled");
+        let control = match model.model_name() {
+            "HBoxModel" | "VBoxModel" | "BoxModel" | "GridBoxModel" => {
+                let children = model
+                    .state
+                    .get("children")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|child| child.as_str()?.strip_prefix(MODEL_REFERENCE_PREFIX))
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                let children = children
+                    .iter()
+                    .map(|child| self.render_widget(child, window, cx))
+                    .collect::<Vec<_>>();
+                return if model.model_name() == "HBoxModel" {
+                    h_flex().gap_2().children(children).into_any_element()
+                } else {
+                    v_flex().gap_1().children(children).into_any_element()
+                };
+            }
+            "IntSliderModel" | "FloatSliderModel" => {
+                let integer = model.model_name() == "IntSliderModel";
+                self.render_slider(model_id, &model, integer, cx)
+            }
+            "IntProgressModel" | "FloatProgressModel" => {
+                let min = model.f64("min").unwrap_or(0.);
+                let max = model.f64("max").unwrap_or(100.);
+                let value = model.f64("value").unwrap_or(min);
+                let status = cx.theme().status();
+                let color = match model.str("bar_style").unwrap_or_default() {
+                    "success" => status.success,
+                    "warning" => status.warning,
+                    "danger" => status.error,
+                    _ => status.info,
+                };
+                div()
+                    .w_64()
+                    .child(
+                        ProgressBar::new(
+                            SharedString::from(format!("widget-progress-{model_id}")),
+                            (value - min) as f32,
+                            (max - min) as f32,
+                            cx,
+                        )
+                        .fg_color(color),
+                    )
+                    .into_any_element()
+            }
+            "DropdownModel" => self.render_dropdown(model_id, &model, disabled, window, cx),
+            "CheckboxModel" => {
+                let store = self.store.clone();
+                let model_id = model_id.to_string();
+                let checked = model.bool("value");
+                return Checkbox::new(
+                    SharedString::from(format!("widget-checkbox-{model_id}")),
+                    checked.into(),
+                )
+                .when_some(description, |checkbox, description| {
+                    checkbox.label(description)
+                })
+                .disabled(disabled)
+                .on_click(move |_, _, cx| {
+                    store.update(cx, |store, cx| {
+                        store.update_state(&model_id, value_state(json!(!checked)), cx)
+                    });
+                })
+                .into_any_element();
+            }
+            "ButtonModel" => {
+                let store = self.store.clone();
+                let model_id = model_id.to_string();
+                let label = description.unwrap_or_default();
+                let tooltip = model.str("tooltip").map(|tooltip| tooltip.to_string());
+                return Button::new(
+                    SharedString::from(format!("widget-button-{model_id}")),
+                    label,
+                )
+                .style(ButtonStyle::Outlined)
+                .disabled(disabled)
+                .when_some(tooltip, |button, tooltip| {
+                    button.tooltip(Tooltip::text(tooltip))
+                })
+                .on_click(move |_, _, cx| {
+                    store.update(cx, |store, cx| {
+                        store.send_custom(&model_id, json!({ "event": "click" }), cx)
+                    });
+                })
+                .into_any_element();
+            }
+            "LabelModel" | "HTMLModel" | "HTMLMathModel" => {
+                Label::new(model.str("value").unwrap_or_default().to_string()).into_any_element()
+            }
+            model_name => Label::new(format!(
+                "{} widgets aren't supported",
+                model_name.strip_suffix("Model").unwrap_or(model_name)
+            ))
+            .color(Color::Muted)
+            .into_any_element(),
+        };
+
+        h_flex()
+            .gap_2()
+            .when_some(description, |row, description| {
+                row.child(Label::new(description))
+            })
+            .child(control)
+            .into_any_element()
+    }
+
+    fn render_slider(
+        &self,
+        model_id: &str,
+        model: &WidgetModel,
+        integer: bool,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let min = model.f64("min").unwrap_or(0.);
+        let max = model.f64("max").unwrap_or(100.);
+        let step = model
+            .f64("step")
+            .filter(|step| *step > 0.)
+            .unwrap_or(if integer { 1. } else { 0.1 });
+        let value = model.f64("value").unwrap_or(min);
+        let fraction = if max > min {
+            ((value - min) / (max - min)).clamp(0., 1.)
+        } else {
+            0.
+        };
+        let readout = if integer {
+            format!("{}", value.round() as i64)
+        } else {
+            format!("{value:.2}")
+        };
+        let disabled = model.bool("disabled");
+
+        let set_value = {
+            let store = self.store.clone();
+            let slider_bounds = self.slider_bounds.clone();
+            let model_id = model_id.to_string();
+            move |position: gpui::Point<Pixels>, cx: &mut App| {
+                let Some(bounds) = slider_bounds.borrow().get(&model_id).copied() else {
+                    return;
+                };
+                let fraction =
+                    ((position.x - bounds.left()) / bounds.size.width).clamp(0., 1.) as f64;
+                let new_value =
+                    slider_value(min, max, step, fraction, integer).filter(|v| *v != value);
+                if let Some(new_value) = new_value {
+                    let new_value = if integer {
+                        json!(new_value as i64)
+                    } else {
+                        json!(new_value)
+                    };
+                    store.update(cx, |store, cx| {
+                        store.update_state(&model_id, value_state(new_value), cx)
+                    });
+                }
+            }
+        };
+        let set_value = Rc::new(set_value);
+        let colors = cx.theme().colors();
+
+        h_flex()
+            .gap_2()
+            .child(
+                div()
+                    .id(SharedString::from(format!("widget-slider-{model_id}")))
+                    .relative()
+                    .w_48()
+                    .h_4()
+                    .flex()
+                    .items_center()
+                    .when(!disabled, |track| {
+                        let on_down = set_value.clone();
+                        let on_move = set_value;
+                        track
+                            .cursor_pointer()
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                move |event: &MouseDownEvent, _, cx| on_down(event.position, cx),
+                            )
+                            .on_mouse_move(move |event: &MouseMoveEvent, _, cx| {
+                                if event.pressed_button == Some(MouseButton::Left) {
+                                    on_move(event.position, cx)
+                                }
+                            })
+                    })
+                    .child(
+                        div()
+                            .w_full()
+                            .h_1()
+                            .rounded_full()
+                            .bg(colors.element_background)
+                            .child(
+                                div()
+                                    .h_full()
+                                    .w(relative(fraction as f32))
+                                    .rounded_full()
+                                    .bg(colors.text_accent),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .absolute()
+                            .left(relative(fraction as f32))
+                            .ml(px(-6.))
+                            .size(px(12.))
+                            .rounded_full()
+                            .border_1()
+                            .border_color(colors.border)
+                            .bg(colors.text_accent),
+                    )
+                    .child(
+                        canvas(
+                            {
+                                let slider_bounds = self.slider_bounds.clone();
+                                let model_id = model_id.to_string();
+                                move |bounds, _, _| {
+                                    slider_bounds.borrow_mut().insert(model_id, bounds);
+                                }
+                            },
+                            |_, _, _, _| {},
+                        )
+                        .absolute()
+                        .size_full(),
+                    ),
+            )
+            .when(
+                model.state.get("readout") != Some(&Value::Bool(false)),
+                |row| row.child(Label::new(readout).buffer_font(cx)),
+            )
+            .into_any_element()
+    }
+
+    fn render_dropdown(
+        &self,
+        model_id: &str,
+        model: &WidgetModel,
+        disabled: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let labels = model
+            .state
+            .get("_options_labels")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|label| Some(SharedString::from(label.as_str()?.to_string())))
+            .collect::<Vec<_>>();
+        let index = model
+            .state
+            .get("index")
+            .and_then(Value::as_u64)
+            .map(|index| index as usize);
+        let selected = index
+            .and_then(|index| labels.get(index).cloned())
+            .unwrap_or_default();
+
+        let store = self.store.clone();
+        let model_id = model_id.to_string();
+        DropdownMenu::new(
+            SharedString::from(format!("widget-dropdown-{model_id}")),
+            selected,
+            ContextMenu::build(window, cx, move |mut menu, _, _| {
+                for (ix, label) in labels.into_iter().enumerate() {
+                    let store = store.clone();
+                    let model_id = model_id.clone();
+                    menu = menu.toggleable_entry(
+                        label,
+                        index == Some(ix),
+                        IconPosition::Start,
+                        None,
+                        move |_, cx| {
+                            store.update(cx, |store, cx| {
+                                let mut state = Map::new();
+                                state.insert("index".into(), json!(ix));
+                                store.update_state(&model_id, state, cx)
+                            });
+                        },
+                    );
+                }
+                menu
+            }),
+        )
+        .style(DropdownStyle::Outlined)
+        .disabled(disabled)
+        .into_any_element()
+    }
+}
+
+fn value_state(value: Value) -> Map<String, Value> {
+    let mut state = Map::new();
+    state.insert("value".into(), value);
+    state
+}
+
+/// Returns the value at `fraction` of a slider's range, snapped to its step.
+fn slider_value(min: f64, max: f64, step: f64, fraction: f64, integer: bool) -> Option<f64> {
+    if max < min {
+        return None;
+    }
+    let steps = ((max - min) * fraction / step).round();
+    let value = (min + steps * step).clamp(min, max);
+    Some(if integer { value.round() } else { value })
+}
+
+impl OutputContent for WidgetView {
+    fn clipboard_content(&self, _window: &Window, _cx: &App) -> Option<ClipboardItem> {
+        None
+    }
+}
+
+impl Render for WidgetView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let model_id = self.model_id.clone();
+        div()
+            .py_1()
+            .child(self.render_widget(&model_id, window, cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use runtimelib::{CommClose, CommOpen};
+
+    fn open_slider(store: &Entity<WidgetStore>, cx: &mut TestAppContext) {
+        store.update(cx, |store, cx| {
+            let Value::Object(data) = json!({
+                "state": {
+                    "_model_name": "IntSliderModel",
+                    "_view_name": "IntSliderView",
+                    "value": 5,
+                    "min": 0,
+                    "max": 10,
+                },
+                "buffer_paths": [],
+            }) else {
+                unreachable!()
+            };
+            assert!(store.handle_message(
+                &JupyterMessageContent::CommOpen(CommOpen {
+                    comm_id: CommId("slider".into()),
+                    target_name: WIDGET_TARGET_NAME.into(),
+                    data,
+                    target_module: None,
+                }),
+                cx,
+            ));
+        });
+    }
+
+    #[gpui::test]
+    fn test_widget_comm_lifecycle(cx: &mut TestAppContext) {
+        let store = cx.new(|_| WidgetStore::default());
+        open_slider(&store, cx);
+
+        store.update(cx, |store, cx| {
+            let Value::Object(data) = json!({ "method": "update", "state": { "value": 7 } }) else {
+                unreachable!()
+            };
+            assert!(store.handle_message(
+                &JupyterMessageContent::CommMsg(CommMsg {
+                    comm_id: CommId("slider".into()),
+                    data,
+                }),
+                cx,
+            ));
+            let model = store.model("slider").unwrap();
+            assert_eq!(model.f64("value"), Some(7.));
+            assert_eq!(model.f64("max"), Some(10.));
+
+            assert!(store.handle_message(
+                &JupyterMessageContent::CommClose(CommClose {
+                    comm_id: CommId("slider".into()),
+                    data: Map::new(),
+                }),
+                cx,
+            ));
+            assert!(store.model("slider").is_none());
+        });
+    }
+
+    #[gpui::test]
+    fn test_user_changes_are_sent_to_kernel(cx: &mut TestAppContext) {
+        let store = cx.new(|_| WidgetStore::default());
+        open_slider(&store, cx);
+
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let sent = sent.clone();
+            cx.subscribe(&store, move |_, event: &WidgetStoreEvent, _| {
+                let WidgetStoreEvent::SendMessage(message) = event;
+                sent.borrow_mut().push(Value::Object(message.data.clone()));
+            })
+            .detach();
+        });
+
+        store.update(cx, |store, cx| {
+            store.update_state("slider", value_state(json!(3)), cx);
+            assert_eq!(store.model("slider").unwrap().f64("value"), Some(3.));
+        });
+        assert_eq!(
+            *sent.borrow(),
+            vec![json!({ "method": "update", "state": { "value": 3 }, "buffer_paths": [] })]
+        );
+    }
+
+    #[test]
+    fn test_slider_value() {
+        assert_eq!(slider_value(0., 10., 1., 0.34, true), Some(3.));
+        assert_eq!(slider_value(0., 1., 0.25, 0.6, false), Some(0.5));
+        assert_eq!(slider_value(-5., 5., 1., 1.2, true), Some(5.));
+    }
+}
@@ -0,0 +1,640 @@
+//! Conversion between Jupyter notebooks and scripts in the percent format, where cells are
+//! separated by `# %%` comments as in the REPL's cell mode.
+//!
+//! Markdown and raw cells are written as comments, and cell metadata follows the cell marker as
+//! `key=value` pairs with JSON values, e.g. `# %% Setup [markdown] tags=["intro"]`. This matches
+//! the format used by [Jupytext](https://jupytext.readthedocs.io/en/latest/formats-scripts.html).
+
+use std::{ops::Range, path::Path, sync::Arc};
+
+use anyhow::{Context as _, Result, anyhow};
+use editor::{Editor, ToPoint as _};
+use gpui::{App, AsyncWindowContext, Context, PromptLevel, WeakEntity, Window};
+use language::Language;
+use nbformat::v4::{Cell, Notebook};
+use project::Fs;
+use serde_json::{Map, Value, json};
+use workspace::{OpenOptions, Workspace};
+
+use crate::{KernelSpecification, outputs::output_json, repl_store::ReplStore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptCellKind {
+    Code,
+    Markdown,
+    Raw,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptCell {
+    pub kind: ScriptCellKind,
+    /// The cell's source, with the comment prefix removed from markdown and raw cells.
+    pub source: String,
+    pub metadata: Map<String, Value>,
+    /// The rows of the script covered by the cell, including its marker.
+    pub rows: Range<u32>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CellOutputs {
+    pub execution_count: Option<usize>,
+    /// Outputs in the notebook's JSON representation.
+    pub outputs: Vec<Value>,
+}
+
+/// Splits a script into cells at lines starting with `{comment_prefix}%%`. Any code before the
+/// first marker becomes a code cell of its own.
+pub fn parse_script(text: &str, comment_prefix: &str) -> Vec<ScriptCell> {
+    let marker = format!("{comment_prefix}%%");
+    let mut cells = Vec::new();
+    let mut header = None;
+    let mut start_row = 0;
+    let mut lines = Vec::new();
+    let mut row_count = 0;
+
+    for (row, line) in text.lines().enumerate() {
+        let row = row as u32;
+        row_count = row + 1;
+        if let Some(rest) = line.strip_prefix(&marker) {
+            push_cell(&mut cells, header, start_row..row, &lines, comment_prefix);
+            header = Some(rest);
+            start_row = row;
+            lines.clear();
+        } else {
+            lines.push(line);
+        }
+    }
+    push_cell(
+        &mut cells,
+        header,
+        start_row..row_count,
+        &lines,
+        comment_prefix,
+    );
+
+    cells
+}
+
+fn push_cell(
+    cells: &mut Vec<ScriptCell>,
+    header: Option<&str>,
+    rows: Range<u32>,
+    lines: &[&str],
+    comment_prefix: &str,
+) {
+    let end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |ix| ix + 1);
+    let start = lines[..end]
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(end);
+    let lines = &lines[start..end];
+
+    let (kind, metadata) = match header {
+        Some(header) => parse_header(header),
+        None if lines.is_empty() => return,
+        None => (ScriptCellKind::Code, Map::new()),
+    };
+
+    let source = match kind {
+        ScriptCellKind::Code => lines.join("\n"),
+        ScriptCellKind::Markdown | ScriptCellKind::Raw => lines
+            .iter()
+            .map(|line| {
+                line.strip_prefix(comment_prefix)
+                    .or_else(|| line.strip_prefix(comment_prefix.trim_end()))
+                    .unwrap_or(line)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    cells.push(ScriptCell {
+        kind,
+        source,
+        metadata,
+        rows,
+    });
+}
+
+/// Parses the text following a cell marker: an optional title, an optional cell type in
+/// brackets, and `key=value` metadata pairs.
+fn parse_header(header: &str) -> (ScriptCellKind, Map<String, Value>) {
+    let header = header.trim();
+    let mut title = header;
+    let mut metadata = Map::new();
+
+    let word_starts = header
+        .char_indices()
+        .filter(|(ix, _)| *ix == 0 || header[..*ix].ends_with(char::is_whitespace))
+        .map(|(ix, _)| ix);
+    for ix in word_starts {
+        if let Some(pairs) = parse_metadata(&header[ix..]) {
+            title = header[..ix].trim_end();
+            metadata = pairs;
+            break;
+        }
+    }
+
+    let mut kind = ScriptCellKind::Code;
+    for (suffix, suffix_kind) in [
+        ("[markdown]", ScriptCellKind::Markdown),
+        ("[md]", ScriptCellKind::Markdown),
+        ("[raw]", ScriptCellKind::Raw),
+    ] {
+        if let Some(rest) = title.strip_suffix(suffix) {
+            title = rest.trim_end();
+            kind = suffix_kind;
+            break;
+        }
+    }
+
+    if !title.is_empty() {
+        metadata
+            .entry("title")
+            .or_insert_with(|| Value::String(title.to_string()));
+    }
+
+    (kind, metadata)
+}
+
+/// Parses whitespace-separated `key=value` pairs, returning `None` unless all of the text is
+/// made of them.
+fn parse_metadata(text: &str) -> Option<Map<String, Value>> {
+    let mut metadata = Map::new();
+    let mut rest = text.trim_start();
+
+    while !rest.is_empty() {
+        let (key, value) = rest.split_once('=')?;
+        if !is_metadata_key(key) {
+            return None;
+        }
+
+        let mut values = serde_json::Deserializer::from_str(value).into_iter::<Value>();
+        let value_json = values.next()?.ok()?;
+        let remainder = &value[values.byte_offset()..];
+        if !remainder.is_empty() && !remainder.starts_with(char::is_whitespace) {
+            return None;
+        }
+
+        metadata.insert(key.to_string(), value_json);
+        rest = remainder.trim_start();
+    }
+
+    (!metadata.is_empty()).then_some(metadata)
+}
+
+fn is_metadata_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn format_header(marker: &str, kind: ScriptCellKind, metadata: &Map<String, Value>) -> String {
+    let mut header = marker.to_string();
+    let title = metadata.get("title").and_then(Value::as_str);
+    if let Some(title) = title {
+        header.push(' ');
+        header.push_str(title);
+    }
+
+    match kind {
+        ScriptCellKind::Code => {}
+        ScriptCellKind::Markdown => header.push_str(" [markdown]"),
+        ScriptCellKind::Raw => header.push_str(" [raw]"),
+    }
+
+    for (key, value) in metadata {
+        if (key == "title" && title.is_some()) || value.is_null() || !is_metadata_key(key) {
+            continue;
+        }
+        header.push_str(&format!(" {key}={value}"));
+    }
+
+    header
+}
+
+/// Writes a notebook as a percent-format script, with markdown and raw cells as comments.
+pub fn notebook_to_script(notebook: &Notebook, comment_prefix: &str) -> String {
+    let marker = format!("{comment_prefix}%%");
+    let empty_comment = comment_prefix.trim_end();
+    let mut script = String::new();
+
+    for cell in &notebook.cells {
+        let (kind, metadata, source) = match cell {
+            Cell::Code {
+                metadata, source, ..
+            } => (ScriptCellKind::Code, metadata, source),
+            Cell::Markdown {
+                metadata, source, ..
+            } => (ScriptCellKind::Markdown, metadata, source),
+            Cell::Raw {
+                metadata, source, ..
+            } => (ScriptCellKind::Raw, metadata, source),
+        };
+        let metadata = match serde_json::to_value(metadata) {
+            Ok(Value::Object(metadata)) => metadata,
+            _ => Map::new(),
+        };
+
+        if !script.is_empty() {
+            script.push('\n');
+        }
+        script.push_str(&format_header(&marker, kind, &metadata));
+        script.push('\n');
+
+        for line in source.concat().lines() {
+            match kind {
+                ScriptCellKind::Code => script.push_str(line),
+                _ if line.is_empty() => script.push_str(empty_comment),
+                _ => {
+                    script.push_str(comment_prefix);
+                    script.push_str(line);
+                }
+            }
+            script.push('\n');
+        }
+    }
+
+    script
+}
+
+/// Builds a notebook from the cells of a script. `outputs` holds the outputs of each cell, by
+/// index, and may be shorter than `cells`.
+pub fn script_to_notebook(
+    cells: &[ScriptCell],
+    outputs: &[CellOutputs],
+    metadata: Value,
+) -> Result<Notebook> {
+    let cells = cells
+        .iter()
+        .enumerate()
+        .map(|(ix, cell)| {
+            let id = uuid::Uuid::new_v4().to_string();
+            let source = cell
+                .source
+                .split_inclusive('\n')
+                .map(Value::from)
+                .collect::<Vec<_>>();
+            match cell.kind {
+                ScriptCellKind::Code => {
+                    let outputs = outputs.get(ix).cloned().unwrap_or_default();
+                    json!({
+                        "cell_type": "code",
+                        "id": id,
+                        "metadata": cell.metadata,
+                        "source": source,
+                        "execution_count": outputs.execution_count,
+                        "outputs": outputs.outputs,
+                    })
+                }
+                ScriptCellKind::Markdown => json!({
+                    "cell_type": "markdown",
+                    "id": id,
+                    "metadata": cell.metadata,
+                    "source": source,
+                }),
+                ScriptCellKind::Raw => json!({
+                    "cell_type": "raw",
+                    "id": id,
+                    "metadata": cell.metadata,
+                    "source": source,
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let notebook = json!({
+        "cells": cells,
+        "metadata": metadata,
+        "nbformat": 4,
+        "nbformat_minor": 5,
+    });
+
+    match nbformat::parse_notebook(&notebook.to_string())
+        .map_err(|error| anyhow!("Invalid notebook: {error:?}"))?
+    {
+        nbformat::Notebook::V4(notebook) => Ok(notebook),
+        nbformat::Notebook::Legacy(_) => Err(anyhow!("Expected a version 4 notebook")),
+    }
+}
+
+fn notebook_metadata(language: &Language, kernel: Option<&KernelSpecification>) -> Value {
+    let language_name = kernel
+        .map(|kernel| kernel.language().to_lowercase())
+        .unwrap_or_else(|| language.code_fence_block_name().to_lowercase());
+
+    let mut metadata = json!({ "language_info": { "name": language_name } });
+    if let Some(kernel) = kernel {
+        metadata["kernelspec"] = json!({
+            "name": kernel.name().to_string(),
+            "display_name": kernel.name().to_string(),
+            "language": language_name,
+        });
+    }
+    metadata
+}
+
+fn notebook_language_name(notebook: &Notebook) -> Option<String> {
+    notebook
+        .metadata
+        .language_info
+        .as_ref()
+        .map(|language_info| language_info.name.clone())
+        .or_else(|| {
+            notebook
+                .metadata
+                .kernelspec
+                .as_ref()
+                .and_then(|kernelspec| kernelspec.language.clone())
+        })
+}
+
+fn line_comment_prefix(language: &Language) -> Result<String> {
+    language
+        .default_scope()
+        .line_comment_prefixes()
+        .first()
+        .map(|prefix| prefix.to_string())
+        .with_context(|| format!("{} has no line comments", language.name()))
+}
+
+/// Exports the script in the editor to a notebook next to it, including the outputs of the
+/// cells that have been run in its REPL session.
+pub fn export_to_notebook(
+    editor: WeakEntity<Editor>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Result<()> {
+    let editor = editor.upgrade().context("Editor was dropped")?;
+    let workspace = editor
+        .read(cx)
+        .workspace()
+        .context("Editor is not in a workspace")?;
+    let multibuffer = editor.read(cx).buffer().clone();
+    let buffer = multibuffer
+        .read(cx)
+        .as_singleton()
+        .context("Only single files can be exported")?;
+
+    let buffer = buffer.read(cx);
+    let language = buffer
+        .language()
+        .context("The file has no language")?
+        .clone();
+    let comment_prefix = line_comment_prefix(&language)?;
+    let project_path = buffer
+        .project_path(cx)
+        .context("The file is not in the project")?;
+    let notebook_path = workspace
+        .read(cx)
+        .project()
+        .read(cx)
+        .absolute_path(&project_path, cx)
+        .context("The file has no path")?
+        .with_extension("ipynb");
+    let cells = parse_script(&buffer.text(), &comment_prefix);
+
+    let store = ReplStore::global(cx);
+    let session = store.read(cx).get_session(editor.entity_id()).cloned();
+
+    let mut outputs = vec![CellOutputs::default(); cells.len()];
+    if let Some(session) = &session {
+        let snapshot = multibuffer.read(cx).snapshot(cx);
+        let mut executions = session
+            .read(cx)
+            .executions()
+            .map(|(range, execution_view)| {
+                let execution_view = execution_view.read(cx);
+                (
+                    execution_view.execution_count,
+                    range.start.to_point(&snapshot).row,
+                    execution_view.output_messages().to_vec(),
+                )
+            })
+            .collect::<Vec<_>>();
+        executions.sort_by_key(|(execution_count, row, _)| (*execution_count, *row));
+
+        for (execution_count, row, messages) in executions {
+            let Some(ix) = cells.iter().position(|cell| cell.rows.contains(&row)) else {
+                continue;
+            };
+            let cell_outputs = &mut outputs[ix];
+            cell_outputs.execution_count = execution_count.or(cell_outputs.execution_count);
+            cell_outputs
+                .outputs
+                .extend(messages.iter().filter_map(output_json));
+        }
+    }
+
+    let kernel_specification = session
+        .map(|session| session.read(cx).kernel_specification.clone())
+        .or_else(|| {
+            store
+                .read(cx)
+                .active_kernelspec(project_path.worktree_id, Some(language.clone()), cx)
+        });
+    let metadata = notebook_metadata(&language, kernel_specification.as_ref());
+    let notebook = script_to_notebook(&cells, &outputs, metadata)?;
+    let mut contents = serde_json::to_string_pretty(&notebook)?;
+    contents.push('\n');
+
+    let fs = store.read(cx).fs().clone();
+    let workspace = workspace.downgrade();
+    window
+        .spawn(cx, async move |cx| {
+            if !confirm_overwrite(&fs, &notebook_path, cx).await? {
+                return Ok(());
+            }
+            fs.atomic_write(notebook_path.clone(), contents).await?;
+            workspace
+                .update_in(cx, |workspace, window, cx| {
+                    workspace.open_abs_path(notebook_path, OpenOptions::default(), window, cx)
+                })?
+                .await?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+    Ok(())
+}
+
+/// Converts the notebook open in the active item to a percent-format script next to it.
+pub fn convert_notebook_to_script(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Result<()> {
+    let project_path = workspace
+        .active_item(cx)
+        .and_then(|item| item.project_path(cx))
+        .context("No file is open")?;
+    anyhow::ensure!(
+        project_path.path.extension().unwrap_or_default() == "ipynb",
+        "The active file is not a notebook"
+    );
+
+    let project = workspace.project().read(cx);
+    let notebook_path = project
+        .absolute_path(&project_path, cx)
+        .context("The notebook has no path")?;
+    let fs = project.fs().clone();
+    let languages = project.languages().clone();
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let contents = fs.load(&notebook_path).await?;
+        let notebook = match nbformat::parse_notebook(&contents)
+            .map_err(|error| anyhow!("Failed to parse notebook: {error:?}"))?
+        {
+            nbformat::Notebook::V4(notebook) => notebook,
+            nbformat::Notebook::Legacy(legacy_notebook) => {
+                nbformat::upgrade_legacy_notebook(legacy_notebook)?
+            }
+        };
+
+        let language_name =
+            notebook_language_name(&notebook).context("The notebook has no language")?;
+        let language = languages.language_for_name(&language_name).await?;
+        let comment_prefix = line_comment_prefix(&language)?;
+        let extension = language
+            .path_suffixes()
+            .first()
+            .with_context(|| format!("{} has no file extension", language.name()))?;
+
+        let script_path = notebook_path.with_extension(extension);
+        if !confirm_overwrite(&fs, &script_path, cx).await? {
+            return Ok(());
+        }
+        fs.atomic_write(
+            script_path.clone(),
+            notebook_to_script(&notebook, &comment_prefix),
+        )
+        .await?;
+
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_abs_path(script_path, OpenOptions::default(), window, cx)
+            })?
+            .await?;
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+
+    Ok(())
+}
+
+/// Asks the user whether to replace `path` if it already exists. Returns whether it may be
+/// written.
+async fn confirm_overwrite(
+    fs: &Arc<dyn Fs>,
+    path: &Path,
+    cx: &mut AsyncWindowContext,
+) -> Result<bool> {
+    if !fs.is_file(path).await {
+        return Ok(true);
+    }
+    let file_name = path
+        .file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy();
+    let message = format!("{file_name} already exists. Do you want to replace it?");
+    let answer = cx.update(|window, cx| {
+        window.prompt(
+            PromptLevel::Warning,
+            &message,
+            None,
+            &["Replace", "Cancel"],
+            cx,
+        )
+    })?;
+    Ok(matches!(answer.await, Ok(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let script = "import os\n\n# %% Load data\nx = 1\n\ny = 2\n\n\n# %% [markdown] tags=[\"intro\"]\n# # Heading\n#\n# Some *text*\n\n# %% [raw]\n# raw text\n";
+        let cells = parse_script(script, "# ");
+
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells[0].kind, ScriptCellKind::Code);
+        assert_eq!(cells[0].source, "import os");
+        assert_eq!(cells[0].rows, 0..2);
+
+        assert_eq!(cells[1].kind, ScriptCellKind::Code);
+        assert_eq!(cells[1].source, "x = 1\n\ny = 2");
+        assert_eq!(cells[1].metadata["title"], json!("Load data"));
+        assert_eq!(cells[1].rows, 2..8);
+
+        assert_eq!(cells[2].kind, ScriptCellKind::Markdown);
+        assert_eq!(cells[2].source, "# Heading\n\nSome *text*");
+        assert_eq!(cells[2].metadata["tags"], json!(["intro"]));
+        assert!(cells[2].metadata.get("title").is_none());
+
+        assert_eq!(cells[3].kind, ScriptCellKind::Raw);
+        assert_eq!(cells[3].source, "raw text");
+        assert_eq!(cells[3].rows, 13..15);
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let (kind, metadata) = parse_header(" Plot it a=1 b={\"c\": [1, 2]} d=\"e f\"");
+        assert_eq!(kind, ScriptCellKind::Code);
+        assert_eq!(
+            Value::Object(metadata),
+            json!({"title": "Plot it", "a": 1, "b": {"c": [1, 2]}, "d": "e f"})
+        );
+
+        let (kind, metadata) = parse_header(" x = 1");
+        assert_eq!(kind, ScriptCellKind::Code);
+        assert_eq!(Value::Object(metadata), json!({"title": "x = 1"}));
+
+        let (kind, metadata) = parse_header("");
+        assert_eq!(kind, ScriptCellKind::Code);
+        assert!(metadata.is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let script = "# %% tags=[\"parameters\"]\nx = 1\n\n# %% [markdown]\n# # Results\n#\n# More text\n\n# %%\nprint(x)\n";
+        let cells = parse_script(script, "# ");
+        let outputs = [
+            CellOutputs::default(),
+            CellOutputs::default(),
+            CellOutputs {
+                execution_count: Some(2),
+                outputs: vec![json!({"output_type": "stream", "name": "stdout", "text": "1\n"})],
+            },
+        ];
+        let notebook = script_to_notebook(
+            &cells,
+            &outputs,
+            json!({"language_info": {"name": "python"}}),
+        )
+        .unwrap();
+
+        assert_eq!(notebook.cells.len(), 3);
+        match &notebook.cells[2] {
+            Cell::Code {
+                execution_count,
+                outputs,
+                source,
+                ..
+            } => {
+                assert_eq!(*execution_count, Some(2));
+                assert_eq!(outputs.len(), 1);
+                assert_eq!(source.concat(), "print(x)");
+            }
+            _ => panic!("expected a code cell"),
+        }
+        assert_eq!(notebook_language_name(&notebook).as_deref(), Some("python"));
+
+        assert_eq!(notebook_to_script(&notebook, "# "), script);
+    }
+}
@@ -1,13 +1,17 @@
 pub mod components;
 mod jupyter_settings;
+mod kernel_inspector;
 pub mod kernels;
 pub mod notebook;
 mod outputs;
+mod percent_script;
 mod repl_editor;
 mod repl_sessions_ui;
 mod repl_settings;
 mod repl_store;
 mod session;
+mod variable_explorer;
+mod variables;
 
 use std::{sync::Arc, time::Duration};
 
@@ -20,11 +24,13 @@ pub use crate::jupyter_settings::JupyterSettings;
 pub use crate::kernels::{Kernel, KernelSpecification, KernelStatus};
 pub use crate::repl_editor::*;
 pub use crate::repl_sessions_ui::{
-    ClearOutputs, Interrupt, ReplSessionsPage, Restart, Run, Sessions, Shutdown,
+    ClearOutputs, ConvertNotebookToScript, ExportToNotebook, Interrupt, ReplSessionsPage, Restart,
+    Run, Sessions, ShowVariables, Shutdown,
 };
 pub use crate::repl_settings::ReplSettings;
 use crate::repl_store::ReplStore;
 pub use crate::session::Session;
+pub use crate::variable_explorer::VariableExplorer;
 
 pub const KERNEL_DOCS_URL: &str = "https://zed.dev/docs/repl#changing-kernels";
 
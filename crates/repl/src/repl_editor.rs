@@ -8,11 +8,13 @@ use editor::{Editor, MultiBufferOffset};
 use gpui::{App, Entity, WeakEntity, Window, prelude::*};
 use language::{BufferSnapshot, Language, LanguageName, Point};
 use project::{ProjectItem as _, WorktreeId};
+use workspace::Workspace;
 
 use crate::repl_store::ReplStore;
 use crate::session::SessionEvent;
 use crate::{
-    ClearOutputs, Interrupt, JupyterSettings, KernelSpecification, Restart, Session, Shutdown,
+    ClearOutputs, Interrupt, JupyterSettings, KernelSpecification, Restart, Session, ShowVariables,
+    Shutdown, VariableExplorer,
 };
 
 pub fn assign_kernelspec(
@@ -59,6 +61,7 @@ pub fn assign_kernelspec(
                             store.remove_session(shutdown_event.entity_id());
                         });
                     }
+                    SessionEvent::ExecutionFinished => {}
                 }
             })
             .detach();
@@ -134,6 +137,7 @@ pub fn run(
                                 store.remove_session(shutdown_event.entity_id());
                             });
                         }
+                        SessionEvent::ExecutionFinished => {}
                     }
                 })
                 .detach();
@@ -288,6 +292,33 @@ pub fn restart(editor: WeakEntity<Editor>, window: &mut Window, cx: &mut App) {
     });
 }
 
+pub fn show_variables(editor: WeakEntity<Editor>, window: &mut Window, cx: &mut App) {
+    let store = ReplStore::global(cx);
+    let entity_id = editor.entity_id();
+    let Some(session) = store.read(cx).get_session(entity_id).cloned() else {
+        return;
+    };
+    let Some(workspace) = Workspace::for_window(window, cx) else {
+        return;
+    };
+
+    workspace.update(cx, |workspace, cx| {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .filter_map(|item| item.downcast::<VariableExplorer>())
+            .find(|explorer| explorer.read(cx).session() == &session);
+
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+        } else {
+            let explorer = VariableExplorer::new(session, cx);
+            workspace.add_item_to_active_pane(Box::new(explorer), None, true, window, cx);
+        }
+    });
+}
+
 pub fn setup_editor_session_actions(editor: &mut Editor, editor_handle: WeakEntity<Editor>) {
     editor
         .register_action({
@@ -330,7 +361,7 @@ pub fn setup_editor_session_actions(editor: &mut Editor, editor_handle: WeakEnti
 
     editor
         .register_action({
-            let editor_handle = editor_handle;
+            let editor_handle = editor_handle.clone();
             move |_: &Restart, window, cx| {
                 if !JupyterSettings::enabled(cx) {
                     return;
@@ -340,6 +371,19 @@ pub fn setup_editor_session_actions(editor: &mut Editor, editor_handle: WeakEnti
             }
         })
         .detach();
+
+    editor
+        .register_action({
+            let editor_handle = editor_handle;
+            move |_: &ShowVariables, window, cx| {
+                if !JupyterSettings::enabled(cx) {
+                    return;
+                }
+
+                crate::show_variables(editor_handle.clone(), window, cx);
+            }
+        })
+        .detach();
 }
 
 fn cell_range(buffer: &BufferSnapshot, start_row: u32, end_row: u32) -> Range<Point> {
@@ -29,8 +29,14 @@ actions!(
         Shutdown,
         /// Restarts the current kernel.
         Restart,
+        /// Opens the variable explorer for the current editor's REPL session.
+        ShowVariables,
         /// Refreshes the list of available kernelspecs.
-        RefreshKernelspecs
+        RefreshKernelspecs,
+        /// Exports the current script and its outputs to a Jupyter notebook next to it.
+        ExportToNotebook,
+        /// Converts the current Jupyter notebook to a script with `# %%` cells next to it.
+        ConvertNotebookToScript
     ]
 );
 
@@ -64,6 +70,10 @@ pub fn init(cx: &mut App) {
                     store.refresh_kernelspecs(cx).detach();
                 });
             });
+
+            workspace.register_action(|workspace, _: &ConvertNotebookToScript, window, cx| {
+                crate::percent_script::convert_notebook_to_script(workspace, window, cx).log_err();
+            });
         },
     )
     .detach();
@@ -101,6 +111,8 @@ pub fn init(cx: &mut App) {
 
                 let editor_handle = cx.entity().downgrade();
 
+                crate::kernel_inspector::register(editor, cx);
+
                 if let Some(language) = language
                     && language.name() == "Python".into()
                     && let (Some(project_path), Some(project)) = (project_path, project)
@@ -126,6 +138,24 @@ pub fn init(cx: &mut App) {
                     })
                     .detach();
 
+                editor
+                    .register_action({
+                        let editor_handle = editor_handle.clone();
+                        move |_: &ExportToNotebook, window, cx| {
+                            if !JupyterSettings::enabled(cx) {
+                                return;
+                            }
+
+                            crate::percent_script::export_to_notebook(
+                                editor_handle.clone(),
+                                window,
+                                cx,
+                            )
+                            .log_err();
+                        }
+                    })
+                    .detach();
+
                 editor
                     .register_action({
                         move |_: &RunInPlace, window, cx| {
@@ -268,6 +268,35 @@ impl ReplStore {
             .cloned()
     }
 
+    /// Finds a kernel for a notebook, preferring the kernel it was saved with, then the kernel
+    /// selected for its language in the settings, then any kernel for its language.
+    pub fn kernelspec_for_notebook(
+        &self,
+        worktree_id: WorktreeId,
+        kernel_name: Option<&str>,
+        language: Option<&str>,
+        cx: &App,
+    ) -> Option<KernelSpecification> {
+        let settings = JupyterSettings::get_global(cx);
+        let selected_kernel = language
+            .and_then(|language| settings.kernel_selections.get(&language.to_lowercase()))
+            .map(String::as_str);
+
+        kernel_name
+            .into_iter()
+            .chain(selected_kernel)
+            .find_map(|name| {
+                self.kernel_specifications_for_worktree(worktree_id)
+                    .find(|spec| spec.name().eq_ignore_ascii_case(name))
+            })
+            .or_else(|| {
+                let language = language?;
+                self.kernel_specifications_for_worktree(worktree_id)
+                    .find(|spec| spec.language().eq_ignore_ascii_case(language))
+            })
+            .cloned()
+    }
+
     pub fn get_session(&self, entity_id: EntityId) -> Option<&Entity<Session>> {
         self.sessions.get(&entity_id)
     }
@@ -6,6 +6,7 @@ use crate::{
     kernels::{Kernel, KernelSpecification, NativeRunningKernel},
     outputs::{
         ExecutionStatus, ExecutionView, ExecutionViewFinishedEmpty, ExecutionViewFinishedSmall,
+        widgets::{WidgetStore, WidgetStoreEvent},
     },
 };
 use anyhow::Context as _;
@@ -24,7 +25,7 @@ use project::InlayId;
 /// Marker types
 enum ReplExecutedRange {}
 
-use futures::FutureExt as _;
+use futures::{FutureExt as _, channel::oneshot};
 use gpui::{
     Context, Entity, EventEmitter, Render, Subscription, Task, WeakEntity, Window, div, prelude::*,
 };
@@ -32,26 +33,56 @@ use language::Point;
 use project::Fs;
 use runtimelib::{
     ExecuteRequest, ExecutionState, InterruptRequest, JupyterMessage, JupyterMessageContent,
-    ShutdownRequest,
+    ShutdownRequest, Stdio,
 };
-use std::{env::temp_dir, ops::Range, sync::Arc, time::Duration};
+use std::{env::temp_dir, ops::Range, path::PathBuf, sync::Arc, time::Duration};
 use theme::ActiveTheme;
 use ui::{IconButtonShape, Tooltip, prelude::*};
 use util::ResultExt as _;
+use workspace::Workspace;
 
 pub struct Session {
     fs: Arc<dyn Fs>,
     editor: WeakEntity<Editor>,
+    /// Where the kernel is started, when it isn't derived from the editor's file.
+    working_directory: Option<PathBuf>,
     pub kernel: Kernel,
     pub kernel_specification: KernelSpecification,
 
     blocks: HashMap<String, EditorBlock>,
-    result_inlays: HashMap<String, (InlayId, Range<Anchor>, usize)>,
+    /// Executions whose outputs are shown outside of an editor, such as notebook cells.
+    cell_executions: HashMap<String, Entity<ExecutionView>>,
+    /// Messages sent while the kernel was restarting, to be sent once it has started again.
+    queued_messages: Vec<JupyterMessage>,
+    /// Executions whose small outputs were replaced with an inlay, keeping their view so the
+    /// outputs can still be exported.
+    result_inlays: HashMap<String, (InlayId, Range<Anchor>, usize, Entity<ExecutionView>)>,
     next_inlay_id: usize,
+    widgets: Entity<WidgetStore>,
+    pending_requests: HashMap<String, PendingRequest>,
 
     _subscriptions: Vec<Subscription>,
 }
 
+/// The reply to a request made with [`Session::request`], along with anything the kernel
+/// printed to stdout while handling it.
+#[derive(Debug)]
+pub struct KernelResponse {
+    pub stdout: String,
+    pub reply: JupyterMessageContent,
+}
+
+/// A request the session made on its own behalf, rather than to show the user its outputs.
+///
+/// Replies arrive on the shell channel and outputs on iopub, which aren't ordered with respect
+/// to each other, so the request is only finished once the kernel has also gone idle.
+struct PendingRequest {
+    stdout: String,
+    reply: Option<JupyterMessageContent>,
+    idle: bool,
+    tx: oneshot::Sender<KernelResponse>,
+}
+
 struct EditorBlock {
     code_range: Range<Anchor>,
     invalidation_anchor: Anchor,
@@ -67,13 +98,15 @@ impl EditorBlock {
         editor: WeakEntity<Editor>,
         code_range: Range<Anchor>,
         status: ExecutionStatus,
+        widgets: Entity<WidgetStore>,
         on_close: CloseBlockFn,
         cx: &mut Context<Session>,
     ) -> anyhow::Result<Self> {
         let editor = editor.upgrade().context("editor is not open")?;
         let workspace = editor.read(cx).workspace().context("workspace dropped")?;
 
-        let execution_view = cx.new(|cx| ExecutionView::new(status, workspace.downgrade(), cx));
+        let execution_view =
+            cx.new(|cx| ExecutionView::new(status, workspace.downgrade(), widgets, cx));
 
         let (block_id, invalidation_anchor) = editor.update(cx, |editor, cx| {
             let buffer = editor.buffer().clone();
@@ -210,6 +243,36 @@ impl Session {
         kernel_specification: KernelSpecification,
         window: &mut Window,
         cx: &mut Context<Self>,
+    ) -> Self {
+        Self::with_working_directory(editor, None, fs, kernel_specification, window, cx)
+    }
+
+    /// Creates a session that isn't attached to an editor, whose code is run with
+    /// [`Session::execute_cell`].
+    pub fn for_notebook(
+        working_directory: PathBuf,
+        fs: Arc<dyn Fs>,
+        kernel_specification: KernelSpecification,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::with_working_directory(
+            WeakEntity::new_invalid(),
+            Some(working_directory),
+            fs,
+            kernel_specification,
+            window,
+            cx,
+        )
+    }
+
+    fn with_working_directory(
+        editor: WeakEntity<Editor>,
+        working_directory: Option<PathBuf>,
+        fs: Arc<dyn Fs>,
+        kernel_specification: KernelSpecification,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Self {
         let subscription = match editor.upgrade() {
             Some(editor) => {
@@ -227,15 +290,27 @@ impl Session {
             })
             .ok();
 
+        let widgets = cx.new(|_| WidgetStore::default());
+        let widgets_subscription =
+            cx.subscribe(&widgets, |session, _, event: &WidgetStoreEvent, cx| {
+                let WidgetStoreEvent::SendMessage(message) = event;
+                session.send(message.clone().into(), cx).ok();
+            });
+
         let mut session = Self {
             fs,
             editor,
+            working_directory,
             kernel: Kernel::StartingKernel(Task::ready(()).shared()),
             blocks: HashMap::default(),
+            cell_executions: HashMap::default(),
+            queued_messages: Vec::new(),
             result_inlays: HashMap::default(),
             next_inlay_id: 0,
+            widgets,
+            pending_requests: HashMap::default(),
             kernel_specification,
-            _subscriptions: vec![subscription],
+            _subscriptions: vec![subscription, widgets_subscription],
         };
 
         session.start_kernel(window, cx);
@@ -246,9 +321,13 @@ impl Session {
         let kernel_language = self.kernel_specification.language();
         let entity_id = self.editor.entity_id();
         let working_directory = self
-            .editor
-            .upgrade()
-            .and_then(|editor| editor.read(cx).working_directory(cx))
+            .working_directory
+            .clone()
+            .or_else(|| {
+                self.editor
+                    .upgrade()
+                    .and_then(|editor| editor.read(cx).working_directory(cx))
+            })
             .unwrap_or_else(temp_dir);
 
         telemetry::event!(
@@ -260,6 +339,9 @@ impl Session {
 
         let session_view = cx.entity();
 
+        // Widgets belong to the kernel that created them.
+        self.widgets.update(cx, |widgets, cx| widgets.clear(cx));
+
         let kernel = match self.kernel_specification.clone() {
             KernelSpecification::Jupyter(kernel_specification)
             | KernelSpecification::PythonEnv(kernel_specification) => NativeRunningKernel::new(
@@ -302,27 +384,35 @@ impl Session {
             .shared();
 
         self.kernel(Kernel::StartingKernel(pending_kernel), cx);
+        for message in std::mem::take(&mut self.queued_messages) {
+            self.send_when_ready(message, cx);
+        }
         cx.notify();
     }
 
     pub fn kernel_errored(&mut self, error_message: String, cx: &mut Context<Self>) {
         self.kernel(Kernel::ErroredLaunch(error_message.clone()), cx);
 
-        self.blocks.values().for_each(|block| {
-            block.execution_view.update(cx, |execution_view, cx| {
-                match execution_view.status {
-                    ExecutionStatus::Finished => {
-                        // Do nothing when the output was good
-                    }
-                    _ => {
-                        // All other cases, set the status to errored
-                        execution_view.status =
-                            ExecutionStatus::KernelErrored(error_message.clone())
+        self.blocks
+            .values()
+            .map(|block| &block.execution_view)
+            .chain(self.cell_executions.values())
+            .for_each(|execution_view| {
+                execution_view.update(cx, |execution_view, cx| {
+                    match execution_view.status {
+                        ExecutionStatus::Finished => {
+                            // Do nothing when the output was good
+                        }
+                        _ => {
+                            // All other cases, set the status to errored
+                            execution_view.status =
+                                ExecutionStatus::KernelErrored(error_message.clone())
+                        }
                     }
-                }
-                cx.notify();
+                    cx.notify();
+                });
             });
-        });
+        self.cell_executions.clear();
     }
 
     fn on_buffer_event(
@@ -352,7 +442,7 @@ impl Session {
             let mut inlays_to_remove: Vec<InlayId> = Vec::new();
 
             self.result_inlays
-                .retain(|id, (inlay_id, code_range, original_len)| {
+                .retain(|id, (inlay_id, code_range, original_len, _)| {
                     let start_offset = code_range.start.to_offset(&snapshot);
                     let end_offset = code_range.end.to_offset(&snapshot);
                     let current_len = end_offset.saturating_sub(start_offset);
@@ -400,6 +490,32 @@ impl Session {
         anyhow::Ok(())
     }
 
+    /// Sends a request to the kernel without displaying its outputs, e.g. to inspect the
+    /// kernel's state.
+    pub fn request(
+        &mut self,
+        message: JupyterMessage,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<KernelResponse>> {
+        let Kernel::RunningKernel(kernel) = &mut self.kernel else {
+            return Task::ready(Err(anyhow::anyhow!("kernel is not running")));
+        };
+
+        let (tx, rx) = oneshot::channel();
+        self.pending_requests.insert(
+            message.header.msg_id.clone(),
+            PendingRequest {
+                stdout: String::new(),
+                reply: None,
+                idle: false,
+                tx,
+            },
+        );
+        kernel.request_tx().try_send(message).ok();
+
+        cx.background_spawn(async move { rx.await.context("kernel request was cancelled") })
+    }
+
     fn replace_block_with_inlay(&mut self, message_id: &str, text: &str, cx: &mut Context<Self>) {
         let Some(block) = self.blocks.remove(message_id) else {
             return;
@@ -436,6 +552,7 @@ impl Session {
                     InlayId::ReplResult(inlay_id),
                     code_range.clone(),
                     original_len,
+                    block.execution_view.clone(),
                 ),
             );
 
@@ -449,12 +566,29 @@ impl Session {
         cx.notify();
     }
 
+    /// The code ranges that have been run in the editor, along with their outputs.
+    pub(crate) fn executions(
+        &self,
+    ) -> impl Iterator<Item = (&Range<Anchor>, &Entity<ExecutionView>)> {
+        self.blocks
+            .values()
+            .map(|block| (&block.code_range, &block.execution_view))
+            .chain(
+                self.result_inlays
+                    .values()
+                    .map(|(_, code_range, _, execution_view)| (code_range, execution_view)),
+            )
+    }
+
     pub fn clear_outputs(&mut self, cx: &mut Context<Self>) {
         let blocks_to_remove: HashSet<CustomBlockId> =
             self.blocks.values().map(|block| block.block_id).collect();
 
-        let inlays_to_remove: Vec<InlayId> =
-            self.result_inlays.values().map(|(id, _, _)| *id).collect();
+        let inlays_to_remove: Vec<InlayId> = self
+            .result_inlays
+            .values()
+            .map(|(id, _, _, _)| *id)
+            .collect();
 
         self.editor
             .update(cx, |editor, cx| {
@@ -508,7 +642,7 @@ impl Session {
         });
 
         self.result_inlays
-            .retain(|_key, (inlay_id, inlay_range, _)| {
+            .retain(|_key, (inlay_id, inlay_range, _, _)| {
                 if anchor_range.overlaps(inlay_range, &buffer) {
                     inlays_to_remove.push(*inlay_id);
                     gutter_ranges_to_remove.push(inlay_range.clone());
@@ -531,14 +665,7 @@ impl Session {
             })
             .ok();
 
-        let status = match &self.kernel {
-            Kernel::Restarting => ExecutionStatus::Restarting,
-            Kernel::RunningKernel(_) => ExecutionStatus::Queued,
-            Kernel::StartingKernel(_) => ExecutionStatus::ConnectingToKernel,
-            Kernel::ErroredLaunch(error) => ExecutionStatus::KernelErrored(error.clone()),
-            Kernel::ShuttingDown => ExecutionStatus::ShuttingDown,
-            Kernel::Shutdown => ExecutionStatus::Shutdown,
-        };
+        let status = self.execution_status();
 
         let parent_message_id = message.header.msg_id.clone();
         let session_view = cx.entity().downgrade();
@@ -572,6 +699,7 @@ impl Session {
             self.editor.clone(),
             anchor_range.clone(),
             status,
+            self.widgets.clone(),
             on_close,
             cx,
         ) else {
@@ -615,6 +743,58 @@ impl Session {
         self.blocks
             .insert(message.header.msg_id.clone(), editor_block);
 
+        self.send_when_ready(message, cx);
+
+        if move_down {
+            editor.update(cx, move |editor, cx| {
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::top_relative(8)),
+                    window,
+                    cx,
+                    |selections| {
+                        selections.select_ranges([new_cursor_pos..new_cursor_pos]);
+                    },
+                );
+            });
+        }
+    }
+
+    /// Runs code whose outputs are shown by the returned view rather than in an editor, e.g.
+    /// for a notebook cell.
+    pub fn execute_cell(
+        &mut self,
+        code: String,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Entity<ExecutionView> {
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            ..ExecuteRequest::default()
+        }
+        .into();
+
+        let status = self.execution_status();
+        let widgets = self.widgets.clone();
+        let execution_view = cx.new(|cx| ExecutionView::new(status, workspace, widgets, cx));
+        self.cell_executions
+            .insert(message.header.msg_id.clone(), execution_view.clone());
+
+        self.send_when_ready(message, cx);
+        execution_view
+    }
+
+    fn execution_status(&self) -> ExecutionStatus {
+        match &self.kernel {
+            Kernel::Restarting => ExecutionStatus::Restarting,
+            Kernel::RunningKernel(_) => ExecutionStatus::Queued,
+            Kernel::StartingKernel(_) => ExecutionStatus::ConnectingToKernel,
+            Kernel::ErroredLaunch(error) => ExecutionStatus::KernelErrored(error.clone()),
+            Kernel::ShuttingDown => ExecutionStatus::ShuttingDown,
+            Kernel::Shutdown => ExecutionStatus::Shutdown,
+        }
+    }
+
+    fn send_when_ready(&mut self, message: JupyterMessage, cx: &mut Context<Self>) {
         match &self.kernel {
             Kernel::RunningKernel(_) => {
                 self.send(message, cx).ok();
@@ -632,24 +812,33 @@ impl Session {
                 })
                 .detach();
             }
+            Kernel::Restarting => self.queued_messages.push(message),
             _ => {}
         }
+    }
 
-        if move_down {
-            editor.update(cx, move |editor, cx| {
-                editor.change_selections(
-                    SelectionEffects::scroll(Autoscroll::top_relative(8)),
-                    window,
-                    cx,
-                    |selections| {
-                        selections.select_ranges([new_cursor_pos..new_cursor_pos]);
-                    },
-                );
+    /// Marks the executions shown outside of an editor as abandoned, since the kernel running
+    /// them is going away.
+    fn abandon_cell_executions(&mut self, cx: &mut Context<Self>) {
+        for (_, execution_view) in self.cell_executions.drain() {
+            execution_view.update(cx, |execution_view, cx| {
+                if !matches!(execution_view.status, ExecutionStatus::Finished) {
+                    execution_view.status = ExecutionStatus::Shutdown;
+                    cx.notify();
+                }
             });
         }
     }
 
     pub fn route(&mut self, message: &JupyterMessage, window: &mut Window, cx: &mut Context<Self>) {
+        // Widgets outlive the execution that created them, so their comm messages are handled
+        // by the session rather than an execution's block.
+        if self.widgets.update(cx, |widgets, cx| {
+            widgets.handle_message(&message.content, cx)
+        }) {
+            return;
+        }
+
         let parent_message_id = match message.parent_header.as_ref() {
             Some(header) => &header.msg_id,
             None => return,
@@ -659,6 +848,13 @@ impl Session {
             JupyterMessageContent::Status(status) => {
                 self.kernel.set_execution_state(&status.execution_state);
 
+                if matches!(status.execution_state, ExecutionState::Idle)
+                    && (self.blocks.contains_key(parent_message_id)
+                        || self.cell_executions.contains_key(parent_message_id))
+                {
+                    cx.emit(SessionEvent::ExecutionFinished);
+                }
+
                 telemetry::event!(
                     "Kernel Status Changed",
                     kernel_language = self.kernel_specification.language(),
@@ -679,19 +875,74 @@ impl Session {
                     return;
                 };
 
-                self.blocks.iter_mut().for_each(|(_, block)| {
-                    block.execution_view.update(cx, |execution_view, cx| {
-                        execution_view.update_display_data(&update.data, &display_id, window, cx);
+                self.blocks
+                    .values()
+                    .map(|block| &block.execution_view)
+                    .chain(self.cell_executions.values())
+                    .for_each(|execution_view| {
+                        execution_view.update(cx, |execution_view, cx| {
+                            execution_view.update_display_data(
+                                &update.data,
+                                &display_id,
+                                window,
+                                cx,
+                            );
+                        });
                     });
-                });
                 return;
             }
             _ => {}
         }
 
+        if let Some(request) = self.pending_requests.get_mut(parent_message_id) {
+            match &message.content {
+                JupyterMessageContent::StreamContent(stream)
+                    if matches!(stream.name, Stdio::Stdout) =>
+                {
+                    request.stdout.push_str(&stream.text);
+                }
+                JupyterMessageContent::Status(status) => {
+                    request.idle = matches!(status.execution_state, ExecutionState::Idle);
+                }
+                JupyterMessageContent::ExecuteReply(_)
+                | JupyterMessageContent::InspectReply(_)
+                | JupyterMessageContent::CompleteReply(_) => {
+                    request.reply = Some(message.content.clone());
+                }
+                _ => {}
+            }
+
+            if request.idle
+                && request.reply.is_some()
+                && let Some(request) = self.pending_requests.remove(parent_message_id)
+                && let Some(reply) = request.reply
+            {
+                request
+                    .tx
+                    .send(KernelResponse {
+                        stdout: request.stdout,
+                        reply,
+                    })
+                    .ok();
+            }
+            return;
+        }
+
         if let Some(block) = self.blocks.get_mut(parent_message_id) {
             block.handle_message(message, window, cx);
         }
+
+        if let Some(execution_view) = self.cell_executions.get(parent_message_id) {
+            execution_view.update(cx, |execution_view, cx| {
+                execution_view.push_message(&message.content, window, cx);
+            });
+
+            if let JupyterMessageContent::Status(status) = &message.content
+                && matches!(status.execution_state, ExecutionState::Idle)
+            {
+                self.cell_executions.remove(parent_message_id);
+            }
+        }
     }
 
     pub fn interrupt(&mut self, cx: &mut Context<Self>) {
@@ -711,6 +962,14 @@ impl Session {
             cx.emit(SessionEvent::Shutdown(self.editor.clone()));
         }
 
+        // Requests can only be answered by the kernel they were sent to.
+        if !matches!(kernel, Kernel::RunningKernel(_)) {
+            self.pending_requests.clear();
+        }
+        if matches!(kernel, Kernel::Shutdown | Kernel::ErroredLaunch(_)) {
+            self.queued_messages.clear();
+        }
+
         let kernel_status = KernelStatus::from(&kernel).to_string();
         let kernel_language = self.kernel_specification.language();
 
@@ -725,6 +984,7 @@ impl Session {
     }
 
     pub fn shutdown(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.abandon_cell_executions(cx);
         let kernel = std::mem::replace(&mut self.kernel, Kernel::ShuttingDown);
 
         match kernel {
@@ -759,6 +1019,7 @@ impl Session {
     }
 
     pub fn restart(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.abandon_cell_executions(cx);
         let kernel = std::mem::replace(&mut self.kernel, Kernel::Restarting);
 
         match kernel {
@@ -803,6 +1064,8 @@ impl Session {
 
 pub enum SessionEvent {
     Shutdown(WeakEntity<Editor>),
+    /// Code the user ran has finished executing, so the kernel's state may have changed.
+    ExecutionFinished,
 }
 
 impl EventEmitter<SessionEvent> for Session {}
@@ -0,0 +1,355 @@
+//! A workspace item listing the variables defined in a REPL session's kernel.
+//!
+//! The list is refreshed whenever code run in the session finishes. Selecting a variable shows
+//! the kernel's inspection of it, or a preview of its rows for tables.
+
+use anyhow::anyhow;
+use gpui::{App, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task, prelude::*};
+use runtimelib::{InspectRequest, JupyterMessageContent};
+use ui::{Tooltip, prelude::*};
+use workspace::item::{Item, ItemEvent};
+
+use crate::{
+    Session,
+    kernel_inspector::inspection,
+    outputs::{plain::TerminalOutput, table::TableView},
+    session::SessionEvent,
+    variables::{
+        KernelLanguage, Variable, complete_names_request, parse_table, parse_variables,
+        variables_from_completions,
+    },
+};
+
+enum VariableDetail {
+    Table(Entity<TableView>),
+    Inspection(Entity<TerminalOutput>),
+    Message(SharedString),
+}
+
+pub struct VariableExplorer {
+    session: Entity<Session>,
+    focus_handle: FocusHandle,
+    variables: Vec<Variable>,
+    error: Option<SharedString>,
+    loading: bool,
+    selected: Option<String>,
+    detail: Option<VariableDetail>,
+    refresh_task: Task<()>,
+    detail_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl VariableExplorer {
+    pub fn new(session: Entity<Session>, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let subscription =
+                cx.subscribe(&session, |this, _, event: &SessionEvent, cx| match event {
+                    SessionEvent::ExecutionFinished => this.refresh(cx),
+                    SessionEvent::Shutdown(_) => {
+                        this.variables.clear();
+                        this.selected = None;
+                        this.detail = None;
+                        cx.notify();
+                    }
+                });
+
+            let mut this = Self {
+                session,
+                focus_handle: cx.focus_handle(),
+                variables: Vec::new(),
+                error: None,
+                loading: false,
+                selected: None,
+                detail: None,
+                refresh_task: Task::ready(()),
+                detail_task: Task::ready(()),
+                _subscriptions: vec![subscription],
+            };
+            this.refresh(cx);
+            this
+        })
+    }
+
+    pub fn session(&self) -> &Entity<Session> {
+        &self.session
+    }
+
+    fn language(&self, cx: &App) -> Option<KernelLanguage> {
+        KernelLanguage::from_name(&self.session.read(cx).kernel_specification.language())
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        let request = match self.language(cx) {
+            Some(language) => language.list_variables_request(),
+            None => complete_names_request(),
+        };
+        let response = self
+            .session
+            .update(cx, |session, cx| session.request(request, cx));
+
+        self.loading = true;
+        self.refresh_task = cx.spawn(async move |this, cx| {
+            let variables = response.await.and_then(|response| match response.reply {
+                JupyterMessageContent::ExecuteReply(_) => parse_variables(&response.stdout),
+                JupyterMessageContent::CompleteReply(reply) => {
+                    Ok(variables_from_completions(&reply))
+                }
+                _ => Err(anyhow!("unexpected reply from kernel")),
+            });
+
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match variables {
+                    Ok(variables) => {
+                        this.variables = variables;
+                        this.error = None;
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn select(&mut self, variable: &Variable, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected.as_ref() == Some(&variable.name) {
+            self.selected = None;
+            self.detail = None;
+            self.detail_task = Task::ready(());
+            cx.notify();
+            return;
+        }
+
+        self.selected = Some(variable.name.clone());
+        self.detail = None;
+
+        let preview_request = self
+            .language(cx)
+            .filter(|_| variable.is_table)
+            .and_then(|language| language.preview_table_request(&variable.name));
+        let is_preview = preview_request.is_some();
+        let request = preview_request.unwrap_or_else(|| {
+            InspectRequest {
+                code: variable.name.clone(),
+                cursor_pos: variable.name.chars().count(),
+                detail_level: Some(0),
+            }
+            .into()
+        });
+        let response = self
+            .session
+            .update(cx, |session, cx| session.request(request, cx));
+
+        self.detail_task = cx.spawn_in(window, async move |this, cx| {
+            let response = response.await;
+            this.update_in(cx, |this, window, cx| {
+                let detail = match response {
+                    Ok(response) if is_preview => match parse_table(&response.stdout) {
+                        Ok(table) => {
+                            VariableDetail::Table(cx.new(|cx| TableView::new(&table, window, cx)))
+                        }
+                        Err(error) => VariableDetail::Message(error.to_string().into()),
+                    },
+                    Ok(response) => {
+                        let text = match &response.reply {
+                            JupyterMessageContent::InspectReply(reply) => {
+                                inspection(reply).map(|(text, _)| text)
+                            }
+                            _ => None,
+                        };
+                        match text {
+                            Some(text) => VariableDetail::Inspection(
+                                cx.new(|cx| TerminalOutput::from(text, window, cx)),
+                            ),
+                            None => VariableDetail::Message("No details available".into()),
+                        }
+                    }
+                    Err(error) => VariableDetail::Message(error.to_string().into()),
+                };
+                this.detail = Some(detail);
+                cx.notify();
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let kernel_name = self.session.read(cx).kernel_specification.name();
+
+        h_flex()
+            .w_full()
+            .justify_between()
+            .child(Label::new(format!("Variables: {kernel_name}")).size(LabelSize::Large))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .when(self.loading, |this| {
+                        this.child(Label::new("Loading…").color(Color::Muted))
+                    })
+                    .child(
+                        IconButton::new("refresh-variables", IconName::ArrowCircle)
+                            .tooltip(Tooltip::text("Refresh Variables"))
+                            .on_click(cx.listener(|this, _, _, cx| this.refresh(cx))),
+                    ),
+            )
+    }
+
+    fn render_row(
+        &self,
+        ix: usize,
+        variable: &Variable,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let selected = self.selected.as_ref() == Some(&variable.name);
+        let column = |text: Option<String>, width: f32| {
+            div().w(rems(width)).flex_none().overflow_hidden().child(
+                Label::new(text.unwrap_or_default())
+                    .buffer_font(cx)
+                    .color(Color::Muted)
+                    .truncate(),
+            )
+        };
+
+        h_flex()
+            .id(("variable", ix))
+            .w_full()
+            .px_2()
+            .py_0p5()
+            .gap_2()
+            .rounded_sm()
+            .cursor_pointer()
+            .when(selected, |this| {
+                this.bg(cx.theme().colors().element_selected)
+            })
+            .hover(|style| style.bg(cx.theme().colors().element_hover))
+            .child(
+                div()
+                    .w(rems(12.))
+                    .flex_none()
+                    .overflow_hidden()
+                    .child(Label::new(variable.name.clone()).buffer_font(cx).truncate()),
+            )
+            .child(column(variable.type_name.clone(), 8.))
+            .child(column(variable.shape_label(), 6.))
+            .child(column(variable.size_label(), 5.))
+            .child(
+                div().flex_1().overflow_hidden().child(
+                    Label::new(variable.summary.clone().unwrap_or_default())
+                        .buffer_font(cx)
+                        .truncate(),
+                ),
+            )
+            .on_click(cx.listener({
+                let variable = variable.clone();
+                move |this, _, window, cx| this.select(&variable, window, cx)
+            }))
+    }
+
+    fn render_column_headings(&self) -> impl IntoElement {
+        let heading =
+            |text: &'static str| Label::new(text).size(LabelSize::Small).color(Color::Muted);
+
+        h_flex()
+            .w_full()
+            .px_2()
+            .gap_2()
+            .child(div().w(rems(12.)).flex_none().child(heading("Name")))
+            .child(div().w(rems(8.)).flex_none().child(heading("Type")))
+            .child(div().w(rems(6.)).flex_none().child(heading("Shape")))
+            .child(div().w(rems(5.)).flex_none().child(heading("Size")))
+            .child(div().flex_1().child(heading("Value")))
+    }
+}
+
+impl EventEmitter<ItemEvent> for VariableExplorer {}
+
+impl Focusable for VariableExplorer {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for VariableExplorer {
+    type Event = ItemEvent;
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        format!(
+            "Variables: {}",
+            self.session.read(cx).kernel_specification.name()
+        )
+        .into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("REPL Variable Explorer Opened")
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(workspace::item::ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for VariableExplorer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(ix, variable)| self.render_row(ix, variable, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let detail = self.detail.as_ref().map(|detail| match detail {
+            VariableDetail::Table(table) => table.clone().into_any_element(),
+            VariableDetail::Inspection(output) => output.clone().into_any_element(),
+            VariableDetail::Message(message) => Label::new(message.clone())
+                .color(Color::Muted)
+                .into_any_element(),
+        });
+
+        v_flex()
+            .key_context("VariableExplorer")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_4()
+            .gap_2()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .children(
+                self.error
+                    .clone()
+                    .map(|error| Label::new(error).color(Color::Error)),
+            )
+            .child(self.render_column_headings())
+            .child(
+                v_flex()
+                    .id("variables")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .when(rows.is_empty() && !self.loading, |this| {
+                        this.child(
+                            Label::new("No variables defined")
+                                .color(Color::Muted)
+                                .into_any_element(),
+                        )
+                    })
+                    .children(rows),
+            )
+            .children(detail.map(|detail| {
+                v_flex()
+                    .id("variable-detail")
+                    .max_h(rems(24.))
+                    .overflow_y_scroll()
+                    .pt_2()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(detail)
+            }))
+    }
+}
@@ -0,0 +1,375 @@
+//! Kernel introspection for the variable explorer.
+//!
+//! Jupyter has no message for listing a kernel's variables, so for the languages we know about
+//! we run a small snippet in the kernel that prints them as JSON. Any other kernel falls back to
+//! a `complete_request` for an empty prefix, which lists names without their details.
+
+use anyhow::{Context as _, Result};
+use runtimelib::{CompleteReply, CompleteRequest, ExecuteRequest, JupyterMessage};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Marks the line of kernel output that carries our result, so output from the user's own
+/// hooks (e.g. `sys.displayhook` or startup files) can't be mistaken for it.
+const RESULT_MARKER: &str = "__zed_result__";
+
+/// The number of rows fetched when previewing a table.
+const TABLE_PREVIEW_ROWS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Variable {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub type_name: Option<String>,
+    /// The dimensions of arrays and tables, or the length of other collections.
+    #[serde(default)]
+    pub shape: Vec<u64>,
+    /// The size of the value in memory, in bytes.
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// A short, single line representation of the value.
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Whether the value can be previewed with [`KernelLanguage::preview_table_request`].
+    #[serde(default)]
+    pub is_table: bool,
+}
+
+impl Variable {
+    pub fn shape_label(&self) -> Option<String> {
+        if self.shape.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.shape
+                .iter()
+                .map(|dimension| dimension.to_string())
+                .collect::<Vec<_>>()
+                .join(" × "),
+        )
+    }
+
+    pub fn size_label(&self) -> Option<String> {
+        let size = self.size?;
+        let units = ["B", "KB", "MB", "GB"];
+        let mut value = size as f64;
+        let mut unit = 0;
+        while value >= 1024. && unit < units.len() - 1 {
+            value /= 1024.;
+            unit += 1;
+        }
+
+        Some(if unit == 0 {
+            format!("{size} B")
+        } else {
+            format!("{value:.1} {}", units[unit])
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelLanguage {
+    Python,
+    R,
+    Julia,
+}
+
+impl KernelLanguage {
+    /// Returns the language of a kernel, as named by its kernelspec.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "python" => Some(Self::Python),
+            "r" => Some(Self::R),
+            "julia" => Some(Self::Julia),
+            _ => None,
+        }
+    }
+
+    /// A silent request whose output is parsed by [`parse_variables`].
+    pub fn list_variables_request(&self) -> JupyterMessage {
+        let code = match self {
+            Self::Python => PYTHON_LIST_VARIABLES,
+            Self::R => R_LIST_VARIABLES,
+            Self::Julia => JULIA_LIST_VARIABLES,
+        };
+        silent_execute_request(code.replace("{marker}", RESULT_MARKER))
+    }
+
+    /// A silent request whose output is parsed by [`parse_table`], or `None` if tables can't be
+    /// previewed in this language.
+    pub fn preview_table_request(&self, name: &str) -> Option<JupyterMessage> {
+        match self {
+            Self::Python => Some(silent_execute_request(
+                PYTHON_PREVIEW_TABLE
+                    .replace("{marker}", RESULT_MARKER)
+                    .replace("{rows}", &TABLE_PREVIEW_ROWS.to_string())
+                    // Looking the name up keeps it from being evaluated as code.
+                    .replace("{name}", &serde_json::to_string(name).ok()?),
+            )),
+            Self::R | Self::Julia => None,
+        }
+    }
+}
+
+/// A request for every name the kernel can complete, for kernels we have no snippet for.
+pub fn complete_names_request() -> JupyterMessage {
+    CompleteRequest {
+        code: String::new(),
+        cursor_pos: 0,
+    }
+    .into()
+}
+
+fn silent_execute_request(code: String) -> JupyterMessage {
+    ExecuteRequest {
+        code,
+        silent: true,
+        store_history: false,
+        ..ExecuteRequest::default()
+    }
+    .into()
+}
+
+fn result_line(stdout: &str) -> Result<&str> {
+    stdout
+        .lines()
+        .rev()
+        .find_map(|line| line.strip_prefix(RESULT_MARKER))
+        .context("kernel did not report a result")
+}
+
+pub fn parse_variables(stdout: &str) -> Result<Vec<Variable>> {
+    let mut variables: Vec<Variable> =
+        serde_json::from_str(result_line(stdout)?).context("parsing kernel variables")?;
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(variables)
+}
+
+pub fn parse_table(stdout: &str) -> Result<runtimelib::media::datatable::TabularDataResource> {
+    serde_json::from_str(result_line(stdout)?).context("parsing table preview")
+}
+
+/// Reads the variables from a completion reply, using the types reported by kernels that
+/// support IPython's experimental completion metadata.
+pub fn variables_from_completions(reply: &CompleteReply) -> Vec<Variable> {
+    let types = reply
+        .metadata
+        .get("_jupyter_types_experimental")
+        .and_then(Value::as_array);
+
+    let mut variables = reply
+        .matches
+        .iter()
+        .filter(|name| !name.starts_with('_') && !name.contains(['.', '(', ' ']))
+        .map(|name| {
+            let type_name = types
+                .and_then(|types| {
+                    types.iter().find(|completion| {
+                        completion.get("text").and_then(Value::as_str) == Some(name.as_str())
+                    })
+                })
+                .and_then(|completion| completion.get("type")?.as_str())
+                .map(str::to_string);
+
+            Variable {
+                name: name.clone(),
+                type_name,
+                shape: Vec::new(),
+                size: None,
+                summary: None,
+                is_table: false,
+            }
+        })
+        .collect::<Vec<_>>();
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    variables.dedup_by(|a, b| a.name == b.name);
+    variables
+}
+
+const PYTHON_LIST_VARIABLES: &str = r#"
+def __zed_list_variables():
+    import json, sys, types
+
+    ip = get_ipython()
+    variables = []
+    for name, value in list(ip.user_ns.items()):
+        if name.startswith("_") or name in ip.user_ns_hidden:
+            continue
+        if isinstance(value, (types.ModuleType, types.FunctionType, types.BuiltinFunctionType, type)):
+            continue
+
+        shape = getattr(value, "shape", None)
+        if isinstance(shape, tuple) and all(isinstance(d, int) for d in shape):
+            shape = list(shape)
+        else:
+            try:
+                shape = [len(value)]
+            except Exception:
+                shape = []
+
+        try:
+            if hasattr(value, "memory_usage") and hasattr(value, "columns"):
+                size = int(value.memory_usage(deep=True).sum())
+            elif hasattr(value, "nbytes"):
+                size = int(value.nbytes)
+            else:
+                size = sys.getsizeof(value)
+        except Exception:
+            size = None
+
+        try:
+            summary = " ".join(repr(value).split())[:120]
+        except Exception:
+            summary = None
+
+        variables.append({
+            "name": name,
+            "type": type(value).__name__,
+            "shape": shape,
+            "size": size,
+            "summary": summary,
+            "is_table": type(value).__module__.startswith("pandas") and hasattr(value, "to_json"),
+        })
+    print("{marker}" + json.dumps(variables))
+
+__zed_list_variables()
+del __zed_list_variables
+"#;
+
+const PYTHON_PREVIEW_TABLE: &str = r#"
+print("{marker}" + get_ipython().user_ns[{name}].head({rows}).to_json(orient="table", default_handler=str))
+"#;
+
+const R_LIST_VARIABLES: &str = r#"
+local({
+  variables <- Filter(Negate(is.null), lapply(ls(globalenv()), function(name) {
+    value <- get(name, envir = globalenv())
+    if (is.function(value)) return(NULL)
+    list(
+      name = name,
+      type = class(value)[[1]],
+      shape = I(if (is.null(dim(value))) length(value) else dim(value)),
+      size = as.numeric(utils::object.size(value)),
+      summary = trimws(paste(utils::capture.output(
+        utils::str(value, max.level = 0, give.attr = FALSE)
+      ), collapse = " ")),
+      is_table = FALSE
+    )
+  }))
+  cat("{marker}", jsonlite::toJSON(variables, auto_unbox = TRUE), "\n", sep = "")
+})
+"#;
+
+const JULIA_LIST_VARIABLES: &str = r##"
+let
+    json(s) = "\"" * replace(string(s), "\\" => "\\\\", "\"" => "\\\"", "\n" => "\\n", "\r" => "\\r", "\t" => "\\t") * "\""
+    entries = String[]
+    for name in names(Main)
+        (name in (:Base, :Core, :Main, :ans) || startswith(string(name), "#")) && continue
+        isdefined(Main, name) || continue
+        value = getfield(Main, name)
+        (value isa Module || value isa Function || value isa Type) && continue
+        shape = applicable(size, value) ? collect(size(value)) : applicable(length, value) ? [length(value)] : Int[]
+        summary = first(sprint(show, value; context = :limit => true), 120)
+        push!(entries, string(
+            "{\"name\":", json(name),
+            ",\"type\":", json(typeof(value)),
+            ",\"shape\":[", join(shape, ","), "]",
+            ",\"size\":", Base.summarysize(value),
+            ",\"summary\":", json(replace(summary, r"\s+" => " ")),
+            "}",
+        ))
+    end
+    println("{marker}[", join(entries, ","), "]")
+end
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_variables() {
+        let stdout = format!(
+            "some output from a startup hook\n{RESULT_MARKER}{}\n",
+            json!([
+                {"name": "x", "type": "int", "shape": [], "size": 28, "summary": "1"},
+                {"name": "df", "type": "DataFrame", "shape": [3, 2], "size": 2048, "is_table": true},
+            ])
+        );
+
+        let variables = parse_variables(&stdout).unwrap();
+        assert_eq!(
+            variables
+                .iter()
+                .map(|variable| variable.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["df", "x"]
+        );
+        assert_eq!(variables[0].shape_label().as_deref(), Some("3 × 2"));
+        assert_eq!(variables[0].size_label().as_deref(), Some("2.0 KB"));
+        assert!(variables[0].is_table);
+        assert_eq!(variables[1].shape_label(), None);
+        assert_eq!(variables[1].size_label().as_deref(), Some("28 B"));
+
+        assert!(parse_variables("no result").is_err());
+    }
+
+    #[test]
+    fn test_variables_from_completions() {
+        let reply: CompleteReply = serde_json::from_value(json!({
+            "status": "ok",
+            "matches": ["numbers", "_hidden", "os.path", "count", "numbers"],
+            "cursor_start": 0,
+            "cursor_end": 0,
+            "metadata": {
+                "_jupyter_types_experimental": [
+                    {"text": "numbers", "type": "instance", "start": 0, "end": 0},
+                ],
+            },
+        }))
+        .unwrap();
+
+        let variables = variables_from_completions(&reply);
+        assert_eq!(
+            variables
+                .iter()
+                .map(|variable| (variable.name.as_str(), variable.type_name.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![("count", None), ("numbers", Some("instance"))]
+        );
+    }
+
+    #[test]
+    fn test_snippets_contain_marker() {
+        for language in [
+            KernelLanguage::Python,
+            KernelLanguage::R,
+            KernelLanguage::Julia,
+        ] {
+            let request = language.list_variables_request();
+            let runtimelib::JupyterMessageContent::ExecuteRequest(request) = request.content else {
+                panic!("expected an execute request");
+            };
+            assert!(request.silent);
+            assert!(request.code.contains(RESULT_MARKER));
+        }
+
+        assert!(KernelLanguage::R.preview_table_request("df").is_none());
+        let request = KernelLanguage::Python
+            .preview_table_request("df); import os; (x")
+            .unwrap();
+        let runtimelib::JupyterMessageContent::ExecuteRequest(request) = request.content else {
+            panic!("expected an execute request");
+        };
+        assert!(
+            request
+                .code
+                .contains(r#"get_ipython().user_ns["df); import os; (x"].head("#),
+            "{}",
+            request.code
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! Works out why a setting has the value it has: which settings files set it, and which of
+//! them takes effect.
+
+use std::sync::Arc;
+
+use ec4rs::property::{
+    FinalNewline, IndentSize, IndentStyle, MaxLineLen, TabWidth, TrimTrailingWs,
+};
+use gpui::App;
+use serde::Serialize;
+use serde_json::Value;
+use util::rel_path::RelPath;
+
+use crate::{
+    ActiveSettingsProfileName, SettingsContent, SettingsLocation, SettingsStore, WorktreeId,
+    settings_store::EditorconfigProperties,
+};
+
+/// A settings file that can set a setting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingSource {
+    Default,
+    Extensions,
+    Global,
+    User,
+    Server,
+    /// A `.zed/settings.json` file in the given directory of a worktree.
+    Project(WorktreeId, Arc<RelPath>),
+    /// An `.editorconfig` file in the given directory of a worktree.
+    Editorconfig(WorktreeId, Arc<RelPath>),
+}
+
+/// A value that a settings file sets for a setting.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingContribution {
+    pub source: SettingSource,
+    /// The keys leading to the value within its JSON file, such as
+    /// `["linux", "languages", "Rust", "tab_size"]`. For `.editorconfig` files, this is the
+    /// section and the property name.
+    pub key_path: Vec<String>,
+    pub value: Value,
+    /// The zero-based line the value is set on, for `.editorconfig` files. For JSON files,
+    /// use [`crate::key_path_range_in_json_text`] with [`Self::key_path`].
+    pub line: Option<u32>,
+}
+
+/// Every value set for a setting, ordered from lowest to highest precedence, so the last one
+/// takes effect.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SettingProvenance {
+    pub contributions: Vec<SettingContribution>,
+}
+
+impl SettingProvenance {
+    pub fn effective(&self) -> Option<&SettingContribution> {
+        self.contributions.last()
+    }
+}
+
+impl SettingsStore {
+    /// Collects every value set for the setting at the dotted `key`, such as
+    /// `"terminal.font_size"`, for a file at `location` in the given language.
+    ///
+    /// Language settings set for the language take precedence over the ones set for all
+    /// languages, in any file, and `.editorconfig` files take precedence over both.
+    pub fn setting_provenance(
+        &self,
+        key: &str,
+        location: Option<SettingsLocation>,
+        language: Option<&str>,
+        cx: &App,
+    ) -> SettingProvenance {
+        let key_path = key.split('.').collect::<Vec<_>>();
+        let mut layers = Vec::new();
+
+        let mut push_layer = |source: SettingSource, prefix: &[&str], content: &SettingsContent| {
+            if let Some(value) = to_value(content) {
+                let prefix = prefix.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+                layers.push((source, prefix, value));
+            }
+        };
+        push_layer(SettingSource::Default, &[], self.raw_default_settings());
+        if let Some(content) = self.raw_extension_settings() {
+            push_layer(SettingSource::Extensions, &[], content);
+        }
+        if let Some(content) = self.raw_global_settings() {
+            push_layer(SettingSource::Global, &[], content);
+        }
+        if let Some(user_settings) = self.raw_user_settings() {
+            push_layer(SettingSource::User, &[], &user_settings.content);
+            if let Some(content) = user_settings.for_release_channel() {
+                let channel = release_channel::RELEASE_CHANNEL.dev_name();
+                push_layer(SettingSource::User, &[channel], content);
+            }
+            if let Some(content) = user_settings.for_os() {
+                push_layer(SettingSource::User, &[std::env::consts::OS], content);
+            }
+            if let Some(content) = user_settings.for_profile(cx)
+                && let Some(profile) = cx.try_global::<ActiveSettingsProfileName>()
+            {
+                push_layer(SettingSource::User, &["profiles", &profile.0], content);
+            }
+        }
+        if let Some(content) = self.raw_server_settings() {
+            push_layer(SettingSource::Server, &[], content);
+        }
+        if let Some(location) = location {
+            for (directory, content) in self.local_settings(location.worktree_id) {
+                if location.path.starts_with(&directory)
+                    && let Some(value) = to_value(content)
+                {
+                    layers.push((
+                        SettingSource::Project(location.worktree_id, directory),
+                        Vec::new(),
+                        value,
+                    ));
+                }
+            }
+        }
+
+        let mut contributions = Vec::new();
+        let mut collect = |key_path: &[&str]| {
+            for (source, prefix, value) in &layers {
+                if let Some(value) = value_at(value, key_path) {
+                    contributions.push(SettingContribution {
+                        source: source.clone(),
+                        key_path: prefix
+                            .iter()
+                            .cloned()
+                            .chain(key_path.iter().map(|key| key.to_string()))
+                            .collect(),
+                        value: value.clone(),
+                        line: None,
+                    });
+                }
+            }
+        };
+        collect(&key_path);
+
+        let is_language_setting =
+            to_value(&self.raw_default_settings().project.all_languages.defaults)
+                .is_some_and(|defaults| value_at(&defaults, &key_path).is_some());
+        if is_language_setting {
+            if let Some(language) = language {
+                let language_key_path = ["languages", language]
+                    .into_iter()
+                    .chain(key_path.iter().copied())
+                    .collect::<Vec<_>>();
+                collect(&language_key_path);
+            }
+            if let Some(location) = location {
+                contributions.extend(self.editorconfig_contributions(key, location));
+            }
+        }
+
+        SettingProvenance { contributions }
+    }
+
+    fn editorconfig_contributions(
+        &self,
+        key: &str,
+        location: SettingsLocation,
+    ) -> Vec<SettingContribution> {
+        if !is_editorconfig_setting(key) {
+            return Vec::new();
+        }
+
+        let mut contributions = Vec::new();
+        // Mirrors `SettingsStore::editorconfig_properties`.
+        for (directory, text, editorconfig) in
+            self.local_editorconfig_settings(location.worktree_id)
+        {
+            if !location.path.starts_with(&directory) {
+                break;
+            }
+            // An unparsable `.editorconfig` file disables all of them.
+            let Some(editorconfig) = editorconfig else {
+                return Vec::new();
+            };
+            if editorconfig.is_root {
+                contributions.clear();
+            }
+
+            let section_lines = section_header_lines(&text);
+            for (index, section) in editorconfig.sections.iter().enumerate() {
+                let mut properties = EditorconfigProperties::new();
+                if section
+                    .apply_to(&mut properties, location.path.as_std_path())
+                    .is_err()
+                {
+                    continue;
+                }
+                let Some((property_name, value)) = editorconfig_value(key, &properties) else {
+                    continue;
+                };
+                let header_line = section_lines.get(index).copied();
+                let line = header_line
+                    .and_then(|header_line| property_line(&text, header_line, property_name));
+                let section_name = header_line
+                    .and_then(|header_line| text.lines().nth(header_line as usize))
+                    .map(|header| header.trim().to_string())
+                    .unwrap_or_default();
+                contributions.push(SettingContribution {
+                    source: SettingSource::Editorconfig(location.worktree_id, directory.clone()),
+                    key_path: vec![section_name, property_name.to_string()],
+                    value,
+                    line: line.or(header_line),
+                });
+            }
+        }
+        contributions
+    }
+}
+
+fn to_value(content: &impl Serialize) -> Option<Value> {
+    serde_json::to_value(content).ok()
+}
+
+fn value_at<'a>(value: &'a Value, key_path: &[&str]) -> Option<&'a Value> {
+    key_path
+        .iter()
+        .try_fold(value, |value, key| value.as_object()?.get(*key))
+        .filter(|value| !value.is_null())
+}
+
+/// Whether `.editorconfig` properties can override a setting.
+fn is_editorconfig_setting(key: &str) -> bool {
+    matches!(
+        key,
+        "tab_size"
+            | "hard_tabs"
+            | "preferred_line_length"
+            | "ensure_final_newline_on_save"
+            | "remove_trailing_whitespace_on_save"
+    )
+}
+
+/// The value a set of `.editorconfig` properties gives a setting, and the property it comes
+/// from. Mirrors how language settings are merged with `.editorconfig` properties.
+fn editorconfig_value(
+    key: &str,
+    properties: &EditorconfigProperties,
+) -> Option<(&'static str, Value)> {
+    match key {
+        "tab_size" => match properties.get::<IndentSize>().ok() {
+            Some(IndentSize::Value(size)) => Some(("indent_size", size.into())),
+            Some(IndentSize::UseTabWidth) | None => match properties.get::<TabWidth>().ok()? {
+                TabWidth::Value(width) => Some(("tab_width", width.into())),
+            },
+        },
+        "hard_tabs" => {
+            let style = properties.get::<IndentStyle>().ok()?;
+            Some(("indent_style", (style == IndentStyle::Tabs).into()))
+        }
+        "preferred_line_length" => match properties.get::<MaxLineLen>().ok()? {
+            MaxLineLen::Value(length) => Some(("max_line_length", length.into())),
+            MaxLineLen::Off => None,
+        },
+        "ensure_final_newline_on_save" => match properties.get::<FinalNewline>().ok()? {
+            FinalNewline::Value(value) => Some(("insert_final_newline", value.into())),
+        },
+        "remove_trailing_whitespace_on_save" => match properties.get::<TrimTrailingWs>().ok()? {
+            TrimTrailingWs::Value(value) => Some(("trim_trailing_whitespace", value.into())),
+        },
+        _ => None,
+    }
+}
+
+/// The zero-based lines of the `[section]` headers in an `.editorconfig` file, in order.
+fn section_header_lines(text: &str) -> Vec<u32> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            line.starts_with('[') && line.ends_with(']')
+        })
+        .map(|(row, _)| row as u32)
+        .collect()
+}
+
+/// The zero-based line of the last assignment to `property_name` in the section starting at
+/// `header_line`.
+fn property_line(text: &str, header_line: u32, property_name: &str) -> Option<u32> {
+    let mut found = None;
+    for (row, line) in text.lines().enumerate().skip(header_line as usize + 1) {
+        let line = line.trim();
+        if line.starts_with('[') {
+            break;
+        }
+        let Some((name, _)) = line.split_once(['=', ':']) else {
+            continue;
+        };
+        if name.trim().eq_ignore_ascii_case(property_name) {
+            found = Some(row as u32);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LocalSettingsKind, test_settings};
+    use serde_json::json;
+    use util::rel_path::rel_path;
+
+    #[gpui::test]
+    fn test_setting_provenance(cx: &mut App) {
+        let mut store = SettingsStore::new(cx, &test_settings());
+        let worktree_id = WorktreeId::from_usize(1);
+        store
+            .set_user_settings(
+                r#"{
+                    "tab_size": 3,
+                    "languages": { "Rust": { "tab_size": 5 } }
+                }"#,
+                cx,
+            )
+            .unwrap();
+        store
+            .set_local_settings(
+                worktree_id,
+                rel_path("a").into(),
+                LocalSettingsKind::Settings,
+                Some(r#"{ "tab_size": 6 }"#),
+                cx,
+            )
+            .unwrap();
+        store
+            .set_local_settings(
+                worktree_id,
+                rel_path("b").into(),
+                LocalSettingsKind::Settings,
+                Some(r#"{ "tab_size": 7 }"#),
+                cx,
+            )
+            .unwrap();
+        let location = SettingsLocation {
+            worktree_id,
+            path: rel_path("a/main.rs"),
+        };
+
+        let provenance = store.setting_provenance("tab_size", Some(location), Some("Rust"), cx);
+        let sources = provenance
+            .contributions
+            .iter()
+            .map(|contribution| {
+                (
+                    contribution.source.clone(),
+                    contribution.key_path.join("."),
+                    contribution.value.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec![
+                (SettingSource::Default, "tab_size".into(), json!(4)),
+                (SettingSource::User, "tab_size".into(), json!(3)),
+                (
+                    SettingSource::Project(worktree_id, rel_path("a").into()),
+                    "tab_size".into(),
+                    json!(6)
+                ),
+                (
+                    SettingSource::User,
+                    "languages.Rust.tab_size".into(),
+                    json!(5)
+                ),
+            ]
+        );
+        assert_eq!(provenance.effective().unwrap().value, json!(5));
+
+        let provenance = store.setting_provenance("tab_size", Some(location), None, cx);
+        assert_eq!(provenance.effective().unwrap().value, json!(6));
+
+        let provenance = store.setting_provenance("languages", None, None, cx);
+        assert!(provenance.effective().is_some());
+        let provenance = store.setting_provenance("no_such_setting", None, None, cx);
+        assert_eq!(provenance.effective(), None);
+    }
+
+    #[gpui::test]
+    fn test_editorconfig_provenance(cx: &mut App) {
+        let mut store = SettingsStore::new(cx, &test_settings());
+        let worktree_id = WorktreeId::from_usize(1);
+        store
+            .set_local_settings(
+                worktree_id,
+                rel_path("").into(),
+                LocalSettingsKind::Editorconfig,
+                Some("root = true\n\n[*]\nindent_size = 2\n\n[*.md]\ntrim_trailing_whitespace = false\nindent_size = 8\n"),
+                cx,
+            )
+            .unwrap();
+        let location = SettingsLocation {
+            worktree_id,
+            path: rel_path("docs/readme.md"),
+        };
+
+        let provenance = store.setting_provenance("tab_size", Some(location), None, cx);
+        let editorconfig_contributions = provenance
+            .contributions
+            .iter()
+            .filter(|contribution| matches!(contribution.source, SettingSource::Editorconfig(..)))
+            .map(|contribution| {
+                (
+                    contribution.key_path.join(" "),
+                    contribution.value.clone(),
+                    contribution.line,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            editorconfig_contributions,
+            vec![
+                ("[*] indent_size".into(), json!(2), Some(3)),
+                ("[*.md] indent_size".into(), json!(8), Some(7)),
+            ]
+        );
+        assert_eq!(provenance.effective().unwrap().value, json!(8));
+
+        let provenance = store.setting_provenance(
+            "remove_trailing_whitespace_on_save",
+            Some(location),
+            None,
+            cx,
+        );
+        assert_eq!(provenance.effective().unwrap().value, json!(false));
+        assert_eq!(provenance.effective().unwrap().line, Some(6));
+    }
+}
@@ -3,11 +3,13 @@ mod editable_setting_control;
 mod fallible_options;
 mod keymap_file;
 pub mod merge_from;
+mod provenance;
 mod serde_helper;
 mod settings_content;
 mod settings_file;
 mod settings_store;
 mod vscode_import;
+mod vscode_keymap_import;
 
 pub use settings_content::*;
 pub use settings_macros::RegisterSetting;
@@ -29,6 +31,7 @@ pub use keymap_file::{
     KeyBindingValidator, KeyBindingValidatorRegistration, KeybindSource, KeybindUpdateOperation,
     KeybindUpdateTarget, KeymapFile, KeymapFileLoadResult,
 };
+pub use provenance::{SettingContribution, SettingProvenance, SettingSource};
 pub use serde_helper::*;
 pub use settings_file::*;
 pub use settings_json::*;
@@ -39,6 +42,9 @@ pub use settings_store::{
 };
 
 pub use vscode_import::{VsCodeSettings, VsCodeSettingsSource};
+pub use vscode_keymap_import::{
+    ImportedKeybinding, UnmappedKeybinding, UnmappedReason, VsCodeKeybindings, VsCodeKeymapImport,
+};
 
 pub use keymap_file::ActionSequence;
 
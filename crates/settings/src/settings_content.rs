@@ -90,6 +90,12 @@ pub struct SettingsContent {
     /// The settings for the image viewer.
     pub image_viewer: Option<ImageViewerSettingsContent>,
 
+    /// The settings for viewing files too large to open in the editor.
+    pub large_file_viewer: Option<LargeFileViewerSettingsContent>,
+
+    /// The settings for exposing Zed to external tools over the Model Context Protocol.
+    pub mcp_server: Option<McpServerSettingsContent>,
+
     pub repl: Option<ReplSettingsContent>,
 
     pub journal: Option<JournalSettingsContent>,
@@ -122,6 +128,10 @@ pub struct SettingsContent {
 
     /// Configuration for session-related features
     pub session: Option<SessionSettingsContent>,
+
+    /// Configuration for syncing settings, keymap, snippets, themes and extensions
+    /// across machines.
+    pub settings_sync: Option<SettingsSyncSettingsContent>,
     /// Control what info is collected by Zed.
     pub telemetry: Option<TelemetrySettingsContent>,
 
@@ -708,6 +718,26 @@ pub struct JournalSettingsContent {
     pub hour_format: Option<HourFormat>,
 }
 
+/// Settings for syncing user configuration through a git repository
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct SettingsSyncSettingsContent {
+    /// The URL of the git repository to sync with, e.g. `git@github.com:me/zed-settings.git`.
+    /// Syncing is disabled when this is not set.
+    ///
+    /// Default: null
+    pub repository: Option<String>,
+    /// The branch of the repository to sync with.
+    ///
+    /// Default: main
+    pub branch: Option<String>,
+    /// Whether to install the extensions listed in a synced configuration that are missing
+    /// on this machine.
+    ///
+    /// Default: true
+    pub install_extensions: Option<bool>,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum HourFormat {
@@ -838,6 +868,33 @@ pub struct ImageViewerSettingsContent {
     pub unit: Option<ImageFileSizeUnit>,
 }
 
+/// The settings for the large file viewer.
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, Default, PartialEq)]
+pub struct LargeFileViewerSettingsContent {
+    /// Whether to open large local files in a read-mostly viewer that maps them into memory,
+    /// instead of loading them into the editor.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The size in megabytes from which files are opened in the large file viewer.
+    ///
+    /// Default: 256
+    pub threshold_mb: Option<u64>,
+}
+
+/// The settings for the MCP server that exposes Zed to external tools.
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, Default, PartialEq)]
+pub struct McpServerSettingsContent {
+    /// Whether to let external agents and CLIs connect to Zed over the Model Context
+    /// Protocol, to read buffers, diagnostics and symbols and to open files and run tasks
+    /// in trusted projects.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+}
+
 #[with_fallible_options]
 #[derive(
     Clone,
@@ -867,6 +924,7 @@ pub struct RemoteSettingsContent {
     pub ssh_connections: Option<Vec<SshConnection>>,
     pub wsl_connections: Option<Vec<WslConnection>>,
     pub dev_container_connections: Option<Vec<DevContainerConnection>>,
+    pub container_connections: Option<Vec<ContainerConnection>>,
     pub read_ssh_config: Option<bool>,
 }
 
@@ -911,6 +969,76 @@ pub struct WslConnection {
     pub projects: BTreeSet<RemoteProject>,
 }
 
+/// A container or pod that Zed connects to by running its runtime's CLI
+/// (e.g. `podman exec`, `kubectl exec`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, JsonSchema, MergeFrom)]
+pub struct ContainerConnection {
+    /// Name to use for this container in UI.
+    pub name: SharedString,
+    /// The container ID or name, or the pod name for `kubectl`.
+    pub target: SharedString,
+    /// The container runtime used to exec into the container and copy files into it.
+    ///
+    /// Default: "podman"
+    #[serde(default)]
+    pub runtime: ContainerRuntimeKind,
+    /// The Kubernetes namespace of the pod. Only used by the `kubectl` runtime.
+    pub namespace: Option<String>,
+    /// The container within the pod. Only used by the `kubectl` runtime.
+    pub container: Option<String>,
+    /// The commands to use for the `custom` runtime.
+    pub command: Option<ContainerCommandContent>,
+    // By default Zed will download the binary inside the container directly.
+    // If this is set to true, Zed will download the binary to your local machine,
+    // and then copy it into the container. Useful if the container has
+    // limited outbound internet access.
+    pub upload_binary_over_exec: Option<bool>,
+    #[serde(default)]
+    pub projects: BTreeSet<RemoteProject>,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema, MergeFrom,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerRuntimeKind {
+    #[default]
+    Podman,
+    Nerdctl,
+    Kubectl,
+    Custom,
+}
+
+/// Command templates for exec-ing into a container with an arbitrary CLI.
+///
+/// `{target}` is replaced with the connection's target in `exec_args` and `copy_args`.
+/// `copy_args` also substitutes `{src}` (a local path) and `{dest}` (a path in the container).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema, MergeFrom)]
+pub struct ContainerCommandContent {
+    /// The CLI to run, e.g. `"podman"`.
+    pub program: String,
+    /// Arguments for running a command in the container, e.g. `["exec", "{target}"]`.
+    /// The command to run is appended after these arguments.
+    pub exec_args: Vec<String>,
+    /// Arguments for copying a local file into the container,
+    /// e.g. `["cp", "{src}", "{target}:{dest}"]`.
+    pub copy_args: Vec<String>,
+    /// Flags that keep stdin open, inserted before `{target}`.
+    ///
+    /// Default: ["-i"]
+    pub stdin_args: Option<Vec<String>>,
+    /// Flags that allocate a TTY for terminals, inserted before `{target}`.
+    ///
+    /// Default: ["-t"]
+    pub tty_args: Option<Vec<String>>,
+    /// Flags that set the working directory, with `{dir}` substituted.
+    /// When unset, the command is wrapped in `sh -c 'cd ...'` instead.
+    pub workdir_args: Option<Vec<String>>,
+    /// Flags that set an environment variable, with `{env}` substituted by `NAME=value`.
+    /// When unset, the command is wrapped in `env NAME=value ...` instead.
+    pub env_args: Option<Vec<String>>,
+}
+
 #[with_fallible_options]
 #[derive(
     Clone, Debug, Default, Serialize, PartialEq, Eq, PartialOrd, Ord, Deserialize, JsonSchema,
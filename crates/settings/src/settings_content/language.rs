@@ -73,6 +73,7 @@ pub enum EditPredictionProvider {
     None,
     #[default]
     Zed,
+    Local,
 }
 
 impl<'de> Deserialize<'de> for EditPredictionProvider {
@@ -85,11 +86,13 @@ impl<'de> Deserialize<'de> for EditPredictionProvider {
         pub enum Content {
             None,
             Zed,
+            Local,
         }
 
         Ok(match Content::deserialize(deserializer)? {
             Content::None => EditPredictionProvider::None,
             Content::Zed => EditPredictionProvider::Zed,
+            Content::Local => EditPredictionProvider::Local,
         })
     }
 }
@@ -98,7 +101,7 @@ impl EditPredictionProvider {
     pub fn is_zed(&self) -> bool {
         match self {
             EditPredictionProvider::Zed => true,
-            EditPredictionProvider::None => false,
+            EditPredictionProvider::None | EditPredictionProvider::Local => false,
         }
     }
 }
@@ -118,6 +121,8 @@ pub struct EditPredictionSettingsContent {
     pub copilot: Option<CopilotSettingsContent>,
     /// Settings specific to Codestral.
     pub codestral: Option<CodestralSettingsContent>,
+    /// Settings for the model used by the `local` provider.
+    pub local: Option<LocalEditPredictionSettingsContent>,
     /// Whether edit predictions are enabled in the assistant prompt editor.
     /// This has no effect if globally disabled.
     pub enabled_in_text_threads: Option<bool>,
@@ -161,6 +166,24 @@ pub struct CodestralSettingsContent {
     pub api_url: Option<String>,
 }
 
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct LocalEditPredictionSettingsContent {
+    /// Base URL of an OpenAI-compatible server, such as llama.cpp, LM Studio or Ollama.
+    /// Requests are sent to its `/completions` endpoint.
+    ///
+    /// Default: "http://localhost:8080/v1"
+    pub api_url: Option<String>,
+    /// Model to request completions from. Servers that only load a single model ignore it.
+    ///
+    /// Default: none
+    pub model: Option<String>,
+    /// Maximum tokens to generate.
+    ///
+    /// Default: 256
+    pub max_tokens: Option<u32>,
+}
+
 /// The mode in which edit predictions should be displayed.
 #[derive(
     Copy,
@@ -403,6 +403,21 @@ impl SettingsStore {
         &self.default_settings
     }
 
+    /// Get the settings content contributed by extensions.
+    pub fn raw_extension_settings(&self) -> Option<&SettingsContent> {
+        self.extension_settings.as_deref()
+    }
+
+    /// Get the global settings content.
+    pub fn raw_global_settings(&self) -> Option<&SettingsContent> {
+        self.global_settings.as_deref()
+    }
+
+    /// Get the settings content sent by the remote server.
+    pub fn raw_server_settings(&self) -> Option<&SettingsContent> {
+        self.server_settings.as_deref()
+    }
+
     /// Get the configured settings profile names.
     pub fn configured_settings_profiles(&self) -> impl Iterator<Item = &str> {
         self.user_settings
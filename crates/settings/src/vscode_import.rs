@@ -187,8 +187,10 @@ impl VsCodeSettings {
             global_lsp_settings: None,
             image_viewer: None,
             journal: None,
+            large_file_viewer: None,
             line_indicator_format: None,
             log: None,
+            mcp_server: None,
             message_editor: None,
             node: self.node_binary_settings(),
             notification_panel: None,
@@ -201,6 +203,7 @@ impl VsCodeSettings {
             repl: None,
             server_url: None,
             session: None,
+            settings_sync: None,
             status_bar: self.status_bar_settings_content(),
             tab_bar: self.tab_bar_settings_content(),
             tabs: self.item_settings_content(),
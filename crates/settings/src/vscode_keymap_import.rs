@@ -0,0 +1,1095 @@
+use crate::{VsCodeSettingsSource, append_top_level_array_value_in_json_text};
+use anyhow::{Context as _, Result, anyhow};
+use collections::IndexMap;
+use fs::Fs;
+use paths::{cursor_keybindings_file_paths, vscode_keybindings_file_paths};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::{fmt, path::Path, sync::Arc};
+
+/// Contexts that name a node in Zed's key context tree, as opposed to a flag set on one.
+const NODE_CONTEXTS: &[&str] = &[
+    "BufferSearchBar",
+    "Editor",
+    "GitPanel",
+    "Pane",
+    "Picker",
+    "ProjectPanel",
+    "Terminal",
+    "Workspace",
+];
+
+/// A single entry of a VS Code `keybindings.json` file.
+#[derive(Clone, Debug, Deserialize)]
+pub struct VsCodeKeybinding {
+    pub key: String,
+    pub command: String,
+    #[serde(default)]
+    pub when: Option<String>,
+    #[serde(default)]
+    pub args: Option<Value>,
+}
+
+pub struct VsCodeKeybindings {
+    pub source: VsCodeSettingsSource,
+    pub path: Arc<Path>,
+    bindings: Vec<VsCodeKeybinding>,
+}
+
+/// A VS Code keybinding translated into a Zed keymap binding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedKeybinding {
+    pub context: String,
+    pub keystrokes: String,
+    pub action: Value,
+    pub vscode_command: String,
+}
+
+/// A VS Code keybinding that could not be translated, and why.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnmappedKeybinding {
+    pub key: String,
+    pub command: String,
+    pub when: Option<String>,
+    pub reason: UnmappedReason,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UnmappedReason {
+    UnknownCommand,
+    UnsupportedKey(String),
+    UnsupportedWhenClause(String),
+    UnsupportedArguments,
+}
+
+impl fmt::Display for UnmappedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnmappedReason::UnknownCommand => write!(f, "no equivalent Zed action"),
+            UnmappedReason::UnsupportedKey(key) => write!(f, "unsupported key `{key}`"),
+            UnmappedReason::UnsupportedWhenClause(clause) => {
+                write!(f, "unsupported `when` clause `{clause}`")
+            }
+            UnmappedReason::UnsupportedArguments => write!(f, "unsupported command arguments"),
+        }
+    }
+}
+
+/// The result of translating a VS Code `keybindings.json` into Zed keymap bindings.
+#[derive(Debug, Default)]
+pub struct VsCodeKeymapImport {
+    pub imported: Vec<ImportedKeybinding>,
+    pub unmapped: Vec<UnmappedKeybinding>,
+}
+
+impl VsCodeKeybindings {
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn from_str(content: &str, source: VsCodeSettingsSource) -> Result<Self> {
+        Ok(Self {
+            source,
+            path: Path::new("/example-path/Code/User/keybindings.json").into(),
+            bindings: serde_json_lenient::from_str(content)?,
+        })
+    }
+
+    pub async fn load_user_keybindings(
+        source: VsCodeSettingsSource,
+        fs: Arc<dyn Fs>,
+    ) -> Result<Self> {
+        let candidate_paths = match source {
+            VsCodeSettingsSource::VsCode => vscode_keybindings_file_paths(),
+            VsCodeSettingsSource::Cursor => cursor_keybindings_file_paths(),
+        };
+        let mut path = None;
+        for candidate_path in candidate_paths.iter() {
+            if fs.is_file(candidate_path).await {
+                path = Some(candidate_path.clone());
+            }
+        }
+        let Some(path) = path else {
+            return Err(anyhow!(
+                "No keybindings file found, expected to find it in one of the following paths:\n{}",
+                candidate_paths
+                    .into_iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        };
+        let content = fs.load(&path).await.with_context(|| {
+            format!(
+                "Error loading {} keybindings file from {}",
+                source,
+                path.display()
+            )
+        })?;
+        let bindings = serde_json_lenient::from_str(&content).with_context(|| {
+            format!(
+                "Error parsing {} keybindings file from {}",
+                source,
+                path.display()
+            )
+        })?;
+        Ok(Self {
+            source,
+            path: path.into(),
+            bindings,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    pub fn import(&self) -> VsCodeKeymapImport {
+        let mut result = VsCodeKeymapImport::default();
+        for binding in &self.bindings {
+            match import_keybinding(binding) {
+                Ok(imported) => result.imported.push(imported),
+                Err(reason) => result.unmapped.push(UnmappedKeybinding {
+                    key: binding.key.clone(),
+                    command: binding.command.clone(),
+                    when: binding.when.clone(),
+                    reason,
+                }),
+            }
+        }
+        result
+    }
+}
+
+impl VsCodeKeymapImport {
+    /// Groups the imported bindings into keymap sections, one per context, in the order
+    /// contexts first appear. Later bindings for the same keystrokes replace earlier ones,
+    /// as they do in VS Code.
+    pub fn keymap_sections(&self) -> Vec<Value> {
+        let mut sections = IndexMap::<&str, Map<String, Value>>::default();
+        for binding in &self.imported {
+            let bindings = sections.entry(binding.context.as_str()).or_default();
+            // Removing a default binding must not unbind a user binding for the same keys.
+            if binding.action.is_null() {
+                bindings
+                    .entry(binding.keystrokes.clone())
+                    .or_insert(Value::Null);
+            } else {
+                bindings.insert(binding.keystrokes.clone(), binding.action.clone());
+            }
+        }
+        sections
+            .into_iter()
+            .map(|(context, bindings)| json!({ "context": context, "bindings": bindings }))
+            .collect()
+    }
+
+    /// Appends the imported bindings to the contents of a user keymap file.
+    pub fn append_to_keymap_text(&self, keymap_contents: &str, tab_size: usize) -> String {
+        let mut keymap_contents = keymap_contents.to_string();
+        for section in self.keymap_sections() {
+            let (range, replacement) =
+                append_top_level_array_value_in_json_text(&keymap_contents, &section, tab_size);
+            keymap_contents.replace_range(range, &replacement);
+        }
+        keymap_contents
+    }
+}
+
+fn import_keybinding(binding: &VsCodeKeybinding) -> Result<ImportedKeybinding, UnmappedReason> {
+    // A leading `-` removes a default binding; Zed expresses that by binding to `null`.
+    let (command, is_removal) = match binding.command.strip_prefix('-') {
+        Some(command) => (command, true),
+        None => (binding.command.as_str(), false),
+    };
+    let mapping = command_mapping(command).ok_or(UnmappedReason::UnknownCommand)?;
+    let keystrokes = vscode_keys_to_zed(&binding.key).map_err(UnmappedReason::UnsupportedKey)?;
+
+    let action = if is_removal {
+        Value::Null
+    } else {
+        let arguments = match &mapping.arguments {
+            Arguments::None if binding.args.is_some() => None,
+            Arguments::None => Some(Value::Null),
+            Arguments::Fixed(_) if binding.args.is_some() => None,
+            Arguments::Fixed(arguments) => Some(arguments.clone()),
+            Arguments::FromVsCode(from_vscode) => from_vscode(binding.args.as_ref()),
+        };
+        match arguments.ok_or(UnmappedReason::UnsupportedArguments)? {
+            Value::Null => Value::String(mapping.action.to_string()),
+            arguments => json!([mapping.action, arguments]),
+        }
+    };
+
+    let when = match binding.when.as_deref().map(str::trim) {
+        Some(when) if !when.is_empty() => {
+            translate_when_clause(when).map_err(UnmappedReason::UnsupportedWhenClause)?
+        }
+        _ => When::Always(true),
+    };
+    let context = match when {
+        When::Always(true) => mapping.context.to_string(),
+        When::Always(false) => {
+            return Err(UnmappedReason::UnsupportedWhenClause(
+                binding.when.clone().unwrap_or_default(),
+            ));
+        }
+        When::Predicate(predicate) if names_node_context(&predicate.text) => predicate.text,
+        When::Predicate(predicate) => format!(
+            "{} && {}",
+            mapping.context,
+            predicate.text_within(Precedence::And)
+        ),
+    };
+
+    Ok(ImportedKeybinding {
+        context,
+        keystrokes,
+        action,
+        vscode_command: binding.command.clone(),
+    })
+}
+
+fn names_node_context(predicate: &str) -> bool {
+    predicate
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|word| NODE_CONTEXTS.contains(&word))
+}
+
+struct CommandMapping {
+    action: &'static str,
+    context: &'static str,
+    arguments: Arguments,
+}
+
+enum Arguments {
+    None,
+    Fixed(Value),
+    /// Derived from the VS Code `args`, or `None` when they cannot be expressed.
+    FromVsCode(fn(Option<&Value>) -> Option<Value>),
+}
+
+fn command_mapping(command: &str) -> Option<CommandMapping> {
+    macro_rules! map {
+        ($action:expr, $context:expr) => {
+            CommandMapping {
+                action: $action,
+                context: $context,
+                arguments: Arguments::None,
+            }
+        };
+        ($action:expr, $context:expr, $arguments:expr) => {
+            CommandMapping {
+                action: $action,
+                context: $context,
+                arguments: Arguments::Fixed($arguments),
+            }
+        };
+    }
+
+    let mapping = match command {
+        // Editing
+        "editor.action.commentLine" => map!(
+            "editor::ToggleComments",
+            "Editor",
+            json!({ "advance_downwards": false })
+        ),
+        "editor.action.copyLinesDownAction" => map!("editor::DuplicateLineDown", "Editor"),
+        "editor.action.copyLinesUpAction" => map!("editor::DuplicateLineUp", "Editor"),
+        "editor.action.moveLinesDownAction" => map!("editor::MoveLineDown", "Editor"),
+        "editor.action.moveLinesUpAction" => map!("editor::MoveLineUp", "Editor"),
+        "editor.action.deleteLines" => map!("editor::DeleteLine", "Editor"),
+        "editor.action.insertLineAfter" => map!("editor::NewlineBelow", "Editor"),
+        "editor.action.insertLineBefore" => map!("editor::NewlineAbove", "Editor"),
+        "editor.action.indentLines" => map!("editor::Indent", "Editor"),
+        "editor.action.outdentLines" => map!("editor::Outdent", "Editor"),
+        "editor.action.joinLines" => map!("editor::JoinLines", "Editor"),
+        "editor.action.formatDocument" => map!("editor::Format", "Editor"),
+        "editor.action.organizeImports" => map!("editor::OrganizeImports", "Editor"),
+        "editor.action.transformToUppercase" => map!("editor::ConvertToUpperCase", "Editor"),
+        "editor.action.transformToLowercase" => map!("editor::ConvertToLowerCase", "Editor"),
+        "editor.action.sortLinesAscending" => map!("editor::SortLinesCaseSensitive", "Editor"),
+        "editor.action.reverseLines" => map!("editor::ReverseLines", "Editor"),
+        "editor.action.clipboardCopyAction" => map!("editor::Copy", "Editor"),
+        "editor.action.clipboardCutAction" => map!("editor::Cut", "Editor"),
+        "editor.action.clipboardPasteAction" => map!("editor::Paste", "Editor"),
+        "editor.action.selectAll" => map!("editor::SelectAll", "Editor"),
+        "undo" => map!("editor::Undo", "Editor"),
+        "redo" => map!("editor::Redo", "Editor"),
+        "cursorUndo" => map!("editor::UndoSelection", "Editor"),
+        "cursorRedo" => map!("editor::RedoSelection", "Editor"),
+        "deleteWordLeft" => map!(
+            "editor::DeleteToPreviousWordStart",
+            "Editor",
+            json!({ "ignore_newlines": false, "ignore_brackets": false })
+        ),
+        "deleteWordRight" => map!(
+            "editor::DeleteToNextWordEnd",
+            "Editor",
+            json!({ "ignore_newlines": false, "ignore_brackets": false })
+        ),
+
+        // Cursor movement and selection
+        "cursorHome" => map!(
+            "editor::MoveToBeginningOfLine",
+            "Editor",
+            json!({ "stop_at_soft_wraps": true, "stop_at_indent": true })
+        ),
+        "cursorEnd" => map!(
+            "editor::MoveToEndOfLine",
+            "Editor",
+            json!({ "stop_at_soft_wraps": true })
+        ),
+        "cursorTop" => map!("editor::MoveToBeginning", "Editor"),
+        "cursorBottom" => map!("editor::MoveToEnd", "Editor"),
+        "cursorWordLeft" => map!("editor::MoveToPreviousWordStart", "Editor"),
+        "cursorWordEndRight" | "cursorWordRight" => map!("editor::MoveToNextWordEnd", "Editor"),
+        "cursorWordLeftSelect" => map!("editor::SelectToPreviousWordStart", "Editor"),
+        "cursorWordEndRightSelect" | "cursorWordRightSelect" => {
+            map!("editor::SelectToNextWordEnd", "Editor")
+        }
+        "expandLineSelection" => map!("editor::SelectLine", "Editor"),
+        "editor.action.smartSelect.expand" => map!("editor::SelectLargerSyntaxNode", "Editor"),
+        "editor.action.smartSelect.shrink" => map!("editor::SelectSmallerSyntaxNode", "Editor"),
+        "editor.action.jumpToBracket" => map!("editor::MoveToEnclosingBracket", "Editor"),
+        "editor.action.insertCursorAbove" => map!("editor::AddSelectionAbove", "Editor"),
+        "editor.action.insertCursorBelow" => map!("editor::AddSelectionBelow", "Editor"),
+        "editor.action.selectHighlights" | "editor.action.changeAll" => {
+            map!("editor::SelectAllMatches", "Editor")
+        }
+        "editor.action.addSelectionToNextFindMatch" => map!(
+            "editor::SelectNext",
+            "Editor",
+            json!({ "replace_newest": false })
+        ),
+        "editor.action.addSelectionToPreviousFindMatch" => map!(
+            "editor::SelectPrevious",
+            "Editor",
+            json!({ "replace_newest": false })
+        ),
+        "editor.action.moveSelectionToNextFindMatch" => map!(
+            "editor::SelectNext",
+            "Editor",
+            json!({ "replace_newest": true })
+        ),
+        "editor.action.moveSelectionToPreviousFindMatch" => map!(
+            "editor::SelectPrevious",
+            "Editor",
+            json!({ "replace_newest": true })
+        ),
+
+        // Folding
+        "editor.fold" => map!("editor::Fold", "Editor"),
+        "editor.unfold" => map!("editor::UnfoldLines", "Editor"),
+        "editor.toggleFold" => map!("editor::ToggleFold", "Editor"),
+        "editor.foldAll" => map!("editor::FoldAll", "Editor"),
+        "editor.unfoldAll" => map!("editor::UnfoldAll", "Editor"),
+        "editor.foldRecursively" => map!("editor::FoldRecursive", "Editor"),
+        "editor.unfoldRecursively" => map!("editor::UnfoldRecursive", "Editor"),
+
+        // Language features
+        "editor.action.rename" => map!("editor::Rename", "Editor"),
+        "editor.action.revealDefinition" | "editor.action.goToDeclaration" => {
+            map!("editor::GoToDefinition", "Editor")
+        }
+        "editor.action.revealDefinitionAside" => map!("editor::GoToDefinitionSplit", "Editor"),
+        "editor.action.goToTypeDefinition" => map!("editor::GoToTypeDefinition", "Editor"),
+        "editor.action.goToImplementation" => map!("editor::GoToImplementation", "Editor"),
+        "editor.action.goToReferences" | "references-view.findReferences" => {
+            map!("editor::FindAllReferences", "Editor")
+        }
+        "editor.action.showHover" => map!("editor::Hover", "Editor"),
+        "editor.action.quickFix" | "editor.action.codeAction" => {
+            map!("editor::ToggleCodeActions", "Editor")
+        }
+        "editor.action.triggerSuggest" => map!("editor::ShowCompletions", "Editor"),
+        "acceptSelectedSuggestion" => map!("editor::ConfirmCompletion", "Editor"),
+        "editor.action.triggerParameterHints" => map!("editor::ShowSignatureHelp", "Editor"),
+        "editor.action.marker.next" | "editor.action.marker.nextInFiles" => {
+            map!("editor::GoToDiagnostic", "Editor")
+        }
+        "editor.action.marker.prev" | "editor.action.marker.prevInFiles" => {
+            map!("editor::GoToPreviousDiagnostic", "Editor")
+        }
+        "editor.action.dirtydiff.next" | "workbench.action.editor.nextChange" => {
+            map!("editor::GoToHunk", "Editor")
+        }
+        "editor.action.dirtydiff.previous" | "workbench.action.editor.previousChange" => {
+            map!("editor::GoToPreviousHunk", "Editor")
+        }
+        "editor.action.inlineSuggest.commit" => map!("editor::AcceptEditPrediction", "Editor"),
+        "editor.action.inlineSuggest.trigger" => map!("editor::ShowEditPrediction", "Editor"),
+        "editor.action.inlineSuggest.showNext" => map!("editor::NextEditPrediction", "Editor"),
+        "editor.action.inlineSuggest.showPrevious" => {
+            map!("editor::PreviousEditPrediction", "Editor")
+        }
+        "editor.action.showContextMenu" => map!("editor::OpenContextMenu", "Editor"),
+        "editor.action.toggleWordWrap" => map!("editor::ToggleSoftWrap", "Editor"),
+        "editor.debug.action.toggleBreakpoint" => map!("editor::ToggleBreakpoint", "Editor"),
+        "workbench.action.gotoLine" => map!("go_to_line::Toggle", "Editor"),
+        "workbench.action.gotoSymbol" => map!("outline::Toggle", "Editor"),
+        "workbench.action.editor.changeLanguageMode" => {
+            map!("language_selector::Toggle", "Editor")
+        }
+        "copyFilePath" => map!("editor::CopyPath", "Editor"),
+        "revealFileInOS" | "workbench.action.files.revealActiveFileInWindows" => {
+            map!("editor::RevealInFileManager", "Editor")
+        }
+
+        // Search
+        "actions.find" => map!("buffer_search::Deploy", "Editor"),
+        "editor.action.startFindReplaceAction" => map!("buffer_search::DeployReplace", "Editor"),
+        "workbench.action.findInFiles" | "workbench.view.search" => {
+            map!("pane::DeploySearch", "Workspace")
+        }
+        "workbench.action.replaceInFiles" => map!(
+            "pane::DeploySearch",
+            "Workspace",
+            json!({ "replace_enabled": true })
+        ),
+
+        // Files and editors
+        "workbench.action.files.save" => map!("workspace::Save", "Workspace"),
+        "workbench.action.files.saveAs" => map!("workspace::SaveAs", "Workspace"),
+        "workbench.action.files.saveAll" => map!("workspace::SaveAll", "Workspace"),
+        "workbench.action.files.saveWithoutFormatting" => {
+            map!("workspace::SaveWithoutFormat", "Workspace")
+        }
+        "workbench.action.files.newUntitledFile" => map!("workspace::NewFile", "Workspace"),
+        "workbench.action.newWindow" => map!("workspace::NewWindow", "Workspace"),
+        "workbench.action.files.openFile"
+        | "workbench.action.files.openFolder"
+        | "workbench.action.files.openFileFolder" => map!("workspace::Open", "Workspace"),
+        "workbench.action.openRecent" => map!(
+            "projects::OpenRecent",
+            "Workspace",
+            json!({ "create_new_window": false })
+        ),
+        "workbench.action.closeWindow" => map!("workspace::CloseWindow", "Workspace"),
+        "workbench.action.quit" => map!("zed::Quit", "Workspace"),
+        "workbench.action.closeActiveEditor" => map!(
+            "pane::CloseActiveItem",
+            "Pane",
+            json!({ "close_pinned": false })
+        ),
+        "workbench.action.closeAllEditors" => map!(
+            "pane::CloseAllItems",
+            "Pane",
+            json!({ "close_pinned": false })
+        ),
+        "workbench.action.closeOtherEditors" => map!(
+            "pane::CloseOtherItems",
+            "Pane",
+            json!({ "close_pinned": false })
+        ),
+        "workbench.action.reopenClosedEditor" => map!("pane::ReopenClosedItem", "Pane"),
+        "workbench.action.nextEditor" | "workbench.action.nextEditorInGroup" => {
+            map!("pane::ActivateNextItem", "Pane")
+        }
+        "workbench.action.previousEditor" | "workbench.action.previousEditorInGroup" => {
+            map!("pane::ActivatePreviousItem", "Pane")
+        }
+        "workbench.action.lastEditorInGroup" => map!("pane::ActivateLastItem", "Pane"),
+        "workbench.action.pinEditor" | "workbench.action.unpinEditor" => {
+            map!("pane::TogglePinTab", "Pane")
+        }
+        "workbench.action.splitEditor" | "workbench.action.splitEditorRight" => {
+            map!("pane::SplitRight", "Pane")
+        }
+        "workbench.action.splitEditorLeft" => map!("pane::SplitLeft", "Pane"),
+        "workbench.action.splitEditorUp" => map!("pane::SplitUp", "Pane"),
+        "workbench.action.splitEditorDown" | "workbench.action.splitEditorOrthogonal" => {
+            map!("pane::SplitDown", "Pane")
+        }
+        "workbench.action.navigateBack" => map!("pane::GoBack", "Pane"),
+        "workbench.action.navigateForward" => map!("pane::GoForward", "Pane"),
+        "workbench.files.action.showActiveFileInExplorer" => {
+            map!("pane::RevealInProjectPanel", "Pane")
+        }
+        "copyRelativeFilePath" => map!("workspace::CopyRelativePath", "Workspace"),
+        "workbench.action.focusLeftGroup" => map!("workspace::ActivatePaneLeft", "Workspace"),
+        "workbench.action.focusRightGroup" => map!("workspace::ActivatePaneRight", "Workspace"),
+        "workbench.action.focusAboveGroup" => map!("workspace::ActivatePaneUp", "Workspace"),
+        "workbench.action.focusBelowGroup" => map!("workspace::ActivatePaneDown", "Workspace"),
+        "workbench.action.showAllEditors"
+        | "workbench.action.showAllEditorsByMostRecentlyUsed"
+        | "workbench.action.quickOpenPreviousRecentlyUsedEditorInGroup" => {
+            map!("tab_switcher::Toggle", "Workspace")
+        }
+
+        // Layout
+        "workbench.action.toggleSidebarVisibility" => {
+            map!("workspace::ToggleLeftDock", "Workspace")
+        }
+        "workbench.action.togglePanel" => map!("workspace::ToggleBottomDock", "Workspace"),
+        "workbench.action.toggleAuxiliaryBar" => map!("workspace::ToggleRightDock", "Workspace"),
+        "workbench.action.toggleMaximizeEditorGroup" | "workbench.action.toggleMaximizedPanel" => {
+            map!("workspace::ToggleZoom", "Workspace")
+        }
+        "workbench.action.toggleFullScreen" => map!("zed::ToggleFullScreen", "Workspace"),
+        "workbench.action.zoomIn" => map!(
+            "zed::IncreaseUiFontSize",
+            "Workspace",
+            json!({ "persist": false })
+        ),
+        "workbench.action.zoomOut" => map!(
+            "zed::DecreaseUiFontSize",
+            "Workspace",
+            json!({ "persist": false })
+        ),
+        "workbench.action.zoomReset" => map!(
+            "zed::ResetUiFontSize",
+            "Workspace",
+            json!({ "persist": false })
+        ),
+        "editor.action.fontZoomIn" => map!(
+            "zed::IncreaseBufferFontSize",
+            "Workspace",
+            json!({ "persist": false })
+        ),
+        "editor.action.fontZoomOut" => map!(
+            "zed::DecreaseBufferFontSize",
+            "Workspace",
+            json!({ "persist": false })
+        ),
+        "editor.action.fontZoomReset" => map!(
+            "zed::ResetBufferFontSize",
+            "Workspace",
+            json!({ "persist": false })
+        ),
+
+        // Pickers, views and panels
+        "workbench.action.showCommands" => map!("command_palette::Toggle", "Workspace"),
+        "workbench.action.quickOpen" => map!("file_finder::Toggle", "Workspace"),
+        "workbench.action.showAllSymbols" => map!("project_symbols::Toggle", "Workspace"),
+        "workbench.action.selectTheme" => map!("theme_selector::Toggle", "Workspace"),
+        "workbench.action.openSettings" => map!("zed::OpenSettings", "Workspace"),
+        "workbench.action.openSettingsJson" => map!("zed::OpenSettingsFile", "Workspace"),
+        "workbench.action.openGlobalKeybindings" => map!("zed::OpenKeymap", "Workspace"),
+        "workbench.action.openGlobalKeybindingsFile" => map!("zed::OpenKeymapFile", "Workspace"),
+        "workbench.view.explorer" => map!("project_panel::ToggleFocus", "Workspace"),
+        "workbench.view.scm" => map!("git_panel::ToggleFocus", "Workspace"),
+        "workbench.view.debug" => map!("debug_panel::ToggleFocus", "Workspace"),
+        "workbench.view.extensions" => map!("zed::Extensions", "Workspace"),
+        "workbench.actions.view.problems" => map!("diagnostics::Deploy", "Workspace"),
+
+        // Terminal
+        "workbench.action.terminal.toggleTerminal" => {
+            map!("terminal_panel::Toggle", "Workspace")
+        }
+        "workbench.action.terminal.new" => map!("workspace::NewTerminal", "Workspace"),
+        "workbench.action.terminal.clear" => map!("terminal::Clear", "Terminal"),
+        "workbench.action.terminal.copySelection" => map!("terminal::Copy", "Terminal"),
+        "workbench.action.terminal.paste" => map!("terminal::Paste", "Terminal"),
+        "workbench.action.terminal.scrollUp" => map!("terminal::ScrollLineUp", "Terminal"),
+        "workbench.action.terminal.scrollDown" => map!("terminal::ScrollLineDown", "Terminal"),
+        "workbench.action.terminal.scrollToTop" => map!("terminal::ScrollToTop", "Terminal"),
+        "workbench.action.terminal.scrollToBottom" => {
+            map!("terminal::ScrollToBottom", "Terminal")
+        }
+        "workbench.action.terminal.sendSequence" => CommandMapping {
+            action: "terminal::SendText",
+            context: "Terminal",
+            arguments: Arguments::FromVsCode(|args| Some(args?.get("text")?.as_str()?.into())),
+        },
+
+        // Project panel
+        "explorer.newFile" => map!("project_panel::NewFile", "ProjectPanel"),
+        "explorer.newFolder" => map!("project_panel::NewDirectory", "ProjectPanel"),
+        "renameFile" => map!("project_panel::Rename", "ProjectPanel"),
+        "deleteFile" | "moveFileToTrash" => map!("project_panel::Trash", "ProjectPanel"),
+        "filesExplorer.copy" => map!("project_panel::Copy", "ProjectPanel"),
+        "filesExplorer.cut" => map!("project_panel::Cut", "ProjectPanel"),
+        "filesExplorer.paste" => map!("project_panel::Paste", "ProjectPanel"),
+        "workbench.files.action.collapseExplorerFolders" => {
+            map!("project_panel::CollapseAllEntries", "ProjectPanel")
+        }
+
+        // Tasks and debugging
+        "workbench.action.tasks.runTask" => map!("task::Spawn", "Workspace"),
+        "workbench.action.tasks.reRunTask" => map!(
+            "task::Rerun",
+            "Workspace",
+            json!({ "reevaluate_context": false })
+        ),
+        "workbench.action.debug.start" | "workbench.action.debug.run" => {
+            map!("debugger::Start", "Workspace")
+        }
+        "workbench.action.debug.continue" => map!("debugger::Continue", "Workspace"),
+        "workbench.action.debug.pause" => map!("debugger::Pause", "Workspace"),
+        "workbench.action.debug.stepOver" => map!("debugger::StepOver", "Workspace"),
+        "workbench.action.debug.stepInto" => map!("debugger::StepInto", "Workspace"),
+        "workbench.action.debug.stepOut" => map!("debugger::StepOut", "Workspace"),
+        "workbench.action.debug.restart" => map!("debugger::Rerun", "Workspace"),
+        "workbench.action.debug.stop" => map!("debugger::Stop", "Workspace"),
+
+        // Git
+        "git.commit" => map!("git::Commit", "Workspace"),
+        "git.push" => map!("git::Push", "Workspace"),
+        "git.pull" => map!("git::Pull", "Workspace"),
+        "git.fetch" => map!("git::Fetch", "Workspace"),
+        "git.stageAll" => map!("git::StageAll", "Workspace"),
+        "git.unstageAll" => map!("git::UnstageAll", "Workspace"),
+
+        _ => {
+            let index = command
+                .strip_prefix("workbench.action.openEditorAtIndex")?
+                .parse::<usize>()
+                .ok()
+                .filter(|index| (1..=9).contains(index))?;
+            map!("pane::ActivateItem", "Pane", json!(index - 1))
+        }
+    };
+    Some(mapping)
+}
+
+/// Converts a VS Code key sequence such as `ctrl+k ctrl+shift+s` to Zed's `ctrl-k ctrl-shift-s`.
+fn vscode_keys_to_zed(keys: &str) -> Result<String, String> {
+    let keystrokes = keys
+        .split_whitespace()
+        .map(vscode_keystroke_to_zed)
+        .collect::<Result<Vec<_>, _>>()?;
+    if keystrokes.is_empty() {
+        return Err(keys.to_string());
+    }
+    Ok(keystrokes.join(" "))
+}
+
+fn vscode_keystroke_to_zed(keystroke: &str) -> Result<String, String> {
+    let keystroke = keystroke.to_lowercase();
+    let (modifiers, key) = if keystroke == "+" {
+        ("", "+")
+    } else if let Some(modifiers) = keystroke.strip_suffix("++") {
+        (modifiers, "+")
+    } else {
+        keystroke
+            .rsplit_once('+')
+            .unwrap_or(("", keystroke.as_str()))
+    };
+
+    let mut parts = Vec::new();
+    for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+        parts.push(match modifier {
+            "ctrl" | "shift" | "alt" | "cmd" | "win" => modifier,
+            "meta" => "super",
+            "option" => "alt",
+            _ => return Err(modifier.to_string()),
+        });
+    }
+
+    let key = match key {
+        "escape" | "enter" | "tab" | "space" | "backspace" | "delete" | "insert" | "home"
+        | "end" | "pageup" | "pagedown" | "up" | "down" | "left" | "right" => key.to_string(),
+        key if key.chars().count() == 1 && !key.chars().all(char::is_whitespace) => key.to_string(),
+        key if key
+            .strip_prefix('f')
+            .and_then(|number| number.parse::<u8>().ok())
+            .is_some_and(|number| (1..=24).contains(&number)) =>
+        {
+            key.to_string()
+        }
+        // Scan code bindings, e.g. `[KeyA]` or `[Digit1]`.
+        key if key.starts_with('[') && key.ends_with(']') => {
+            let code = &key[1..key.len() - 1];
+            code.strip_prefix("key")
+                .or_else(|| code.strip_prefix("digit"))
+                .filter(|key| key.len() == 1)
+                .ok_or_else(|| key.to_string())?
+                .to_string()
+        }
+        _ => return Err(key.to_string()),
+    };
+    parts.push(&key);
+    Ok(parts.join("-"))
+}
+
+/// A translated `when` clause. Clauses that only depend on the platform, like `isMac`, are
+/// resolved at import time, since the keymap file is specific to this machine.
+#[derive(Debug, PartialEq)]
+enum When {
+    Always(bool),
+    Predicate(Predicate),
+}
+
+#[derive(Debug, PartialEq)]
+struct Predicate {
+    text: String,
+    precedence: Precedence,
+}
+
+/// How tightly a translated predicate binds, to know when it needs parentheses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Or,
+    And,
+    Comparison,
+    Identifier,
+}
+
+impl Predicate {
+    fn new(text: impl Into<String>, precedence: Precedence) -> When {
+        When::Predicate(Self {
+            text: text.into(),
+            precedence,
+        })
+    }
+
+    /// The predicate's text, parenthesized if it binds more loosely than `precedence`.
+    fn text_within(self, precedence: Precedence) -> String {
+        if self.precedence < precedence {
+            format!("({})", self.text)
+        } else {
+            self.text
+        }
+    }
+}
+
+/// Translates a VS Code `when` clause into a Zed context predicate. Errors carry the part of
+/// the clause that has no Zed equivalent.
+fn translate_when_clause(when: &str) -> Result<When, String> {
+    let tokens = tokenize_when_clause(when)?;
+    let mut parser = WhenParser {
+        clause: when,
+        tokens,
+        position: 0,
+    };
+    let result = parser.parse_or()?;
+    if parser.position != parser.tokens.len() {
+        return Err(when.to_string());
+    }
+    Ok(result)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum WhenToken {
+    Word(String),
+    Not,
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    OpenParen,
+    CloseParen,
+}
+
+fn tokenize_when_clause(when: &str) -> Result<Vec<WhenToken>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = when.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => WhenToken::OpenParen,
+            ')' => WhenToken::CloseParen,
+            '&' if chars.next_if(|(_, c)| *c == '&').is_some() => WhenToken::And,
+            '|' if chars.next_if(|(_, c)| *c == '|').is_some() => WhenToken::Or,
+            '=' if chars.next_if(|(_, c)| *c == '=').is_some() => {
+                chars.next_if(|(_, c)| *c == '=');
+                WhenToken::Equal
+            }
+            '!' if chars.next_if(|(_, c)| *c == '=').is_some() => {
+                chars.next_if(|(_, c)| *c == '=');
+                WhenToken::NotEqual
+            }
+            '!' => WhenToken::Not,
+            '\'' | '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, end)) if end == c => break,
+                        Some((_, c)) => value.push(c),
+                        None => return Err(when[start..].to_string()),
+                    }
+                }
+                WhenToken::Word(value)
+            }
+            c if c.is_alphanumeric() || "._-:/".contains(c) => {
+                let mut end = start + c.len_utf8();
+                while let Some((ix, c)) =
+                    chars.next_if(|(_, c)| c.is_alphanumeric() || "._-:/".contains(*c))
+                {
+                    end = ix + c.len_utf8();
+                }
+                WhenToken::Word(when[start..end].to_string())
+            }
+            _ => return Err(when[start..].to_string()),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct WhenParser<'a> {
+    clause: &'a str,
+    tokens: Vec<WhenToken>,
+    position: usize,
+}
+
+impl WhenParser<'_> {
+    fn next_if(&mut self, token: &WhenToken) -> bool {
+        if self.tokens.get(self.position) == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn next_word(&mut self) -> Result<String, String> {
+        match self.tokens.get(self.position) {
+            Some(WhenToken::Word(word)) => {
+                self.position += 1;
+                Ok(word.clone())
+            }
+            _ => Err(self.clause.to_string()),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<When, String> {
+        let mut result = self.parse_and()?;
+        while self.next_if(&WhenToken::Or) {
+            let operand = self.parse_and()?;
+            result = match (result, operand) {
+                (When::Always(true), _) | (_, When::Always(true)) => When::Always(true),
+                (When::Always(false), operand) | (operand, When::Always(false)) => operand,
+                (When::Predicate(left), When::Predicate(right)) => {
+                    Predicate::new(format!("{} || {}", left.text, right.text), Precedence::Or)
+                }
+            };
+        }
+        Ok(result)
+    }
+
+    fn parse_and(&mut self) -> Result<When, String> {
+        let mut result = self.parse_unary()?;
+        while self.next_if(&WhenToken::And) {
+            let operand = self.parse_unary()?;
+            result = match (result, operand) {
+                (When::Always(false), _) | (_, When::Always(false)) => When::Always(false),
+                (When::Always(true), operand) | (operand, When::Always(true)) => operand,
+                (When::Predicate(left), When::Predicate(right)) => Predicate::new(
+                    format!(
+                        "{} && {}",
+                        left.text_within(Precedence::And),
+                        right.text_within(Precedence::And)
+                    ),
+                    Precedence::And,
+                ),
+            };
+        }
+        Ok(result)
+    }
+
+    fn parse_unary(&mut self) -> Result<When, String> {
+        if self.next_if(&WhenToken::Not) {
+            return Ok(match self.parse_unary()? {
+                When::Always(value) => When::Always(!value),
+                When::Predicate(operand) => Predicate::new(
+                    format!("!{}", operand.text_within(Precedence::Identifier)),
+                    Precedence::Identifier,
+                ),
+            });
+        }
+        if self.next_if(&WhenToken::OpenParen) {
+            let inner = self.parse_or()?;
+            if !self.next_if(&WhenToken::CloseParen) {
+                return Err(self.clause.to_string());
+            }
+            return Ok(inner);
+        }
+
+        let key = self.next_word()?;
+        for (operator, zed_operator) in [(WhenToken::Equal, "=="), (WhenToken::NotEqual, "!=")] {
+            if self.next_if(&operator) {
+                let value = self.next_word()?;
+                let extension = translate_equality(&key, &value)
+                    .ok_or_else(|| format!("{key} {zed_operator} {value}"))?;
+                return Ok(Predicate::new(
+                    format!("extension {zed_operator} {extension}"),
+                    Precedence::Comparison,
+                ));
+            }
+        }
+        translate_context_key(&key)
+    }
+}
+
+fn translate_context_key(key: &str) -> Result<When, String> {
+    let text = match key {
+        "true" => return Ok(When::Always(true)),
+        "false" => return Ok(When::Always(false)),
+        "isLinux" => return Ok(When::Always(cfg!(target_os = "linux"))),
+        "isMac" => return Ok(When::Always(cfg!(target_os = "macos"))),
+        "isWindows" => return Ok(When::Always(cfg!(target_os = "windows"))),
+        "editorFocus" | "editorTextFocus" | "textInputFocus" => "Editor",
+        "terminalFocus" | "terminalIsOpen" => "Terminal",
+        "filesExplorerFocus" | "explorerViewletFocus" | "explorerViewletVisible" => "ProjectPanel",
+        "inQuickOpen" => "Picker",
+        "findWidgetVisible" => "BufferSearchBar",
+        "suggestWidgetVisible" => "showing_completions",
+        "parameterHintsVisible" => "showing_signature_help",
+        "renameInputVisible" => "renaming",
+        "inSnippetMode" => "in_snippet",
+        "hasNextTabstop" => "has_next_tabstop",
+        "hasPrevTabstop" => "has_previous_tabstop",
+        "inlineSuggestionVisible" | "inlineEditIsVisible" => "edit_prediction",
+        _ => return Err(key.to_string()),
+    };
+    Ok(Predicate::new(text, Precedence::Identifier))
+}
+
+/// Zed keys language-specific bindings on the file extension rather than the language id.
+fn translate_equality(key: &str, value: &str) -> Option<String> {
+    match key {
+        "resourceExtname" => Some(value.trim_start_matches('.').to_string()),
+        "editorLangId" | "resourceLangId" => {
+            let extension = match value {
+                "c" => "c",
+                "cpp" => "cpp",
+                "csharp" => "cs",
+                "css" => "css",
+                "go" => "go",
+                "html" => "html",
+                "java" => "java",
+                "javascript" => "js",
+                "javascriptreact" => "jsx",
+                "json" => "json",
+                "lua" => "lua",
+                "markdown" => "md",
+                "php" => "php",
+                "python" => "py",
+                "ruby" => "rb",
+                "rust" => "rs",
+                "shellscript" => "sh",
+                "toml" => "toml",
+                "typescript" => "ts",
+                "typescriptreact" => "tsx",
+                _ => return None,
+            };
+            Some(extension.to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn import(content: &str) -> VsCodeKeymapImport {
+        VsCodeKeybindings::from_str(content, VsCodeSettingsSource::VsCode)
+            .unwrap()
+            .import()
+    }
+
+    #[test]
+    fn test_key_conversion() {
+        assert_eq!(vscode_keys_to_zed("ctrl+shift+P").unwrap(), "ctrl-shift-p");
+        assert_eq!(
+            vscode_keys_to_zed("ctrl+k ctrl+s").unwrap(),
+            "ctrl-k ctrl-s"
+        );
+        assert_eq!(vscode_keys_to_zed("meta+alt+f12").unwrap(), "super-alt-f12");
+        assert_eq!(vscode_keys_to_zed("ctrl++").unwrap(), "ctrl-+");
+        assert_eq!(vscode_keys_to_zed("ctrl+[KeyA]").unwrap(), "ctrl-a");
+        assert_eq!(
+            vscode_keys_to_zed("ctrl+numpad_add").unwrap_err(),
+            "numpad_add"
+        );
+    }
+
+    #[test]
+    fn test_when_clause_translation() {
+        let translate = |when| match translate_when_clause(when) {
+            Ok(When::Predicate(predicate)) => Ok(predicate.text),
+            Ok(When::Always(value)) => Ok(value.to_string()),
+            Err(error) => Err(error),
+        };
+        assert_eq!(
+            translate("editorTextFocus && !suggestWidgetVisible").unwrap(),
+            "Editor && !showing_completions"
+        );
+        assert_eq!(
+            translate("suggestWidgetVisible || inSnippetMode && hasNextTabstop").unwrap(),
+            "showing_completions || in_snippet && has_next_tabstop"
+        );
+        assert_eq!(
+            translate("editorFocus && (renameInputVisible || parameterHintsVisible)").unwrap(),
+            "Editor && (renaming || showing_signature_help)"
+        );
+        assert_eq!(
+            translate("resourceExtname == .rs || editorLangId == 'python'").unwrap(),
+            "extension == rs || extension == py"
+        );
+        assert_eq!(
+            translate("!(terminalFocus || inQuickOpen)").unwrap(),
+            "!(Terminal || Picker)"
+        );
+        assert_eq!(translate("editorTextFocus && true").unwrap(), "Editor");
+        assert_eq!(translate("isLinux || isMac || isWindows").unwrap(), "true");
+        assert_eq!(
+            translate("editorTextFocus && config.editor.stablePeek").unwrap_err(),
+            "config.editor.stablePeek"
+        );
+        assert_eq!(
+            translate("resourceFilename =~ /docker/").unwrap_err(),
+            "=~ /docker/"
+        );
+    }
+
+    #[test]
+    fn test_import_keybindings() {
+        let result = import(
+            r#"
+            // Place your key bindings in this file to override the defaults
+            [
+                { "key": "ctrl+shift+d", "command": "editor.action.copyLinesDownAction", "when": "editorTextFocus && !editorReadonly" },
+                { "key": "ctrl+d", "command": "editor.action.copyLinesDownAction", "when": "editorTextFocus" },
+                { "key": "ctrl+d", "command": "-editor.action.addSelectionToNextFindMatch" },
+                { "key": "tab", "command": "acceptSelectedSuggestion", "when": "suggestWidgetVisible" },
+                { "key": "ctrl+alt+3", "command": "workbench.action.openEditorAtIndex3" },
+                { "key": "ctrl+alt+l", "command": "workbench.action.terminal.sendSequence", "args": { "text": "ls\n" } },
+                { "key": "ctrl+alt+c", "command": "workbench.action.terminal.sendSequence", "args": { "cmd": "ls" } },
+                { "key": "ctrl+alt+e", "command": "myExtension.doSomething" },
+            ]
+            "#,
+        );
+
+        assert_eq!(
+            result.keymap_sections(),
+            vec![
+                json!({
+                    "context": "Editor",
+                    "bindings": {
+                        "ctrl-d": "editor::DuplicateLineDown",
+                        "tab": "editor::ConfirmCompletion",
+                    }
+                }),
+                json!({
+                    "context": "Pane",
+                    "bindings": { "ctrl-alt-3": ["pane::ActivateItem", 2] }
+                }),
+                json!({
+                    "context": "Terminal",
+                    "bindings": { "ctrl-alt-l": ["terminal::SendText", "ls\n"] }
+                }),
+            ]
+        );
+        assert_eq!(
+            result
+                .unmapped
+                .iter()
+                .map(|binding| (binding.key.as_str(), binding.reason.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "ctrl+shift+d",
+                    UnmappedReason::UnsupportedWhenClause("editorReadonly".into())
+                ),
+                ("ctrl+alt+c", UnmappedReason::UnsupportedArguments),
+                ("ctrl+alt+e", UnmappedReason::UnknownCommand),
+            ]
+        );
+    }
+}
@@ -713,6 +713,43 @@ pub fn infer_json_indent_size(text: &str) -> usize {
     if max_count == 0 { 2 } else { max_indent }
 }
 
+/// Finds the key-value pair at `key_path` in JSON text, returning the byte range of the pair,
+/// or `None` if the key isn't present. When a key appears several times in one object, the
+/// last occurrence is returned, since that is the one that takes effect.
+pub fn key_path_range_in_json_text<T: AsRef<str>>(
+    text: &str,
+    key_path: &[T],
+) -> Option<Range<usize>> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_json::LANGUAGE.into())
+        .unwrap();
+    let syntax_tree = parser.parse(text, None)?;
+
+    let root = syntax_tree.root_node();
+    let mut cursor = root.walk();
+    let mut value = root
+        .named_children(&mut cursor)
+        .find(|node| node.kind() == "object")?;
+    let mut pair_range = None;
+    for key in key_path {
+        let key = serde_json::to_string(key.as_ref()).ok()?;
+        let mut cursor = value.walk();
+        let pair = value
+            .named_children(&mut cursor)
+            .filter(|node| node.kind() == "pair")
+            .filter(|pair| {
+                pair.child_by_field_name("key")
+                    .and_then(|key_node| text.get(key_node.byte_range()))
+                    .is_some_and(|key_text| key_text == key)
+            })
+            .last()?;
+        pair_range = Some(pair.byte_range());
+        value = pair.child_by_field_name("value")?;
+    }
+    pair_range
+}
+
 pub fn to_pretty_json(
     value: &impl Serialize,
     indent_size: usize,
@@ -2632,4 +2669,28 @@ mod tests {
 }"#;
         assert_eq!(infer_json_indent_size(json_mixed), 2);
     }
+
+    #[test]
+    fn test_key_path_range_in_json_text() {
+        let text = r#"{
+  // The theme
+  "theme": "One Dark",
+  "languages": {
+    "Rust": { "tab_size": 2 },
+    "Rust": { "tab_size": 8 }
+  },
+  "terminal": {}
+}"#;
+        let pair_text = |key_path: &[&str]| {
+            key_path_range_in_json_text(text, key_path).map(|range| &text[range])
+        };
+        assert_eq!(pair_text(&["theme"]), Some(r#""theme": "One Dark""#));
+        assert_eq!(
+            pair_text(&["languages", "Rust", "tab_size"]),
+            Some(r#""tab_size": 8"#)
+        );
+        assert_eq!(pair_text(&["terminal", "font_size"]), None);
+        assert_eq!(pair_text(&["theme", "mode"]), None);
+        assert_eq!(key_path_range_in_json_text("[]", &["theme"]), None);
+    }
 }
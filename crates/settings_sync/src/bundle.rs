@@ -0,0 +1,330 @@
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use collections::{BTreeMap, BTreeSet};
+use fs::{Fs, RemoveOptions};
+use futures::StreamExt as _;
+use serde::{Deserialize, Serialize};
+
+use crate::merge::{MergeConflict, merge_json_text, merge_opaque_text};
+
+const BUNDLE_VERSION: u32 = 1;
+
+const SETTINGS_FILE: &str = "settings.json";
+const KEYMAP_FILE: &str = "keymap.json";
+const EXTENSIONS_FILE: &str = "extensions.json";
+const SNIPPETS_DIR: &str = "snippets";
+const THEMES_DIR: &str = "themes";
+
+/// A snapshot of the user's configuration. Files are kept as text, so that comments and
+/// formatting survive a round trip.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    #[serde(default)]
+    pub settings: Option<String>,
+    #[serde(default)]
+    pub keymap: Option<String>,
+    /// Snippet files, keyed by file name.
+    #[serde(default)]
+    pub snippets: BTreeMap<String, String>,
+    /// Theme files, keyed by file name.
+    #[serde(default)]
+    pub themes: BTreeMap<String, String>,
+    /// The ids of installed extensions.
+    #[serde(default)]
+    pub extensions: BTreeSet<String>,
+}
+
+impl SettingsBundle {
+    /// Collects the configuration of this machine.
+    pub async fn collect(fs: &dyn Fs, extensions: BTreeSet<String>) -> Result<Self> {
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            settings: load_optional(fs, paths::settings_file()).await?,
+            keymap: load_optional(fs, paths::keymap_file()).await?,
+            snippets: load_json_files(fs, paths::snippets_dir()).await?,
+            themes: load_json_files(fs, paths::themes_dir()).await?,
+            extensions,
+        })
+    }
+
+    /// Writes the bundle over the configuration of this machine. Extensions are installed
+    /// separately.
+    pub async fn apply(&self, fs: &dyn Fs) -> Result<()> {
+        write_optional(fs, paths::settings_file(), self.settings.as_deref()).await?;
+        write_optional(fs, paths::keymap_file(), self.keymap.as_deref()).await?;
+        write_json_files(fs, paths::snippets_dir(), &self.snippets).await?;
+        write_json_files(fs, paths::themes_dir(), &self.themes).await?;
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let bundle: Self = serde_json::from_str(json).context("Invalid settings bundle")?;
+        anyhow::ensure!(
+            bundle.version <= BUNDLE_VERSION,
+            "Settings bundle version {} is newer than this version of Zed supports",
+            bundle.version
+        );
+        Ok(bundle)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Reads a bundle laid out as files in a directory, such as a git repository.
+    pub async fn read_dir(fs: &dyn Fs, dir: &Path) -> Result<Self> {
+        let extensions = match load_optional(fs, &dir.join(EXTENSIONS_FILE)).await? {
+            Some(extensions) => serde_json_lenient::from_str(&extensions)
+                .with_context(|| format!("Invalid {EXTENSIONS_FILE}"))?,
+            None => BTreeSet::default(),
+        };
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            settings: load_optional(fs, &dir.join(SETTINGS_FILE)).await?,
+            keymap: load_optional(fs, &dir.join(KEYMAP_FILE)).await?,
+            snippets: load_json_files(fs, &dir.join(SNIPPETS_DIR)).await?,
+            themes: load_json_files(fs, &dir.join(THEMES_DIR)).await?,
+            extensions,
+        })
+    }
+
+    /// Writes the bundle as files in a directory, such as a git repository.
+    pub async fn write_dir(&self, fs: &dyn Fs, dir: &Path) -> Result<()> {
+        write_optional(fs, &dir.join(SETTINGS_FILE), self.settings.as_deref()).await?;
+        write_optional(fs, &dir.join(KEYMAP_FILE), self.keymap.as_deref()).await?;
+        write_json_files(fs, &dir.join(SNIPPETS_DIR), &self.snippets).await?;
+        write_json_files(fs, &dir.join(THEMES_DIR), &self.themes).await?;
+        let extensions = serde_json::to_string_pretty(&self.extensions)?;
+        write_optional(fs, &dir.join(EXTENSIONS_FILE), Some(&extensions)).await?;
+        Ok(())
+    }
+
+    /// Three-way merges the local and remote configurations, given the configuration both
+    /// were last synced from. Conflicting changes keep the local version and are reported.
+    ///
+    /// Extensions are only ever added, so that uninstalling an extension on one machine
+    /// doesn't remove it from the others.
+    pub fn merge(base: &Self, local: &Self, remote: &Self) -> (Self, Vec<MergeConflict>) {
+        let mut conflicts = Vec::new();
+        let merged = Self {
+            version: BUNDLE_VERSION,
+            settings: merge_json_text(
+                SETTINGS_FILE,
+                base.settings.as_deref(),
+                local.settings.as_deref(),
+                remote.settings.as_deref(),
+                &mut conflicts,
+            ),
+            keymap: merge_json_text(
+                KEYMAP_FILE,
+                base.keymap.as_deref(),
+                local.keymap.as_deref(),
+                remote.keymap.as_deref(),
+                &mut conflicts,
+            ),
+            snippets: merge_files(
+                SNIPPETS_DIR,
+                &base.snippets,
+                &local.snippets,
+                &remote.snippets,
+                merge_json_text,
+                &mut conflicts,
+            ),
+            themes: merge_files(
+                THEMES_DIR,
+                &base.themes,
+                &local.themes,
+                &remote.themes,
+                merge_opaque_text,
+                &mut conflicts,
+            ),
+            extensions: local
+                .extensions
+                .union(&remote.extensions)
+                .cloned()
+                .collect(),
+        };
+        (merged, conflicts)
+    }
+}
+
+type MergeFn =
+    fn(&str, Option<&str>, Option<&str>, Option<&str>, &mut Vec<MergeConflict>) -> Option<String>;
+
+fn merge_files(
+    dir: &str,
+    base: &BTreeMap<String, String>,
+    local: &BTreeMap<String, String>,
+    remote: &BTreeMap<String, String>,
+    merge: MergeFn,
+    conflicts: &mut Vec<MergeConflict>,
+) -> BTreeMap<String, String> {
+    let names = base
+        .keys()
+        .chain(local.keys())
+        .chain(remote.keys())
+        .collect::<BTreeSet<_>>();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let merged = merge(
+                &format!("{dir}/{name}"),
+                base.get(name).map(String::as_str),
+                local.get(name).map(String::as_str),
+                remote.get(name).map(String::as_str),
+                conflicts,
+            )?;
+            Some((name.clone(), merged))
+        })
+        .collect()
+}
+
+async fn load_optional(fs: &dyn Fs, path: &Path) -> Result<Option<String>> {
+    if !fs.is_file(path).await {
+        return Ok(None);
+    }
+    let text = fs
+        .load(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(Some(text))
+}
+
+async fn write_optional(fs: &dyn Fs, path: &Path, text: Option<&str>) -> Result<()> {
+    let existing = load_optional(fs, path).await?;
+    if existing.as_deref() == text {
+        return Ok(());
+    }
+    match text {
+        Some(text) => {
+            if let Some(parent) = path.parent() {
+                fs.create_dir(parent).await?;
+            }
+            fs.atomic_write(path.to_path_buf(), text.to_string())
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))
+        }
+        None => fs
+            .remove_file(
+                path,
+                RemoveOptions {
+                    recursive: false,
+                    ignore_if_not_exists: true,
+                },
+            )
+            .await
+            .with_context(|| format!("Failed to remove {}", path.display())),
+    }
+}
+
+async fn load_json_files(fs: &dyn Fs, dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::default();
+    if !fs.is_dir(dir).await {
+        return Ok(files);
+    }
+    let mut entries = fs.read_dir(dir).await?;
+    while let Some(path) = entries.next().await {
+        let path = path?;
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if let Some(text) = load_optional(fs, &path).await? {
+            files.insert(name.to_string(), text);
+        }
+    }
+    Ok(files)
+}
+
+async fn write_json_files(fs: &dyn Fs, dir: &Path, files: &BTreeMap<String, String>) -> Result<()> {
+    let existing = load_json_files(fs, dir).await?;
+    for name in existing.keys() {
+        if !files.contains_key(name) {
+            write_optional(fs, &dir.join(name), None).await?;
+        }
+    }
+    for (name, text) in files {
+        if existing.get(name) != Some(text) {
+            write_optional(fs, &dir.join(name), Some(text)).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[gpui::test]
+    async fn test_bundle_directory_round_trip(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let dir = PathBuf::from("/sync");
+        let bundle = SettingsBundle {
+            version: BUNDLE_VERSION,
+            settings: Some("{\n  // comment\n  \"vim_mode\": true\n}".into()),
+            keymap: None,
+            snippets: BTreeMap::from_iter([("rust.json".into(), "{}".into())]),
+            themes: BTreeMap::from_iter([("mine.json".into(), "{ \"name\": \"Mine\" }".into())]),
+            extensions: BTreeSet::from_iter(["html".into(), "toml".into()]),
+        };
+
+        bundle.write_dir(fs.as_ref(), &dir).await.unwrap();
+        assert_eq!(
+            SettingsBundle::read_dir(fs.as_ref(), &dir).await.unwrap(),
+            bundle
+        );
+
+        let mut without_theme = bundle.clone();
+        without_theme.themes.clear();
+        without_theme.write_dir(fs.as_ref(), &dir).await.unwrap();
+        assert!(!fs.is_file(&dir.join("themes/mine.json")).await);
+        assert_eq!(
+            SettingsBundle::from_json(&without_theme.to_json().unwrap()).unwrap(),
+            without_theme
+        );
+    }
+
+    #[test]
+    fn test_merge_bundles() {
+        let base = SettingsBundle {
+            version: BUNDLE_VERSION,
+            themes: BTreeMap::from_iter([("old.json".into(), "{}".into())]),
+            extensions: BTreeSet::from_iter(["html".into()]),
+            ..Default::default()
+        };
+        let local = SettingsBundle {
+            version: BUNDLE_VERSION,
+            settings: Some("{ \"vim_mode\": true }".into()),
+            themes: base.themes.clone(),
+            extensions: BTreeSet::from_iter(["html".into(), "toml".into()]),
+            ..Default::default()
+        };
+        let remote = SettingsBundle {
+            version: BUNDLE_VERSION,
+            keymap: Some("[]".into()),
+            extensions: BTreeSet::from_iter(["zig".into()]),
+            ..Default::default()
+        };
+
+        let (merged, conflicts) = SettingsBundle::merge(&base, &local, &remote);
+        assert_eq!(
+            merged,
+            SettingsBundle {
+                version: BUNDLE_VERSION,
+                settings: local.settings.clone(),
+                keymap: remote.keymap.clone(),
+                extensions: BTreeSet::from_iter(["html".into(), "toml".into(), "zig".into()]),
+                ..Default::default()
+            }
+        );
+        assert_eq!(conflicts, Vec::new());
+    }
+}
@@ -0,0 +1,405 @@
+use serde_json::{Map, Value};
+
+/// A key that was changed differently on both sides of a merge. The local value is kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeConflict {
+    pub file: String,
+    pub key_path: String,
+}
+
+/// Three-way merges a JSON file given as text, returning the merged text, or `None` if the
+/// file should not exist.
+///
+/// When only one side changed, its text is taken verbatim, so comments and formatting
+/// survive. Otherwise the merged value is written into the local text, key by key.
+pub fn merge_json_text(
+    file: &str,
+    base: Option<&str>,
+    local: Option<&str>,
+    remote: Option<&str>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<String> {
+    if local == remote {
+        return local.map(str::to_string);
+    }
+
+    let parse = |text: Option<&str>| -> Option<Value> {
+        text.and_then(|text| serde_json_lenient::from_str(text).ok())
+    };
+    let (base_value, local_value, remote_value) = (parse(base), parse(local), parse(remote));
+    if base_value == local_value {
+        return remote.map(str::to_string);
+    }
+    if base_value == remote_value || local_value == remote_value {
+        return local.map(str::to_string);
+    }
+
+    let mut file_conflicts = Vec::new();
+    let merged = merge_values(
+        base_value.as_ref(),
+        local_value.as_ref(),
+        remote_value.as_ref(),
+        &mut Vec::new(),
+        &mut file_conflicts,
+    );
+    conflicts.extend(file_conflicts.into_iter().map(|key_path| MergeConflict {
+        file: file.to_string(),
+        key_path,
+    }));
+
+    let merged = merged?;
+    match (local, &local_value) {
+        (Some(local), Some(local_value @ Value::Object(_))) if merged.is_object() => {
+            let mut text = local.to_string();
+            let tab_size = settings::infer_json_indent_size(&text);
+            settings::update_value_in_json_text(
+                &mut text,
+                &mut Vec::new(),
+                tab_size,
+                local_value,
+                &merged,
+                &mut Vec::new(),
+            );
+            Some(text)
+        }
+        _ => serde_json::to_string_pretty(&merged).ok(),
+    }
+}
+
+/// Three-way merges a file that can't be merged structurally, such as a theme. When both
+/// sides changed, the local version is kept.
+pub fn merge_opaque_text(
+    file: &str,
+    base: Option<&str>,
+    local: Option<&str>,
+    remote: Option<&str>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<String> {
+    if local == remote || base == remote {
+        return local.map(str::to_string);
+    }
+    if base == local {
+        return remote.map(str::to_string);
+    }
+    conflicts.push(MergeConflict {
+        file: file.to_string(),
+        key_path: String::new(),
+    });
+    local.map(str::to_string)
+}
+
+/// Three-way merges two JSON values. Objects are merged key by key, and keymap-style arrays
+/// of sections are merged section by section. `None` means the value was removed.
+fn merge_values(
+    base: Option<&Value>,
+    local: Option<&Value>,
+    remote: Option<&Value>,
+    key_path: &mut Vec<String>,
+    conflicts: &mut Vec<String>,
+) -> Option<Value> {
+    if local == remote || base == remote {
+        return local.cloned();
+    }
+    if base == local {
+        return remote.cloned();
+    }
+
+    match (base, local, remote) {
+        (
+            None | Some(Value::Object(_)),
+            Some(Value::Object(local)),
+            Some(Value::Object(remote)),
+        ) => {
+            let empty = Map::new();
+            let base = base.and_then(Value::as_object).unwrap_or(&empty);
+            Some(Value::Object(merge_objects(
+                base, local, remote, key_path, conflicts,
+            )))
+        }
+        (None | Some(Value::Array(_)), Some(Value::Array(local)), Some(Value::Array(remote)))
+            if is_section_array(local) && is_section_array(remote) =>
+        {
+            let base = base
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if is_section_array(base) {
+                Some(Value::Array(merge_sections(
+                    base, local, remote, key_path, conflicts,
+                )))
+            } else {
+                conflicts.push(key_path.join("."));
+                Some(Value::Array(local.clone()))
+            }
+        }
+        _ => {
+            conflicts.push(key_path.join("."));
+            local.cloned()
+        }
+    }
+}
+
+fn merge_objects(
+    base: &Map<String, Value>,
+    local: &Map<String, Value>,
+    remote: &Map<String, Value>,
+    key_path: &mut Vec<String>,
+    conflicts: &mut Vec<String>,
+) -> Map<String, Value> {
+    let mut merged = Map::new();
+    let keys = local
+        .keys()
+        .chain(remote.keys().filter(|key| !local.contains_key(*key)))
+        .chain(
+            base.keys()
+                .filter(|key| !local.contains_key(*key) && !remote.contains_key(*key)),
+        );
+    for key in keys {
+        key_path.push(key.clone());
+        if let Some(value) = merge_values(
+            base.get(key),
+            local.get(key),
+            remote.get(key),
+            key_path,
+            conflicts,
+        ) {
+            merged.insert(key.clone(), value);
+        }
+        key_path.pop();
+    }
+    merged
+}
+
+/// Keymap files are arrays of sections, identified by their `context` and
+/// `use_key_equivalents` fields.
+fn is_section_array(values: &[Value]) -> bool {
+    let mut identities = values.iter().map(section_identity).collect::<Vec<_>>();
+    let len = identities.len();
+    identities.sort();
+    identities.dedup();
+    identities.len() == len && identities.iter().all(Option::is_some)
+}
+
+fn section_identity(value: &Value) -> Option<String> {
+    let section = value.as_object()?;
+    let context = match section.get("context") {
+        Some(Value::String(context)) => context.as_str(),
+        None => "",
+        _ => return None,
+    };
+    let use_key_equivalents = section
+        .get("use_key_equivalents")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    Some(format!("{context}\0{use_key_equivalents}"))
+}
+
+fn merge_sections(
+    base: &[Value],
+    local: &[Value],
+    remote: &[Value],
+    key_path: &mut Vec<String>,
+    conflicts: &mut Vec<String>,
+) -> Vec<Value> {
+    let find = |sections: &[Value], identity: &Option<String>| {
+        sections
+            .iter()
+            .find(|section| &section_identity(section) == identity)
+            .cloned()
+    };
+    let mut identities = local.iter().map(section_identity).collect::<Vec<_>>();
+    for section in remote.iter().chain(base) {
+        let identity = section_identity(section);
+        if !identities.contains(&identity) {
+            identities.push(identity);
+        }
+    }
+
+    let mut merged = Vec::new();
+    for identity in identities {
+        let context = identity
+            .as_deref()
+            .and_then(|identity| identity.split('\0').next())
+            .unwrap_or_default();
+        key_path.push(format!("[{context}]"));
+        let (base, local, remote) = (
+            find(base, &identity),
+            find(local, &identity),
+            find(remote, &identity),
+        );
+        if let Some(section) = merge_values(
+            base.as_ref(),
+            local.as_ref(),
+            remote.as_ref(),
+            key_path,
+            conflicts,
+        ) {
+            merged.push(section);
+        }
+        key_path.pop();
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_takes_the_only_changed_side_verbatim() {
+        let base = "{ \"theme\": \"One Dark\" }";
+        let local = "{\n  // My favorite\n  \"theme\": \"One Dark\"\n}";
+        let remote = "{ \"theme\": \"Ayu Dark\" }";
+        let mut conflicts = Vec::new();
+
+        assert_eq!(
+            merge_json_text(
+                "settings.json",
+                Some(base),
+                Some(local),
+                Some(remote),
+                &mut conflicts
+            )
+            .as_deref(),
+            Some(remote)
+        );
+        assert_eq!(
+            merge_json_text(
+                "settings.json",
+                Some(remote),
+                Some(local),
+                Some(remote),
+                &mut conflicts
+            )
+            .as_deref(),
+            Some(local)
+        );
+        assert_eq!(conflicts, Vec::new());
+    }
+
+    #[test]
+    fn test_merge_settings_key_by_key() {
+        let base = indoc! {r#"
+            {
+              "theme": "One Dark",
+              "tab_size": 4,
+              "vim_mode": false
+            }
+        "#};
+        let local = indoc! {r#"
+            {
+              // Keep this one
+              "theme": "One Light",
+              "tab_size": 4,
+              "vim_mode": true
+            }
+        "#};
+        let remote = indoc! {r#"
+            {
+              "theme": "Ayu Dark",
+              "tab_size": 2,
+              "vim_mode": false,
+              "buffer_font_size": 14
+            }
+        "#};
+        let mut conflicts = Vec::new();
+        let merged = merge_json_text(
+            "settings.json",
+            Some(base),
+            Some(local),
+            Some(remote),
+            &mut conflicts,
+        )
+        .unwrap();
+
+        assert!(merged.contains("// Keep this one"), "{merged}");
+        assert_eq!(
+            serde_json_lenient::from_str::<Value>(&merged).unwrap(),
+            json!({
+                "theme": "One Light",
+                "tab_size": 2,
+                "vim_mode": true,
+                "buffer_font_size": 14
+            })
+        );
+        assert_eq!(
+            conflicts,
+            vec![MergeConflict {
+                file: "settings.json".into(),
+                key_path: "theme".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merge_keymap_sections() {
+        let base = json!([
+            { "bindings": { "ctrl-a": "a" } },
+            { "context": "Editor", "bindings": { "ctrl-b": "b" } },
+        ]);
+        let local = json!([
+            { "bindings": { "ctrl-a": "a", "ctrl-l": "l" } },
+            { "context": "Editor", "bindings": { "ctrl-b": "b" } },
+        ]);
+        let remote = json!([
+            { "bindings": { "ctrl-a": "a", "ctrl-r": "r" } },
+            { "context": "Terminal", "bindings": { "ctrl-t": "t" } },
+        ]);
+        let mut conflicts = Vec::new();
+        let merged = merge_values(
+            Some(&base),
+            Some(&local),
+            Some(&remote),
+            &mut Vec::new(),
+            &mut conflicts,
+        );
+
+        assert_eq!(
+            merged,
+            Some(json!([
+                { "bindings": { "ctrl-a": "a", "ctrl-l": "l", "ctrl-r": "r" } },
+                { "context": "Terminal", "bindings": { "ctrl-t": "t" } },
+            ]))
+        );
+        assert_eq!(conflicts, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_merge_opaque_text() {
+        let mut conflicts = Vec::new();
+        assert_eq!(
+            merge_opaque_text("themes/a.json", None, None, Some("remote"), &mut conflicts),
+            Some("remote".into())
+        );
+        assert_eq!(
+            merge_opaque_text(
+                "themes/b.json",
+                Some("base"),
+                None,
+                Some("base"),
+                &mut conflicts
+            ),
+            None
+        );
+        assert_eq!(
+            merge_opaque_text(
+                "themes/c.json",
+                Some("base"),
+                Some("local"),
+                Some("remote"),
+                &mut conflicts
+            ),
+            Some("local".into())
+        );
+        assert_eq!(
+            conflicts,
+            vec![MergeConflict {
+                file: "themes/c.json".into(),
+                key_path: String::new(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use util::command::new_smol_command;
+
+/// A clone of the sync repository, managed by Zed. Its working tree is overwritten on every
+/// sync, so it must not be edited by hand.
+pub struct SyncRepository {
+    path: PathBuf,
+    url: String,
+    branch: String,
+}
+
+impl SyncRepository {
+    pub fn new(path: PathBuf, url: String, branch: String) -> Self {
+        Self { path, url, branch }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Clones the repository if needed, and resets it to the remote branch. Returns whether
+    /// the remote branch exists yet.
+    pub async fn fetch(&self) -> Result<bool> {
+        if !self.path.join(".git").is_dir() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let path = self.path.to_string_lossy().into_owned();
+            git(None, &["clone", "--no-checkout", "--", &self.url, &path])
+                .await
+                .with_context(|| format!("Failed to clone {}", self.url))?;
+        } else {
+            git(
+                Some(&self.path),
+                &["remote", "set-url", "origin", &self.url],
+            )
+            .await?;
+            git(Some(&self.path), &["fetch", "--prune", "origin"])
+                .await
+                .with_context(|| format!("Failed to fetch from {}", self.url))?;
+        }
+
+        let remote_branch = format!("origin/{}", self.branch);
+        let remote_branch_exists = git(
+            Some(&self.path),
+            &["rev-parse", "--verify", "--quiet", &remote_branch],
+        )
+        .await
+        .is_ok();
+        if remote_branch_exists {
+            git(
+                Some(&self.path),
+                &["checkout", "--force", "-B", &self.branch, &remote_branch],
+            )
+            .await?;
+        } else {
+            let branch_ref = format!("refs/heads/{}", self.branch);
+            git(Some(&self.path), &["symbolic-ref", "HEAD", &branch_ref]).await?;
+        }
+        Ok(remote_branch_exists)
+    }
+
+    /// Commits all changes in the working tree and pushes them. Does nothing if there are
+    /// no changes.
+    pub async fn commit_and_push(&self, message: &str) -> Result<()> {
+        git(Some(&self.path), &["add", "--all"]).await?;
+        let has_changes = git(Some(&self.path), &["diff", "--cached", "--quiet"])
+            .await
+            .is_err();
+        if !has_changes {
+            return Ok(());
+        }
+        git(Some(&self.path), &["commit", "--quiet", "-m", message]).await?;
+        let refspec = format!("HEAD:refs/heads/{}", self.branch);
+        git(Some(&self.path), &["push", "origin", &refspec])
+            .await
+            .with_context(|| format!("Failed to push to {}", self.url))?;
+        Ok(())
+    }
+}
+
+async fn git(working_directory: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut command = new_smol_command("git");
+    if let Some(working_directory) = working_directory {
+        command.current_dir(working_directory);
+    }
+    // Never block on a credential prompt; authentication has to be set up for the
+    // repository beforehand, e.g. through an SSH agent or a credential helper.
+    command.env("GIT_TERMINAL_PROMPT", "0").args(args);
+    let output = command
+        .output()
+        .await
+        .context("Failed to run git. Is it installed?")?;
+    anyhow::ensure!(
+        output.status.success(),
+        "git {} failed: {}",
+        args.first().copied().unwrap_or_default(),
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MergeConflict, SettingsBundle};
+    use fs::RealFs;
+    use gpui::TestAppContext;
+    use pretty_assertions::assert_eq;
+    use serde_json::{Value, json};
+
+    #[gpui::test]
+    async fn test_sync_repository_merges_changes_from_both_machines(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let fs = RealFs::new(None, cx.executor());
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = init_machines(dir.path()).await;
+
+        let base = settings_bundle(r#"{ "theme": "One Dark", "tab_size": 4 }"#);
+        assert!(!fetch(&first).await);
+        base.write_dir(&fs, first.path()).await.unwrap();
+        first.commit_and_push("Initial sync").await.unwrap();
+
+        assert!(fetch(&second).await);
+        let remote = SettingsBundle::read_dir(&fs, second.path()).await.unwrap();
+        assert_eq!(remote.settings, base.settings);
+        settings_bundle(r#"{ "theme": "Ayu Dark", "tab_size": 2 }"#)
+            .write_dir(&fs, second.path())
+            .await
+            .unwrap();
+        second.commit_and_push("Sync from second").await.unwrap();
+
+        let local = settings_bundle(r#"{ "theme": "One Light", "tab_size": 4 }"#);
+        assert!(fetch(&first).await);
+        let remote = SettingsBundle::read_dir(&fs, first.path()).await.unwrap();
+        let (merged, conflicts) = SettingsBundle::merge(&base, &local, &remote);
+        assert_eq!(
+            conflicts,
+            vec![MergeConflict {
+                file: "settings.json".into(),
+                key_path: "theme".into(),
+            }]
+        );
+        assert_eq!(
+            settings_value(&merged),
+            json!({ "theme": "One Light", "tab_size": 2 })
+        );
+        merged.write_dir(&fs, first.path()).await.unwrap();
+        first.commit_and_push("Sync from first").await.unwrap();
+
+        assert!(fetch(&second).await);
+        let synced = SettingsBundle::read_dir(&fs, second.path()).await.unwrap();
+        assert_eq!(synced.settings, merged.settings);
+    }
+
+    #[gpui::test]
+    async fn test_sync_repository_rejects_pushing_over_newer_changes(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let fs = RealFs::new(None, cx.executor());
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = init_machines(dir.path()).await;
+
+        fetch(&first).await;
+        settings_bundle(r#"{ "tab_size": 4 }"#)
+            .write_dir(&fs, first.path())
+            .await
+            .unwrap();
+        first.commit_and_push("Initial sync").await.unwrap();
+        assert!(fetch(&second).await);
+
+        let newer = settings_bundle(r#"{ "tab_size": 2 }"#);
+        newer.write_dir(&fs, second.path()).await.unwrap();
+        second.commit_and_push("Sync from second").await.unwrap();
+
+        // The first machine merged against a stale remote, so its push must not overwrite
+        // what the second machine pushed in the meantime.
+        settings_bundle(r#"{ "tab_size": 8 }"#)
+            .write_dir(&fs, first.path())
+            .await
+            .unwrap();
+        assert!(first.commit_and_push("Sync from first").await.is_err());
+
+        assert!(fetch(&first).await);
+        let remote = SettingsBundle::read_dir(&fs, first.path()).await.unwrap();
+        assert_eq!(remote.settings, newer.settings);
+    }
+
+    async fn init_machines(dir: &Path) -> (SyncRepository, SyncRepository) {
+        let remote = dir.join("remote.git");
+        let remote = remote.to_string_lossy().into_owned();
+        git(None, &["init", "--quiet", "--bare", &remote])
+            .await
+            .unwrap();
+        let machine =
+            |name: &str| SyncRepository::new(dir.join(name), remote.clone(), "main".into());
+        (machine("first"), machine("second"))
+    }
+
+    async fn fetch(repository: &SyncRepository) -> bool {
+        let remote_branch_exists = repository.fetch().await.unwrap();
+        for (key, value) in [("user.name", "Zed"), ("user.email", "zed@example.com")] {
+            git(Some(repository.path()), &["config", key, value])
+                .await
+                .unwrap();
+        }
+        remote_branch_exists
+    }
+
+    fn settings_bundle(settings: &str) -> SettingsBundle {
+        SettingsBundle {
+            settings: Some(settings.into()),
+            ..Default::default()
+        }
+    }
+
+    fn settings_value(bundle: &SettingsBundle) -> Value {
+        serde_json_lenient::from_str(bundle.settings.as_deref().unwrap()).unwrap()
+    }
+}
@@ -0,0 +1,333 @@
+//! Syncs the user's settings, keymap, snippets, themes and installed extensions across
+//! machines, either through an exported bundle file or through a git repository.
+//!
+//! Both sides are three-way merged against the configuration this machine last synced, so
+//! that changes made on either side are kept. When both sides changed the same setting, the
+//! local value wins and the conflict is reported.
+
+mod bundle;
+mod merge;
+mod repository;
+
+use std::{collections::BTreeSet, path::PathBuf};
+
+use anyhow::{Context as _, Result};
+use extension_host::ExtensionStore;
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, PathPromptOptions, Window, actions};
+use notifications::status_toast::{StatusToast, ToastIcon};
+use settings::{RegisterSetting, Settings};
+use ui::{Color, IconName, SharedString};
+use util::ResultExt as _;
+use workspace::{
+    Workspace,
+    notifications::{DetachAndPromptErr as _, NotificationId, simple_message_notification},
+};
+
+pub use bundle::SettingsBundle;
+pub use merge::MergeConflict;
+
+use crate::repository::SyncRepository;
+
+actions!(
+    settings_sync,
+    [
+        /// Exports settings, keymap, snippets, themes and the list of installed extensions
+        /// to a bundle file.
+        ExportBundle,
+        /// Merges a settings bundle exported on another machine into this one.
+        ImportBundle,
+        /// Syncs settings, keymap, snippets, themes and extensions with the git repository
+        /// configured in `settings_sync.repository`.
+        SyncWithRepository
+    ]
+);
+
+const BUNDLE_FILE_NAME: &str = "zed-settings.json";
+
+#[derive(Clone, Debug, RegisterSetting)]
+pub struct SettingsSyncSettings {
+    /// The URL of the git repository to sync with.
+    pub repository: Option<String>,
+    /// The branch of the repository to sync with.
+    pub branch: String,
+    /// Whether to install synced extensions that are missing on this machine.
+    pub install_extensions: bool,
+}
+
+impl Settings for SettingsSyncSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let settings_sync = content.settings_sync.clone().unwrap();
+        Self {
+            repository: settings_sync
+                .repository
+                .filter(|repository| !repository.trim().is_empty()),
+            branch: settings_sync.branch.unwrap(),
+            install_extensions: settings_sync.install_extensions.unwrap(),
+        }
+    }
+}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(|workspace, _: &ExportBundle, window, cx| {
+                export_bundle(workspace, window, cx);
+            })
+            .register_action(|workspace, _: &ImportBundle, window, cx| {
+                import_bundle(workspace, window, cx);
+            })
+            .register_action(|workspace, _: &SyncWithRepository, window, cx| {
+                sync_with_repository(workspace, window, cx);
+            });
+    })
+    .detach();
+}
+
+fn export_bundle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let fs = workspace.app_state().fs.clone();
+    let extensions = installed_extensions(cx);
+    let save_path = cx.prompt_for_new_path(paths::home_dir(), Some(BUNDLE_FILE_NAME));
+    cx.spawn_in(window, async move |workspace, cx| {
+        let Some(path) = save_path.await?? else {
+            return Ok(());
+        };
+        let bundle = SettingsBundle::collect(fs.as_ref(), extensions).await?;
+        fs.atomic_write(path.clone(), bundle.to_json()?)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        // What was exported is what the other machines will merge from, so it is the common
+        // ancestor for the next import here.
+        save_base(fs.as_ref(), &bundle).await?;
+        workspace.update(cx, |workspace, cx| {
+            show_success_toast(
+                workspace,
+                format!("Exported your settings to {}", path.display()),
+                cx,
+            );
+        })
+    })
+    .detach_and_prompt_err("Failed to export settings", window, cx, |error, _, _| {
+        Some(format!("{error:#}"))
+    });
+}
+
+fn import_bundle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let fs = workspace.app_state().fs.clone();
+    let extensions = installed_extensions(cx);
+    let open_paths = cx.prompt_for_paths(PathPromptOptions {
+        files: true,
+        directories: false,
+        multiple: false,
+        prompt: Some("Import Settings".into()),
+    });
+    cx.spawn_in(window, async move |workspace, cx| {
+        let Some(path) = open_paths
+            .await??
+            .and_then(|paths| paths.into_iter().next())
+        else {
+            return Ok(());
+        };
+        let json = fs
+            .load(&path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let remote = SettingsBundle::from_json(&json)?;
+        let (merged, conflicts) = merge_into_local(fs.as_ref(), Some(remote), extensions).await?;
+        save_base(fs.as_ref(), &merged).await?;
+        workspace.update(cx, |workspace, cx| {
+            finish_sync(
+                workspace,
+                &merged,
+                conflicts,
+                format!("Imported your settings from {}", path.display()),
+                cx,
+            );
+        })
+    })
+    .detach_and_prompt_err("Failed to import settings", window, cx, |error, _, _| {
+        Some(format!("{error:#}"))
+    });
+}
+
+fn sync_with_repository(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let fs = workspace.app_state().fs.clone();
+    let extensions = installed_extensions(cx);
+    let settings = SettingsSyncSettings::get_global(cx).clone();
+    cx.spawn_in(window, async move |workspace, cx| {
+        let url = settings.repository.context(
+            "No sync repository is configured. Set `settings_sync.repository` in your settings.",
+        )?;
+        let repository = SyncRepository::new(
+            paths::settings_sync_dir().join("repository"),
+            url.clone(),
+            settings.branch,
+        );
+        let (merged, conflicts) = cx
+            .background_spawn(async move {
+                let remote_exists = repository.fetch().await?;
+                let remote = if remote_exists {
+                    Some(SettingsBundle::read_dir(fs.as_ref(), repository.path()).await?)
+                } else {
+                    None
+                };
+                let (merged, conflicts) = merge_into_local(fs.as_ref(), remote, extensions).await?;
+                merged.write_dir(fs.as_ref(), repository.path()).await?;
+                repository.commit_and_push("Sync Zed settings").await?;
+                // Only once the other machines can see the merged configuration is it the
+                // common ancestor for the next sync.
+                save_base(fs.as_ref(), &merged).await?;
+                anyhow::Ok((merged, conflicts))
+            })
+            .await?;
+        workspace.update(cx, |workspace, cx| {
+            finish_sync(
+                workspace,
+                &merged,
+                conflicts,
+                format!("Synced your settings with {url}"),
+                cx,
+            );
+        })
+    })
+    .detach_and_prompt_err("Failed to sync settings", window, cx, |error, _, _| {
+        Some(format!("{error:#}"))
+    });
+}
+
+/// Merges the remote configuration into this machine's. Without a remote configuration,
+/// the local one is taken as is.
+///
+/// The caller records the result as the common ancestor for the next sync once it has been
+/// shared, see [`save_base`].
+async fn merge_into_local(
+    fs: &dyn Fs,
+    remote: Option<SettingsBundle>,
+    extensions: BTreeSet<String>,
+) -> Result<(SettingsBundle, Vec<MergeConflict>)> {
+    let local = SettingsBundle::collect(fs, extensions).await?;
+    let (merged, conflicts) = match remote {
+        Some(remote) => {
+            let base = load_base(fs).await;
+            SettingsBundle::merge(&base, &local, &remote)
+        }
+        None => (local, Vec::new()),
+    };
+    merged.apply(fs).await?;
+    Ok((merged, conflicts))
+}
+
+fn base_path() -> PathBuf {
+    paths::settings_sync_dir().join("base.json")
+}
+
+async fn load_base(fs: &dyn Fs) -> SettingsBundle {
+    let path = base_path();
+    if !fs.is_file(&path).await {
+        return SettingsBundle::default();
+    }
+    fs.load(&path)
+        .await
+        .and_then(|json| SettingsBundle::from_json(&json))
+        .context("Failed to load the last synced settings")
+        .log_err()
+        .unwrap_or_default()
+}
+
+async fn save_base(fs: &dyn Fs, bundle: &SettingsBundle) -> Result<()> {
+    fs.create_dir(paths::settings_sync_dir()).await?;
+    fs.atomic_write(base_path(), bundle.to_json()?)
+        .await
+        .context("Failed to save the synced settings")
+}
+
+fn installed_extensions(cx: &App) -> BTreeSet<String> {
+    let Some(extension_store) = ExtensionStore::try_global(cx) else {
+        return BTreeSet::default();
+    };
+    extension_store
+        .read(cx)
+        .installed_extensions()
+        .iter()
+        .filter(|(_, entry)| !entry.dev)
+        .map(|(id, _)| id.to_string())
+        .collect()
+}
+
+fn install_missing_extensions(extensions: &BTreeSet<String>, cx: &mut App) {
+    let Some(extension_store) = ExtensionStore::try_global(cx) else {
+        return;
+    };
+    extension_store.update(cx, |store, cx| {
+        let missing = extensions
+            .iter()
+            .filter(|id| {
+                !store.installed_extensions().contains_key(id.as_str())
+                    && !store.outstanding_operations().contains_key(id.as_str())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in missing {
+            store.install_latest_extension(id.into(), cx);
+        }
+    });
+}
+
+fn finish_sync(
+    workspace: &mut Workspace,
+    merged: &SettingsBundle,
+    conflicts: Vec<MergeConflict>,
+    message: String,
+    cx: &mut Context<Workspace>,
+) {
+    if SettingsSyncSettings::get_global(cx).install_extensions {
+        install_missing_extensions(&merged.extensions, cx);
+    }
+
+    if conflicts.is_empty() {
+        show_success_toast(workspace, message, cx);
+        return;
+    }
+
+    let mut details = format!(
+        "{message}, but {} changed on both sides. Your local version was kept for:",
+        if conflicts.len() == 1 {
+            "1 setting".to_string()
+        } else {
+            format!("{} settings", conflicts.len())
+        }
+    );
+    for conflict in &conflicts {
+        if conflict.key_path.is_empty() {
+            details.push_str(&format!("\n• {}", conflict.file));
+        } else {
+            details.push_str(&format!("\n• {}: {}", conflict.file, conflict.key_path));
+        }
+    }
+
+    struct SettingsSyncConflicts;
+    workspace.show_notification(
+        NotificationId::unique::<SettingsSyncConflicts>(),
+        cx,
+        |cx| {
+            cx.new(|cx| {
+                simple_message_notification::MessageNotification::new(
+                    SharedString::from(details),
+                    cx,
+                )
+            })
+        },
+    );
+}
+
+fn show_success_toast(workspace: &mut Workspace, message: String, cx: &mut Context<Workspace>) {
+    let toast = StatusToast::new(message, cx, |this, _| {
+        this.icon(ToastIcon::new(IconName::Check).color(Color::Success))
+            .dismiss_button(true)
+    });
+    workspace.toggle_status_toast(toast, cx);
+}
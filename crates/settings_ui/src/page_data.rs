@@ -7698,7 +7698,7 @@ fn language_settings_data() -> Box<[SettingsPageItem]> {
         ]
     }
 
-    fn global_only_miscellaneous_sub_section() -> [SettingsPageItem; 3] {
+    fn global_only_miscellaneous_sub_section() -> [SettingsPageItem; 6] {
         [
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Image Viewer",
@@ -7718,6 +7718,66 @@ fn language_settings_data() -> Box<[SettingsPageItem]> {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Large File Viewer",
+                description: "Whether to open files that are too large for the editor in a memory-mapped viewer.",
+                field: Box::new(SettingField {
+                    json_path: Some("large_file_viewer.enabled"),
+                    pick: |settings_content| {
+                        settings_content
+                            .large_file_viewer
+                            .as_ref()
+                            .and_then(|large_file_viewer| large_file_viewer.enabled.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .large_file_viewer
+                            .get_or_insert_default()
+                            .enabled = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Large File Threshold",
+                description: "The size in megabytes from which files open in the large file viewer.",
+                field: Box::new(SettingField {
+                    json_path: Some("large_file_viewer.threshold_mb"),
+                    pick: |settings_content| {
+                        settings_content
+                            .large_file_viewer
+                            .as_ref()
+                            .and_then(|large_file_viewer| large_file_viewer.threshold_mb.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .large_file_viewer
+                            .get_or_insert_default()
+                            .threshold_mb = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "MCP Server",
+                description: "Whether external agents and CLIs can connect to Zed over the Model Context Protocol by running `zed --mcp`.",
+                field: Box::new(SettingField {
+                    json_path: Some("mcp_server.enabled"),
+                    pick: |settings_content| {
+                        settings_content
+                            .mcp_server
+                            .as_ref()
+                            .and_then(|mcp_server| mcp_server.enabled.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content.mcp_server.get_or_insert_default().enabled = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Auto Replace Emoji Shortcode",
                 description: "Whether to automatically replace emoji shortcodes with emoji characters.",
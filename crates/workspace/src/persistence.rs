@@ -24,7 +24,8 @@ use project::{
 
 use language::{LanguageName, Toolchain, ToolchainScope};
 use remote::{
-    DockerConnectionOptions, RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions,
+    ContainerConnectionOptions, DockerConnectionOptions, RemoteConnectionOptions,
+    SshConnectionOptions, WslConnectionOptions,
 };
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -881,6 +882,9 @@ impl Domain for WorkspaceDb {
             DROP TABLE user_toolchains;
             ALTER TABLE user_toolchains2 RENAME TO user_toolchains;
         ),
+        sql!(
+            ALTER TABLE remote_connections ADD COLUMN container_runtime TEXT;
+        ),
     ];
 
     // Allow recovering from bad migration that was initially shipped to nightly
@@ -1308,6 +1312,7 @@ impl WorkspaceDb {
         let mut distro = None;
         let mut name = None;
         let mut container_id = None;
+        let mut container_runtime = None;
         match options {
             RemoteConnectionOptions::Ssh(options) => {
                 kind = RemoteConnectionKind::Ssh;
@@ -1325,6 +1330,12 @@ impl WorkspaceDb {
                 container_id = Some(options.container_id);
                 name = Some(options.name);
             }
+            RemoteConnectionOptions::Container(options) => {
+                kind = RemoteConnectionKind::Container;
+                container_id = Some(options.target);
+                name = Some(options.name);
+                container_runtime = Some(serde_json::to_string(&options.runtime)?);
+            }
             RemoteConnectionOptions::Mock(options) => {
                 kind = RemoteConnectionKind::Ssh;
                 host = Some(format!("mock-{}", options.id));
@@ -1339,6 +1350,7 @@ impl WorkspaceDb {
             distro,
             name,
             container_id,
+            container_runtime,
         )
     }
 
@@ -1351,6 +1363,7 @@ impl WorkspaceDb {
         distro: Option<String>,
         name: Option<String>,
         container_id: Option<String>,
+        container_runtime: Option<String>,
     ) -> Result<RemoteConnectionId> {
         if let Some(id) = this.select_row_bound(sql!(
             SELECT id
@@ -1362,7 +1375,8 @@ impl WorkspaceDb {
                 user IS ? AND
                 distro IS ? AND
                 name IS ? AND
-                container_id IS ?
+                container_id IS ? AND
+                container_runtime IS ?
             LIMIT 1
         ))?((
             kind.serialize(),
@@ -1372,6 +1386,7 @@ impl WorkspaceDb {
             distro.clone(),
             name.clone(),
             container_id.clone(),
+            container_runtime.clone(),
         ))? {
             Ok(RemoteConnectionId(id))
         } else {
@@ -1383,8 +1398,9 @@ impl WorkspaceDb {
                     user,
                     distro,
                     name,
-                    container_id
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                    container_id,
+                    container_runtime
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
                 RETURNING id
             ))?((
                 kind.serialize(),
@@ -1394,6 +1410,7 @@ impl WorkspaceDb {
                 distro,
                 name,
                 container_id,
+                container_runtime,
             ))?
             .context("failed to insert remote project")?;
             Ok(RemoteConnectionId(id))
@@ -1477,25 +1494,28 @@ impl WorkspaceDb {
     fn remote_connections(&self) -> Result<HashMap<RemoteConnectionId, RemoteConnectionOptions>> {
         Ok(self.select(sql!(
             SELECT
-                id, kind, host, port, user, distro, container_id, name
+                id, kind, host, port, user, distro, container_id, name, container_runtime
             FROM
                 remote_connections
         ))?()?
         .into_iter()
-        .filter_map(|(id, kind, host, port, user, distro, container_id, name)| {
-            Some((
-                RemoteConnectionId(id),
-                Self::remote_connection_from_row(
-                    kind,
-                    host,
-                    port,
-                    user,
-                    distro,
-                    container_id,
-                    name,
-                )?,
-            ))
-        })
+        .filter_map(
+            |(id, kind, host, port, user, distro, container_id, name, container_runtime)| {
+                Some((
+                    RemoteConnectionId(id),
+                    Self::remote_connection_from_row(
+                        kind,
+                        host,
+                        port,
+                        user,
+                        distro,
+                        container_id,
+                        name,
+                        container_runtime,
+                    )?,
+                ))
+            },
+        )
         .collect())
     }
 
@@ -1503,14 +1523,24 @@ impl WorkspaceDb {
         &self,
         id: RemoteConnectionId,
     ) -> Result<RemoteConnectionOptions> {
-        let (kind, host, port, user, distro, container_id, name) = self.select_row_bound(sql!(
-            SELECT kind, host, port, user, distro, container_id, name
-            FROM remote_connections
-            WHERE id = ?
-        ))?(id.0)?
-        .context("no such remote connection")?;
-        Self::remote_connection_from_row(kind, host, port, user, distro, container_id, name)
-            .context("invalid remote_connection row")
+        let (kind, host, port, user, distro, container_id, name, container_runtime) =
+            self.select_row_bound(sql!(
+                SELECT kind, host, port, user, distro, container_id, name, container_runtime
+                FROM remote_connections
+                WHERE id = ?
+            ))?(id.0)?
+            .context("no such remote connection")?;
+        Self::remote_connection_from_row(
+            kind,
+            host,
+            port,
+            user,
+            distro,
+            container_id,
+            name,
+            container_runtime,
+        )
+        .context("invalid remote_connection row")
     }
 
     fn remote_connection_from_row(
@@ -1521,6 +1551,7 @@ impl WorkspaceDb {
         distro: Option<String>,
         container_id: Option<String>,
         name: Option<String>,
+        container_runtime: Option<String>,
     ) -> Option<RemoteConnectionOptions> {
         match RemoteConnectionKind::deserialize(&kind)? {
             RemoteConnectionKind::Wsl => Some(RemoteConnectionOptions::Wsl(WslConnectionOptions {
@@ -1540,6 +1571,14 @@ impl WorkspaceDb {
                     upload_binary_over_docker_exec: false,
                 }))
             }
+            RemoteConnectionKind::Container => Some(RemoteConnectionOptions::Container(
+                ContainerConnectionOptions {
+                    target: container_id?,
+                    name: name?,
+                    runtime: serde_json::from_str(&container_runtime?).ok()?,
+                    upload_binary_over_exec: false,
+                },
+            )),
         }
     }
 
@@ -4284,6 +4284,7 @@ impl BackgroundScanner {
         !mem::take(&mut self.state.lock().await.paths_to_scan).is_empty()
     }
 
+    #[ztracing::instrument(name = "scan_worktree", skip_all)]
     async fn scan_dirs(
         &self,
         enable_progress_updates: bool,
@@ -255,7 +255,18 @@ fn main() {
             zlog::init_output_stdout();
         };
     }
-    ztracing::init();
+    if env::var_os("ZED_LOG_JSON").is_some()
+        && let Err(err) =
+            zlog::init_output_json_file(paths::json_log_file(), Some(paths::old_json_log_file()))
+    {
+        eprintln!("Could not open JSON log file: {}", err);
+    }
+    zlog::init_output_memory();
+    ztracing::init(
+        env::var_os("ZED_EXPORT_TRACES")
+            .is_some()
+            .then(|| paths::traces_file().as_path()),
+    );
 
     let version = option_env!("ZED_BUILD_ID");
     let app_commit_sha =
@@ -604,7 +615,11 @@ fn main() {
         load_embedded_fonts(cx);
 
         editor::init(cx);
+        // Openers registered later take precedence, so dedicated viewers for images and
+        // notebooks must come after the large file viewer to keep handling big files.
+        large_file_viewer::init(cx);
         image_viewer::init(cx);
+        hex_editor::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
@@ -619,6 +634,8 @@ fn main() {
         project_panel::init(cx);
         outline_panel::init(cx);
         tasks_ui::init(cx);
+        mcp_server::init(cx);
+        context_server_ui::init(cx);
         snippets_ui::init(cx);
         search::init(cx);
         terminal_view::init(cx);
@@ -637,6 +654,7 @@ fn main() {
         svg_preview::init(cx);
         onboarding::init(cx);
         settings_ui::init(cx);
+        settings_sync::init(cx);
         keymap_editor::init(cx);
         extensions_ui::init(cx);
         edit_prediction::init(cx);
@@ -7,6 +7,7 @@ mod open_listener;
 mod open_url_modal;
 mod quick_action_bar;
 pub mod remote_debug;
+mod setting_provenance;
 pub mod telemetry_log;
 #[cfg(all(target_os = "macos", any(test, feature = "test-support")))]
 pub mod visual_tests;
@@ -96,6 +97,9 @@ actions!(
         DebugElements,
         /// Hides the application window.
         Hide,
+        /// Shows which settings files set a setting for the active file, and which one
+        /// takes effect.
+        InspectSetting,
         /// Hides all other application windows.
         HideOthers,
         /// Minimizes the current window.
@@ -621,6 +625,7 @@ fn initialize_panels(window: &mut Window, cx: &mut Context<Workspace>) {
         let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
         let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
         let git_panel = GitPanel::load(workspace_handle.clone(), cx.clone());
+        let ports_panel = recent_projects::PortsPanel::load(workspace_handle.clone(), cx.clone());
         let debug_panel = DebugPanel::load(workspace_handle.clone(), cx);
 
         async fn add_panel_when_ready(
@@ -644,6 +649,7 @@ fn initialize_panels(window: &mut Window, cx: &mut Context<Workspace>) {
             add_panel_when_ready(terminal_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(git_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(debug_panel, workspace_handle.clone(), cx.clone()),
+            add_panel_when_ready(ports_panel, workspace_handle.clone(), cx.clone()),
         );
 
         anyhow::Ok(())
@@ -674,6 +680,9 @@ fn register_actions(
                 ..Default::default()
             })
         })
+        .register_action(|workspace, _: &InspectSetting, window, cx| {
+            setting_provenance::toggle(workspace, window, cx);
+        })
         .register_action(|workspace, _: &OpenUrlPrompt, window, cx| {
             workspace.toggle_modal(window, cx, |window, cx| {
                 open_url_modal::OpenUrlModal::new(window, cx)
@@ -1789,6 +1798,19 @@ fn open_bundled_file(
     window: &mut Window,
     cx: &mut Context<Workspace>,
 ) {
+    open_bundled_file_in_editor(workspace, text, title, language, window, cx)
+        .detach_and_log_err(cx);
+}
+
+/// Opens bundled text, such as the default settings, in a read-only editor.
+fn open_bundled_file_in_editor(
+    workspace: &Workspace,
+    text: Cow<'static, str>,
+    title: &'static str,
+    language: &'static str,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<anyhow::Result<Entity<Editor>>> {
     let language = workspace.app_state().languages.language_for_name(language);
     cx.spawn_in(window, async move |workspace, cx| {
         let language = language.await.log_err();
@@ -1806,25 +1828,26 @@ fn open_bundled_file(
                     });
                     let buffer =
                         cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title.into()));
+                    let editor = cx.new(|cx| {
+                        let mut editor =
+                            Editor::for_multibuffer(buffer, Some(project.clone()), window, cx);
+                        editor.set_read_only(true);
+                        editor.set_should_serialize(false, cx);
+                        editor.set_breadcrumb_header(title.into());
+                        editor
+                    });
                     workspace.add_item_to_active_pane(
-                        Box::new(cx.new(|cx| {
-                            let mut editor =
-                                Editor::for_multibuffer(buffer, Some(project.clone()), window, cx);
-                            editor.set_read_only(true);
-                            editor.set_should_serialize(false, cx);
-                            editor.set_breadcrumb_header(title.into());
-                            editor
-                        })),
+                        Box::new(editor.clone()),
                         None,
                         true,
                         window,
                         cx,
                     );
+                    editor
                 })
             })?
             .await
     })
-    .detach_and_log_err(cx);
 }
 
 fn open_settings_file(
@@ -4633,7 +4656,9 @@ mod tests {
                 copilot::copilot_chat::CopilotChatConfiguration::default(),
                 cx,
             );
+            large_file_viewer::init(cx);
             image_viewer::init(cx);
+            hex_editor::init(cx);
             repl::init(app_state.fs.clone(), cx);
             repl::notebook::init(cx);
             tasks_ui::init(cx);
@@ -149,7 +149,7 @@ fn assign_edit_prediction_provider(
         EditPredictionProvider::None => {
             editor.set_edit_prediction_provider::<ZedEditPredictionDelegate>(None, window, cx);
         }
-        EditPredictionProvider::Zed => {
+        EditPredictionProvider::Zed | EditPredictionProvider::Local => {
             if let Some(project) = editor.project()
                 && let Some(buffer) = &singleton_buffer
                 && buffer.read(cx).file().is_some()
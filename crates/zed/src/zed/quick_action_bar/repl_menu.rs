@@ -172,6 +172,20 @@ impl QuickActionBar {
                             }
                         },
                     )
+                    .custom_entry(
+                        move |_window, _cx| {
+                            Label::new("Show Variables")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .into_any_element()
+                        },
+                        {
+                            let editor = editor.clone();
+                            move |window, cx| {
+                                repl::show_variables(editor.clone(), window, cx);
+                            }
+                        },
+                    )
                     .separator()
                     .custom_entry(
                         move |_window, _cx| {
@@ -0,0 +1,509 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use editor::Editor;
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, Task,
+    WeakEntity, Window,
+};
+use language::Point;
+use paths::{EDITORCONFIG_NAME, local_settings_file_relative_path};
+use picker::{Picker, PickerDelegate};
+use project::ProjectPath;
+use serde_json::Value;
+use settings::{
+    SettingContribution, SettingProvenance, SettingSource, SettingsLocation, SettingsStore,
+    WorktreeId, key_path_range_in_json_text,
+};
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, Modal, ModalHeader, Section, prelude::*};
+use util::{ResultExt as _, rel_path::RelPath};
+use workspace::{ModalView, OpenOptions, OpenVisible, Workspace, item::ItemHandle};
+
+/// Top-level settings whose keys are user-defined names rather than setting names.
+const KEYED_BY_NAME: &[&str] = &["languages", "lsp", "profiles", "file_types"];
+
+/// The file the inspected setting applies to: the one in the active editor.
+#[derive(Clone)]
+struct InspectedFile {
+    worktree_id: WorktreeId,
+    path: Arc<RelPath>,
+    language: Option<SharedString>,
+}
+
+pub fn toggle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let file = workspace.active_item_as::<Editor>(cx).and_then(|editor| {
+        let (_, buffer, _) = editor.read(cx).active_excerpt(cx)?;
+        let buffer = buffer.read(cx);
+        let file = buffer.file()?;
+        Some(InspectedFile {
+            worktree_id: file.worktree_id(cx),
+            path: file.path().clone(),
+            language: buffer.language().map(|language| language.name().0),
+        })
+    });
+    let keys = setting_keys(cx);
+    let workspace_handle = cx.entity().downgrade();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        let delegate = SettingKeyPickerDelegate {
+            picker: cx.entity().downgrade(),
+            workspace: workspace_handle,
+            file,
+            keys,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        SettingKeyPicker::new(delegate, window, cx)
+    });
+}
+
+/// The dotted paths of all settings, taken from the default settings.
+fn setting_keys(cx: &App) -> Vec<String> {
+    fn collect(value: &Value, prefix: &mut Vec<String>, keys: &mut Vec<String>) {
+        match value {
+            Value::Object(object) if !object.is_empty() => {
+                for (key, value) in object {
+                    if prefix.is_empty() && KEYED_BY_NAME.contains(&key.as_str()) {
+                        continue;
+                    }
+                    prefix.push(key.clone());
+                    collect(value, prefix, keys);
+                    prefix.pop();
+                }
+            }
+            _ => keys.push(prefix.join(".")),
+        }
+    }
+
+    let mut keys = Vec::new();
+    if let Ok(defaults) = serde_json::to_value(SettingsStore::global(cx).raw_default_settings()) {
+        collect(&defaults, &mut Vec::new(), &mut keys);
+    }
+    keys.sort();
+    keys
+}
+
+pub struct SettingKeyPicker {
+    picker: Entity<Picker<SettingKeyPickerDelegate>>,
+}
+
+impl SettingKeyPicker {
+    fn new(
+        delegate: SettingKeyPickerDelegate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl ModalView for SettingKeyPicker {}
+
+impl EventEmitter<DismissEvent> for SettingKeyPicker {}
+
+impl Focusable for SettingKeyPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SettingKeyPicker {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct SettingKeyPickerDelegate {
+    picker: WeakEntity<SettingKeyPicker>,
+    workspace: WeakEntity<Workspace>,
+    file: Option<InspectedFile>,
+    keys: Vec<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for SettingKeyPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut Window, _: &mut App) -> Arc<str> {
+        "Inspect a setting...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, _: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .keys
+            .iter()
+            .enumerate()
+            .map(|(id, key)| StringMatchCandidate::new(id, key))
+            .collect::<Vec<_>>();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(key) = self
+            .matches
+            .get(self.selected_index)
+            .map(|mat| mat.string.clone())
+        else {
+            return;
+        };
+        let file = self.file.clone();
+        let workspace = self.workspace.clone();
+        window.defer(cx, move |window, cx| {
+            workspace
+                .update(cx, |workspace, cx| {
+                    let workspace_handle = cx.entity().downgrade();
+                    workspace.toggle_modal(window, cx, |_, cx| {
+                        SettingProvenanceModal::new(key, file, workspace_handle, cx)
+                    });
+                })
+                .log_err();
+        });
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.picker.update(cx, |_, cx| cx.emit(DismissEvent)).ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
+
+/// Lists every settings file that sets a setting, with the one that takes effect first.
+pub struct SettingProvenanceModal {
+    key: String,
+    file: Option<InspectedFile>,
+    provenance: SettingProvenance,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+}
+
+impl SettingProvenanceModal {
+    fn new(
+        key: String,
+        file: Option<InspectedFile>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let location = file.as_ref().map(|file| SettingsLocation {
+            worktree_id: file.worktree_id,
+            path: &file.path,
+        });
+        let language = file.as_ref().and_then(|file| file.language.as_deref());
+        let provenance = SettingsStore::global(cx).setting_provenance(&key, location, language, cx);
+        Self {
+            key,
+            file,
+            provenance,
+            workspace,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn open_contribution(
+        &mut self,
+        contribution: SettingContribution,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let open_editor = workspace.update(cx, |workspace, cx| match &contribution.source {
+            SettingSource::Default => super::open_bundled_file_in_editor(
+                workspace,
+                settings::default_settings(),
+                "Default Settings",
+                "JSON",
+                window,
+                cx,
+            ),
+            SettingSource::User => open_as_editor(
+                workspace.open_abs_path(
+                    paths::settings_file().clone(),
+                    OpenOptions {
+                        visible: Some(OpenVisible::None),
+                        ..Default::default()
+                    },
+                    window,
+                    cx,
+                ),
+                cx,
+            ),
+            SettingSource::Project(worktree_id, directory) => open_as_editor(
+                workspace.open_path(
+                    ProjectPath {
+                        worktree_id: *worktree_id,
+                        path: directory.join(local_settings_file_relative_path()),
+                    },
+                    None,
+                    true,
+                    window,
+                    cx,
+                ),
+                cx,
+            ),
+            SettingSource::Editorconfig(worktree_id, directory) => open_as_editor(
+                workspace.open_path(
+                    ProjectPath {
+                        worktree_id: *worktree_id,
+                        path: directory.join(RelPath::unix(EDITORCONFIG_NAME).unwrap()),
+                    },
+                    None,
+                    true,
+                    window,
+                    cx,
+                ),
+                cx,
+            ),
+            SettingSource::Extensions | SettingSource::Global | SettingSource::Server => {
+                Task::ready(Err(anyhow::anyhow!("This settings file can't be opened")))
+            }
+        });
+
+        cx.spawn_in(window, async move |_, cx| {
+            let editor = open_editor.await?;
+            editor.update_in(cx, |editor, window, cx| {
+                let row = contribution.line.or_else(|| {
+                    let text = editor.text(cx);
+                    let range = key_path_range_in_json_text(&text, &contribution.key_path)?;
+                    Some(text[..range.start].matches('\n').count() as u32)
+                });
+                if let Some(row) = row {
+                    editor.go_to_singleton_buffer_point(Point::new(row, 0), window, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn render_contribution(
+        &self,
+        ix: usize,
+        contribution: &SettingContribution,
+        is_effective: bool,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let is_openable = !matches!(
+            contribution.source,
+            SettingSource::Extensions | SettingSource::Global | SettingSource::Server
+        );
+        let value = serde_json::to_string(&contribution.value).unwrap_or_default();
+        let key_path = match contribution.source {
+            SettingSource::Editorconfig(..) => contribution.key_path.join(" "),
+            _ => contribution.key_path.join("."),
+        };
+        let contribution = contribution.clone();
+
+        ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .disabled(!is_openable)
+            .child(
+                v_flex()
+                    .w_full()
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_2()
+                            .justify_between()
+                            .child(Label::new(source_label(&contribution.source)))
+                            .child(
+                                Label::new(if is_effective {
+                                    "In effect"
+                                } else {
+                                    "Overridden"
+                                })
+                                .size(LabelSize::Small)
+                                .color(if is_effective {
+                                    Color::Success
+                                } else {
+                                    Color::Muted
+                                }),
+                            ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Label::new(key_path)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .buffer_font(cx),
+                            )
+                            .child(
+                                Label::new(value)
+                                    .size(LabelSize::Small)
+                                    .buffer_font(cx)
+                                    .when(!is_effective, |label| label.strikethrough()),
+                            ),
+                    ),
+            )
+            .when(is_openable, |item| {
+                item.on_click(cx.listener(move |this, _, window, cx| {
+                    this.open_contribution(contribution.clone(), window, cx);
+                }))
+            })
+    }
+}
+
+fn open_as_editor(
+    open_item: Task<anyhow::Result<Box<dyn ItemHandle>>>,
+    cx: &mut Context<Workspace>,
+) -> Task<anyhow::Result<Entity<Editor>>> {
+    cx.spawn(async move |_, _| {
+        open_item
+            .await?
+            .downcast::<Editor>()
+            .context("Settings file didn't open in an editor")
+    })
+}
+
+fn source_label(source: &SettingSource) -> String {
+    match source {
+        SettingSource::Default => "Default settings".into(),
+        SettingSource::Extensions => "Extensions".into(),
+        SettingSource::Global => "Global settings".into(),
+        SettingSource::User => "User settings".into(),
+        SettingSource::Server => "Remote server settings".into(),
+        SettingSource::Project(_, directory) => directory
+            .join(local_settings_file_relative_path())
+            .as_unix_str()
+            .to_string(),
+        SettingSource::Editorconfig(_, directory) => directory
+            .join(RelPath::unix(EDITORCONFIG_NAME).unwrap())
+            .as_unix_str()
+            .to_string(),
+    }
+}
+
+impl EventEmitter<DismissEvent> for SettingProvenanceModal {}
+
+impl Focusable for SettingProvenanceModal {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl ModalView for SettingProvenanceModal {}
+
+impl Render for SettingProvenanceModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let subject = match &self.file {
+            Some(file) => match &file.language {
+                Some(language) => format!("For {} ({language})", file.path.as_unix_str()),
+                None => format!("For {}", file.path.as_unix_str()),
+            },
+            None => "No file is open, so project settings are not included.".to_string(),
+        };
+        let contributions = &self.provenance.contributions;
+
+        div()
+            .track_focus(&self.focus_handle)
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::cancel))
+            .w(rems(34.))
+            .child(
+                Modal::new("setting-provenance", None)
+                    .header(
+                        ModalHeader::new()
+                            .show_dismiss_button(true)
+                            .headline(self.key.clone())
+                            .description(subject),
+                    )
+                    .section(Section::new().child(if contributions.is_empty() {
+                        Label::new("This setting isn't set in any settings file.")
+                            .color(Color::Muted)
+                            .into_any_element()
+                    } else {
+                        v_flex()
+                            .id("setting-contributions")
+                            .max_h(rems(24.))
+                            .overflow_y_scroll()
+                            .children(contributions.iter().enumerate().rev().map(
+                                |(ix, contribution)| {
+                                    self.render_contribution(
+                                        ix,
+                                        contribution,
+                                        ix + 1 == contributions.len(),
+                                        cx,
+                                    )
+                                },
+                            ))
+                            .into_any_element()
+                    })),
+            )
+    }
+}
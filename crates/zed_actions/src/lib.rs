@@ -288,6 +288,18 @@ pub mod project_panel {
         ]
     );
 }
+
+pub mod hex_editor {
+    use gpui::actions;
+
+    actions!(
+        hex_editor,
+        [
+            /// Opens the active file in the hex editor.
+            Open
+        ]
+    );
+}
 pub mod feedback {
     use gpui::actions;
 
@@ -11,6 +11,11 @@ use log;
 
 static ENV_FILTER: OnceLock<env_config::EnvFilter> = OnceLock::new();
 static SCOPE_MAP: RwLock<ScopeMap> = RwLock::new(ScopeMap::empty());
+/// The scope levels from the user's settings, kept so that overrides can be applied on top of them.
+static SCOPE_LEVEL_SETTINGS: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+/// Scope levels changed at runtime, such as from the log view, which take precedence over the
+/// user's settings until they're cleared.
+static SCOPE_LEVEL_OVERRIDES: RwLock<Vec<(String, log::LevelFilter)>> = RwLock::new(Vec::new());
 
 pub const LEVEL_ENABLED_MAX_DEFAULT: log::LevelFilter = log::LevelFilter::Info;
 /// The maximum log level of verbosity that is enabled by default.
@@ -91,14 +96,77 @@ pub fn is_scope_enabled(
 }
 
 pub fn refresh_from_settings(settings: &HashMap<String, String>) {
+    {
+        let mut settings_global = SCOPE_LEVEL_SETTINGS.write().unwrap_or_else(|err| {
+            SCOPE_LEVEL_SETTINGS.clear_poison();
+            err.into_inner()
+        });
+        *settings_global = Some(settings.clone());
+    }
+    refresh();
+}
+
+/// Sets the level for a scope, or a module path such as `editor::element`, overriding the level
+/// from the user's settings. Passing `None` removes the override.
+pub fn set_scope_level_override(scope: &str, level: Option<log::LevelFilter>) {
+    {
+        let mut overrides = SCOPE_LEVEL_OVERRIDES.write().unwrap_or_else(|err| {
+            SCOPE_LEVEL_OVERRIDES.clear_poison();
+            err.into_inner()
+        });
+        overrides.retain(|(existing, _)| existing != scope);
+        if let Some(level) = level {
+            overrides.push((scope.to_string(), level));
+        }
+    }
+    refresh();
+}
+
+pub fn scope_level_overrides() -> Vec<(String, log::LevelFilter)> {
+    SCOPE_LEVEL_OVERRIDES
+        .read()
+        .unwrap_or_else(|err| {
+            SCOPE_LEVEL_OVERRIDES.clear_poison();
+            err.into_inner()
+        })
+        .clone()
+}
+
+pub fn clear_scope_level_overrides() {
+    {
+        let mut overrides = SCOPE_LEVEL_OVERRIDES.write().unwrap_or_else(|err| {
+            SCOPE_LEVEL_OVERRIDES.clear_poison();
+            err.into_inner()
+        });
+        if overrides.is_empty() {
+            return;
+        }
+        overrides.clear();
+    }
+    refresh();
+}
+
+fn refresh() {
+    let settings = SCOPE_LEVEL_SETTINGS
+        .read()
+        .unwrap_or_else(|err| {
+            SCOPE_LEVEL_SETTINGS.clear_poison();
+            err.into_inner()
+        })
+        .clone()
+        .unwrap_or_default();
+    let settings = settings_with_overrides(settings, &scope_level_overrides());
     let env_config = ENV_FILTER.get();
-    let map_new = ScopeMap::new_from_settings_and_env(settings, env_config, DEFAULT_FILTERS);
+    let map_new = ScopeMap::new_from_settings_and_env(&settings, env_config, DEFAULT_FILTERS);
     let mut level_enabled_max = LEVEL_ENABLED_MAX_STATIC.load(Ordering::Acquire);
     for entry in &map_new.entries {
         if let Some(level) = entry.enabled {
             level_enabled_max = level_enabled_max.max(level as u8);
         }
     }
+    for (_, level) in &map_new.modules {
+        level_enabled_max = level_enabled_max.max(*level as u8);
+    }
     LEVEL_ENABLED_MAX_CONFIG.store(level_enabled_max, Ordering::Release);
 
     {
@@ -111,6 +179,16 @@ pub fn refresh_from_settings(settings: &HashMap<String, String>) {
     log::trace!("Log configuration updated");
 }
 
+fn settings_with_overrides(
+    mut settings: HashMap<String, String>,
+    overrides: &[(String, log::LevelFilter)],
+) -> HashMap<String, String> {
+    for (scope, level) in overrides {
+        settings.insert(scope.clone(), level.as_str().to_string());
+    }
+    settings
+}
+
 fn level_filter_from_str(level_str: &str) -> Option<log::LevelFilter> {
     use log::LevelFilter::*;
     let level = match level_str.to_ascii_lowercase().as_str() {
@@ -840,4 +918,39 @@ mod tests {
             "crate::submodule should be disabled by disabling `crate` filter"
         );
     }
+
+    #[test]
+    fn test_settings_with_overrides() {
+        let settings: HashMap<String, String> = [
+            ("a.b".to_string(), "info".to_string()),
+            ("c".to_string(), "warn".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let settings = settings_with_overrides(
+            settings,
+            &[
+                ("a.b".to_string(), LevelFilter::Trace),
+                ("crate::module".to_string(), LevelFilter::Off),
+            ],
+        );
+        let map = ScopeMap::new_from_settings_and_env(&settings, None, &[]);
+
+        use log::Level;
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("a.b"), None, Level::Trace),
+            EnabledStatus::Enabled,
+            "Overrides take precedence over settings"
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("c"), None, Level::Info),
+            EnabledStatus::Disabled,
+            "Settings without overrides are kept"
+        );
+        assert_eq!(
+            map.is_enabled(&scope_new(&[""]), Some("crate::module"), Level::Error),
+            EnabledStatus::Disabled,
+            "Overrides apply to module paths"
+        );
+    }
 }
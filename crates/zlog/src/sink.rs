@@ -1,4 +1,5 @@
 use std::{
+    collections::VecDeque,
     fs,
     io::{self, Write},
     path::PathBuf,
@@ -19,20 +20,40 @@ const ANSI_GREEN: &str = "\x1b[32m";
 const ANSI_BLUE: &str = "\x1b[34m";
 const ANSI_MAGENTA: &str = "\x1b[35m";
 
-/// Is Some(file) if file output is enabled.
-static ENABLED_SINKS_FILE: Mutex<Option<std::fs::File>> = Mutex::new(None);
-static SINK_FILE_PATH: OnceLock<&'static PathBuf> = OnceLock::new();
-static SINK_FILE_PATH_ROTATE: OnceLock<&'static PathBuf> = OnceLock::new();
+/// The plain text log file.
+static SINK_FILE: FileSink = FileSink::new();
+/// The log file with one JSON object per record, for loading logs into other tools.
+static SINK_JSON_FILE: FileSink = FileSink::new();
 
 // NB: Since this can be accessed in tests, we probably should stick to atomics here.
 /// Whether stdout output is enabled.
 static ENABLED_SINKS_STDOUT: AtomicBool = AtomicBool::new(false);
 /// Whether stderr output is enabled.
 static ENABLED_SINKS_STDERR: AtomicBool = AtomicBool::new(false);
-/// Atomic counter for the size of the log file in bytes.
-static SINK_FILE_SIZE_BYTES: AtomicU64 = AtomicU64::new(0);
 /// Maximum size of the log file before it will be rotated, in bytes.
 const SINK_FILE_SIZE_BYTES_MAX: u64 = 1024 * 1024; // 1 MB
+/// Is Some(buffer) if memory output is enabled.
+static ENABLED_SINKS_MEMORY: Mutex<Option<MemoryBuffer>> = Mutex::new(None);
+/// Maximum number of records kept in memory, after which the oldest records are dropped.
+pub const SINK_MEMORY_RECORDS_MAX: usize = 10_000;
+
+struct MemoryBuffer {
+    records: VecDeque<MemoryRecord>,
+    next_id: u64,
+}
+
+/// A log message kept by the memory sink, such as for displaying logs in the app.
+#[derive(Clone, Debug)]
+pub struct MemoryRecord {
+    /// Increases with each record, so that records can be fetched after a previous fetch.
+    pub id: u64,
+    pub timestamp: chrono::DateTime<chrono::Local>,
+    pub level: log::Level,
+    /// The scope, or the module path for messages not logged with a scoped logger.
+    pub scope: String,
+    pub line: Option<u32>,
+    pub message: String,
+}
 
 pub struct Record<'a> {
     pub scope: ScopeRef<'a>,
@@ -40,6 +61,8 @@ pub struct Record<'a> {
     pub message: &'a std::fmt::Arguments<'a>,
     pub module_path: Option<&'a str>,
     pub line: Option<u32>,
+    /// Structured data attached to the record, such as with `log::info!(id = 1; "...")`.
+    pub key_values: Option<&'a dyn log::kv::Source>,
 }
 
 pub fn init_output_stdout() {
@@ -52,38 +75,48 @@ pub fn init_output_stderr() {
     ENABLED_SINKS_STDERR.store(true, Ordering::Release);
 }
 
+pub fn init_output_memory() {
+    let mut memory = ENABLED_SINKS_MEMORY.lock().unwrap_or_else(|handle| {
+        ENABLED_SINKS_MEMORY.clear_poison();
+        handle.into_inner()
+    });
+    memory.get_or_insert_with(|| MemoryBuffer {
+        records: VecDeque::new(),
+        next_id: 0,
+    });
+}
+
+/// Returns the records in the memory sink that were logged after the record with the given id,
+/// or all of them if no id is given.
+pub fn memory_records_after(id: Option<u64>) -> Vec<MemoryRecord> {
+    let memory = ENABLED_SINKS_MEMORY.lock().unwrap_or_else(|handle| {
+        ENABLED_SINKS_MEMORY.clear_poison();
+        handle.into_inner()
+    });
+    let Some(memory) = memory.as_ref() else {
+        return Vec::new();
+    };
+    let start = match id {
+        Some(id) => memory.records.partition_point(|record| record.id <= id),
+        None => 0,
+    };
+    memory.records.range(start..).cloned().collect()
+}
+
 pub fn init_output_file(
     path: &'static PathBuf,
     path_rotate: Option<&'static PathBuf>,
 ) -> io::Result<()> {
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)?;
-
-    SINK_FILE_PATH
-        .set(path)
-        .expect("Init file output should only be called once");
-    if let Some(path_rotate) = path_rotate {
-        SINK_FILE_PATH_ROTATE
-            .set(path_rotate)
-            .expect("Init file output should only be called once");
-    }
-
-    let mut enabled_sinks_file = ENABLED_SINKS_FILE
-        .try_lock()
-        .expect("Log file lock is available during init");
-
-    let size_bytes = file.metadata().map_or(0, |metadata| metadata.len());
-    if size_bytes >= SINK_FILE_SIZE_BYTES_MAX {
-        rotate_log_file(&mut file, Some(path), path_rotate, &SINK_FILE_SIZE_BYTES);
-    } else {
-        SINK_FILE_SIZE_BYTES.store(size_bytes, Ordering::Release);
-    }
-
-    *enabled_sinks_file = Some(file);
+    SINK_FILE.init(path, path_rotate)
+}
 
-    Ok(())
+/// Writes each record as a JSON object on its own line, with the record's timestamp, level,
+/// scope, module path, line, message and key-values.
+pub fn init_output_json_file(
+    path: &'static PathBuf,
+    path_rotate: Option<&'static PathBuf>,
+) -> io::Result<()> {
+    SINK_JSON_FILE.init(path, path_rotate)
 }
 
 const LEVEL_OUTPUT_STRINGS: [&str; 6] = [
@@ -144,11 +177,174 @@ pub fn submit(mut record: Record) {
             record.message
         );
     }
-    let mut file = ENABLED_SINKS_FILE.lock().unwrap_or_else(|handle| {
-        ENABLED_SINKS_FILE.clear_poison();
-        handle.into_inner()
+    {
+        let mut memory = ENABLED_SINKS_MEMORY.lock().unwrap_or_else(|handle| {
+            ENABLED_SINKS_MEMORY.clear_poison();
+            handle.into_inner()
+        });
+        if let Some(memory) = memory.as_mut() {
+            push_memory_record(
+                memory,
+                record.level,
+                ScopeFmt {
+                    scope: record.scope,
+                    module_path: record.module_path,
+                }
+                .to_string(),
+                record.line,
+                record.message.to_string(),
+            );
+        }
+    }
+    SINK_FILE.write(|writer| {
+        writeln!(
+            writer,
+            "{} {} {} {}",
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
+            LEVEL_OUTPUT_STRINGS[record.level as usize],
+            SourceFmt {
+                scope: record.scope,
+                module_path: record.module_path,
+                line: record.line,
+                ansi: false,
+            },
+            record.message
+        )
     });
-    if let Some(file) = file.as_mut() {
+    SINK_JSON_FILE.write(|writer| {
+        serde_json::to_writer(&mut *writer, &json_record(&record, chrono::Local::now()))?;
+        writeln!(writer)
+    });
+}
+
+fn json_record(record: &Record, timestamp: chrono::DateTime<chrono::Local>) -> serde_json::Value {
+    struct KeyValues(serde_json::Map<String, serde_json::Value>);
+
+    impl<'kvs> log::kv::VisitSource<'kvs> for KeyValues {
+        fn visit_pair(
+            &mut self,
+            key: log::kv::Key<'kvs>,
+            value: log::kv::Value<'kvs>,
+        ) -> Result<(), log::kv::Error> {
+            self.0.insert(
+                key.to_string(),
+                serde_json::Value::String(value.to_string()),
+            );
+            Ok(())
+        }
+    }
+
+    let mut key_values = KeyValues(serde_json::Map::new());
+    if let Some(source) = record.key_values {
+        _ = source.visit(&mut key_values);
+    }
+    serde_json::json!({
+        "timestamp": timestamp.to_rfc3339_opts(chrono::SecondsFormat::Micros, false),
+        "level": record.level.as_str(),
+        "scope": ScopeFmt {
+            scope: record.scope,
+            module_path: record.module_path,
+        }
+        .to_string(),
+        "module": record.module_path,
+        "line": record.line,
+        "message": record.message.to_string(),
+        "key_values": key_values.0,
+    })
+}
+
+fn push_memory_record(
+    memory: &mut MemoryBuffer,
+    level: log::Level,
+    scope: String,
+    line: Option<u32>,
+    message: String,
+) {
+    if memory.records.len() >= SINK_MEMORY_RECORDS_MAX {
+        memory.records.pop_front();
+    }
+    memory.records.push_back(MemoryRecord {
+        id: memory.next_id,
+        timestamp: chrono::Local::now(),
+        level,
+        scope,
+        line,
+        message,
+    });
+    memory.next_id += 1;
+}
+
+pub fn flush() {
+    if ENABLED_SINKS_STDOUT.load(Ordering::Acquire) {
+        _ = std::io::stdout().lock().flush();
+    }
+    SINK_FILE.flush();
+    SINK_JSON_FILE.flush();
+}
+
+struct FileSink {
+    /// Is Some(file) if this output is enabled.
+    file: Mutex<Option<fs::File>>,
+    path: OnceLock<&'static PathBuf>,
+    path_rotate: OnceLock<&'static PathBuf>,
+    /// Atomic counter for the size of the log file in bytes.
+    size_bytes: AtomicU64,
+}
+
+impl FileSink {
+    const fn new() -> Self {
+        Self {
+            file: Mutex::new(None),
+            path: OnceLock::new(),
+            path_rotate: OnceLock::new(),
+            size_bytes: AtomicU64::new(0),
+        }
+    }
+
+    fn init(
+        &self,
+        path: &'static PathBuf,
+        path_rotate: Option<&'static PathBuf>,
+    ) -> io::Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        self.path
+            .set(path)
+            .expect("Init file output should only be called once");
+        if let Some(path_rotate) = path_rotate {
+            self.path_rotate
+                .set(path_rotate)
+                .expect("Init file output should only be called once");
+        }
+
+        let mut enabled_file = self
+            .file
+            .try_lock()
+            .expect("Log file lock is available during init");
+
+        let size_bytes = file.metadata().map_or(0, |metadata| metadata.len());
+        if size_bytes >= SINK_FILE_SIZE_BYTES_MAX {
+            rotate_log_file(&mut file, Some(path), path_rotate, &self.size_bytes);
+        } else {
+            self.size_bytes.store(size_bytes, Ordering::Release);
+        }
+
+        *enabled_file = Some(file);
+
+        Ok(())
+    }
+
+    fn write(&self, write: impl FnOnce(&mut dyn io::Write) -> io::Result<()>) {
+        let mut file = self.file.lock().unwrap_or_else(|handle| {
+            self.file.clear_poison();
+            handle.into_inner()
+        });
+        let Some(file) = file.as_mut() else {
+            return;
+        };
         struct SizedWriter<'a> {
             file: &'a mut std::fs::File,
             written: u64,
@@ -166,44 +362,29 @@ pub fn submit(mut record: Record) {
         }
         let file_size_bytes = {
             let mut writer = SizedWriter { file, written: 0 };
-            _ = writeln!(
-                &mut writer,
-                "{} {} {} {}",
-                chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
-                LEVEL_OUTPUT_STRINGS[record.level as usize],
-                SourceFmt {
-                    scope: record.scope,
-                    module_path: record.module_path,
-                    line: record.line,
-                    ansi: false,
-                },
-                record.message
-            );
-            SINK_FILE_SIZE_BYTES.fetch_add(writer.written, Ordering::AcqRel) + writer.written
+            _ = write(&mut writer);
+            self.size_bytes.fetch_add(writer.written, Ordering::AcqRel) + writer.written
         };
         if file_size_bytes > SINK_FILE_SIZE_BYTES_MAX {
             rotate_log_file(
                 file,
-                SINK_FILE_PATH.get(),
-                SINK_FILE_PATH_ROTATE.get(),
-                &SINK_FILE_SIZE_BYTES,
+                self.path.get(),
+                self.path_rotate.get(),
+                &self.size_bytes,
             );
         }
     }
-}
 
-pub fn flush() {
-    if ENABLED_SINKS_STDOUT.load(Ordering::Acquire) {
-        _ = std::io::stdout().lock().flush();
-    }
-    let mut file = ENABLED_SINKS_FILE.lock().unwrap_or_else(|handle| {
-        ENABLED_SINKS_FILE.clear_poison();
-        handle.into_inner()
-    });
-    if let Some(file) = file.as_mut()
-        && let Err(err) = file.flush()
-    {
-        eprintln!("Failed to flush log file: {}", err);
+    fn flush(&self) {
+        let mut file = self.file.lock().unwrap_or_else(|handle| {
+            self.file.clear_poison();
+            handle.into_inner()
+        });
+        if let Some(file) = file.as_mut()
+            && let Err(err) = file.flush()
+        {
+            eprintln!("Failed to flush log file: {}", err);
+        }
     }
 }
 
@@ -221,6 +402,34 @@ impl std::fmt::Display for SourceFmt<'_> {
         if self.ansi {
             f.write_str(ANSI_BOLD)?;
         }
+        write!(
+            f,
+            "{}",
+            ScopeFmt {
+                scope: self.scope,
+                module_path: self.module_path,
+            }
+        )?;
+        if let Some(line) = self.line {
+            f.write_char(':')?;
+            line.fmt(f)?;
+        }
+        if self.ansi {
+            f.write_str(ANSI_RESET)?;
+        }
+        f.write_char(']')?;
+        Ok(())
+    }
+}
+
+struct ScopeFmt<'a> {
+    scope: ScopeRef<'a>,
+    module_path: Option<&'a str>,
+}
+
+impl std::fmt::Display for ScopeFmt<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::fmt::Write;
         // NOTE: if no longer prefixing scopes with their crate name, check if scope[0] is empty
         if (self.scope[1].is_empty() && self.module_path.is_some()) || self.scope[0].is_empty() {
             f.write_str(self.module_path.unwrap_or("?"))?;
@@ -234,14 +443,6 @@ impl std::fmt::Display for SourceFmt<'_> {
                 f.write_str(subscope)?;
             }
         }
-        if let Some(line) = self.line {
-            f.write_char(':')?;
-            line.fmt(f)?;
-        }
-        if self.ansi {
-            f.write_str(ANSI_RESET)?;
-        }
-        f.write_char(']')?;
         Ok(())
     }
 }
@@ -312,6 +513,71 @@ mod tests {
         assert_eq!(size.load(Ordering::Acquire), 0);
     }
 
+    #[test]
+    fn test_memory_records() {
+        let mut memory = MemoryBuffer {
+            records: VecDeque::new(),
+            next_id: 0,
+        };
+        for index in 0..SINK_MEMORY_RECORDS_MAX + 2 {
+            push_memory_record(
+                &mut memory,
+                log::Level::Info,
+                "scope".into(),
+                None,
+                index.to_string(),
+            );
+        }
+        assert_eq!(memory.records.len(), SINK_MEMORY_RECORDS_MAX);
+        assert_eq!(memory.records.front().unwrap().id, 2);
+        assert_eq!(memory.records.front().unwrap().message, "2");
+        assert_eq!(
+            memory.records.back().unwrap().id,
+            SINK_MEMORY_RECORDS_MAX as u64 + 1
+        );
+    }
+
+    #[test]
+    fn test_scope_fmt() {
+        let scope = ScopeFmt {
+            scope: crate::private::scope_ref_new(&["crate", "sub"]),
+            module_path: Some("crate::module"),
+        };
+        assert_eq!(scope.to_string(), "crate.sub");
+        let scope = ScopeFmt {
+            scope: crate::private::scope_ref_new(&["crate::module"]),
+            module_path: Some("crate::module"),
+        };
+        assert_eq!(scope.to_string(), "crate::module");
+    }
+
+    #[test]
+    fn test_json_record() {
+        let key_values = [("buffer_id", 7)];
+        let timestamp = chrono::DateTime::parse_from_rfc3339("2025-01-02T03:04:05.000006+00:00")
+            .unwrap()
+            .with_timezone(&chrono::Local);
+        let record = Record {
+            scope: crate::private::scope_ref_new(&["project", "lsp"]),
+            level: log::Level::Warn,
+            message: &format_args!("server {} exited", "rust-analyzer"),
+            module_path: Some("project::lsp_store"),
+            line: Some(12),
+            key_values: Some(&key_values),
+        };
+        let json = json_record(&record, timestamp);
+        assert_eq!(
+            json["timestamp"],
+            timestamp.to_rfc3339_opts(chrono::SecondsFormat::Micros, false)
+        );
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["scope"], "project.lsp");
+        assert_eq!(json["module"], "project::lsp_store");
+        assert_eq!(json["line"], 12);
+        assert_eq!(json["message"], "server rust-analyzer exited");
+        assert_eq!(json["key_values"], serde_json::json!({"buffer_id": "7"}));
+    }
+
     /// Regression test, ensuring that if log level values change we are made aware
     #[test]
     fn test_log_level_names() {
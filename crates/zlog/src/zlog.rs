@@ -5,7 +5,10 @@ mod env_config;
 pub mod filter;
 pub mod sink;
 
-pub use sink::{flush, init_output_file, init_output_stderr, init_output_stdout};
+pub use sink::{
+    flush, init_output_file, init_output_json_file, init_output_memory, init_output_stderr,
+    init_output_stdout,
+};
 
 pub const SCOPE_DEPTH_MAX: usize = 4;
 
@@ -94,6 +97,7 @@ impl log::Log for Zlog {
             // PERF(batching): store non-static paths in a cache + leak them and pass static str here
             module_path,
             line: record.line(),
+            key_values: Some(record.key_values()),
         });
     }
 
@@ -115,6 +119,7 @@ macro_rules! log {
                 message: &format_args!($($arg)+),
                 module_path: Some(module_path!()),
                 line: Some(line!()),
+                key_values: None,
             });
         }
     }
@@ -311,6 +316,7 @@ impl log::Log for Logger {
             message: record.args(),
             module_path: record.module_path(),
             line: record.line(),
+            key_values: Some(record.key_values()),
         });
     }
 
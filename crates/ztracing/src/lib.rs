@@ -1,8 +1,13 @@
+pub mod otlp;
+
+use std::path::Path;
+
 pub use tracing::{Level, field};
 
 #[cfg(ztracing)]
 pub use tracing::{
-    Span, debug_span, error_span, event, info_span, instrument, span, trace_span, warn_span,
+    Instrument, Span, debug_span, error_span, event, info_span, instrument, span, trace_span,
+    warn_span,
 };
 #[cfg(not(ztracing))]
 pub use ztracing_macro::instrument;
@@ -44,15 +49,50 @@ impl Span {
     pub fn record<T, S>(&self, _t: T, _s: S) {}
 }
 
+#[cfg(not(ztracing))]
+pub trait Instrument: Sized {
+    fn instrument(self, _span: Span) -> Self {
+        self
+    }
+}
+
+#[cfg(not(ztracing))]
+impl<T> Instrument for T {}
+
+/// Sets up the subscribers for spans: the tracy profiler when built with the `tracy` feature, and
+/// an OpenTelemetry (OTLP) JSON file exporter when `otlp_file` is given.
 #[cfg(ztracing)]
-pub fn init() {
-    zlog::info!("Starting tracy subscriber, you can now connect the profiler");
+pub fn init(otlp_file: Option<&Path>) {
     use tracing_subscriber::prelude::*;
+
+    let otlp_layer = otlp_file.and_then(|path| match otlp::OtlpFileLayer::new(path) {
+        Ok(layer) => {
+            zlog::info!("Exporting spans to {}", path.display());
+            Some(layer)
+        }
+        Err(err) => {
+            zlog::error!("Failed to open {} to export spans: {err}", path.display());
+            None
+        }
+    });
+    #[cfg(feature = "tracy")]
+    let tracy_layer = {
+        zlog::info!("Starting tracy subscriber, you can now connect the profiler");
+        tracing_tracy::TracyLayer::default()
+    };
+    #[cfg(not(feature = "tracy"))]
+    let tracy_layer = tracing_subscriber::layer::Identity::new();
     tracing::subscriber::set_global_default(
-        tracing_subscriber::registry().with(tracing_tracy::TracyLayer::default()),
+        tracing_subscriber::registry()
+            .with(tracy_layer)
+            .with(otlp_layer),
     )
-    .expect("setup tracy layer");
+    .expect("setup tracing layers");
 }
 
 #[cfg(not(ztracing))]
-pub fn init() {}
+pub fn init(otlp_file: Option<&Path>) {
+    if otlp_file.is_some() {
+        zlog::warn!("Not exporting spans, as Zed was built without ZTRACING=1");
+    }
+}
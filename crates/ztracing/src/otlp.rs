@@ -0,0 +1,338 @@
+//! Exports spans to a file in the OpenTelemetry (OTLP) JSON file format, with one
+//! `ExportTraceServiceRequest` per line, so that traces can be loaded into standard tools
+//! without running a collector.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Write as _},
+    path::Path,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{Value, json};
+use tracing::{
+    Subscriber,
+    field::{Field, Visit},
+    span,
+};
+use tracing_subscriber::{Layer, layer::Context, registry::LookupSpan};
+
+/// The number of finished spans that are buffered before they're written, unless their trace's
+/// root span finishes first.
+const PENDING_SPANS_MAX: usize = 256;
+
+/// How often buffered spans are written, so that spans within long-running traces aren't held
+/// back until their root span finishes.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `SPAN_KIND_INTERNAL`, as spans are recorded for operations within Zed.
+const SPAN_KIND_INTERNAL: u32 = 1;
+
+/// Buffered spans are written on an interval and when the layer is dropped.
+pub struct OtlpFileLayer {
+    state: Arc<Mutex<ExportState>>,
+    /// Distinguishes trace ids from those of other runs that exported to the same file.
+    trace_id_prefix: u64,
+    next_id: AtomicU64,
+}
+
+struct ExportState {
+    file: File,
+    pending_spans: Vec<Value>,
+}
+
+struct SpanData {
+    trace_id: u128,
+    span_id: u64,
+    parent_span_id: Option<u64>,
+    start_time: SystemTime,
+    attributes: Vec<Value>,
+}
+
+impl OtlpFileLayer {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let file = File::options().create(true).append(true).open(path)?;
+        let start_time = unix_nanos(SystemTime::now()) as u64;
+        let state = Arc::new(Mutex::new(ExportState {
+            file,
+            pending_spans: Vec::new(),
+        }));
+
+        let weak_state = Arc::downgrade(&state);
+        std::thread::Builder::new()
+            .name("otlp-export".into())
+            .spawn(move || {
+                loop {
+                    std::thread::sleep(FLUSH_INTERVAL);
+                    let Some(state) = weak_state.upgrade() else {
+                        break;
+                    };
+                    lock(&state).flush();
+                }
+            })?;
+
+        Ok(Self {
+            state,
+            trace_id_prefix: start_time ^ (std::process::id() as u64).rotate_left(32),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn export(&self, span: Value, is_root: bool) {
+        let mut state = lock(&self.state);
+        state.pending_spans.push(span);
+        if is_root || state.pending_spans.len() >= PENDING_SPANS_MAX {
+            state.flush();
+        }
+    }
+}
+
+impl Drop for OtlpFileLayer {
+    fn drop(&mut self) {
+        lock(&self.state).flush();
+    }
+}
+
+impl ExportState {
+    fn flush(&mut self) {
+        if self.pending_spans.is_empty() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.pending_spans);
+        let mut line = export_request(spans).to_string();
+        line.push('\n');
+        if let Err(err) = self.file.write_all(line.as_bytes()) {
+            eprintln!("Failed to export spans: {err}");
+        }
+    }
+}
+
+fn lock(state: &Mutex<ExportState>) -> MutexGuard<'_, ExportState> {
+    state.lock().unwrap_or_else(|err| {
+        state.clear_poison();
+        err.into_inner()
+    })
+}
+
+impl<S> Layer<S> for OtlpFileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.parent().and_then(|parent| {
+            let extensions = parent.extensions();
+            let data = extensions.get::<SpanData>()?;
+            Some((data.trace_id, data.span_id))
+        });
+        let (trace_id, parent_span_id) = match parent {
+            Some((trace_id, parent_span_id)) => (trace_id, Some(parent_span_id)),
+            None => (
+                ((self.trace_id_prefix as u128) << 64) | self.next_id() as u128,
+                None,
+            ),
+        };
+
+        let metadata = attrs.metadata();
+        let mut attributes = vec![attribute(
+            "code.namespace",
+            json!({ "stringValue": metadata.target() }),
+        )];
+        if let Some(file) = metadata.file() {
+            attributes.push(attribute("code.filepath", json!({ "stringValue": file })));
+        }
+        if let Some(line) = metadata.line() {
+            attributes.push(attribute(
+                "code.lineno",
+                json!({ "intValue": line.to_string() }),
+            ));
+        }
+        attrs.record(&mut AttributeVisitor(&mut attributes));
+
+        span.extensions_mut().insert(SpanData {
+            trace_id,
+            span_id: self.next_id(),
+            parent_span_id,
+            start_time: SystemTime::now(),
+            attributes,
+        });
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(data) = span.extensions_mut().get_mut::<SpanData>()
+        {
+            values.record(&mut AttributeVisitor(&mut data.attributes));
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(data) = span.extensions_mut().remove::<SpanData>() else {
+            return;
+        };
+        let mut span_json = json!({
+            "traceId": format!("{:032x}", data.trace_id),
+            "spanId": format!("{:016x}", data.span_id),
+            "name": span.name(),
+            "kind": SPAN_KIND_INTERNAL,
+            "startTimeUnixNano": unix_nanos(data.start_time).to_string(),
+            "endTimeUnixNano": unix_nanos(SystemTime::now()).to_string(),
+            "attributes": data.attributes,
+        });
+        if let Some(parent_span_id) = data.parent_span_id {
+            span_json["parentSpanId"] = format!("{parent_span_id:016x}").into();
+        }
+        self.export(span_json, data.parent_span_id.is_none());
+    }
+}
+
+fn export_request(spans: Vec<Value>) -> Value {
+    let resource_attributes = [
+        attribute("service.name", json!({ "stringValue": "zed" })),
+        attribute(
+            "process.pid",
+            json!({ "intValue": std::process::id().to_string() }),
+        ),
+    ];
+    json!({
+        "resourceSpans": [{
+            "resource": { "attributes": resource_attributes },
+            "scopeSpans": [{
+                "scope": { "name": "ztracing" },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+fn attribute(key: &str, value: Value) -> Value {
+    json!({ "key": key, "value": value })
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+}
+
+/// Converts span fields to OTLP attributes. 64-bit integers are strings, as in the JSON
+/// encoding of OTLP's protobuf messages.
+struct AttributeVisitor<'a>(&'a mut Vec<Value>);
+
+impl AttributeVisitor<'_> {
+    fn push(&mut self, field: &Field, value: Value) {
+        self.0.push(attribute(field.name(), value));
+    }
+}
+
+impl Visit for AttributeVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, json!({ "stringValue": value }));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, json!({ "boolValue": value }));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, json!({ "intValue": value.to_string() }));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, json!({ "intValue": value.to_string() }));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field, json!({ "doubleValue": value }));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, json!({ "stringValue": format!("{value:?}") }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_export_spans() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traces.jsonl");
+        let layer = OtlpFileLayer::new(&path).unwrap();
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            let root = tracing::info_span!("project_search", query = "needle", limit = 10u64);
+            let _root = root.enter();
+            tracing::info_span!("scan_worktree").in_scope(|| {});
+        });
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines.len(),
+            1,
+            "spans are written when the root span closes"
+        );
+        let request: Value = serde_json::from_str(lines[0]).unwrap();
+        let spans = request["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .unwrap();
+        assert_eq!(spans.len(), 2);
+        let (child, root) = (&spans[0], &spans[1]);
+        assert_eq!(child["name"], "scan_worktree");
+        assert_eq!(root["name"], "project_search");
+        assert_eq!(child["traceId"], root["traceId"]);
+        assert_eq!(child["parentSpanId"], root["spanId"]);
+        assert!(root.get("parentSpanId").is_none());
+        assert_eq!(root["traceId"].as_str().unwrap().len(), 32);
+
+        let attribute_value = |span: &Value, key: &str| {
+            span["attributes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|attribute| attribute["key"] == key)
+                .map(|attribute| attribute["value"].clone())
+        };
+        assert_eq!(
+            attribute_value(root, "query"),
+            Some(json!({ "stringValue": "needle" }))
+        );
+        assert_eq!(
+            attribute_value(root, "limit"),
+            Some(json!({ "intValue": "10" }))
+        );
+    }
+
+    #[test]
+    fn test_flush_spans_when_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traces.jsonl");
+        let layer = OtlpFileLayer::new(&path).unwrap();
+        // A span whose trace's root span hasn't finished is buffered, until the layer is dropped.
+        layer.export(json!({ "name": "open_project" }), false);
+        drop(layer);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+        let request: Value = serde_json::from_str(lines[0]).unwrap();
+        let spans = &request["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        assert_eq!(spans, &json!([{ "name": "open_project" }]));
+    }
+}